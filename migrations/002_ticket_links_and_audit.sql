@@ -0,0 +1,27 @@
+-- Migration 002: Linked Child Tickets and Audit Entries
+-- Adds parent/child ticket links and a field-level audit trail
+
+-- ============================================================================
+-- Parent/Child Ticket Links
+-- ============================================================================
+ALTER TABLE support_tickets
+    ADD COLUMN IF NOT EXISTS parent_ticket_id UUID REFERENCES support_tickets(id);
+
+CREATE INDEX IF NOT EXISTS idx_support_tickets_parent_ticket_id ON support_tickets(parent_ticket_id) WHERE deleted_at IS NULL AND parent_ticket_id IS NOT NULL;
+
+-- ============================================================================
+-- Ticket Audit Entries Table
+-- ============================================================================
+CREATE TABLE IF NOT EXISTS ticket_audit_entries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    ticket_id UUID NOT NULL REFERENCES support_tickets(id) ON DELETE CASCADE,
+    actor_id UUID,  -- References auth.users(id), NULL for system changes
+    field VARCHAR(100) NOT NULL,
+    old_value TEXT,
+    new_value TEXT,
+    source_ticket_id UUID,  -- Set when the change was propagated from another ticket
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_ticket_audit_entries_ticket_id ON ticket_audit_entries(ticket_id);
+CREATE INDEX IF NOT EXISTS idx_ticket_audit_entries_created_at ON ticket_audit_entries(created_at);
@@ -0,0 +1,15 @@
+-- Migration 003: Application-Supplied Timestamps
+-- Lets the application clock own updated_at so tests can freeze time
+
+-- ============================================================================
+-- Trigger: Only default updated_at when the statement did not set it
+-- ============================================================================
+CREATE OR REPLACE FUNCTION update_support_tickets_updated_at()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.updated_at IS NOT DISTINCT FROM OLD.updated_at THEN
+        NEW.updated_at = NOW();
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,11 @@
+-- Migration 004: SLA Pause While Waiting on Customer
+-- Tracks time tickets spend in WAITING_ON_CUSTOMER so resolution clocks can exclude it
+
+ALTER TABLE support_tickets
+    ADD COLUMN IF NOT EXISTS waiting_since TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS customer_wait_seconds BIGINT NOT NULL DEFAULT 0;
+
+-- Tickets already waiting start their wait clock now
+UPDATE support_tickets
+SET waiting_since = updated_at
+WHERE status = 'WAITING_ON_CUSTOMER' AND waiting_since IS NULL;
@@ -0,0 +1,8 @@
+-- Migration 005: SLA Grace Windows per Customer Plan
+-- Records the customer's plan at intake so breach evaluation can apply its grace window
+
+ALTER TABLE support_tickets
+    ADD COLUMN IF NOT EXISTS customer_plan VARCHAR(50);
+
+CREATE INDEX IF NOT EXISTS idx_support_tickets_open_sla ON support_tickets(created_at)
+    WHERE deleted_at IS NULL AND sla_breach = FALSE AND status NOT IN ('RESOLVED', 'CLOSED');
@@ -0,0 +1,17 @@
+-- Migration 006: Support Agent Profiles
+-- Skills, timezone and availability used for assignment suggestions
+
+CREATE TABLE IF NOT EXISTS support_agents (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    product VARCHAR(50) NOT NULL,
+    user_id UUID NOT NULL,  -- References auth.users(id), not enforced by FK
+    display_name VARCHAR(200) NOT NULL,
+    skills TEXT[] NOT NULL DEFAULT '{}',
+    utc_offset_minutes INTEGER NOT NULL DEFAULT 0,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (product, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_support_agents_product ON support_agents(product) WHERE active = TRUE;
@@ -0,0 +1,17 @@
+-- Migration 007: Internal Message Reactions
+-- Lightweight per-agent acknowledgements on internal notes
+
+CREATE TYPE message_reaction AS ENUM (
+    'ACK',
+    'LOOKING'
+);
+
+CREATE TABLE IF NOT EXISTS ticket_message_reactions (
+    message_id UUID NOT NULL REFERENCES ticket_messages(id) ON DELETE CASCADE,
+    agent_id UUID NOT NULL,  -- References auth.users(id), not enforced by FK
+    reaction message_reaction NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (message_id, agent_id, reaction)
+);
+
+CREATE INDEX IF NOT EXISTS idx_ticket_message_reactions_agent_id ON ticket_message_reactions(agent_id);
@@ -0,0 +1,12 @@
+-- Migration 008: Customer Portal View Tracking
+-- Records when customers open their tickets (or a specific reply) in the portal
+
+CREATE TABLE IF NOT EXISTS ticket_views (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    ticket_id UUID NOT NULL REFERENCES support_tickets(id) ON DELETE CASCADE,
+    customer_id UUID NOT NULL,
+    message_id UUID REFERENCES ticket_messages(id) ON DELETE SET NULL,
+    viewed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_ticket_views_ticket_id_viewed_at ON ticket_views(ticket_id, viewed_at);
@@ -0,0 +1,36 @@
+-- Migration 009: Human-Readable Ticket References
+-- Per-product ticket numbers (e.g. NOVASKYN-000042) backed by collision-safe counters
+
+CREATE TABLE IF NOT EXISTS ticket_number_sequences (
+    product VARCHAR(50) NOT NULL,
+    period INTEGER NOT NULL,  -- Year for yearly-reset formats, 0 for continuous numbering
+    last_number BIGINT NOT NULL,
+    PRIMARY KEY (product, period)
+);
+
+ALTER TABLE support_tickets
+    ADD COLUMN IF NOT EXISTS ticket_number BIGINT,
+    ADD COLUMN IF NOT EXISTS ticket_ref VARCHAR(100);
+
+-- Backfill existing tickets with the default continuous format
+WITH numbered AS (
+    SELECT id, product, ROW_NUMBER() OVER (PARTITION BY product ORDER BY created_at, id) as n
+    FROM support_tickets
+    WHERE ticket_number IS NULL
+)
+UPDATE support_tickets st
+SET ticket_number = numbered.n,
+    ticket_ref = UPPER(numbered.product) || '-' ||
+        CASE WHEN LENGTH(numbered.n::TEXT) >= 6 THEN numbered.n::TEXT ELSE LPAD(numbered.n::TEXT, 6, '0') END
+FROM numbered
+WHERE st.id = numbered.id;
+
+INSERT INTO ticket_number_sequences (product, period, last_number)
+SELECT product, 0, MAX(ticket_number) FROM support_tickets GROUP BY product
+ON CONFLICT (product, period) DO NOTHING;
+
+ALTER TABLE support_tickets
+    ALTER COLUMN ticket_number SET NOT NULL,
+    ALTER COLUMN ticket_ref SET NOT NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_support_tickets_product_ticket_ref ON support_tickets(product, ticket_ref);
@@ -0,0 +1,32 @@
+-- Migration 010: Content Moderation
+-- Flag abusive inbound messages and track abuse incidents per customer
+
+-- ============================================================================
+-- MODERATION STATE
+-- ============================================================================
+
+CREATE TYPE moderation_action AS ENUM (
+    'ALLOW',
+    'FLAG',
+    'ESCALATE'
+);
+
+ALTER TABLE ticket_messages
+    ADD COLUMN IF NOT EXISTS flagged BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- ============================================================================
+-- ABUSE INCIDENTS
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS abuse_incidents (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    product VARCHAR(50) NOT NULL,
+    customer_id UUID NOT NULL,  -- References auth.users(id), not enforced by FK
+    ticket_id UUID NOT NULL REFERENCES support_tickets(id) ON DELETE CASCADE,
+    message_id UUID NOT NULL REFERENCES ticket_messages(id) ON DELETE CASCADE,
+    action moderation_action NOT NULL,
+    matched_terms TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_abuse_incidents_customer ON abuse_incidents(product, customer_id, created_at DESC);
@@ -0,0 +1,14 @@
+-- Migration 011: Ticket Intake Channels
+-- Record the channel each ticket arrived through for per-channel metrics
+
+CREATE TYPE ticket_channel AS ENUM (
+    'EMAIL',
+    'CHAT',
+    'WEB',
+    'API'
+);
+
+ALTER TABLE support_tickets
+    ADD COLUMN IF NOT EXISTS channel ticket_channel NOT NULL DEFAULT 'WEB';
+
+CREATE INDEX IF NOT EXISTS idx_support_tickets_channel ON support_tickets(product, channel, created_at DESC);
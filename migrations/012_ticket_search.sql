@@ -0,0 +1,47 @@
+-- Migration 012: Ticket Full-Text Search
+-- Search vector over subject and description; existing rows are filled by
+-- `SupportRepository::rebuild_search_vectors` (pleme-support-cli maintenance rebuild-search)
+
+-- ============================================================================
+-- SEARCH VECTOR
+-- ============================================================================
+
+ALTER TABLE support_tickets
+    ADD COLUMN IF NOT EXISTS search_vector TSVECTOR;
+
+CREATE INDEX IF NOT EXISTS idx_support_tickets_search_vector ON support_tickets USING GIN (search_vector);
+
+-- 'simple' configuration: products span several languages, so no stemming
+CREATE OR REPLACE FUNCTION support_ticket_search_vector(subject TEXT, description TEXT)
+RETURNS TSVECTOR AS $$
+    SELECT setweight(to_tsvector('simple', COALESCE(subject, '')), 'A')
+        || setweight(to_tsvector('simple', COALESCE(description, '')), 'B');
+$$ LANGUAGE sql IMMUTABLE;
+
+CREATE OR REPLACE FUNCTION set_support_ticket_search_vector()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.search_vector = support_ticket_search_vector(NEW.subject, NEW.description);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_set_support_ticket_search_vector
+    BEFORE INSERT OR UPDATE OF subject, description ON support_tickets
+    FOR EACH ROW
+    EXECUTE FUNCTION set_support_ticket_search_vector();
+
+-- ============================================================================
+-- Trigger: Search vector rebuilds are not ticket updates
+-- ============================================================================
+CREATE OR REPLACE FUNCTION update_support_tickets_updated_at()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF to_jsonb(NEW) - 'search_vector' - 'updated_at' = to_jsonb(OLD) - 'search_vector' - 'updated_at' THEN
+        NEW.updated_at = OLD.updated_at;
+    ELSIF NEW.updated_at IS NOT DISTINCT FROM OLD.updated_at THEN
+        NEW.updated_at = NOW();
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,39 @@
+-- Migration 013: Saved Ticket Views and Agent Read State
+-- Named ticket filters for agent sidebars, with per-agent unread tracking for badges
+
+-- ============================================================================
+-- SAVED VIEWS
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS saved_views (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    product VARCHAR(50) NOT NULL,
+    owner_id UUID NOT NULL,  -- References auth.users(id), not enforced by FK
+    name VARCHAR(100) NOT NULL,
+
+    -- Filter criteria (NULL = any), mirroring TicketFilter
+    status ticket_status,
+    priority ticket_priority,
+    assigned_to UUID,
+    customer_id UUID,
+    category VARCHAR(100),
+    search_query TEXT,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_saved_views_owner ON saved_views(product, owner_id);
+
+-- ============================================================================
+-- AGENT READ STATE
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS ticket_reads (
+    ticket_id UUID NOT NULL REFERENCES support_tickets(id) ON DELETE CASCADE,
+    agent_id UUID NOT NULL,  -- References auth.users(id), not enforced by FK
+    last_read_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (ticket_id, agent_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_ticket_messages_ticket_id_created_at ON ticket_messages(ticket_id, created_at DESC);
@@ -0,0 +1,21 @@
+-- Migration 014: Intake Submission Log
+-- Every screened customer-facing submission, accepted or rejected, with its telemetry signals
+
+CREATE TABLE IF NOT EXISTS intake_submissions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    product VARCHAR(50) NOT NULL,
+    ticket_id UUID REFERENCES support_tickets(id) ON DELETE SET NULL,  -- NULL when rejected
+    accepted BOOLEAN NOT NULL,
+    signals TEXT[] NOT NULL DEFAULT '{}',
+    form_fill_ms BIGINT,
+    honeypot_triggered BOOLEAN NOT NULL DEFAULT FALSE,
+    client_fingerprint_hash VARCHAR(128),
+    ip_hash VARCHAR(64),  -- Salted SHA-256, never the raw address
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_intake_submissions_product_created_at ON intake_submissions(product, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_intake_submissions_fingerprint ON intake_submissions(client_fingerprint_hash, created_at DESC)
+    WHERE client_fingerprint_hash IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_intake_submissions_ip_hash ON intake_submissions(ip_hash, created_at DESC)
+    WHERE ip_hash IS NOT NULL;
@@ -0,0 +1,14 @@
+-- Migration 015: Outbound Email Threading
+-- Stores the SMTP Message-ID and thread headers of agent replies sent by email
+
+CREATE TABLE IF NOT EXISTS outbound_emails (
+    message_id UUID PRIMARY KEY REFERENCES ticket_messages(id) ON DELETE CASCADE,
+    ticket_id UUID NOT NULL REFERENCES support_tickets(id) ON DELETE CASCADE,
+    -- Without angle brackets, e.g. 'abc123@mail.example.com'
+    smtp_message_id TEXT NOT NULL UNIQUE,
+    -- References header of the sent email, oldest first
+    thread_refs TEXT[] NOT NULL DEFAULT '{}',
+    sent_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_outbound_emails_ticket_id ON outbound_emails(ticket_id);
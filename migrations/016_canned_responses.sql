@@ -0,0 +1,44 @@
+-- Migration 016: Scoped Canned Responses
+-- Personal, team and product-wide reply templates with usage counters
+
+-- ============================================================================
+-- Agent teams and roles
+-- ============================================================================
+
+CREATE TYPE agent_role AS ENUM (
+    'AGENT',
+    'LEAD',
+    'ADMIN'
+);
+
+ALTER TABLE support_agents
+    ADD COLUMN IF NOT EXISTS team VARCHAR(100),
+    ADD COLUMN IF NOT EXISTS role agent_role NOT NULL DEFAULT 'AGENT';
+
+-- ============================================================================
+-- Canned responses
+-- ============================================================================
+
+CREATE TYPE canned_response_scope AS ENUM (
+    'PERSONAL',
+    'TEAM',
+    'PRODUCT'
+);
+
+CREATE TABLE IF NOT EXISTS canned_responses (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    product VARCHAR(50) NOT NULL,
+    scope canned_response_scope NOT NULL,
+    owner_id UUID NOT NULL,  -- References auth.users(id), not enforced by FK
+    team VARCHAR(100),
+    title VARCHAR(200) NOT NULL,
+    body TEXT NOT NULL,
+    usage_count BIGINT NOT NULL DEFAULT 0,
+    last_used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (scope <> 'TEAM' OR team IS NOT NULL)
+);
+
+CREATE INDEX IF NOT EXISTS idx_canned_responses_product_scope ON canned_responses(product, scope);
+CREATE INDEX IF NOT EXISTS idx_canned_responses_owner_id ON canned_responses(owner_id) WHERE scope = 'PERSONAL';
@@ -0,0 +1,36 @@
+-- Migration 017: Resolving Agent Attribution
+-- Captures who resolved a ticket, and with which reply, so CSAT credits them rather than the last assignee
+
+ALTER TABLE support_tickets
+    ADD COLUMN IF NOT EXISTS resolving_agent_id UUID,  -- References auth.users(id), not enforced by FK
+    ADD COLUMN IF NOT EXISTS resolving_message_id UUID REFERENCES ticket_messages(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_support_tickets_resolving_agent_id
+    ON support_tickets(product, resolving_agent_id) WHERE resolving_agent_id IS NOT NULL;
+
+-- ============================================================================
+-- Backfill: best available attribution for already resolved tickets
+-- ============================================================================
+-- The last public agent reply before resolution, else the current assignee.
+-- Not a ticket update, so updated_at is left alone.
+ALTER TABLE support_tickets DISABLE TRIGGER trigger_update_support_tickets_updated_at;
+
+UPDATE support_tickets st
+SET resolving_message_id = r.id,
+    resolving_agent_id = COALESCE(r.author_id, st.assigned_to)
+FROM support_tickets t
+LEFT JOIN LATERAL (
+    SELECT m.id, m.author_id
+    FROM ticket_messages m
+    WHERE m.ticket_id = t.id
+      AND m.is_internal = FALSE
+      AND m.author_id <> t.customer_id
+      AND m.created_at <= COALESCE(t.resolved_at, t.closed_at)
+    ORDER BY m.created_at DESC
+    LIMIT 1
+) r ON TRUE
+WHERE st.id = t.id
+  AND t.status IN ('RESOLVED', 'CLOSED')
+  AND st.resolving_agent_id IS NULL;
+
+ALTER TABLE support_tickets ENABLE TRIGGER trigger_update_support_tickets_updated_at;
@@ -0,0 +1,8 @@
+-- Migration 018: SLA Breach Timestamps
+-- Records when a ticket was flagged as breached, for breach-rate alerting
+
+ALTER TABLE support_tickets
+    ADD COLUMN IF NOT EXISTS sla_breached_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_support_tickets_sla_breached_at
+    ON support_tickets(product, sla_breached_at) WHERE sla_breached_at IS NOT NULL;
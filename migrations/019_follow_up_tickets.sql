@@ -0,0 +1,7 @@
+-- Migration 019: Follow-up Tickets
+-- Links tickets opened by a customer reply to a closed ticket back to the original
+
+ALTER TABLE support_tickets
+    ADD COLUMN IF NOT EXISTS follow_up_of UUID REFERENCES support_tickets(id);
+
+CREATE INDEX IF NOT EXISTS idx_support_tickets_follow_up_of ON support_tickets(follow_up_of) WHERE follow_up_of IS NOT NULL;
@@ -0,0 +1,64 @@
+-- Migration 020: Maintained Open Ticket Counters
+-- Open tickets per product and priority, kept current by trigger for cheap header badges
+
+CREATE TABLE IF NOT EXISTS ticket_open_counters (
+    product VARCHAR(50) NOT NULL,
+    priority ticket_priority NOT NULL,
+    open_count BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (product, priority)
+);
+
+-- ============================================================================
+-- Trigger: Apply each ticket write's change in open counts
+-- ============================================================================
+-- A ticket is open while not RESOLVED/CLOSED and not soft-deleted. Both sides
+-- of a change go through one upsert, in key order, so concurrent writers lock
+-- counter rows in the same order and cannot deadlock each other.
+CREATE OR REPLACE FUNCTION maintain_ticket_open_counters()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO ticket_open_counters AS c (product, priority, open_count)
+    SELECT d.product, d.priority, SUM(d.delta)
+    FROM (
+        SELECT OLD.product, OLD.priority, -1
+        WHERE TG_OP <> 'INSERT' AND OLD.deleted_at IS NULL AND OLD.status NOT IN ('RESOLVED', 'CLOSED')
+        UNION ALL
+        SELECT NEW.product, NEW.priority, 1
+        WHERE TG_OP <> 'DELETE' AND NEW.deleted_at IS NULL AND NEW.status NOT IN ('RESOLVED', 'CLOSED')
+    ) AS d(product, priority, delta)
+    GROUP BY d.product, d.priority
+    HAVING SUM(d.delta) <> 0
+    ORDER BY d.product, d.priority
+    ON CONFLICT (product, priority) DO UPDATE SET open_count = c.open_count + EXCLUDED.open_count;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_ticket_open_counters_insert_delete
+    AFTER INSERT OR DELETE ON support_tickets
+    FOR EACH ROW
+    EXECUTE FUNCTION maintain_ticket_open_counters();
+
+CREATE TRIGGER trigger_ticket_open_counters_update
+    AFTER UPDATE OF product, priority, status, deleted_at ON support_tickets
+    FOR EACH ROW
+    WHEN (
+        OLD.product IS DISTINCT FROM NEW.product
+        OR OLD.priority IS DISTINCT FROM NEW.priority
+        OR OLD.status IS DISTINCT FROM NEW.status
+        OR OLD.deleted_at IS DISTINCT FROM NEW.deleted_at
+    )
+    EXECUTE FUNCTION maintain_ticket_open_counters();
+
+-- ============================================================================
+-- Initial counts
+-- ============================================================================
+-- CREATE TRIGGER holds a lock blocking ticket writes until the migration
+-- commits, so no write can slip between these counts and the triggers.
+INSERT INTO ticket_open_counters (product, priority, open_count)
+SELECT product, priority, COUNT(*)
+FROM support_tickets
+WHERE deleted_at IS NULL AND status NOT IN ('RESOLVED', 'CLOSED')
+GROUP BY product, priority
+ON CONFLICT (product, priority) DO UPDATE SET open_count = EXCLUDED.open_count;
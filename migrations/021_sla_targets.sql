@@ -0,0 +1,29 @@
+-- Migration 021: SLA Targets
+-- Per product/priority SLA targets and the deadlines they give each ticket
+
+-- ============================================================================
+-- Targets
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS sla_targets (
+    product VARCHAR(50) NOT NULL,
+    priority ticket_priority NOT NULL,
+    -- NULL falls back to the repository's SlaPolicy target
+    first_response_minutes INTEGER CHECK (first_response_minutes > 0),
+    resolution_minutes INTEGER CHECK (resolution_minutes > 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (product, priority)
+);
+
+-- ============================================================================
+-- Ticket deadlines
+-- ============================================================================
+
+ALTER TABLE support_tickets
+    ADD COLUMN IF NOT EXISTS first_response_due_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS sla_due_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_support_tickets_sla_due_at
+    ON support_tickets(product, sla_due_at)
+    WHERE deleted_at IS NULL AND status NOT IN ('RESOLVED', 'CLOSED');
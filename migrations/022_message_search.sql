@@ -0,0 +1,61 @@
+-- Migration 022: Message Content in Ticket Search
+-- Public message content joins subject and description in each ticket's search vector;
+-- existing rows are filled by `pleme-support-cli maintenance rebuild-search`
+
+-- ============================================================================
+-- SEARCH VECTOR
+-- ============================================================================
+
+-- Internal notes are left out: ticket search also backs customer-facing lists
+CREATE OR REPLACE FUNCTION support_ticket_search_vector(ticket_id UUID, subject TEXT, description TEXT)
+RETURNS TSVECTOR AS $$
+    SELECT support_ticket_search_vector(subject, description)
+        || COALESCE((
+            SELECT setweight(to_tsvector('simple', string_agg(m.content, ' ' ORDER BY m.created_at)), 'C')
+            FROM ticket_messages m
+            WHERE m.ticket_id = $1 AND m.is_internal = FALSE
+        ), ''::TSVECTOR);
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION set_support_ticket_search_vector()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.search_vector = support_ticket_search_vector(NEW.id, NEW.subject, NEW.description);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- ============================================================================
+-- Trigger: Keep ticket search vectors in step with their messages
+-- ============================================================================
+
+CREATE OR REPLACE FUNCTION refresh_ticket_search_vector_from_messages()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        -- Appending is enough for a new message
+        IF NOT NEW.is_internal THEN
+            UPDATE support_tickets
+            SET search_vector = COALESCE(search_vector, ''::TSVECTOR)
+                || setweight(to_tsvector('simple', NEW.content), 'C')
+            WHERE id = NEW.ticket_id;
+        END IF;
+        RETURN NEW;
+    END IF;
+
+    UPDATE support_tickets
+    SET search_vector = support_ticket_search_vector(id, subject, description)
+    WHERE id = COALESCE(NEW.ticket_id, OLD.ticket_id);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_refresh_ticket_search_vector_on_insert
+    AFTER INSERT ON ticket_messages
+    FOR EACH ROW
+    EXECUTE FUNCTION refresh_ticket_search_vector_from_messages();
+
+CREATE TRIGGER trigger_refresh_ticket_search_vector_on_change
+    AFTER UPDATE OF content, is_internal OR DELETE ON ticket_messages
+    FOR EACH ROW
+    EXECUTE FUNCTION refresh_ticket_search_vector_from_messages();
@@ -0,0 +1,90 @@
+-- Migration 023: Warehouse Views
+-- Stable star-schema extracts for the analytics warehouse, each with a
+-- (changed_at, watermark_key) order for incremental loads
+
+-- ============================================================================
+-- Facts
+-- ============================================================================
+
+CREATE OR REPLACE VIEW warehouse_fact_tickets AS
+SELECT
+    t.id as ticket_id,
+    t.product,
+    t.ticket_ref,
+    t.customer_id,
+    t.status::TEXT as status,
+    t.priority::TEXT as priority,
+    t.channel::TEXT as channel,
+    t.category,
+    t.customer_plan,
+    t.assigned_to,
+    t.resolving_agent_id,
+    t.parent_ticket_id,
+    t.follow_up_of,
+    t.created_at,
+    t.first_response_at,
+    t.resolved_at,
+    t.closed_at,
+    t.deleted_at,
+    EXTRACT(EPOCH FROM (t.first_response_at - t.created_at))::BIGINT as first_response_seconds,
+    EXTRACT(EPOCH FROM (t.resolved_at - t.created_at))::BIGINT as resolution_seconds,
+    t.customer_wait_seconds,
+    t.sla_breach,
+    t.sla_due_at,
+    t.csat_score,
+    t.updated_at as changed_at,
+    t.id::TEXT as watermark_key
+FROM support_tickets t;
+
+-- Message content stays in the operational store
+CREATE OR REPLACE VIEW warehouse_fact_messages AS
+SELECT
+    m.id as message_id,
+    m.ticket_id,
+    t.product,
+    m.author_id,
+    (m.author_id = t.customer_id) as is_customer,
+    m.is_internal,
+    m.flagged,
+    LENGTH(m.content) as content_length,
+    m.created_at,
+    m.created_at as changed_at,
+    m.id::TEXT as watermark_key
+FROM ticket_messages m
+JOIN support_tickets t ON t.id = m.ticket_id;
+
+-- ============================================================================
+-- Dimensions
+-- ============================================================================
+
+-- Tickets reference agents by user_id (assigned_to, resolving_agent_id)
+CREATE OR REPLACE VIEW warehouse_dim_agents AS
+SELECT
+    a.id as agent_id,
+    a.product,
+    a.user_id,
+    a.display_name,
+    a.team,
+    a.role::TEXT as role,
+    a.active,
+    a.created_at,
+    a.updated_at as changed_at,
+    a.id::TEXT as watermark_key
+FROM support_agents a;
+
+CREATE OR REPLACE VIEW warehouse_dim_categories AS
+SELECT
+    t.product,
+    t.category,
+    MIN(t.created_at) as first_seen_at,
+    MIN(t.created_at) as changed_at,
+    t.category as watermark_key
+FROM support_tickets t
+WHERE t.category IS NOT NULL
+GROUP BY t.product, t.category;
+
+-- ============================================================================
+-- Incremental extraction
+-- ============================================================================
+
+CREATE INDEX IF NOT EXISTS idx_support_tickets_product_updated_at ON support_tickets(product, updated_at, (id::TEXT));
@@ -0,0 +1,27 @@
+-- Migration 024: Customer Journey Timestamps
+-- When a CSAT rating was submitted and when a ticket was linked to a known issue
+
+ALTER TABLE support_tickets
+    ADD COLUMN IF NOT EXISTS csat_submitted_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS linked_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_support_tickets_customer_created_at
+    ON support_tickets(customer_id, created_at) WHERE deleted_at IS NULL;
+
+-- ============================================================================
+-- Backfill: best available times for existing ratings and links
+-- ============================================================================
+-- Not a ticket update, so updated_at is left alone.
+ALTER TABLE support_tickets DISABLE TRIGGER trigger_update_support_tickets_updated_at;
+
+UPDATE support_tickets
+SET csat_submitted_at = COALESCE(resolved_at, closed_at, updated_at)
+WHERE csat_score IS NOT NULL AND csat_submitted_at IS NULL;
+
+UPDATE support_tickets st
+SET linked_at = GREATEST(st.created_at, p.created_at)
+FROM support_tickets p
+WHERE p.id = st.parent_ticket_id
+  AND st.linked_at IS NULL;
+
+ALTER TABLE support_tickets ENABLE TRIGGER trigger_update_support_tickets_updated_at;
@@ -0,0 +1,30 @@
+-- Migration 025: Canned Response Translation
+-- Ticket and canned response locales, and how a canned response was sent
+
+-- ============================================================================
+-- Locales
+-- ============================================================================
+-- BCP 47 language tags; NULL means the product's default locale
+
+ALTER TABLE support_tickets
+    ADD COLUMN IF NOT EXISTS locale VARCHAR(35);
+
+ALTER TABLE canned_responses
+    ADD COLUMN IF NOT EXISTS locale VARCHAR(35);
+
+-- ============================================================================
+-- Canned responses sent as messages
+-- ============================================================================
+
+CREATE TYPE message_translation_status AS ENUM (
+    'NOT_NEEDED',
+    'TRANSLATED',
+    'DISABLED',
+    'UNAVAILABLE',
+    'FAILED'
+);
+
+ALTER TABLE ticket_messages
+    ADD COLUMN IF NOT EXISTS canned_response_id UUID REFERENCES canned_responses(id) ON DELETE SET NULL,
+    ADD COLUMN IF NOT EXISTS translation_status message_translation_status,
+    ADD COLUMN IF NOT EXISTS translated_from VARCHAR(35);
@@ -0,0 +1,32 @@
+-- Migration 026: Business Hours
+-- Elapsed time counted only within a weekly business-hours schedule, for contractual SLA reports
+
+-- ============================================================================
+-- support_business_seconds
+-- ============================================================================
+-- Seconds between p_start and p_end that fall on p_days (ISO day of week,
+-- 1 = Monday) between p_open_minute and p_close_minute (minutes after local
+-- midnight, 0-1440) in p_timezone. A 24x7 schedule is all days, 0 to 1440.
+
+CREATE OR REPLACE FUNCTION support_business_seconds(
+    p_start TIMESTAMPTZ,
+    p_end TIMESTAMPTZ,
+    p_timezone TEXT,
+    p_days INTEGER[],
+    p_open_minute INTEGER,
+    p_close_minute INTEGER
+) RETURNS BIGINT AS $$
+    SELECT COALESCE(SUM(EXTRACT(EPOCH FROM window_end - window_start)), 0)::BIGINT
+    FROM (
+        SELECT
+            GREATEST(p_start, (day + make_interval(mins => p_open_minute)) AT TIME ZONE p_timezone) AS window_start,
+            LEAST(p_end, (day + make_interval(mins => p_close_minute)) AT TIME ZONE p_timezone) AS window_end
+        FROM generate_series(
+            (p_start AT TIME ZONE p_timezone)::DATE::TIMESTAMP,
+            (p_end AT TIME ZONE p_timezone)::DATE::TIMESTAMP,
+            INTERVAL '1 day'
+        ) AS day
+        WHERE EXTRACT(ISODOW FROM day)::INTEGER = ANY(p_days)
+    ) windows
+    WHERE window_end > window_start
+$$ LANGUAGE SQL STABLE;
@@ -0,0 +1,62 @@
+-- Migration 027: CSAT Surveys
+-- Satisfaction surveys with comments, channels and token-based links
+
+-- ============================================================================
+-- Enums
+-- ============================================================================
+
+CREATE TYPE csat_survey_channel AS ENUM (
+    'EMAIL',
+    'WEB',
+    'IN_APP',
+    'CHAT',
+    'SMS'
+);
+
+-- ============================================================================
+-- Surveys
+-- ============================================================================
+-- One row per survey sent (token_hash set) or per rating submitted directly
+-- by an authenticated customer (no token). The latest response is mirrored
+-- into support_tickets.csat_score so existing analytics keep working.
+
+CREATE TABLE IF NOT EXISTS csat_surveys (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    ticket_id UUID NOT NULL REFERENCES support_tickets(id) ON DELETE CASCADE,
+    product VARCHAR(50) NOT NULL,
+    customer_id UUID NOT NULL,
+    -- Agent credited with the rating: the ticket's resolving agent when sent
+    agent_id UUID,
+    channel csat_survey_channel NOT NULL,
+    -- SHA-256 of the survey link token, hex encoded; the token itself is never stored
+    token_hash VARCHAR(64) UNIQUE,
+    sent_at TIMESTAMPTZ NOT NULL,
+    expires_at TIMESTAMPTZ,
+    score INTEGER CHECK (score >= 1 AND score <= 5),
+    comment TEXT,
+    responded_at TIMESTAMPTZ,
+    CHECK ((score IS NULL) = (responded_at IS NULL))
+);
+
+CREATE INDEX IF NOT EXISTS idx_csat_surveys_ticket_id ON csat_surveys(ticket_id);
+CREATE INDEX IF NOT EXISTS idx_csat_surveys_product_responded_at
+    ON csat_surveys(product, responded_at) WHERE responded_at IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_csat_surveys_product_sent_at ON csat_surveys(product, sent_at);
+
+-- ============================================================================
+-- Backfill: existing ratings become responded surveys
+-- ============================================================================
+
+INSERT INTO csat_surveys (ticket_id, product, customer_id, agent_id, channel, sent_at, score, responded_at)
+SELECT
+    id,
+    product,
+    customer_id,
+    resolving_agent_id,
+    'WEB',
+    COALESCE(csat_submitted_at, resolved_at, closed_at, updated_at),
+    csat_score,
+    COALESCE(csat_submitted_at, resolved_at, closed_at, updated_at)
+FROM support_tickets
+WHERE csat_score IS NOT NULL
+  AND NOT EXISTS (SELECT 1 FROM csat_surveys cs WHERE cs.ticket_id = support_tickets.id);
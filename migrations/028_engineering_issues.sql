@@ -0,0 +1,40 @@
+-- Migration 028: Engineering Issues
+-- Links from support tickets to issues in engineering trackers (GitHub, Jira)
+
+-- ============================================================================
+-- Enums
+-- ============================================================================
+
+CREATE TYPE issue_tracker AS ENUM (
+    'GITHUB',
+    'JIRA'
+);
+
+CREATE TYPE engineering_issue_state AS ENUM (
+    'OPEN',
+    'CLOSED'
+);
+
+-- ============================================================================
+-- Engineering issues
+-- ============================================================================
+-- external_key is the tracker's own reference: owner/repo#123 or ENG-123.
+-- Several tickets may link the same issue; status syncs update them all.
+
+CREATE TABLE IF NOT EXISTS engineering_issues (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    ticket_id UUID NOT NULL REFERENCES support_tickets(id) ON DELETE CASCADE,
+    product VARCHAR(50) NOT NULL,
+    tracker issue_tracker NOT NULL,
+    project VARCHAR(200) NOT NULL,
+    external_key VARCHAR(200) NOT NULL,
+    url TEXT NOT NULL,
+    title VARCHAR(500) NOT NULL,
+    state engineering_issue_state NOT NULL DEFAULT 'OPEN',
+    linked_by UUID NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    closed_at TIMESTAMPTZ,
+    UNIQUE (ticket_id, tracker, external_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_engineering_issues_external_key ON engineering_issues(tracker, external_key);
@@ -0,0 +1,40 @@
+-- Migration 029: Assignment Rules
+-- Automatic routing of new tickets to agents by round-robin, workload or skills
+
+-- ============================================================================
+-- Enums
+-- ============================================================================
+
+CREATE TYPE assignment_strategy AS ENUM (
+    'ROUND_ROBIN',
+    'LEAST_LOADED',
+    'SKILL_BASED'
+);
+
+-- ============================================================================
+-- Assignment rules
+-- ============================================================================
+-- Rules are tried in ascending position; the first whose conditions match
+-- and that finds an eligible agent assigns the ticket. NULL conditions match
+-- any ticket. last_assigned_to is the round-robin cursor.
+
+CREATE TABLE IF NOT EXISTS assignment_rules (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    product VARCHAR(50) NOT NULL,
+    name VARCHAR(200) NOT NULL,
+    position INTEGER NOT NULL DEFAULT 0,
+    strategy assignment_strategy NOT NULL,
+    category VARCHAR(100),
+    priority ticket_priority,
+    channel ticket_channel,
+    -- Only agents on this team are eligible
+    team VARCHAR(100),
+    -- Agents with this many open tickets are skipped
+    max_open_tickets INTEGER CHECK (max_open_tickets > 0),
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    last_assigned_to UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_assignment_rules_product ON assignment_rules(product, position) WHERE active = TRUE;
@@ -0,0 +1,41 @@
+-- Migration 030: Maintenance Windows
+-- Scheduled maintenance per product, during which SLA clocks pause and alerts are suppressed
+
+-- ============================================================================
+-- Maintenance windows
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS maintenance_windows (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    product VARCHAR(50) NOT NULL,
+    starts_at TIMESTAMPTZ NOT NULL,
+    ends_at TIMESTAMPTZ NOT NULL,
+    reason TEXT NOT NULL,
+    created_by UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (ends_at > starts_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_maintenance_windows_product ON maintenance_windows(product, starts_at, ends_at);
+
+-- ============================================================================
+-- support_maintenance_seconds
+-- ============================================================================
+-- Seconds between p_start and p_end covered by the product's maintenance
+-- windows; overlapping windows are counted once.
+
+CREATE OR REPLACE FUNCTION support_maintenance_seconds(
+    p_product TEXT,
+    p_start TIMESTAMPTZ,
+    p_end TIMESTAMPTZ
+) RETURNS BIGINT AS $$
+    SELECT COALESCE(SUM(EXTRACT(EPOCH FROM upper(covered) - lower(covered))), 0)::BIGINT
+    FROM unnest((
+        SELECT range_agg(tstzrange(starts_at, ends_at) * tstzrange(p_start, p_end))
+        FROM maintenance_windows
+        WHERE product = p_product
+          AND p_end > p_start
+          AND starts_at < p_end
+          AND ends_at > p_start
+    )) AS covered
+$$ LANGUAGE SQL STABLE;
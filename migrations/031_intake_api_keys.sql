@@ -0,0 +1,47 @@
+-- Migration 031: Intake API Keys
+-- Product-scoped API keys for partner ticket intake, with per-key usage metering
+
+-- ============================================================================
+-- Enums
+-- ============================================================================
+
+CREATE TYPE api_key_scope AS ENUM (
+    'CREATE_TICKETS',
+    'READ_TICKETS',
+    'ADD_MESSAGES'
+);
+
+-- ============================================================================
+-- API keys
+-- ============================================================================
+-- Only the SHA-256 of the secret is stored; key_prefix is the non-secret
+-- leading part shown in listings so keys can be told apart.
+
+CREATE TABLE IF NOT EXISTS intake_api_keys (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    product VARCHAR(50) NOT NULL,
+    name VARCHAR(200) NOT NULL,
+    key_prefix VARCHAR(20) NOT NULL,
+    key_hash CHAR(64) NOT NULL UNIQUE,
+    scopes api_key_scope[] NOT NULL,
+    created_by UUID NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    revoked_at TIMESTAMPTZ,
+    last_used_at TIMESTAMPTZ,
+    usage_count BIGINT NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS idx_intake_api_keys_product ON intake_api_keys(product);
+
+-- ============================================================================
+-- Usage metering
+-- ============================================================================
+-- Authenticated requests per key, UTC day and scope.
+
+CREATE TABLE IF NOT EXISTS intake_api_key_usage (
+    key_id UUID NOT NULL REFERENCES intake_api_keys(id) ON DELETE CASCADE,
+    day DATE NOT NULL,
+    scope api_key_scope NOT NULL,
+    request_count BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (key_id, day, scope)
+);
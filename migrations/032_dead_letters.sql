@@ -0,0 +1,36 @@
+-- Migration 032: Dead Letters
+-- Outbox and webhook events that exhausted their retries, kept for manual retry or discard
+
+-- ============================================================================
+-- Enums
+-- ============================================================================
+
+CREATE TYPE dead_letter_status AS ENUM (
+    'PENDING',
+    'RETRIED',
+    'DISCARDED'
+);
+
+-- ============================================================================
+-- Dead letters
+-- ============================================================================
+-- source names the integration that failed (e.g. webhook:acme, outbox:email)
+-- and selects the handler that redelivers the event on retry.
+
+CREATE TABLE IF NOT EXISTS dead_letters (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    product VARCHAR(50) NOT NULL,
+    source VARCHAR(100) NOT NULL,
+    event_type VARCHAR(100) NOT NULL,
+    payload JSONB NOT NULL,
+    ticket_id UUID REFERENCES support_tickets(id) ON DELETE SET NULL,
+    attempts INTEGER NOT NULL,
+    last_error TEXT NOT NULL,
+    status dead_letter_status NOT NULL DEFAULT 'PENDING',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_failed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMPTZ,
+    resolved_by UUID
+);
+
+CREATE INDEX IF NOT EXISTS idx_dead_letters_pending ON dead_letters(product, created_at) WHERE status = 'PENDING';
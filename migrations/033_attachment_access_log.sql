@@ -0,0 +1,22 @@
+-- Migration 033: Attachment Access Log
+-- Audit trail of attachment downloads: who, when and from where
+
+-- Attachments are stored by services; attachment_id is the service's
+-- identifier. The IP is kept as given because the log exists for security
+-- investigations.
+
+CREATE TABLE IF NOT EXISTS attachment_access_log (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    ticket_id UUID NOT NULL REFERENCES support_tickets(id) ON DELETE CASCADE,
+    attachment_id UUID NOT NULL,
+    file_name VARCHAR(500),
+    accessed_by UUID NOT NULL,
+    accessed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ip_address VARCHAR(45),
+    user_agent TEXT,
+    country VARCHAR(2),
+    region VARCHAR(100)
+);
+
+CREATE INDEX IF NOT EXISTS idx_attachment_access_log_attachment ON attachment_access_log(attachment_id, accessed_at);
+CREATE INDEX IF NOT EXISTS idx_attachment_access_log_ticket ON attachment_access_log(ticket_id, accessed_at);
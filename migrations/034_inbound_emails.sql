@@ -0,0 +1,16 @@
+-- Migration 034: Inbound Emails
+-- Message-IDs of ingested customer emails, for deduplication and threading
+
+-- A customer's follow-up may reference their own earlier email rather than
+-- an agent reply, so inbound ids are matched alongside outbound_emails.
+
+CREATE TABLE IF NOT EXISTS inbound_emails (
+    smtp_message_id TEXT PRIMARY KEY,
+    ticket_id UUID NOT NULL REFERENCES support_tickets(id) ON DELETE CASCADE,
+    -- NULL when the email opened the ticket
+    message_id UUID REFERENCES ticket_messages(id) ON DELETE SET NULL,
+    from_address VARCHAR(320) NOT NULL,
+    received_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_inbound_emails_ticket_id ON inbound_emails(ticket_id);
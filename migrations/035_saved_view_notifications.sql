@@ -0,0 +1,25 @@
+-- Migration 035: Saved View Notifications
+-- Saved views that notify their owner of newly matching tickets
+
+-- ============================================================================
+-- Saved views
+-- ============================================================================
+-- last_evaluated_at is the evaluator's watermark: tickets created or updated
+-- after it are checked against the view on the next run.
+
+ALTER TABLE saved_views ADD COLUMN IF NOT EXISTS notify_on_match BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE saved_views ADD COLUMN IF NOT EXISTS last_evaluated_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_saved_views_notify ON saved_views(product) WHERE notify_on_match = TRUE;
+
+-- ============================================================================
+-- Notified matches
+-- ============================================================================
+-- Each ticket is notified at most once per view, however often it changes.
+
+CREATE TABLE IF NOT EXISTS saved_view_notifications (
+    view_id UUID NOT NULL REFERENCES saved_views(id) ON DELETE CASCADE,
+    ticket_id UUID NOT NULL REFERENCES support_tickets(id) ON DELETE CASCADE,
+    notified_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (view_id, ticket_id)
+);
@@ -0,0 +1,110 @@
+-- Migration 036: Locale-Aware Ticket Search
+-- Search vectors stem in the ticket's language; existing rows are re-indexed by
+-- `pleme-support-cli maintenance rebuild-search`
+
+-- ============================================================================
+-- SEARCH CONFIGURATION
+-- ============================================================================
+
+-- Text search configuration for a BCP 47 language tag. Thai (and any language
+-- without a Postgres stemmer) falls back to 'simple', so no other language's
+-- stemming rules are applied to it.
+CREATE OR REPLACE FUNCTION support_search_config(locale TEXT)
+RETURNS TEXT AS $$
+    SELECT CASE LOWER(SPLIT_PART(REPLACE(COALESCE(locale, ''), '_', '-'), '-', 1))
+        WHEN 'pt' THEN 'portuguese'
+        WHEN 'en' THEN 'english'
+        ELSE 'simple'
+    END;
+$$ LANGUAGE sql IMMUTABLE;
+
+-- Resolved from the ticket's locale, or the product's default locale when the
+-- ticket has none
+ALTER TABLE support_tickets
+    ADD COLUMN IF NOT EXISTS search_config VARCHAR(32) NOT NULL DEFAULT 'simple';
+
+-- ============================================================================
+-- SEARCH VECTOR
+-- ============================================================================
+
+-- Stemmed lexemes in the ticket's language plus unstemmed ones, so words in
+-- other languages (and exact forms) still match
+CREATE OR REPLACE FUNCTION support_search_vector(config TEXT, content TEXT)
+RETURNS TSVECTOR AS $$
+    SELECT CASE
+        WHEN config = 'simple' THEN to_tsvector('simple', COALESCE(content, ''))
+        ELSE to_tsvector(config::REGCONFIG, COALESCE(content, '')) || to_tsvector('simple', COALESCE(content, ''))
+    END;
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION support_ticket_search_vector(ticket_id UUID, subject TEXT, description TEXT, config TEXT)
+RETURNS TSVECTOR AS $$
+    SELECT setweight(support_search_vector(config, subject), 'A')
+        || setweight(support_search_vector(config, description), 'B')
+        || COALESCE((
+            SELECT setweight(support_search_vector(config, string_agg(m.content, ' ' ORDER BY m.created_at)), 'C')
+            FROM ticket_messages m
+            WHERE m.ticket_id = $1 AND m.is_internal = FALSE
+        ), ''::TSVECTOR);
+$$ LANGUAGE sql STABLE;
+
+-- Queries come in any language: match the stems of every configured language
+-- and the unstemmed words
+CREATE OR REPLACE FUNCTION support_search_query(query TEXT)
+RETURNS TSQUERY AS $$
+    SELECT websearch_to_tsquery('portuguese', query)
+        || websearch_to_tsquery('english', query)
+        || websearch_to_tsquery('simple', query);
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION set_support_ticket_search_vector()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.search_vector = support_ticket_search_vector(NEW.id, NEW.subject, NEW.description, NEW.search_config);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_set_support_ticket_search_vector ON support_tickets;
+
+CREATE TRIGGER trigger_set_support_ticket_search_vector
+    BEFORE INSERT OR UPDATE OF subject, description, search_config ON support_tickets
+    FOR EACH ROW
+    EXECUTE FUNCTION set_support_ticket_search_vector();
+
+CREATE OR REPLACE FUNCTION refresh_ticket_search_vector_from_messages()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        -- Appending is enough for a new message
+        IF NOT NEW.is_internal THEN
+            UPDATE support_tickets
+            SET search_vector = COALESCE(search_vector, ''::TSVECTOR)
+                || setweight(support_search_vector(search_config, NEW.content), 'C')
+            WHERE id = NEW.ticket_id;
+        END IF;
+        RETURN NEW;
+    END IF;
+
+    UPDATE support_tickets
+    SET search_vector = support_ticket_search_vector(id, subject, description, search_config)
+    WHERE id = COALESCE(NEW.ticket_id, OLD.ticket_id);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- ============================================================================
+-- Trigger: Search re-indexing is not a ticket update
+-- ============================================================================
+CREATE OR REPLACE FUNCTION update_support_tickets_updated_at()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF to_jsonb(NEW) - 'search_vector' - 'search_config' - 'updated_at'
+        = to_jsonb(OLD) - 'search_vector' - 'search_config' - 'updated_at' THEN
+        NEW.updated_at = OLD.updated_at;
+    ELSIF NEW.updated_at IS NOT DISTINCT FROM OLD.updated_at THEN
+        NEW.updated_at = NOW();
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,19 @@
+-- Migration 037: External CSAT Import
+-- Survey responses imported from external tools (SurveyMonkey, Typeform, ...)
+
+-- ============================================================================
+-- CSAT SURVEYS
+-- ============================================================================
+-- source and external_id identify an imported response, so re-importing the
+-- same export adds nothing. NPS responses keep their 0-10 score; score holds
+-- it scaled to 1-5 for CSAT analytics.
+
+ALTER TABLE csat_surveys ADD COLUMN IF NOT EXISTS source VARCHAR(50);
+ALTER TABLE csat_surveys ADD COLUMN IF NOT EXISTS external_id VARCHAR(255);
+ALTER TABLE csat_surveys ADD COLUMN IF NOT EXISTS nps_score INTEGER CHECK (nps_score >= 0 AND nps_score <= 10);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_csat_surveys_source_external_id
+    ON csat_surveys(source, external_id) WHERE external_id IS NOT NULL;
+
+-- Email matching looks up inbound senders case-insensitively
+CREATE INDEX IF NOT EXISTS idx_inbound_emails_from_address ON inbound_emails(LOWER(from_address));
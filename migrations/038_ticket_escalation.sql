@@ -0,0 +1,49 @@
+-- Migration 038: Ticket Escalation
+-- Rules that raise the escalation level of neglected tickets and notify team leads
+
+-- ============================================================================
+-- Enums
+-- ============================================================================
+
+CREATE TYPE escalation_condition AS ENUM (
+    'UNANSWERED',
+    'UNRESOLVED'
+);
+
+-- ============================================================================
+-- Escalation rules
+-- ============================================================================
+-- A rule escalates open tickets (not waiting on the customer) below its level
+-- once they have gone after_minutes since creation without a first response
+-- (UNANSWERED) or a resolution (UNRESOLVED). NULL priority matches any ticket.
+-- Leads of notify_team are notified, else leads of the assignee's team.
+
+CREATE TABLE IF NOT EXISTS escalation_rules (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    product VARCHAR(50) NOT NULL,
+    name VARCHAR(200) NOT NULL,
+    level INTEGER NOT NULL CHECK (level > 0),
+    condition escalation_condition NOT NULL,
+    priority ticket_priority,
+    after_minutes INTEGER NOT NULL CHECK (after_minutes > 0),
+    notify_team VARCHAR(100),
+    -- Raise the ticket's priority one step when escalating
+    bump_priority BOOLEAN NOT NULL DEFAULT FALSE,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_escalation_rules_product ON escalation_rules(product, level) WHERE active = TRUE;
+
+-- ============================================================================
+-- Ticket escalation state
+-- ============================================================================
+
+ALTER TABLE support_tickets
+    ADD COLUMN IF NOT EXISTS escalation_level INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS escalated_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_support_tickets_escalation
+    ON support_tickets(product, escalation_level)
+    WHERE deleted_at IS NULL AND status NOT IN ('RESOLVED', 'CLOSED');
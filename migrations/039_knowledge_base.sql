@@ -0,0 +1,58 @@
+-- Migration 039: Knowledge Base
+-- Help articles suggested to customers and agents by similarity to a ticket
+
+-- ============================================================================
+-- Knowledge articles
+-- ============================================================================
+-- search_config is resolved from the article's locale, or the product's
+-- default locale when it has none, like support_tickets.search_config.
+
+CREATE TABLE IF NOT EXISTS knowledge_articles (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    product VARCHAR(50) NOT NULL,
+    title VARCHAR(500) NOT NULL,
+    body TEXT NOT NULL,
+    -- Language of the article (BCP 47); NULL means the product's locale
+    locale VARCHAR(35),
+    search_config VARCHAR(32) NOT NULL DEFAULT 'simple',
+    search_vector TSVECTOR,
+    published BOOLEAN NOT NULL DEFAULT TRUE,
+    created_by UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_knowledge_articles_product ON knowledge_articles(product, updated_at DESC);
+CREATE INDEX IF NOT EXISTS idx_knowledge_articles_search ON knowledge_articles USING GIN(search_vector) WHERE published = TRUE;
+
+CREATE OR REPLACE FUNCTION set_knowledge_article_search_vector()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.search_vector = setweight(support_search_vector(NEW.search_config, NEW.title), 'A')
+        || setweight(support_search_vector(NEW.search_config, NEW.body), 'B');
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_set_knowledge_article_search_vector ON knowledge_articles;
+
+CREATE TRIGGER trigger_set_knowledge_article_search_vector
+    BEFORE INSERT OR UPDATE OF title, body, search_config ON knowledge_articles
+    FOR EACH ROW
+    EXECUTE FUNCTION set_knowledge_article_search_vector();
+
+-- ============================================================================
+-- Similarity query
+-- ============================================================================
+
+-- Any of the stemmed words of `content`, for ranking documents by how much of
+-- it they share. Only the stemmed lexemes are used so stop words don't match
+-- everything; NULL when nothing is left.
+CREATE OR REPLACE FUNCTION support_similarity_query(config TEXT, content TEXT)
+RETURNS TSQUERY AS $$
+    SELECT string_agg(
+        '''' || REPLACE(REPLACE(lexeme, '\', '\\'), '''', '''''') || '''',
+        ' | ' ORDER BY lexeme
+    )::TSQUERY
+    FROM unnest(tsvector_to_array(to_tsvector(config::REGCONFIG, COALESCE(content, '')))) AS lexeme;
+$$ LANGUAGE sql STABLE;
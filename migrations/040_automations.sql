@@ -0,0 +1,93 @@
+-- Migration 040: Workflow Automations
+-- Trigger-condition-action rules run on ticket changes or timers, with execution logs
+
+-- ============================================================================
+-- Ticket tags
+-- ============================================================================
+-- Lowercase, set by automations and matched by their conditions
+
+ALTER TABLE support_tickets
+    ADD COLUMN IF NOT EXISTS tags TEXT[] NOT NULL DEFAULT '{}';
+
+CREATE INDEX IF NOT EXISTS idx_support_tickets_tags ON support_tickets USING GIN(tags);
+
+-- ============================================================================
+-- Enums
+-- ============================================================================
+
+CREATE TYPE automation_trigger AS ENUM (
+    'TICKET_CREATED',
+    'STATUS_CHANGED',
+    'MESSAGE_ADDED',
+    'TIMER'
+);
+
+CREATE TYPE automation_outcome AS ENUM (
+    'APPLIED',
+    'FAILED',
+    'LOOP_PREVENTED'
+);
+
+-- ============================================================================
+-- Automation rules
+-- ============================================================================
+-- Rules for a trigger run in ascending position. conditions and actions are
+-- JSON arrays of {field, operator, value} and {kind, value}. TIMER rules fire
+-- on tickets not updated for timer_minutes, once per idle period.
+
+CREATE TABLE IF NOT EXISTS automation_rules (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    product VARCHAR(50) NOT NULL,
+    name VARCHAR(200) NOT NULL,
+    position INTEGER NOT NULL DEFAULT 0,
+    trigger automation_trigger NOT NULL,
+    timer_minutes INTEGER CHECK (timer_minutes > 0),
+    conditions JSONB NOT NULL DEFAULT '[]',
+    actions JSONB NOT NULL,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((trigger = 'TIMER') = (timer_minutes IS NOT NULL))
+);
+
+CREATE INDEX IF NOT EXISTS idx_automation_rules_product ON automation_rules(product, trigger, position) WHERE active = TRUE;
+
+-- ============================================================================
+-- Execution log
+-- ============================================================================
+-- One row per rule run on a ticket whose conditions matched. depth counts the
+-- automations that led to this one; a failed run's actions are rolled back.
+
+CREATE TABLE IF NOT EXISTS automation_executions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    rule_id UUID NOT NULL REFERENCES automation_rules(id) ON DELETE CASCADE,
+    ticket_id UUID NOT NULL REFERENCES support_tickets(id) ON DELETE CASCADE,
+    trigger automation_trigger NOT NULL,
+    depth INTEGER NOT NULL DEFAULT 0,
+    outcome automation_outcome NOT NULL,
+    actions_applied INTEGER NOT NULL DEFAULT 0,
+    error TEXT,
+    executed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_automation_executions_rule ON automation_executions(rule_id, executed_at DESC);
+CREATE INDEX IF NOT EXISTS idx_automation_executions_ticket ON automation_executions(ticket_id, rule_id, executed_at DESC);
+
+-- ============================================================================
+-- Trigger: Automated replies are not a first response
+-- ============================================================================
+
+CREATE OR REPLACE FUNCTION set_first_response_at()
+RETURNS TRIGGER AS $$
+BEGIN
+    -- Set first_response_at if this is the first response from an agent
+    UPDATE support_tickets
+    SET first_response_at = NEW.created_at
+    WHERE id = NEW.ticket_id
+      AND first_response_at IS NULL
+      AND NEW.is_internal = FALSE
+      AND NEW.author_id != '00000000-0000-0000-0000-000000000000'
+      AND NEW.author_id != (SELECT customer_id FROM support_tickets WHERE id = NEW.ticket_id);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,104 @@
+-- Migration 041: Test Mode Tickets
+-- Sandbox tickets for smoke tests and demos, kept out of SLA, analytics and notifications
+
+-- ============================================================================
+-- Test mode flag
+-- ============================================================================
+-- Set at creation only. Test tickets get no SLA deadlines or breaches, are
+-- left out of metrics and warehouse extracts, and can be purged in bulk.
+
+ALTER TABLE support_tickets
+    ADD COLUMN IF NOT EXISTS test_mode BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE INDEX IF NOT EXISTS idx_support_tickets_test_mode ON support_tickets(product, created_at) WHERE test_mode = TRUE;
+
+-- ============================================================================
+-- Warehouse views
+-- ============================================================================
+
+CREATE OR REPLACE VIEW warehouse_fact_tickets AS
+SELECT
+    t.id as ticket_id,
+    t.product,
+    t.ticket_ref,
+    t.customer_id,
+    t.status::TEXT as status,
+    t.priority::TEXT as priority,
+    t.channel::TEXT as channel,
+    t.category,
+    t.customer_plan,
+    t.assigned_to,
+    t.resolving_agent_id,
+    t.parent_ticket_id,
+    t.follow_up_of,
+    t.created_at,
+    t.first_response_at,
+    t.resolved_at,
+    t.closed_at,
+    t.deleted_at,
+    EXTRACT(EPOCH FROM (t.first_response_at - t.created_at))::BIGINT as first_response_seconds,
+    EXTRACT(EPOCH FROM (t.resolved_at - t.created_at))::BIGINT as resolution_seconds,
+    t.customer_wait_seconds,
+    t.sla_breach,
+    t.sla_due_at,
+    t.csat_score,
+    t.updated_at as changed_at,
+    t.id::TEXT as watermark_key
+FROM support_tickets t
+WHERE NOT t.test_mode;
+
+CREATE OR REPLACE VIEW warehouse_fact_messages AS
+SELECT
+    m.id as message_id,
+    m.ticket_id,
+    t.product,
+    m.author_id,
+    (m.author_id = t.customer_id) as is_customer,
+    m.is_internal,
+    m.flagged,
+    LENGTH(m.content) as content_length,
+    m.created_at,
+    m.created_at as changed_at,
+    m.id::TEXT as watermark_key
+FROM ticket_messages m
+JOIN support_tickets t ON t.id = m.ticket_id
+WHERE NOT t.test_mode;
+
+CREATE OR REPLACE VIEW warehouse_dim_categories AS
+SELECT
+    t.product,
+    t.category,
+    MIN(t.created_at) as first_seen_at,
+    MIN(t.created_at) as changed_at,
+    t.category as watermark_key
+FROM support_tickets t
+WHERE t.category IS NOT NULL
+  AND NOT t.test_mode
+GROUP BY t.product, t.category;
+
+-- ============================================================================
+-- Open ticket counters
+-- ============================================================================
+-- test_mode never changes after insert, so the update trigger's column list
+-- can stay as it is. No ticket is in test mode yet, so the counts hold.
+
+CREATE OR REPLACE FUNCTION maintain_ticket_open_counters()
+RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO ticket_open_counters AS c (product, priority, open_count)
+    SELECT d.product, d.priority, SUM(d.delta)
+    FROM (
+        SELECT OLD.product, OLD.priority, -1
+        WHERE TG_OP <> 'INSERT' AND NOT OLD.test_mode AND OLD.deleted_at IS NULL AND OLD.status NOT IN ('RESOLVED', 'CLOSED')
+        UNION ALL
+        SELECT NEW.product, NEW.priority, 1
+        WHERE TG_OP <> 'DELETE' AND NOT NEW.test_mode AND NEW.deleted_at IS NULL AND NEW.status NOT IN ('RESOLVED', 'CLOSED')
+    ) AS d(product, priority, delta)
+    GROUP BY d.product, d.priority
+    HAVING SUM(d.delta) <> 0
+    ORDER BY d.product, d.priority
+    ON CONFLICT (product, priority) DO UPDATE SET open_count = c.open_count + EXCLUDED.open_count;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
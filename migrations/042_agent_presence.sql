@@ -0,0 +1,7 @@
+-- Migration 042: Agent Presence
+-- Heartbeat timestamp on agent profiles, for counting agents online on wallboards
+
+ALTER TABLE support_agents
+    ADD COLUMN IF NOT EXISTS last_seen_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_support_agents_last_seen_at ON support_agents(product, last_seen_at) WHERE active = TRUE;
@@ -0,0 +1,24 @@
+-- Migration 043: Duplicate Burst Collapse
+-- Occurrence counters on tickets that absorbed repeat submissions, and a log of collapsed submissions
+
+ALTER TABLE support_tickets
+    ADD COLUMN IF NOT EXISTS error_signature TEXT,
+    ADD COLUMN IF NOT EXISTS occurrence_count INTEGER NOT NULL DEFAULT 1,
+    ADD COLUMN IF NOT EXISTS last_occurred_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_support_tickets_error_signature
+    ON support_tickets(product, error_signature, created_at DESC)
+    WHERE error_signature IS NOT NULL AND deleted_at IS NULL;
+
+CREATE TABLE IF NOT EXISTS collapsed_submissions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    product VARCHAR(50) NOT NULL,
+    ticket_id UUID NOT NULL REFERENCES support_tickets(id) ON DELETE CASCADE,
+    customer_id UUID NOT NULL,
+    -- SAME_CUSTOMER or ERROR_SIGNATURE
+    reason TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_collapsed_submissions_product ON collapsed_submissions(product, created_at);
+CREATE INDEX IF NOT EXISTS idx_collapsed_submissions_ticket ON collapsed_submissions(ticket_id);
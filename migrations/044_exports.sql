@@ -0,0 +1,20 @@
+-- Migration 044: Exports
+-- Generated CSV exports of tickets and dashboard metrics, kept for download until they expire
+
+CREATE TABLE IF NOT EXISTS support_exports (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    product VARCHAR(50) NOT NULL,
+    -- TICKETS or DASHBOARD_METRICS
+    kind TEXT NOT NULL,
+    -- CSV or EXCEL
+    format TEXT NOT NULL,
+    filename TEXT NOT NULL,
+    content BYTEA NOT NULL,
+    row_count BIGINT NOT NULL,
+    created_by UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_support_exports_expires_at ON support_exports(expires_at);
+CREATE INDEX IF NOT EXISTS idx_support_exports_product ON support_exports(product, created_at DESC);
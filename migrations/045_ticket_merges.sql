@@ -0,0 +1,32 @@
+-- Migration 045: Ticket Merges
+-- Duplicates merged into a primary ticket, with what is needed to undo the merge within its window
+
+ALTER TABLE support_tickets ADD COLUMN IF NOT EXISTS merged_into UUID REFERENCES support_tickets(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_support_tickets_merged_into ON support_tickets(merged_into)
+    WHERE merged_into IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS ticket_merges (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    product VARCHAR(50) NOT NULL,
+    primary_ticket_id UUID NOT NULL REFERENCES support_tickets(id) ON DELETE CASCADE,
+    merged_by UUID,
+    merged_at TIMESTAMPTZ NOT NULL,
+    -- undo_merge is refused after this
+    undo_until TIMESTAMPTZ NOT NULL,
+    undone_by UUID,
+    undone_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_ticket_merges_primary ON ticket_merges(primary_ticket_id, merged_at DESC);
+
+-- One row per duplicate: the state the merge changed, restored by undo_merge
+CREATE TABLE IF NOT EXISTS ticket_merge_duplicates (
+    merge_id UUID NOT NULL REFERENCES ticket_merges(id) ON DELETE CASCADE,
+    ticket_id UUID NOT NULL REFERENCES support_tickets(id) ON DELETE CASCADE,
+    previous_status ticket_status NOT NULL,
+    previous_closed_at TIMESTAMPTZ,
+    -- Messages moved from the duplicate to the primary ticket
+    message_ids UUID[] NOT NULL DEFAULT '{}',
+    PRIMARY KEY (merge_id, ticket_id)
+);
@@ -0,0 +1,14 @@
+-- Migration 046: Ticket Viewers
+-- Per-ticket heartbeats from agent consoles, for warning agents who open or reply to the same ticket
+
+CREATE TABLE IF NOT EXISTS ticket_viewers (
+    ticket_id UUID NOT NULL REFERENCES support_tickets(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL,
+    viewing_since TIMESTAMPTZ NOT NULL,
+    last_seen_at TIMESTAMPTZ NOT NULL,
+    -- Latest keystroke in the reply box; NULL once the agent stops or sends
+    typing_at TIMESTAMPTZ,
+    PRIMARY KEY (ticket_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_ticket_viewers_last_seen_at ON ticket_viewers(last_seen_at);
@@ -0,0 +1,59 @@
+-- Migration 047: Report Schedules
+-- Daily and weekly summary reports, generated by `run_due_reports` and kept per run
+
+-- ============================================================================
+-- Next run: the first `hour` o'clock (on ISO weekday `day_of_week` for WEEKLY)
+-- in `timezone` strictly after `after`
+-- ============================================================================
+CREATE OR REPLACE FUNCTION support_report_next_run(after TIMESTAMPTZ, timezone TEXT, frequency TEXT, day_of_week INT, hour INT)
+RETURNS TIMESTAMPTZ AS $$
+    SELECT (candidate + CASE WHEN candidate <= local_time THEN step ELSE INTERVAL '0' END) AT TIME ZONE $2
+    FROM (
+        SELECT
+            local_time,
+            DATE_TRUNC('day', local_time) + make_interval(hours => $5)
+                + CASE WHEN $3 = 'WEEKLY'
+                    THEN make_interval(days => ($4 - EXTRACT(ISODOW FROM local_time)::INT + 7) % 7)
+                    ELSE INTERVAL '0'
+                END AS candidate,
+            CASE WHEN $3 = 'WEEKLY' THEN INTERVAL '7 days' ELSE INTERVAL '1 day' END AS step
+        FROM (SELECT $1 AT TIME ZONE $2 AS local_time) l
+    ) c;
+$$ LANGUAGE sql STABLE;
+
+CREATE TABLE IF NOT EXISTS report_schedules (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    product VARCHAR(50) NOT NULL,
+    name TEXT NOT NULL,
+    -- DAILY or WEEKLY
+    frequency TEXT NOT NULL,
+    -- ISO weekday weekly reports run on, 1 = Monday
+    day_of_week INTEGER NOT NULL DEFAULT 1 CHECK (day_of_week BETWEEN 1 AND 7),
+    -- Local hour reports run at
+    hour INTEGER NOT NULL CHECK (hour BETWEEN 0 AND 23),
+    timezone TEXT NOT NULL,
+    -- Where the host service delivers the report, e.g. email addresses
+    recipients TEXT[] NOT NULL DEFAULT '{}',
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    next_run_at TIMESTAMPTZ NOT NULL,
+    last_run_at TIMESTAMPTZ,
+    created_by UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_report_schedules_due ON report_schedules(next_run_at) WHERE active = TRUE;
+CREATE INDEX IF NOT EXISTS idx_report_schedules_product ON report_schedules(product);
+
+CREATE TABLE IF NOT EXISTS report_runs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    schedule_id UUID NOT NULL REFERENCES report_schedules(id) ON DELETE CASCADE,
+    product VARCHAR(50) NOT NULL,
+    -- The schedule's recipients when it ran
+    recipients TEXT[] NOT NULL DEFAULT '{}',
+    -- SupportReport as generated
+    report JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_report_runs_schedule ON report_runs(schedule_id, created_at DESC);
@@ -0,0 +1,10 @@
+-- Migration 048: Client Info
+-- App version and platform reported by clients, stored in ticket metadata under 'client'
+
+CREATE INDEX IF NOT EXISTS idx_support_tickets_client_app_version
+    ON support_tickets(product, (metadata->'client'->>'app_version'), created_at)
+    WHERE metadata ? 'client';
+
+ALTER TABLE saved_views ADD COLUMN IF NOT EXISTS app_version TEXT;
+-- IOS, ANDROID, WEB, MACOS, WINDOWS, LINUX or OTHER
+ALTER TABLE saved_views ADD COLUMN IF NOT EXISTS platform TEXT;
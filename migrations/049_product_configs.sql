@@ -0,0 +1,25 @@
+-- Migration 049: Product Configs
+-- Registry of products tickets can be filed under, with their defaults
+
+CREATE TABLE IF NOT EXISTS product_configs (
+    product VARCHAR(50) PRIMARY KEY,
+    display_name TEXT,
+    default_priority ticket_priority NOT NULL DEFAULT 'MEDIUM',
+    -- Name of the SLA policy the product follows; resolved by the host service
+    sla_policy TEXT,
+    business_timezone TEXT NOT NULL DEFAULT 'UTC',
+    -- ISO weekdays, 1 = Monday
+    business_days INTEGER[] NOT NULL DEFAULT '{1,2,3,4,5,6,7}',
+    business_opens_at_minute INTEGER NOT NULL DEFAULT 0,
+    business_closes_at_minute INTEGER NOT NULL DEFAULT 1440,
+    enabled_channels ticket_channel[] NOT NULL DEFAULT '{EMAIL,CHAT,WEB,API}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (business_opens_at_minute >= 0 AND business_opens_at_minute < business_closes_at_minute
+           AND business_closes_at_minute <= 1440)
+);
+
+-- Products already in use stay open for new tickets
+INSERT INTO product_configs (product)
+SELECT DISTINCT product FROM support_tickets
+ON CONFLICT (product) DO NOTHING;
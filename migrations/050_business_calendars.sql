@@ -0,0 +1,106 @@
+-- Migration 050: Business Calendars
+-- Holidays on product business hours, and first-response SLA clocks that only run during them
+
+ALTER TABLE product_configs ADD COLUMN IF NOT EXISTS business_holidays DATE[] NOT NULL DEFAULT '{}';
+
+-- ============================================================================
+-- support_business_seconds (with holidays)
+-- ============================================================================
+-- As the six-argument version, skipping the local dates in p_holidays.
+
+CREATE OR REPLACE FUNCTION support_business_seconds(
+    p_start TIMESTAMPTZ,
+    p_end TIMESTAMPTZ,
+    p_timezone TEXT,
+    p_days INTEGER[],
+    p_open_minute INTEGER,
+    p_close_minute INTEGER,
+    p_holidays DATE[]
+) RETURNS BIGINT AS $$
+    SELECT COALESCE(SUM(EXTRACT(EPOCH FROM window_end - window_start)), 0)::BIGINT
+    FROM (
+        SELECT
+            GREATEST(p_start, (day + make_interval(mins => p_open_minute)) AT TIME ZONE p_timezone) AS window_start,
+            LEAST(p_end, (day + make_interval(mins => p_close_minute)) AT TIME ZONE p_timezone) AS window_end
+        FROM generate_series(
+            (p_start AT TIME ZONE p_timezone)::DATE::TIMESTAMP,
+            (p_end AT TIME ZONE p_timezone)::DATE::TIMESTAMP,
+            INTERVAL '1 day'
+        ) AS day
+        WHERE EXTRACT(ISODOW FROM day)::INTEGER = ANY(p_days)
+          AND NOT day::DATE = ANY(p_holidays)
+    ) windows
+    WHERE window_end > window_start
+$$ LANGUAGE SQL STABLE;
+
+-- ============================================================================
+-- support_product_business_seconds
+-- ============================================================================
+-- Seconds between p_start and p_end within the product's business calendar.
+-- Products open around the clock, or not registered, count wall-clock time.
+
+CREATE OR REPLACE FUNCTION support_product_business_seconds(
+    p_product TEXT,
+    p_start TIMESTAMPTZ,
+    p_end TIMESTAMPTZ
+) RETURNS BIGINT AS $$
+    SELECT CASE
+        WHEN c.product IS NULL
+            OR (c.business_days @> '{1,2,3,4,5,6,7}' AND c.business_opens_at_minute = 0
+                AND c.business_closes_at_minute = 1440 AND cardinality(c.business_holidays) = 0)
+            THEN EXTRACT(EPOCH FROM p_end - p_start)::BIGINT
+        ELSE support_business_seconds(
+            p_start, p_end, c.business_timezone, c.business_days,
+            c.business_opens_at_minute, c.business_closes_at_minute, c.business_holidays
+        )
+    END
+    FROM (SELECT p_product AS product) p
+    LEFT JOIN product_configs c ON c.product = p.product
+$$ LANGUAGE SQL STABLE;
+
+-- ============================================================================
+-- support_product_business_deadline
+-- ============================================================================
+-- The moment p_seconds of the product's business time have passed since
+-- p_start; NULL when p_seconds is NULL. Looks up to 400 days ahead.
+
+CREATE OR REPLACE FUNCTION support_product_business_deadline(
+    p_product TEXT,
+    p_start TIMESTAMPTZ,
+    p_seconds BIGINT
+) RETURNS TIMESTAMPTZ AS $$
+    SELECT CASE
+        WHEN p_seconds IS NULL THEN NULL
+        WHEN c.product IS NULL
+            OR (c.business_days @> '{1,2,3,4,5,6,7}' AND c.business_opens_at_minute = 0
+                AND c.business_closes_at_minute = 1440 AND cardinality(c.business_holidays) = 0)
+            THEN p_start + p_seconds * INTERVAL '1 second'
+        ELSE (
+            SELECT window_start + (p_seconds - (elapsed - window_seconds)) * INTERVAL '1 second'
+            FROM (
+                SELECT
+                    window_start,
+                    EXTRACT(EPOCH FROM window_end - window_start) AS window_seconds,
+                    SUM(EXTRACT(EPOCH FROM window_end - window_start)) OVER (ORDER BY window_start) AS elapsed
+                FROM (
+                    SELECT
+                        GREATEST(p_start, (day + make_interval(mins => c.business_opens_at_minute)) AT TIME ZONE c.business_timezone) AS window_start,
+                        (day + make_interval(mins => c.business_closes_at_minute)) AT TIME ZONE c.business_timezone AS window_end
+                    FROM generate_series(
+                        (p_start AT TIME ZONE c.business_timezone)::DATE::TIMESTAMP,
+                        (p_start AT TIME ZONE c.business_timezone)::DATE::TIMESTAMP + INTERVAL '400 days',
+                        INTERVAL '1 day'
+                    ) AS day
+                    WHERE EXTRACT(ISODOW FROM day)::INTEGER = ANY(c.business_days)
+                      AND NOT day::DATE = ANY(c.business_holidays)
+                ) windows
+                WHERE window_end > window_start
+            ) cumulative
+            WHERE elapsed >= p_seconds
+            ORDER BY window_start
+            LIMIT 1
+        )
+    END
+    FROM (SELECT p_product AS product) p
+    LEFT JOIN product_configs c ON c.product = p.product
+$$ LANGUAGE SQL STABLE;
@@ -0,0 +1,23 @@
+-- Migration 051: Resolution Codes
+-- Per-product taxonomy of how tickets were resolved, required when resolving once a product has one
+
+CREATE TABLE IF NOT EXISTS resolution_codes (
+    product VARCHAR(50) NOT NULL REFERENCES product_configs(product) ON DELETE CASCADE,
+    -- Uppercase letters, digits and underscores, e.g. PASSWORD_RESET
+    code VARCHAR(50) NOT NULL,
+    label TEXT NOT NULL,
+    description TEXT,
+    -- Help article documenting the fix, if one is written
+    article_id UUID REFERENCES knowledge_articles(id) ON DELETE SET NULL,
+    -- Retired codes stay on the tickets that used them but cannot be chosen
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (product, code)
+);
+
+ALTER TABLE support_tickets ADD COLUMN IF NOT EXISTS resolution_code VARCHAR(50);
+
+CREATE INDEX IF NOT EXISTS idx_support_tickets_resolution_code
+    ON support_tickets(product, resolution_code, resolved_at)
+    WHERE resolution_code IS NOT NULL;
@@ -0,0 +1,7 @@
+-- Migration 052: SLA Breach Digests
+-- Report schedules that attach a CSV of the period's missed SLA deadlines to each run
+
+ALTER TABLE report_schedules ADD COLUMN IF NOT EXISTS include_breach_digest BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- The digest CSV as generated, when the schedule included one
+ALTER TABLE report_runs ADD COLUMN IF NOT EXISTS breach_digest_csv TEXT;
@@ -0,0 +1,17 @@
+-- Migration 053: Message Mentions
+-- Content format of ticket messages, and the agents each message @mentions
+
+-- PLAINTEXT, MARKDOWN or HTML
+ALTER TABLE ticket_messages ADD COLUMN IF NOT EXISTS content_format TEXT NOT NULL DEFAULT 'PLAINTEXT';
+
+CREATE TABLE IF NOT EXISTS message_mentions (
+    -- The ticket is the message's, which merges may change
+    message_id UUID NOT NULL REFERENCES ticket_messages(id) ON DELETE CASCADE,
+    -- User id of the mentioned agent
+    agent_id UUID NOT NULL,
+    author_id UUID NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (message_id, agent_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_message_mentions_agent ON message_mentions(agent_id, created_at DESC);
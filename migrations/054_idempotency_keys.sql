@@ -0,0 +1,16 @@
+-- Migration 054: Idempotency Keys
+-- Client request keys that make ticket creation safe to retry
+
+CREATE TABLE IF NOT EXISTS ticket_idempotency_keys (
+    product VARCHAR(50) NOT NULL,
+    customer_id UUID NOT NULL,
+    idempotency_key VARCHAR(255) NOT NULL,
+    -- Deleting (purging) the ticket frees its key
+    ticket_id UUID NOT NULL REFERENCES support_tickets(id) ON DELETE CASCADE,
+    -- SHA-256 of the request the key was first used with
+    request_fingerprint TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (product, customer_id, idempotency_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_ticket_idempotency_keys_ticket ON ticket_idempotency_keys(ticket_id);
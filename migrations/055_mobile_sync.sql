@@ -0,0 +1,33 @@
+-- Migration 055: Mobile Sync
+-- Change tracking for offline clients: message moves, purge tombstones and pushed changes
+
+-- Set when a merge or its undo moves a message to another ticket
+ALTER TABLE ticket_messages ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_support_tickets_sync ON support_tickets(product, updated_at, id);
+CREATE INDEX IF NOT EXISTS idx_ticket_messages_sync ON ticket_messages((COALESCE(updated_at, created_at)), id);
+
+-- Purged tickets, so devices offline during a purge drop them too
+CREATE TABLE IF NOT EXISTS sync_tombstones (
+    ticket_id UUID PRIMARY KEY,
+    product VARCHAR(50) NOT NULL,
+    -- Assignee when purged, for agent-scoped pulls
+    assigned_to UUID,
+    removed_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_sync_tombstones_product ON sync_tombstones(product, removed_at, ticket_id);
+
+-- Results of changes pushed by devices, returned again when a change is re-pushed
+CREATE TABLE IF NOT EXISTS sync_pushed_changes (
+    change_id UUID PRIMARY KEY,
+    agent_id UUID NOT NULL,
+    -- No foreign key: results outlive purged tickets
+    ticket_id UUID NOT NULL,
+    -- APPLIED, CONFLICT or REJECTED
+    outcome TEXT NOT NULL,
+    conflicting_fields TEXT[] NOT NULL DEFAULT '{}',
+    error TEXT,
+    message_id UUID,
+    pushed_at TIMESTAMPTZ NOT NULL
+);
@@ -0,0 +1,7 @@
+-- Migration 056: Message Sentiment
+-- Sentiment scores on customer messages and the SENTIMENT_DROPPED automation trigger
+
+-- From -1 to 1; set on non-internal messages from the ticket's customer
+ALTER TABLE ticket_messages ADD COLUMN IF NOT EXISTS sentiment_score DOUBLE PRECISION;
+
+ALTER TYPE automation_trigger ADD VALUE IF NOT EXISTS 'SENTIMENT_DROPPED';
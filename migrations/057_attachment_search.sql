@@ -0,0 +1,65 @@
+-- Migration 057: Attachment Text Search
+-- Text extracted from attachments joins public message content in each ticket's search vector
+
+CREATE TABLE IF NOT EXISTS attachment_texts (
+    -- The storing service's attachment identifier
+    attachment_id UUID PRIMARY KEY,
+    ticket_id UUID NOT NULL REFERENCES support_tickets(id) ON DELETE CASCADE,
+    -- Attachments follow their message when a merge moves it
+    message_id UUID REFERENCES ticket_messages(id) ON DELETE CASCADE,
+    file_name TEXT,
+    content_type VARCHAR(255) NOT NULL,
+    extractor VARCHAR(100) NOT NULL,
+    content TEXT NOT NULL,
+    extracted_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_attachment_texts_ticket ON attachment_texts(ticket_id);
+CREATE INDEX IF NOT EXISTS idx_attachment_texts_message ON attachment_texts(message_id) WHERE message_id IS NOT NULL;
+
+-- ============================================================================
+-- SEARCH VECTOR
+-- ============================================================================
+
+-- Attachment text ranks below everything else. Attachments of internal notes
+-- are left out, like the notes.
+CREATE OR REPLACE FUNCTION support_ticket_search_vector(ticket_id UUID, subject TEXT, description TEXT, config TEXT)
+RETURNS TSVECTOR AS $$
+    SELECT setweight(support_search_vector(config, subject), 'A')
+        || setweight(support_search_vector(config, description), 'B')
+        || COALESCE((
+            SELECT setweight(support_search_vector(config, string_agg(m.content, ' ' ORDER BY m.created_at)), 'C')
+            FROM ticket_messages m
+            WHERE m.ticket_id = $1 AND m.is_internal = FALSE
+        ), ''::TSVECTOR)
+        || COALESCE((
+            SELECT setweight(support_search_vector(config, string_agg(a.content, ' ' ORDER BY a.extracted_at)), 'D')
+            FROM attachment_texts a
+            LEFT JOIN ticket_messages m ON m.id = a.message_id
+            WHERE (a.message_id IS NULL AND a.ticket_id = $1)
+               OR (m.ticket_id = $1 AND m.is_internal = FALSE)
+        ), ''::TSVECTOR);
+$$ LANGUAGE sql STABLE;
+
+-- ============================================================================
+-- Trigger: Keep ticket search vectors in step with attachment text
+-- ============================================================================
+
+CREATE OR REPLACE FUNCTION refresh_ticket_search_vector_from_attachments()
+RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE support_tickets
+    SET search_vector = support_ticket_search_vector(id, subject, description, search_config)
+    WHERE id IN (
+        SELECT COALESCE(m.ticket_id, a.ticket_id)
+        FROM (VALUES (NEW.ticket_id, NEW.message_id), (OLD.ticket_id, OLD.message_id)) AS a(ticket_id, message_id)
+        LEFT JOIN ticket_messages m ON m.id = a.message_id
+    );
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trigger_refresh_ticket_search_vector_on_attachment
+    AFTER INSERT OR UPDATE OR DELETE ON attachment_texts
+    FOR EACH ROW
+    EXECUTE FUNCTION refresh_ticket_search_vector_from_attachments();
@@ -0,0 +1,4 @@
+-- Migration 058: Saved View Sort Order
+-- How a saved view lists its tickets
+
+ALTER TABLE saved_views ADD COLUMN IF NOT EXISTS sort_order TEXT NOT NULL DEFAULT 'NEWEST_FIRST';
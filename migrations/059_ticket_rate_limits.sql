@@ -0,0 +1,5 @@
+-- Migration 059: Ticket Rate Limits
+-- Index for counting a customer's recent tickets per product, deleted ones included
+
+CREATE INDEX IF NOT EXISTS idx_support_tickets_customer_recent
+    ON support_tickets(customer_id, product, created_at DESC);
@@ -0,0 +1,10 @@
+-- Migration 060: Message Visibility
+-- PUBLIC, INTERNAL or RESTRICTED messages; is_internal stays set for every non-public message
+
+ALTER TABLE ticket_messages ADD COLUMN IF NOT EXISTS visibility TEXT NOT NULL DEFAULT 'PUBLIC';
+
+UPDATE ticket_messages SET visibility = 'INTERNAL' WHERE is_internal AND visibility = 'PUBLIC';
+
+ALTER TABLE ticket_messages DROP CONSTRAINT IF EXISTS ticket_messages_visibility_check;
+ALTER TABLE ticket_messages ADD CONSTRAINT ticket_messages_visibility_check
+    CHECK (visibility IN ('PUBLIC', 'INTERNAL', 'RESTRICTED') AND is_internal = (visibility <> 'PUBLIC'));
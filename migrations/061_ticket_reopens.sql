@@ -0,0 +1,4 @@
+-- Migration 061: Ticket Reopens
+-- How many times a resolved or closed ticket was reopened, for the reopen rate
+
+ALTER TABLE support_tickets ADD COLUMN IF NOT EXISTS reopen_count INTEGER NOT NULL DEFAULT 0;
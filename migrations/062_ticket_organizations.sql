@@ -0,0 +1,10 @@
+-- Migration 062: Ticket Organizations
+-- B2B account a ticket belongs to, for org-scoped lists, views and metrics
+
+ALTER TABLE support_tickets ADD COLUMN IF NOT EXISTS organization_id UUID;
+
+CREATE INDEX IF NOT EXISTS idx_support_tickets_organization
+    ON support_tickets(organization_id, product, created_at DESC)
+    WHERE organization_id IS NOT NULL AND deleted_at IS NULL;
+
+ALTER TABLE saved_views ADD COLUMN IF NOT EXISTS organization_id UUID;
@@ -0,0 +1,4 @@
+-- Migration 063: Product Categories
+-- Categories a product's tickets may be filed under; empty allows any
+
+ALTER TABLE product_configs ADD COLUMN IF NOT EXISTS categories TEXT[] NOT NULL DEFAULT '{}';
@@ -0,0 +1,28 @@
+-- Migration 064: Attachment Scans
+-- Attachments registered for virus scanning; only AVAILABLE ones are indexed or downloadable
+
+CREATE TYPE attachment_status AS ENUM (
+    'PENDING',
+    'AVAILABLE',
+    'QUARANTINED'
+);
+
+CREATE TABLE IF NOT EXISTS ticket_attachments (
+    -- The storing service's attachment identifier
+    attachment_id UUID PRIMARY KEY,
+    ticket_id UUID NOT NULL REFERENCES support_tickets(id) ON DELETE CASCADE,
+    -- Attachments follow their message when a merge moves it
+    message_id UUID REFERENCES ticket_messages(id) ON DELETE CASCADE,
+    file_name TEXT,
+    content_type VARCHAR(255) NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    status attachment_status NOT NULL DEFAULT 'PENDING',
+    -- Scanner that decided the status; NULL while pending or without a scanner
+    scanner VARCHAR(100),
+    threat TEXT,
+    scanned_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_ticket_attachments_ticket ON ticket_attachments(ticket_id);
+CREATE INDEX IF NOT EXISTS idx_ticket_attachments_message ON ticket_attachments(message_id) WHERE message_id IS NOT NULL;
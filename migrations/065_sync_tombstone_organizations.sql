@@ -0,0 +1,4 @@
+-- Migration 065: Sync Tombstone Organizations
+-- B2B account of purged tickets, for organization-scoped pulls
+
+ALTER TABLE sync_tombstones ADD COLUMN IF NOT EXISTS organization_id UUID;
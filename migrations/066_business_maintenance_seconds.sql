@@ -0,0 +1,26 @@
+-- Migration 066: Business-Hour Maintenance Seconds
+-- Maintenance credit for first-response clocks, which run on the business calendar
+
+-- ============================================================================
+-- support_product_business_maintenance_seconds
+-- ============================================================================
+-- Seconds between p_start and p_end covered by the product's maintenance
+-- windows and inside its business calendar; overlapping windows are counted
+-- once. Maintenance while the product is closed pauses nothing, since the
+-- first-response clock is not running then.
+
+CREATE OR REPLACE FUNCTION support_product_business_maintenance_seconds(
+    p_product TEXT,
+    p_start TIMESTAMPTZ,
+    p_end TIMESTAMPTZ
+) RETURNS BIGINT AS $$
+    SELECT COALESCE(SUM(support_product_business_seconds(p_product, lower(covered), upper(covered))), 0)::BIGINT
+    FROM unnest((
+        SELECT range_agg(tstzrange(starts_at, ends_at) * tstzrange(p_start, p_end))
+        FROM maintenance_windows
+        WHERE product = p_product
+          AND p_end > p_start
+          AND starts_at < p_end
+          AND ends_at > p_start
+    )) AS covered
+$$ LANGUAGE SQL STABLE;
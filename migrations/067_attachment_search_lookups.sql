@@ -0,0 +1,35 @@
+-- Migration 067: Attachment Search Lookups
+-- Attachment text for a ticket's search vector through two indexed lookups
+
+-- ============================================================================
+-- SEARCH VECTOR
+-- ============================================================================
+
+-- Same vector as migration 057. Ticket-level attachments are found through
+-- idx_attachment_texts_ticket and message attachments through
+-- idx_attachment_texts_message, instead of one OR'd join that scans the
+-- whole table.
+CREATE OR REPLACE FUNCTION support_ticket_search_vector(ticket_id UUID, subject TEXT, description TEXT, config TEXT)
+RETURNS TSVECTOR AS $$
+    SELECT setweight(support_search_vector(config, subject), 'A')
+        || setweight(support_search_vector(config, description), 'B')
+        || COALESCE((
+            SELECT setweight(support_search_vector(config, string_agg(m.content, ' ' ORDER BY m.created_at)), 'C')
+            FROM ticket_messages m
+            WHERE m.ticket_id = $1 AND m.is_internal = FALSE
+        ), ''::TSVECTOR)
+        || COALESCE((
+            SELECT setweight(support_search_vector(config, string_agg(a.content, ' ' ORDER BY a.extracted_at)), 'D')
+            FROM (
+                SELECT content, extracted_at
+                FROM attachment_texts
+                WHERE attachment_texts.ticket_id = $1 AND message_id IS NULL
+                UNION ALL
+                SELECT content, extracted_at
+                FROM attachment_texts
+                WHERE message_id IN (
+                    SELECT m.id FROM ticket_messages m WHERE m.ticket_id = $1 AND m.is_internal = FALSE
+                )
+            ) a
+        ), ''::TSVECTOR);
+$$ LANGUAGE sql STABLE;
@@ -0,0 +1,4 @@
+-- Migration 068: Dead Letter Retrying
+-- Letters claimed by a retry in progress, so two retries never redeliver the same event
+
+ALTER TYPE dead_letter_status ADD VALUE IF NOT EXISTS 'RETRYING';
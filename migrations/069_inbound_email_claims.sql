@@ -0,0 +1,6 @@
+-- Migration 069: Inbound Email Claims
+-- Ingestion claims an email's Message-ID before writing its ticket or reply,
+-- in the same transaction, so concurrent deliveries of one email write once
+
+-- Unset only inside the claiming transaction, until the ticket is written
+ALTER TABLE inbound_emails ALTER COLUMN ticket_id DROP NOT NULL;
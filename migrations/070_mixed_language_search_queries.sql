@@ -0,0 +1,28 @@
+-- Migration 070: Mixed-Language Search Queries
+-- Each query word matches on its own, in any configured language
+
+-- ============================================================================
+-- SEARCH QUERY
+-- ============================================================================
+
+-- Every word of the query becomes its Portuguese stem, its English stem or
+-- the word as typed, keeping the websearch operators (quoted phrases, OR,
+-- -exclusions) around it. A query mixing languages, like
+-- `reembolsos refunded`, then matches a ticket with one word stemmed in each;
+-- migration 036 only matched queries stemmed wholly in one language.
+CREATE OR REPLACE FUNCTION support_search_query(query TEXT)
+RETURNS TSQUERY AS $$
+DECLARE
+    result TSQUERY := websearch_to_tsquery('simple', query);
+    word TEXT;
+BEGIN
+    FOR word IN SELECT DISTINCT lexeme FROM unnest(to_tsvector('simple', query)) AS words(lexeme) LOOP
+        result := ts_rewrite(
+            result,
+            plainto_tsquery('simple', word),
+            plainto_tsquery('portuguese', word) || plainto_tsquery('english', word) || plainto_tsquery('simple', word)
+        );
+    END LOOP;
+    RETURN result;
+END;
+$$ LANGUAGE plpgsql STABLE;
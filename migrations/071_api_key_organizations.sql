@@ -0,0 +1,5 @@
+-- Migration 071: API Key Organizations
+-- B2B account an intake key files tickets for; tickets created with the key
+-- always take it, so a partner can't file into another tenant
+
+ALTER TABLE intake_api_keys ADD COLUMN IF NOT EXISTS organization_id UUID;
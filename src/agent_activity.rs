@@ -0,0 +1,210 @@
+//! Per-agent activity exports for performance reviews
+//!
+//! [`SupportRepository::export_agent_activity`](crate::SupportRepository::export_agent_activity)
+//! bundles what one agent did in a period into a single document: the
+//! tickets they handled (wrote on or resolved), every message they authored
+//! up to the caller's visibility (restricted notes only for callers cleared
+//! for them), and the CSAT ratings credited to them.
+//!
+//! QA reviews and time logs are not part of the export: this crate has no
+//! record of either, so a review pack needing them must merge them in from
+//! the system that keeps them.
+//!
+//! Reviews are often shared beyond the support team, so customer data can be
+//! anonymized:
+//!
+//! ```rust,no_run
+//! use chrono::{Duration, Utc};
+//! use pleme_support::{ActivityAnonymization, MessageVisibility, SupportRepository};
+//! use uuid::Uuid;
+//!
+//! # async fn example(repo: SupportRepository, agent_id: Uuid) -> pleme_support::Result<()> {
+//! let anonymization = ActivityAnonymization { pseudonymize_customers: true, redact_content: true };
+//! let export = repo
+//!     .export_agent_activity(
+//!         "novaskyn",
+//!         agent_id,
+//!         Utc::now() - Duration::days(90),
+//!         Utc::now(),
+//!         MessageVisibility::Internal,
+//!         anonymization,
+//!     )
+//!     .await?;
+//! println!("{}", serde_json::to_string_pretty(&export).unwrap());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Pseudonymized customers appear as "Customer 1", "Customer 2", ... in order
+//! of first appearance; aliases are stable within one export, so repeat
+//! customers are still visible, but cannot be matched across exports.
+
+use async_graphql::{InputObject, SimpleObject};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::csat::CsatChannel;
+use crate::models::{TicketChannel, TicketPriority, TicketStatus};
+
+/// How customer data appears in an agent activity export
+#[derive(Debug, Clone, Copy, Default, InputObject)]
+pub struct ActivityAnonymization {
+    /// Replace customer ids with aliases stable within the export
+    #[graphql(default)]
+    pub pseudonymize_customers: bool,
+    /// Leave out ticket subjects, message bodies and CSAT comments
+    #[graphql(default)]
+    pub redact_content: bool,
+}
+
+/// Everything the crate records about an agent in a period
+///
+/// QA reviews and time logs are not included; the crate records neither.
+#[derive(Debug, Clone, Serialize, SimpleObject)]
+pub struct AgentActivityExport {
+    pub product: String,
+    pub agent_id: Uuid,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub generated_at: DateTime<Utc>,
+    pub customers_pseudonymized: bool,
+    pub content_redacted: bool,
+    pub summary: AgentActivitySummary,
+    /// Oldest first
+    pub tickets: Vec<HandledTicket>,
+    /// Oldest first
+    pub messages: Vec<AuthoredMessage>,
+    /// Oldest response first
+    pub csat: Vec<ReceivedCsat>,
+}
+
+#[derive(Debug, Clone, Serialize, SimpleObject)]
+pub struct AgentActivitySummary {
+    pub tickets_handled: i64,
+    /// Tickets the agent resolved in the period
+    pub tickets_resolved: i64,
+    pub public_replies: i64,
+    pub internal_notes: i64,
+    /// Handled tickets that breached SLA
+    pub sla_breaches: i64,
+    pub csat_responses: i64,
+    pub avg_csat_score: Option<f64>,
+}
+
+/// A ticket the agent wrote on or resolved in the period
+#[derive(Debug, Clone, Serialize, FromRow, SimpleObject)]
+pub struct HandledTicket {
+    pub ticket_id: Uuid,
+    pub ticket_ref: String,
+    /// `None` when customers are pseudonymized
+    pub customer_id: Option<Uuid>,
+    /// Set when customers are pseudonymized
+    #[sqlx(default)]
+    pub customer_alias: Option<String>,
+    /// `None` when content is redacted
+    pub subject: Option<String>,
+    pub status: TicketStatus,
+    pub priority: TicketPriority,
+    pub channel: TicketChannel,
+    pub created_at: DateTime<Utc>,
+    pub first_response_at: Option<DateTime<Utc>>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    /// The agent is the ticket's resolving agent, resolved in the period
+    pub resolved_by_agent: bool,
+    pub sla_breach: bool,
+    /// Messages the agent wrote on the ticket in the period
+    pub messages_authored: i64,
+}
+
+/// A reply or internal note the agent wrote in the period
+#[derive(Debug, Clone, Serialize, FromRow, SimpleObject)]
+pub struct AuthoredMessage {
+    pub message_id: Uuid,
+    pub ticket_id: Uuid,
+    pub is_internal: bool,
+    /// `None` when content is redacted
+    pub content: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A CSAT response credited to the agent
+#[derive(Debug, Clone, Serialize, FromRow, SimpleObject)]
+pub struct ReceivedCsat {
+    pub survey_id: Uuid,
+    pub ticket_id: Uuid,
+    /// `None` when customers are pseudonymized
+    pub customer_id: Option<Uuid>,
+    /// Set when customers are pseudonymized
+    #[sqlx(default)]
+    pub customer_alias: Option<String>,
+    pub score: i32,
+    pub nps_score: Option<i32>,
+    /// `None` when content is redacted
+    pub comment: Option<String>,
+    pub channel: CsatChannel,
+    /// External survey tool of an imported response
+    pub source: Option<String>,
+    pub responded_at: DateTime<Utc>,
+}
+
+impl AgentActivitySummary {
+    pub(crate) fn of(tickets: &[HandledTicket], messages: &[AuthoredMessage], csat: &[ReceivedCsat]) -> Self {
+        let internal_notes = messages.iter().filter(|m| m.is_internal).count() as i64;
+        let avg_csat_score = (!csat.is_empty())
+            .then(|| csat.iter().map(|c| c.score as f64).sum::<f64>() / csat.len() as f64);
+
+        Self {
+            tickets_handled: tickets.len() as i64,
+            tickets_resolved: tickets.iter().filter(|t| t.resolved_by_agent).count() as i64,
+            public_replies: messages.len() as i64 - internal_notes,
+            internal_notes,
+            sla_breaches: tickets.iter().filter(|t| t.sla_breach).count() as i64,
+            csat_responses: csat.len() as i64,
+            avg_csat_score,
+        }
+    }
+}
+
+impl AgentActivityExport {
+    /// Pseudonymize customers and/or redact content in place
+    ///
+    /// Already anonymized parts are left as they are.
+    pub fn anonymize(&mut self, anonymization: ActivityAnonymization) {
+        if anonymization.pseudonymize_customers && !self.customers_pseudonymized {
+            let mut aliases = CustomerAliases::default();
+            for ticket in &mut self.tickets {
+                ticket.customer_alias = ticket.customer_id.take().map(|id| aliases.alias(id));
+            }
+            for response in &mut self.csat {
+                response.customer_alias = response.customer_id.take().map(|id| aliases.alias(id));
+            }
+            self.customers_pseudonymized = true;
+        }
+
+        if anonymization.redact_content {
+            self.tickets.iter_mut().for_each(|t| t.subject = None);
+            self.messages.iter_mut().for_each(|m| m.content = None);
+            self.csat.iter_mut().for_each(|c| c.comment = None);
+            self.content_redacted = true;
+        }
+    }
+}
+
+/// "Customer N" aliases, numbered by first appearance
+#[derive(Default)]
+struct CustomerAliases {
+    aliases: HashMap<Uuid, String>,
+}
+
+impl CustomerAliases {
+    fn alias(&mut self, customer_id: Uuid) -> String {
+        let next = self.aliases.len() + 1;
+        self.aliases
+            .entry(customer_id)
+            .or_insert_with(|| format!("Customer {}", next))
+            .clone()
+    }
+}
@@ -0,0 +1,141 @@
+//! Alerting on systemic SLA degradation
+//!
+//! Individual breaches are expected; managers want to be paged when the
+//! breach rate or compliance moves past a line. Thresholds are configured per
+//! product and evaluated by the metrics job
+//! (`SupportRepository::evaluate_alert_thresholds`, or
+//! `pleme-support-cli maintenance evaluate-alerts`). Every crossed threshold
+//! produces a [`MetricThresholdCrossed`] event, published to the repository's
+//! [`AlertSink`]. Events repeat on each evaluation while the threshold stays
+//! crossed; deduplicating pages is up to the sink.
+//!
+//! ```rust
+//! use chrono::Duration;
+//! use pleme_support::{AlertMetric, AlertThreshold, AlertThresholds};
+//!
+//! let thresholds = AlertThresholds::default()
+//!     .with_threshold("novaskyn", AlertThreshold::above(AlertMetric::SlaBreaches, 10.0, Duration::hours(1)))
+//!     .with_threshold("novaskyn", AlertThreshold::below(AlertMetric::SlaComplianceRate, 90.0, Duration::days(1)));
+//!
+//! let breaches = &thresholds.thresholds_for("novaskyn")[0];
+//! assert!(breaches.is_crossed(11.0));
+//! assert!(!breaches.is_crossed(10.0));
+//! assert!(thresholds.thresholds_for("thai").is_empty());
+//! ```
+
+use async_graphql::Enum;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::Result;
+
+/// Metric computed over an alert window ending now
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AlertMetric {
+    /// Tickets flagged as SLA breached during the window
+    SlaBreaches,
+    /// Percentage of tickets created in the window not breaching SLA
+    SlaComplianceRate,
+    /// Tickets created in the window
+    NewTickets,
+    /// Average minutes to first response, for tickets first answered in the window
+    AvgFirstResponseMinutes,
+    /// Pending dead letters recorded during the window
+    DeadLetters,
+    /// Age in minutes of the oldest pending dead letter, whatever the window
+    OldestDeadLetterMinutes,
+}
+
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ThresholdDirection {
+    /// Crossed when the metric exceeds the value
+    Above,
+    /// Crossed when the metric falls short of the value
+    Below,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertThreshold {
+    pub metric: AlertMetric,
+    pub direction: ThresholdDirection,
+    pub value: f64,
+    /// How far back from now the metric is computed
+    pub window: Duration,
+}
+
+impl AlertThreshold {
+    pub fn above(metric: AlertMetric, value: f64, window: Duration) -> Self {
+        Self { metric, direction: ThresholdDirection::Above, value, window }
+    }
+
+    pub fn below(metric: AlertMetric, value: f64, window: Duration) -> Self {
+        Self { metric, direction: ThresholdDirection::Below, value, window }
+    }
+
+    pub fn is_crossed(&self, current_value: f64) -> bool {
+        match self.direction {
+            ThresholdDirection::Above => current_value > self.value,
+            ThresholdDirection::Below => current_value < self.value,
+        }
+    }
+}
+
+/// Per-product alert thresholds
+#[derive(Debug, Clone, Default)]
+pub struct AlertThresholds {
+    thresholds: HashMap<String, Vec<AlertThreshold>>,
+}
+
+impl AlertThresholds {
+    pub fn with_threshold(mut self, product: impl Into<String>, threshold: AlertThreshold) -> Self {
+        self.thresholds.entry(product.into()).or_default().push(threshold);
+        self
+    }
+
+    pub fn thresholds_for(&self, product: &str) -> &[AlertThreshold] {
+        self.thresholds.get(product).map(Vec::as_slice).unwrap_or_default()
+    }
+}
+
+/// A product metric past its alert threshold
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricThresholdCrossed {
+    pub product: String,
+    pub metric: AlertMetric,
+    pub direction: ThresholdDirection,
+    pub threshold: f64,
+    pub current_value: f64,
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+}
+
+/// Destination for alert events, e.g. a pager or message queue
+#[async_trait]
+pub trait AlertSink: Send + Sync {
+    async fn publish(&self, event: &MetricThresholdCrossed) -> Result<()>;
+}
+
+/// Logs alert events as warnings
+#[derive(Debug, Clone, Default)]
+pub struct LogAlertSink;
+
+#[async_trait]
+impl AlertSink for LogAlertSink {
+    async fn publish(&self, event: &MetricThresholdCrossed) -> Result<()> {
+        tracing::warn!(
+            "{} {:?} is {} (threshold {:?} {}) between {} and {}",
+            event.product,
+            event.metric,
+            event.current_value,
+            event.direction,
+            event.threshold,
+            event.window_start,
+            event.window_end,
+        );
+        Ok(())
+    }
+}
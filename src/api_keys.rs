@@ -0,0 +1,117 @@
+//! API keys for partner ticket intake
+//!
+//! Partners submitting tickets from their own systems (webhooks, REST
+//! integrations) authenticate with an [`IntakeApiKey`] instead of platform
+//! auth. A key belongs to one product and grants a set of [`ApiKeyScope`]s,
+//! e.g. only `CREATE_TICKETS`. A key bound to a B2B organization files every
+//! ticket for it, and an unbound key files tickets for none; callers can't
+//! pick the organization themselves. The secret is shown once at creation; only its
+//! SHA-256 is stored, alongside a short non-secret prefix for listings.
+//!
+//! Services' intake handlers call `authenticate_api_key` with the scope the
+//! operation needs (or `create_ticket_with_api_key` for intake), which rejects
+//! unknown, revoked or under-scoped keys with `Unauthorized` and meters the
+//! request: `last_used_at`, `usage_count` and per-day [`ApiKeyUsage`].
+//!
+//! ```rust
+//! use pleme_support::api_keys::{api_key_prefix, hash_api_key, new_api_key, API_KEY_PREFIX};
+//!
+//! let key = new_api_key();
+//! assert!(key.starts_with(API_KEY_PREFIX));
+//! assert_eq!(api_key_prefix(&key).len(), API_KEY_PREFIX.len() + 8);
+//! assert_eq!(hash_api_key(&key), hash_api_key(&format!(" {} ", key)));
+//! assert_ne!(hash_api_key(&key), key);
+//! ```
+
+use async_graphql::{Enum, InputObject, SimpleObject};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Leading marker of every intake API key, so leaked keys are recognizable
+pub const API_KEY_PREFIX: &str = "psk_";
+
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "api_key_scope", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ApiKeyScope {
+    /// Submit new tickets
+    CreateTickets,
+    /// Read tickets of the key's product
+    ReadTickets,
+    /// Add messages to existing tickets
+    AddMessages,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, SimpleObject)]
+pub struct IntakeApiKey {
+    pub id: Uuid,
+    pub product: String,
+    pub name: String,
+    /// Non-secret leading part of the key, e.g. `psk_1a2b3c4d`
+    pub key_prefix: String,
+    #[graphql(skip)]
+    #[serde(skip)]
+    pub key_hash: String,
+    pub scopes: Vec<ApiKeyScope>,
+    /// B2B account every ticket created with the key belongs to
+    pub organization_id: Option<Uuid>,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    /// Authenticated requests made with the key
+    pub usage_count: i64,
+}
+
+impl IntakeApiKey {
+    pub fn is_active(&self) -> bool {
+        self.revoked_at.is_none()
+    }
+
+    pub fn has_scope(&self, scope: ApiKeyScope) -> bool {
+        self.scopes.contains(&scope)
+    }
+}
+
+/// A newly created key with its secret, which is not retrievable later
+#[derive(Debug, Clone, SimpleObject)]
+pub struct CreatedApiKey {
+    pub api_key: IntakeApiKey,
+    pub secret: String,
+}
+
+#[derive(Debug, Clone, InputObject)]
+pub struct CreateApiKeyInput {
+    pub name: String,
+    pub scopes: Vec<ApiKeyScope>,
+    /// Bind the key to one B2B account's tickets
+    pub organization_id: Option<Uuid>,
+}
+
+/// Requests made with a key on one UTC day under one scope
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, SimpleObject)]
+pub struct ApiKeyUsage {
+    pub key_id: Uuid,
+    pub day: NaiveDate,
+    pub scope: ApiKeyScope,
+    pub request_count: i64,
+}
+
+/// Random API key: [`API_KEY_PREFIX`] followed by 64 hex characters
+pub fn new_api_key() -> String {
+    format!("{}{}{}", API_KEY_PREFIX, Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// SHA-256 of a key, hex encoded, as stored in `intake_api_keys.key_hash`
+pub fn hash_api_key(key: &str) -> String {
+    format!("{:x}", Sha256::digest(key.trim().as_bytes()))
+}
+
+/// The key's non-secret prefix: the marker and the first 8 characters after it
+pub fn api_key_prefix(key: &str) -> &str {
+    let key = key.trim();
+    let end = (API_KEY_PREFIX.len() + 8).min(key.len());
+    key.get(..end).unwrap_or(key)
+}
@@ -0,0 +1,139 @@
+//! Assignment suggestions
+//!
+//! Ranks a product's active agents for a ticket and explains each ranking, so
+//! the manual-assignment UI can show why an agent is suggested.
+
+use async_graphql::{Enum, SimpleObject};
+use chrono::{DateTime, Duration, Timelike, Utc};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::models::{SupportAgent, SupportTicket};
+
+const SKILL_MATCH_WEIGHT: f64 = 3.0;
+const WORKLOAD_WEIGHT: f64 = 2.0;
+const WORKING_HOURS_WEIGHT: f64 = 1.5;
+const CUSTOMER_HISTORY_WEIGHT: f64 = 0.5;
+const CUSTOMER_HISTORY_CAP: i64 = 4;
+
+/// Local working hours used for the timezone signal, `[start, end)`
+const WORKING_HOURS: (u32, u32) = (9, 17);
+
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq)]
+pub enum AssignmentReasonKind {
+    SkillMatch,
+    LowWorkload,
+    WorkingHours,
+    PastCustomerTickets,
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct AssignmentReason {
+    pub kind: AssignmentReasonKind,
+    pub detail: String,
+    /// Contribution of this reason to the candidate's score
+    pub weight: f64,
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct AssigneeSuggestion {
+    pub agent_id: Uuid,
+    pub agent_name: String,
+    pub score: f64,
+    pub open_tickets: i64,
+    /// Open tickets weighted by priority
+    pub weighted_workload: i64,
+    pub reasons: Vec<AssignmentReason>,
+}
+
+/// An agent plus the per-ticket statistics used for ranking
+#[derive(Debug, Clone, FromRow)]
+pub struct AgentCandidate {
+    #[sqlx(flatten)]
+    pub agent: SupportAgent,
+    /// Open tickets currently assigned to the agent
+    pub open_tickets: i64,
+    /// Open tickets weighted by priority
+    pub weighted_workload: i64,
+    /// Other tickets from the same customer the agent has handled
+    pub customer_tickets: i64,
+}
+
+/// Score and order candidates for `ticket`, best first
+pub fn rank_candidates(
+    ticket: &SupportTicket,
+    candidates: Vec<AgentCandidate>,
+    now: DateTime<Utc>,
+) -> Vec<AssigneeSuggestion> {
+    let max_load = candidates.iter().map(|c| c.weighted_workload).max().unwrap_or(0);
+    let min_load = candidates.iter().map(|c| c.weighted_workload).min().unwrap_or(0);
+
+    let mut suggestions: Vec<AssigneeSuggestion> = candidates
+        .into_iter()
+        .map(|candidate| {
+            let mut reasons = Vec::new();
+
+            if let Some(category) = &ticket.category {
+                if candidate.agent.skills.iter().any(|s| s.eq_ignore_ascii_case(category)) {
+                    reasons.push(AssignmentReason {
+                        kind: AssignmentReasonKind::SkillMatch,
+                        detail: format!("Has the '{}' skill", category),
+                        weight: SKILL_MATCH_WEIGHT,
+                    });
+                }
+            }
+
+            let spread = (max_load - min_load) as f64;
+            let workload_weight = if spread == 0.0 {
+                WORKLOAD_WEIGHT
+            } else {
+                WORKLOAD_WEIGHT * (max_load - candidate.weighted_workload) as f64 / spread
+            };
+            if workload_weight > 0.0 {
+                let label = if candidate.weighted_workload == min_load { "Lowest" } else { "Lighter" };
+                reasons.push(AssignmentReason {
+                    kind: AssignmentReasonKind::LowWorkload,
+                    detail: format!(
+                        "{} workload ({} weighted, {} open tickets)",
+                        label, candidate.weighted_workload, candidate.open_tickets
+                    ),
+                    weight: workload_weight,
+                });
+            }
+
+            let local = now + Duration::minutes(candidate.agent.utc_offset_minutes as i64);
+            if (WORKING_HOURS.0..WORKING_HOURS.1).contains(&local.hour()) {
+                reasons.push(AssignmentReason {
+                    kind: AssignmentReasonKind::WorkingHours,
+                    detail: format!("Within working hours (local time {:02}:{:02})", local.hour(), local.minute()),
+                    weight: WORKING_HOURS_WEIGHT,
+                });
+            }
+
+            if candidate.customer_tickets > 0 {
+                reasons.push(AssignmentReason {
+                    kind: AssignmentReasonKind::PastCustomerTickets,
+                    detail: format!("Handled {} previous tickets from this customer", candidate.customer_tickets),
+                    weight: CUSTOMER_HISTORY_WEIGHT * candidate.customer_tickets.min(CUSTOMER_HISTORY_CAP) as f64,
+                });
+            }
+
+            AssigneeSuggestion {
+                agent_id: candidate.agent.user_id,
+                agent_name: candidate.agent.display_name,
+                score: reasons.iter().map(|r| r.weight).sum(),
+                open_tickets: candidate.open_tickets,
+                weighted_workload: candidate.weighted_workload,
+                reasons,
+            }
+        })
+        .collect();
+
+    suggestions.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(a.weighted_workload.cmp(&b.weighted_workload))
+            .then(a.open_tickets.cmp(&b.open_tickets))
+    });
+    suggestions
+}
@@ -0,0 +1,214 @@
+//! Attachment text for ticket search
+//!
+//! Attachments live in the host service's storage; after storing one, it
+//! passes the bytes to `index_attachment_text`, which runs the registered
+//! [`TextExtractor`] for the content type and keeps the text. Ticket search
+//! then matches it alongside subjects, descriptions and messages, so the
+//! ticket with the invoice PDF mentioning order 1042 turns up for "1042".
+//!
+//! Only plain text formats are extracted out of the box. PDF, Office and
+//! image OCR extractors are plugged in by the host with
+//! [`TextExtractors::with_extractor`], wrapping whatever library or service
+//! it uses; attachments of other types are skipped.
+//!
+//! ```rust
+//! use pleme_support::attachments::{normalize_content_type, TextExtractors};
+//!
+//! assert_eq!(normalize_content_type("Text/Plain; charset=utf-8"), "text/plain");
+//!
+//! let extractors = TextExtractors::default();
+//! assert!(extractors.for_content_type("text/csv").is_some());
+//! assert!(extractors.for_content_type("application/pdf").is_none());
+//! ```
+//!
+//! Attachments on internal messages are kept out of search, like the
+//! messages themselves. Text is stored with whitespace collapsed, cut at
+//! [`MAX_ATTACHMENT_TEXT_LENGTH`] characters.
+//!
+//! # Virus scanning
+//!
+//! Hosts that scan uploads register each attachment with
+//! `register_attachment` instead of indexing it directly. It is recorded as a
+//! [`TicketAttachment`] in `PENDING` status, handed to the repository's
+//! [`ScannerHook`] (ClamAV, a cloud scanner, ...), and only then marked
+//! `AVAILABLE` and indexed, or `QUARANTINED` with the threat found. Registered
+//! attachments that are not available are never indexed, and
+//! `record_attachment_access` refuses their downloads. A scanner error leaves
+//! the attachment pending; registering it again rescans it. Without a
+//! scanner, registered attachments become available straight away.
+
+use async_graphql::{Enum, SimpleObject};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::Result;
+
+/// Characters of an attachment's text kept for search
+pub const MAX_ATTACHMENT_TEXT_LENGTH: usize = 100_000;
+
+#[async_trait]
+pub trait TextExtractor: Send + Sync {
+    /// Recorded with the extracted text, e.g. a library name and version
+    fn name(&self) -> &str;
+
+    /// Whether the extractor reads `content_type`, given lowercase without parameters
+    fn supports(&self, content_type: &str) -> bool;
+
+    async fn extract(&self, content_type: &str, content: &[u8]) -> Result<String>;
+}
+
+/// Reads `text/*`, JSON and XML attachments as UTF-8, replacing invalid bytes
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlainTextExtractor;
+
+#[async_trait]
+impl TextExtractor for PlainTextExtractor {
+    fn name(&self) -> &str {
+        "plain-text"
+    }
+
+    fn supports(&self, content_type: &str) -> bool {
+        content_type.starts_with("text/") || matches!(content_type, "application/json" | "application/xml")
+    }
+
+    async fn extract(&self, _content_type: &str, content: &[u8]) -> Result<String> {
+        Ok(String::from_utf8_lossy(content).into_owned())
+    }
+}
+
+/// The extractors attachments are read with
+///
+/// Starts with [`PlainTextExtractor`]; for each content type, the most
+/// recently added extractor supporting it is used.
+#[derive(Clone)]
+pub struct TextExtractors {
+    extractors: Vec<Arc<dyn TextExtractor>>,
+}
+
+impl Default for TextExtractors {
+    fn default() -> Self {
+        Self { extractors: vec![Arc::new(PlainTextExtractor)] }
+    }
+}
+
+impl TextExtractors {
+    pub fn with_extractor(mut self, extractor: Arc<dyn TextExtractor>) -> Self {
+        self.extractors.push(extractor);
+        self
+    }
+
+    pub fn for_content_type(&self, content_type: &str) -> Option<&Arc<dyn TextExtractor>> {
+        let content_type = normalize_content_type(content_type);
+        self.extractors.iter().rev().find(|extractor| extractor.supports(&content_type))
+    }
+}
+
+/// An attachment stored by the host service, to scan or index
+#[derive(Debug, Clone)]
+pub struct IndexAttachmentInput {
+    pub ticket_id: Uuid,
+    /// The message the attachment came with, if any
+    pub message_id: Option<Uuid>,
+    /// The storing service's attachment identifier
+    pub attachment_id: Uuid,
+    pub file_name: Option<String>,
+    pub content_type: String,
+}
+
+/// Whether an attachment may be served
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "attachment_status", rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AttachmentStatus {
+    /// Registered, not scanned yet
+    Pending,
+    Available,
+    /// The scanner found a threat; not indexed or downloadable
+    Quarantined,
+}
+
+impl AttachmentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AttachmentStatus::Pending => "PENDING",
+            AttachmentStatus::Available => "AVAILABLE",
+            AttachmentStatus::Quarantined => "QUARANTINED",
+        }
+    }
+}
+
+/// What a [`ScannerHook`] found in an attachment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanVerdict {
+    Clean,
+    /// `threat` names what was found, e.g. `Eicar-Test-Signature`
+    Infected { threat: String },
+}
+
+/// Scans an attachment before it is marked available
+///
+/// Errors leave the attachment pending, so a scanner outage fails closed.
+#[async_trait]
+pub trait ScannerHook: Send + Sync {
+    /// Recorded with each verdict, e.g. `clamav-1.3`
+    fn name(&self) -> &str;
+
+    async fn scan(&self, attachment: &IndexAttachmentInput, content: &[u8]) -> Result<ScanVerdict>;
+}
+
+/// An attachment registered for scanning
+#[derive(Debug, Clone, Serialize, FromRow, SimpleObject)]
+pub struct TicketAttachment {
+    /// The storing service's attachment identifier
+    pub attachment_id: Uuid,
+    pub ticket_id: Uuid,
+    pub message_id: Option<Uuid>,
+    pub file_name: Option<String>,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub status: AttachmentStatus,
+    /// Scanner that decided the status; absent while pending or without a scanner
+    pub scanner: Option<String>,
+    /// What the scanner found in a quarantined attachment
+    pub threat: Option<String>,
+    pub scanned_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Text extracted from an attachment
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct AttachmentText {
+    pub attachment_id: Uuid,
+    pub ticket_id: Uuid,
+    pub message_id: Option<Uuid>,
+    pub file_name: Option<String>,
+    pub content_type: String,
+    pub extractor: String,
+    pub content: String,
+    pub extracted_at: DateTime<Utc>,
+}
+
+/// Lowercase media type without parameters
+pub fn normalize_content_type(content_type: &str) -> String {
+    content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase()
+}
+
+/// Collapse whitespace and cut to [`MAX_ATTACHMENT_TEXT_LENGTH`] characters
+pub(crate) fn searchable_text(text: &str) -> String {
+    let mut collapsed = String::new();
+    // Postgres text can't hold NUL bytes
+    for word in text.split(|c: char| c.is_whitespace() || c == '\0').filter(|w| !w.is_empty()) {
+        if !collapsed.is_empty() {
+            collapsed.push(' ');
+        }
+        collapsed.push_str(word);
+    }
+    match collapsed.char_indices().nth(MAX_ATTACHMENT_TEXT_LENGTH) {
+        Some((end, _)) => collapsed[..end].to_string(),
+        None => collapsed,
+    }
+}
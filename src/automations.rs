@@ -0,0 +1,434 @@
+//! Workflow automations
+//!
+//! An [`AutomationRule`] is a trigger, conditions and actions: "when a
+//! ticket is created, if its plan is `enterprise`, tag it `vip` and set it to
+//! HIGH". It is the general layer above routing, escalation and auto-close:
+//!
+//! | Trigger | Fires |
+//! |---------|-------|
+//! | `TICKET_CREATED` | After a ticket is created (CSV imports excepted), including follow-ups |
+//! | `STATUS_CHANGED` | After an update, bulk update or customer reply changes the status |
+//! | `MESSAGE_ADDED` | After a public message is added; internal notes don't fire it |
+//! | `TIMER` | From `run_timer_automations`, on tickets not updated for `timer_minutes` |
+//! | `SENTIMENT_DROPPED` | After a run of negative customer messages (see [`sentiment`](crate::sentiment)) |
+//!
+//! A rule runs when all its [`AutomationCondition`]s hold (none means
+//! always), applying its [`AutomationAction`]s in order in one transaction:
+//! if any fails, none apply. Every run is recorded as an
+//! [`AutomationExecution`]. Rules for one trigger run in `position` order,
+//! each seeing the ticket as the previous ones left it.
+//!
+//! Actions can fire further triggers (a status change, an automated reply),
+//! which run after the rule commits. To stop loops, a rule never runs twice
+//! in one chain and chains stop after [`MAX_AUTOMATION_DEPTH`] rules; a run
+//! stopped either way is logged as `LOOP_PREVENTED`. Automations never fail
+//! the change that triggered them.
+//!
+//! ```rust
+//! use pleme_support::automations::{AutomationCondition, AutomationTrigger, ConditionField, ConditionOperator};
+//!
+//! let vip = AutomationCondition {
+//!     field: ConditionField::CustomerPlan,
+//!     operator: ConditionOperator::Equals,
+//!     value: Some("enterprise".to_string()),
+//! };
+//! assert!(vip.validate(AutomationTrigger::TicketCreated).is_ok());
+//!
+//! let reopened = AutomationCondition {
+//!     field: ConditionField::PreviousStatus,
+//!     operator: ConditionOperator::Equals,
+//!     value: Some("CLOSED".to_string()),
+//! };
+//! assert!(reopened.validate(AutomationTrigger::StatusChanged).is_ok());
+//! // Only status changes have a previous status
+//! assert!(reopened.validate(AutomationTrigger::Timer).is_err());
+//! ```
+
+use async_graphql::{Enum, InputObject, SimpleObject};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::models::{SupportTicket, TicketChannel, TicketPriority, TicketStatus};
+use crate::{Result, SupportError};
+
+/// Most rules in one chain of automations triggering each other
+pub const MAX_AUTOMATION_DEPTH: usize = 5;
+
+/// Most tickets one timer rule fires on per `run_timer_automations`
+pub const TIMER_BATCH_SIZE: i64 = 500;
+
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "automation_trigger", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AutomationTrigger {
+    TicketCreated,
+    StatusChanged,
+    MessageAdded,
+    Timer,
+    SentimentDropped,
+}
+
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ConditionField {
+    Status,
+    /// Status before the change; `STATUS_CHANGED` rules only
+    PreviousStatus,
+    Priority,
+    Category,
+    Channel,
+    CustomerPlan,
+    /// Compares against each of the ticket's tags
+    Tag,
+    AssignedTo,
+    /// `CUSTOMER`, `AGENT` or `SYSTEM`; `MESSAGE_ADDED` rules only
+    MessageAuthor,
+}
+
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ConditionOperator {
+    /// Case-insensitive; for tags, the ticket has the tag
+    Equals,
+    /// Case-insensitive; an unset field is not equal to any value
+    NotEquals,
+    /// For tags, the ticket has any tag
+    IsSet,
+    IsNotSet,
+}
+
+/// A field comparison that must hold for a rule to run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SimpleObject, InputObject)]
+#[graphql(input_name = "AutomationConditionInput")]
+pub struct AutomationCondition {
+    pub field: ConditionField,
+    pub operator: ConditionOperator,
+    /// Required by `EQUALS` and `NOT_EQUALS`
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ActionKind {
+    /// Value is a status, optionally followed by `:` and the resolution code
+    /// to resolve or close with, e.g. `RESOLVED:PASSWORD_RESET`; products with
+    /// resolution codes need one to resolve
+    SetStatus,
+    SetPriority,
+    /// Raises the priority one level; value is the highest priority to raise to
+    BoostPriority,
+    SetCategory,
+    AddTag,
+    RemoveTag,
+    /// Value is the agent's user id
+    Assign,
+    /// Value is a canned response id; sent as a public system reply
+    SendCannedReply,
+    /// Publishes `automation.triggered` with the value as its label
+    EmitWebhook,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SimpleObject, InputObject)]
+#[graphql(input_name = "AutomationActionInput")]
+pub struct AutomationAction {
+    pub kind: ActionKind,
+    pub value: String,
+}
+
+/// Who wrote the message that fired a `MESSAGE_ADDED` rule
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MessageAuthor {
+    Customer,
+    Agent,
+    /// Automated replies
+    System,
+}
+
+impl MessageAuthor {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MessageAuthor::Customer => "CUSTOMER",
+            MessageAuthor::Agent => "AGENT",
+            MessageAuthor::System => "SYSTEM",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "automation_outcome", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AutomationOutcome {
+    /// Every action applied
+    Applied,
+    /// An action failed and none applied
+    Failed,
+    /// Skipped to stop an automation loop
+    LoopPrevented,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, SimpleObject)]
+pub struct AutomationRule {
+    pub id: Uuid,
+    pub product: String,
+    pub name: String,
+    pub position: i32,
+    pub trigger: AutomationTrigger,
+    /// Idle minutes before a `TIMER` rule fires
+    pub timer_minutes: Option<i32>,
+    #[sqlx(json)]
+    pub conditions: Vec<AutomationCondition>,
+    #[sqlx(json)]
+    pub actions: Vec<AutomationAction>,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, InputObject)]
+pub struct CreateAutomationRuleInput {
+    pub name: String,
+    #[graphql(default)]
+    pub position: i32,
+    pub trigger: AutomationTrigger,
+    /// Required for `TIMER` rules, not allowed otherwise
+    pub timer_minutes: Option<i32>,
+    #[graphql(default)]
+    pub conditions: Vec<AutomationCondition>,
+    pub actions: Vec<AutomationAction>,
+}
+
+/// One run of a rule on a ticket
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, SimpleObject)]
+pub struct AutomationExecution {
+    pub id: Uuid,
+    pub rule_id: Uuid,
+    pub ticket_id: Uuid,
+    pub trigger: AutomationTrigger,
+    /// Automations that ran before this one in its chain
+    pub depth: i32,
+    pub outcome: AutomationOutcome,
+    pub actions_applied: i32,
+    pub error: Option<String>,
+    pub executed_at: DateTime<Utc>,
+}
+
+/// What a rule's conditions are checked against
+#[derive(Debug, Clone, Copy)]
+pub struct AutomationContext<'a> {
+    pub ticket: &'a SupportTicket,
+    /// Set for `STATUS_CHANGED`
+    pub previous_status: Option<TicketStatus>,
+    /// Set for `MESSAGE_ADDED`
+    pub message_author: Option<MessageAuthor>,
+}
+
+/// A trigger to fire on a ticket once the change behind it commits
+#[derive(Debug, Clone)]
+pub(crate) struct AutomationFiring {
+    pub trigger: AutomationTrigger,
+    pub ticket_id: Uuid,
+    pub previous_status: Option<TicketStatus>,
+    pub message_author: Option<MessageAuthor>,
+    /// Rules that already ran in this chain
+    pub chain: Vec<Uuid>,
+    /// Fire only this rule, for timers
+    pub rule_id: Option<Uuid>,
+}
+
+impl AutomationFiring {
+    pub fn new(trigger: AutomationTrigger, ticket_id: Uuid) -> Self {
+        Self { trigger, ticket_id, previous_status: None, message_author: None, chain: Vec::new(), rule_id: None }
+    }
+
+    pub fn status_changed(ticket_id: Uuid, previous_status: TicketStatus) -> Self {
+        Self { previous_status: Some(previous_status), ..Self::new(AutomationTrigger::StatusChanged, ticket_id) }
+    }
+
+    pub fn message_added(ticket_id: Uuid, author: MessageAuthor) -> Self {
+        Self { message_author: Some(author), ..Self::new(AutomationTrigger::MessageAdded, ticket_id) }
+    }
+}
+
+impl ConditionField {
+    /// Whether the field has a value for rules with this trigger
+    fn applies_to(&self, trigger: AutomationTrigger) -> bool {
+        match self {
+            ConditionField::PreviousStatus => trigger == AutomationTrigger::StatusChanged,
+            ConditionField::MessageAuthor => trigger == AutomationTrigger::MessageAdded,
+            _ => true,
+        }
+    }
+}
+
+impl AutomationCondition {
+    /// Check the condition can be evaluated for rules with this trigger
+    pub fn validate(&self, trigger: AutomationTrigger) -> Result<()> {
+        if !self.field.applies_to(trigger) {
+            return Err(SupportError::Validation(format!(
+                "{:?} conditions don't apply to {:?} rules", self.field, trigger
+            )));
+        }
+
+        let value = match self.operator {
+            ConditionOperator::Equals | ConditionOperator::NotEquals => match self.value.as_deref().map(str::trim) {
+                Some(value) if !value.is_empty() => value,
+                _ => return Err(SupportError::Validation(format!("{:?} conditions need a value", self.operator))),
+            },
+            ConditionOperator::IsSet | ConditionOperator::IsNotSet => return Ok(()),
+        };
+        let parsed = match self.field {
+            ConditionField::Status | ConditionField::PreviousStatus => value.parse::<TicketStatus>().map(|_| ()),
+            ConditionField::Priority => value.parse::<TicketPriority>().map(|_| ()),
+            ConditionField::Channel => value.parse::<TicketChannel>().map(|_| ()),
+            ConditionField::AssignedTo => value.parse::<Uuid>().map(|_| ()).map_err(|e| e.to_string()),
+            ConditionField::MessageAuthor => match value.to_ascii_uppercase().as_str() {
+                "CUSTOMER" | "AGENT" | "SYSTEM" => Ok(()),
+                other => Err(format!("unknown message author '{}'", other)),
+            },
+            ConditionField::Category | ConditionField::CustomerPlan | ConditionField::Tag => Ok(()),
+        };
+        parsed.map_err(|e| SupportError::Validation(format!("Invalid {:?} condition: {}", self.field, e)))
+    }
+
+    /// Whether the condition holds in `context`
+    pub fn matches(&self, context: &AutomationContext<'_>) -> bool {
+        let expected = self.value.as_deref().map(|v| v.trim().to_lowercase());
+        if self.field == ConditionField::Tag {
+            let tags = &context.ticket.tags;
+            return match self.operator {
+                ConditionOperator::Equals => expected.is_some_and(|tag| tags.contains(&tag)),
+                ConditionOperator::NotEquals => expected.is_some_and(|tag| !tags.contains(&tag)),
+                ConditionOperator::IsSet => !tags.is_empty(),
+                ConditionOperator::IsNotSet => tags.is_empty(),
+            };
+        }
+
+        let actual = self.field_value(context).map(|v| v.to_lowercase());
+        match self.operator {
+            ConditionOperator::Equals => actual.is_some() && actual == expected,
+            ConditionOperator::NotEquals => actual != expected,
+            ConditionOperator::IsSet => actual.is_some(),
+            ConditionOperator::IsNotSet => actual.is_none(),
+        }
+    }
+
+    fn field_value(&self, context: &AutomationContext<'_>) -> Option<String> {
+        let ticket = context.ticket;
+        match self.field {
+            ConditionField::Status => Some(ticket.status.as_str().to_string()),
+            ConditionField::PreviousStatus => context.previous_status.map(|s| s.as_str().to_string()),
+            ConditionField::Priority => Some(ticket.priority.as_str().to_string()),
+            ConditionField::Category => ticket.category.clone(),
+            ConditionField::Channel => Some(ticket.channel.as_str().to_string()),
+            ConditionField::CustomerPlan => ticket.customer_plan.clone(),
+            ConditionField::AssignedTo => ticket.assigned_to.map(|id| id.to_string()),
+            ConditionField::MessageAuthor => context.message_author.map(|a| a.as_str().to_string()),
+            ConditionField::Tag => None,
+        }
+    }
+
+    /// SQL over `support_tickets` equivalent to [`matches`](Self::matches),
+    /// with the value to bind as `$param`, if any; `None` for fields that
+    /// come from the trigger rather than the ticket
+    pub(crate) fn to_sql(&self, param: usize) -> Option<(String, Option<String>)> {
+        let column = match self.field {
+            ConditionField::Status => "status::TEXT",
+            ConditionField::Priority => "priority::TEXT",
+            ConditionField::Category => "category",
+            ConditionField::Channel => "channel::TEXT",
+            ConditionField::CustomerPlan => "customer_plan",
+            ConditionField::AssignedTo => "assigned_to::TEXT",
+            ConditionField::Tag => "tags",
+            ConditionField::PreviousStatus | ConditionField::MessageAuthor => return None,
+        };
+        let value = self.value.as_deref().map(|v| v.trim().to_lowercase());
+
+        let sql = match (self.field, self.operator) {
+            (ConditionField::Tag, ConditionOperator::Equals) => format!("${} = ANY(tags)", param),
+            (ConditionField::Tag, ConditionOperator::NotEquals) => format!("NOT (${} = ANY(tags))", param),
+            (ConditionField::Tag, ConditionOperator::IsSet) => "cardinality(tags) > 0".to_string(),
+            (ConditionField::Tag, ConditionOperator::IsNotSet) => "cardinality(tags) = 0".to_string(),
+            (_, ConditionOperator::Equals) => format!("LOWER({}) = ${}", column, param),
+            (_, ConditionOperator::NotEquals) => format!("LOWER({}) IS DISTINCT FROM ${}", column, param),
+            (_, ConditionOperator::IsSet) => format!("{} IS NOT NULL", column),
+            (_, ConditionOperator::IsNotSet) => format!("{} IS NULL", column),
+        };
+        let bind = matches!(self.operator, ConditionOperator::Equals | ConditionOperator::NotEquals).then_some(value).flatten();
+        Some((sql, bind))
+    }
+}
+
+impl AutomationAction {
+    /// Check the action's value; canned responses are checked when the rule is saved
+    pub fn validate(&self) -> Result<()> {
+        let value = self.value.trim();
+        let parsed = match self.kind {
+            ActionKind::SetStatus => Self::status_value(value).map(|_| ()),
+            ActionKind::SetPriority | ActionKind::BoostPriority => value.parse::<TicketPriority>().map(|_| ()),
+            ActionKind::Assign | ActionKind::SendCannedReply => value.parse::<Uuid>().map(|_| ()).map_err(|e| e.to_string()),
+            ActionKind::SetCategory | ActionKind::AddTag | ActionKind::RemoveTag | ActionKind::EmitWebhook => {
+                if value.is_empty() { Err("value is required".to_string()) } else { Ok(()) }
+            }
+        };
+        parsed.map_err(|e| SupportError::Validation(format!("Invalid {:?} action: {}", self.kind, e)))
+    }
+
+    /// The status and resolution code of a `SET_STATUS` value
+    ///
+    /// ```rust
+    /// use pleme_support::automations::AutomationAction;
+    /// use pleme_support::TicketStatus;
+    ///
+    /// assert_eq!(AutomationAction::status_value("RESOLVED:PASSWORD_RESET"), Ok((TicketStatus::Resolved, Some("PASSWORD_RESET"))));
+    /// assert_eq!(AutomationAction::status_value("CLOSED"), Ok((TicketStatus::Closed, None)));
+    /// assert!(AutomationAction::status_value("OPEN:PASSWORD_RESET").is_err());
+    /// ```
+    pub fn status_value(value: &str) -> std::result::Result<(TicketStatus, Option<&str>), String> {
+        let (status, code) = match value.split_once(':') {
+            Some((status, code)) => (status.trim(), Some(code.trim()).filter(|c| !c.is_empty())),
+            None => (value.trim(), None),
+        };
+        let status = status.parse::<TicketStatus>()?;
+        if code.is_some() && !matches!(status, TicketStatus::Resolved | TicketStatus::Closed) {
+            return Err("resolution codes can only be set with RESOLVED or CLOSED".to_string());
+        }
+        Ok((status, code))
+    }
+}
+
+impl AutomationRule {
+    /// Whether all the rule's conditions hold in `context`
+    pub fn matches(&self, context: &AutomationContext<'_>) -> bool {
+        self.conditions.iter().all(|condition| condition.matches(context))
+    }
+}
+
+impl CreateAutomationRuleInput {
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(SupportError::Validation("Automation rule name is required".to_string()));
+        }
+        match (self.trigger, self.timer_minutes) {
+            (AutomationTrigger::Timer, Some(minutes)) if minutes > 0 => {}
+            (AutomationTrigger::Timer, _) => {
+                return Err(SupportError::Validation("Timer rules need a positive timer_minutes".to_string()));
+            }
+            (_, Some(_)) => {
+                return Err(SupportError::Validation("Only timer rules take timer_minutes".to_string()));
+            }
+            (_, None) => {}
+        }
+        if self.actions.is_empty() {
+            return Err(SupportError::Validation("Automation rules need at least one action".to_string()));
+        }
+
+        for condition in &self.conditions {
+            condition.validate(self.trigger)?;
+        }
+        for action in &self.actions {
+            action.validate()?;
+        }
+        Ok(())
+    }
+}
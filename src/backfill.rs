@@ -0,0 +1,20 @@
+//! Batched backfills for existing data
+//!
+//! Installations that adopt a feature (SLA evaluation, full-text search) on
+//! top of years of tickets run these once from the operations CLI. Work is
+//! done in keyset-paginated batches of [`BACKFILL_BATCH_SIZE`] tickets, each
+//! in its own statement, so a long backfill never holds locks on the whole
+//! table and can be safely interrupted and re-run.
+
+/// Tickets processed per statement
+pub const BACKFILL_BATCH_SIZE: i64 = 1000;
+
+/// Running totals reported after every batch, and as the final result
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct BackfillProgress {
+    /// Tickets in scope when the backfill started
+    pub total: i64,
+    pub processed: i64,
+    /// Tickets whose stored value actually changed
+    pub updated: u64,
+}
@@ -0,0 +1,689 @@
+//! pleme-support-cli
+//!
+//! One-off operational tasks against a support database.
+//!
+//! ```bash
+//! DATABASE_URL=postgres://... pleme-support-cli migrate
+//! pleme-support-cli export --product novaskyn --output tickets.jsonl
+//! pleme-support-cli warehouse --product novaskyn --table fact-tickets --output fact_tickets.jsonl
+//! pleme-support-cli metrics-history --product novaskyn --from 2026-01-01T00:00:00Z --to 2026-07-01T00:00:00Z --interval month
+//! pleme-support-cli retention --older-than-days 90 --dry-run
+//! ```
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use clap::{Parser, Subcommand, ValueEnum};
+use futures_util::{pin_mut, TryStreamExt};
+use sqlx::postgres::PgPoolOptions;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use uuid::Uuid;
+
+use pleme_support::import::{self, CsvColumnMapping, ImportMode};
+use pleme_support::{
+    ActivityAnonymization, AlertMetric, AlertThreshold, AlertThresholds, BackfillProgress, CreateTicketInput, DimAgent, DimCategory, ExportFormat, FactMessage, FactTicket, IntakePrivacy,
+    MessageVisibility, MetricsInterval, ProductConfigInput, SlaPolicy, SupportRepository, TicketFilter, TicketPriority, WarehouseExtract, Watermark, MIGRATOR,
+};
+
+#[derive(Parser)]
+#[command(name = "pleme-support-cli", version, about = "Support ticket operations")]
+struct Cli {
+    /// PostgreSQL connection string
+    #[arg(long, env = "DATABASE_URL")]
+    database_url: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Apply pending database migrations
+    Migrate,
+
+    /// Routine maintenance tasks
+    #[command(subcommand)]
+    Maintenance(MaintenanceCommand),
+
+    /// Products tickets can be created for
+    #[command(subcommand)]
+    Products(ProductCommand),
+
+    /// Import tickets from JSON lines (CreateTicketInput records) or CSV
+    Import {
+        #[arg(long)]
+        product: String,
+        /// Input file, `-` for stdin
+        #[arg(long, default_value = "-")]
+        input: PathBuf,
+        #[arg(long, value_enum, default_value_t = ImportFormat::Jsonl)]
+        format: ImportFormat,
+        /// Keep valid CSV rows even when other rows fail
+        #[arg(long)]
+        partial: bool,
+    },
+
+    /// Export tickets as JSON lines or CSV
+    Export {
+        #[arg(long)]
+        product: String,
+        #[arg(long, value_enum, default_value_t = ExportFileFormat::Jsonl)]
+        format: ExportFileFormat,
+        /// Output file, `-` for stdout
+        #[arg(long, default_value = "-")]
+        output: PathBuf,
+    },
+
+    /// Write the SLA deadlines missed in a week as CSV, with summary rows
+    SlaBreachDigest {
+        #[arg(long)]
+        product: String,
+        /// Any day of the ISO week to report; defaults to last week
+        #[arg(long)]
+        week: Option<NaiveDate>,
+        /// Defaults to the product's timezone
+        #[arg(long)]
+        timezone: Option<String>,
+        /// Add a UTF-8 byte order mark and CRLF line ends for Excel
+        #[arg(long)]
+        excel: bool,
+        /// Output file, `-` for stdout
+        #[arg(long, default_value = "-")]
+        output: PathBuf,
+    },
+
+    /// Write the product's KPIs per day, week or month as CSV or JSON
+    MetricsHistory {
+        #[arg(long)]
+        product: String,
+        /// Range start, RFC 3339
+        #[arg(long)]
+        from: DateTime<Utc>,
+        /// Range end (exclusive), RFC 3339
+        #[arg(long)]
+        to: DateTime<Utc>,
+        #[arg(long, value_enum, default_value_t = HistoryInterval::Week)]
+        interval: HistoryInterval,
+        #[arg(long, value_enum, default_value_t = HistoryFileFormat::Csv)]
+        format: HistoryFileFormat,
+        /// Output file, `-` for stdout
+        #[arg(long, default_value = "-")]
+        output: PathBuf,
+    },
+
+    /// Extract a warehouse view incrementally as JSON lines
+    ///
+    /// Prints the watermark to pass as `--after` next time on stderr.
+    Warehouse {
+        #[arg(long)]
+        product: String,
+        #[arg(long, value_enum)]
+        table: WarehouseTable,
+        /// Watermark JSON printed by the previous run
+        #[arg(long)]
+        after: Option<String>,
+        /// Output file, `-` for stdout
+        #[arg(long, default_value = "-")]
+        output: PathBuf,
+    },
+
+    /// Export one agent's tickets, messages and CSAT for a period as a JSON document
+    /// (QA reviews and time logs are not included)
+    AgentActivity {
+        #[arg(long)]
+        product: String,
+        #[arg(long)]
+        agent_id: Uuid,
+        /// Period start, RFC 3339
+        #[arg(long)]
+        from: DateTime<Utc>,
+        /// Period end (exclusive), RFC 3339
+        #[arg(long)]
+        to: DateTime<Utc>,
+        /// Replace customer ids with per-export aliases
+        #[arg(long)]
+        pseudonymize_customers: bool,
+        /// Leave out ticket subjects, message bodies and CSAT comments
+        #[arg(long)]
+        redact_content: bool,
+        /// Output file, `-` for stdout
+        #[arg(long, default_value = "-")]
+        output: PathBuf,
+    },
+
+    /// Permanently purge soft-deleted tickets
+    Retention {
+        /// Purge tickets soft-deleted more than this many days ago
+        #[arg(long)]
+        older_than_days: i64,
+        /// Only purge this product's tickets
+        #[arg(long)]
+        product: Option<String>,
+        /// Only report how many tickets would be purged
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Create sample tickets for development environments
+    Seed {
+        #[arg(long)]
+        product: String,
+        /// Existing customer to attach the tickets to
+        #[arg(long)]
+        customer_id: Uuid,
+        #[arg(long, default_value_t = 10)]
+        count: usize,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ImportFormat {
+    Jsonl,
+    Csv,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFileFormat {
+    Jsonl,
+    Csv,
+    /// CSV with a UTF-8 byte order mark and CRLF line ends, for Excel
+    Excel,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum HistoryInterval {
+    Day,
+    Week,
+    Month,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum HistoryFileFormat {
+    Json,
+    Csv,
+    /// CSV with a UTF-8 byte order mark and CRLF line ends, for Excel
+    Excel,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum WarehouseTable {
+    FactTickets,
+    FactMessages,
+    DimAgents,
+    DimCategories,
+}
+
+#[derive(Subcommand)]
+enum ProductCommand {
+    /// Print a JSON line per registered product
+    List,
+    /// Register a product with default settings, or rename a registered one
+    Register {
+        #[arg(long)]
+        product: String,
+        #[arg(long)]
+        display_name: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum MaintenanceCommand {
+    /// Close tickets that have stayed RESOLVED for the given number of days
+    CloseResolved {
+        #[arg(long, default_value_t = 7)]
+        after_days: i64,
+    },
+    /// Strip submitter IP/user agent/geo captured more than the given number of days ago
+    PurgeSubmitterInfo {
+        #[arg(long, default_value_t = 90)]
+        older_than_days: i64,
+    },
+    /// Fill missing first response times from message history
+    BackfillFirstResponse {
+        #[arg(long)]
+        product: String,
+    },
+    /// Re-evaluate SLA breach flags for tickets created in the last N days
+    RecomputeSla {
+        #[arg(long)]
+        product: String,
+        #[arg(long, default_value_t = 365)]
+        days: i64,
+        /// First response target in minutes
+        #[arg(long)]
+        first_response_minutes: Option<i64>,
+        /// Resolution target in hours
+        #[arg(long)]
+        resolution_hours: Option<i64>,
+    },
+    /// Recompute full-text search vectors
+    RebuildSearch {
+        #[arg(long)]
+        product: String,
+    },
+    /// Check alert thresholds and print a JSON line per crossed threshold
+    EvaluateAlerts {
+        #[arg(long)]
+        product: String,
+        /// Alert when more tickets breached SLA in the last hour
+        #[arg(long)]
+        max_breaches_per_hour: Option<f64>,
+        /// Alert when SLA compliance of the last day's tickets falls below this percentage
+        #[arg(long)]
+        min_daily_compliance: Option<f64>,
+        /// Alert when a dead letter has been pending longer than this many minutes
+        #[arg(long)]
+        max_dead_letter_age_minutes: Option<f64>,
+    },
+    /// Match saved views against new tickets and print a JSON line per notification
+    EvaluateSavedViews {
+        #[arg(long)]
+        product: String,
+    },
+    /// Flag tickets past their SLA deadlines and print a JSON line per newly breached ticket
+    DetectSlaBreaches {
+        /// First response target in minutes, for products without `sla_targets`
+        #[arg(long)]
+        first_response_minutes: Option<i64>,
+        /// Resolution target in hours, for products without `sla_targets`
+        #[arg(long)]
+        resolution_hours: Option<i64>,
+    },
+    /// Apply escalation rules to neglected tickets and print a JSON line per escalation
+    RunEscalations {
+        #[arg(long)]
+        product: String,
+    },
+    /// Generate every scheduled report that is due and print a JSON line per report
+    RunReports,
+    /// Fire timer automations on idle tickets and print a JSON line per execution
+    RunAutomations {
+        #[arg(long)]
+        product: String,
+    },
+    /// Permanently delete test-mode tickets
+    PurgeTestTickets {
+        #[arg(long)]
+        product: String,
+        /// Only purge test tickets created more than this many hours ago
+        #[arg(long, default_value_t = 0)]
+        older_than_hours: i64,
+        /// Only report how many tickets would be purged
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Delete generated exports past their download window
+    PurgeExports,
+}
+
+const EXPORT_PAGE_SIZE: i64 = 500;
+
+/// Write every row of `T`'s extract after `after`; returns the row count and final watermark
+async fn extract_warehouse<T: WarehouseExtract>(
+    repo: &SupportRepository,
+    product: &str,
+    mut after: Option<Watermark>,
+    writer: &mut impl Write,
+) -> Result<(usize, Option<Watermark>)> {
+    let mut rows = 0;
+    loop {
+        let batch = repo.extract_warehouse::<T>(product, after.as_ref(), EXPORT_PAGE_SIZE).await?;
+        for row in &batch.rows {
+            serde_json::to_writer(&mut *writer, row)?;
+            writer.write_all(b"\n")?;
+        }
+        rows += batch.rows.len();
+        after = batch.watermark;
+        if (batch.rows.len() as i64) < EXPORT_PAGE_SIZE {
+            return Ok((rows, after));
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    let pool = PgPoolOptions::new()
+        .max_connections(2)
+        .connect(&cli.database_url)
+        .await
+        .context("failed to connect to database")?;
+
+    let repo = SupportRepository::new(pool.clone());
+
+    match cli.command {
+        Command::Migrate => {
+            MIGRATOR.run(&pool).await.context("failed to apply migrations")?;
+            eprintln!("Migrations applied");
+        }
+        Command::Products(ProductCommand::List) => {
+            let configs = repo.list_product_configs().await?;
+            let mut out = io::stdout().lock();
+            for config in &configs {
+                serde_json::to_writer(&mut out, config)?;
+                writeln!(out)?;
+            }
+        }
+        Command::Products(ProductCommand::Register { product, display_name }) => {
+            let input = ProductConfigInput { display_name, ..Default::default() };
+            let config = repo.upsert_product_config(&product, &input).await?;
+            eprintln!("Registered product {}", config.product);
+        }
+        Command::Maintenance(MaintenanceCommand::CloseResolved { after_days }) => {
+            let closed = repo.close_resolved_tickets(repo.now() - Duration::days(after_days)).await?;
+            eprintln!("Closed {} resolved tickets", closed);
+        }
+        Command::Maintenance(MaintenanceCommand::PurgeSubmitterInfo { older_than_days }) => {
+            let repo = repo.with_intake_privacy(IntakePrivacy {
+                retention: Some(Duration::days(older_than_days)),
+                ..IntakePrivacy::default()
+            });
+            let purged = repo.purge_expired_submitter_info().await?;
+            eprintln!("Removed submitter info from {} tickets", purged);
+        }
+        Command::Maintenance(MaintenanceCommand::BackfillFirstResponse { product }) => {
+            let done = repo.backfill_first_response(&product, report_progress).await?;
+            eprintln!("Backfilled first response on {} of {} tickets", done.updated, done.total);
+        }
+        Command::Maintenance(MaintenanceCommand::RecomputeSla {
+            product,
+            days,
+            first_response_minutes,
+            resolution_hours,
+        }) => {
+            let repo = repo.with_sla_policy(SlaPolicy {
+                first_response_target: first_response_minutes.map(Duration::minutes),
+                resolution_target: resolution_hours.map(Duration::hours),
+                ..SlaPolicy::default()
+            });
+            let now = repo.now();
+            let done = repo
+                .recompute_sla_breaches(&product, now - Duration::days(days), now, report_progress)
+                .await?;
+            eprintln!("Changed SLA breach flag on {} of {} tickets", done.updated, done.total);
+        }
+        Command::Maintenance(MaintenanceCommand::RebuildSearch { product }) => {
+            let done = repo.rebuild_search_vectors(&product, report_progress).await?;
+            eprintln!("Rebuilt search vectors for {} of {} tickets", done.updated, done.total);
+        }
+        Command::Maintenance(MaintenanceCommand::EvaluateAlerts {
+            product,
+            max_breaches_per_hour,
+            min_daily_compliance,
+            max_dead_letter_age_minutes,
+        }) => {
+            let mut thresholds = AlertThresholds::default();
+            if let Some(max) = max_breaches_per_hour {
+                thresholds = thresholds.with_threshold(&product, AlertThreshold::above(AlertMetric::SlaBreaches, max, Duration::hours(1)));
+            }
+            if let Some(min) = min_daily_compliance {
+                thresholds = thresholds.with_threshold(&product, AlertThreshold::below(AlertMetric::SlaComplianceRate, min, Duration::days(1)));
+            }
+            if let Some(max) = max_dead_letter_age_minutes {
+                thresholds = thresholds.with_threshold(&product, AlertThreshold::above(AlertMetric::OldestDeadLetterMinutes, max, Duration::days(1)));
+            }
+
+            let repo = repo.with_alert_thresholds(thresholds);
+            let events = repo.evaluate_alert_thresholds(&product).await?;
+            let mut out = io::stdout().lock();
+            for event in &events {
+                serde_json::to_writer(&mut out, event)?;
+                writeln!(out)?;
+            }
+            eprintln!("{} alert thresholds crossed", events.len());
+        }
+        Command::Maintenance(MaintenanceCommand::EvaluateSavedViews { product }) => {
+            let events = repo.evaluate_saved_view_notifications(&product).await?;
+            let mut out = io::stdout().lock();
+            for event in &events {
+                serde_json::to_writer(&mut out, event)?;
+                writeln!(out)?;
+            }
+            eprintln!("{} saved view notifications sent", events.len());
+        }
+        Command::Maintenance(MaintenanceCommand::DetectSlaBreaches {
+            first_response_minutes,
+            resolution_hours,
+        }) => {
+            let repo = repo.with_sla_policy(SlaPolicy {
+                first_response_target: first_response_minutes.map(Duration::minutes),
+                resolution_target: resolution_hours.map(Duration::hours),
+                ..SlaPolicy::default()
+            });
+            let breached = repo.detect_sla_breaches().await?;
+            let mut out = io::stdout().lock();
+            for ticket in &breached {
+                serde_json::to_writer(&mut out, ticket)?;
+                writeln!(out)?;
+            }
+            eprintln!("{} tickets newly breached their SLA", breached.len());
+        }
+        Command::Maintenance(MaintenanceCommand::RunEscalations { product }) => {
+            let escalations = repo.run_escalations(&product).await?;
+            let mut out = io::stdout().lock();
+            for escalation in &escalations {
+                serde_json::to_writer(&mut out, escalation)?;
+                writeln!(out)?;
+            }
+            eprintln!("{} tickets escalated", escalations.len());
+        }
+        Command::Maintenance(MaintenanceCommand::RunReports) => {
+            let runs = repo.run_due_reports().await?;
+            let mut out = io::stdout().lock();
+            for run in &runs {
+                serde_json::to_writer(&mut out, run)?;
+                writeln!(out)?;
+            }
+            eprintln!("{} reports generated", runs.len());
+        }
+        Command::Maintenance(MaintenanceCommand::RunAutomations { product }) => {
+            let executions = repo.run_timer_automations(&product).await?;
+            let mut out = io::stdout().lock();
+            for execution in &executions {
+                serde_json::to_writer(&mut out, execution)?;
+                writeln!(out)?;
+            }
+            eprintln!("{} automation executions", executions.len());
+        }
+        Command::Maintenance(MaintenanceCommand::PurgeTestTickets { product, older_than_hours, dry_run }) => {
+            let cutoff = repo.now() - Duration::hours(older_than_hours);
+            if dry_run {
+                let count = repo.count_test_tickets(&product, cutoff).await?;
+                eprintln!("{} test tickets would be purged", count);
+            } else {
+                let purged = repo.purge_test_tickets(&product, cutoff).await?;
+                eprintln!("Purged {} test tickets", purged);
+            }
+        }
+        Command::Maintenance(MaintenanceCommand::PurgeExports) => {
+            let purged = repo.purge_expired_exports().await?;
+            eprintln!("Purged {} expired exports", purged);
+        }
+        Command::Import { product, input, format, partial } => {
+            let reader: Box<dyn BufRead> = if input.as_os_str() == "-" {
+                Box::new(BufReader::new(io::stdin()))
+            } else {
+                Box::new(BufReader::new(File::open(&input).with_context(|| format!("failed to open {}", input.display()))?))
+            };
+
+            if let ImportFormat::Csv = format {
+                let parsed = import::csv_tickets(reader, &CsvColumnMapping::default())?;
+                let mode = if partial { ImportMode::PartialSuccess } else { ImportMode::AllOrNothing };
+                let report = repo.import_tickets(&product, parsed, mode).await?;
+                for error in &report.errors {
+                    eprintln!(
+                        "row {}{}: {}",
+                        error.row,
+                        error.column.as_deref().map(|c| format!(" [{}]", c)).unwrap_or_default(),
+                        error.message
+                    );
+                }
+                eprintln!("Imported {} of {} rows ({} failed)", report.imported, report.total_rows, report.failed);
+                return Ok(());
+            }
+
+            let mut imported = 0;
+            for (index, line) in reader.lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let ticket: CreateTicketInput = serde_json::from_str(&line)
+                    .with_context(|| format!("invalid record on line {}", index + 1))?;
+                repo.create_ticket(&product, &ticket).await?;
+                imported += 1;
+            }
+            eprintln!("Imported {} tickets", imported);
+        }
+        Command::Export { product, format, output } => {
+            let mut writer: Box<dyn Write> = if output.as_os_str() == "-" {
+                Box::new(BufWriter::new(io::stdout()))
+            } else {
+                Box::new(BufWriter::new(File::create(&output).with_context(|| format!("failed to create {}", output.display()))?))
+            };
+
+            let filter = TicketFilter::default();
+            match format {
+                ExportFileFormat::Jsonl => {
+                    let tickets = repo.stream_tickets(&product, &filter, EXPORT_PAGE_SIZE);
+                    pin_mut!(tickets);
+                    while let Some(ticket) = tickets.try_next().await? {
+                        serde_json::to_writer(&mut writer, &ticket)?;
+                        writer.write_all(b"\n")?;
+                    }
+                }
+                ExportFileFormat::Csv | ExportFileFormat::Excel => {
+                    let csv_format = if matches!(format, ExportFileFormat::Excel) { ExportFormat::Excel } else { ExportFormat::Csv };
+                    let rows = repo.export_tickets_csv(&product, &filter, csv_format, &mut writer).await?;
+                    eprintln!("Exported {} tickets", rows);
+                }
+            }
+            writer.flush()?;
+        }
+        Command::SlaBreachDigest { product, week, timezone, excel, output } => {
+            let mut writer: Box<dyn Write> = if output.as_os_str() == "-" {
+                Box::new(BufWriter::new(io::stdout()))
+            } else {
+                Box::new(BufWriter::new(File::create(&output).with_context(|| format!("failed to create {}", output.display()))?))
+            };
+
+            let week = week.unwrap_or_else(|| (Utc::now() - Duration::days(7)).date_naive());
+            let digest = repo.sla_breach_digest(&product, week, timezone.as_deref()).await?;
+            let format = if excel { ExportFormat::Excel } else { ExportFormat::Csv };
+            let rows = digest.write_csv(&mut writer, format)?;
+            writer.flush()?;
+            eprintln!("Wrote {} SLA breaches from {} to {}", rows, digest.period_start, digest.period_end);
+        }
+        Command::MetricsHistory { product, from, to, interval, format, output } => {
+            let mut writer: Box<dyn Write> = if output.as_os_str() == "-" {
+                Box::new(BufWriter::new(io::stdout()))
+            } else {
+                Box::new(BufWriter::new(File::create(&output).with_context(|| format!("failed to create {}", output.display()))?))
+            };
+
+            let interval = match interval {
+                HistoryInterval::Day => MetricsInterval::Day,
+                HistoryInterval::Week => MetricsInterval::Week,
+                HistoryInterval::Month => MetricsInterval::Month,
+            };
+            let history = repo.export_metrics_history(&product, None, from, to, interval).await?;
+            match format {
+                HistoryFileFormat::Json => {
+                    history.write_json(&mut writer)?;
+                    writer.write_all(b"\n")?;
+                }
+                HistoryFileFormat::Csv => {
+                    history.write_csv(&mut writer, ExportFormat::Csv)?;
+                }
+                HistoryFileFormat::Excel => {
+                    history.write_csv(&mut writer, ExportFormat::Excel)?;
+                }
+            }
+            writer.flush()?;
+            eprintln!("Wrote {} buckets from {} to {}", history.points.len(), history.from, history.to);
+        }
+        Command::AgentActivity {
+            product,
+            agent_id,
+            from,
+            to,
+            pseudonymize_customers,
+            redact_content,
+            output,
+        } => {
+            let mut writer: Box<dyn Write> = if output.as_os_str() == "-" {
+                Box::new(BufWriter::new(io::stdout()))
+            } else {
+                Box::new(BufWriter::new(File::create(&output).with_context(|| format!("failed to create {}", output.display()))?))
+            };
+
+            let anonymization = ActivityAnonymization { pseudonymize_customers, redact_content };
+            let export = repo.export_agent_activity(&product, agent_id, from, to, MessageVisibility::Internal, anonymization).await?;
+            serde_json::to_writer_pretty(&mut writer, &export)?;
+            writer.write_all(b"\n")?;
+            writer.flush()?;
+            eprintln!(
+                "Exported {} tickets, {} messages and {} CSAT responses",
+                export.summary.tickets_handled,
+                export.messages.len(),
+                export.summary.csat_responses
+            );
+        }
+        Command::Warehouse { product, table, after, output } => {
+            let mut writer: Box<dyn Write> = if output.as_os_str() == "-" {
+                Box::new(BufWriter::new(io::stdout()))
+            } else {
+                Box::new(BufWriter::new(File::create(&output).with_context(|| format!("failed to create {}", output.display()))?))
+            };
+            let after: Option<Watermark> = after
+                .map(|json| serde_json::from_str(&json))
+                .transpose()
+                .context("invalid --after watermark")?;
+
+            let (rows, watermark) = match table {
+                WarehouseTable::FactTickets => extract_warehouse::<FactTicket>(&repo, &product, after, &mut writer).await?,
+                WarehouseTable::FactMessages => extract_warehouse::<FactMessage>(&repo, &product, after, &mut writer).await?,
+                WarehouseTable::DimAgents => extract_warehouse::<DimAgent>(&repo, &product, after, &mut writer).await?,
+                WarehouseTable::DimCategories => extract_warehouse::<DimCategory>(&repo, &product, after, &mut writer).await?,
+            };
+            writer.flush()?;
+            eprintln!("Extracted {} rows", rows);
+            if let Some(watermark) = watermark {
+                eprintln!("{}", serde_json::to_string(&watermark)?);
+            }
+        }
+        Command::Retention { older_than_days, product, dry_run } => {
+            let cutoff = repo.now() - Duration::days(older_than_days);
+            if dry_run {
+                let count = repo.count_purgeable_tickets(product.as_deref(), cutoff).await?;
+                eprintln!("{} soft-deleted tickets would be purged", count);
+            } else {
+                let purged = repo.purge_deleted_tickets(product.as_deref(), cutoff).await?;
+                eprintln!("Purged {} soft-deleted tickets", purged);
+            }
+        }
+        Command::Seed { product, customer_id, count } => {
+            let priorities = [TicketPriority::Low, TicketPriority::Medium, TicketPriority::High, TicketPriority::Urgent];
+            for i in 0..count {
+                let input = CreateTicketInput {
+                    customer_id,
+                    subject: format!("Sample ticket #{}", i + 1),
+                    description: "Seeded by pleme-support-cli".to_string(),
+                    priority: Some(priorities[i % priorities.len()]),
+                    category: Some("sample".to_string()),
+                    ..Default::default()
+                };
+                repo.create_ticket(&product, &input).await?;
+            }
+            eprintln!("Seeded {} tickets", count);
+        }
+    }
+
+    Ok(())
+}
+
+fn report_progress(progress: &BackfillProgress) {
+    eprintln!("{}/{} tickets processed, {} updated", progress.processed, progress.total, progress.updated);
+}
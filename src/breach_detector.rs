@@ -0,0 +1,58 @@
+//! Background SLA breach detection
+//!
+//! Without a detector, `sla_breach` is only set when a late first response
+//! arrives or someone calls `evaluate_sla_breaches`, so a ticket nobody
+//! touches can sit past its deadline unflagged. [`SlaBreachDetector`] is the
+//! worker for that sweep: the host schedules [`run_once`](SlaBreachDetector::run_once)
+//! every minute or so (or runs `pleme-support-cli maintenance detect-sla-breaches`
+//! from cron), and each run
+//!
+//! - flags every ticket whose first-response or resolution clock has run past
+//!   its target plus grace, as the [SLA policy](crate::sla) computes it,
+//! - stamps `sla_breached_at` with the run's time, and
+//! - publishes a `ticket.sla_breached` [event](crate::events) per newly
+//!   breached ticket.
+//!
+//! Breaches are sticky, so a ticket is reported once no matter how often the
+//! detector runs, including from several instances at once. Test-mode and
+//! deleted tickets are skipped.
+//!
+//! ```rust,no_run
+//! use std::sync::Arc;
+//! use pleme_support::{SlaBreachDetector, SupportRepository};
+//!
+//! # async fn example(repo: Arc<SupportRepository>) -> pleme_support::Result<()> {
+//! let detector = SlaBreachDetector::new(repo);
+//! let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+//! loop {
+//!     interval.tick().await;
+//!     match detector.run_once().await {
+//!         Ok(breached) => tracing::info!("{} tickets breached", breached.len()),
+//!         Err(e) => tracing::error!("SLA breach detection failed: {}", e),
+//!     }
+//! }
+//! # }
+//! ```
+
+use std::sync::Arc;
+
+use crate::models::SupportTicket;
+use crate::repository::SupportRepository;
+use crate::Result;
+
+/// Flags tickets past their SLA deadlines when the host runs it
+#[derive(Clone)]
+pub struct SlaBreachDetector {
+    repo: Arc<SupportRepository>,
+}
+
+impl SlaBreachDetector {
+    pub fn new(repo: Arc<SupportRepository>) -> Self {
+        Self { repo }
+    }
+
+    /// Flag and publish the tickets that breached since the last run, returning them
+    pub async fn run_once(&self) -> Result<Vec<SupportTicket>> {
+        self.repo.detect_sla_breaches().await
+    }
+}
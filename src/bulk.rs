@@ -0,0 +1,45 @@
+//! Bulk ticket operations
+//!
+//! `bulk_update_tickets`, `bulk_assign` and `bulk_close` apply one change to
+//! up to [`MAX_BULK_TICKETS`] tickets in a single transaction. Each ticket is
+//! updated under its own savepoint, exactly as `update_ticket` would (audit
+//! entries, SLA recomputation), so a ticket that fails (e.g. it was deleted)
+//! is reported in its [`BulkTicketResult`] and rolled back alone while the
+//! rest commit together.
+
+use async_graphql::SimpleObject;
+use uuid::Uuid;
+
+use crate::models::SupportTicket;
+
+/// Most tickets one bulk operation may touch
+pub const MAX_BULK_TICKETS: usize = 500;
+
+/// Outcome for one ticket of a bulk operation
+#[derive(Debug, Clone, SimpleObject)]
+pub struct BulkTicketResult {
+    pub ticket_id: Uuid,
+    pub success: bool,
+    /// The updated ticket, when successful
+    pub ticket: Option<SupportTicket>,
+    pub error: Option<String>,
+}
+
+/// Per-ticket outcomes of a bulk operation, in request order
+#[derive(Debug, Clone, SimpleObject)]
+pub struct BulkUpdateReport {
+    pub results: Vec<BulkTicketResult>,
+    pub succeeded: i32,
+    pub failed: i32,
+}
+
+impl BulkUpdateReport {
+    pub fn new(results: Vec<BulkTicketResult>) -> Self {
+        let succeeded = results.iter().filter(|r| r.success).count() as i32;
+        Self {
+            failed: results.len() as i32 - succeeded,
+            succeeded,
+            results,
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! Collapsing duplicate bursts at intake
+//!
+//! Retried form submissions and monitoring floods open the same ticket over
+//! and over within minutes. With a rule for the product, a submission that
+//! repeats an open ticket inside the rule's window is folded into it instead:
+//! the existing ticket's `occurrence_count` goes up, the submission is logged
+//! for analytics, `ticket.updated` is published for the existing ticket, and
+//! the caller gets it back. Concurrent repeats wait for each other, so a
+//! flood arriving at once still opens a single ticket.
+//!
+//! A submission repeats a ticket when it comes from the same customer with the
+//! same subject and description (ignoring case and whitespace), or carries the
+//! same `error_signature`, from any customer. The window runs from the
+//! ticket's latest occurrence, so a steady flood keeps collapsing.
+//!
+//! ```rust
+//! use chrono::Duration;
+//! use pleme_support::{BurstCollapsing, BurstRule};
+//!
+//! let collapsing = BurstCollapsing::default()
+//!     .with_rule("novaskyn", BurstRule::new(Duration::minutes(10)))
+//!     .with_rule("thai", BurstRule { same_customer: false, ..BurstRule::new(Duration::minutes(30)) });
+//!
+//! assert!(collapsing.rule_for("novaskyn").is_some());
+//! assert!(collapsing.rule_for("lilitu").is_none());
+//! ```
+//!
+//! Products without a rule never collapse. Imports and tickets created
+//! through a [`SupportTransaction`](crate::SupportTransaction) are not
+//! collapsed either.
+
+use async_graphql::SimpleObject;
+use chrono::Duration;
+use std::collections::HashMap;
+
+/// When a product's submissions collapse into an open ticket
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BurstRule {
+    /// How long after a ticket's latest occurrence repeats still collapse
+    pub window: Duration,
+    /// Collapse a customer's repeats of their own ticket
+    pub same_customer: bool,
+    /// Collapse submissions sharing an `error_signature`, whoever sends them
+    pub same_error_signature: bool,
+}
+
+impl BurstRule {
+    /// Collapse on both matches within `window`
+    pub fn new(window: Duration) -> Self {
+        Self { window, same_customer: true, same_error_signature: true }
+    }
+}
+
+/// Per-product duplicate burst rules
+#[derive(Debug, Clone, Default)]
+pub struct BurstCollapsing {
+    rules: HashMap<String, BurstRule>,
+}
+
+impl BurstCollapsing {
+    pub fn with_rule(mut self, product: impl Into<String>, rule: BurstRule) -> Self {
+        self.rules.insert(product.into(), rule);
+        self
+    }
+
+    pub fn rule_for(&self, product: &str) -> Option<&BurstRule> {
+        self.rules.get(product)
+    }
+}
+
+/// Why a submission was collapsed
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum CollapseReason {
+    SameCustomer,
+    ErrorSignature,
+}
+
+impl CollapseReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CollapseReason::SameCustomer => "SAME_CUSTOMER",
+            CollapseReason::ErrorSignature => "ERROR_SIGNATURE",
+        }
+    }
+}
+
+/// Collapsed submissions in a period
+#[derive(Debug, Clone, SimpleObject)]
+pub struct CollapsedSubmissionCounts {
+    pub total: i64,
+    pub same_customer: i64,
+    pub error_signature: i64,
+    /// Tickets that absorbed at least one submission
+    pub tickets: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_rules_collapse_on_both_matches() {
+        let rule = BurstRule::new(Duration::minutes(10));
+        assert_eq!(rule.window, Duration::minutes(10));
+        assert!(rule.same_customer && rule.same_error_signature);
+    }
+
+    #[test]
+    fn rules_are_per_product_and_later_ones_replace_earlier() {
+        let collapsing = BurstCollapsing::default()
+            .with_rule("novaskyn", BurstRule::new(Duration::minutes(10)))
+            .with_rule("novaskyn", BurstRule::new(Duration::minutes(30)));
+
+        assert_eq!(collapsing.rule_for("novaskyn").map(|r| r.window), Some(Duration::minutes(30)));
+        assert!(collapsing.rule_for("NOVASKYN").is_none());
+    }
+
+    #[test]
+    fn reasons_match_the_stored_values() {
+        assert_eq!(CollapseReason::SameCustomer.as_str(), "SAME_CUSTOMER");
+        assert_eq!(CollapseReason::ErrorSignature.as_str(), "ERROR_SIGNATURE");
+    }
+}
@@ -0,0 +1,285 @@
+//! Business-hours calendars for SLA clocks
+//!
+//! A [`BusinessCalendar`] is a weekly schedule ([`BusinessHours`]) plus the
+//! dates a product is closed. Each registered product has one, set through
+//! its [`ProductConfig`](crate::ProductConfig): working days, opening hours
+//! and holidays in the product's timezone.
+//!
+//! First-response SLA clocks run on the product's calendar: a ticket filed on
+//! Friday evening is not late by Monday morning when the product is closed
+//! over the weekend. Breach detection, `first_response_due_at` and the
+//! `timeToFirstResponseBusinessMinutes` SLA metric all use it. Business time
+//! is computed in the database (`support_product_business_seconds`, migration
+//! 050); products open around the clock count wall-clock time, as before.
+//! Resolution clocks stay on wall-clock time, paused while waiting on the
+//! customer.
+//!
+//! ```rust
+//! use chrono::{NaiveDate, Weekday};
+//! use pleme_support::{BusinessCalendar, BusinessHours};
+//!
+//! let calendar = BusinessCalendar::new(BusinessHours::weekdays("America/Sao_Paulo", 9 * 60, 18 * 60))
+//!     .with_holiday(NaiveDate::from_ymd_opt(2026, 11, 2).unwrap());
+//!
+//! assert!(!calendar.is_always_open());
+//! assert!(calendar.is_working_day(NaiveDate::from_ymd_opt(2026, 11, 3).unwrap()));
+//! assert!(!calendar.is_working_day(NaiveDate::from_ymd_opt(2026, 11, 2).unwrap()));
+//! assert!(!calendar.is_working_day(NaiveDate::from_ymd_opt(2026, 11, 7).unwrap()));
+//! assert_eq!(calendar.hours.days.first(), Some(&Weekday::Mon));
+//!
+//! assert!(BusinessCalendar::default().is_always_open());
+//! ```
+
+use chrono::{Datelike, NaiveDate, Weekday};
+
+/// Weekly schedule during which business-hours SLA clocks run
+#[derive(Debug, Clone)]
+pub struct BusinessHours {
+    /// IANA timezone the schedule is in
+    pub timezone: String,
+    pub days: Vec<Weekday>,
+    /// Minutes after local midnight the business day starts
+    pub opens_at_minute: u32,
+    /// Minutes after local midnight the business day ends, up to 1440
+    pub closes_at_minute: u32,
+}
+
+impl BusinessHours {
+    /// Every hour of every day, in UTC
+    pub fn always() -> Self {
+        Self {
+            timezone: "UTC".to_string(),
+            days: vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri, Weekday::Sat, Weekday::Sun],
+            opens_at_minute: 0,
+            closes_at_minute: 24 * 60,
+        }
+    }
+
+    /// Monday to Friday between the given minutes after local midnight
+    pub fn weekdays(timezone: impl Into<String>, opens_at_minute: u32, closes_at_minute: u32) -> Self {
+        Self {
+            timezone: timezone.into(),
+            days: vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri],
+            opens_at_minute,
+            closes_at_minute,
+        }
+    }
+
+    /// Whether the schedule covers every hour of every day
+    pub fn is_always_open(&self) -> bool {
+        self.days.len() == 7 && self.opens_at_minute == 0 && self.closes_at_minute >= 24 * 60
+    }
+
+    /// ISO day numbers (1 = Monday), as `support_business_seconds` takes them
+    pub(crate) fn iso_days(&self) -> Vec<i32> {
+        self.days.iter().map(|day| day.number_from_monday() as i32).collect()
+    }
+}
+
+impl Default for BusinessHours {
+    fn default() -> Self {
+        Self::always()
+    }
+}
+
+/// Business hours and the local dates they do not apply on
+#[derive(Debug, Clone, Default)]
+pub struct BusinessCalendar {
+    pub hours: BusinessHours,
+    /// Local dates the product is closed all day
+    pub holidays: Vec<NaiveDate>,
+}
+
+impl BusinessCalendar {
+    pub fn new(hours: BusinessHours) -> Self {
+        Self { hours, holidays: Vec::new() }
+    }
+
+    pub fn with_holiday(mut self, date: NaiveDate) -> Self {
+        self.holidays.push(date);
+        self
+    }
+
+    /// Whether business time equals wall-clock time
+    pub fn is_always_open(&self) -> bool {
+        self.hours.is_always_open() && self.holidays.is_empty()
+    }
+
+    /// Whether the product opens at all on local `date`
+    pub fn is_working_day(&self, date: NaiveDate) -> bool {
+        self.hours.days.contains(&date.weekday()) && !self.holidays.contains(&date)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn always_open_needs_every_minute_of_every_day() {
+        assert!(BusinessHours::always().is_always_open());
+        assert!(!BusinessHours::weekdays("UTC", 0, 1440).is_always_open());
+        assert!(!BusinessHours { closes_at_minute: 1439, ..BusinessHours::always() }.is_always_open());
+        assert!(!BusinessHours { opens_at_minute: 1, ..BusinessHours::always() }.is_always_open());
+
+        // The timezone doesn't matter around the clock, holidays do
+        let elsewhere = BusinessHours { timezone: "America/New_York".to_string(), ..BusinessHours::always() };
+        assert!(BusinessCalendar::new(elsewhere.clone()).is_always_open());
+        assert!(!BusinessCalendar::new(elsewhere).with_holiday(date(2026, 12, 25)).is_always_open());
+    }
+
+    #[test]
+    fn numbers_days_from_monday() {
+        assert_eq!(BusinessHours::always().iso_days(), vec![1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(BusinessHours::weekdays("UTC", 540, 1020).iso_days(), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn working_days_skip_weekends_and_holidays() {
+        let calendar = BusinessCalendar::new(BusinessHours::weekdays("America/New_York", 540, 1020))
+            .with_holiday(date(2026, 3, 10));
+
+        assert!(calendar.is_working_day(date(2026, 3, 6)));
+        assert!(!calendar.is_working_day(date(2026, 3, 7)));
+        // Spring-forward Sunday and the Monday after it
+        assert!(!calendar.is_working_day(date(2026, 3, 8)));
+        assert!(calendar.is_working_day(date(2026, 3, 9)));
+        assert!(!calendar.is_working_day(date(2026, 3, 10)));
+
+        // Holidays close the product even around the clock
+        let always = BusinessCalendar::default().with_holiday(date(2026, 3, 8));
+        assert!(!always.is_working_day(date(2026, 3, 8)));
+        assert!(always.is_working_day(date(2026, 3, 7)));
+    }
+
+    /// Business time is computed in the database (migration 050). These run
+    /// in a throwaway database on the server `TEST_DATABASE_URL` points to,
+    /// and are skipped when it is not set.
+    mod database {
+        use chrono::{DateTime, Utc};
+        use sqlx::postgres::{PgConnectOptions, PgPool};
+        use uuid::Uuid;
+
+        fn utc(value: &str) -> DateTime<Utc> {
+            value.parse().unwrap()
+        }
+
+        /// Migrate a fresh database; the host service owns `customers`
+        async fn migrate(pool: &PgPool) -> sqlx::Result<()> {
+            sqlx::query("CREATE TABLE customers (id UUID PRIMARY KEY)").execute(pool).await?;
+            crate::MIGRATOR.run(pool).await?;
+            Ok(())
+        }
+
+        /// New York, weekdays 09:00-17:00, closed on Tuesday 2026-03-10.
+        /// Clocks spring forward on Sunday 2026-03-08 and fall back on
+        /// Sunday 2026-11-01.
+        async fn business_time(pool: &PgPool) -> sqlx::Result<(Vec<DateTime<Utc>>, Vec<i64>)> {
+            sqlx::query(
+                "INSERT INTO product_configs (product, business_timezone, business_days,
+                     business_opens_at_minute, business_closes_at_minute, business_holidays)
+                 VALUES ('nyc', 'America/New_York', '{1,2,3,4,5}', 540, 1020, '{2026-03-10}')",
+            )
+            .execute(pool)
+            .await?;
+
+            let mut deadlines = Vec::new();
+            for (product, start, seconds) in [
+                // Friday's last hour (EST), then Monday's first (EDT)
+                ("nyc", "2026-03-06T21:00:00Z", 7200),
+                // Monday's last half hour, over the holiday to Wednesday
+                ("nyc", "2026-03-09T20:30:00Z", 3600),
+                // A whole business day ends at closing, one more second is the next working day
+                ("nyc", "2026-03-09T13:00:00Z", 28800),
+                ("nyc", "2026-03-09T13:00:00Z", 28801),
+                // Filed on a Saturday
+                ("nyc", "2026-03-07T12:00:00Z", 60),
+                // Friday (EDT) to Monday (EST) over the fall-back weekend
+                ("nyc", "2026-10-30T20:00:00Z", 7200),
+                // Unregistered products run on wall-clock time
+                ("elsewhere", "2026-03-07T12:00:00Z", 90),
+            ] {
+                deadlines.push(
+                    sqlx::query_scalar("SELECT support_product_business_deadline($1, $2, $3)")
+                        .bind(product)
+                        .bind(utc(start))
+                        .bind(seconds as i64)
+                        .fetch_one(pool)
+                        .await?,
+                );
+            }
+
+            let mut seconds = Vec::new();
+            for (product, start, end) in [
+                ("nyc", "2026-03-06T21:00:00Z", "2026-03-09T14:00:00Z"),
+                ("nyc", "2026-03-09T13:00:00Z", "2026-03-11T13:00:00Z"),
+                ("nyc", "2026-03-07T12:00:00Z", "2026-03-08T12:00:00Z"),
+                ("elsewhere", "2026-03-07T12:00:00Z", "2026-03-08T12:00:00Z"),
+            ] {
+                seconds.push(
+                    sqlx::query_scalar("SELECT support_product_business_seconds($1, $2, $3)")
+                        .bind(product)
+                        .bind(utc(start))
+                        .bind(utc(end))
+                        .fetch_one(pool)
+                        .await?,
+                );
+            }
+
+            // Local days around the clock are 23 and 25 hours long on the change days
+            for (start, end) in [
+                ("2026-03-08T05:00:00Z", "2026-03-09T04:00:00Z"),
+                ("2026-11-01T04:00:00Z", "2026-11-02T05:00:00Z"),
+            ] {
+                seconds.push(
+                    sqlx::query_scalar(
+                        "SELECT support_business_seconds($1, $2, 'America/New_York', '{1,2,3,4,5,6,7}', 0, 1440, '{}')",
+                    )
+                    .bind(utc(start))
+                    .bind(utc(end))
+                    .fetch_one(pool)
+                    .await?,
+                );
+            }
+
+            Ok((deadlines, seconds))
+        }
+
+        #[tokio::test]
+        async fn business_time_follows_the_product_calendar() {
+            let Ok(url) = std::env::var("TEST_DATABASE_URL") else { return };
+            let admin = PgPool::connect(&url).await.unwrap();
+            let name = format!("pleme_support_test_{}", Uuid::new_v4().simple());
+            sqlx::query(&format!("CREATE DATABASE {name}")).execute(&admin).await.unwrap();
+
+            let options = url.parse::<PgConnectOptions>().unwrap().database(&name);
+            let pool = PgPool::connect_with(options).await.unwrap();
+            let result = match migrate(&pool).await {
+                Ok(()) => business_time(&pool).await,
+                Err(e) => Err(e),
+            };
+            pool.close().await;
+            sqlx::query(&format!("DROP DATABASE {name} WITH (FORCE)")).execute(&admin).await.unwrap();
+
+            let (deadlines, seconds) = result.unwrap();
+            assert_eq!(
+                deadlines,
+                [
+                    "2026-03-09T14:00:00Z",
+                    "2026-03-11T13:30:00Z",
+                    "2026-03-09T21:00:00Z",
+                    "2026-03-11T13:00:01Z",
+                    "2026-03-09T13:01:00Z",
+                    "2026-11-02T15:00:00Z",
+                    "2026-03-07T12:01:30Z",
+                ]
+                .map(utc)
+            );
+            assert_eq!(seconds, vec![7200, 28800, 0, 86400, 82800, 90000]);
+        }
+    }
+}
@@ -0,0 +1,638 @@
+//! Inbound email
+//!
+//! Mail providers deliver inbound messages to services as raw RFC 5322
+//! text. [`InboundEmail::parse`] extracts what a ticket needs: the sender,
+//! subject, threading headers and a plain-text body, decoding multipart
+//! MIME, base64 and quoted-printable bodies, RFC 2047 encoded headers and
+//! Latin-1 charsets. HTML-only messages are reduced to text; attachments are
+//! skipped.
+//!
+//! `SupportRepository::ingest_email` then threads the email onto a ticket: a
+//! reply whose `In-Reply-To`/`References` name an email the ticket sent or
+//! received is appended to it (without the quoted history, see
+//! [`strip_quoted_reply`]); anything else opens a new `EMAIL` channel ticket.
+//! Each Message-ID is ingested once, so provider retries are harmless.
+//! Emails are normalized rather than rejected: a blank subject becomes
+//! `(no subject)`, a blank body (an attachment-only reply, say) falls back to
+//! the subject, and text over the validation limits is cut to fit.
+//!
+//! ```rust
+//! use pleme_support::channels::email::InboundEmail;
+//!
+//! let raw = "From: =?UTF-8?Q?Jo=C3=A3o?= <joao@example.com>\r\n\
+//!            Subject: Re: Login issue\r\n\
+//!            Message-ID: <reply-1@example.com>\r\n\
+//!            In-Reply-To: <agent-1@support.example.com>\r\n\
+//!            Content-Type: text/plain; charset=utf-8\r\n\
+//!            Content-Transfer-Encoding: quoted-printable\r\n\
+//!            \r\n\
+//!            Still failing =E2=80=94 see attached.\r\n\
+//!            \r\n\
+//!            On Mon, 2 Mar 2026, Support wrote:\r\n\
+//!            > Please try again\r\n";
+//!
+//! let email = InboundEmail::parse(raw).unwrap();
+//! assert_eq!(email.from_name.as_deref(), Some("João"));
+//! assert_eq!(email.from_address, "joao@example.com");
+//! assert_eq!(email.message_id.as_deref(), Some("reply-1@example.com"));
+//! assert_eq!(email.thread_refs(), vec!["agent-1@support.example.com"]);
+//! assert_eq!(email.reply_text(), "Still failing — see attached.");
+//!
+//! let attachment_only = InboundEmail { subject: "  ".to_string(), ..email };
+//! assert_eq!(attachment_only.ticket_subject(), "(no subject)");
+//! ```
+
+use async_graphql::SimpleObject;
+use base64::Engine;
+
+use crate::models::{SupportTicket, TicketMessage};
+use crate::validation::{MAX_DESCRIPTION_LENGTH, MAX_MESSAGE_LENGTH, MAX_SUBJECT_LENGTH};
+use crate::{Result, SupportError};
+
+/// Where an ingested email ended up
+#[derive(Debug, Clone, SimpleObject)]
+pub struct EmailIngestion {
+    pub ticket: SupportTicket,
+    /// The reply appended to an existing ticket; `None` when the email opened `ticket`
+    pub message: Option<TicketMessage>,
+    pub created_ticket: bool,
+    /// The Message-ID was already ingested; nothing changed
+    pub duplicate: bool,
+}
+
+/// An inbound email, reduced to what ticket intake uses
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InboundEmail {
+    /// Without angle brackets
+    pub message_id: Option<String>,
+    pub in_reply_to: Vec<String>,
+    /// Oldest first, without angle brackets
+    pub references: Vec<String>,
+    pub from_name: Option<String>,
+    pub from_address: String,
+    pub subject: String,
+    /// Plain-text body, including any quoted history
+    pub text_body: String,
+}
+
+impl InboundEmail {
+    /// Parse a raw RFC 5322 message
+    ///
+    /// Fails with `InvalidInput` when there is no usable `From` address.
+    pub fn parse(raw: &str) -> Result<Self> {
+        let raw = raw.replace("\r\n", "\n");
+        let part = Part::parse(&raw);
+
+        let from = part.header("from").unwrap_or_default();
+        let (from_name, from_address) = parse_mailbox(&from)
+            .ok_or_else(|| SupportError::InvalidInput("Email has no valid From address".to_string()))?;
+
+        Ok(Self {
+            message_id: part.header("message-id").and_then(|v| message_ids(&v).into_iter().next()),
+            in_reply_to: part.header("in-reply-to").map(|v| message_ids(&v)).unwrap_or_default(),
+            references: part.header("references").map(|v| message_ids(&v)).unwrap_or_default(),
+            from_name,
+            from_address,
+            subject: part.header("subject").map(|s| decode_encoded_words(&s)).unwrap_or_default().trim().to_string(),
+            text_body: part.text_body().unwrap_or_default().trim().to_string(),
+        })
+    }
+
+    /// Ids of the emails this one replies to, most direct first
+    pub fn thread_refs(&self) -> Vec<String> {
+        let mut refs = self.in_reply_to.clone();
+        for reference in self.references.iter().rev() {
+            if !refs.contains(reference) {
+                refs.push(reference.clone());
+            }
+        }
+        refs
+    }
+
+    /// The body without the quoted history of a reply
+    pub fn reply_text(&self) -> String {
+        strip_quoted_reply(&self.text_body)
+    }
+
+    /// Subject of the ticket the email opens; `(no subject)` when blank
+    pub fn ticket_subject(&self) -> String {
+        let subject = self.subject.trim();
+        if subject.is_empty() {
+            "(no subject)".to_string()
+        } else {
+            truncate_chars(subject, MAX_SUBJECT_LENGTH)
+        }
+    }
+
+    /// Description of the ticket the email opens; the subject when the body is blank
+    pub fn ticket_description(&self) -> String {
+        let body = self.text_body.trim();
+        if body.is_empty() {
+            self.ticket_subject()
+        } else {
+            truncate_chars(body, MAX_DESCRIPTION_LENGTH)
+        }
+    }
+
+    /// Content of the message a reply adds; the subject when the reply text is blank
+    pub fn reply_content(&self) -> String {
+        let reply = self.reply_text();
+        if reply.trim().is_empty() {
+            self.ticket_subject()
+        } else {
+            truncate_chars(&reply, MAX_MESSAGE_LENGTH)
+        }
+    }
+}
+
+/// At most `max` characters of `text`
+fn truncate_chars(text: &str, max: usize) -> String {
+    text.chars().take(max).collect()
+}
+
+/// Cut a reply body at the start of its quoted history
+///
+/// History starts at an `On ... wrote:` line, an `-----Original Message-----`
+/// separator or the first `>` quoted line. Bodies that are nothing but
+/// history are returned whole.
+pub fn strip_quoted_reply(body: &str) -> String {
+    let mut kept = Vec::new();
+    for line in body.lines() {
+        let trimmed = line.trim();
+        let history = trimmed.starts_with('>')
+            || (trimmed.starts_with("On ") && trimmed.ends_with("wrote:"))
+            || trimmed.eq_ignore_ascii_case("-----Original Message-----");
+        if history {
+            break;
+        }
+        kept.push(line);
+    }
+
+    let reply = kept.join("\n").trim().to_string();
+    if reply.is_empty() {
+        body.trim().to_string()
+    } else {
+        reply
+    }
+}
+
+/// A MIME entity: headers and undecoded body
+struct Part<'a> {
+    headers: Vec<(String, String)>,
+    body: &'a str,
+}
+
+impl<'a> Part<'a> {
+    fn parse(raw: &'a str) -> Self {
+        let (head, body) = match raw.find("\n\n") {
+            Some(i) => (&raw[..i], &raw[i + 2..]),
+            None if raw.starts_with('\n') => ("", &raw[1..]),
+            None => (raw, ""),
+        };
+
+        let mut headers: Vec<(String, String)> = Vec::new();
+        for line in head.lines() {
+            if line.starts_with([' ', '\t']) {
+                if let Some((_, value)) = headers.last_mut() {
+                    value.push(' ');
+                    value.push_str(line.trim());
+                }
+            } else if let Some((name, value)) = line.split_once(':') {
+                headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+            }
+        }
+
+        Self { headers, body }
+    }
+
+    fn header(&self, name: &str) -> Option<String> {
+        self.headers.iter().find(|(n, _)| n == name).map(|(_, v)| v.clone())
+    }
+
+    /// Lowercased media type and its parameters, defaulting to text/plain
+    fn content_type(&self) -> (String, Vec<(String, String)>) {
+        let value = self.header("content-type").unwrap_or_else(|| "text/plain".to_string());
+        let mut pieces = split_params(&value).into_iter();
+        let media_type = pieces.next().unwrap_or_default().to_ascii_lowercase();
+        let params = pieces
+            .filter_map(|p| {
+                let (k, v) = p.split_once('=')?;
+                Some((k.trim().to_ascii_lowercase(), v.trim().trim_matches('"').to_string()))
+            })
+            .collect();
+        (media_type, params)
+    }
+
+    fn is_attachment(&self) -> bool {
+        self.header("content-disposition")
+            .is_some_and(|d| d.trim_start().to_ascii_lowercase().starts_with("attachment"))
+    }
+
+    /// The first text/plain part, else the first text/html part as text
+    fn text_body(&self) -> Option<String> {
+        self.find_text("text/plain").or_else(|| self.find_text("text/html").map(|html| html_to_text(&html)))
+    }
+
+    fn find_text(&self, wanted: &str) -> Option<String> {
+        if self.is_attachment() {
+            return None;
+        }
+        let (media_type, params) = self.content_type();
+        let param = |name: &str| params.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str());
+
+        if media_type.starts_with("multipart/") {
+            let boundary = param("boundary")?;
+            return multipart_bodies(self.body, boundary)
+                .into_iter()
+                .find_map(|body| Part::parse(body).find_text(wanted));
+        }
+        if media_type != wanted {
+            return None;
+        }
+
+        let encoding = self.header("content-transfer-encoding").unwrap_or_default().to_ascii_lowercase();
+        let bytes = match encoding.trim() {
+            "base64" => {
+                let compact: String = self.body.chars().filter(|c| !c.is_whitespace()).collect();
+                base64::engine::general_purpose::STANDARD.decode(compact).ok()?
+            }
+            "quoted-printable" => decode_quoted_printable(self.body),
+            _ => self.body.as_bytes().to_vec(),
+        };
+        Some(decode_charset(&bytes, param("charset").unwrap_or("utf-8")))
+    }
+}
+
+/// Bodies of a multipart entity's parts
+fn multipart_bodies<'a>(body: &'a str, boundary: &str) -> Vec<&'a str> {
+    let delimiter = format!("--{}", boundary);
+    let mut parts = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut offset = 0;
+    for line in body.split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        if trimmed == delimiter || trimmed == format!("{}--", delimiter) {
+            if let Some(s) = start {
+                parts.push(body[s..offset].trim_end_matches('\n'));
+            }
+            if trimmed.ends_with("--") && trimmed != delimiter {
+                return parts;
+            }
+            start = Some(offset + line.len());
+        }
+        offset += line.len();
+    }
+    if let Some(s) = start {
+        parts.push(&body[s..]);
+    }
+    parts
+}
+
+/// Split a header value at `;` outside quotes
+fn split_params(value: &str) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in value.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                current.push(c);
+            }
+            ';' if !quoted => pieces.push(std::mem::take(&mut current).trim().to_string()),
+            _ => current.push(c),
+        }
+    }
+    pieces.push(current.trim().to_string());
+    pieces
+}
+
+/// Display name and address of the first mailbox in a From header
+fn parse_mailbox(value: &str) -> Option<(Option<String>, String)> {
+    let (name, address) = match (value.find('<'), value.rfind('>')) {
+        (Some(open), Some(close)) if open < close => {
+            let name = decode_encoded_words(value[..open].trim().trim_matches('"').trim());
+            (Some(name).filter(|n| !n.is_empty()), value[open + 1..close].trim())
+        }
+        _ => (None, value.split(',').next().unwrap_or_default().trim()),
+    };
+
+    let (local, domain) = address.split_once('@')?;
+    if local.is_empty() || domain.is_empty() || address.contains(char::is_whitespace) {
+        return None;
+    }
+    Some((name, address.to_ascii_lowercase()))
+}
+
+/// Every `<id>` in a Message-ID, In-Reply-To or References header
+fn message_ids(value: &str) -> Vec<String> {
+    value
+        .split('<')
+        .skip(1)
+        .filter_map(|s| s.split_once('>').map(|(id, _)| id.trim().to_string()))
+        .filter(|id| !id.is_empty())
+        .collect()
+}
+
+/// Decode RFC 2047 `=?charset?B|Q?text?=` words, joining adjacent ones
+fn decode_encoded_words(value: &str) -> String {
+    let mut out = String::new();
+    let mut rest = value;
+    let mut after_word = false;
+    while let Some(start) = rest.find("=?") {
+        let decoded = rest[start + 2..].split_once('?').and_then(|(charset, tail)| {
+            let (encoding, tail) = tail.split_once('?')?;
+            let (text, tail) = tail.split_once("?=")?;
+            let bytes = match encoding.to_ascii_uppercase().as_str() {
+                "B" => base64::engine::general_purpose::STANDARD.decode(text).ok()?,
+                "Q" => decode_quoted_printable(&text.replace('_', " ")),
+                _ => return None,
+            };
+            Some((decode_charset(&bytes, charset), tail))
+        });
+
+        match decoded {
+            Some((text, tail)) => {
+                let between = &rest[..start];
+                // Whitespace between adjacent encoded words is not displayed
+                if !(after_word && between.trim().is_empty()) {
+                    out.push_str(between);
+                }
+                out.push_str(&text);
+                rest = tail;
+                after_word = true;
+            }
+            None => {
+                out.push_str(&rest[..start + 2]);
+                rest = &rest[start + 2..];
+                after_word = false;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn decode_quoted_printable(text: &str) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'=' {
+            // Soft line break
+            if bytes.get(i + 1) == Some(&b'\n') {
+                i += 2;
+                continue;
+            }
+            let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+            if let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    out
+}
+
+fn decode_charset(bytes: &[u8], charset: &str) -> String {
+    match charset.trim().to_ascii_lowercase().as_str() {
+        "iso-8859-1" | "latin1" | "latin-1" | "windows-1252" | "cp1252" => bytes.iter().map(|&b| b as char).collect(),
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
+/// Rough text rendering of an HTML body: tags dropped, breaks kept
+fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut tag = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => {
+                in_tag = true;
+                tag.clear();
+            }
+            '>' if in_tag => {
+                in_tag = false;
+                let name = tag.trim_start_matches('/').split_whitespace().next().unwrap_or_default().to_ascii_lowercase();
+                if matches!(name.as_str(), "br" | "br/" | "p" | "div" | "li" | "tr") {
+                    text.push('\n');
+                }
+            }
+            _ if in_tag => tag.push(c),
+            _ => text.push(c),
+        }
+    }
+
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(raw: &str) -> InboundEmail {
+        InboundEmail::parse(raw).unwrap()
+    }
+
+    #[test]
+    fn rejects_emails_without_a_usable_from_address() {
+        for raw in [
+            "Subject: Hi\n\nBody",
+            "From: \nSubject: Hi\n\nBody",
+            "From: Maria\n\nBody",
+            "From: <@example.com>\n\nBody",
+            "From: maria@\n\nBody",
+            "From: <maria silva@example.com>\n\nBody",
+        ] {
+            assert!(matches!(InboundEmail::parse(raw), Err(SupportError::InvalidInput(_))), "{:?}", raw);
+        }
+    }
+
+    #[test]
+    fn takes_the_first_mailbox_lowercased() {
+        let email = parse("FROM: \"Silva, Maria\" <Maria@Example.COM>\n\nBody");
+        assert_eq!(email.from_name.as_deref(), Some("Silva, Maria"));
+        assert_eq!(email.from_address, "maria@example.com");
+
+        let email = parse("From: maria@example.com, joao@example.com\n\nBody");
+        assert_eq!(email.from_name, None);
+        assert_eq!(email.from_address, "maria@example.com");
+    }
+
+    #[test]
+    fn unfolds_continued_header_lines() {
+        let email = parse("From: a@example.com\nSubject: Order\n  not delivered\n\tafter two weeks\n\nBody");
+        assert_eq!(email.subject, "Order not delivered after two weeks");
+    }
+
+    #[test]
+    fn headers_without_a_body_parse_with_an_empty_body() {
+        let email = parse("From: a@example.com\r\nSubject: Only headers");
+        assert_eq!(email.subject, "Only headers");
+        assert_eq!(email.text_body, "");
+        assert_eq!(email.ticket_description(), "Only headers");
+    }
+
+    #[test]
+    fn collects_message_ids_and_ignores_malformed_ones() {
+        let email = parse(
+            "From: a@example.com\n\
+             Message-ID: not-bracketed@example.com\n\
+             In-Reply-To: <> <b@example.com\n\
+             References: <r1@example.com> junk <r2@example.com>\n\nBody",
+        );
+        assert_eq!(email.message_id, None);
+        assert!(email.in_reply_to.is_empty());
+        assert_eq!(email.references, vec!["r1@example.com", "r2@example.com"]);
+        assert_eq!(email.thread_refs(), vec!["r2@example.com", "r1@example.com"]);
+    }
+
+    #[test]
+    fn thread_refs_put_in_reply_to_first_without_duplicates() {
+        let email = parse(
+            "From: a@example.com\n\
+             In-Reply-To: <r2@example.com>\n\
+             References: <r1@example.com> <r2@example.com>\n\nBody",
+        );
+        assert_eq!(email.thread_refs(), vec!["r2@example.com", "r1@example.com"]);
+    }
+
+    #[test]
+    fn decodes_encoded_words() {
+        assert_eq!(decode_encoded_words("=?UTF-8?B?Sm/Do28=?= Silva"), "João Silva");
+        assert_eq!(decode_encoded_words("=?iso-8859-1?q?Jos=E9_Mar=EDa?="), "José María");
+        // Whitespace between adjacent encoded words is dropped, around plain text it is kept
+        assert_eq!(decode_encoded_words("=?UTF-8?Q?Ol=C3=A1?= =?UTF-8?Q?_mundo?="), "Olá mundo");
+        assert_eq!(decode_encoded_words("Re: =?UTF-8?Q?Ol=C3=A1?= again"), "Re: Olá again");
+    }
+
+    #[test]
+    fn leaves_malformed_encoded_words_as_they_are() {
+        for value in ["=?UTF-8?X?abc?=", "=?UTF-8?B?not base64!?=", "=?UTF-8?Q?unterminated", "=?", "a =? b"] {
+            assert_eq!(decode_encoded_words(value), value);
+        }
+    }
+
+    #[test]
+    fn decodes_quoted_printable_and_keeps_invalid_escapes() {
+        assert_eq!(decode_quoted_printable("caf=C3=A9 =\nsoft"), "café soft".as_bytes());
+        assert_eq!(decode_quoted_printable("100=ZZ and =4"), b"100=ZZ and =4");
+        assert_eq!(decode_quoted_printable("ends with ="), b"ends with =");
+    }
+
+    #[test]
+    fn prefers_the_plain_text_alternative() {
+        let email = parse(
+            "From: a@example.com\n\
+             Content-Type: multipart/alternative; boundary=\"b1\"\n\n\
+             preamble\n\
+             --b1\n\
+             Content-Type: text/html\n\n\
+             <p>HTML version</p>\n\
+             --b1\n\
+             Content-Type: text/plain; charset=\"utf-8\"\n\n\
+             Plain version\n\
+             --b1--\n\
+             epilogue\n",
+        );
+        assert_eq!(email.text_body, "Plain version");
+    }
+
+    #[test]
+    fn falls_back_to_html_as_text() {
+        let email = parse(
+            "From: a@example.com\n\
+             Content-Type: text/html; charset=utf-8\n\n\
+             <div>Hello&nbsp;there</div><p>Line &lt;two&gt; &amp; more</p>",
+        );
+        assert_eq!(email.text_body, "Hello there\n\nLine <two> & more");
+    }
+
+    #[test]
+    fn skips_attachments_and_decodes_nested_parts() {
+        let email = parse(
+            "From: a@example.com\n\
+             Content-Type: multipart/mixed; boundary=outer\n\n\
+             --outer\n\
+             Content-Type: text/plain\n\
+             Content-Disposition: attachment; filename=log.txt\n\n\
+             attached log\n\
+             --outer\n\
+             Content-Type: multipart/alternative; boundary=inner\n\n\
+             --inner\n\
+             Content-Type: text/plain; charset=iso-8859-1\n\
+             Content-Transfer-Encoding: base64\n\n\
+             T2zhLCBK\n\
+             b3PpIQ==\n\
+             --inner--\n\
+             --outer--\n",
+        );
+        assert_eq!(email.text_body, "Olá, José!");
+    }
+
+    #[test]
+    fn tolerates_broken_mime() {
+        // Undecodable base64 yields no body rather than an error
+        let email = parse(
+            "From: a@example.com\nContent-Type: text/plain\nContent-Transfer-Encoding: base64\n\n@@@not base64@@@",
+        );
+        assert_eq!(email.text_body, "");
+
+        // A multipart body without a boundary parameter has no text
+        let email = parse("From: a@example.com\nContent-Type: multipart/mixed\n\n--x\n\nHello\n--x--");
+        assert_eq!(email.text_body, "");
+
+        // A missing closing delimiter still yields the last part
+        let email = parse("From: a@example.com\nContent-Type: multipart/mixed; boundary=x\n\n--x\n\nHello");
+        assert_eq!(email.text_body, "Hello");
+    }
+
+    #[test]
+    fn splits_parameters_outside_quotes() {
+        assert_eq!(
+            split_params("multipart/mixed; boundary=\"a;b\"; charset=utf-8"),
+            vec!["multipart/mixed", "boundary=\"a;b\"", "charset=utf-8"]
+        );
+    }
+
+    #[test]
+    fn strips_quoted_history() {
+        assert_eq!(strip_quoted_reply("Thanks!\n\nOn Tue, Support wrote:\n> hi"), "Thanks!");
+        assert_eq!(strip_quoted_reply("Fixed\n-----original message-----\nFrom: x"), "Fixed");
+        assert_eq!(strip_quoted_reply("Yes\n> Did it work?\nmore"), "Yes");
+        // Nothing but history is kept whole
+        assert_eq!(strip_quoted_reply("> quoted only\n> still quoted"), "> quoted only\n> still quoted");
+        // "On" lines that don't end the way a client writes them are content
+        assert_eq!(strip_quoted_reply("On Monday it broke again"), "On Monday it broke again");
+    }
+
+    #[test]
+    fn normalizes_text_to_the_validation_limits() {
+        let email = InboundEmail {
+            subject: "é".repeat(MAX_SUBJECT_LENGTH + 10),
+            text_body: "b".repeat(MAX_DESCRIPTION_LENGTH + 1),
+            ..Default::default()
+        };
+        assert_eq!(email.ticket_subject().chars().count(), MAX_SUBJECT_LENGTH);
+        assert_eq!(email.ticket_description().chars().count(), MAX_DESCRIPTION_LENGTH);
+        assert_eq!(email.reply_content().chars().count(), MAX_MESSAGE_LENGTH.min(MAX_DESCRIPTION_LENGTH + 1));
+
+        let blank = InboundEmail { subject: " ".to_string(), text_body: "\n> quoted\n".to_string(), ..Default::default() };
+        assert_eq!(blank.ticket_subject(), "(no subject)");
+        assert_eq!(blank.ticket_description(), "> quoted");
+        assert_eq!(blank.reply_content(), "> quoted");
+
+        let empty = InboundEmail { subject: "Help".to_string(), ..Default::default() };
+        assert_eq!(empty.ticket_description(), "Help");
+        assert_eq!(empty.reply_content(), "Help");
+    }
+}
@@ -0,0 +1,7 @@
+//! Intake channels
+//!
+//! Parsers for the formats tickets arrive in outside the GraphQL API. Each
+//! channel reduces its payload to what ticket intake needs; the repository
+//! does the threading and ticket creation.
+
+pub mod email;
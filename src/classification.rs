@@ -0,0 +1,122 @@
+//! Suggesting priority and category for new tickets
+//!
+//! When a ticket is created without a priority or category, the repository
+//! asks its registered [`TicketClassifier`] for one. Classifiers can be
+//! anything from keyword rules ([`KeywordClassifier`]) to a call out to a
+//! model service. Only omitted fields are filled in; what the classifier
+//! chose is stored in ticket metadata (under the `classification` key) so
+//! suggestions can later be compared with how agents triaged the ticket.
+//!
+//! Creating a ticket never fails because of classification: without a
+//! classifier, or when it errors, omitted fields take their defaults.
+//!
+//! ```rust
+//! use pleme_support::{CreateTicketInput, KeywordClassifier, TicketClassifier, TicketPriority};
+//!
+//! # async fn example() -> pleme_support::Result<()> {
+//! let classifier = KeywordClassifier::default()
+//!     .with_priority("outage", TicketPriority::Urgent)
+//!     .with_category("refund", "billing");
+//!
+//! let input = CreateTicketInput {
+//!     subject: "Refund after the outage".to_string(),
+//!     ..Default::default()
+//! };
+//! let suggestion = classifier.classify("novaskyn", &input).await?;
+//! assert_eq!(suggestion.priority, Some(TicketPriority::Urgent));
+//! assert_eq!(suggestion.category.as_deref(), Some("billing"));
+//! # Ok(())
+//! # }
+//! ```
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{CreateTicketInput, SupportTicket, TicketPriority};
+use crate::Result;
+
+/// Metadata key holding [`ClassificationRecord`]
+pub const CLASSIFICATION_METADATA_KEY: &str = "classification";
+
+/// A classifier's suggestion; `None` leaves the field to its default
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Classification {
+    pub priority: Option<TicketPriority>,
+    pub category: Option<String>,
+    /// Classifier's confidence in the suggestion, from 0 to 1
+    pub confidence: Option<f64>,
+}
+
+#[async_trait]
+pub trait TicketClassifier: Send + Sync {
+    /// Recorded with every classification, e.g. a model name and version
+    fn name(&self) -> &str;
+
+    async fn classify(&self, product: &str, input: &CreateTicketInput) -> Result<Classification>;
+}
+
+/// What a classifier chose for a ticket, as stored in its metadata
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClassificationRecord {
+    pub classifier: String,
+    /// Priority applied from the suggestion; `None` when the caller set one
+    pub priority: Option<TicketPriority>,
+    /// Category applied from the suggestion; `None` when the caller set one
+    pub category: Option<String>,
+    pub confidence: Option<f64>,
+    pub classified_at: DateTime<Utc>,
+}
+
+impl SupportTicket {
+    /// What the classifier chose at creation, if anything
+    pub fn classification(&self) -> Option<ClassificationRecord> {
+        self.metadata
+            .get(CLASSIFICATION_METADATA_KEY)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
+}
+
+/// Suggests from keywords in the subject and description
+///
+/// Matching ignores case; for each field the first rule added that matches wins.
+#[derive(Debug, Clone, Default)]
+pub struct KeywordClassifier {
+    priorities: Vec<(String, TicketPriority)>,
+    categories: Vec<(String, String)>,
+}
+
+impl KeywordClassifier {
+    pub fn with_priority(mut self, keyword: impl Into<String>, priority: TicketPriority) -> Self {
+        self.priorities.push((keyword.into().to_lowercase(), priority));
+        self
+    }
+
+    pub fn with_category(mut self, keyword: impl Into<String>, category: impl Into<String>) -> Self {
+        self.categories.push((keyword.into().to_lowercase(), category.into()));
+        self
+    }
+}
+
+#[async_trait]
+impl TicketClassifier for KeywordClassifier {
+    fn name(&self) -> &str {
+        "keywords"
+    }
+
+    async fn classify(&self, _product: &str, input: &CreateTicketInput) -> Result<Classification> {
+        let text = format!("{}\n{}", input.subject, input.description).to_lowercase();
+        let priority = self
+            .priorities
+            .iter()
+            .find(|(keyword, _)| text.contains(keyword.as_str()))
+            .map(|(_, priority)| *priority);
+        let category = self
+            .categories
+            .iter()
+            .find(|(keyword, _)| text.contains(keyword.as_str()))
+            .map(|(_, category)| category.clone());
+
+        Ok(Classification { priority, category, confidence: None })
+    }
+}
@@ -0,0 +1,57 @@
+//! Time source for business logic
+//!
+//! Every timestamp that feeds SLA math, auto-close and analytics windows is
+//! taken from a [`Clock`] instead of `Utc::now()` or the database's `NOW()`,
+//! so integration tests can freeze and advance time.
+//!
+//! ```rust
+//! use chrono::{Duration, TimeZone, Utc};
+//! use pleme_support::{Clock, MockClock};
+//!
+//! let clock = MockClock::new(Utc.with_ymd_and_hms(2024, 1, 1, 9, 0, 0).unwrap());
+//! clock.advance(Duration::hours(2));
+//! assert_eq!(clock.now(), Utc.with_ymd_and_hms(2024, 1, 1, 11, 0, 0).unwrap());
+//! ```
+
+use chrono::{DateTime, Duration, Utc};
+use std::sync::Mutex;
+
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// Wall-clock time, used by default
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Manually controlled time for tests
+#[derive(Debug)]
+pub struct MockClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl MockClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self { now: Mutex::new(now) }
+    }
+
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner()) = now;
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner()) += by;
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,162 @@
+//! Contractual SLA definitions and monthly compliance reports
+//!
+//! Enterprise accounts negotiate their own SLA terms, separate from the
+//! operational [`SlaPolicy`](crate::SlaPolicy): clocks that only run during
+//! business hours, a grace window, and a mapping of ticket priorities to the
+//! contract's severity levels. Register each account's [`SlaContract`] on the
+//! repository and generate the customer-facing report for a calendar month:
+//!
+//! ```rust,no_run
+//! use chrono::{Duration, NaiveDate, Weekday};
+//! use pleme_support::contract::{BusinessHours, ContractSeverity, SlaContract, SlaContracts};
+//! use pleme_support::{SupportRepository, TicketPriority};
+//! use uuid::Uuid;
+//!
+//! # async fn example(repo: SupportRepository, customer_id: Uuid) -> pleme_support::Result<()> {
+//! let contract = SlaContract {
+//!     customer_ids: vec![customer_id],
+//!     business_hours: BusinessHours::weekdays("America/Sao_Paulo", 9 * 60, 18 * 60),
+//!     grace: Duration::minutes(15),
+//!     severities: vec![ContractSeverity {
+//!         name: "Sev1".to_string(),
+//!         priorities: vec![TicketPriority::Urgent],
+//!         first_response: Some(Duration::hours(1)),
+//!         resolution: Some(Duration::hours(8)),
+//!     }],
+//! };
+//! let repo = repo.with_sla_contracts(SlaContracts::default().with_contract("acme", contract));
+//!
+//! let report = repo.get_contractual_sla_report("novaskyn", "acme", NaiveDate::from_ymd_opt(2026, 9, 1).unwrap()).await?;
+//! report.write_breaches_csv(std::io::stdout())?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Clocks are measured in business time from ticket creation to the first
+//! agent response and to resolution; tickets still open are measured up to
+//! the time the report is generated. Time waiting on the customer is not
+//! paused, matching the typical contract wording.
+
+use async_graphql::SimpleObject;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Write;
+use uuid::Uuid;
+
+pub use crate::business_hours::BusinessHours;
+use crate::models::TicketPriority;
+use crate::{Result, SupportError};
+
+/// A contract severity level and the ticket priorities that map to it
+#[derive(Debug, Clone)]
+pub struct ContractSeverity {
+    /// Name used in the report, e.g. `Sev1`
+    pub name: String,
+    pub priorities: Vec<TicketPriority>,
+    /// Business time allowed before the first agent response; `None` disables the check
+    pub first_response: Option<Duration>,
+    /// Business time allowed until resolution; `None` disables the check
+    pub resolution: Option<Duration>,
+}
+
+/// SLA terms agreed with one customer account
+#[derive(Debug, Clone, Default)]
+pub struct SlaContract {
+    /// Customers whose tickets the contract covers
+    pub customer_ids: Vec<Uuid>,
+    pub business_hours: BusinessHours,
+    /// Added to every target before a breach counts
+    pub grace: Duration,
+    /// Tickets whose priority no severity lists are left out of compliance
+    pub severities: Vec<ContractSeverity>,
+}
+
+impl SlaContract {
+    pub fn severity_for(&self, priority: TicketPriority) -> Option<&ContractSeverity> {
+        self.severities.iter().find(|severity| severity.priorities.contains(&priority))
+    }
+}
+
+/// SLA contracts keyed by account (a customer or organization identifier)
+#[derive(Debug, Clone, Default)]
+pub struct SlaContracts {
+    contracts: HashMap<String, SlaContract>,
+}
+
+impl SlaContracts {
+    pub fn with_contract(mut self, account: impl Into<String>, contract: SlaContract) -> Self {
+        self.contracts.insert(account.into(), contract);
+        self
+    }
+
+    pub fn contract(&self, account: &str) -> Option<&SlaContract> {
+        self.contracts.get(account)
+    }
+}
+
+/// Compliance for one contract severity level
+#[derive(Debug, Clone, Serialize, SimpleObject)]
+pub struct ContractSeverityCompliance {
+    pub severity: String,
+    pub total_tickets: i64,
+    pub met: i64,
+    pub breached: i64,
+    /// Open tickets that have not breached yet; excluded from the rate
+    pub pending: i64,
+    /// Percentage of met tickets among met and breached; `None` when neither
+    pub compliance_rate: Option<f64>,
+}
+
+/// A ticket that breached its contractual SLA
+#[derive(Debug, Clone, Serialize, SimpleObject)]
+pub struct ContractSlaBreach {
+    pub ticket_id: Uuid,
+    pub ticket_ref: String,
+    pub severity: String,
+    pub priority: TicketPriority,
+    pub created_at: DateTime<Utc>,
+    pub first_response_at: Option<DateTime<Utc>>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    /// Business minutes until the first response, or until the report if none yet
+    pub first_response_business_minutes: i64,
+    /// Business minutes until resolution, or until the report if unresolved
+    pub resolution_business_minutes: i64,
+    pub first_response_breached: bool,
+    pub resolution_breached: bool,
+}
+
+/// Contractual SLA compliance of one account for a calendar month
+#[derive(Debug, Clone, Serialize, SimpleObject)]
+pub struct ContractualSlaReport {
+    pub account: String,
+    pub product: String,
+    /// First day of the month, in the contract's timezone
+    pub month: NaiveDate,
+    pub timezone: String,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub generated_at: DateTime<Utc>,
+    pub severities: Vec<ContractSeverityCompliance>,
+    /// Tickets in the period whose priority maps to no severity
+    pub unmapped_tickets: i64,
+    /// Breaching tickets, oldest first
+    pub breaches: Vec<ContractSlaBreach>,
+}
+
+impl ContractualSlaReport {
+    /// Write the breaching tickets as CSV, one row per ticket, for the customer-facing report
+    pub fn write_breaches_csv<W: Write>(&self, writer: W) -> Result<()> {
+        let mut csv_writer = csv::Writer::from_writer(writer);
+        for breach in &self.breaches {
+            csv_writer
+                .serialize(breach)
+                .map_err(|e| SupportError::Internal(format!("Failed to write SLA report: {}", e)))?;
+        }
+        csv_writer
+            .flush()
+            .map_err(|e| SupportError::Internal(format!("Failed to write SLA report: {}", e)))?;
+
+        Ok(())
+    }
+}
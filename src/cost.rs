@@ -0,0 +1,44 @@
+//! Estimated support handling cost
+//!
+//! A deliberately simple model: a fixed cost per ticket plus a cost per agent
+//! message, in the product's currency (see [`ProductLocale`](crate::ProductLocale)).
+//! Good enough to rank accounts by support load in at-risk reviews.
+//!
+//! ```rust
+//! use pleme_support::{HandlingCost, HandlingCosts};
+//!
+//! let costs = HandlingCosts::default().with_cost("novaskyn", HandlingCost { per_ticket: 12.0, per_agent_message: 4.5 });
+//! assert_eq!(costs.cost_for("novaskyn").estimate(2, 4), 42.0);
+//! assert_eq!(costs.cost_for("thai").estimate(2, 4), 0.0);
+//! ```
+
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct HandlingCost {
+    pub per_ticket: f64,
+    pub per_agent_message: f64,
+}
+
+impl HandlingCost {
+    pub fn estimate(&self, tickets: i64, agent_messages: i64) -> f64 {
+        tickets as f64 * self.per_ticket + agent_messages as f64 * self.per_agent_message
+    }
+}
+
+/// Per-product handling costs; products without one cost nothing
+#[derive(Debug, Clone, Default)]
+pub struct HandlingCosts {
+    costs: HashMap<String, HandlingCost>,
+}
+
+impl HandlingCosts {
+    pub fn with_cost(mut self, product: impl Into<String>, cost: HandlingCost) -> Self {
+        self.costs.insert(product.into(), cost);
+        self
+    }
+
+    pub fn cost_for(&self, product: &str) -> HandlingCost {
+        self.costs.get(product).copied().unwrap_or_default()
+    }
+}
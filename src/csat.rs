@@ -0,0 +1,168 @@
+//! Customer satisfaction surveys
+//!
+//! Every CSAT rating is a [`CsatSurvey`]: its score, an optional comment, the
+//! channel it came through and when the customer responded. Surveys reach
+//! customers in two ways:
+//!
+//! - **Survey links**: `send_csat_survey` creates a survey for a resolved
+//!   ticket and returns the survey's token (and a link when
+//!   [`CsatSurveySettings::link_base_url`] is set) to embed in an email or
+//!   in-app prompt. Only the token's SHA-256 is stored; anyone holding the
+//!   token can respond until it expires, without signing in, and answering
+//!   again before then replaces the earlier answer.
+//! - **Direct submission**: a signed-in customer rates their own ticket with
+//!   `submit_csat_feedback`.
+//!
+//! The latest response is mirrored into the ticket's `csat_score`, so
+//! dashboards keep working; [`CsatBreakdown`] aggregates responses by agent,
+//! category or period.
+//!
+//! ```rust
+//! use pleme_support::csat::{hash_survey_token, new_survey_token};
+//!
+//! let token = new_survey_token();
+//! assert_eq!(token.len(), 64);
+//! assert_eq!(hash_survey_token(&token), hash_survey_token(&token));
+//! assert_ne!(hash_survey_token(&token), token);
+//! ```
+
+use async_graphql::{Enum, InputObject, SimpleObject};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// How long survey links stay valid by default
+pub const DEFAULT_SURVEY_VALIDITY_DAYS: i64 = 30;
+
+#[derive(Debug, Clone)]
+pub struct CsatSurveySettings {
+    /// Survey page; links are `{link_base_url}?token={token}`. `None` returns bare tokens
+    pub link_base_url: Option<String>,
+    /// How long a survey link accepts responses
+    pub validity: Duration,
+}
+
+impl Default for CsatSurveySettings {
+    fn default() -> Self {
+        Self {
+            link_base_url: None,
+            validity: Duration::days(DEFAULT_SURVEY_VALIDITY_DAYS),
+        }
+    }
+}
+
+impl CsatSurveySettings {
+    pub fn survey_link(&self, token: &str) -> Option<String> {
+        self.link_base_url.as_ref().map(|base| format!("{}?token={}", base, token))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Enum, Eq, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "csat_survey_channel", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CsatChannel {
+    Email,
+    #[default]
+    Web,
+    InApp,
+    Chat,
+    Sms,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, SimpleObject)]
+pub struct CsatSurvey {
+    pub id: Uuid,
+    pub ticket_id: Uuid,
+    pub product: String,
+    pub customer_id: Uuid,
+    /// Agent credited with the rating: the ticket's resolving agent
+    pub agent_id: Option<Uuid>,
+    pub channel: CsatChannel,
+    #[graphql(skip)]
+    #[serde(skip)]
+    pub token_hash: Option<String>,
+    pub sent_at: DateTime<Utc>,
+    /// `None` for direct submissions
+    pub expires_at: Option<DateTime<Utc>>,
+    pub score: Option<i32>,
+    pub comment: Option<String>,
+    pub responded_at: Option<DateTime<Utc>>,
+    /// External survey tool of an imported response
+    pub source: Option<String>,
+    /// The response's id in `source`
+    pub external_id: Option<String>,
+    /// Original 0-10 score of an imported NPS response; `score` is it scaled to 1-5
+    pub nps_score: Option<i32>,
+}
+
+/// A newly sent survey and the token to reach it
+///
+/// The token is only available here; it cannot be recovered later.
+#[derive(Debug, Clone, SimpleObject)]
+pub struct CsatSurveyLink {
+    pub survey: CsatSurvey,
+    pub token: String,
+    /// Set when the repository has a survey link base URL
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, InputObject)]
+pub struct CsatFeedbackInput {
+    /// 1 (very dissatisfied) to 5 (very satisfied)
+    pub score: i32,
+    pub comment: Option<String>,
+    #[graphql(default)]
+    pub channel: CsatChannel,
+}
+
+/// What CSAT analytics are grouped by
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq)]
+pub enum CsatDimension {
+    /// The agent credited with each rating; unattributed ratings have no key
+    Agent,
+    /// Ticket category; uncategorized tickets have no key
+    Category,
+    /// Calendar day the survey was sent, in the product's timezone
+    Day,
+    Week,
+    Month,
+}
+
+impl CsatDimension {
+    /// `DATE_TRUNC` unit for the period dimensions
+    pub(crate) fn period_unit(&self) -> Option<&'static str> {
+        match self {
+            CsatDimension::Day => Some("day"),
+            CsatDimension::Week => Some("week"),
+            CsatDimension::Month => Some("month"),
+            CsatDimension::Agent | CsatDimension::Category => None,
+        }
+    }
+}
+
+/// CSAT for one agent, category or period
+#[derive(Debug, Clone, Serialize, FromRow, SimpleObject)]
+pub struct CsatBreakdown {
+    /// Agent id, category or period start date (`YYYY-MM-DD`)
+    pub key: Option<String>,
+    /// Surveys sent in the period, including direct submissions
+    pub surveys_sent: i64,
+    pub responses: i64,
+    /// Percentage of sent surveys that were answered
+    pub response_rate: Option<f64>,
+    pub avg_score: Option<f64>,
+    /// Percentage of responses scoring 4 or 5
+    pub satisfied_rate: Option<f64>,
+    pub comments: i64,
+}
+
+/// Random survey link token: 64 hex characters (two v4 UUIDs' worth of randomness)
+pub fn new_survey_token() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// SHA-256 of a survey token, hex encoded, as stored in `csat_surveys.token_hash`
+pub fn hash_survey_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.trim().as_bytes()))
+}
@@ -0,0 +1,126 @@
+//! Human-readable customer info for analytics and exports
+//!
+//! Tickets only store customer ids; the customer records live in the host
+//! service. Services implement [`CustomerDirectory`] over their own store and
+//! register it with `SupportRepository::with_customer_directory`. GraphQL
+//! `customer` fields then resolve names, emails and plans, batched per
+//! request when a `DataLoader<CustomerLoader>` is in the request data:
+//!
+//! ```rust,no_run
+//! use async_graphql::dataloader::DataLoader;
+//! use pleme_support::{CustomerDirectory, CustomerLoader};
+//! use std::sync::Arc;
+//!
+//! # fn example(directory: Arc<dyn CustomerDirectory>, request: async_graphql::Request) {
+//! let loader = DataLoader::new(CustomerLoader::new(directory), tokio::spawn);
+//! let request = request.data(loader);
+//! # }
+//! ```
+
+use async_graphql::dataloader::Loader;
+use async_graphql::SimpleObject;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::{Result, SupportError};
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct CustomerInfo {
+    pub id: Uuid,
+    pub name: Option<String>,
+    pub email: Option<String>,
+    pub plan: Option<String>,
+    pub segment: Option<String>,
+}
+
+#[async_trait]
+pub trait CustomerDirectory: Send + Sync {
+    /// Look up many customers at once; unknown ids are left out of the result
+    async fn lookup(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, CustomerInfo>>;
+
+    /// Customer ids by lowercased email, for matching imported survey
+    /// responses; directories that can't search by email find nobody
+    async fn find_by_email(&self, emails: &[String]) -> Result<HashMap<String, Uuid>> {
+        let _ = emails;
+        Ok(HashMap::new())
+    }
+}
+
+/// Caches another directory's results for a fixed time
+///
+/// Only found customers are cached, so newly created customers show up on
+/// the next lookup.
+pub struct CachedCustomerDirectory {
+    inner: Arc<dyn CustomerDirectory>,
+    ttl: Duration,
+    entries: Mutex<HashMap<Uuid, (Instant, CustomerInfo)>>,
+}
+
+impl CachedCustomerDirectory {
+    pub fn new(inner: Arc<dyn CustomerDirectory>, ttl: Duration) -> Self {
+        Self {
+            inner,
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl CustomerDirectory for CachedCustomerDirectory {
+    async fn lookup(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, CustomerInfo>> {
+        let mut found = HashMap::with_capacity(ids.len());
+        let mut missing = Vec::new();
+        {
+            let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+            entries.retain(|_, (cached_at, _)| cached_at.elapsed() < self.ttl);
+            for id in ids {
+                match entries.get(id) {
+                    Some((_, info)) => {
+                        found.insert(*id, info.clone());
+                    }
+                    None => missing.push(*id),
+                }
+            }
+        }
+
+        if !missing.is_empty() {
+            let fetched = self.inner.lookup(&missing).await?;
+            let now = Instant::now();
+            let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+            for (id, info) in fetched {
+                entries.insert(id, (now, info.clone()));
+                found.insert(id, info);
+            }
+        }
+
+        Ok(found)
+    }
+
+    async fn find_by_email(&self, emails: &[String]) -> Result<HashMap<String, Uuid>> {
+        self.inner.find_by_email(emails).await
+    }
+}
+
+/// Per-request batching of directory lookups for GraphQL
+pub struct CustomerLoader {
+    directory: Arc<dyn CustomerDirectory>,
+}
+
+impl CustomerLoader {
+    pub fn new(directory: Arc<dyn CustomerDirectory>) -> Self {
+        Self { directory }
+    }
+}
+
+impl Loader<Uuid> for CustomerLoader {
+    type Value = CustomerInfo;
+    type Error = Arc<SupportError>;
+
+    async fn load(&self, keys: &[Uuid]) -> std::result::Result<HashMap<Uuid, CustomerInfo>, Self::Error> {
+        self.directory.lookup(keys).await.map_err(Arc::new)
+    }
+}
@@ -0,0 +1,105 @@
+//! Ticket escalation
+//!
+//! An [`EscalationRule`] raises the `escalation_level` of tickets that have
+//! gone too long without attention, e.g. "URGENT tickets unanswered for 30
+//! minutes go to level 1, notify the on-call team's leads and bump priority".
+//! `run_escalations`, driven by a scheduler (or `pleme-support-cli
+//! maintenance run-escalations`), applies a product's active rules:
+//!
+//! - A ticket qualifies while it is open and not waiting on the customer, is
+//!   below the rule's level, matches its priority, and was created at least
+//!   `after_minutes` ago without a first response ([`EscalationCondition::Unanswered`])
+//!   or a resolution ([`EscalationCondition::Unresolved`]).
+//! - Rules run highest level first, so a ticket that qualifies for several
+//!   levels jumps straight to the highest one. Levels never go down.
+//! - Each escalation is audited, published as `ticket.updated`, and sent to
+//!   the [`NotificationSink`](crate::NotificationSink) as a [`TicketEscalated`]
+//!   addressed to the LEAD agents of the rule's team, or of the assignee's team.
+//!
+//! Nothing escalates while a maintenance window is active.
+
+use async_graphql::{Enum, InputObject, SimpleObject};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::models::TicketPriority;
+
+/// What a ticket has gone without for a rule to escalate it
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "escalation_condition", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EscalationCondition {
+    /// No agent has responded yet
+    Unanswered,
+    /// The ticket has not been resolved
+    Unresolved,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, SimpleObject)]
+pub struct EscalationRule {
+    pub id: Uuid,
+    pub product: String,
+    pub name: String,
+    /// Escalation level tickets are raised to
+    pub level: i32,
+    pub condition: EscalationCondition,
+    /// Only tickets of this priority; `None` matches any
+    pub priority: Option<TicketPriority>,
+    /// Minutes since creation before the ticket escalates
+    pub after_minutes: i32,
+    /// Team whose leads are notified; `None` means the assignee's team
+    pub notify_team: Option<String>,
+    /// Raise the ticket's priority one step when escalating
+    pub bump_priority: bool,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, InputObject)]
+pub struct CreateEscalationRuleInput {
+    pub name: String,
+    pub level: i32,
+    pub condition: EscalationCondition,
+    pub priority: Option<TicketPriority>,
+    pub after_minutes: i32,
+    pub notify_team: Option<String>,
+    #[graphql(default)]
+    pub bump_priority: bool,
+}
+
+/// A ticket raised to a new escalation level
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SimpleObject)]
+pub struct TicketEscalated {
+    pub product: String,
+    pub ticket_id: Uuid,
+    pub ticket_ref: String,
+    pub rule_id: Uuid,
+    pub rule_name: String,
+    pub previous_level: i32,
+    pub level: i32,
+    pub previous_priority: TicketPriority,
+    pub priority: TicketPriority,
+    /// Leads to notify; empty when the team has none
+    pub lead_ids: Vec<Uuid>,
+    pub escalated_at: DateTime<Utc>,
+}
+
+impl TicketPriority {
+    /// The next priority up, or the same for URGENT
+    ///
+    /// ```rust
+    /// use pleme_support::TicketPriority;
+    ///
+    /// assert_eq!(TicketPriority::Medium.raised(), TicketPriority::High);
+    /// assert_eq!(TicketPriority::Urgent.raised(), TicketPriority::Urgent);
+    /// ```
+    pub fn raised(self) -> Self {
+        match self {
+            TicketPriority::Low => TicketPriority::Medium,
+            TicketPriority::Medium => TicketPriority::High,
+            TicketPriority::High | TicketPriority::Urgent => TicketPriority::Urgent,
+        }
+    }
+}
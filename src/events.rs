@@ -0,0 +1,383 @@
+//! Ticket lifecycle events for other services
+//!
+//! With a [`SupportEventPublisher`] configured (`with_event_publisher`), the
+//! repository publishes a [`SupportEvent`] after each of these commits:
+//!
+//! | Event | When |
+//! |-------|------|
+//! | `ticket.created` | A ticket is created (CSV imports excepted), including follow-ups to closed tickets |
+//! | `ticket.updated` | A ticket is updated, singly or in bulk, or a repeat submission collapses into it |
+//! | `ticket.resolved` | An update enters `RESOLVED`, or `CLOSED` without resolving first; follows its `ticket.updated` |
+//! | `ticket.sla_breached` | A ticket is newly flagged as breaching its SLA, by a [breach detection](crate::breach_detector) run or a late first response |
+//! | `message.added` | A message or internal note is added |
+//! | `automation.triggered` | An [automation](crate::automations) with an `EMIT_WEBHOOK` action ran |
+//!
+//! Events are published in the background, so a slow or failing subscriber
+//! never fails or delays the ticket operation. Events of one operation are
+//! published in order; events of different operations may arrive out of
+//! order, so consumers should compare `occurredAt`. When the publisher gives
+//! up on an event it is kept as a [dead letter](crate::integrations::dead_letter)
+//! with source [`EVENT_DEAD_LETTER_SOURCE`]; register [`EventRedelivery`] for
+//! that source to retry it.
+//!
+//! With the `webhooks` feature, `WebhookPublisher` POSTs each event as JSON,
+//! signed with [`sign_webhook`], retrying per [`RetryPolicy`]. Receivers check
+//! the `X-Pleme-Signature` header against `X-Pleme-Timestamp` and the raw body:
+//!
+//! ```rust
+//! use pleme_support::events::{sign_webhook, verify_webhook};
+//!
+//! let body = br#"{"type":"ticket.created"}"#;
+//! let signature = sign_webhook("s3cret", 1_760_000_000, body);
+//! assert!(signature.starts_with("sha256="));
+//! assert!(verify_webhook("s3cret", 1_760_000_000, body, &signature));
+//! assert!(!verify_webhook("s3cret", 1_760_000_001, body, &signature));
+//! ```
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::integrations::dead_letter::{DeadLetter, DeadLetterHandler, NewDeadLetter};
+use crate::models::{SupportTicket, TicketMessage};
+use crate::{Result, SupportError};
+
+/// Dead letter source of events a publisher gave up on
+pub const EVENT_DEAD_LETTER_SOURCE: &str = "events";
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum SupportEventType {
+    #[serde(rename = "ticket.created")]
+    TicketCreated,
+    #[serde(rename = "ticket.updated")]
+    TicketUpdated,
+    #[serde(rename = "ticket.resolved")]
+    TicketResolved,
+    #[serde(rename = "ticket.sla_breached")]
+    SlaBreached,
+    #[serde(rename = "message.added")]
+    MessageAdded,
+    #[serde(rename = "automation.triggered")]
+    AutomationTriggered,
+}
+
+impl SupportEventType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SupportEventType::TicketCreated => "ticket.created",
+            SupportEventType::TicketUpdated => "ticket.updated",
+            SupportEventType::TicketResolved => "ticket.resolved",
+            SupportEventType::SlaBreached => "ticket.sla_breached",
+            SupportEventType::MessageAdded => "message.added",
+            SupportEventType::AutomationTriggered => "automation.triggered",
+        }
+    }
+}
+
+/// A ticket lifecycle event, as published
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SupportEvent {
+    /// Unique per event; redeliveries keep it, so consumers can deduplicate
+    pub id: Uuid,
+    #[serde(rename = "type")]
+    pub event_type: SupportEventType,
+    pub product: String,
+    pub ticket_id: Uuid,
+    pub occurred_at: DateTime<Utc>,
+    /// The ticket after the change; absent on `message.added`
+    pub ticket: Option<SupportTicket>,
+    /// The added message, on `message.added`
+    pub message: Option<TicketMessage>,
+    /// The rule that emitted an `automation.triggered` event
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub automation: Option<AutomationFired>,
+    /// Agents the message @mentions, on `message.added`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mentions: Vec<Uuid>,
+}
+
+/// Which automation emitted an `automation.triggered` event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutomationFired {
+    pub rule_id: Uuid,
+    pub rule_name: String,
+    /// The action's value, for receivers to route on
+    pub label: String,
+}
+
+impl SupportEvent {
+    pub fn ticket(event_type: SupportEventType, ticket: &SupportTicket, occurred_at: DateTime<Utc>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            event_type,
+            product: ticket.product.clone(),
+            ticket_id: ticket.id,
+            occurred_at,
+            ticket: Some(ticket.clone()),
+            message: None,
+            automation: None,
+            mentions: Vec::new(),
+        }
+    }
+
+    pub fn message_added(product: &str, message: &TicketMessage) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            event_type: SupportEventType::MessageAdded,
+            product: product.to_string(),
+            ticket_id: message.ticket_id,
+            occurred_at: message.created_at,
+            ticket: None,
+            message: Some(message.clone()),
+            automation: None,
+            mentions: Vec::new(),
+        }
+    }
+
+    /// The same event, listing the agents its message mentions
+    pub fn with_mentions(mut self, mentions: Vec<Uuid>) -> Self {
+        self.mentions = mentions;
+        self
+    }
+
+    /// An `automation.triggered` event carrying the ticket as of the action
+    pub fn automation_triggered(ticket: &SupportTicket, fired: AutomationFired, occurred_at: DateTime<Utc>) -> Self {
+        Self {
+            automation: Some(fired),
+            ..Self::ticket(SupportEventType::AutomationTriggered, ticket, occurred_at)
+        }
+    }
+
+    /// The dead letter to keep when publishing failed after `attempts`
+    pub fn dead_letter(&self, attempts: i32, error: &SupportError) -> Result<NewDeadLetter> {
+        let payload = serde_json::to_value(self)
+            .map_err(|e| SupportError::Internal(format!("Failed to encode support event: {}", e)))?;
+        Ok(NewDeadLetter {
+            product: self.product.clone(),
+            source: EVENT_DEAD_LETTER_SOURCE.to_string(),
+            event_type: self.event_type.as_str().to_string(),
+            payload,
+            ticket_id: Some(self.ticket_id),
+            attempts,
+            last_error: error.to_string(),
+        })
+    }
+}
+
+/// Destination for ticket lifecycle events, e.g. webhooks or a message bus
+#[async_trait]
+pub trait SupportEventPublisher: Send + Sync {
+    /// Deliver `event`, retrying as the publisher sees fit; an error means it gave up
+    async fn publish(&self, event: &SupportEvent) -> Result<()>;
+
+    /// Most delivery attempts `publish` makes, recorded on dead letters
+    fn max_attempts(&self) -> u32 {
+        1
+    }
+}
+
+/// Redelivers dead-lettered events through a publisher
+///
+/// Register it for [`EVENT_DEAD_LETTER_SOURCE`] in the repository's
+/// `DeadLetterHandlers`.
+pub struct EventRedelivery {
+    publisher: Arc<dyn SupportEventPublisher>,
+}
+
+impl EventRedelivery {
+    pub fn new(publisher: Arc<dyn SupportEventPublisher>) -> Self {
+        Self { publisher }
+    }
+}
+
+#[async_trait]
+impl DeadLetterHandler for EventRedelivery {
+    async fn redeliver(&self, letter: &DeadLetter) -> Result<()> {
+        let event: SupportEvent = serde_json::from_value(letter.payload.clone())
+            .map_err(|e| SupportError::InvalidInput(format!("Dead letter {} is not a support event: {}", letter.id, e)))?;
+        self.publisher.publish(&event).await
+    }
+}
+
+/// Exponential backoff between delivery attempts
+///
+/// ```rust
+/// use pleme_support::events::RetryPolicy;
+/// use std::time::Duration;
+///
+/// let retry = RetryPolicy::default();
+/// assert_eq!(retry.backoff(1), Duration::from_millis(500));
+/// assert_eq!(retry.backoff(3), Duration::from_secs(2));
+/// assert_eq!(retry.backoff(20), Duration::from_secs(30));
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct RetryPolicy {
+    /// Attempts in total, including the first
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Delay after failed attempt number `attempt` (from 1): doubling from
+    /// `initial_backoff`, capped at `max_backoff`
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+/// `X-Pleme-Signature` value for a webhook body: `sha256=` and the hex
+/// HMAC-SHA256 of `"{timestamp}.{body}"` keyed with the endpoint's secret
+pub fn sign_webhook(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mac = webhook_mac(secret, timestamp, body).finalize().into_bytes();
+    let hex: String = mac.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("sha256={}", hex)
+}
+
+/// Whether `signature` is `body`'s signature at `timestamp`, compared in
+/// constant time
+///
+/// Receivers should also reject timestamps too far from their own clock, so
+/// captured requests can't be replayed later.
+pub fn verify_webhook(secret: &str, timestamp: i64, body: &[u8], signature: &str) -> bool {
+    let Some(hex) = signature.strip_prefix("sha256=") else {
+        return false;
+    };
+    if hex.len() != 64 || !hex.is_ascii() {
+        return false;
+    }
+    let Ok(expected) = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+        .collect::<std::result::Result<Vec<u8>, _>>()
+    else {
+        return false;
+    };
+    webhook_mac(secret, timestamp, body).verify_slice(&expected).is_ok()
+}
+
+fn webhook_mac(secret: &str, timestamp: i64, body: &[u8]) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac
+}
+
+#[cfg(feature = "webhooks")]
+pub use webhook::WebhookPublisher;
+
+#[cfg(feature = "webhooks")]
+mod webhook {
+    use super::*;
+
+    /// POSTs events as signed JSON to one endpoint
+    ///
+    /// Connection errors, timeouts, `429` and `5xx` responses are retried per
+    /// the [`RetryPolicy`]; any other non-`2xx` response fails at once, since
+    /// resending the same body won't change it.
+    pub struct WebhookPublisher {
+        client: reqwest::Client,
+        url: String,
+        secret: String,
+        retry: RetryPolicy,
+    }
+
+    impl WebhookPublisher {
+        pub fn new(url: impl Into<String>, secret: impl Into<String>) -> Self {
+            Self {
+                client: reqwest::Client::builder()
+                    .timeout(Duration::from_secs(10))
+                    .build()
+                    .unwrap_or_default(),
+                url: url.into(),
+                secret: secret.into(),
+                retry: RetryPolicy::default(),
+            }
+        }
+
+        pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+            self.retry = retry;
+            self
+        }
+
+        /// Use `client`, e.g. for proxies or other timeouts
+        pub fn with_client(mut self, client: reqwest::Client) -> Self {
+            self.client = client;
+            self
+        }
+
+        /// One delivery attempt; `Err((retryable, error))` on failure
+        async fn attempt(&self, event: &SupportEvent, body: &[u8]) -> std::result::Result<(), (bool, String)> {
+            let timestamp = Utc::now().timestamp();
+            let response = self
+                .client
+                .post(&self.url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header("X-Pleme-Event", event.event_type.as_str())
+                .header("X-Pleme-Delivery", event.id.to_string())
+                .header("X-Pleme-Timestamp", timestamp.to_string())
+                .header("X-Pleme-Signature", sign_webhook(&self.secret, timestamp, body))
+                .body(body.to_vec())
+                .send()
+                .await
+                .map_err(|e| (true, e.to_string()))?;
+
+            let status = response.status();
+            if status.is_success() {
+                Ok(())
+            } else {
+                let retryable = status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS;
+                Err((retryable, format!("HTTP {}", status)))
+            }
+        }
+    }
+
+    #[async_trait]
+    impl SupportEventPublisher for WebhookPublisher {
+        async fn publish(&self, event: &SupportEvent) -> Result<()> {
+            let body = serde_json::to_vec(event)
+                .map_err(|e| SupportError::Internal(format!("Failed to encode support event: {}", e)))?;
+
+            let mut attempt = 1;
+            loop {
+                match self.attempt(event, &body).await {
+                    Ok(()) => return Ok(()),
+                    Err((retryable, error)) => {
+                        if !retryable || attempt >= self.retry.max_attempts {
+                            return Err(SupportError::Internal(format!(
+                                "Webhook delivery of {} to {} failed after {} attempts: {}",
+                                event.id, self.url, attempt, error
+                            )));
+                        }
+                        tracing::debug!("Webhook delivery of {} failed (attempt {}): {}", event.id, attempt, error);
+                        tokio::time::sleep(self.retry.backoff(attempt)).await;
+                        attempt += 1;
+                    }
+                }
+            }
+        }
+
+        fn max_attempts(&self) -> u32 {
+            self.retry.max_attempts
+        }
+    }
+}
@@ -0,0 +1,344 @@
+//! CSV exports of tickets and dashboard metrics
+//!
+//! Compliance requests and offline analysis want spreadsheets rather than
+//! GraphQL. The repository writes exports straight to any `Write`:
+//!
+//! ```rust,no_run
+//! use pleme_support::{ExportFormat, SupportRepository, TicketFilter};
+//! use std::fs::File;
+//!
+//! # async fn example(repo: SupportRepository) -> pleme_support::Result<()> {
+//! let file = File::create("novaskyn-tickets.csv").unwrap();
+//! let rows = repo
+//!     .export_tickets_csv("novaskyn", &TicketFilter::default(), ExportFormat::Excel, file)
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Tickets are streamed to the writer in keyset-paged batches, so exports
+//! of any size run in bounded memory. Dashboard metrics are written in long
+//! form, one `section,dimension,metric,value` row per figure, so every
+//! section of the dashboard fits one sheet.
+//!
+//! For the console, `exportSupportTickets` and `exportSupportDashboardMetrics`
+//! generate the file, keep it for [`EXPORT_TTL_HOURS`] and return a
+//! [`SupportExport`] handle. Services serve the content from
+//! [`SupportRepository::get_export_file`](crate::SupportRepository::get_export_file)
+//! on their own authenticated download route. These stored exports are built
+//! in memory and kept in the database whole; write very large ticket exports
+//! to a file with `export_tickets_csv` instead.
+//!
+//! Text cells that start with `=`, `+`, `-`, `@`, a tab or a carriage return
+//! are prefixed with `'`, so spreadsheets show customer-written subjects and
+//! tags as text instead of evaluating them as formulas.
+
+use async_graphql::{Enum, SimpleObject};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::io::Write;
+use uuid::Uuid;
+
+use crate::models::{CrmCoreSupportDashboardMetrics, SupportTicket};
+use crate::{Result, SupportError};
+
+/// How long generated exports stay downloadable
+pub const EXPORT_TTL_HOURS: i64 = 24;
+
+/// Tickets fetched per batch while streaming an export
+pub const EXPORT_PAGE_SIZE: i64 = 500;
+
+/// Content type of every export format
+pub const EXPORT_CONTENT_TYPE: &str = "text/csv; charset=utf-8";
+
+/// Byte order mark that tells Excel the file is UTF-8
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+#[derive(Debug, Clone, Copy, Default, Enum, Eq, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ExportFormat {
+    /// Plain RFC 4180 CSV
+    #[default]
+    Csv,
+    /// CSV that Excel opens with accents intact: UTF-8 byte order mark, CRLF line ends
+    Excel,
+}
+
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ExportKind {
+    Tickets,
+    DashboardMetrics,
+    SlaBreachDigest,
+    MetricsHistory,
+}
+
+impl ExportKind {
+    fn file_stem(&self) -> &'static str {
+        match self {
+            ExportKind::Tickets => "tickets",
+            ExportKind::DashboardMetrics => "dashboard-metrics",
+            ExportKind::SlaBreachDigest => "sla-breach-digest",
+            ExportKind::MetricsHistory => "metrics-history",
+        }
+    }
+}
+
+/// A generated export, downloadable until `expires_at`
+#[derive(Debug, Clone, FromRow, SimpleObject)]
+pub struct SupportExport {
+    /// Download handle for `SupportRepository::get_export_file`
+    pub id: Uuid,
+    pub product: String,
+    pub kind: ExportKind,
+    pub format: ExportFormat,
+    pub filename: String,
+    /// Data rows, not counting the header
+    pub row_count: i64,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Content of a generated export, for serving as a download
+#[derive(Debug, Clone)]
+pub struct ExportFile {
+    pub filename: String,
+    pub content_type: &'static str,
+    pub content: Vec<u8>,
+}
+
+/// File name for an export generated at `at`, e.g. `novaskyn-tickets-20260317-0930.csv`
+pub(crate) fn export_filename(product: &str, kind: ExportKind, at: DateTime<Utc>) -> String {
+    format!("{}-{}-{}.csv", product, kind.file_stem(), at.format("%Y%m%d-%H%M"))
+}
+
+/// CSV writer for `format`, with the byte order mark already written for Excel
+pub fn csv_writer<W: Write>(mut writer: W, format: ExportFormat) -> Result<csv::Writer<W>> {
+    let mut builder = csv::WriterBuilder::new();
+    if format == ExportFormat::Excel {
+        writer.write_all(UTF8_BOM).map_err(|e| csv_error(e.into()))?;
+        builder.terminator(csv::Terminator::CRLF);
+    }
+    Ok(builder.from_writer(writer))
+}
+
+pub(crate) fn csv_error(e: csv::Error) -> SupportError {
+    SupportError::Internal(format!("Failed to write CSV: {}", e))
+}
+
+/// Header of ticket exports
+pub const TICKET_EXPORT_COLUMNS: &[&str] = &[
+    "ticket_ref",
+    "id",
+    "customer_id",
+    "subject",
+    "status",
+    "priority",
+    "category",
+    "channel",
+    "assigned_to",
+    "customer_plan",
+    "locale",
+    "tags",
+    "created_at",
+    "first_response_at",
+    "resolved_at",
+    "closed_at",
+    "sla_breach",
+    "sla_breached_at",
+    "csat_score",
+    "occurrence_count",
+    "reopen_count",
+];
+
+pub(crate) fn ticket_record(ticket: &SupportTicket) -> Vec<String> {
+    vec![
+        ticket.ticket_ref.clone(),
+        ticket.id.to_string(),
+        ticket.customer_id.to_string(),
+        text_cell(&ticket.subject),
+        ticket.status.as_str().to_string(),
+        ticket.priority.as_str().to_string(),
+        text_cell(&optional(ticket.category.as_ref())),
+        ticket.channel.as_str().to_string(),
+        optional(ticket.assigned_to),
+        text_cell(&optional(ticket.customer_plan.as_ref())),
+        text_cell(&optional(ticket.locale.as_ref())),
+        text_cell(&ticket.tags.join(";")),
+        timestamp(Some(ticket.created_at)),
+        timestamp(ticket.first_response_at),
+        timestamp(ticket.resolved_at),
+        timestamp(ticket.closed_at),
+        ticket.sla_breach.to_string(),
+        timestamp(ticket.sla_breached_at),
+        optional(ticket.csat_score),
+        ticket.occurrence_count.to_string(),
+        ticket.reopen_count.to_string(),
+    ]
+}
+
+/// Header of dashboard metric exports
+pub const DASHBOARD_EXPORT_COLUMNS: &[&str] = &["section", "dimension", "metric", "value"];
+
+/// Every dashboard figure as a `section,dimension,metric,value` row
+pub(crate) fn dashboard_records(metrics: &CrmCoreSupportDashboardMetrics) -> Vec<[String; 4]> {
+    let mut rows = MetricRows::default();
+
+    let o = &metrics.overview;
+    rows.section("overview", "");
+    rows.count("total_active_tickets", o.total_active_tickets);
+    rows.count("new_tickets_today", o.new_tickets_today);
+    rows.count("resolved_tickets_today", o.resolved_tickets_today);
+    rows.value("avg_first_response_time_minutes", o.avg_first_response_time_minutes);
+    rows.value("avg_resolution_time_hours", o.avg_resolution_time_hours);
+    rows.value("first_contact_resolution_rate", o.first_contact_resolution_rate);
+    rows.value("sla_compliance_rate", o.sla_compliance_rate);
+    rows.count("sla_breach_count", o.sla_breach_count);
+    rows.value("avg_csat_score", o.avg_csat_score);
+    rows.value("reopen_rate", o.reopen_rate);
+
+    for s in &metrics.ticket_by_status {
+        rows.section("ticket_by_status", &s.status);
+        rows.count("count", s.count);
+    }
+    for p in &metrics.ticket_by_priority {
+        rows.section("ticket_by_priority", &p.priority);
+        rows.count("count", p.count);
+    }
+
+    let sla = &metrics.sla_metrics;
+    rows.section("sla", "");
+    rows.count("total_tickets", sla.total_tickets);
+    rows.count("tickets_meeting_sla", sla.tickets_meeting_sla);
+    rows.count("tickets_breaching_sla", sla.tickets_breaching_sla);
+    rows.value("compliance_rate", Some(sla.compliance_rate));
+    rows.value("avg_first_response_minutes", sla.avg_first_response_minutes);
+    rows.value("avg_resolution_hours", sla.avg_resolution_hours);
+    for (section, breakdowns) in [("sla_by_priority", &sla.by_priority), ("sla_by_category", &sla.by_category)] {
+        for b in breakdowns {
+            rows.section(section, b.key.as_deref().unwrap_or(""));
+            rows.count("total_tickets", b.total_tickets);
+            rows.count("tickets_meeting_sla", b.tickets_meeting_sla);
+            rows.count("tickets_breaching_sla", b.tickets_breaching_sla);
+            rows.value("compliance_rate", Some(b.compliance_rate));
+            rows.value("avg_first_response_minutes", b.avg_first_response_minutes);
+            rows.value("avg_resolution_hours", b.avg_resolution_hours);
+        }
+    }
+
+    let r = &metrics.response_metrics;
+    rows.section("response", "");
+    rows.value("avg_first_response_minutes", r.avg_first_response_minutes);
+    rows.value("median_first_response_minutes", r.median_first_response_minutes);
+    rows.value("avg_response_minutes", r.avg_response_minutes);
+    rows.value("median_response_minutes", r.median_response_minutes);
+    rows.value("avg_resolution_hours", r.avg_resolution_hours);
+    rows.value("median_resolution_hours", r.median_resolution_hours);
+    rows.value("avg_customer_wait_hours", r.avg_customer_wait_hours);
+    rows.value("median_customer_wait_hours", r.median_customer_wait_hours);
+    rows.value("avg_reply_to_view_minutes", r.avg_reply_to_view_minutes);
+    rows.value("median_reply_to_view_minutes", r.median_reply_to_view_minutes);
+
+    for a in &metrics.top_agents {
+        rows.section("top_agents", &a.agent_id);
+        rows.text("agent_name", &a.agent_name);
+        rows.count("tickets_assigned", a.tickets_assigned);
+        rows.count("tickets_resolved", a.tickets_resolved);
+        rows.value("avg_first_response_minutes", a.avg_first_response_minutes);
+        rows.value("avg_resolution_hours", a.avg_resolution_hours);
+        rows.value("csat_score", a.csat_score);
+    }
+
+    for t in &metrics.ticket_trends {
+        rows.section("ticket_trends", &t.date);
+        rows.count("new_tickets", t.new_tickets);
+        rows.count("resolved_tickets", t.resolved_tickets);
+        rows.count("active_tickets", t.active_tickets);
+    }
+
+    for (section, points) in [("csat_trends_daily", &metrics.csat_trends.daily), ("csat_trends_weekly", &metrics.csat_trends.weekly)] {
+        for p in points {
+            rows.section(section, &p.date);
+            rows.count("surveys_sent", p.surveys_sent);
+            rows.count("responses", p.responses);
+            rows.value("response_rate", p.response_rate);
+            rows.value("avg_score", p.avg_score);
+            for (score, count) in (1..).zip(&p.score_distribution) {
+                rows.count(&format!("score_{}", score), *count);
+            }
+        }
+    }
+
+    for stage in &metrics.status_funnel.stages {
+        rows.section("status_funnel", &stage.status);
+        rows.count("tickets_entered", stage.tickets_entered);
+        rows.count("tickets_remaining", stage.tickets_remaining);
+        rows.value("reached_rate", Some(stage.reached_rate));
+        rows.value("median_minutes_in_status", stage.median_minutes_in_status);
+    }
+    for transition in &metrics.status_funnel.transitions {
+        rows.section("status_transitions", &format!("{}->{}", transition.from_status, transition.to_status));
+        rows.count("count", transition.count);
+        rows.value("percentage", Some(transition.percentage));
+    }
+
+    for c in &metrics.channel_metrics {
+        rows.section("channels", &c.channel);
+        rows.count("total_tickets", c.total_tickets);
+        rows.count("open_tickets", c.open_tickets);
+        rows.count("resolved_tickets", c.resolved_tickets);
+        rows.value("volume_percentage", Some(c.volume_percentage));
+        rows.value("avg_first_response_minutes", c.avg_first_response_minutes);
+        rows.value("avg_resolution_hours", c.avg_resolution_hours);
+        rows.count("sla_breach_count", c.sla_breach_count);
+        rows.value("avg_csat_score", c.avg_csat_score);
+    }
+
+    rows.rows
+}
+
+/// Accumulates rows for the current section and dimension
+#[derive(Default)]
+struct MetricRows {
+    section: &'static str,
+    dimension: String,
+    rows: Vec<[String; 4]>,
+}
+
+impl MetricRows {
+    fn section(&mut self, section: &'static str, dimension: &str) {
+        self.section = section;
+        self.dimension = text_cell(dimension);
+    }
+
+    fn text(&mut self, metric: &str, value: &str) {
+        self.rows.push([self.section.to_string(), self.dimension.clone(), metric.to_string(), value.to_string()]);
+    }
+
+    fn count(&mut self, metric: &str, value: i64) {
+        self.text(metric, &value.to_string());
+    }
+
+    fn value(&mut self, metric: &str, value: Option<f64>) {
+        self.text(metric, &optional(value));
+    }
+}
+
+/// `value`, prefixed with `'` when a spreadsheet would read it as a formula
+pub(crate) fn text_cell(value: &str) -> String {
+    if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+fn timestamp(value: Option<DateTime<Utc>>) -> String {
+    optional(value.map(|t| t.to_rfc3339()))
+}
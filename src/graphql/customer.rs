@@ -0,0 +1,270 @@
+//! Customer portal surface
+//!
+//! Every resolver that takes a ticket is scoped to the signed-in customer's
+//! own tickets, taking the customer id from the request's
+//! [`ViewerRole::Customer`] rather than from arguments; other tickets are
+//! `Unauthorized`. Messages are public ones only, and the [`FieldGuard`]
+//! hides assignment and metadata from customers.
+//!
+//! Survey links, intake API keys and help article suggestions need no
+//! signed-in customer: the token or key is the credential, and articles are
+//! public content.
+//!
+//! [`FieldGuard`]: crate::FieldGuard
+
+use async_graphql::{Context, ErrorExtensions, InputObject, Object, Result as GraphQLResult};
+use uuid::Uuid;
+
+use crate::csat::{CsatFeedbackInput, CsatSurvey};
+use crate::guard::{ViewerOrganization, ViewerRole};
+use crate::intake::{ClientInfo, IntakeTelemetry, SubmitterInfo};
+use crate::kb::ArticleSuggestion;
+use crate::models::{
+    AddTicketMessageInput, CreateTicketInput, MessageContentFormat, MessageVisibility, SupportTicket, TicketChannel, TicketFilter, TicketMessage, TicketStatus,
+    TicketView,
+};
+use crate::pagination::{TicketConnection, TicketCursor, MAX_PAGE_SIZE};
+use crate::repository::SupportRepository;
+use crate::system::repository;
+use crate::validation;
+use crate::SupportError;
+use super::ticket_projection;
+
+/// A ticket opened by the signed-in customer
+#[derive(Debug, Clone, InputObject)]
+pub struct CustomerTicketInput {
+    pub subject: String,
+    pub description: String,
+    pub category: Option<String>,
+    /// Customer's language as detected by the portal (BCP 47)
+    pub locale: Option<String>,
+    /// App version, platform and device the ticket is filed from
+    pub client: Option<ClientInfo>,
+    /// Sent again with retries so they return the first attempt's ticket
+    pub idempotency_key: Option<String>,
+}
+
+/// Customer portal queries
+#[derive(Default)]
+pub struct CustomerSupportQueries;
+
+#[Object(name = "CustomerSupportQuery", extends)]
+impl CustomerSupportQueries {
+    /// The signed-in customer's tickets, newest first, as a Relay connection
+    ///
+    /// `first` defaults to 20 and is capped at 100.
+    async fn my_support_tickets(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        status: Option<TicketStatus>,
+        first: Option<i64>,
+        after: Option<String>,
+    ) -> GraphQLResult<TicketConnection> {
+        let customer_id = ViewerRole::customer_id(ctx)?;
+        let support_repo = repository(ctx)?;
+
+        let filter = TicketFilter { customer_id: Some(customer_id), status, ..Default::default() };
+        let after = after.as_deref().map(TicketCursor::decode).transpose()?;
+
+        let selection = ctx.look_ahead();
+
+        let connection = support_repo
+            .list_page_projected(
+                &product,
+                &filter,
+                first.unwrap_or(20).clamp(0, MAX_PAGE_SIZE),
+                after,
+                ticket_projection(selection.field("edges").field("node")),
+                selection.field("totalCount").exists(),
+            )
+            .await?;
+        Ok(connection)
+    }
+
+    /// One of the signed-in customer's tickets
+    async fn my_support_ticket(&self, ctx: &Context<'_>, id: Uuid) -> GraphQLResult<SupportTicket> {
+        let customer_id = ViewerRole::customer_id(ctx)?;
+        let support_repo = repository(ctx)?;
+
+        let ticket = own_ticket(support_repo, customer_id, id).await?;
+        Ok(ticket)
+    }
+
+    /// Public messages on one of the signed-in customer's tickets
+    async fn my_ticket_messages(&self, ctx: &Context<'_>, ticket_id: Uuid) -> GraphQLResult<Vec<TicketMessage>> {
+        let customer_id = ViewerRole::customer_id(ctx)?;
+        let support_repo = repository(ctx)?;
+
+        own_ticket(support_repo, customer_id, ticket_id).await?;
+        let messages = support_repo.get_messages(ticket_id, MessageVisibility::Public).await?;
+        Ok(messages)
+    }
+
+    /// Published articles most similar to a ticket the customer is still writing
+    ///
+    /// For deflecting tickets before they are created; needs no ticket or signed-in customer.
+    async fn suggest_articles_for_text(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        subject: String,
+        #[graphql(default)] description: String,
+        locale: Option<String>,
+        limit: Option<i64>,
+    ) -> GraphQLResult<Vec<ArticleSuggestion>> {
+        let support_repo = repository(ctx)?;
+
+        let suggestions = support_repo
+            .suggest_articles_for_text(&product, &subject, &description, locale.as_deref(), limit)
+            .await?;
+        Ok(suggestions)
+    }
+
+    /// The survey behind a survey link, for rendering the survey page
+    ///
+    /// The token is the credential; no signed-in user is required.
+    async fn csat_survey_by_token(&self, ctx: &Context<'_>, token: String) -> GraphQLResult<CsatSurvey> {
+        let support_repo = repository(ctx)?;
+
+        let survey = support_repo.find_csat_survey_by_token(&token).await?;
+        Ok(survey)
+    }
+}
+
+/// Customer portal mutations
+#[derive(Default)]
+pub struct CustomerSupportMutations;
+
+#[Object(name = "CustomerSupportMutation", extends)]
+impl CustomerSupportMutations {
+    /// Open a ticket as the signed-in customer
+    ///
+    /// Captures `SubmitterInfo` and screens for spam like `createSupportTicket`.
+    async fn create_my_support_ticket(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        input: CustomerTicketInput,
+        telemetry: Option<IntakeTelemetry>,
+    ) -> GraphQLResult<SupportTicket> {
+        let customer_id = ViewerRole::customer_id(ctx)?;
+        let support_repo = repository(ctx)?;
+
+        let input = CreateTicketInput {
+            customer_id,
+            organization_id: ViewerOrganization::scope(ctx, None)?,
+            subject: input.subject,
+            description: input.description,
+            category: input.category,
+            channel: TicketChannel::Web,
+            locale: input.locale,
+            client: input.client,
+            idempotency_key: input.idempotency_key,
+            ..Default::default()
+        };
+        validation::validate_new_ticket(&input).map_err(|e| e.extend())?;
+        let mut submitter = ctx.data_opt::<SubmitterInfo>().cloned();
+        if let Some(telemetry) = telemetry {
+            submitter.get_or_insert_with(SubmitterInfo::default).telemetry = Some(telemetry);
+        }
+        let ticket = support_repo.create_ticket_with_submitter(&product, &input, submitter.as_ref()).await.map_err(|e| e.extend())?;
+        Ok(ticket)
+    }
+
+    /// Reply to one of the signed-in customer's tickets
+    ///
+    /// Replies to closed tickets follow the product's closed-ticket policy.
+    async fn reply_to_my_ticket(&self, ctx: &Context<'_>, ticket_id: Uuid, content: String) -> GraphQLResult<TicketMessage> {
+        let customer_id = ViewerRole::customer_id(ctx)?;
+        let support_repo = repository(ctx)?;
+
+        let input = AddTicketMessageInput {
+            ticket_id,
+            content,
+            is_internal: false,
+            visibility: None,
+            content_format: MessageContentFormat::Plaintext,
+        };
+        validation::validate_message(&input).map_err(|e| e.extend())?;
+        own_ticket(support_repo, customer_id, ticket_id).await?;
+        let message = support_repo.add_message(customer_id, &input).await.map_err(|e| e.extend())?;
+        Ok(message)
+    }
+
+    /// Rate one of the signed-in customer's resolved or closed tickets
+    async fn rate_my_ticket(
+        &self,
+        ctx: &Context<'_>,
+        ticket_id: Uuid,
+        input: CsatFeedbackInput,
+    ) -> GraphQLResult<CsatSurvey> {
+        let customer_id = ViewerRole::customer_id(ctx)?;
+        let support_repo = repository(ctx)?;
+        validation::validate_csat_score(input.score).map_err(|e| e.extend())?;
+
+        let survey = support_repo.submit_csat_feedback(ticket_id, customer_id, &input).await.map_err(|e| e.extend())?;
+        Ok(survey)
+    }
+
+    /// Record that the signed-in customer viewed their ticket (or a specific reply)
+    async fn record_my_ticket_view(
+        &self,
+        ctx: &Context<'_>,
+        ticket_id: Uuid,
+        message_id: Option<Uuid>,
+    ) -> GraphQLResult<TicketView> {
+        let customer_id = ViewerRole::customer_id(ctx)?;
+        let support_repo = repository(ctx)?;
+
+        let view = support_repo.record_ticket_view(ticket_id, customer_id, message_id).await?;
+        Ok(view)
+    }
+
+    /// Create a support ticket with a partner's intake API key
+    ///
+    /// The key, which needs the `CREATE_TICKETS` scope, replaces platform auth
+    /// and decides the ticket's product and organization.
+    async fn submit_ticket_with_api_key(
+        &self,
+        ctx: &Context<'_>,
+        api_key: String,
+        input: CreateTicketInput,
+        telemetry: Option<IntakeTelemetry>,
+    ) -> GraphQLResult<SupportTicket> {
+        let support_repo = repository(ctx)?;
+        validation::validate_new_ticket(&input).map_err(|e| e.extend())?;
+
+        let mut submitter = ctx.data_opt::<SubmitterInfo>().cloned();
+        if let Some(telemetry) = telemetry {
+            submitter.get_or_insert_with(SubmitterInfo::default).telemetry = Some(telemetry);
+        }
+        let ticket = support_repo.create_ticket_with_api_key(&api_key, &input, submitter.as_ref()).await.map_err(|e| e.extend())?;
+        Ok(ticket)
+    }
+
+    /// Answer a survey through its link token
+    ///
+    /// The token is the credential; no signed-in user is required.
+    async fn respond_to_csat_survey(
+        &self,
+        ctx: &Context<'_>,
+        token: String,
+        input: CsatFeedbackInput,
+    ) -> GraphQLResult<CsatSurvey> {
+        let support_repo = repository(ctx)?;
+        validation::validate_csat_score(input.score).map_err(|e| e.extend())?;
+
+        let survey = support_repo.respond_to_csat_survey(&token, &input).await.map_err(|e| e.extend())?;
+        Ok(survey)
+    }
+}
+
+/// The ticket, if it belongs to `customer_id`
+async fn own_ticket(support_repo: &SupportRepository, customer_id: Uuid, ticket_id: Uuid) -> crate::Result<SupportTicket> {
+    let ticket = support_repo.find_by_id(ticket_id).await?;
+    if ticket.customer_id != customer_id {
+        return Err(SupportError::Unauthorized);
+    }
+    Ok(ticket)
+}
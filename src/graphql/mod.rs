@@ -0,0 +1,2514 @@
+//! GraphQL API for support ticket system
+//!
+//! Provides two surfaces that can be integrated into any service's GraphQL
+//! schema, separately or merged:
+//!
+//! - [`AgentSupportQueries`]/[`AgentSupportMutations`], the support console,
+//!   admitted only for a [`ViewerRole::Agent`](crate::ViewerRole) request
+//! - [`CustomerSupportQueries`]/[`CustomerSupportMutations`], the customer
+//!   portal, scoped to a [`ViewerRole::Customer`](crate::ViewerRole)'s own
+//!   tickets, plus the survey-link and intake API key endpoints whose token
+//!   is the credential
+//! - [`SupportSubscriptions`], pushed updates for agent displays such as
+//!   wallboards and ticket collision warnings
+//!
+//! [`support_sdl`] prints the SDL of all three on their own, for gateway
+//! composition checks and client generation.
+//!
+//! ## Usage in Services
+//!
+//! Services should provide SupportRepository and the request's `ViewerRole`
+//! in the GraphQL context. Finer checks (admin-only operations, per-team
+//! access) are still done by the service layer before delegating to these
+//! resolvers.
+
+use async_graphql::dataloader::DataLoader;
+use async_graphql::{ComplexObject, Context, ErrorExtensions, Lookahead, Object, Result as GraphQLResult, Upload, ID};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use uuid::Uuid;
+
+use crate::models::{
+    SupportTicket, TicketMessage, TicketPriority, CreateTicketInput, UpdateTicketInput,
+    AddTicketMessageInput, TicketFilter, TicketSearchResult, CrmCoreSupportDashboardMetrics, CrmCoreMetricsComparison, MetricsPeriod, CrmCoreIntakeTelemetryReport, CrmCoreAppVersionMetrics, CrmCoreTopCustomer,
+    PropagationPolicy, TicketAuditEntry, SupportAgent, UpsertSupportAgentInput,
+    SlaTarget, SetSlaTargetInput, CannedResponse, CreateCannedResponseInput, UpdateCannedResponseInput,
+    MessageReaction, MessageVisibility, TicketMessageReaction, TicketView, TicketLastSeen, OutboundEmail, CustomerContext, CustomerJourneyEvent, CustomerSupportSummary, QuickStats,
+    AbuseIncident, AbuseBlocklistCandidate, SavedView, SavedViewCount, SaveViewInput, UpdateSavedViewInput, TicketRead,
+    AssignmentRule, CreateAssignmentRuleInput, AttachmentAccess,
+};
+use crate::pagination::{TicketConnection, TicketCursor, MAX_PAGE_SIZE};
+use crate::SupportError;
+use crate::products::{ProductConfig, ProductConfigInput};
+use crate::agent_activity::{ActivityAnonymization, AgentActivityExport};
+use crate::api_keys::{ApiKeyUsage, CreateApiKeyInput, CreatedApiKey, IntakeApiKey};
+use crate::assignment::AssigneeSuggestion;
+use crate::attachments::TicketAttachment;
+use crate::burst::CollapsedSubmissionCounts;
+use crate::bulk::BulkUpdateReport;
+use crate::sync::{SyncChangeInput, SyncChangeResult, SyncChanges, SyncScope};
+use crate::channels::email::{EmailIngestion, InboundEmail};
+use crate::contract::ContractualSlaReport;
+use crate::csat::{CsatBreakdown, CsatChannel, CsatDimension, CsatFeedbackInput, CsatSurvey, CsatSurveyLink};
+use crate::guard::{AgentGuard, FieldGuard, SensitiveField, SensitiveFieldGuard, ViewerOrganization, ViewerRole};
+use crate::import::{self, CsatImportReport, CsvColumnMapping, ExternalCsatRecord, ImportMode, ImportReport};
+use crate::integrations::dead_letter::{DeadLetter, DeadLetterStatus};
+use crate::loaders::MessagesByTicketLoader;
+use crate::integrations::issue_tracker::{EngineeringIssue, IssueTracker};
+use crate::directory::{CustomerInfo, CustomerLoader};
+use crate::automations::{AutomationExecution, AutomationRule, CreateAutomationRuleInput};
+use crate::escalation::{CreateEscalationRuleInput, EscalationRule};
+use crate::export::{ExportFormat, SupportExport};
+use crate::metrics_history::{MetricsHistory, MetricsInterval};
+use crate::projection::TicketProjection;
+use crate::staffing::{ShiftPattern, StaffingSimulation};
+use crate::validation;
+use crate::kb::{ArticleSuggestion, CreateKnowledgeArticleInput, KnowledgeArticle, UpdateKnowledgeArticleInput};
+use crate::intake::{ClientInfo, ClientPlatform, IntakeTelemetry, SubmitterInfo, SubmitterMetadata};
+use crate::node::{decode_global_id, encode_global_id, Node, NodeType};
+use crate::system::repository;
+use crate::maintenance::{CreateMaintenanceWindowInput, MaintenanceWindow};
+use crate::mentions::MessageMention;
+use crate::merge::{MergePreview, TicketMerge};
+use crate::reports::{
+    CreateReportScheduleInput, ReportFrequency, ReportRun, ReportSchedule, SlaBreachDigest, SupportReport, UpdateReportScheduleInput,
+};
+use crate::resolution::{CrmCoreResolutionCodeMetrics, KbArticleCandidate, ResolutionCode, ResolutionCodeInput};
+use crate::routing::RoutingDecision;
+use crate::sla::SlaClock;
+use crate::templates::TemplateVariable;
+use crate::viewers::TicketViewer;
+use crate::workload::AgentWorkload;
+
+mod customer;
+mod schema;
+mod subscription;
+
+pub use customer::{CustomerSupportMutations, CustomerSupportQueries, CustomerTicketInput};
+pub use schema::{support_schema, support_sdl, SupportMutationRoot, SupportQueryRoot, SupportSchema, SupportSubscriptionRoot};
+pub use subscription::SupportSubscriptions;
+
+/// Support console queries; every field requires a [`ViewerRole::Agent`](crate::ViewerRole)
+#[derive(Default)]
+pub struct AgentSupportQueries;
+
+#[Object(name = "AgentSupportQuery", extends, guard = "AgentGuard")]
+impl AgentSupportQueries {
+    /// Fetch any support object by its global id (Relay `Node`)
+    ///
+    /// Returns null when the object does not exist or the signed-in agent may
+    /// not see it: messages the registered `FieldGuard` hides, other agents'
+    /// saved views and canned responses outside the agent's scope.
+    ///
+    /// Note: Services should implement authorization checks before calling this
+    async fn node(&self, ctx: &Context<'_>, id: ID) -> GraphQLResult<Option<Node>> {
+        let support_repo = repository(ctx)?;
+        let agent_id = ViewerRole::agent_id(ctx)?;
+
+        let (node_type, id) = decode_global_id(&id)?;
+        let node = support_repo
+            .find_node(node_type, id, agent_id, FieldGuard::message_visibility(ctx))
+            .await?;
+        match &node {
+            Some(Node::SupportTicket(ticket)) => check_ticket_scope(ctx, ticket.id).await?,
+            Some(Node::TicketMessage(message)) => check_ticket_scope(ctx, message.ticket_id).await?,
+            _ => {}
+        }
+        Ok(node)
+    }
+
+    /// Get a single support ticket by ID
+    ///
+    /// Note: Services should implement authorization checks before calling this
+    async fn support_ticket(&self, ctx: &Context<'_>, id: Uuid) -> GraphQLResult<SupportTicket> {
+        let support_repo = repository(ctx)?;
+
+        let ticket = support_repo.find_by_id_projected(id, ticket_projection(ctx.look_ahead())).await?;
+        if ViewerOrganization::scope(ctx, None)?.is_some_and(|org| ticket.organization_id != Some(org)) {
+            return Err(SupportError::TicketNotFound(id).into());
+        }
+        Ok(ticket)
+    }
+
+    /// Get a single support ticket by its human-readable reference (e.g. NOVASKYN-000042)
+    ///
+    /// Note: Services should implement authorization checks before calling this
+    async fn support_ticket_by_ref(&self, ctx: &Context<'_>, reference: String) -> GraphQLResult<SupportTicket> {
+        let support_repo = repository(ctx)?;
+
+        let ticket = support_repo.find_by_ref_projected(&reference, ticket_projection(ctx.look_ahead())).await?;
+        if ViewerOrganization::scope(ctx, None)?.is_some_and(|org| ticket.organization_id != Some(org)) {
+            return Err(SupportError::TicketRefNotFound(ticket.ticket_ref).into());
+        }
+        Ok(ticket)
+    }
+
+    /// List support tickets with filters, newest first, as a Relay connection
+    ///
+    /// `first` defaults to 20 and is capped at 100; pass `pageInfo.endCursor`
+    /// as `after` for the next page.
+    ///
+    /// Note: Services should implement authorization checks and apply filters
+    async fn support_tickets(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        filter: Option<TicketFilter>,
+        first: Option<i64>,
+        after: Option<String>,
+    ) -> GraphQLResult<TicketConnection> {
+        let support_repo = repository(ctx)?;
+
+        let mut filter = filter.unwrap_or_default();
+        filter.organization_id = ViewerOrganization::scope(ctx, filter.organization_id)?;
+        let after = after.as_deref().map(TicketCursor::decode).transpose()?;
+        let selection = ctx.look_ahead();
+
+        let connection = support_repo.list_page_projected(
+            &product,
+            &filter,
+            first.unwrap_or(20).clamp(0, MAX_PAGE_SIZE),
+            after,
+            ticket_projection(selection.field("edges").field("node")),
+            selection.field("totalCount").exists(),
+        ).await?;
+
+        Ok(connection)
+    }
+
+    /// Search tickets by subject, description and public message content, most relevant first
+    ///
+    /// Note: Services should implement authorization checks and apply filters
+    async fn search_tickets(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        query: String,
+        filter: Option<TicketFilter>,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> GraphQLResult<Vec<TicketSearchResult>> {
+        let support_repo = repository(ctx)?;
+
+        let mut filter = filter.unwrap_or_default();
+        filter.organization_id = ViewerOrganization::scope(ctx, filter.organization_id)?;
+
+        let results = support_repo.search_tickets_projected(
+            &product,
+            &query,
+            &filter,
+            limit.unwrap_or(20),
+            offset.unwrap_or(0),
+            ticket_projection(ctx.look_ahead().field("ticket")),
+        ).await?;
+
+        Ok(results)
+    }
+
+    /// Get messages for a ticket
+    ///
+    /// Internal and restricted messages are omitted unless the registered `FieldGuard` allows them
+    async fn ticket_messages(&self, ctx: &Context<'_>, ticket_id: Uuid) -> GraphQLResult<Vec<TicketMessage>> {
+        let support_repo = repository(ctx)?;
+        check_ticket_scope(ctx, ticket_id).await?;
+
+        let messages = support_repo.get_messages(ticket_id, FieldGuard::message_visibility(ctx)).await?;
+        Ok(messages)
+    }
+
+    /// Recent-context sidebar for a customer: last tickets, CSAT, open tickets, known issues
+    ///
+    /// Note: Services should implement agent-only authorization before calling this
+    async fn customer_context(
+        &self,
+        ctx: &Context<'_>,
+        customer_id: Uuid,
+        product: String,
+    ) -> GraphQLResult<CustomerContext> {
+        let support_repo = repository(ctx)?;
+
+        let organization_id = ViewerOrganization::scope(ctx, None)?;
+        let context = support_repo.customer_context(customer_id, &product, organization_id).await?;
+        Ok(context)
+    }
+
+    /// Ticket totals, average CSAT, last contact and common categories of a customer, across products
+    ///
+    /// Note: Services should implement agent-only authorization before calling this
+    async fn customer_support_summary(&self, ctx: &Context<'_>, customer_id: Uuid) -> GraphQLResult<CustomerSupportSummary> {
+        let support_repo = repository(ctx)?;
+
+        let organization_id = ViewerOrganization::scope(ctx, None)?;
+        let summary = support_repo.customer_support_summary(customer_id, organization_id).await?;
+        Ok(summary)
+    }
+
+    /// Chronological feed of a customer's tickets, CSAT ratings and known-issue links across products
+    ///
+    /// Note: Services should implement agent-only authorization before calling this
+    async fn customer_journey(
+        &self,
+        ctx: &Context<'_>,
+        customer_id: Uuid,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> GraphQLResult<Vec<CustomerJourneyEvent>> {
+        let support_repo = repository(ctx)?;
+
+        let organization_id = ViewerOrganization::scope(ctx, None)?;
+        let events = support_repo.customer_journey(customer_id, period_start, period_end, organization_id).await?;
+        Ok(events)
+    }
+
+    /// An agent's saved ticket views
+    ///
+    /// Note: Services should provide owner_id from authenticated user context
+    async fn saved_views(&self, ctx: &Context<'_>, product: String, owner_id: Uuid) -> GraphQLResult<Vec<SavedView>> {
+        let support_repo = repository(ctx)?;
+
+        let views = support_repo.list_saved_views(&product, owner_id).await?;
+        Ok(views)
+    }
+
+    /// Tickets in one of the agent's saved views, in the view's sort order
+    ///
+    /// Note: Services should provide owner_id from authenticated user context
+    async fn saved_view_tickets(
+        &self,
+        ctx: &Context<'_>,
+        view_id: Uuid,
+        owner_id: Uuid,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> GraphQLResult<Vec<SupportTicket>> {
+        let support_repo = repository(ctx)?;
+
+        let organization_id = ViewerOrganization::scope(ctx, None)?;
+        let tickets = support_repo
+            .list_saved_view_tickets(
+                view_id,
+                owner_id,
+                organization_id,
+                limit.unwrap_or(20).clamp(0, MAX_PAGE_SIZE),
+                offset.unwrap_or(0),
+            )
+            .await?;
+        Ok(tickets)
+    }
+
+    /// A product's knowledge base articles, including unpublished ones
+    ///
+    /// Note: Services should implement agent-only authorization before calling this
+    async fn knowledge_articles(&self, ctx: &Context<'_>, product: String) -> GraphQLResult<Vec<KnowledgeArticle>> {
+        let support_repo = repository(ctx)?;
+
+        let articles = support_repo.list_knowledge_articles(&product).await?;
+        Ok(articles)
+    }
+
+    /// Published articles most similar to a ticket, best first
+    ///
+    /// Note: Services should implement authorization checks before calling this
+    async fn suggest_articles(
+        &self,
+        ctx: &Context<'_>,
+        ticket_id: Uuid,
+        limit: Option<i64>,
+    ) -> GraphQLResult<Vec<ArticleSuggestion>> {
+        let support_repo = repository(ctx)?;
+        check_ticket_scope(ctx, ticket_id).await?;
+
+        let suggestions = support_repo.suggest_articles(ticket_id, limit).await?;
+        Ok(suggestions)
+    }
+
+    /// Canned responses an agent can use: personal, team and product-wide
+    ///
+    /// Note: Services should provide agent_id from authenticated user context
+    async fn canned_responses(&self, ctx: &Context<'_>, product: String, agent_id: Uuid) -> GraphQLResult<Vec<CannedResponse>> {
+        let support_repo = repository(ctx)?;
+
+        let responses = support_repo.list_canned_responses(&product, agent_id).await?;
+        Ok(responses)
+    }
+
+    /// Preview a canned response on a ticket with its placeholders filled in
+    ///
+    /// Note: Services should provide author_id from authenticated user context
+    async fn render_canned_response(
+        &self,
+        ctx: &Context<'_>,
+        ticket_id: Uuid,
+        author_id: Uuid,
+        canned_response_id: Uuid,
+        variables: Option<Vec<TemplateVariable>>,
+    ) -> GraphQLResult<String> {
+        let support_repo = repository(ctx)?;
+        check_ticket_scope(ctx, ticket_id).await?;
+
+        let body = support_repo
+            .render_canned_response(author_id, ticket_id, canned_response_id, &variables.unwrap_or_default())
+            .await?;
+        Ok(body)
+    }
+
+    /// Canned responses by usage, most used first, for pruning stale templates
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn most_used_canned_responses(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        limit: Option<i64>,
+    ) -> GraphQLResult<Vec<CannedResponse>> {
+        let support_repo = repository(ctx)?;
+
+        let responses = support_repo.most_used_canned_responses(&product, limit).await?;
+        Ok(responses)
+    }
+
+    /// Ticket and unread counts for sidebar badges, for many views in one round trip
+    ///
+    /// Note: Services should provide agent_id from authenticated user context
+    async fn view_counts(
+        &self,
+        ctx: &Context<'_>,
+        view_ids: Vec<Uuid>,
+        agent_id: Uuid,
+    ) -> GraphQLResult<Vec<SavedViewCount>> {
+        let support_repo = repository(ctx)?;
+
+        let counts = support_repo.view_counts(&view_ids, agent_id).await?;
+        Ok(counts)
+    }
+
+    /// Abuse incidents recorded against a customer by content moderation
+    ///
+    /// Note: Services should implement agent-only authorization before calling this
+    async fn customer_abuse_incidents(
+        &self,
+        ctx: &Context<'_>,
+        customer_id: Uuid,
+        product: String,
+    ) -> GraphQLResult<Vec<AbuseIncident>> {
+        let support_repo = repository(ctx)?;
+
+        let incidents = support_repo.get_abuse_incidents(customer_id, &product).await?;
+        Ok(incidents)
+    }
+
+    /// Customers with repeated abuse incidents, for blocklist review
+    ///
+    /// Defaults to customers with 3+ incidents in the last 30 days.
+    ///
+    /// Note: Services should implement admin authorization before calling this
+    async fn abuse_blocklist_candidates(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        min_incidents: Option<i64>,
+        since_days: Option<i64>,
+    ) -> GraphQLResult<Vec<AbuseBlocklistCandidate>> {
+        let support_repo = repository(ctx)?;
+
+        let since = support_repo.now() - Duration::days(since_days.unwrap_or(30));
+        let candidates = support_repo
+            .abuse_blocklist_candidates(&product, min_incidents.unwrap_or(3), since)
+            .await?;
+        Ok(candidates)
+    }
+
+    /// Get child tickets linked to a parent (incident) ticket
+    async fn child_tickets(&self, ctx: &Context<'_>, parent_id: Uuid) -> GraphQLResult<Vec<SupportTicket>> {
+        let support_repo = repository(ctx)?;
+        check_ticket_scope(ctx, parent_id).await?;
+
+        let tickets = support_repo.get_child_tickets(parent_id).await?;
+        Ok(tickets)
+    }
+
+    /// Who changed what on a ticket and when, oldest first
+    ///
+    /// Covers status, priority, assignment, subject, description and category
+    /// changes, propagated changes and closed-ticket replies.
+    ///
+    /// Note: Services should implement agent-only authorization before calling this
+    async fn ticket_history(&self, ctx: &Context<'_>, ticket_id: Uuid) -> GraphQLResult<Vec<TicketAuditEntry>> {
+        let support_repo = repository(ctx)?;
+        check_ticket_scope(ctx, ticket_id).await?;
+
+        let entries = support_repo.get_audit_entries(ticket_id).await?;
+        Ok(entries)
+    }
+
+    /// Get audit entries for a ticket
+    ///
+    /// Note: Services should implement agent-only authorization before calling this
+    #[graphql(deprecation = "Use ticketHistory")]
+    async fn ticket_audit_entries(&self, ctx: &Context<'_>, ticket_id: Uuid) -> GraphQLResult<Vec<TicketAuditEntry>> {
+        let support_repo = repository(ctx)?;
+        check_ticket_scope(ctx, ticket_id).await?;
+
+        let entries = support_repo.get_audit_entries(ticket_id).await?;
+        Ok(entries)
+    }
+
+    /// Internal notes on the agent's tickets that they have not acknowledged
+    async fn unacknowledged_internal_notes(&self, ctx: &Context<'_>, agent_id: Uuid) -> GraphQLResult<Vec<TicketMessage>> {
+        let support_repo = repository(ctx)?;
+
+        FieldGuard::require(ctx, SensitiveField::InternalMessages)?;
+
+        let viewer = FieldGuard::message_visibility(ctx);
+        let mut messages = support_repo.unacknowledged_internal_notes(agent_id).await?;
+        messages.retain(|m| m.visibility.visible_to(viewer));
+        Ok(messages)
+    }
+
+    /// Messages that @mention the agent, newest first; `limit` defaults to 20, at most 100
+    async fn agent_mentions(
+        &self,
+        ctx: &Context<'_>,
+        agent_id: Uuid,
+        since: Option<DateTime<Utc>>,
+        limit: Option<i64>,
+    ) -> GraphQLResult<Vec<MessageMention>> {
+        let support_repo = repository(ctx)?;
+
+        FieldGuard::require(ctx, SensitiveField::InternalMessages)?;
+
+        let mentions = support_repo.agent_mentions(agent_id, since, limit.unwrap_or(20)).await?;
+        Ok(mentions)
+    }
+
+    /// What changed in `scope` since `cursor`, for the offline mobile app
+    ///
+    /// Omit `cursor` on a device's first pull. Each of tickets, messages and
+    /// tombstones is limited to `limit` rows (default 200, at most 1000).
+    /// Internal and restricted messages are omitted unless the registered `FieldGuard` allows them.
+    ///
+    /// Note: Services should implement agent-only authorization before calling this
+    async fn changes_since(
+        &self,
+        ctx: &Context<'_>,
+        scope: SyncScope,
+        cursor: Option<String>,
+        limit: Option<i64>,
+    ) -> GraphQLResult<SyncChanges> {
+        let support_repo = repository(ctx)?;
+
+        let mut scope = scope;
+        scope.organization_id = ViewerOrganization::scope(ctx, scope.organization_id)?;
+        let mut changes = support_repo.changes_since(&scope, cursor.as_deref(), limit).await?;
+        let viewer = FieldGuard::message_visibility(ctx);
+        changes.messages.retain(|m| m.visibility.visible_to(viewer));
+        Ok(changes)
+    }
+
+    /// List agent profiles for a product
+    async fn support_agents(&self, ctx: &Context<'_>, product: String) -> GraphQLResult<Vec<SupportAgent>> {
+        let support_repo = repository(ctx)?;
+
+        let agents = support_repo.list_agents(&product).await?;
+        Ok(agents)
+    }
+
+    /// Open tickets per priority and priority-weighted workload of the product's active agents, heaviest first
+    async fn agent_workloads(&self, ctx: &Context<'_>, product: String) -> GraphQLResult<Vec<AgentWorkload>> {
+        let support_repo = repository(ctx)?;
+
+        let workloads = support_repo.agent_workloads(&product).await?;
+        Ok(workloads)
+    }
+
+    /// A product's intake API keys, including revoked ones
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn intake_api_keys(&self, ctx: &Context<'_>, product: String) -> GraphQLResult<Vec<IntakeApiKey>> {
+        let support_repo = repository(ctx)?;
+
+        let keys = support_repo.list_api_keys(&product).await?;
+        Ok(keys)
+    }
+
+    /// Daily request counts for an intake API key
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn intake_api_key_usage(
+        &self,
+        ctx: &Context<'_>,
+        key_id: Uuid,
+        since: NaiveDate,
+    ) -> GraphQLResult<Vec<ApiKeyUsage>> {
+        let support_repo = repository(ctx)?;
+
+        let usage = support_repo.api_key_usage(key_id, since).await?;
+        Ok(usage)
+    }
+
+    /// Maintenance windows in effect for a product right now
+    ///
+    /// SLA clocks are paused and alerts suppressed while any is active.
+    async fn active_maintenance_windows(&self, ctx: &Context<'_>, product: String) -> GraphQLResult<Vec<MaintenanceWindow>> {
+        let support_repo = repository(ctx)?;
+
+        let windows = support_repo.active_maintenance_windows(&product).await?;
+        Ok(windows)
+    }
+
+    /// A product's current and upcoming maintenance windows
+    ///
+    /// Note: Services should implement agent-only authorization before calling this
+    async fn maintenance_windows(&self, ctx: &Context<'_>, product: String) -> GraphQLResult<Vec<MaintenanceWindow>> {
+        let support_repo = repository(ctx)?;
+
+        let windows = support_repo.list_maintenance_windows(&product, support_repo.now()).await?;
+        Ok(windows)
+    }
+
+    /// A product's automatic assignment rules in routing order
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn assignment_rules(&self, ctx: &Context<'_>, product: String) -> GraphQLResult<Vec<AssignmentRule>> {
+        let support_repo = repository(ctx)?;
+
+        let rules = support_repo.list_assignment_rules(&product).await?;
+        Ok(rules)
+    }
+
+    /// A product's escalation rules, lowest level first
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn escalation_rules(&self, ctx: &Context<'_>, product: String) -> GraphQLResult<Vec<EscalationRule>> {
+        let support_repo = repository(ctx)?;
+
+        let rules = support_repo.list_escalation_rules(&product).await?;
+        Ok(rules)
+    }
+
+    /// A product's scheduled reports, by name
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn report_schedules(&self, ctx: &Context<'_>, product: String) -> GraphQLResult<Vec<ReportSchedule>> {
+        let support_repo = repository(ctx)?;
+
+        let schedules = support_repo.list_report_schedules(&product).await?;
+        Ok(schedules)
+    }
+
+    /// A report schedule's latest runs, newest first; `limit` defaults to 10, at most 100
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn report_runs(
+        &self,
+        ctx: &Context<'_>,
+        schedule_id: Uuid,
+        limit: Option<i64>,
+    ) -> GraphQLResult<Vec<ReportRun>> {
+        let support_repo = repository(ctx)?;
+
+        let runs = support_repo.get_report_runs(schedule_id, limit.unwrap_or(10)).await?;
+        Ok(runs)
+    }
+
+    /// A product's summary for the last full day or week, generated now without a schedule
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn support_report(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        frequency: ReportFrequency,
+        timezone: Option<String>,
+    ) -> GraphQLResult<SupportReport> {
+        let support_repo = repository(ctx)?;
+
+        let report = support_repo.generate_report(&product, frequency, timezone.as_deref()).await?;
+        Ok(report)
+    }
+
+    /// Every SLA deadline missed by the product's tickets in the ISO week containing `week`
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn sla_breach_digest(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        week: NaiveDate,
+        timezone: Option<String>,
+    ) -> GraphQLResult<SlaBreachDigest> {
+        let support_repo = repository(ctx)?;
+
+        let digest = support_repo.sla_breach_digest(&product, week, timezone.as_deref()).await?;
+        Ok(digest)
+    }
+
+    /// A product's automation rules, by trigger and position
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn automation_rules(&self, ctx: &Context<'_>, product: String) -> GraphQLResult<Vec<AutomationRule>> {
+        let support_repo = repository(ctx)?;
+
+        let rules = support_repo.list_automation_rules(&product).await?;
+        Ok(rules)
+    }
+
+    /// An automation rule's most recent executions (default 50, at most 500)
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn automation_executions(
+        &self,
+        ctx: &Context<'_>,
+        rule_id: Uuid,
+        limit: Option<i64>,
+    ) -> GraphQLResult<Vec<AutomationExecution>> {
+        let support_repo = repository(ctx)?;
+
+        let executions = support_repo
+            .list_automation_executions(rule_id, limit.unwrap_or(50).clamp(1, 500))
+            .await?;
+        Ok(executions)
+    }
+
+    /// Ranked assignee candidates for a ticket, with the reasons behind each ranking
+    ///
+    /// Note: Services should implement agent-only authorization before calling this
+    async fn suggest_assignee(
+        &self,
+        ctx: &Context<'_>,
+        ticket_id: Uuid,
+        limit: Option<i32>,
+    ) -> GraphQLResult<Vec<AssigneeSuggestion>> {
+        let support_repo = repository(ctx)?;
+        check_ticket_scope(ctx, ticket_id).await?;
+
+        let suggestions = support_repo
+            .suggest_assignee(ticket_id, limit.unwrap_or(5).max(0) as usize)
+            .await?;
+        Ok(suggestions)
+    }
+
+    /// Get support dashboard metrics for analytics
+    ///
+    /// `timezone` (IANA name) sets the calendar day used for trends and "today"
+    /// counts; defaults to the product's configured timezone. With
+    /// `organizationId` only that B2B account's tickets are counted.
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn support_dashboard_metrics(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+        timezone: Option<String>,
+        organization_id: Option<Uuid>,
+    ) -> GraphQLResult<CrmCoreSupportDashboardMetrics> {
+        let support_repo = repository(ctx)?;
+
+        let metrics = match ViewerOrganization::scope(ctx, organization_id)? {
+            Some(organization_id) => support_repo.get_organization_dashboard_metrics(
+                &product,
+                organization_id,
+                period_start,
+                period_end,
+                timezone.as_deref(),
+            ).await?,
+            None => support_repo.get_dashboard_metrics_in_timezone(
+                &product,
+                period_start,
+                period_end,
+                timezone.as_deref(),
+            ).await?,
+        };
+
+        Ok(metrics)
+    }
+
+    /// Volume, SLA, CSAT and response percentiles per day, week or month, oldest first
+    ///
+    /// Buckets start in the product's timezone; `interval` defaults to WEEK.
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn metrics_history(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        interval: Option<MetricsInterval>,
+    ) -> GraphQLResult<MetricsHistory> {
+        let support_repo = repository(ctx)?;
+
+        let organization_id = ViewerOrganization::scope(ctx, None)?;
+        let history = support_repo
+            .export_metrics_history(&product, organization_id, from, to, interval.unwrap_or_default())
+            .await?;
+        Ok(history)
+    }
+
+    /// Projected waits, first-response breaches and utilization of replaying
+    /// `historicalPeriod`'s tickets against `agents` per shift of `shiftPattern`
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn staffing_simulation(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        historical_period: MetricsPeriod,
+        agents: u32,
+        shift_pattern: ShiftPattern,
+    ) -> GraphQLResult<StaffingSimulation> {
+        let support_repo = repository(ctx)?;
+
+        let simulation = support_repo.simulate_staffing(&product, historical_period, agents, &shift_pattern).await?;
+        Ok(simulation)
+    }
+
+    /// Overview, SLA and response metrics of two periods with absolute and
+    /// percentage changes from `periodA` to `periodB`
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn compare_support_dashboard_periods(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        period_a: MetricsPeriod,
+        period_b: MetricsPeriod,
+    ) -> GraphQLResult<CrmCoreMetricsComparison> {
+        let support_repo = repository(ctx)?;
+
+        let organization_id = ViewerOrganization::scope(ctx, None)?;
+        let comparison = support_repo.compare_dashboard_periods(&product, organization_id, period_a, period_b).await?;
+        Ok(comparison)
+    }
+
+    /// CSAT for surveys sent in the period, grouped by agent, category, day, week or month
+    ///
+    /// Note: Services should implement agent-only authorization before calling this
+    async fn csat_breakdown(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+        by: CsatDimension,
+    ) -> GraphQLResult<Vec<CsatBreakdown>> {
+        let support_repo = repository(ctx)?;
+
+        let breakdown = support_repo.get_csat_breakdown(&product, period_start, period_end, by).await?;
+        Ok(breakdown)
+    }
+
+    /// Download history of one attachment, newest first
+    ///
+    /// Note: Services should implement security-team authorization before calling this
+    async fn attachment_access_log(&self, ctx: &Context<'_>, attachment_id: Uuid) -> GraphQLResult<Vec<AttachmentAccess>> {
+        let support_repo = repository(ctx)?;
+
+        let accesses = support_repo.list_attachment_accesses(attachment_id).await?;
+        if let Some(access) = accesses.first() {
+            check_ticket_scope(ctx, access.ticket_id).await?;
+        }
+        Ok(accesses)
+    }
+
+    /// Download history of all of a ticket's attachments, newest first
+    ///
+    /// Note: Services should implement security-team authorization before calling this
+    async fn ticket_attachment_access_log(&self, ctx: &Context<'_>, ticket_id: Uuid) -> GraphQLResult<Vec<AttachmentAccess>> {
+        let support_repo = repository(ctx)?;
+        check_ticket_scope(ctx, ticket_id).await?;
+
+        let accesses = support_repo.list_ticket_attachment_accesses(ticket_id).await?;
+        Ok(accesses)
+    }
+
+    /// A registered attachment and its scan status
+    ///
+    /// Note: Services should implement agent-only authorization before calling this
+    async fn ticket_attachment(&self, ctx: &Context<'_>, attachment_id: Uuid) -> GraphQLResult<Option<TicketAttachment>> {
+        let support_repo = repository(ctx)?;
+
+        let attachment = support_repo.get_attachment(attachment_id).await?;
+        if let Some(attachment) = &attachment {
+            check_ticket_scope(ctx, attachment.ticket_id).await?;
+        }
+        Ok(attachment)
+    }
+
+    /// A ticket's registered attachments and their scan status, oldest first
+    ///
+    /// Note: Services should implement agent-only authorization before calling this
+    async fn ticket_attachments(&self, ctx: &Context<'_>, ticket_id: Uuid) -> GraphQLResult<Vec<TicketAttachment>> {
+        let support_repo = repository(ctx)?;
+        check_ticket_scope(ctx, ticket_id).await?;
+
+        let attachments = support_repo.list_ticket_attachments(ticket_id).await?;
+        Ok(attachments)
+    }
+
+    /// Engineering issues linked to a ticket
+    ///
+    /// Note: Services should implement agent-only authorization before calling this
+    async fn engineering_issues(&self, ctx: &Context<'_>, ticket_id: Uuid) -> GraphQLResult<Vec<EngineeringIssue>> {
+        let support_repo = repository(ctx)?;
+        check_ticket_scope(ctx, ticket_id).await?;
+
+        let issues = support_repo.list_engineering_issues(ticket_id).await?;
+        Ok(issues)
+    }
+
+    /// A product's dead-lettered events, oldest first
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn dead_letters(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        status: Option<DeadLetterStatus>,
+        limit: Option<i32>,
+    ) -> GraphQLResult<Vec<DeadLetter>> {
+        let support_repo = repository(ctx)?;
+
+        let letters = support_repo
+            .list_dead_letters(&product, status, limit.unwrap_or(100).clamp(1, 500) as i64)
+            .await?;
+        Ok(letters)
+    }
+
+    /// Satisfaction surveys sent for a ticket, newest first
+    ///
+    /// Note: Services should implement agent-only authorization before calling this
+    async fn csat_surveys(&self, ctx: &Context<'_>, ticket_id: Uuid) -> GraphQLResult<Vec<CsatSurvey>> {
+        let support_repo = repository(ctx)?;
+        check_ticket_scope(ctx, ticket_id).await?;
+
+        let surveys = support_repo.list_csat_surveys(ticket_id).await?;
+        Ok(surveys)
+    }
+
+    /// Open ticket counts by priority for header badges; cheap enough for every page load
+    ///
+    /// Note: Services should implement agent-only authorization before calling this
+    async fn quick_stats(&self, ctx: &Context<'_>, product: String) -> GraphQLResult<QuickStats> {
+        let support_repo = repository(ctx)?;
+
+        let stats = support_repo.quick_stats(&product).await?;
+        Ok(stats)
+    }
+
+    /// Whether support is read-only, so the console can disable editing
+    async fn support_read_only(&self, ctx: &Context<'_>) -> GraphQLResult<bool> {
+        let support_repo = repository(ctx)?;
+        Ok(support_repo.is_read_only())
+    }
+
+    /// Every registered product and its configuration
+    ///
+    /// Note: Services should implement agent-only authorization before calling this
+    async fn product_configs(&self, ctx: &Context<'_>) -> GraphQLResult<Vec<ProductConfig>> {
+        let support_repo = repository(ctx)?;
+
+        let configs = support_repo.list_product_configs().await?;
+        Ok(configs)
+    }
+
+    /// A registered product's configuration
+    ///
+    /// Note: Services should implement agent-only authorization before calling this
+    async fn product_config(&self, ctx: &Context<'_>, product: String) -> GraphQLResult<ProductConfig> {
+        let support_repo = repository(ctx)?;
+
+        let config = support_repo.get_product_config(&product).await?;
+        Ok(config)
+    }
+
+    /// A product's resolution codes, optionally including retired ones
+    ///
+    /// Note: Services should implement agent-only authorization before calling this
+    async fn resolution_codes(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        #[graphql(default)] include_inactive: bool,
+    ) -> GraphQLResult<Vec<ResolutionCode>> {
+        let support_repo = repository(ctx)?;
+
+        let codes = support_repo.list_resolution_codes(&product, include_inactive).await?;
+        Ok(codes)
+    }
+
+    /// SLA targets per priority configured for a product
+    ///
+    /// Note: Services should implement agent-only authorization before calling this
+    async fn sla_targets(&self, ctx: &Context<'_>, product: String) -> GraphQLResult<Vec<SlaTarget>> {
+        let support_repo = repository(ctx)?;
+
+        let targets = support_repo.list_sla_targets(&product).await?;
+        Ok(targets)
+    }
+
+    /// A ticket's SLA clocks: elapsed and remaining time per target, pauses and the policy in force
+    ///
+    /// Note: Services should implement agent-only authorization before calling this
+    async fn sla_clock(&self, ctx: &Context<'_>, ticket_id: Uuid) -> GraphQLResult<SlaClock> {
+        let support_repo = repository(ctx)?;
+        check_ticket_scope(ctx, ticket_id).await?;
+
+        let clock = support_repo.sla_clock(ticket_id).await?;
+        Ok(clock)
+    }
+
+    /// Contractual SLA compliance of an account for the calendar month containing `month`
+    ///
+    /// Note: Services should restrict this to agents and the account's own users
+    async fn contractual_sla_report(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        account: String,
+        month: NaiveDate,
+    ) -> GraphQLResult<ContractualSlaReport> {
+        let support_repo = repository(ctx)?;
+
+        let report = support_repo.get_contractual_sla_report(&product, &account, month).await?;
+        Ok(report)
+    }
+
+    /// Customers with the most tickets in the period, with open tickets, CSAT and estimated handling cost
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn top_customers(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+        limit: Option<i64>,
+    ) -> GraphQLResult<Vec<CrmCoreTopCustomer>> {
+        let support_repo = repository(ctx)?;
+
+        let customers = support_repo
+            .get_top_customers(&product, period_start, period_end, limit.unwrap_or(10).clamp(1, 100))
+            .await?;
+        Ok(customers)
+    }
+
+    /// An agent's handled tickets, authored messages and received CSAT in the period, for performance reviews
+    ///
+    /// QA reviews and time logs are not included.
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn agent_activity_export(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        agent_id: Uuid,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+        anonymization: Option<ActivityAnonymization>,
+    ) -> GraphQLResult<AgentActivityExport> {
+        let support_repo = repository(ctx)?;
+
+        let export = support_repo
+            .export_agent_activity(
+                &product,
+                agent_id,
+                period_start,
+                period_end,
+                FieldGuard::message_visibility(ctx),
+                anonymization.unwrap_or_default(),
+            )
+            .await?;
+        Ok(export)
+    }
+
+    /// Accepted vs rejected intake by telemetry signal (honeypot, fast fill, repeats)
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn intake_telemetry_report(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> GraphQLResult<CrmCoreIntakeTelemetryReport> {
+        let support_repo = repository(ctx)?;
+
+        let report = support_repo.get_intake_telemetry_report(&product, period_start, period_end).await?;
+        Ok(report)
+    }
+
+    /// Tickets filed per app release, to see whether a release spiked support volume
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn tickets_by_app_version(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+        platform: Option<ClientPlatform>,
+    ) -> GraphQLResult<Vec<CrmCoreAppVersionMetrics>> {
+        let support_repo = repository(ctx)?;
+
+        let metrics = support_repo
+            .get_tickets_by_app_version(&product, period_start, period_end, platform)
+            .await?;
+        Ok(metrics)
+    }
+
+    /// Tickets resolved in a period, broken down by resolution code
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn resolutions_by_code(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> GraphQLResult<Vec<CrmCoreResolutionCodeMetrics>> {
+        let support_repo = repository(ctx)?;
+
+        let metrics = support_repo.get_resolutions_by_code(&product, period_start, period_end).await?;
+        Ok(metrics)
+    }
+
+    /// Recurring (category, resolution code) pairs with no help article, for the knowledge team
+    ///
+    /// Note: Services should implement agent-only authorization before calling this
+    async fn kb_article_candidates(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+        min_tickets: Option<i64>,
+    ) -> GraphQLResult<Vec<KbArticleCandidate>> {
+        let support_repo = repository(ctx)?;
+
+        let candidates = support_repo
+            .kb_article_candidates(&product, period_start, period_end, min_tickets)
+            .await?;
+        Ok(candidates)
+    }
+
+    /// Intake submissions collapsed into existing tickets as duplicate bursts
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn collapsed_submission_counts(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> GraphQLResult<CollapsedSubmissionCounts> {
+        let support_repo = repository(ctx)?;
+
+        let counts = support_repo.get_collapsed_submission_counts(&product, period_start, period_end).await?;
+        Ok(counts)
+    }
+
+    /// The primary ticket's thread if `duplicate_ids` were merged into it; changes nothing
+    ///
+    /// Internal messages are omitted unless the registered `FieldGuard` allows them.
+    async fn preview_merge(
+        &self,
+        ctx: &Context<'_>,
+        primary_id: Uuid,
+        duplicate_ids: Vec<Uuid>,
+    ) -> GraphQLResult<MergePreview> {
+        let support_repo = repository(ctx)?;
+        check_ticket_scope(ctx, primary_id).await?;
+        check_tickets_scope(ctx, &duplicate_ids).await?;
+
+        let mut preview = support_repo.preview_merge(primary_id, &duplicate_ids).await?;
+        let viewer = FieldGuard::message_visibility(ctx);
+        preview.thread.retain(|m| m.visibility.visible_to(viewer));
+        Ok(preview)
+    }
+
+    /// Merges into a ticket, newest first, with their undo deadlines
+    async fn ticket_merges(&self, ctx: &Context<'_>, ticket_id: Uuid) -> GraphQLResult<Vec<TicketMerge>> {
+        let support_repo = repository(ctx)?;
+        check_ticket_scope(ctx, ticket_id).await?;
+
+        let merges = support_repo.get_ticket_merges(ticket_id).await?;
+        Ok(merges)
+    }
+}
+
+/// Support console mutations; every field requires a [`ViewerRole::Agent`](crate::ViewerRole)
+#[derive(Default)]
+pub struct AgentSupportMutations;
+
+#[Object(name = "AgentSupportMutation", extends, guard = "AgentGuard")]
+impl AgentSupportMutations {
+    /// Create a new support ticket
+    ///
+    /// If the request carries `SubmitterInfo` data (IP, user agent, geo), it is
+    /// captured into ticket metadata according to the repository's privacy settings.
+    /// Submissions with submitter info or `telemetry` from the intake form are
+    /// screened for spam and may be rejected.
+    ///
+    /// Note: Services should verify user authentication before calling this
+    async fn create_support_ticket(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        input: CreateTicketInput,
+        telemetry: Option<IntakeTelemetry>,
+    ) -> GraphQLResult<SupportTicket> {
+        let support_repo = repository(ctx)?;
+        validation::validate_new_ticket(&input).map_err(|e| e.extend())?;
+
+        let mut input = input;
+        input.organization_id = ViewerOrganization::scope(ctx, input.organization_id)?;
+        let mut submitter = ctx.data_opt::<SubmitterInfo>().cloned();
+        if let Some(telemetry) = telemetry {
+            submitter.get_or_insert_with(SubmitterInfo::default).telemetry = Some(telemetry);
+        }
+        let ticket = support_repo.create_ticket_with_submitter(&product, &input, submitter.as_ref()).await.map_err(|e| e.extend())?;
+        Ok(ticket)
+    }
+
+    /// Apply ticket updates and messages the signed-in agent made offline
+    ///
+    /// Results come back in order. Fields changed on the server since a
+    /// change's `baseUpdatedAt` keep the server's value and are listed in
+    /// `conflictingFields`; re-pushed `changeId`s return their first result.
+    ///
+    /// Note: Services should implement agent-only authorization before calling this
+    async fn push_local_changes(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        changes: Vec<SyncChangeInput>,
+    ) -> GraphQLResult<Vec<SyncChangeResult>> {
+        let support_repo = repository(ctx)?;
+        let agent_id = ViewerRole::agent_id(ctx)?;
+        check_tickets_scope(ctx, &changes.iter().map(|c| c.ticket_id).collect::<Vec<_>>()).await?;
+
+        let results = support_repo.push_local_changes(agent_id, &product, &changes).await?;
+        Ok(results)
+    }
+
+    /// Apply one update to many tickets in a single transaction
+    ///
+    /// Returns per-ticket success or failure; failed tickets are left unchanged.
+    ///
+    /// Note: Services should implement authorization checks (e.g., support:write permission)
+    async fn bulk_update_support_tickets(
+        &self,
+        ctx: &Context<'_>,
+        ids: Vec<Uuid>,
+        input: UpdateTicketInput,
+        actor_id: Option<Uuid>,
+    ) -> GraphQLResult<BulkUpdateReport> {
+        let support_repo = repository(ctx)?;
+        validation::validate_ticket_update(&input).map_err(|e| e.extend())?;
+        check_tickets_scope(ctx, &ids).await?;
+
+        let report = support_repo.bulk_update_tickets(&ids, actor_id, &input).await.map_err(|e| e.extend())?;
+        Ok(report)
+    }
+
+    /// Assign many tickets to one agent in a single transaction
+    ///
+    /// Note: Services should implement authorization checks (e.g., support:write permission)
+    async fn bulk_assign_support_tickets(
+        &self,
+        ctx: &Context<'_>,
+        ids: Vec<Uuid>,
+        assignee_id: Uuid,
+        actor_id: Option<Uuid>,
+    ) -> GraphQLResult<BulkUpdateReport> {
+        let support_repo = repository(ctx)?;
+        check_tickets_scope(ctx, &ids).await?;
+
+        let report = support_repo.bulk_assign(&ids, assignee_id, actor_id).await?;
+        Ok(report)
+    }
+
+    /// Close many tickets in a single transaction
+    ///
+    /// Note: Services should implement authorization checks (e.g., support:write permission)
+    async fn bulk_close_support_tickets(
+        &self,
+        ctx: &Context<'_>,
+        ids: Vec<Uuid>,
+        actor_id: Option<Uuid>,
+    ) -> GraphQLResult<BulkUpdateReport> {
+        let support_repo = repository(ctx)?;
+        check_tickets_scope(ctx, &ids).await?;
+
+        let report = support_repo.bulk_close(&ids, actor_id).await?;
+        Ok(report)
+    }
+
+    /// Create an intake API key for partners; the secret is only returned here
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn create_intake_api_key(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        created_by: Uuid,
+        input: CreateApiKeyInput,
+    ) -> GraphQLResult<CreatedApiKey> {
+        let support_repo = repository(ctx)?;
+
+        let created = support_repo.create_api_key(&product, created_by, &input).await?;
+        Ok(created)
+    }
+
+    /// Revoke an intake API key
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn revoke_intake_api_key(&self, ctx: &Context<'_>, id: Uuid) -> GraphQLResult<IntakeApiKey> {
+        let support_repo = repository(ctx)?;
+
+        let key = support_repo.revoke_api_key(id).await?;
+        Ok(key)
+    }
+
+    /// Update a support ticket
+    ///
+    /// When a propagation policy is given, priority/status changes are copied
+    /// to linked child tickets in the same transaction. `actor_id`, when given,
+    /// is credited as the resolving agent on resolution.
+    ///
+    /// Note: Services should implement authorization checks (e.g., support:write permission)
+    async fn update_support_ticket(
+        &self,
+        ctx: &Context<'_>,
+        id: Uuid,
+        input: UpdateTicketInput,
+        propagation: Option<PropagationPolicy>,
+        actor_id: Option<Uuid>,
+    ) -> GraphQLResult<SupportTicket> {
+        let support_repo = repository(ctx)?;
+        validation::validate_ticket_update(&input).map_err(|e| e.extend())?;
+        check_ticket_scope(ctx, id).await?;
+
+        let ticket = support_repo
+            .update_ticket_with_propagation(id, actor_id, &input, propagation.unwrap_or_default())
+            .await
+            .map_err(|e| e.extend())?;
+        Ok(ticket)
+    }
+
+    /// Reopen a resolved or closed ticket, moving it back to IN_PROGRESS (NEW if unassigned) and counting the reopen
+    ///
+    /// Note: Services should implement authorization checks (e.g., support:write permission)
+    async fn reopen_ticket(&self, ctx: &Context<'_>, id: Uuid) -> GraphQLResult<SupportTicket> {
+        let support_repo = repository(ctx)?;
+        let agent_id = ViewerRole::agent_id(ctx)?;
+        check_ticket_scope(ctx, id).await?;
+
+        let ticket = support_repo.reopen_ticket(id, Some(agent_id)).await?;
+        Ok(ticket)
+    }
+
+    /// Rate a resolved or closed ticket from 1 to 5
+    ///
+    /// The rating is credited to the agent who resolved the ticket.
+    ///
+    /// Note: Services should provide customer_id from authenticated user context
+    async fn submit_csat(&self, ctx: &Context<'_>, ticket_id: Uuid, customer_id: Uuid, score: i32) -> GraphQLResult<SupportTicket> {
+        let support_repo = repository(ctx)?;
+        validation::validate_csat_score(score).map_err(|e| e.extend())?;
+        check_ticket_scope(ctx, ticket_id).await?;
+
+        let ticket = support_repo.submit_csat(ticket_id, customer_id, score).await?;
+        Ok(ticket)
+    }
+
+    /// Rate a resolved or closed ticket with an optional comment
+    ///
+    /// Note: Services should provide customer_id from authenticated user context
+    async fn submit_csat_feedback(
+        &self,
+        ctx: &Context<'_>,
+        ticket_id: Uuid,
+        customer_id: Uuid,
+        input: CsatFeedbackInput,
+    ) -> GraphQLResult<CsatSurvey> {
+        let support_repo = repository(ctx)?;
+        validation::validate_csat_score(input.score).map_err(|e| e.extend())?;
+        check_ticket_scope(ctx, ticket_id).await?;
+
+        let survey = support_repo.submit_csat_feedback(ticket_id, customer_id, &input).await.map_err(|e| e.extend())?;
+        Ok(survey)
+    }
+
+    /// File an issue for a ticket in an engineering tracker and link it
+    ///
+    /// `project` is `owner/repo` for GitHub or the project key for Jira.
+    ///
+    /// Note: Services should provide actor_id from authenticated agent context
+    async fn escalate_to_engineering(
+        &self,
+        ctx: &Context<'_>,
+        ticket_id: Uuid,
+        actor_id: Uuid,
+        tracker: IssueTracker,
+        project: String,
+    ) -> GraphQLResult<EngineeringIssue> {
+        let support_repo = repository(ctx)?;
+        check_ticket_scope(ctx, ticket_id).await?;
+
+        let issue = support_repo.escalate_to_engineering(ticket_id, actor_id, tracker, &project).await?;
+        Ok(issue)
+    }
+
+    /// Link an existing engineering issue (e.g. `owner/repo#123`, `ENG-123`) to a ticket
+    ///
+    /// Note: Services should provide actor_id from authenticated agent context
+    async fn link_engineering_issue(
+        &self,
+        ctx: &Context<'_>,
+        ticket_id: Uuid,
+        actor_id: Uuid,
+        tracker: IssueTracker,
+        external_key: String,
+    ) -> GraphQLResult<EngineeringIssue> {
+        let support_repo = repository(ctx)?;
+        check_ticket_scope(ctx, ticket_id).await?;
+
+        let issue = support_repo.link_engineering_issue(ticket_id, actor_id, tracker, &external_key).await?;
+        Ok(issue)
+    }
+
+    /// Close an engineering issue in its tracker; every ticket linked to it is notified
+    ///
+    /// Note: Services should implement agent-only authorization before calling this
+    async fn close_engineering_issue(&self, ctx: &Context<'_>, id: Uuid) -> GraphQLResult<Vec<EngineeringIssue>> {
+        let support_repo = repository(ctx)?;
+        check_ticket_scope(ctx, support_repo.get_engineering_issue(id).await?.ticket_id).await?;
+
+        let issues = support_repo.close_engineering_issue(id).await?;
+        Ok(issues)
+    }
+
+    /// Redeliver a pending dead letter; it stays pending if redelivery fails again
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn retry_dead_letter(&self, ctx: &Context<'_>, id: Uuid, actor_id: Option<Uuid>) -> GraphQLResult<DeadLetter> {
+        let support_repo = repository(ctx)?;
+
+        let letter = support_repo.retry_dead_letter(id, actor_id).await?;
+        Ok(letter)
+    }
+
+    /// Discard a pending dead letter
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn discard_dead_letter(&self, ctx: &Context<'_>, id: Uuid, actor_id: Option<Uuid>) -> GraphQLResult<DeadLetter> {
+        let support_repo = repository(ctx)?;
+
+        let letter = support_repo.discard_dead_letter(id, actor_id).await?;
+        Ok(letter)
+    }
+
+    /// Send a satisfaction survey for a resolved or closed ticket
+    ///
+    /// Returns the survey token and link to deliver to the customer; the survey
+    /// can be answered, and re-answered, until the link expires.
+    ///
+    /// Note: Services should implement agent-only authorization before calling this
+    async fn send_csat_survey(
+        &self,
+        ctx: &Context<'_>,
+        ticket_id: Uuid,
+        #[graphql(default)] channel: CsatChannel,
+    ) -> GraphQLResult<CsatSurveyLink> {
+        let support_repo = repository(ctx)?;
+        check_ticket_scope(ctx, ticket_id).await?;
+
+        let link = support_repo.send_csat_survey(ticket_id, channel).await?;
+        Ok(link)
+    }
+
+    /// Bulk-create tickets from an uploaded CSV file
+    ///
+    /// Defaults to the conventional column names and all-or-nothing mode.
+    ///
+    /// Note: Services should implement agent-only authorization before calling this
+    async fn import_support_tickets(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        file: Upload,
+        mapping: Option<CsvColumnMapping>,
+        mode: Option<ImportMode>,
+    ) -> GraphQLResult<ImportReport> {
+        let support_repo = repository(ctx)?;
+
+        let upload = file.value(ctx)?;
+        let parsed = import::csv_tickets(upload.into_read(), &mapping.unwrap_or_default())?;
+
+        let report = support_repo.import_tickets(&product, parsed, mode.unwrap_or_default()).await?;
+        Ok(report)
+    }
+
+    /// Import CSAT and NPS responses collected in external survey tools
+    ///
+    /// Responses are matched to tickets by reference or by email; the report
+    /// lists the ones that matched no ticket.
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn import_external_csat(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        records: Vec<ExternalCsatRecord>,
+    ) -> GraphQLResult<CsatImportReport> {
+        let support_repo = repository(ctx)?;
+
+        let report = support_repo.import_csat(&product, import::csat(records)).await?;
+        Ok(report)
+    }
+
+    /// Generate a CSV of the product's tickets matching `filter`, downloadable for 24 hours
+    ///
+    /// Note: Services should implement admin-only authorization before calling this,
+    /// and serve the file from `SupportRepository::get_export_file` on their download route
+    async fn export_support_tickets(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        filter: Option<TicketFilter>,
+        format: Option<ExportFormat>,
+    ) -> GraphQLResult<SupportExport> {
+        let support_repo = repository(ctx)?;
+        let agent_id = ViewerRole::agent_id(ctx)?;
+
+        let mut filter = filter.unwrap_or_default();
+        filter.organization_id = ViewerOrganization::scope(ctx, filter.organization_id)?;
+        let export = support_repo
+            .generate_ticket_export(&product, &filter, format.unwrap_or_default(), Some(agent_id))
+            .await?;
+        Ok(export)
+    }
+
+    /// Generate a CSV of the product's dashboard metrics for the period, downloadable for 24 hours
+    ///
+    /// Note: Services should implement admin-only authorization before calling this,
+    /// and serve the file from `SupportRepository::get_export_file` on their download route
+    async fn export_support_dashboard_metrics(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+        timezone: Option<String>,
+        format: Option<ExportFormat>,
+    ) -> GraphQLResult<SupportExport> {
+        let support_repo = repository(ctx)?;
+        let agent_id = ViewerRole::agent_id(ctx)?;
+
+        let organization_id = ViewerOrganization::scope(ctx, None)?;
+        let export = support_repo
+            .generate_dashboard_export(
+                &product,
+                organization_id,
+                period_start,
+                period_end,
+                timezone.as_deref(),
+                format.unwrap_or_default(),
+                Some(agent_id),
+            )
+            .await?;
+        Ok(export)
+    }
+
+    /// Generate the SLA breach digest CSV for the ISO week containing `week`, downloadable for 24 hours
+    ///
+    /// Note: Services should implement admin-only authorization before calling this,
+    /// and serve the file from `SupportRepository::get_export_file` on their download route
+    async fn export_sla_breach_digest(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        week: NaiveDate,
+        timezone: Option<String>,
+        format: Option<ExportFormat>,
+    ) -> GraphQLResult<SupportExport> {
+        let support_repo = repository(ctx)?;
+        let agent_id = ViewerRole::agent_id(ctx)?;
+
+        let export = support_repo
+            .generate_sla_breach_digest_export(&product, week, timezone.as_deref(), format.unwrap_or_default(), Some(agent_id))
+            .await?;
+        Ok(export)
+    }
+
+    /// Generate a CSV of the product's metrics history, one row per bucket, downloadable for 24 hours
+    ///
+    /// Note: Services should implement admin-only authorization before calling this,
+    /// and serve the file from `SupportRepository::get_export_file` on their download route
+    async fn export_metrics_history(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        interval: Option<MetricsInterval>,
+        format: Option<ExportFormat>,
+    ) -> GraphQLResult<SupportExport> {
+        let support_repo = repository(ctx)?;
+        let agent_id = ViewerRole::agent_id(ctx)?;
+
+        let organization_id = ViewerOrganization::scope(ctx, None)?;
+        let export = support_repo
+            .generate_metrics_history_export(
+                &product,
+                organization_id,
+                from,
+                to,
+                interval.unwrap_or_default(),
+                format.unwrap_or_default(),
+                Some(agent_id),
+            )
+            .await?;
+        Ok(export)
+    }
+
+    /// Record that the customer viewed their ticket (or a specific reply) in the portal
+    ///
+    /// Note: Services should provide customer_id from authenticated user context
+    async fn record_ticket_view(
+        &self,
+        ctx: &Context<'_>,
+        ticket_id: Uuid,
+        customer_id: Uuid,
+        message_id: Option<Uuid>,
+    ) -> GraphQLResult<TicketView> {
+        let support_repo = repository(ctx)?;
+        check_ticket_scope(ctx, ticket_id).await?;
+
+        let view = support_repo.record_ticket_view(ticket_id, customer_id, message_id).await?;
+        Ok(view)
+    }
+
+    /// Record a download of a ticket attachment for the security audit log
+    ///
+    /// Where the download came from is taken from the request's `SubmitterInfo`.
+    ///
+    /// Note: Services should call this from their attachment download handler
+    /// with accessed_by from authenticated user context
+    async fn record_attachment_access(
+        &self,
+        ctx: &Context<'_>,
+        ticket_id: Uuid,
+        attachment_id: Uuid,
+        file_name: Option<String>,
+        accessed_by: Uuid,
+    ) -> GraphQLResult<AttachmentAccess> {
+        let support_repo = repository(ctx)?;
+        check_ticket_scope(ctx, ticket_id).await?;
+
+        let origin = ctx.data_opt::<SubmitterInfo>();
+        let access = support_repo
+            .record_attachment_access(ticket_id, attachment_id, file_name.as_deref(), accessed_by, origin)
+            .await?;
+        Ok(access)
+    }
+
+    /// Ingest a customer's inbound email as a new ticket or a reply on their thread
+    ///
+    /// `raw_email` is the full RFC 5322 message from the mail provider.
+    ///
+    /// Note: Services should call this from their inbound mail webhook after
+    /// resolving the sender address to customer_id
+    async fn ingest_inbound_email(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        customer_id: Uuid,
+        raw_email: String,
+    ) -> GraphQLResult<EmailIngestion> {
+        let support_repo = repository(ctx)?;
+
+        let email = InboundEmail::parse(&raw_email)?;
+        let ingestion = support_repo.ingest_email(&product, customer_id, &email).await?;
+        Ok(ingestion)
+    }
+
+    /// Record the SMTP Message-ID and References of an agent reply sent by email
+    ///
+    /// Note: Services should call this from their mail sender after delivery
+    async fn record_outbound_email(
+        &self,
+        ctx: &Context<'_>,
+        message_id: Uuid,
+        smtp_message_id: String,
+        thread_refs: Option<Vec<String>>,
+    ) -> GraphQLResult<OutboundEmail> {
+        let support_repo = repository(ctx)?;
+        check_message_scope(ctx, message_id).await?;
+
+        let email = support_repo
+            .record_outbound_email(message_id, &smtp_message_id, &thread_refs.unwrap_or_default())
+            .await?;
+        Ok(email)
+    }
+
+    /// React to an internal message (e.g. ACK to acknowledge it)
+    ///
+    /// Note: Services should provide agent_id from authenticated user context
+    async fn react_to_message(
+        &self,
+        ctx: &Context<'_>,
+        message_id: Uuid,
+        agent_id: Uuid,
+        reaction: MessageReaction,
+    ) -> GraphQLResult<TicketMessageReaction> {
+        let support_repo = repository(ctx)?;
+        check_message_scope(ctx, message_id).await?;
+
+        FieldGuard::require(ctx, SensitiveField::InternalMessages)?;
+
+        let reaction = support_repo.add_reaction(message_id, agent_id, reaction).await?;
+        Ok(reaction)
+    }
+
+    /// Remove a reaction from a message
+    async fn remove_message_reaction(
+        &self,
+        ctx: &Context<'_>,
+        message_id: Uuid,
+        agent_id: Uuid,
+        reaction: MessageReaction,
+    ) -> GraphQLResult<bool> {
+        let support_repo = repository(ctx)?;
+        check_message_scope(ctx, message_id).await?;
+
+        let removed = support_repo.remove_reaction(message_id, agent_id, reaction).await?;
+        Ok(removed)
+    }
+
+    /// Create or update an agent profile (skills, timezone, availability)
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn upsert_support_agent(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        input: UpsertSupportAgentInput,
+    ) -> GraphQLResult<SupportAgent> {
+        let support_repo = repository(ctx)?;
+
+        let agent = support_repo.upsert_agent(&product, &input).await?;
+        Ok(agent)
+    }
+
+    /// Record that the signed-in agent's console is open, for agents-online counts
+    async fn record_agent_presence(&self, ctx: &Context<'_>, product: String) -> GraphQLResult<SupportAgent> {
+        let support_repo = repository(ctx)?;
+        let agent_id = ViewerRole::agent_id(ctx)?;
+
+        let agent = support_repo.record_agent_presence(&product, agent_id).await?;
+        Ok(agent)
+    }
+
+    /// Record that the signed-in agent has a ticket open; call every few seconds while it is
+    ///
+    /// Returns everyone on the ticket, including the caller.
+    async fn view_ticket(&self, ctx: &Context<'_>, ticket_id: Uuid) -> GraphQLResult<Vec<TicketViewer>> {
+        let support_repo = repository(ctx)?;
+        let agent_id = ViewerRole::agent_id(ctx)?;
+        check_ticket_scope(ctx, ticket_id).await?;
+
+        let viewers = support_repo.record_ticket_viewing(ticket_id, agent_id).await?;
+        Ok(viewers)
+    }
+
+    /// Show or clear the signed-in agent's typing indicator on a ticket
+    ///
+    /// Call with `typing: true` as the agent types; the indicator lapses after a few
+    /// seconds without a call, and sending a reply clears it.
+    async fn set_ticket_typing(
+        &self,
+        ctx: &Context<'_>,
+        ticket_id: Uuid,
+        typing: bool,
+    ) -> GraphQLResult<Vec<TicketViewer>> {
+        let support_repo = repository(ctx)?;
+        let agent_id = ViewerRole::agent_id(ctx)?;
+        check_ticket_scope(ctx, ticket_id).await?;
+
+        let viewers = support_repo.record_ticket_typing(ticket_id, agent_id, typing).await?;
+        Ok(viewers)
+    }
+
+    /// Record that the signed-in agent closed a ticket
+    async fn leave_ticket(&self, ctx: &Context<'_>, ticket_id: Uuid) -> GraphQLResult<bool> {
+        let support_repo = repository(ctx)?;
+        let agent_id = ViewerRole::agent_id(ctx)?;
+        check_ticket_scope(ctx, ticket_id).await?;
+
+        let left = support_repo.leave_ticket(ticket_id, agent_id).await?;
+        Ok(left)
+    }
+
+    /// Register a product, or change a registered product's configuration
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn upsert_product_config(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        input: ProductConfigInput,
+    ) -> GraphQLResult<ProductConfig> {
+        let support_repo = repository(ctx)?;
+
+        let config = support_repo.upsert_product_config(&product, &input).await?;
+        Ok(config)
+    }
+
+    /// Unregister a product so no new tickets can be created for it
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn delete_product_config(&self, ctx: &Context<'_>, product: String) -> GraphQLResult<bool> {
+        let support_repo = repository(ctx)?;
+
+        let deleted = support_repo.delete_product_config(&product).await?;
+        Ok(deleted)
+    }
+
+    /// Add a resolution code to a product's taxonomy, or change or retire one
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn upsert_resolution_code(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        code: String,
+        input: ResolutionCodeInput,
+    ) -> GraphQLResult<ResolutionCode> {
+        let support_repo = repository(ctx)?;
+
+        let resolution_code = support_repo.upsert_resolution_code(&product, &code, &input).await?;
+        Ok(resolution_code)
+    }
+
+    /// Set first-response and resolution targets for a product and priority
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn set_sla_target(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        input: SetSlaTargetInput,
+    ) -> GraphQLResult<SlaTarget> {
+        let support_repo = repository(ctx)?;
+
+        let target = support_repo.set_sla_target(&product, &input).await?;
+        Ok(target)
+    }
+
+    /// Remove a product and priority's SLA targets, falling back to the default policy
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn delete_sla_target(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        priority: TicketPriority,
+    ) -> GraphQLResult<bool> {
+        let support_repo = repository(ctx)?;
+
+        let deleted = support_repo.delete_sla_target(&product, priority).await?;
+        Ok(deleted)
+    }
+
+    /// Schedule a maintenance window that pauses SLA clocks and suppresses alerts
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn create_maintenance_window(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        created_by: Uuid,
+        input: CreateMaintenanceWindowInput,
+    ) -> GraphQLResult<MaintenanceWindow> {
+        let support_repo = repository(ctx)?;
+
+        let window = support_repo.create_maintenance_window(&product, created_by, &input).await?;
+        Ok(window)
+    }
+
+    /// Delete a maintenance window
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn delete_maintenance_window(&self, ctx: &Context<'_>, id: Uuid) -> GraphQLResult<bool> {
+        let support_repo = repository(ctx)?;
+
+        let deleted = support_repo.delete_maintenance_window(id).await?;
+        Ok(deleted)
+    }
+
+    /// Add an automatic assignment rule for new tickets
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn create_assignment_rule(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        input: CreateAssignmentRuleInput,
+    ) -> GraphQLResult<AssignmentRule> {
+        let support_repo = repository(ctx)?;
+
+        let rule = support_repo.create_assignment_rule(&product, &input).await?;
+        Ok(rule)
+    }
+
+    /// Enable or disable an assignment rule
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn set_assignment_rule_active(&self, ctx: &Context<'_>, id: Uuid, active: bool) -> GraphQLResult<AssignmentRule> {
+        let support_repo = repository(ctx)?;
+
+        let rule = support_repo.set_assignment_rule_active(id, active).await?;
+        Ok(rule)
+    }
+
+    /// Delete an assignment rule
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn delete_assignment_rule(&self, ctx: &Context<'_>, id: Uuid) -> GraphQLResult<bool> {
+        let support_repo = repository(ctx)?;
+
+        let deleted = support_repo.delete_assignment_rule(id).await?;
+        Ok(deleted)
+    }
+
+    /// Add an escalation rule for neglected tickets
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn create_escalation_rule(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        input: CreateEscalationRuleInput,
+    ) -> GraphQLResult<EscalationRule> {
+        let support_repo = repository(ctx)?;
+
+        let rule = support_repo.create_escalation_rule(&product, &input).await?;
+        Ok(rule)
+    }
+
+    /// Enable or disable an escalation rule
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn set_escalation_rule_active(&self, ctx: &Context<'_>, id: Uuid, active: bool) -> GraphQLResult<EscalationRule> {
+        let support_repo = repository(ctx)?;
+
+        let rule = support_repo.set_escalation_rule_active(id, active).await?;
+        Ok(rule)
+    }
+
+    /// Delete an escalation rule; tickets keep the levels it gave them
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn delete_escalation_rule(&self, ctx: &Context<'_>, id: Uuid) -> GraphQLResult<bool> {
+        let support_repo = repository(ctx)?;
+
+        let deleted = support_repo.delete_escalation_rule(id).await?;
+        Ok(deleted)
+    }
+
+    /// Schedule a daily or weekly summary report
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn create_report_schedule(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        input: CreateReportScheduleInput,
+    ) -> GraphQLResult<ReportSchedule> {
+        let support_repo = repository(ctx)?;
+        let agent_id = ViewerRole::agent_id(ctx)?;
+
+        let schedule = support_repo.create_report_schedule(&product, &input, Some(agent_id)).await?;
+        Ok(schedule)
+    }
+
+    /// Change a report schedule; omitted fields keep their value
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn update_report_schedule(
+        &self,
+        ctx: &Context<'_>,
+        id: Uuid,
+        input: UpdateReportScheduleInput,
+    ) -> GraphQLResult<ReportSchedule> {
+        let support_repo = repository(ctx)?;
+
+        let schedule = support_repo.update_report_schedule(id, &input).await?;
+        Ok(schedule)
+    }
+
+    /// Delete a report schedule with its past runs
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn delete_report_schedule(&self, ctx: &Context<'_>, id: Uuid) -> GraphQLResult<bool> {
+        let support_repo = repository(ctx)?;
+
+        let deleted = support_repo.delete_report_schedule(id).await?;
+        Ok(deleted)
+    }
+
+    /// Add a workflow automation rule
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn create_automation_rule(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        input: CreateAutomationRuleInput,
+    ) -> GraphQLResult<AutomationRule> {
+        let support_repo = repository(ctx)?;
+
+        let rule = support_repo.create_automation_rule(&product, &input).await?;
+        Ok(rule)
+    }
+
+    /// Enable or disable an automation rule
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn set_automation_rule_active(&self, ctx: &Context<'_>, id: Uuid, active: bool) -> GraphQLResult<AutomationRule> {
+        let support_repo = repository(ctx)?;
+
+        let rule = support_repo.set_automation_rule_active(id, active).await?;
+        Ok(rule)
+    }
+
+    /// Delete an automation rule and its execution log; tickets keep its changes
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn delete_automation_rule(&self, ctx: &Context<'_>, id: Uuid) -> GraphQLResult<bool> {
+        let support_repo = repository(ctx)?;
+
+        let deleted = support_repo.delete_automation_rule(id).await?;
+        Ok(deleted)
+    }
+
+    /// Route an unassigned ticket with the product's assignment rules
+    ///
+    /// Returns null when no rule matches or no agent is eligible.
+    ///
+    /// Note: Services should implement agent-only authorization before calling this
+    async fn auto_assign_ticket(&self, ctx: &Context<'_>, ticket_id: Uuid) -> GraphQLResult<Option<RoutingDecision>> {
+        let support_repo = repository(ctx)?;
+        check_ticket_scope(ctx, ticket_id).await?;
+
+        let decision = support_repo.auto_assign_ticket(ticket_id).await?;
+        Ok(decision)
+    }
+
+    /// Link a child ticket to a parent (incident) ticket of the same product and organization
+    async fn link_child_ticket(
+        &self,
+        ctx: &Context<'_>,
+        parent_id: Uuid,
+        child_id: Uuid,
+    ) -> GraphQLResult<SupportTicket> {
+        let support_repo = repository(ctx)?;
+        check_tickets_scope(ctx, &[parent_id, child_id]).await?;
+
+        let ticket = support_repo.link_child_ticket(parent_id, child_id).await?;
+        Ok(ticket)
+    }
+
+    /// Remove a child ticket's link to its parent
+    async fn unlink_child_ticket(&self, ctx: &Context<'_>, child_id: Uuid) -> GraphQLResult<SupportTicket> {
+        let support_repo = repository(ctx)?;
+        check_ticket_scope(ctx, child_id).await?;
+
+        let ticket = support_repo.unlink_child_ticket(child_id).await?;
+        Ok(ticket)
+    }
+
+    /// Merge duplicates into a primary ticket, closing them; undo with `undoMerge`
+    async fn merge_tickets(
+        &self,
+        ctx: &Context<'_>,
+        primary_id: Uuid,
+        duplicate_ids: Vec<Uuid>,
+    ) -> GraphQLResult<TicketMerge> {
+        let support_repo = repository(ctx)?;
+        let agent_id = ViewerRole::agent_id(ctx)?;
+        check_ticket_scope(ctx, primary_id).await?;
+        check_tickets_scope(ctx, &duplicate_ids).await?;
+
+        let merge = support_repo.merge_tickets(primary_id, &duplicate_ids, Some(agent_id)).await?;
+        Ok(merge)
+    }
+
+    /// Restore the tickets of a merge, while its undo window is open
+    async fn undo_merge(&self, ctx: &Context<'_>, merge_id: Uuid) -> GraphQLResult<TicketMerge> {
+        let support_repo = repository(ctx)?;
+        let agent_id = ViewerRole::agent_id(ctx)?;
+        check_ticket_scope(ctx, support_repo.get_ticket_merge(merge_id).await?.primary_ticket_id).await?;
+
+        let merge = support_repo.undo_merge(merge_id, Some(agent_id)).await?;
+        Ok(merge)
+    }
+
+    /// Soft-delete a ticket; it can be restored until purged
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn delete_support_ticket(&self, ctx: &Context<'_>, id: Uuid) -> GraphQLResult<SupportTicket> {
+        let support_repo = repository(ctx)?;
+        let agent_id = ViewerRole::agent_id(ctx)?;
+        check_ticket_scope(ctx, id).await?;
+
+        let ticket = support_repo.delete_ticket(id, agent_id).await?;
+        Ok(ticket)
+    }
+
+    /// Restore a soft-deleted ticket
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn restore_support_ticket(&self, ctx: &Context<'_>, id: Uuid) -> GraphQLResult<SupportTicket> {
+        let support_repo = repository(ctx)?;
+        let agent_id = ViewerRole::agent_id(ctx)?;
+        check_ticket_scope(ctx, id).await?;
+
+        let ticket = support_repo.restore_ticket(id, agent_id).await?;
+        Ok(ticket)
+    }
+
+    /// Permanently remove the product's tickets soft-deleted before `deletedBefore`; returns how many were purged
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn purge_deleted_tickets(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        deleted_before: DateTime<Utc>,
+    ) -> GraphQLResult<u64> {
+        let support_repo = repository(ctx)?;
+
+        let purged = support_repo.purge_deleted_tickets(Some(&product), deleted_before).await?;
+        Ok(purged)
+    }
+
+    /// Create a personal, team or product-wide canned response
+    ///
+    /// Note: Services should provide author_id from authenticated user context
+    async fn create_canned_response(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        author_id: Uuid,
+        input: CreateCannedResponseInput,
+    ) -> GraphQLResult<CannedResponse> {
+        let support_repo = repository(ctx)?;
+
+        let response = support_repo.create_canned_response(&product, author_id, &input).await?;
+        Ok(response)
+    }
+
+    /// Edit a canned response the agent may manage
+    ///
+    /// Note: Services should provide editor_id from authenticated user context
+    async fn update_canned_response(
+        &self,
+        ctx: &Context<'_>,
+        id: Uuid,
+        editor_id: Uuid,
+        input: UpdateCannedResponseInput,
+    ) -> GraphQLResult<CannedResponse> {
+        let support_repo = repository(ctx)?;
+
+        let response = support_repo.update_canned_response(id, editor_id, &input).await?;
+        Ok(response)
+    }
+
+    /// Delete a canned response the agent may manage
+    ///
+    /// Note: Services should provide editor_id from authenticated user context
+    async fn delete_canned_response(&self, ctx: &Context<'_>, id: Uuid, editor_id: Uuid) -> GraphQLResult<bool> {
+        let support_repo = repository(ctx)?;
+
+        support_repo.delete_canned_response(id, editor_id).await?;
+        Ok(true)
+    }
+
+    /// Add a knowledge base article
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn create_knowledge_article(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        created_by: Option<Uuid>,
+        input: CreateKnowledgeArticleInput,
+    ) -> GraphQLResult<KnowledgeArticle> {
+        let support_repo = repository(ctx)?;
+
+        let article = support_repo.create_knowledge_article(&product, created_by, &input).await?;
+        Ok(article)
+    }
+
+    /// Edit, publish or unpublish a knowledge base article
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn update_knowledge_article(
+        &self,
+        ctx: &Context<'_>,
+        id: Uuid,
+        input: UpdateKnowledgeArticleInput,
+    ) -> GraphQLResult<KnowledgeArticle> {
+        let support_repo = repository(ctx)?;
+
+        let article = support_repo.update_knowledge_article(id, &input).await?;
+        Ok(article)
+    }
+
+    /// Delete a knowledge base article
+    ///
+    /// Note: Services should implement admin-only authorization before calling this
+    async fn delete_knowledge_article(&self, ctx: &Context<'_>, id: Uuid) -> GraphQLResult<bool> {
+        let support_repo = repository(ctx)?;
+
+        let deleted = support_repo.delete_knowledge_article(id).await?;
+        Ok(deleted)
+    }
+
+    /// Count a use of a canned response when an agent inserts it into a reply
+    async fn record_canned_response_use(&self, ctx: &Context<'_>, id: Uuid) -> GraphQLResult<CannedResponse> {
+        let support_repo = repository(ctx)?;
+
+        let response = support_repo.record_canned_response_use(id).await?;
+        Ok(response)
+    }
+
+    /// Reply on a ticket with a canned response, translated into the ticket's language
+    ///
+    /// Placeholders such as `{{customer_name}}` are filled in from the ticket,
+    /// customer and agent; `variables` add or override values. Set `translate`
+    /// to false to send the response's original text.
+    ///
+    /// Note: Services should provide author_id from authenticated user context
+    async fn send_canned_response(
+        &self,
+        ctx: &Context<'_>,
+        ticket_id: Uuid,
+        author_id: Uuid,
+        canned_response_id: Uuid,
+        variables: Option<Vec<TemplateVariable>>,
+        #[graphql(default = true)] translate: bool,
+    ) -> GraphQLResult<TicketMessage> {
+        let support_repo = repository(ctx)?;
+        check_ticket_scope(ctx, ticket_id).await?;
+
+        let message = support_repo
+            .send_canned_response(author_id, ticket_id, canned_response_id, &variables.unwrap_or_default(), translate)
+            .await?;
+        Ok(message)
+    }
+
+    /// Save a named ticket filter for the agent's sidebar
+    ///
+    /// Note: Services should provide owner_id from authenticated user context
+    async fn create_saved_view(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        owner_id: Uuid,
+        input: SaveViewInput,
+    ) -> GraphQLResult<SavedView> {
+        let support_repo = repository(ctx)?;
+
+        let view = support_repo.create_saved_view(&product, owner_id, &input).await?;
+        Ok(view)
+    }
+
+    /// Rename one of the agent's saved views or change its filter or sort order
+    ///
+    /// Note: Services should provide owner_id from authenticated user context
+    async fn update_saved_view(
+        &self,
+        ctx: &Context<'_>,
+        view_id: Uuid,
+        owner_id: Uuid,
+        input: UpdateSavedViewInput,
+    ) -> GraphQLResult<SavedView> {
+        let support_repo = repository(ctx)?;
+
+        let view = support_repo.update_saved_view(view_id, owner_id, &input).await?;
+        Ok(view)
+    }
+
+    /// Delete one of the agent's saved views
+    ///
+    /// Note: Services should provide owner_id from authenticated user context
+    async fn delete_saved_view(&self, ctx: &Context<'_>, view_id: Uuid, owner_id: Uuid) -> GraphQLResult<bool> {
+        let support_repo = repository(ctx)?;
+
+        let deleted = support_repo.delete_saved_view(view_id, owner_id).await?;
+        Ok(deleted)
+    }
+
+    /// Turn notifications of newly matching tickets on or off for a saved view
+    ///
+    /// Note: Services should provide owner_id from authenticated user context
+    async fn set_saved_view_notifications(
+        &self,
+        ctx: &Context<'_>,
+        view_id: Uuid,
+        owner_id: Uuid,
+        enabled: bool,
+    ) -> GraphQLResult<SavedView> {
+        let support_repo = repository(ctx)?;
+
+        let view = support_repo.set_saved_view_notifications(view_id, owner_id, enabled).await?;
+        Ok(view)
+    }
+
+    /// Mark a ticket as read by the agent, clearing it from unread badges
+    ///
+    /// Note: Services should provide agent_id from authenticated user context
+    async fn mark_ticket_read(&self, ctx: &Context<'_>, ticket_id: Uuid, agent_id: Uuid) -> GraphQLResult<TicketRead> {
+        let support_repo = repository(ctx)?;
+        check_ticket_scope(ctx, ticket_id).await?;
+
+        let read = support_repo.mark_ticket_read(ticket_id, agent_id).await?;
+        Ok(read)
+    }
+
+    /// Add a message to a ticket
+    ///
+    /// Note: Services should provide author_id from authenticated user context
+    async fn add_ticket_message(
+        &self,
+        ctx: &Context<'_>,
+        author_id: Uuid,
+        input: AddTicketMessageInput,
+    ) -> GraphQLResult<TicketMessage> {
+        let support_repo = repository(ctx)?;
+        validation::validate_message(&input).map_err(|e| e.extend())?;
+        check_ticket_scope(ctx, input.ticket_id).await?;
+
+        match input.visibility() {
+            MessageVisibility::Public => {}
+            MessageVisibility::Internal => FieldGuard::require(ctx, SensitiveField::InternalMessages)?,
+            MessageVisibility::Restricted => {
+                FieldGuard::require(ctx, SensitiveField::InternalMessages)?;
+                FieldGuard::require(ctx, SensitiveField::RestrictedMessages)?;
+            }
+        }
+
+        let message = support_repo.add_message(author_id, &input).await.map_err(|e| e.extend())?;
+        Ok(message)
+    }
+}
+
+#[ComplexObject]
+impl SupportTicket {
+    /// Globally unique, opaque id (Relay `Node`)
+    pub(crate) async fn id(&self) -> ID {
+        encode_global_id(NodeType::SupportTicket, self.id)
+    }
+
+    /// Database UUID, for arguments that take one
+    async fn database_id(&self) -> Uuid {
+        self.id
+    }
+
+    /// Submitter IP/user agent/geo captured at intake, for abuse investigation
+    #[graphql(name = "submitter", guard = "SensitiveFieldGuard(SensitiveField::Metadata)")]
+    async fn submitter_info(&self) -> Option<SubmitterMetadata> {
+        self.submitter()
+    }
+
+    /// App version, platform and device the ticket was filed from, if reported
+    #[graphql(name = "client")]
+    async fn client_info(&self) -> Option<ClientInfo> {
+        self.client()
+    }
+
+    /// When the customer last viewed the ticket and the latest agent reply
+    async fn customer_last_seen(&self, ctx: &Context<'_>) -> GraphQLResult<TicketLastSeen> {
+        let support_repo = repository(ctx)?;
+
+        let last_seen = support_repo.get_last_seen(self.id).await?;
+        Ok(last_seen)
+    }
+
+    /// Customer name/email/plan from the registered customer directory
+    async fn customer(&self, ctx: &Context<'_>) -> GraphQLResult<Option<CustomerInfo>> {
+        load_customer(ctx, self.customer_id).await
+    }
+
+    /// The ticket's messages, oldest first
+    ///
+    /// Internal and restricted messages are omitted unless the registered
+    /// `FieldGuard` allows them. Batched through the request's `DataLoader<MessagesByTicketLoader>`
+    /// when present.
+    async fn messages(&self, ctx: &Context<'_>) -> GraphQLResult<Vec<TicketMessage>> {
+        let mut messages = match ctx.data_opt::<DataLoader<MessagesByTicketLoader>>() {
+            Some(loader) => loader.load_one(self.id).await?.unwrap_or_default(),
+            None => repository(ctx)?.get_messages(self.id, MessageVisibility::Restricted).await?,
+        };
+        let viewer = FieldGuard::message_visibility(ctx);
+        messages.retain(|m| m.visibility.visible_to(viewer));
+        Ok(messages)
+    }
+}
+
+#[ComplexObject]
+impl AbuseBlocklistCandidate {
+    /// Customer name/email/plan from the registered customer directory
+    async fn customer(&self, ctx: &Context<'_>) -> GraphQLResult<Option<CustomerInfo>> {
+        load_customer(ctx, self.customer_id).await
+    }
+}
+
+#[ComplexObject]
+impl CrmCoreTopCustomer {
+    /// Customer name/email/plan from the registered customer directory
+    async fn customer(&self, ctx: &Context<'_>) -> GraphQLResult<Option<CustomerInfo>> {
+        load_customer(ctx, self.customer_id).await
+    }
+}
+
+#[ComplexObject]
+impl CustomerContext {
+    /// Customer name/email/plan from the registered customer directory
+    async fn customer(&self, ctx: &Context<'_>) -> GraphQLResult<Option<CustomerInfo>> {
+        load_customer(ctx, self.customer_id).await
+    }
+}
+
+#[ComplexObject]
+impl CustomerSupportSummary {
+    /// Customer name/email/plan from the registered customer directory
+    async fn customer(&self, ctx: &Context<'_>) -> GraphQLResult<Option<CustomerInfo>> {
+        load_customer(ctx, self.customer_id).await
+    }
+}
+
+/// Wide ticket columns the selection of a `SupportTicket` reads
+pub(crate) fn ticket_projection(ticket: Lookahead<'_>) -> TicketProjection {
+    TicketProjection {
+        description: ticket.field("description").exists(),
+        metadata: ["metadata", "submitter", "client"].into_iter().any(|field| ticket.field(field).exists()),
+    }
+}
+
+/// Fail with `TicketNotFound`, as if it did not exist, when the request is
+/// confined to a [`ViewerOrganization`] the ticket does not belong to
+pub(crate) async fn check_ticket_scope(ctx: &Context<'_>, ticket_id: Uuid) -> GraphQLResult<()> {
+    if let Some(organization_id) = ViewerOrganization::scope(ctx, None)? {
+        if repository(ctx)?.ticket_organization(ticket_id).await? != Some(organization_id) {
+            return Err(SupportError::TicketNotFound(ticket_id).into());
+        }
+    }
+    Ok(())
+}
+
+/// [`check_ticket_scope`] for each of `ticket_ids`
+async fn check_tickets_scope(ctx: &Context<'_>, ticket_ids: &[Uuid]) -> GraphQLResult<()> {
+    for &ticket_id in ticket_ids {
+        check_ticket_scope(ctx, ticket_id).await?;
+    }
+    Ok(())
+}
+
+/// [`check_ticket_scope`] for the ticket a message is on
+async fn check_message_scope(ctx: &Context<'_>, message_id: Uuid) -> GraphQLResult<()> {
+    if ViewerOrganization::scope(ctx, None)?.is_some() {
+        let ticket_id = repository(ctx)?.message_ticket_id(message_id).await?;
+        check_ticket_scope(ctx, ticket_id).await?;
+    }
+    Ok(())
+}
+
+/// Resolve a customer through the request's `DataLoader<CustomerLoader>` when
+/// present, otherwise straight from the repository's directory
+async fn load_customer(ctx: &Context<'_>, customer_id: Uuid) -> GraphQLResult<Option<CustomerInfo>> {
+    if let Some(loader) = ctx.data_opt::<DataLoader<CustomerLoader>>() {
+        return Ok(loader.load_one(customer_id).await?);
+    }
+
+    let support_repo = repository(ctx)?;
+    let mut customers = support_repo.lookup_customers(&[customer_id]).await?;
+    Ok(customers.remove(&customer_id))
+}
+
+#[ComplexObject]
+impl TicketMessage {
+    /// Globally unique, opaque id (Relay `Node`)
+    pub(crate) async fn id(&self) -> ID {
+        encode_global_id(NodeType::TicketMessage, self.id)
+    }
+
+    /// Database UUID, for arguments that take one
+    async fn database_id(&self) -> Uuid {
+        self.id
+    }
+
+    /// Agent reactions on this message (internal messages only)
+    async fn reactions(&self, ctx: &Context<'_>) -> GraphQLResult<Vec<TicketMessageReaction>> {
+        if !self.is_internal {
+            return Ok(Vec::new());
+        }
+        let support_repo = repository(ctx)?;
+
+        let reactions = support_repo.get_reactions(self.id).await?;
+        Ok(reactions)
+    }
+
+    /// User ids of the agents this message @mentions
+    async fn mentions(&self, ctx: &Context<'_>) -> GraphQLResult<Vec<Uuid>> {
+        let support_repo = repository(ctx)?;
+
+        let mentions = support_repo.get_message_mentions(self.id).await?;
+        Ok(mentions.into_iter().map(|m| m.agent_id).collect())
+    }
+}
+
+#[ComplexObject]
+impl SupportAgent {
+    /// Globally unique, opaque id (Relay `Node`)
+    pub(crate) async fn id(&self) -> ID {
+        encode_global_id(NodeType::SupportAgent, self.id)
+    }
+
+    /// Database UUID, for arguments that take one
+    async fn database_id(&self) -> Uuid {
+        self.id
+    }
+}
+
+#[ComplexObject]
+impl SavedView {
+    /// Globally unique, opaque id (Relay `Node`)
+    pub(crate) async fn id(&self) -> ID {
+        encode_global_id(NodeType::SavedView, self.id)
+    }
+
+    /// Database UUID, for arguments that take one
+    async fn database_id(&self) -> Uuid {
+        self.id
+    }
+}
+
+#[ComplexObject]
+impl CannedResponse {
+    /// Globally unique, opaque id (Relay `Node`)
+    pub(crate) async fn id(&self) -> ID {
+        encode_global_id(NodeType::CannedResponse, self.id)
+    }
+
+    /// Database UUID, for arguments that take one
+    async fn database_id(&self) -> Uuid {
+        self.id
+    }
+}
@@ -0,0 +1,55 @@
+//! Standalone support schema and its SDL
+//!
+//! Gateway teams validate supergraph composition, and consuming services
+//! generate clients, from the SDL of the support extensions alone.
+//! [`support_sdl`] prints it federation-ready, with the `Query`/`Mutation`
+//! extensions marked `extend`, so a CI step can write it without a database
+//! or a running service:
+//!
+//! ```rust
+//! let sdl = pleme_support::support_sdl();
+//!
+//! assert!(sdl.contains("extend type Query"));
+//! assert!(sdl.contains("supportTickets("));
+//! assert!(sdl.contains("extend type Subscription"));
+//! assert!(sdl.contains("queueSnapshot("));
+//! // std::fs::write("schema/support.graphql", sdl)?;
+//! ```
+
+use async_graphql::{MergedObject, MergedSubscription, Schema, SDLExportOptions};
+
+use super::{AgentSupportMutations, AgentSupportQueries, CustomerSupportMutations, CustomerSupportQueries, SupportSubscriptions};
+
+/// Agent console and customer portal queries in one root
+#[derive(MergedObject, Default)]
+#[graphql(name = "Query", extends)]
+pub struct SupportQueryRoot(AgentSupportQueries, CustomerSupportQueries);
+
+/// Agent console and customer portal mutations in one root
+#[derive(MergedObject, Default)]
+#[graphql(name = "Mutation", extends)]
+pub struct SupportMutationRoot(AgentSupportMutations, CustomerSupportMutations);
+
+/// Agent subscriptions as the subscription root
+#[derive(MergedSubscription, Default)]
+#[graphql(name = "Subscription", extends)]
+pub struct SupportSubscriptionRoot(SupportSubscriptions);
+
+/// Schema holding only the support extensions
+pub type SupportSchema = Schema<SupportQueryRoot, SupportMutationRoot, SupportSubscriptionRoot>;
+
+/// Build the support-only schema with federation enabled
+///
+/// No data is registered; it is meant for introspection and SDL export,
+/// not for executing requests.
+pub fn support_schema() -> SupportSchema {
+    Schema::build(SupportQueryRoot::default(), SupportMutationRoot::default(), SupportSubscriptionRoot::default())
+        .enable_federation()
+        .enable_subscription_in_federation()
+        .finish()
+}
+
+/// Federation-ready SDL of the support Query/Mutation/Subscription extensions
+pub fn support_sdl() -> String {
+    support_schema().sdl_with_options(SDLExportOptions::new().federation())
+}
@@ -0,0 +1,89 @@
+//! Agent subscriptions
+//!
+//! Pushed updates for displays that would otherwise poll, admitted only for a
+//! [`ViewerRole::Agent`](crate::ViewerRole) request like the rest of the
+//! support console.
+
+use async_graphql::{Context, Result as GraphQLResult, Subscription};
+use futures_util::stream::{self, Stream};
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+use uuid::Uuid;
+
+use crate::guard::AgentGuard;
+use crate::graphql::check_ticket_scope;
+use crate::system::repository;
+use crate::viewers::{TicketViewer, DEFAULT_VIEWER_POLL_SECONDS, MAX_VIEWER_POLL_SECONDS, MIN_VIEWER_POLL_SECONDS};
+use crate::wallboard::{
+    QueueSnapshot, DEFAULT_SNAPSHOT_INTERVAL_SECONDS, MAX_SNAPSHOT_INTERVAL_SECONDS, MIN_SNAPSHOT_INTERVAL_SECONDS,
+};
+
+#[derive(Default)]
+pub struct SupportSubscriptions;
+
+#[Subscription(guard = "AgentGuard")]
+impl SupportSubscriptions {
+    /// Queue aggregates for wallboards, pushed on subscribing and then every `intervalSeconds`
+    ///
+    /// `intervalSeconds` defaults to 30 and is clamped to 5..3600. A failed
+    /// snapshot is sent as an error and the subscription carries on.
+    async fn queue_snapshot(
+        &self,
+        ctx: &Context<'_>,
+        product: String,
+        interval_seconds: Option<u64>,
+    ) -> async_graphql::Result<impl Stream<Item = GraphQLResult<QueueSnapshot>>> {
+        let support_repo = repository(ctx)?.clone();
+
+        let interval = interval_seconds
+            .unwrap_or(DEFAULT_SNAPSHOT_INTERVAL_SECONDS)
+            .clamp(MIN_SNAPSHOT_INTERVAL_SECONDS, MAX_SNAPSHOT_INTERVAL_SECONDS);
+        let mut ticks = tokio::time::interval(Duration::from_secs(interval));
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        Ok(stream::unfold((support_repo, product, ticks), |(support_repo, product, mut ticks)| async move {
+            ticks.tick().await;
+            let snapshot = support_repo.queue_snapshot(&product).await.map_err(Into::into);
+            Some((snapshot, (support_repo, product, ticks)))
+        }))
+    }
+
+    /// Agents on a ticket, pushed on subscribing and whenever someone arrives,
+    /// leaves, starts or stops typing
+    ///
+    /// Changes are picked up every `pollSeconds`, which defaults to 2 and is
+    /// clamped to 1..30. A failed check is sent as an error and the
+    /// subscription carries on.
+    async fn ticket_viewers(
+        &self,
+        ctx: &Context<'_>,
+        ticket_id: Uuid,
+        poll_seconds: Option<u64>,
+    ) -> async_graphql::Result<impl Stream<Item = GraphQLResult<Vec<TicketViewer>>>> {
+        let support_repo = repository(ctx)?.clone();
+        check_ticket_scope(ctx, ticket_id).await?;
+
+        let interval = poll_seconds
+            .unwrap_or(DEFAULT_VIEWER_POLL_SECONDS)
+            .clamp(MIN_VIEWER_POLL_SECONDS, MAX_VIEWER_POLL_SECONDS);
+        let mut ticks = tokio::time::interval(Duration::from_secs(interval));
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let state = (support_repo, ticks, None::<Vec<(Uuid, bool)>>);
+        Ok(stream::unfold(state, move |(support_repo, mut ticks, mut pushed)| async move {
+            loop {
+                ticks.tick().await;
+                match support_repo.get_ticket_viewers(ticket_id).await {
+                    Ok(viewers) => {
+                        let present: Vec<(Uuid, bool)> = viewers.iter().map(|v| (v.user_id, v.typing)).collect();
+                        if pushed.as_ref() != Some(&present) {
+                            pushed = Some(present);
+                            return Some((Ok(viewers), (support_repo, ticks, pushed)));
+                        }
+                    }
+                    Err(e) => return Some((Err(e.into()), (support_repo, ticks, pushed))),
+                }
+            }
+        }))
+    }
+}
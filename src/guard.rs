@@ -0,0 +1,198 @@
+//! Field-level authorization hooks
+//!
+//! Embedding services register a [`FieldGuard`] in schema data to restrict
+//! sensitive fields by role without forking the resolvers in this crate.
+//!
+//! ```rust,no_run
+//! use pleme_support::{FieldGuard, SensitiveField};
+//!
+//! struct Role(&'static str);
+//!
+//! let guard = FieldGuard::new(|ctx, field| {
+//!     let is_agent = ctx.data_opt::<Role>().map(|r| r.0 == "agent").unwrap_or(false);
+//!     match field {
+//!         SensitiveField::CsatScore | SensitiveField::AssignedTo => true,
+//!         SensitiveField::InternalMessages | SensitiveField::Metadata => is_agent,
+//!         SensitiveField::RestrictedMessages => false,
+//!     }
+//! });
+//!
+//! // Schema::build(QueryRoot, MutationRoot, EmptySubscription)
+//! //     .data(guard)
+//! //     .finish()
+//! ```
+//!
+//! When no `FieldGuard` is registered every field resolves as before, except
+//! `metadata` and restricted messages, which stay hidden until a guard
+//! explicitly allows them.
+//!
+//! Services also register the request's [`ViewerRole`]. The agent surface
+//! (`AgentSupportQueries`/`AgentSupportMutations`) requires an agent, and the
+//! customer surface scopes every resolver to the customer's own tickets.
+//! Customers never see internal messages, assignment or metadata, whatever
+//! the `FieldGuard` says. A [`ViewerOrganization`] additionally confines
+//! every ticket read or write, list and dashboard to one B2B account's
+//! tickets.
+
+use async_graphql::{Context, Enum, Guard, Result as GraphQLResult};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::system::SupportSystem;
+use crate::{MessageVisibility, SupportError};
+
+/// Fields that embedding services can restrict
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Hash)]
+pub enum SensitiveField {
+    CsatScore,
+    InternalMessages,
+    /// `RESTRICTED` messages; also needs `InternalMessages`
+    RestrictedMessages,
+    Metadata,
+    AssignedTo,
+}
+
+type GuardFn = dyn Fn(&Context<'_>, SensitiveField) -> bool + Send + Sync;
+
+/// Closure-based access check consulted by the support resolvers
+#[derive(Clone)]
+pub struct FieldGuard {
+    check: Arc<GuardFn>,
+}
+
+impl FieldGuard {
+    pub fn new<F>(check: F) -> Self
+    where
+        F: Fn(&Context<'_>, SensitiveField) -> bool + Send + Sync + 'static,
+    {
+        Self { check: Arc::new(check) }
+    }
+
+    /// Whether the current request may read `field`
+    pub fn allows(ctx: &Context<'_>, field: SensitiveField) -> bool {
+        if matches!(ctx.data_opt::<ViewerRole>(), Some(ViewerRole::Customer(_))) && field != SensitiveField::CsatScore {
+            return false;
+        }
+        let guard = ctx.data_opt::<FieldGuard>().or_else(|| ctx.data_opt::<SupportSystem>().and_then(SupportSystem::field_guard));
+        match guard {
+            Some(guard) => (guard.check)(ctx, field),
+            None => !matches!(field, SensitiveField::Metadata | SensitiveField::RestrictedMessages),
+        }
+    }
+
+    /// The most restricted messages the current request may read
+    ///
+    /// Customers read public messages only.
+    pub fn message_visibility(ctx: &Context<'_>) -> MessageVisibility {
+        if !Self::allows(ctx, SensitiveField::InternalMessages) {
+            MessageVisibility::Public
+        } else if Self::allows(ctx, SensitiveField::RestrictedMessages) {
+            MessageVisibility::Restricted
+        } else {
+            MessageVisibility::Internal
+        }
+    }
+
+    /// Like [`FieldGuard::allows`], but returns `SupportError::Unauthorized` on denial
+    pub fn require(ctx: &Context<'_>, field: SensitiveField) -> crate::Result<()> {
+        if Self::allows(ctx, field) {
+            Ok(())
+        } else {
+            Err(SupportError::Unauthorized)
+        }
+    }
+}
+
+impl std::fmt::Debug for FieldGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FieldGuard").finish_non_exhaustive()
+    }
+}
+
+/// async-graphql guard that defers to the registered [`FieldGuard`]
+pub struct SensitiveFieldGuard(pub SensitiveField);
+
+impl Guard for SensitiveFieldGuard {
+    async fn check(&self, ctx: &Context<'_>) -> GraphQLResult<()> {
+        FieldGuard::require(ctx, self.0)?;
+        Ok(())
+    }
+}
+
+/// Who is making the request, registered by the service in request data
+///
+/// ```rust,no_run
+/// use pleme_support::ViewerRole;
+/// # fn example(request: async_graphql::Request, customer_id: uuid::Uuid) {
+/// let request = request.data(ViewerRole::Customer(customer_id));
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ViewerRole {
+    /// A signed-in customer, by customer id
+    Customer(Uuid),
+    /// A support agent or admin, by user id; also used for service-to-service calls
+    Agent(Uuid),
+}
+
+impl ViewerRole {
+    /// The request's role, or `SupportError::Unauthorized` when none is registered
+    pub fn of(ctx: &Context<'_>) -> crate::Result<ViewerRole> {
+        ctx.data_opt::<ViewerRole>().copied().ok_or(SupportError::Unauthorized)
+    }
+
+    /// The signed-in customer's id, or `SupportError::Unauthorized` for anyone else
+    pub fn customer_id(ctx: &Context<'_>) -> crate::Result<Uuid> {
+        match Self::of(ctx)? {
+            ViewerRole::Customer(customer_id) => Ok(customer_id),
+            ViewerRole::Agent(_) => Err(SupportError::Unauthorized),
+        }
+    }
+
+    /// The signed-in agent's id, or `SupportError::Unauthorized` for anyone else
+    pub fn agent_id(ctx: &Context<'_>) -> crate::Result<Uuid> {
+        match Self::of(ctx)? {
+            ViewerRole::Agent(user_id) => Ok(user_id),
+            ViewerRole::Customer(_) => Err(SupportError::Unauthorized),
+        }
+    }
+}
+
+/// async-graphql guard admitting only [`ViewerRole::Agent`] requests
+pub struct AgentGuard;
+
+impl Guard for AgentGuard {
+    async fn check(&self, ctx: &Context<'_>) -> GraphQLResult<()> {
+        ViewerRole::agent_id(ctx)?;
+        Ok(())
+    }
+}
+
+/// The B2B account a request is confined to, registered by the service in
+/// request data for tenant-scoped agents and customers
+///
+/// ```rust,no_run
+/// use pleme_support::{ViewerOrganization, ViewerRole};
+/// # fn example(request: async_graphql::Request, agent_id: uuid::Uuid, organization_id: uuid::Uuid) {
+/// let request = request.data(ViewerRole::Agent(agent_id)).data(ViewerOrganization(organization_id));
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ViewerOrganization(pub Uuid);
+
+impl ViewerOrganization {
+    /// The organization a query must be scoped to: the registered one, or
+    /// `requested` when none is registered
+    ///
+    /// Asking for another organization than the registered one fails with
+    /// `SupportError::Unauthorized`.
+    pub fn scope(ctx: &Context<'_>, requested: Option<Uuid>) -> crate::Result<Option<Uuid>> {
+        match ctx.data_opt::<ViewerOrganization>() {
+            None => Ok(requested),
+            Some(ViewerOrganization(organization_id)) => match requested {
+                Some(requested) if requested != *organization_id => Err(SupportError::Unauthorized),
+                _ => Ok(Some(*organization_id)),
+            },
+        }
+    }
+}
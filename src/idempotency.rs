@@ -0,0 +1,67 @@
+//! Idempotent ticket creation
+//!
+//! Mobile apps and partner integrations retry ticket submissions when a
+//! response is lost on a flaky network. A client that sends the same
+//! `idempotency_key` with each attempt gets the ticket its first attempt
+//! created back instead of a duplicate. Keys are scoped to the product and
+//! customer, and remembered for as long as the ticket exists.
+//!
+//! A key is bound to the request it was first sent with: reusing it for a
+//! different subject, description, priority, category, channel or error
+//! signature fails with `DuplicateRequest` rather than silently returning an
+//! unrelated ticket.
+//!
+//! ```rust
+//! use pleme_support::idempotency::{normalize_idempotency_key, request_fingerprint};
+//! use pleme_support::CreateTicketInput;
+//!
+//! assert_eq!(normalize_idempotency_key(" 7f3c-retry ").unwrap(), "7f3c-retry");
+//! assert!(normalize_idempotency_key("   ").is_err());
+//!
+//! let first = CreateTicketInput { subject: "Can't log in".into(), ..Default::default() };
+//! let retry = CreateTicketInput { locale: Some("pt-BR".into()), ..first.clone() };
+//! let other = CreateTicketInput { subject: "Refund".into(), ..first.clone() };
+//! assert_eq!(request_fingerprint(&first), request_fingerprint(&retry));
+//! assert_ne!(request_fingerprint(&first), request_fingerprint(&other));
+//! ```
+//!
+//! Tickets created through a [`SupportTransaction`](crate::SupportTransaction)
+//! honor keys too, but a concurrent request holding the same key fails the
+//! transaction with `DuplicateRequest` instead of waiting for its ticket.
+
+use sha2::{Digest, Sha256};
+
+use crate::{CreateTicketInput, Result, SupportError};
+
+/// Longest accepted idempotency key
+pub const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 255;
+
+/// Trim an idempotency key, rejecting empty or overlong keys
+pub fn normalize_idempotency_key(key: &str) -> Result<String> {
+    let key = key.trim();
+    if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LENGTH {
+        return Err(SupportError::Validation(format!(
+            "Idempotency key must be between 1 and {} characters",
+            MAX_IDEMPOTENCY_KEY_LENGTH
+        )));
+    }
+    Ok(key.to_string())
+}
+
+/// Hash of the parts of a ticket request a retry must repeat
+///
+/// Intake details that may differ between attempts (locale, client info,
+/// customer plan) are left out.
+pub fn request_fingerprint(input: &CreateTicketInput) -> String {
+    let request = serde_json::json!([
+        input.customer_id,
+        input.subject,
+        input.description,
+        input.priority,
+        input.category,
+        input.channel,
+        input.test_mode,
+        input.error_signature,
+    ]);
+    format!("{:x}", Sha256::digest(request.to_string().as_bytes()))
+}
@@ -0,0 +1,412 @@
+//! Bulk ticket intake from CSV, and CSAT from external survey tools
+//!
+//! [`csv_tickets`] parses and validates a spreadsheet export using a
+//! [`CsvColumnMapping`], collecting per-row errors instead of stopping at the
+//! first bad row. The parsed result is handed to
+//! [`SupportRepository::import_tickets`](crate::SupportRepository::import_tickets),
+//! which either inserts every valid row or, in all-or-nothing mode, none.
+//!
+//! [`csat`] validates survey responses exported from tools like SurveyMonkey
+//! or Typeform the same way, normalizing NPS scores to the 1-5 CSAT scale.
+//! [`SupportRepository::import_csat`](crate::SupportRepository::import_csat)
+//! then matches each response to a ticket and reports the ones it couldn't
+//! place:
+//!
+//! ```rust
+//! use chrono::Utc;
+//! use pleme_support::import::{self, ExternalCsatRecord, SurveyScale};
+//!
+//! let record = |score, scale| ExternalCsatRecord {
+//!     source: "typeform".to_string(),
+//!     response_id: format!("r{}", score),
+//!     external_ref: Some("SUP-000042".to_string()),
+//!     email: None,
+//!     score,
+//!     scale,
+//!     comment: None,
+//!     responded_at: Utc::now(),
+//! };
+//!
+//! let parsed = import::csat(vec![
+//!     record(4, SurveyScale::Csat),
+//!     record(10, SurveyScale::Nps),
+//!     record(7, SurveyScale::Csat),
+//! ]);
+//! assert_eq!(parsed.responses.len(), 2);
+//! assert_eq!(parsed.responses[1].1.score, 5);
+//! assert_eq!(parsed.responses[1].1.nps_score, Some(10));
+//! assert_eq!(parsed.errors[0].row, 3);
+//! ```
+
+use async_graphql::{Enum, InputObject, SimpleObject};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::io::Read;
+use uuid::Uuid;
+
+use crate::models::{CreateTicketInput, TicketChannel, TicketPriority};
+use crate::validation::{MAX_CATEGORY_LENGTH, MAX_SUBJECT_LENGTH};
+use crate::{Result, SupportError};
+
+/// Header names of the CSV columns holding each ticket field
+#[derive(Debug, Clone, InputObject)]
+pub struct CsvColumnMapping {
+    pub customer_id: String,
+    pub subject: String,
+    pub description: String,
+    /// Optional column; rows without a value default to MEDIUM
+    pub priority: Option<String>,
+    pub category: Option<String>,
+    pub customer_plan: Option<String>,
+    /// Optional column; rows without a value default to WEB
+    pub channel: Option<String>,
+    /// Optional column of B2B account UUIDs; rows without a value have no organization
+    pub organization_id: Option<String>,
+}
+
+impl Default for CsvColumnMapping {
+    fn default() -> Self {
+        Self {
+            customer_id: "customer_id".to_string(),
+            subject: "subject".to_string(),
+            description: "description".to_string(),
+            priority: Some("priority".to_string()),
+            category: Some("category".to_string()),
+            customer_plan: None,
+            channel: None,
+            organization_id: None,
+        }
+    }
+}
+
+/// How rows are committed when some of them fail
+#[derive(Debug, Clone, Copy, Default, Enum, Eq, PartialEq)]
+pub enum ImportMode {
+    /// Insert nothing unless every row is valid and inserts cleanly
+    #[default]
+    AllOrNothing,
+    /// Insert every valid row and report the rest
+    PartialSuccess,
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct ImportRowError {
+    /// 1-based data row number, excluding the header
+    pub row: i32,
+    pub column: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct ImportReport {
+    pub total_rows: i32,
+    pub imported: i32,
+    pub failed: i32,
+    pub ticket_ids: Vec<Uuid>,
+    pub errors: Vec<ImportRowError>,
+}
+
+/// Validated rows ready for insertion, plus the rows that failed validation
+#[derive(Debug, Clone, Default)]
+pub struct CsvImport {
+    pub tickets: Vec<(i32, CreateTicketInput)>,
+    pub errors: Vec<ImportRowError>,
+    pub total_rows: i32,
+}
+
+/// Parse and validate tickets from CSV using the given column mapping
+///
+/// Returns an error only when the file itself is unusable (unreadable header
+/// or a mapped column missing); row problems are collected in the result.
+pub fn csv_tickets<R: Read>(reader: R, mapping: &CsvColumnMapping) -> Result<CsvImport> {
+    let mut csv_reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(reader);
+
+    let headers: HashMap<String, usize> = csv_reader
+        .headers()
+        .map_err(|e| SupportError::InvalidInput(format!("Unreadable CSV header: {}", e)))?
+        .iter()
+        .enumerate()
+        .map(|(i, h)| (h.to_string(), i))
+        .collect();
+
+    let column = |name: &str| -> Result<usize> {
+        headers.get(name).copied().ok_or_else(|| {
+            SupportError::InvalidInput(format!("CSV is missing mapped column '{}'", name))
+        })
+    };
+    let customer_id_col = column(&mapping.customer_id)?;
+    let subject_col = column(&mapping.subject)?;
+    let description_col = column(&mapping.description)?;
+    let priority_col = mapping.priority.as_deref().map(column).transpose()?;
+    let category_col = mapping.category.as_deref().map(column).transpose()?;
+    let customer_plan_col = mapping.customer_plan.as_deref().map(column).transpose()?;
+    let channel_col = mapping.channel.as_deref().map(column).transpose()?;
+    let organization_id_col = mapping.organization_id.as_deref().map(column).transpose()?;
+
+    let mut import = CsvImport::default();
+
+    for (index, record) in csv_reader.records().enumerate() {
+        let row = index as i32 + 1;
+        import.total_rows = row;
+
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                import.errors.push(ImportRowError { row, column: None, message: e.to_string() });
+                continue;
+            }
+        };
+
+        let field = |col: usize| record.get(col).unwrap_or("");
+        let mut row_errors = Vec::new();
+        let mut error = |column: &str, message: String| {
+            row_errors.push(ImportRowError { row, column: Some(column.to_string()), message });
+        };
+
+        let customer_id = match Uuid::parse_str(field(customer_id_col)) {
+            Ok(id) => Some(id),
+            Err(_) => {
+                error(&mapping.customer_id, format!("'{}' is not a valid UUID", field(customer_id_col)));
+                None
+            }
+        };
+
+        let subject = field(subject_col);
+        if subject.is_empty() {
+            error(&mapping.subject, "Subject is required".to_string());
+        } else if subject.chars().count() > MAX_SUBJECT_LENGTH {
+            error(&mapping.subject, format!("Subject exceeds {} characters", MAX_SUBJECT_LENGTH));
+        }
+
+        let description = field(description_col);
+        if description.is_empty() {
+            error(&mapping.description, "Description is required".to_string());
+        }
+
+        let priority = match priority_col.map(field).filter(|v| !v.is_empty()) {
+            None => Some(TicketPriority::Medium),
+            Some(value) => match value.parse::<TicketPriority>() {
+                Ok(priority) => Some(priority),
+                Err(message) => {
+                    error(mapping.priority.as_deref().unwrap_or_default(), message);
+                    None
+                }
+            },
+        };
+
+        let category = category_col.map(field).filter(|v| !v.is_empty());
+        if category.is_some_and(|c| c.chars().count() > MAX_CATEGORY_LENGTH) {
+            error(
+                mapping.category.as_deref().unwrap_or_default(),
+                format!("Category exceeds {} characters", MAX_CATEGORY_LENGTH),
+            );
+        }
+
+        let customer_plan = customer_plan_col.map(field).filter(|v| !v.is_empty());
+
+        let organization_id = match organization_id_col.map(field).filter(|v| !v.is_empty()) {
+            None => None,
+            Some(value) => match Uuid::parse_str(value) {
+                Ok(id) => Some(id),
+                Err(_) => {
+                    error(mapping.organization_id.as_deref().unwrap_or_default(), format!("'{}' is not a valid UUID", value));
+                    None
+                }
+            },
+        };
+
+        let channel = match channel_col.map(field).filter(|v| !v.is_empty()) {
+            None => Some(TicketChannel::Web),
+            Some(value) => match value.parse::<TicketChannel>() {
+                Ok(channel) => Some(channel),
+                Err(message) => {
+                    error(mapping.channel.as_deref().unwrap_or_default(), message);
+                    None
+                }
+            },
+        };
+
+        match (customer_id, priority, channel) {
+            (Some(customer_id), Some(priority), Some(channel)) if row_errors.is_empty() => {
+                import.tickets.push((row, CreateTicketInput {
+                    customer_id,
+                    organization_id,
+                    subject: subject.to_string(),
+                    description: description.to_string(),
+                    priority: Some(priority),
+                    category: category.map(str::to_string),
+                    customer_plan: customer_plan.map(str::to_string),
+                    channel,
+                    locale: None,
+                    test_mode: false,
+                    error_signature: None,
+                    client: None,
+                    idempotency_key: None,
+                }));
+            }
+            _ => import.errors.append(&mut row_errors),
+        }
+    }
+
+    Ok(import)
+}
+
+/// How far back from a response the ticket it rates may have been resolved,
+/// when matching by email
+pub const CSAT_MATCH_WINDOW_DAYS: i64 = 30;
+
+const MAX_SOURCE_LEN: usize = 50;
+const MAX_RESPONSE_ID_LEN: usize = 255;
+
+/// Scale of an external survey's score
+#[derive(Debug, Clone, Copy, Default, Enum, Eq, PartialEq)]
+pub enum SurveyScale {
+    /// 1 (very dissatisfied) to 5 (very satisfied)
+    #[default]
+    Csat,
+    /// Net Promoter Score, 0 to 10
+    Nps,
+}
+
+impl SurveyScale {
+    /// `score` on the 1-5 CSAT scale; `None` when out of range
+    pub fn to_csat(&self, score: i32) -> Option<i32> {
+        match self {
+            SurveyScale::Csat => (1..=5).contains(&score).then_some(score),
+            // 0-10 mapped linearly onto 1-5, rounding half up
+            SurveyScale::Nps => (0..=10).contains(&score).then(|| 1 + (score * 4 + 5) / 10),
+        }
+    }
+}
+
+/// A survey response exported from an external tool
+#[derive(Debug, Clone, InputObject)]
+pub struct ExternalCsatRecord {
+    /// The tool it came from, e.g. `surveymonkey`
+    pub source: String,
+    /// The response's id in the tool, so re-imports are skipped
+    pub response_id: String,
+    /// Ticket reference or id carried by the survey, e.g. as a hidden field
+    pub external_ref: Option<String>,
+    /// Respondent email, matched when there is no reference
+    pub email: Option<String>,
+    pub score: i32,
+    #[graphql(default)]
+    pub scale: SurveyScale,
+    pub comment: Option<String>,
+    pub responded_at: DateTime<Utc>,
+}
+
+/// A validated external response, with its score on the CSAT scale
+#[derive(Debug, Clone)]
+pub struct ExternalCsatResponse {
+    pub source: String,
+    pub response_id: String,
+    pub external_ref: Option<String>,
+    /// Lowercased
+    pub email: Option<String>,
+    /// 1-5
+    pub score: i32,
+    /// The original score of NPS responses
+    pub nps_score: Option<i32>,
+    pub comment: Option<String>,
+    pub responded_at: DateTime<Utc>,
+}
+
+/// Validated responses ready for matching, plus the records that failed validation
+#[derive(Debug, Clone, Default)]
+pub struct CsatImport {
+    pub responses: Vec<(i32, ExternalCsatResponse)>,
+    pub errors: Vec<ImportRowError>,
+    pub total_rows: i32,
+}
+
+/// A valid response that matched no ticket
+#[derive(Debug, Clone, SimpleObject)]
+pub struct UnmatchedCsatRecord {
+    /// 1-based record number
+    pub row: i32,
+    pub source: String,
+    pub response_id: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, SimpleObject)]
+pub struct CsatImportReport {
+    pub total_rows: i32,
+    pub imported: i32,
+    /// Responses imported before, skipped
+    pub duplicates: i32,
+    pub unmatched: Vec<UnmatchedCsatRecord>,
+    pub errors: Vec<ImportRowError>,
+    /// Surveys created for the imported responses
+    pub survey_ids: Vec<Uuid>,
+}
+
+/// Validate external survey responses, collecting per-record errors
+///
+/// Each record needs a source, a response id, a score in its scale's range
+/// and a ticket reference or email to be matched by.
+pub fn csat(records: Vec<ExternalCsatRecord>) -> CsatImport {
+    let mut import = CsatImport::default();
+
+    for (index, record) in records.into_iter().enumerate() {
+        let row = index as i32 + 1;
+        import.total_rows = row;
+
+        let mut row_errors = Vec::new();
+        let mut error = |column: &str, message: String| {
+            row_errors.push(ImportRowError { row, column: Some(column.to_string()), message });
+        };
+
+        let source = record.source.trim().to_lowercase();
+        if source.is_empty() {
+            error("source", "Source is required".to_string());
+        } else if source.chars().count() > MAX_SOURCE_LEN {
+            error("source", format!("Source exceeds {} characters", MAX_SOURCE_LEN));
+        }
+
+        let response_id = record.response_id.trim();
+        if response_id.is_empty() {
+            error("response_id", "Response id is required".to_string());
+        } else if response_id.chars().count() > MAX_RESPONSE_ID_LEN {
+            error("response_id", format!("Response id exceeds {} characters", MAX_RESPONSE_ID_LEN));
+        }
+
+        let score = record.scale.to_csat(record.score);
+        if score.is_none() {
+            let range = match record.scale {
+                SurveyScale::Csat => "1 to 5",
+                SurveyScale::Nps => "0 to 10",
+            };
+            error("score", format!("Score {} is outside {}", record.score, range));
+        }
+
+        let external_ref = record.external_ref.map(|r| r.trim().to_string()).filter(|r| !r.is_empty());
+        let email = record.email.map(|e| e.trim().to_lowercase()).filter(|e| !e.is_empty());
+        if external_ref.is_none() && email.is_none() {
+            error("external_ref", "A ticket reference or email is required".to_string());
+        }
+
+        match score {
+            Some(score) if row_errors.is_empty() => {
+                import.responses.push((row, ExternalCsatResponse {
+                    source,
+                    response_id: response_id.to_string(),
+                    external_ref,
+                    email,
+                    score,
+                    nps_score: (record.scale == SurveyScale::Nps).then_some(record.score),
+                    comment: record.comment.map(|c| c.trim().to_string()).filter(|c| !c.is_empty()),
+                    responded_at: record.responded_at,
+                }));
+            }
+            _ => import.errors.append(&mut row_errors),
+        }
+    }
+
+    import
+}
@@ -0,0 +1,248 @@
+//! Submitter metadata captured at intake
+//!
+//! Customer-facing intake paths can record the submitter's IP address, user
+//! agent and coarse geo into ticket metadata (under the `submitter` key) for
+//! abuse investigation. Services provide a [`SubmitterInfo`] per request,
+//! e.g. as GraphQL request data built from HTTP headers; what is stored is
+//! governed by the repository's [`IntakePrivacy`] settings. Form telemetry
+//! ([`IntakeTelemetry`]) is stored under `intake_telemetry` and screened by
+//! the spam classifier.
+//!
+//! Apps filing tickets can also describe themselves with a [`ClientInfo`]
+//! (app version, platform, device model) on `CreateTicketInput`. It is
+//! checked and stored under the `client` key, so tickets can be filtered and
+//! counted by release.
+//!
+//! ```rust
+//! use pleme_support::{ClientInfo, ClientPlatform};
+//!
+//! let client = ClientInfo {
+//!     app_version: Some(" 4.12.0 ".to_string()),
+//!     platform: Some(ClientPlatform::Ios),
+//!     device_model: Some(String::new()),
+//! };
+//! let normalized = client.normalized().unwrap().unwrap();
+//! assert_eq!(normalized.app_version.as_deref(), Some("4.12.0"));
+//! assert_eq!(normalized.device_model, None);
+//!
+//! let bad = ClientInfo { app_version: Some("4.12 beta".to_string()), ..Default::default() };
+//! assert!(bad.normalized().is_err());
+//! assert_eq!(ClientInfo::default().normalized().unwrap(), None);
+//! ```
+
+use async_graphql::{Enum, InputObject, SimpleObject};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::net::IpAddr;
+
+use crate::models::SupportTicket;
+use crate::{Result, SupportError};
+
+/// Metadata key holding [`SubmitterMetadata`]
+pub const SUBMITTER_METADATA_KEY: &str = "submitter";
+
+/// Metadata key holding [`IntakeTelemetry`]
+pub const INTAKE_TELEMETRY_METADATA_KEY: &str = "intake_telemetry";
+
+/// Metadata key holding [`ClientInfo`]
+pub const CLIENT_METADATA_KEY: &str = "client";
+
+/// Longest accepted `app_version`
+pub const MAX_APP_VERSION_LENGTH: usize = 32;
+
+/// Longest accepted `device_model`
+pub const MAX_DEVICE_MODEL_LENGTH: usize = 64;
+
+/// Request-level information about whoever submitted a ticket
+#[derive(Debug, Clone, Default)]
+pub struct SubmitterInfo {
+    pub ip: Option<IpAddr>,
+    pub user_agent: Option<String>,
+    pub geo: Option<CoarseGeo>,
+    pub telemetry: Option<IntakeTelemetry>,
+}
+
+/// Signals from the intake form, collected client-side
+#[derive(Debug, Clone, Default, Serialize, Deserialize, SimpleObject, InputObject)]
+#[graphql(input_name = "IntakeTelemetryInput")]
+pub struct IntakeTelemetry {
+    /// Milliseconds between the form being shown and submitted
+    pub form_fill_ms: Option<i64>,
+    /// The hidden honeypot field was filled in
+    #[graphql(default)]
+    pub honeypot_triggered: bool,
+    /// Client-computed fingerprint hash; never a raw fingerprint
+    pub client_fingerprint_hash: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ClientPlatform {
+    Ios,
+    Android,
+    Web,
+    Macos,
+    Windows,
+    Linux,
+    Other,
+}
+
+/// The app a ticket was filed from, as reported by the client
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, SimpleObject, InputObject)]
+#[graphql(input_name = "ClientInfoInput")]
+pub struct ClientInfo {
+    /// Release the app reports, e.g. `4.12.0` or `4.12.0-beta.2+381`
+    pub app_version: Option<String>,
+    pub platform: Option<ClientPlatform>,
+    /// Device model the app reports, e.g. `iPhone15,2` or `Pixel 8`
+    pub device_model: Option<String>,
+}
+
+impl ClientInfo {
+    /// Trim the fields and check them, dropping blank ones
+    ///
+    /// Versions may only use letters, digits and `.-+_`. Returns `None` when
+    /// nothing is left to store.
+    pub fn normalized(&self) -> Result<Option<ClientInfo>> {
+        let trimmed = |value: &Option<String>| {
+            value.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(str::to_string)
+        };
+        let app_version = trimmed(&self.app_version);
+        let device_model = trimmed(&self.device_model);
+
+        if let Some(version) = &app_version {
+            if version.len() > MAX_APP_VERSION_LENGTH {
+                return Err(SupportError::Validation(format!(
+                    "app_version must be at most {} characters",
+                    MAX_APP_VERSION_LENGTH
+                )));
+            }
+            if !version.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+' | '_')) {
+                return Err(SupportError::Validation(format!("Invalid app_version: {}", version)));
+            }
+        }
+        if let Some(model) = &device_model {
+            if model.chars().count() > MAX_DEVICE_MODEL_LENGTH {
+                return Err(SupportError::Validation(format!(
+                    "device_model must be at most {} characters",
+                    MAX_DEVICE_MODEL_LENGTH
+                )));
+            }
+            if model.chars().any(char::is_control) {
+                return Err(SupportError::Validation("device_model must not contain control characters".to_string()));
+            }
+        }
+
+        let normalized = ClientInfo { app_version, platform: self.platform, device_model };
+        Ok((normalized != ClientInfo::default()).then_some(normalized))
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, SimpleObject)]
+pub struct CoarseGeo {
+    /// ISO 3166-1 alpha-2 country code
+    pub country: Option<String>,
+    pub region: Option<String>,
+}
+
+/// Privacy controls applied before submitter info is stored
+#[derive(Debug, Clone)]
+pub struct IntakePrivacy {
+    /// Capture submitter info at all
+    pub capture: bool,
+    /// Store a salted SHA-256 of the IP instead of the address itself
+    pub hash_ip: bool,
+    pub hash_salt: String,
+    pub capture_user_agent: bool,
+    pub capture_geo: bool,
+    /// How long submitter info is kept before `purge_expired_submitter_info` removes it
+    pub retention: Option<Duration>,
+}
+
+impl Default for IntakePrivacy {
+    fn default() -> Self {
+        Self {
+            capture: true,
+            hash_ip: true,
+            hash_salt: String::new(),
+            capture_user_agent: true,
+            capture_geo: true,
+            retention: Some(Duration::days(90)),
+        }
+    }
+}
+
+/// Submitter info as stored in ticket metadata
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct SubmitterMetadata {
+    pub ip: Option<String>,
+    pub ip_hash: Option<String>,
+    pub user_agent: Option<String>,
+    pub geo: Option<CoarseGeo>,
+    pub captured_at: DateTime<Utc>,
+}
+
+impl IntakePrivacy {
+    /// Apply the privacy settings, returning `None` when nothing should be stored
+    pub fn apply(&self, info: &SubmitterInfo, now: DateTime<Utc>) -> Option<SubmitterMetadata> {
+        if !self.capture {
+            return None;
+        }
+
+        let (ip, ip_hash) = match info.ip {
+            Some(ip) if self.hash_ip => (None, Some(self.hash_ip_address(ip))),
+            Some(ip) => (Some(ip.to_string()), None),
+            None => (None, None),
+        };
+
+        let metadata = SubmitterMetadata {
+            ip,
+            ip_hash,
+            user_agent: info.user_agent.clone().filter(|_| self.capture_user_agent),
+            geo: info.geo.clone().filter(|_| self.capture_geo),
+            captured_at: now,
+        };
+
+        let empty = metadata.ip.is_none()
+            && metadata.ip_hash.is_none()
+            && metadata.user_agent.is_none()
+            && metadata.geo.is_none();
+        (!empty).then_some(metadata)
+    }
+
+    /// Salted SHA-256 of an IP address, hex encoded
+    ///
+    /// Stable for a given salt, so hashed addresses can still be matched
+    /// against each other (e.g. to spot repeat submitters).
+    pub fn hash_ip_address(&self, ip: IpAddr) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.hash_salt.as_bytes());
+        hasher.update(ip.to_string().as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+impl SupportTicket {
+    /// Submitter info captured at intake, if any
+    pub fn submitter(&self) -> Option<SubmitterMetadata> {
+        self.metadata
+            .get(SUBMITTER_METADATA_KEY)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
+
+    /// Intake form telemetry, if any
+    pub fn intake_telemetry(&self) -> Option<IntakeTelemetry> {
+        self.metadata
+            .get(INTAKE_TELEMETRY_METADATA_KEY)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
+
+    /// App version, platform and device the ticket was filed from, if reported
+    pub fn client(&self) -> Option<ClientInfo> {
+        self.metadata
+            .get(CLIENT_METADATA_KEY)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
+}
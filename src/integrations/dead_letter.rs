@@ -0,0 +1,98 @@
+//! Dead letters for failed outbox and webhook events
+//!
+//! Service-side event processors retry deliveries themselves; once an event
+//! exhausts its retries they hand it to `record_dead_letter` instead of
+//! dropping it. A [`DeadLetter`] keeps the payload, attempt count and last
+//! error until someone acts on it:
+//!
+//! - `retry_dead_letter` redelivers it through the [`DeadLetterHandler`]
+//!   registered for its `source`. The letter is claimed as `RETRYING` first,
+//!   so concurrent retries never redeliver it twice. Success marks it
+//!   `RETRIED`; failure puts it back to `PENDING` with the new error and
+//!   attempt count.
+//! - `discard_dead_letter` marks it `DISCARDED` when it no longer matters.
+//!
+//! Pending and retrying letters feed the `DEAD_LETTERS` and `OLDEST_DEAD_LETTER_MINUTES`
+//! [alert metrics](crate::alerts), so letters left to age page someone.
+
+use async_graphql::{Enum, SimpleObject};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{Result, SupportError};
+
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "dead_letter_status", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DeadLetterStatus {
+    /// Awaiting retry or discard
+    Pending,
+    /// Being redelivered by `retry_dead_letter`
+    Retrying,
+    /// Redelivered successfully
+    Retried,
+    Discarded,
+}
+
+/// An event that exhausted its retries
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, SimpleObject)]
+pub struct DeadLetter {
+    pub id: Uuid,
+    pub product: String,
+    /// Integration that failed, e.g. `webhook:acme` or `outbox:email`
+    pub source: String,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub ticket_id: Option<Uuid>,
+    /// Delivery attempts so far, including manual retries
+    pub attempts: i32,
+    pub last_error: String,
+    pub status: DeadLetterStatus,
+    pub created_at: DateTime<Utc>,
+    pub last_failed_at: DateTime<Utc>,
+    /// When it was retried successfully or discarded
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub resolved_by: Option<Uuid>,
+}
+
+/// Event to record as a dead letter
+#[derive(Debug, Clone)]
+pub struct NewDeadLetter {
+    pub product: String,
+    pub source: String,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub ticket_id: Option<Uuid>,
+    pub attempts: i32,
+    pub last_error: String,
+}
+
+/// Redelivers dead letters of one source, implemented by services
+#[async_trait]
+pub trait DeadLetterHandler: Send + Sync {
+    async fn redeliver(&self, letter: &DeadLetter) -> Result<()>;
+}
+
+/// Dead letter handlers by source
+#[derive(Clone, Default)]
+pub struct DeadLetterHandlers {
+    handlers: HashMap<String, Arc<dyn DeadLetterHandler>>,
+}
+
+impl DeadLetterHandlers {
+    pub fn with_handler(mut self, source: impl Into<String>, handler: Arc<dyn DeadLetterHandler>) -> Self {
+        self.handlers.insert(source.into(), handler);
+        self
+    }
+
+    /// The registered handler; `Validation` when the source has none
+    pub fn handler(&self, source: &str) -> Result<&Arc<dyn DeadLetterHandler>> {
+        self.handlers
+            .get(source)
+            .ok_or_else(|| SupportError::Validation(format!("No dead letter handler registered for {}", source)))
+    }
+}
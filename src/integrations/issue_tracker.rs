@@ -0,0 +1,213 @@
+//! Escalation of tickets to engineering issue trackers
+//!
+//! When a ticket needs an engineering fix, `escalate_to_engineering` files an
+//! issue through the tracker's [`IssueTrackerClient`] and links it to the
+//! ticket as an [`EngineeringIssue`]; `link_engineering_issue` links an issue
+//! that already exists. Services implement the client over the GitHub or Jira
+//! API and register it per [`IssueTracker`]:
+//!
+//! ```rust,no_run
+//! use pleme_support::integrations::issue_tracker::{IssueTracker, IssueTrackers};
+//! use pleme_support::SupportRepository;
+//! # use pleme_support::integrations::issue_tracker::IssueTrackerClient;
+//! # use std::sync::Arc;
+//!
+//! # fn example(repo: SupportRepository, github: Arc<dyn IssueTrackerClient>) {
+//! let repo = repo.with_issue_trackers(IssueTrackers::default().with_client(IssueTracker::Github, github));
+//! # }
+//! ```
+//!
+//! Tracker webhooks (or a poller) report issue state changes back with
+//! `sync_engineering_issue_state`, which updates every linked ticket's issue
+//! and posts an internal note on the tickets so agents can follow up with
+//! their customers.
+//!
+//! Issue keys are the tracker's own references:
+//!
+//! ```rust
+//! use pleme_support::integrations::issue_tracker::{GithubIssueRef, JiraIssueRef};
+//!
+//! let issue: GithubIssueRef = "pleme-io/pleme-support#42".parse().unwrap();
+//! assert_eq!(issue.url(), "https://github.com/pleme-io/pleme-support/issues/42");
+//! assert_eq!(issue.to_string(), "pleme-io/pleme-support#42");
+//!
+//! let issue: JiraIssueRef = "ENG-123".parse().unwrap();
+//! assert_eq!(issue.project, "ENG");
+//! assert_eq!(issue.url("https://pleme.atlassian.net"), "https://pleme.atlassian.net/browse/ENG-123");
+//! assert!("ENG".parse::<JiraIssueRef>().is_err());
+//! ```
+
+use async_graphql::{Enum, SimpleObject};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{Result, SupportError};
+
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "issue_tracker", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum IssueTracker {
+    Github,
+    Jira,
+}
+
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "engineering_issue_state", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum IssueState {
+    Open,
+    /// Fixed, or otherwise closed in the tracker
+    Closed,
+}
+
+/// Issue to file in a tracker
+#[derive(Debug, Clone)]
+pub struct NewIssue {
+    /// `owner/repo` for GitHub, the project key for Jira
+    pub project: String,
+    pub title: String,
+    pub body: String,
+}
+
+/// An issue as the tracker reports it
+#[derive(Debug, Clone)]
+pub struct ExternalIssue {
+    /// The tracker's reference, e.g. `owner/repo#123` or `ENG-123`
+    pub key: String,
+    pub project: String,
+    pub url: String,
+    pub title: String,
+    pub state: IssueState,
+}
+
+/// Client for one issue tracker, implemented by services over its API
+#[async_trait]
+pub trait IssueTrackerClient: Send + Sync {
+    async fn create_issue(&self, issue: &NewIssue) -> Result<ExternalIssue>;
+
+    /// Look up an existing issue by key, to link it to a ticket
+    async fn get_issue(&self, key: &str) -> Result<ExternalIssue>;
+
+    async fn close_issue(&self, key: &str) -> Result<()>;
+}
+
+/// Issue tracker clients by tracker
+#[derive(Clone, Default)]
+pub struct IssueTrackers {
+    clients: HashMap<IssueTracker, Arc<dyn IssueTrackerClient>>,
+}
+
+impl IssueTrackers {
+    pub fn with_client(mut self, tracker: IssueTracker, client: Arc<dyn IssueTrackerClient>) -> Self {
+        self.clients.insert(tracker, client);
+        self
+    }
+
+    /// The registered client; `Validation` when the tracker is not configured
+    pub fn client(&self, tracker: IssueTracker) -> Result<&Arc<dyn IssueTrackerClient>> {
+        self.clients
+            .get(&tracker)
+            .ok_or_else(|| SupportError::Validation(format!("No issue tracker configured for {:?}", tracker)))
+    }
+}
+
+/// A tracker issue linked to a support ticket
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, SimpleObject)]
+pub struct EngineeringIssue {
+    pub id: Uuid,
+    pub ticket_id: Uuid,
+    pub product: String,
+    pub tracker: IssueTracker,
+    pub project: String,
+    pub external_key: String,
+    pub url: String,
+    pub title: String,
+    pub state: IssueState,
+    /// Agent who escalated or linked the issue
+    pub linked_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub closed_at: Option<DateTime<Utc>>,
+}
+
+/// GitHub issue reference, `owner/repo#number`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GithubIssueRef {
+    pub owner: String,
+    pub repo: String,
+    pub number: u64,
+}
+
+impl GithubIssueRef {
+    pub fn url(&self) -> String {
+        format!("https://github.com/{}/{}/issues/{}", self.owner, self.repo, self.number)
+    }
+}
+
+impl fmt::Display for GithubIssueRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}#{}", self.owner, self.repo, self.number)
+    }
+}
+
+impl FromStr for GithubIssueRef {
+    type Err = SupportError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || SupportError::InvalidInput(format!("Invalid GitHub issue reference: {}", s));
+
+        let (repository, number) = s.trim().split_once('#').ok_or_else(invalid)?;
+        let (owner, repo) = repository.split_once('/').ok_or_else(invalid)?;
+        if owner.is_empty() || repo.is_empty() || repo.contains('/') {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            number: number.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// Jira issue reference, `PROJECT-number`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct JiraIssueRef {
+    pub project: String,
+    pub number: u64,
+}
+
+impl JiraIssueRef {
+    /// Browse URL on the Jira site at `base_url`
+    pub fn url(&self, base_url: &str) -> String {
+        format!("{}/browse/{}", base_url.trim_end_matches('/'), self)
+    }
+}
+
+impl fmt::Display for JiraIssueRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.project, self.number)
+    }
+}
+
+impl FromStr for JiraIssueRef {
+    type Err = SupportError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || SupportError::InvalidInput(format!("Invalid Jira issue key: {}", s));
+
+        let (project, number) = s.trim().rsplit_once('-').ok_or_else(invalid)?;
+        if project.is_empty() || !project.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            project: project.to_ascii_uppercase(),
+            number: number.parse().map_err(|_| invalid())?,
+        })
+    }
+}
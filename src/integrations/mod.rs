@@ -0,0 +1,7 @@
+//! Integrations with external systems
+//!
+//! Each integration defines a client trait that services implement over the
+//! external API, so this crate stays free of HTTP clients and credentials.
+
+pub mod dead_letter;
+pub mod issue_tracker;
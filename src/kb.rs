@@ -0,0 +1,75 @@
+//! Knowledge base articles and suggestions
+//!
+//! A product's [`KnowledgeArticle`]s are help-center content indexed for
+//! full-text search in their own language, like tickets. `suggest_articles`
+//! ranks the published articles by how many of a ticket's stemmed subject and
+//! description words they share, weighting title matches above body matches.
+//! `suggest_articles_for_text` does the same for a ticket the customer is
+//! still writing, so the UI can offer an answer before the ticket is created.
+
+use async_graphql::{InputObject, SimpleObject};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Suggestions returned when no limit is given
+pub const DEFAULT_SUGGESTIONS: i64 = 5;
+
+/// Most suggestions returned for one ticket
+pub const MAX_SUGGESTIONS: i64 = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, SimpleObject)]
+pub struct KnowledgeArticle {
+    pub id: Uuid,
+    pub product: String,
+    pub title: String,
+    pub body: String,
+    /// Language of the article (BCP 47); `None` means the product's locale
+    pub locale: Option<String>,
+    /// Unpublished articles are never suggested
+    pub published: bool,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, InputObject)]
+pub struct CreateKnowledgeArticleInput {
+    pub title: String,
+    pub body: String,
+    pub locale: Option<String>,
+    #[graphql(default = true)]
+    pub published: bool,
+}
+
+/// Unset fields are left unchanged
+#[derive(Debug, Clone, Default, InputObject)]
+pub struct UpdateKnowledgeArticleInput {
+    pub title: Option<String>,
+    pub body: Option<String>,
+    pub locale: Option<String>,
+    pub published: Option<bool>,
+}
+
+/// An article and how closely it matches the ticket
+#[derive(Debug, Clone, FromRow, SimpleObject)]
+pub struct ArticleSuggestion {
+    #[sqlx(flatten)]
+    pub article: KnowledgeArticle,
+    /// Full-text rank; only comparable within one set of suggestions
+    pub score: f64,
+}
+
+/// Clamp a requested suggestion count to `1..=MAX_SUGGESTIONS`
+///
+/// ```rust
+/// use pleme_support::kb::{suggestion_limit, DEFAULT_SUGGESTIONS, MAX_SUGGESTIONS};
+///
+/// assert_eq!(suggestion_limit(None), DEFAULT_SUGGESTIONS);
+/// assert_eq!(suggestion_limit(Some(0)), 1);
+/// assert_eq!(suggestion_limit(Some(500)), MAX_SUGGESTIONS);
+/// ```
+pub fn suggestion_limit(limit: Option<i64>) -> i64 {
+    limit.unwrap_or(DEFAULT_SUGGESTIONS).clamp(1, MAX_SUGGESTIONS)
+}
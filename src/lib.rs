@@ -5,10 +5,70 @@
 //! ## Features
 //!
 //! - **Support Tickets** - Tickets with priorities, statuses, SLA tracking
-//! - **CSAT Scores** - Customer satisfaction tracking (1-5 scale)
+//! - **CSAT Surveys** - Satisfaction surveys with comments, token links and breakdowns
 //! - **Product Scoping** - Multi-product support (novaskyn, lilitu, thai)
-//! - **Dashboard Analytics** - 7 comprehensive metrics views
-//! - **GraphQL API** - Queries and mutations for ticket management
+//! - **Organization Scoping** - B2B account on tickets, with org-scoped lists, lookups and dashboards
+//! - **Product Registry** - Registered products with default priority, business hours and enabled channels
+//! - **Dashboard Analytics** - Comprehensive metrics views, including daily and weekly CSAT trends
+//! - **GraphQL API** - Agent console and customer portal surfaces, with viewer roles enforced in the resolvers
+//! - **SDL Export** - Federation-ready SDL of the support schema alone, for gateway composition checks in CI
+//! - **Cursor Pagination** - Relay connections with total counts for ticket lists
+//! - **Selective Loading** - Ticket queries load wide columns and counts only when the GraphQL selection reads them
+//! - **Bulk Operations** - Update, assign or close hundreds of tickets in one transaction
+//! - **Field Guards** - Role-based restriction of sensitive fields
+//! - **Attachment Access Log** - Audit trail of who downloaded attachments, when and from where
+//! - **Attachment Search** - Pluggable text extraction so ticket search matches attachment contents
+//! - **Attachment Scanning** - A scanner hook run before attachments become available, quarantining infected ones
+//! - **CSV Import** - Bulk ticket intake with per-row validation reports
+//! - **External CSAT Import** - Survey tool responses matched to tickets by reference or email
+//! - **Knowledge Base** - Help articles suggested by similarity to a ticket, before or after it is created
+//! - **Message Mentions** - Plaintext, Markdown or HTML messages with @mentions of agents recorded for notifications
+//! - **Message Visibility** - Public, internal and restricted messages, filtered by the viewer's level
+//! - **Resolution Codes** - Per-product taxonomies required on resolve, with resolution analytics and help-article candidates
+//! - **Ticket Reopens** - Resolved or closed tickets reopened with a count per ticket and a dashboard reopen rate
+//! - **Assignment Suggestions** - Ranked agent candidates with explanations
+//! - **Automatic Routing** - Round-robin, least-loaded and skill-based assignment rules
+//! - **Weighted Workload** - Open tickets weighted by priority for load-based assignment and agent workload views
+//! - **Ticket Escalation** - Rules that escalate neglected tickets and notify team leads
+//! - **Scheduled Reports** - Daily and weekly backlog, SLA and CSAT summaries generated from a cron job, with weekly SLA breach digests as CSV
+//! - **Workflow Automations** - Trigger-condition-action rules that tag, update, reply to or publish tickets
+//! - **Engineering Escalation** - GitHub/Jira issue links with state sync
+//! - **Saved View Notifications** - Saved searches that notify agents of new matching tickets
+//! - **Lifecycle Events** - Ticket and message events for other services, with signed webhooks
+//! - **Dead Letters** - Failed outbox/webhook events kept for retry or discard
+//! - **Email Channel** - Inbound email parsed into tickets or threaded replies
+//! - **Content Moderation** - Pluggable abuse filter on inbound customer messages
+//! - **Sentiment Boosts** - Scores customer messages and lets automations raise priority as sentiment falls
+//! - **Spam Screening** - Honeypot and form telemetry checks at intake
+//! - **Intake Rate Limits** - Per-customer, per-product caps on tickets opened in a rolling window
+//! - **Client Info** - App version, platform and device captured at intake, with per-release ticket counts
+//! - **Ticket Classification** - Pluggable priority and category suggestions for tickets created without them
+//! - **Intake API Keys** - Scoped, metered keys for partner ticket submission
+//! - **SLA Breach Detection** - A scheduled worker that flags tickets past their SLA deadlines and publishes `ticket.sla_breached`
+//! - **SLA Alerting** - Threshold events on breach rate and compliance
+//! - **Maintenance Windows** - Scheduled downtime that pauses SLA clocks and alerts
+//! - **Business Hours** - Per-product calendars with holidays that first-response SLA clocks run on
+//! - **Contractual SLA Reports** - Monthly per-account compliance in business hours
+//! - **Query Timeouts** - Statement timeouts for reads and analytics
+//! - **Warehouse Extracts** - Incremental star-schema views for analytics
+//! - **Response Translation** - Canned responses translated to the ticket's language
+//! - **Test Mode** - Sandbox tickets kept out of SLA, analytics and notifications
+//! - **DataLoaders** - Batched ticket, message and per-customer ticket loads for federated resolvers
+//! - **Wallboard Snapshots** - Subscription pushing queue aggregates and agents online
+//! - **Collision Warnings** - Subscription showing which agents are viewing or typing on a ticket
+//! - **Agent Activity Exports** - Per-agent performance review packs with customer anonymization
+//! - **Soft Delete** - Restorable ticket deletion with purge after retention
+//! - **Duplicate Burst Collapse** - Repeated submissions folded into one ticket with an occurrence count
+//! - **Idempotent Intake** - Client request keys that make ticket creation safe to retry
+//! - **CSV Exports** - Ticket and dashboard metric spreadsheets, streamed or kept for download
+//! - **Staffing Simulation** - Replays past ticket arrivals against proposed shifts to project waits, SLA breaches and utilization
+//! - **Metrics History** - Day, week or month time series of volume, SLA, CSAT and response percentiles as CSV or JSON
+//! - **Read-Only Mode** - Lookups stay available while writes are refused during failovers
+//! - **Ticket Merges** - Duplicates folded into a primary ticket, previewed first and undoable for a window
+//! - **Offline Mobile Sync** - Change feeds with tombstones and conflict-resolving pushes for the field-agent app
+//! - **Input Validation** - Ticket, message and rating writes checked field by field, with per-product allowed categories
+//! - **Composable Transactions** - Support writes sharing the host service's Postgres transaction
+//! - **Support System Builder** - Repository, clock, events, cached customer directory, field guard and feature flags wired into one GraphQL handle
 //! - **Repository Pattern** - PostgreSQL data access layer
 //!
 //! ## Usage
@@ -16,21 +76,20 @@
 //! ### In a Service
 //!
 //! ```rust,no_run
-//! use pleme_support::{SupportRepository, SupportQueries, SupportMutations};
+//! use pleme_support::{SupportSystem, AgentSupportQueries, AgentSupportMutations, ViewerRole};
 //! use sqlx::PgPool;
-//! use std::sync::Arc;
 //!
 //! # async fn example(db_pool: PgPool) {
-//! // Create repository
-//! let support_repo = Arc::new(SupportRepository::new(db_pool.clone()));
-//!
-//! // Add to GraphQL context
-//! // context.support_repo = support_repo;
+//! // Wire the repository and its services
+//! let support = SupportSystem::builder(db_pool.clone()).build();
 //!
 //! // Use in GraphQL schema
 //! // Schema::build(QueryRoot, MutationRoot, EmptySubscription)
-//! //     .data(support_repo)
+//! //     .data(support)
 //! //     .finish()
+//!
+//! // Per request, say who is asking
+//! // schema.execute(request.data(ViewerRole::Agent(user_id)))
 //! # }
 //! ```
 //!
@@ -44,20 +103,155 @@
 //!     customer_id: Uuid::new_v4(),
 //!     subject: "Login issue".to_string(),
 //!     description: "Cannot log in to account".to_string(),
-//!     priority: Some("HIGH".to_string()),
+//!     priority: Some(TicketPriority::High),
 //!     category: Some("authentication".to_string()),
 //!     ..Default::default()
 //! };
 //! ```
 
+pub mod agent_activity;
+pub mod alerts;
+pub mod api_keys;
+pub mod assignment;
+pub mod attachments;
+pub mod automations;
+pub mod backfill;
+pub mod breach_detector;
+pub mod bulk;
+pub mod burst;
+pub mod business_hours;
+pub mod channels;
+pub mod classification;
+pub mod clock;
+pub mod contract;
+pub mod cost;
+pub mod csat;
+pub mod escalation;
+pub mod export;
+pub mod events;
+pub mod directory;
 pub mod models;
+pub mod numbering;
+pub mod pagination;
+pub mod products;
+pub mod projection;
+pub mod rate_limit;
+pub mod reopen;
+pub mod reports;
+pub mod resolution;
+pub mod sentiment;
+pub mod routing;
 pub mod repository;
+pub mod sla;
+pub mod spam;
+pub mod staffing;
+pub mod sync;
+pub mod system;
+pub mod templates;
+pub mod timeout;
+pub mod translation;
+pub mod wallboard;
+pub mod viewers;
+pub mod warehouse;
+pub mod validation;
+pub mod workload;
 pub mod graphql;
+pub mod guard;
+pub mod idempotency;
+pub mod import;
+pub mod integrations;
+pub mod intake;
+pub mod kb;
+pub mod loaders;
+pub mod locale;
+pub mod maintenance;
+pub mod mentions;
+pub mod merge;
+pub mod metrics_history;
+pub mod moderation;
+pub mod node;
+pub mod notifications;
 
 // Re-export commonly used types
 pub use models::*;
-pub use repository::SupportRepository;
-pub use graphql::{SupportQueries, SupportMutations};
+pub use agent_activity::{ActivityAnonymization, AgentActivityExport, AgentActivitySummary, AuthoredMessage, HandledTicket, ReceivedCsat};
+pub use attachments::{
+    AttachmentStatus, AttachmentText, IndexAttachmentInput, PlainTextExtractor, ScanVerdict, ScannerHook, TextExtractor, TextExtractors,
+    TicketAttachment,
+};
+pub use backfill::BackfillProgress;
+pub use breach_detector::SlaBreachDetector;
+pub use bulk::{BulkTicketResult, BulkUpdateReport};
+pub use burst::{BurstCollapsing, BurstRule, CollapseReason, CollapsedSubmissionCounts};
+pub use business_hours::{BusinessCalendar, BusinessHours};
+pub use classification::{Classification, ClassificationRecord, KeywordClassifier, TicketClassifier, CLASSIFICATION_METADATA_KEY};
+pub use clock::{Clock, MockClock, SystemClock};
+pub use contract::{ContractSeverity, ContractualSlaReport, SlaContract, SlaContracts};
+pub use api_keys::{ApiKeyScope, ApiKeyUsage, CreateApiKeyInput, CreatedApiKey, IntakeApiKey};
+pub use alerts::{AlertMetric, AlertSink, AlertThreshold, AlertThresholds, LogAlertSink, MetricThresholdCrossed, ThresholdDirection};
+pub use mentions::{MessageMention, MAX_MENTIONS_PER_MESSAGE};
+pub use metrics_history::{MetricsHistory, MetricsHistoryPoint, MetricsInterval, MAX_METRICS_HISTORY_POINTS};
+pub use merge::{MergePreview, MergeSettings, TicketMerge, DEFAULT_MERGE_UNDO_WINDOW_HOURS, MAX_MERGE_DUPLICATES};
+pub use loaders::{MessagesByTicketLoader, TicketLoader, TicketsByCustomerLoader};
+pub use node::Node;
+pub use notifications::{LogNotificationSink, NotificationSink, SavedViewMatched};
+pub use pagination::{PageInfo, TicketConnection, TicketCursor, TicketEdge};
+pub use products::{ProductConfig, ProductConfigInput, MAX_PRODUCT_KEY_LENGTH};
+pub use rate_limit::{RateLimit, RateLimits};
+pub use reopen::{ClosedTicketReplies, ClosedTicketReply};
+pub use reports::{
+    CreateReportScheduleInput, ReportFrequency, ReportRun, ReportSchedule, SlaBreach, SlaBreachDigest, SlaBreachSummary, SlaBreachType,
+    SupportReport, UpdateReportScheduleInput,
+};
+pub use resolution::{CrmCoreResolutionCodeMetrics, KbArticleCandidate, ResolutionCode, ResolutionCodeInput};
+pub use routing::{RoutingCandidate, RoutingDecision};
+pub use timeout::{OperationClass, QueryTimeouts};
+pub use templates::{render_template, TemplateVariable};
+pub use translation::{TranslationProvider, TranslationStatus};
+pub use warehouse::{DimAgent, DimCategory, FactMessage, FactTicket, WarehouseBatch, WarehouseExtract, Watermark};
+pub use workload::{AgentWorkload, WorkloadWeights};
+pub use cost::{HandlingCost, HandlingCosts};
+pub use wallboard::QueueSnapshot;
+pub use viewers::{TicketViewer, TYPING_TIMEOUT_SECONDS, VIEWER_TIMEOUT_SECONDS};
+pub use automations::{
+    ActionKind, AutomationAction, AutomationCondition, AutomationExecution, AutomationOutcome, AutomationRule, AutomationTrigger,
+    ConditionField, ConditionOperator, CreateAutomationRuleInput, MessageAuthor,
+};
+pub use escalation::{CreateEscalationRuleInput, EscalationCondition, EscalationRule, TicketEscalated};
+pub use export::{ExportFile, ExportFormat, ExportKind, SupportExport, EXPORT_TTL_HOURS};
+pub use events::{AutomationFired, EventRedelivery, RetryPolicy, SupportEvent, SupportEventPublisher, SupportEventType};
+#[cfg(feature = "webhooks")]
+pub use events::WebhookPublisher;
+pub use csat::{CsatBreakdown, CsatChannel, CsatDimension, CsatFeedbackInput, CsatSurvey, CsatSurveyLink, CsatSurveySettings};
+pub use directory::{CachedCustomerDirectory, CustomerDirectory, CustomerInfo, CustomerLoader};
+pub use assignment::{AssigneeSuggestion, AssignmentReason, AssignmentReasonKind};
+pub use projection::TicketProjection;
+pub use repository::{SupportRepository, SupportTransaction};
+pub use sla::{SlaClock, SlaClockTarget, SlaPause, SlaPauseReason, SlaPolicy, SlaTargetSource};
+pub use graphql::{
+    support_schema, support_sdl, AgentSupportMutations, AgentSupportQueries, CustomerSupportMutations, CustomerSupportQueries, CustomerTicketInput,
+    SupportMutationRoot, SupportQueryRoot, SupportSchema, SupportSubscriptionRoot, SupportSubscriptions,
+};
+pub use guard::{AgentGuard, FieldGuard, SensitiveField, ViewerOrganization, ViewerRole};
+pub use idempotency::MAX_IDEMPOTENCY_KEY_LENGTH;
+pub use import::{CsatImportReport, CsvColumnMapping, ExternalCsatRecord, ImportMode, ImportReport, SurveyScale};
+pub use numbering::{NumberReset, TicketNumber, TicketNumberFormat, TicketNumbering};
+pub use intake::{
+    ClientInfo, ClientPlatform, CoarseGeo, IntakePrivacy, IntakeTelemetry, SubmitterInfo, SubmitterMetadata,
+    CLIENT_METADATA_KEY, MAX_APP_VERSION_LENGTH, MAX_DEVICE_MODEL_LENGTH,
+};
+pub use sync::{
+    SyncChangeInput, SyncChangeResult, SyncChanges, SyncCursor, SyncMessageInput, SyncOutcome, SyncScope, SyncTombstone, TombstoneReason,
+};
+pub use staffing::{ShiftPattern, StaffingProjection, StaffingShift, StaffingSimulation, StaffingWeekdayProjection, MAX_STAFFING_SIMULATION_DAYS};
+pub use spam::{IntakeSignals, SpamClassifier, SpamSignal, SpamVerdict, TelemetrySpamClassifier};
+pub use kb::{ArticleSuggestion, CreateKnowledgeArticleInput, KnowledgeArticle, UpdateKnowledgeArticleInput};
+pub use locale::{ProductLocale, ProductLocales};
+pub use maintenance::{CreateMaintenanceWindowInput, MaintenanceWindow};
+pub use moderation::{ContentModerator, ModerationAction, ModerationResult, WordlistModerator};
+pub use validation::{FieldError, ValidationErrors};
+pub use system::{FeatureFlags, SupportSystem, SupportSystemBuilder};
+pub use sentiment::{LexiconSentimentAnalyzer, SentimentAnalyzer, SentimentRule, SentimentRules};
 
 use thiserror::Error;
 
@@ -65,25 +259,81 @@ use thiserror::Error;
 #[derive(Error, Debug)]
 pub enum SupportError {
     #[error("Database error: {0}")]
-    Database(#[from] sqlx::Error),
+    Database(#[source] sqlx::Error),
+
+    #[error("Query timed out")]
+    Timeout,
 
     #[error("Ticket not found: {0}")]
     TicketNotFound(uuid::Uuid),
 
+    #[error("Ticket not found: {0}")]
+    TicketRefNotFound(String),
+
     #[error("Message not found: {0}")]
     MessageNotFound(uuid::Uuid),
 
+    #[error("Export not found: {0}")]
+    ExportNotFound(uuid::Uuid),
+
+    #[error("Unknown product: {0}")]
+    UnknownProduct(String),
+
     #[error("Invalid input: {0}")]
     InvalidInput(String),
 
     #[error("Validation error: {0}")]
     Validation(String),
 
+    /// Input fields that failed [`validation`]
+    #[error("Validation error: {0}")]
+    InvalidFields(#[from] ValidationErrors),
+
+    #[error("Submission rejected: {0}")]
+    SubmissionRejected(String),
+
+    #[error("Reply rejected: {0}")]
+    ReplyRejected(String),
+
+    #[error("Duplicate request: {0}")]
+    DuplicateRequest(String),
+
+    #[error("Too many tickets; retry after {} seconds", retry_after.num_seconds())]
+    RateLimited { retry_after: chrono::Duration },
+
     #[error("Unauthorized")]
     Unauthorized,
 
+    #[error("Support is in read-only mode; changes are not accepted right now")]
+    ReadOnly,
+
     #[error("Internal error: {0}")]
     Internal(String),
 }
 
+impl From<sqlx::Error> for SupportError {
+    /// Statements cancelled by `statement_timeout` become [`SupportError::Timeout`],
+    /// writes refused by a read-only server (a standby) [`SupportError::ReadOnly`]
+    fn from(e: sqlx::Error) -> Self {
+        match &e {
+            sqlx::Error::Database(db) if db.code().as_deref() == Some("57014") => SupportError::Timeout,
+            sqlx::Error::Database(db) if db.code().as_deref() == Some("25006") => SupportError::ReadOnly,
+            _ => SupportError::Database(e),
+        }
+    }
+}
+
+impl async_graphql::ErrorExtensions for SupportError {
+    /// `InvalidFields` carries the `fields` extension of [`ValidationErrors`]
+    fn extend(&self) -> async_graphql::Error {
+        match self {
+            SupportError::InvalidFields(errors) => errors.extend(),
+            _ => async_graphql::Error::new(self.to_string()),
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, SupportError>;
+
+/// Embedded SQL migrations from `migrations/`
+pub static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!();
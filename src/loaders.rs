@@ -0,0 +1,128 @@
+//! DataLoaders for batching ticket reads per GraphQL request
+//!
+//! Host services that resolve support data from their own types (a federated
+//! `Customer` entity's tickets, an order's linked ticket) would otherwise run
+//! one query per row. Wrap these loaders in a `DataLoader` per request and
+//! load through it instead; every key requested in the same tick is fetched
+//! with one `= ANY(...)` query:
+//!
+//! ```rust,no_run
+//! use async_graphql::dataloader::DataLoader;
+//! use pleme_support::{MessagesByTicketLoader, SupportRepository, TicketLoader, TicketsByCustomerLoader};
+//! use std::sync::Arc;
+//!
+//! # fn example(repo: Arc<SupportRepository>, request: async_graphql::Request) {
+//! let request = request
+//!     .data(DataLoader::new(TicketLoader::new(repo.clone()), tokio::spawn))
+//!     .data(DataLoader::new(MessagesByTicketLoader::new(repo.clone()), tokio::spawn))
+//!     .data(DataLoader::new(TicketsByCustomerLoader::new(repo, "novaskyn"), tokio::spawn));
+//! # }
+//! ```
+//!
+//! With a `DataLoader<MessagesByTicketLoader>` in the request data, this
+//! crate's own `SupportTicket.messages` field batches through it too.
+//!
+//! Loaders do no authorization: like the repository, they return whatever is
+//! asked for, so resolvers must check access before exposing the results.
+
+use async_graphql::dataloader::Loader;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::models::{MessageVisibility, SupportTicket, TicketMessage};
+use crate::repository::SupportRepository;
+use crate::SupportError;
+
+/// Tickets by id; missing and deleted tickets load as `None`
+pub struct TicketLoader {
+    repo: Arc<SupportRepository>,
+}
+
+impl TicketLoader {
+    pub fn new(repo: Arc<SupportRepository>) -> Self {
+        Self { repo }
+    }
+}
+
+impl Loader<Uuid> for TicketLoader {
+    type Value = SupportTicket;
+    type Error = Arc<SupportError>;
+
+    async fn load(&self, keys: &[Uuid]) -> std::result::Result<HashMap<Uuid, SupportTicket>, Self::Error> {
+        let tickets = self.repo.find_by_ids(keys).await.map_err(Arc::new)?;
+        Ok(tickets.into_iter().map(|ticket| (ticket.id, ticket)).collect())
+    }
+}
+
+/// A ticket's messages, oldest first, internal and restricted notes included
+///
+/// Every requested ticket gets an entry, empty when it has no messages.
+/// Use [`MessagesByTicketLoader::public_only`] or [`MessagesByTicketLoader::up_to`]
+/// where notes above a visibility level must never be loaded.
+pub struct MessagesByTicketLoader {
+    repo: Arc<SupportRepository>,
+    visibility: MessageVisibility,
+}
+
+impl MessagesByTicketLoader {
+    pub fn new(repo: Arc<SupportRepository>) -> Self {
+        Self::up_to(repo, MessageVisibility::Restricted)
+    }
+
+    /// Load public messages only
+    pub fn public_only(repo: Arc<SupportRepository>) -> Self {
+        Self::up_to(repo, MessageVisibility::Public)
+    }
+
+    /// Load messages a viewer at `visibility` may read
+    pub fn up_to(repo: Arc<SupportRepository>, visibility: MessageVisibility) -> Self {
+        Self { repo, visibility }
+    }
+}
+
+impl Loader<Uuid> for MessagesByTicketLoader {
+    type Value = Vec<TicketMessage>;
+    type Error = Arc<SupportError>;
+
+    async fn load(&self, keys: &[Uuid]) -> std::result::Result<HashMap<Uuid, Vec<TicketMessage>>, Self::Error> {
+        let messages = self.repo.get_messages_for_tickets(keys).await.map_err(Arc::new)?;
+
+        let mut by_ticket: HashMap<Uuid, Vec<TicketMessage>> = keys.iter().map(|id| (*id, Vec::new())).collect();
+        for message in messages {
+            if message.visibility.visible_to(self.visibility) {
+                by_ticket.entry(message.ticket_id).or_default().push(message);
+            }
+        }
+        Ok(by_ticket)
+    }
+}
+
+/// A customer's tickets in one product, newest first
+///
+/// Every requested customer gets an entry, empty when they have no tickets.
+pub struct TicketsByCustomerLoader {
+    repo: Arc<SupportRepository>,
+    product: String,
+}
+
+impl TicketsByCustomerLoader {
+    pub fn new(repo: Arc<SupportRepository>, product: impl Into<String>) -> Self {
+        Self { repo, product: product.into() }
+    }
+}
+
+impl Loader<Uuid> for TicketsByCustomerLoader {
+    type Value = Vec<SupportTicket>;
+    type Error = Arc<SupportError>;
+
+    async fn load(&self, keys: &[Uuid]) -> std::result::Result<HashMap<Uuid, Vec<SupportTicket>>, Self::Error> {
+        let tickets = self.repo.find_by_customers(&self.product, keys).await.map_err(Arc::new)?;
+
+        let mut by_customer: HashMap<Uuid, Vec<SupportTicket>> = keys.iter().map(|id| (*id, Vec::new())).collect();
+        for ticket in tickets {
+            by_customer.entry(ticket.customer_id).or_default().push(ticket);
+        }
+        Ok(by_customer)
+    }
+}
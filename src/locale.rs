@@ -0,0 +1,64 @@
+//! Per-product locale settings for analytics
+//!
+//! Dashboards for different markets render amounts and days differently: a
+//! Brazilian product reports in BRL on `America/Sao_Paulo` days, a Thai one in
+//! THB on `Asia/Bangkok` days. Analytics responses carry the product's
+//! [`ProductLocale`] so clients format them consistently.
+//!
+//! ```rust
+//! use pleme_support::{ProductLocale, ProductLocales};
+//!
+//! let locales = ProductLocales::default()
+//!     .with_locale("novaskyn", ProductLocale::new("pt-BR", "BRL", "America/Sao_Paulo"))
+//!     .with_locale("thai", ProductLocale::new("th-TH", "THB", "Asia/Bangkok"));
+//!
+//! assert_eq!(locales.locale_for("thai").currency_code, "THB");
+//! assert_eq!(locales.locale_for("lilitu").timezone, "UTC");
+//! ```
+
+use async_graphql::SimpleObject;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Eq, PartialEq, SimpleObject)]
+#[graphql(name = "CrmCoreFormattingMetadata")]
+pub struct ProductLocale {
+    /// BCP 47 language tag, e.g. `pt-BR`
+    pub locale: String,
+    /// ISO 4217 currency code for impact amounts
+    pub currency_code: String,
+    /// IANA timezone whose calendar days trends are bucketed by
+    pub timezone: String,
+}
+
+impl ProductLocale {
+    pub fn new(locale: impl Into<String>, currency_code: impl Into<String>, timezone: impl Into<String>) -> Self {
+        Self {
+            locale: locale.into(),
+            currency_code: currency_code.into(),
+            timezone: timezone.into(),
+        }
+    }
+}
+
+impl Default for ProductLocale {
+    fn default() -> Self {
+        Self::new("en-US", "USD", "UTC")
+    }
+}
+
+/// Per-product locale settings
+#[derive(Debug, Clone, Default)]
+pub struct ProductLocales {
+    locales: HashMap<String, ProductLocale>,
+}
+
+impl ProductLocales {
+    pub fn with_locale(mut self, product: impl Into<String>, locale: ProductLocale) -> Self {
+        self.locales.insert(product.into(), locale);
+        self
+    }
+
+    pub fn locale_for(&self, product: &str) -> ProductLocale {
+        self.locales.get(product).cloned().unwrap_or_default()
+    }
+}
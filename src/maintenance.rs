@@ -0,0 +1,52 @@
+//! Scheduled maintenance windows
+//!
+//! A [`MaintenanceWindow`] covers a product's planned downtime. Time inside a
+//! window never counts against SLA targets: breach checks subtract it and due
+//! dates move out by it, with overlapping windows counted once. First-response
+//! clocks run on the business calendar, so only maintenance inside business
+//! hours extends them (`support_product_business_maintenance_seconds`,
+//! migration 066); resolution clocks are extended by the whole window.
+//!
+//! While a window is active `evaluate_alert_thresholds` publishes nothing, and
+//! tickets created during it get an internal system note with the window's
+//! reason so agents know why the customer wrote in.
+
+use async_graphql::{InputObject, SimpleObject};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, SimpleObject)]
+pub struct MaintenanceWindow {
+    pub id: Uuid,
+    pub product: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub reason: String,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl MaintenanceWindow {
+    /// Whether `at` falls inside the window, which ends exclusively
+    pub fn is_active_at(&self, at: DateTime<Utc>) -> bool {
+        self.starts_at <= at && at < self.ends_at
+    }
+
+    /// Internal note posted on tickets created during the window
+    pub fn ticket_note(&self) -> String {
+        format!(
+            "Created during scheduled maintenance: {} (until {})",
+            self.reason,
+            self.ends_at.format("%Y-%m-%d %H:%M UTC")
+        )
+    }
+}
+
+#[derive(Debug, Clone, InputObject)]
+pub struct CreateMaintenanceWindowInput {
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub reason: String,
+}
@@ -0,0 +1,71 @@
+//! Agent @mentions in ticket messages
+//!
+//! Agents mention each other by writing `@` followed by the agent's user id,
+//! e.g. `@3f0c2a8e-5d1b-4c7e-9a36-0b2d4e6f8a10 can you check the invoice?`.
+//! Clients usually insert the id from an agent picker and render it as the
+//! agent's name. Mentions are parsed when a message is added, in any
+//! [`MessageContentFormat`](crate::MessageContentFormat), and those naming an
+//! agent of the ticket's product are kept in `message_mentions`. Customers
+//! cannot mention agents.
+//!
+//! The mentioned agents are exposed as `TicketMessage.mentions` and carried
+//! on the `message.added` event, so notification systems can alert them;
+//! `agentMentions` lists an agent's mentions, newest first.
+//!
+//! ```rust
+//! use pleme_support::mentions::extract_mentions;
+//! use uuid::Uuid;
+//!
+//! let agent = Uuid::parse_str("3f0c2a8e-5d1b-4c7e-9a36-0b2d4e6f8a10").unwrap();
+//! let content = format!("@{agent} can you check? cc **@{agent}**, billing@{agent}.com");
+//! assert_eq!(extract_mentions(&content), vec![agent]);
+//! assert!(extract_mentions("email me @ home").is_empty());
+//! ```
+
+use async_graphql::SimpleObject;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Most distinct agents one message can mention
+pub const MAX_MENTIONS_PER_MESSAGE: usize = 20;
+
+/// Most mentions returned by one `agent_mentions` call
+pub const MAX_AGENT_MENTIONS: i64 = 100;
+
+/// Length of a hyphenated UUID
+const UUID_LENGTH: usize = 36;
+
+/// An agent mentioned in a message
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, SimpleObject)]
+pub struct MessageMention {
+    pub message_id: Uuid,
+    pub ticket_id: Uuid,
+    /// User id of the mentioned agent
+    pub agent_id: Uuid,
+    pub author_id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Distinct ids mentioned as `@<uuid>` in `content`, in order of first mention
+///
+/// An `@` preceded by a letter or digit is part of an email address, not a
+/// mention. At most [`MAX_MENTIONS_PER_MESSAGE`] ids are returned.
+pub fn extract_mentions(content: &str) -> Vec<Uuid> {
+    let mut mentions = Vec::new();
+    for (at, _) in content.match_indices('@') {
+        let follows_word = content[..at].chars().next_back().is_some_and(|c| c.is_alphanumeric());
+        let candidate = content.get(at + 1..at + 1 + UUID_LENGTH);
+        let Some(id) = candidate.filter(|_| !follows_word).and_then(|c| Uuid::try_parse(c).ok()) else {
+            continue;
+        };
+        if !mentions.contains(&id) {
+            mentions.push(id);
+            if mentions.len() == MAX_MENTIONS_PER_MESSAGE {
+                break;
+            }
+        }
+    }
+    mentions
+}
@@ -0,0 +1,84 @@
+//! Merging duplicate tickets, with a preview and an undo window
+//!
+//! `merge_tickets` folds duplicates into a primary ticket: their messages
+//! move to the primary's thread, and each duplicate is closed with
+//! `merged_into` pointing at the primary. `preview_merge` shows the resulting
+//! thread without changing anything.
+//!
+//! Every merge records what it changed, so `undo_merge` can put the
+//! duplicates back (status, close time and messages) until the merge's
+//! `undo_until`. Replies added to the primary after the merge stay there.
+//!
+//! ```rust
+//! use chrono::Duration;
+//! use pleme_support::{MergeSettings, DEFAULT_MERGE_UNDO_WINDOW_HOURS};
+//!
+//! let settings = MergeSettings::default();
+//! assert_eq!(settings.undo_window, Duration::hours(DEFAULT_MERGE_UNDO_WINDOW_HOURS));
+//!
+//! let settings = MergeSettings { undo_window: Duration::minutes(30) };
+//! assert_eq!(settings.undo_window.num_minutes(), 30);
+//! ```
+
+use async_graphql::SimpleObject;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::models::{SupportTicket, TicketMessage, TicketStatus};
+
+/// How long a merge can be undone unless configured otherwise
+pub const DEFAULT_MERGE_UNDO_WINDOW_HOURS: i64 = 24;
+
+/// Most duplicates one merge may fold into a primary ticket
+pub const MAX_MERGE_DUPLICATES: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MergeSettings {
+    /// How long after a merge `undo_merge` still restores the duplicates
+    pub undo_window: Duration,
+}
+
+impl Default for MergeSettings {
+    fn default() -> Self {
+        Self { undo_window: Duration::hours(DEFAULT_MERGE_UNDO_WINDOW_HOURS) }
+    }
+}
+
+/// A recorded merge of duplicates into a primary ticket
+#[derive(Debug, Clone, FromRow, SimpleObject)]
+pub struct TicketMerge {
+    /// Handle for `undo_merge`
+    pub id: Uuid,
+    pub product: String,
+    pub primary_ticket_id: Uuid,
+    pub duplicate_ticket_ids: Vec<Uuid>,
+    pub merged_by: Option<Uuid>,
+    pub merged_at: DateTime<Utc>,
+    /// The merge can be undone until then
+    pub undo_until: DateTime<Utc>,
+    pub undone_by: Option<Uuid>,
+    pub undone_at: Option<DateTime<Utc>>,
+}
+
+/// What a duplicate looked like before a merge closed it
+#[derive(Debug, Clone, FromRow)]
+pub(crate) struct MergedDuplicate {
+    pub ticket_id: Uuid,
+    pub previous_status: TicketStatus,
+    pub previous_closed_at: Option<DateTime<Utc>>,
+    pub message_ids: Vec<Uuid>,
+}
+
+/// The outcome of a merge, computed without changing anything
+#[derive(Debug, Clone, SimpleObject)]
+pub struct MergePreview {
+    pub primary: SupportTicket,
+    /// Tickets that would be closed into the primary
+    pub duplicates: Vec<SupportTicket>,
+    /// The primary's thread after the merge, oldest first; each message keeps
+    /// the `ticketId` it comes from
+    pub thread: Vec<TicketMessage>,
+    /// Messages that would move from the duplicates
+    pub moved_message_count: i64,
+}
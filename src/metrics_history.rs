@@ -0,0 +1,153 @@
+//! Dashboard KPIs as a time series
+//!
+//! Leadership decks chart how support is trending, not one period's
+//! dashboard. `export_metrics_history` splits a range into day, ISO week or
+//! month buckets, in the product's timezone, and computes the core KPIs for
+//! each:
+//!
+//! - volume: tickets created and resolved in the bucket
+//! - SLA compliance of the tickets created in the bucket, as on the dashboard
+//! - CSAT responses submitted in the bucket and their average score
+//! - first response (minutes) and resolution (hours) percentiles of the
+//!   tickets created in the bucket
+//!
+//! The first and last buckets are cut to the requested range, so their
+//! `bucket_start`/`bucket_end` show when they are partial. Deleted and test
+//! tickets are left out. The result is written as CSV, one row per bucket,
+//! or as JSON:
+//!
+//! ```rust,no_run
+//! use chrono::{Duration, Utc};
+//! use pleme_support::{ExportFormat, MetricsInterval, SupportRepository};
+//!
+//! # async fn example(repo: SupportRepository) -> pleme_support::Result<()> {
+//! let to = Utc::now();
+//! let history = repo.export_metrics_history("novaskyn", None, to - Duration::days(90), to, MetricsInterval::Week).await?;
+//!
+//! history.write_csv(std::fs::File::create("novaskyn-weekly.csv").unwrap(), ExportFormat::Excel)?;
+//! history.write_json(std::io::stdout())?;
+//! # Ok(())
+//! # }
+//! ```
+
+use async_graphql::{Enum, SimpleObject};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::io::Write;
+
+use crate::export::{csv_error, csv_writer, ExportFormat};
+use crate::{Result, SupportError};
+
+/// Most buckets one history may have
+pub const MAX_METRICS_HISTORY_POINTS: i64 = 366;
+
+/// Width of a metrics history bucket
+#[derive(Debug, Clone, Copy, Default, Enum, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MetricsInterval {
+    /// Calendar days
+    Day,
+    /// ISO weeks, starting on Monday
+    #[default]
+    Week,
+    /// Calendar months
+    Month,
+}
+
+impl MetricsInterval {
+    /// Postgres `date_trunc` field of the interval
+    pub(crate) fn as_sql(&self) -> &'static str {
+        match self {
+            MetricsInterval::Day => "day",
+            MetricsInterval::Week => "week",
+            MetricsInterval::Month => "month",
+        }
+    }
+}
+
+/// The core KPIs for one bucket
+#[derive(Debug, Clone, Serialize, FromRow, SimpleObject)]
+pub struct MetricsHistoryPoint {
+    pub bucket_start: DateTime<Utc>,
+    /// Exclusive
+    pub bucket_end: DateTime<Utc>,
+    pub new_tickets: i64,
+    pub resolved_tickets: i64,
+    /// Tickets created in the bucket that met their SLA, in percent; absent without tickets
+    pub sla_compliance_rate: Option<f64>,
+    pub sla_breach_count: i64,
+    pub csat_responses: i64,
+    pub avg_csat_score: Option<f64>,
+    pub first_response_p50_minutes: Option<f64>,
+    pub first_response_p90_minutes: Option<f64>,
+    pub resolution_p50_hours: Option<f64>,
+    pub resolution_p90_hours: Option<f64>,
+}
+
+/// KPI time series for a product
+#[derive(Debug, Clone, Serialize, SimpleObject)]
+pub struct MetricsHistory {
+    pub product: String,
+    pub interval: MetricsInterval,
+    /// The timezone buckets start in
+    pub timezone: String,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    /// Oldest first
+    pub points: Vec<MetricsHistoryPoint>,
+}
+
+/// Header of metrics history exports
+pub const METRICS_HISTORY_COLUMNS: &[&str] = &[
+    "bucket_start",
+    "bucket_end",
+    "new_tickets",
+    "resolved_tickets",
+    "sla_compliance_rate",
+    "sla_breach_count",
+    "csat_responses",
+    "avg_csat_score",
+    "first_response_p50_minutes",
+    "first_response_p90_minutes",
+    "resolution_p50_hours",
+    "resolution_p90_hours",
+];
+
+impl MetricsHistory {
+    /// Write one CSV row per bucket, returning the number of data rows written
+    pub fn write_csv<W: Write>(&self, writer: W, format: ExportFormat) -> Result<i64> {
+        let mut csv = csv_writer(writer, format)?;
+        csv.write_record(METRICS_HISTORY_COLUMNS).map_err(csv_error)?;
+        for point in &self.points {
+            csv.write_record(point_record(point)).map_err(csv_error)?;
+        }
+        csv.flush().map_err(|e| csv_error(e.into()))?;
+
+        Ok(self.points.len() as i64)
+    }
+
+    /// Write the history as a JSON document
+    pub fn write_json<W: Write>(&self, writer: W) -> Result<()> {
+        serde_json::to_writer_pretty(writer, self)
+            .map_err(|e| SupportError::Internal(format!("Failed to write JSON: {}", e)))
+    }
+}
+
+fn point_record(point: &MetricsHistoryPoint) -> Vec<String> {
+    let optional = |value: Option<f64>| value.map(|v| v.to_string()).unwrap_or_default();
+    vec![
+        point.bucket_start.to_rfc3339(),
+        point.bucket_end.to_rfc3339(),
+        point.new_tickets.to_string(),
+        point.resolved_tickets.to_string(),
+        optional(point.sla_compliance_rate),
+        point.sla_breach_count.to_string(),
+        point.csat_responses.to_string(),
+        optional(point.avg_csat_score),
+        optional(point.first_response_p50_minutes),
+        optional(point.first_response_p90_minutes),
+        optional(point.resolution_p50_hours),
+        optional(point.resolution_p90_hours),
+    ]
+}
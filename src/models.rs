@@ -4,58 +4,719 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 
+use crate::guard::{SensitiveField, SensitiveFieldGuard};
+use crate::intake::{ClientInfo, ClientPlatform};
+use crate::locale::ProductLocale;
+use crate::moderation::ModerationAction;
+use crate::translation::TranslationStatus;
+
+/// Author of notes the system posts on tickets, e.g. engineering issue updates
+pub const SYSTEM_AUTHOR_ID: Uuid = Uuid::nil();
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, SimpleObject)]
+#[graphql(complex)]
 pub struct SupportTicket {
+    #[graphql(skip)]
     pub id: Uuid,
     pub product: String,
     pub customer_id: Uuid,
+    /// B2B account the customer filed the ticket for
+    pub organization_id: Option<Uuid>,
     pub subject: String,
     pub description: String,
     pub status: TicketStatus,
     pub priority: TicketPriority,
     pub category: Option<String>,
+    #[graphql(guard = "SensitiveFieldGuard(SensitiveField::AssignedTo)")]
     pub assigned_to: Option<Uuid>,
     pub first_response_at: Option<DateTime<Utc>>,
     pub resolved_at: Option<DateTime<Utc>>,
     pub closed_at: Option<DateTime<Utc>>,
     pub sla_breach: bool,
+    /// When `sla_breach` was flagged by breach evaluation
+    pub sla_breached_at: Option<DateTime<Utc>>,
+    #[graphql(guard = "SensitiveFieldGuard(SensitiveField::CsatScore)")]
     pub csat_score: Option<i32>,
-    #[graphql(skip)]
+    #[graphql(guard = "SensitiveFieldGuard(SensitiveField::Metadata)")]
     pub metadata: sqlx::types::JsonValue,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub deleted_at: Option<DateTime<Utc>>,
+    pub parent_ticket_id: Option<Uuid>,
+    /// Start of the current WAITING_ON_CUSTOMER period, if any
+    pub waiting_since: Option<DateTime<Utc>>,
+    /// Cumulative seconds spent in completed WAITING_ON_CUSTOMER periods
+    pub customer_wait_seconds: i64,
+    /// Customer plan at intake, used to pick the SLA grace window
+    pub customer_plan: Option<String>,
+    /// Per-product sequence number
+    pub ticket_number: i64,
+    /// Human-readable reference, e.g. NOVASKYN-000042
+    pub ticket_ref: String,
+    /// Intake channel the ticket arrived through
+    pub channel: TicketChannel,
+    /// Agent credited with the latest resolution, and with its CSAT
+    #[graphql(guard = "SensitiveFieldGuard(SensitiveField::AssignedTo)")]
+    pub resolving_agent_id: Option<Uuid>,
+    /// Final public agent reply at the latest resolution
+    pub resolving_message_id: Option<Uuid>,
+    /// Closed ticket whose customer reply opened this one
+    pub follow_up_of: Option<Uuid>,
+    /// When the customer last submitted `csat_score`
+    pub csat_submitted_at: Option<DateTime<Utc>>,
+    /// When the ticket was linked to `parent_ticket_id`
+    pub linked_at: Option<DateTime<Utc>>,
+    /// Language of the conversation (BCP 47); `None` means the product's locale
+    pub locale: Option<String>,
+    /// Deadline for the first agent response under the ticket's SLA target
+    pub first_response_due_at: Option<DateTime<Utc>>,
+    /// Resolution deadline, pushed back by time waiting on the customer
+    pub sla_due_at: Option<DateTime<Utc>>,
+    /// Highest escalation rule level applied; 0 when never escalated
+    pub escalation_level: i32,
+    /// When the ticket last escalated
+    pub escalated_at: Option<DateTime<Utc>>,
+    /// Lowercase labels set by automations
+    pub tags: Vec<String>,
+    /// Sandbox ticket, excluded from SLA tracking, analytics and notifications
+    pub test_mode: bool,
+    /// Error code or crash fingerprint reported with the submission
+    pub error_signature: Option<String>,
+    /// Submissions this ticket stands for, including duplicates collapsed into it
+    pub occurrence_count: i32,
+    /// When the latest duplicate was collapsed into the ticket
+    pub last_occurred_at: Option<DateTime<Utc>>,
+    /// Primary ticket this duplicate was merged into
+    pub merged_into: Option<Uuid>,
+    /// How the ticket was resolved, from the product's resolution codes
+    pub resolution_code: Option<String>,
+    /// Times the ticket was reopened after being resolved or closed
+    pub reopen_count: i32,
 }
 
 #[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "ticket_status", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum TicketStatus {
+    #[serde(alias = "NEW")]
     New,
+    #[serde(alias = "IN_PROGRESS")]
     InProgress,
+    #[serde(alias = "WAITING_ON_CUSTOMER")]
     WaitingOnCustomer,
+    #[serde(alias = "RESOLVED")]
     Resolved,
+    #[serde(alias = "CLOSED")]
     Closed,
 }
 
-#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, Copy, Default, Enum, Eq, PartialEq, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "ticket_priority", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum TicketPriority {
+    #[serde(alias = "LOW")]
     Low,
+    #[default]
+    #[serde(alias = "MEDIUM")]
     Medium,
+    #[serde(alias = "HIGH")]
     High,
+    #[serde(alias = "URGENT")]
     Urgent,
 }
 
+impl TicketStatus {
+    /// Database representation of the status
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TicketStatus::New => "NEW",
+            TicketStatus::InProgress => "IN_PROGRESS",
+            TicketStatus::WaitingOnCustomer => "WAITING_ON_CUSTOMER",
+            TicketStatus::Resolved => "RESOLVED",
+            TicketStatus::Closed => "CLOSED",
+        }
+    }
+}
+
+impl std::str::FromStr for TicketStatus {
+    type Err = String;
+
+    /// Parses the database representation, case-insensitively
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "NEW" => Ok(TicketStatus::New),
+            "IN_PROGRESS" => Ok(TicketStatus::InProgress),
+            "WAITING_ON_CUSTOMER" => Ok(TicketStatus::WaitingOnCustomer),
+            "RESOLVED" => Ok(TicketStatus::Resolved),
+            "CLOSED" => Ok(TicketStatus::Closed),
+            other => Err(format!("unknown status '{}'", other)),
+        }
+    }
+}
+
+impl TicketPriority {
+    /// Database representation of the priority
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TicketPriority::Low => "LOW",
+            TicketPriority::Medium => "MEDIUM",
+            TicketPriority::High => "HIGH",
+            TicketPriority::Urgent => "URGENT",
+        }
+    }
+}
+
+impl std::str::FromStr for TicketPriority {
+    type Err = String;
+
+    /// Parses the database representation, case-insensitively
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "LOW" => Ok(TicketPriority::Low),
+            "MEDIUM" => Ok(TicketPriority::Medium),
+            "HIGH" => Ok(TicketPriority::High),
+            "URGENT" => Ok(TicketPriority::Urgent),
+            other => Err(format!("unknown priority '{}'", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Enum, Eq, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "ticket_channel", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TicketChannel {
+    #[serde(alias = "EMAIL")]
+    Email,
+    #[serde(alias = "CHAT")]
+    Chat,
+    #[default]
+    #[serde(alias = "WEB")]
+    Web,
+    #[serde(alias = "API")]
+    Api,
+}
+
+impl TicketChannel {
+    /// Database representation of the channel
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TicketChannel::Email => "EMAIL",
+            TicketChannel::Chat => "CHAT",
+            TicketChannel::Web => "WEB",
+            TicketChannel::Api => "API",
+        }
+    }
+}
+
+impl std::str::FromStr for TicketChannel {
+    type Err = String;
+
+    /// Parses the database representation, case-insensitively
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "EMAIL" => Ok(TicketChannel::Email),
+            "CHAT" => Ok(TicketChannel::Chat),
+            "WEB" => Ok(TicketChannel::Web),
+            "API" => Ok(TicketChannel::Api),
+            other => Err(format!("unknown channel '{}'", other)),
+        }
+    }
+}
+
+/// How a message's content is written, for clients to render it
+///
+/// HTML is stored as submitted; clients must sanitize it before display.
+#[derive(Debug, Clone, Copy, Default, Enum, Eq, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MessageContentFormat {
+    #[default]
+    Plaintext,
+    Markdown,
+    Html,
+}
+
+/// Who can read a message, from least to most restricted
+///
+/// Each level includes the ones before it: a viewer at `INTERNAL` reads
+/// public and internal messages. `is_internal` is set on every message that
+/// is not `PUBLIC`.
+#[derive(Debug, Clone, Copy, Default, Enum, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MessageVisibility {
+    /// The customer and agents
+    #[default]
+    Public,
+    /// Agents only
+    Internal,
+    /// Agents allowed restricted notes, e.g. for legal or security matters
+    Restricted,
+}
+
+impl MessageVisibility {
+    pub fn is_internal(self) -> bool {
+        self != MessageVisibility::Public
+    }
+
+    /// Whether a viewer at `viewer` may read messages at this level
+    pub fn visible_to(self, viewer: MessageVisibility) -> bool {
+        self <= viewer
+    }
+
+    /// The levels a viewer at `viewer` may read
+    pub(crate) fn up_to(viewer: MessageVisibility) -> Vec<MessageVisibility> {
+        [MessageVisibility::Public, MessageVisibility::Internal, MessageVisibility::Restricted]
+            .into_iter()
+            .filter(|level| level.visible_to(viewer))
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, SimpleObject)]
+#[graphql(complex)]
 pub struct TicketMessage {
+    #[graphql(skip)]
     pub id: Uuid,
     pub ticket_id: Uuid,
     pub author_id: Uuid,
     pub is_internal: bool,
+    #[serde(default)]
+    pub visibility: MessageVisibility,
     pub content: String,
+    /// Set by content moderation for agent review
+    pub flagged: bool,
+    pub created_at: DateTime<Utc>,
+    /// Canned response the message was sent from
+    pub canned_response_id: Option<Uuid>,
+    /// Set for messages sent from a canned response
+    pub translation_status: Option<TranslationStatus>,
+    /// Locale of the canned response when the message was translated from it
+    pub translated_from: Option<String>,
+    #[serde(default)]
+    pub content_format: MessageContentFormat,
+    /// Customer sentiment from -1 to 1, on the customer's public messages; agents only
+    #[serde(default)]
+    #[graphql(guard = "SensitiveFieldGuard(SensitiveField::Metadata)")]
+    pub sentiment_score: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "message_reaction", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MessageReaction {
+    /// 👍 Seen and acknowledged
+    Ack,
+    /// 👀 Looking into it
+    Looking,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, SimpleObject)]
+pub struct TicketMessageReaction {
+    pub message_id: Uuid,
+    pub agent_id: Uuid,
+    pub reaction: MessageReaction,
     pub created_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, SimpleObject)]
+pub struct TicketView {
+    pub id: Uuid,
+    pub ticket_id: Uuid,
+    pub customer_id: Uuid,
+    /// Set when a specific reply was viewed
+    pub message_id: Option<Uuid>,
+    pub viewed_at: DateTime<Utc>,
+}
+
+/// One download of a ticket attachment, for security audits
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, SimpleObject)]
+pub struct AttachmentAccess {
+    pub id: Uuid,
+    pub ticket_id: Uuid,
+    /// The storing service's attachment identifier
+    pub attachment_id: Uuid,
+    pub file_name: Option<String>,
+    pub accessed_by: Uuid,
+    pub accessed_at: DateTime<Utc>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    /// ISO 3166-1 alpha-2 country code
+    pub country: Option<String>,
+    pub region: Option<String>,
+}
+
+/// Named ticket filter shown in an agent's sidebar
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, SimpleObject)]
+#[graphql(complex)]
+pub struct SavedView {
+    #[graphql(skip)]
+    pub id: Uuid,
+    pub product: String,
+    pub owner_id: Uuid,
+    pub name: String,
+    pub status: Option<TicketStatus>,
+    pub priority: Option<TicketPriority>,
+    pub assigned_to: Option<Uuid>,
+    pub customer_id: Option<Uuid>,
+    pub organization_id: Option<Uuid>,
+    pub category: Option<String>,
+    pub search_query: Option<String>,
+    pub app_version: Option<String>,
+    pub platform: Option<ClientPlatform>,
+    /// Notify the owner of tickets that newly match
+    pub notify_on_match: bool,
+    /// When notification matching last ran for this view
+    pub last_evaluated_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// How the view lists its tickets
+    pub sort_order: TicketSortOrder,
+}
+
+impl SavedView {
+    /// The view's criteria, for listing its tickets
+    pub fn filter(&self) -> TicketFilter {
+        TicketFilter {
+            status: self.status,
+            priority: self.priority,
+            assigned_to: self.assigned_to,
+            customer_id: self.customer_id,
+            organization_id: self.organization_id,
+            category: self.category.clone(),
+            search_query: self.search_query.clone(),
+            app_version: self.app_version.clone(),
+            platform: self.platform,
+            include_deleted: false,
+        }
+    }
+}
+
+/// How a ticket list is ordered
+#[derive(Debug, Clone, Copy, Default, Enum, Eq, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TicketSortOrder {
+    #[default]
+    NewestFirst,
+    OldestFirst,
+    /// URGENT first, oldest first within a priority
+    PriorityFirst,
+    /// Most recently updated first
+    RecentlyUpdated,
+}
+
+impl TicketSortOrder {
+    /// `ORDER BY` clause over `support_tickets`
+    pub(crate) fn order_by(&self) -> &'static str {
+        match self {
+            TicketSortOrder::NewestFirst => "created_at DESC, id DESC",
+            TicketSortOrder::OldestFirst => "created_at ASC, id ASC",
+            TicketSortOrder::PriorityFirst => "priority DESC, created_at ASC, id ASC",
+            TicketSortOrder::RecentlyUpdated => "updated_at DESC, id DESC",
+        }
+    }
+}
+
+/// Ticket matching a full-text search, with its relevance
+#[derive(Debug, Clone, FromRow, SimpleObject)]
+pub struct TicketSearchResult {
+    #[sqlx(flatten)]
+    pub ticket: SupportTicket,
+    /// Higher is more relevant; only comparable within one search
+    pub rank: f64,
+}
+
+/// Sidebar badge numbers for a saved view
+#[derive(Debug, Clone, FromRow, SimpleObject)]
+pub struct SavedViewCount {
+    pub view_id: Uuid,
+    pub ticket_count: i64,
+    /// Tickets with customer activity since the agent last read them
+    pub unread_count: i64,
+}
+
+/// When an agent last read a ticket
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, SimpleObject)]
+pub struct TicketRead {
+    pub ticket_id: Uuid,
+    pub agent_id: Uuid,
+    pub last_read_at: DateTime<Utc>,
+}
+
+/// What the customer has seen of a ticket, for "customer viewed your reply 2h ago"
+#[derive(Debug, Clone, FromRow, SimpleObject)]
+pub struct TicketLastSeen {
+    pub last_viewed_at: Option<DateTime<Utc>>,
+    pub last_agent_reply_at: Option<DateTime<Utc>>,
+    /// First customer view at or after the latest agent reply
+    pub last_agent_reply_viewed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, SimpleObject)]
+pub struct TicketAuditEntry {
+    pub id: Uuid,
+    pub ticket_id: Uuid,
+    pub actor_id: Option<Uuid>,
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub source_ticket_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, SimpleObject)]
+#[graphql(complex)]
+pub struct SupportAgent {
+    #[graphql(skip)]
+    pub id: Uuid,
+    pub product: String,
+    pub user_id: Uuid,
+    pub display_name: String,
+    pub skills: Vec<String>,
+    /// Offset from UTC of the agent's working timezone
+    pub utc_offset_minutes: i32,
+    pub active: bool,
+    pub team: Option<String>,
+    pub role: AgentRole,
+    /// Last presence heartbeat from the agent's console
+    pub last_seen_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SupportAgent {
+    /// Whether the agent may create, edit or delete a canned response with this scope, owner and team
+    ///
+    /// Personal responses belong to their owner, team responses to the team's
+    /// leads, and product-wide responses to admins. Admins manage everything.
+    pub fn can_manage_canned_response(&self, scope: CannedResponseScope, owner_id: Uuid, team: Option<&str>) -> bool {
+        if !self.active {
+            return false;
+        }
+        if self.role == AgentRole::Admin {
+            return true;
+        }
+        match scope {
+            CannedResponseScope::Personal => owner_id == self.user_id,
+            CannedResponseScope::Team => self.role == AgentRole::Lead && team.is_some() && self.team.as_deref() == team,
+            CannedResponseScope::Product => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Enum, Eq, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "agent_role", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AgentRole {
+    #[default]
+    Agent,
+    /// Manages their team's shared canned responses
+    Lead,
+    /// Manages everything in the product
+    Admin,
+}
+
+/// Who sees a canned response
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "canned_response_scope", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CannedResponseScope {
+    /// Only its owner
+    Personal,
+    /// Agents on the owner's team
+    Team,
+    /// Every agent on the product
+    Product,
+}
+
+/// SLA targets for one product and priority
+///
+/// Unset targets fall back to the repository's `SlaPolicy`.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, SimpleObject)]
+pub struct SlaTarget {
+    pub product: String,
+    pub priority: TicketPriority,
+    pub first_response_minutes: Option<i32>,
+    pub resolution_minutes: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// How an assignment rule picks among eligible agents
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "assignment_strategy", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AssignmentStrategy {
+    /// Each eligible agent in turn
+    RoundRobin,
+    /// The agent with the lowest priority-weighted workload
+    LeastLoaded,
+    /// The least-loaded agent with a skill matching the ticket's category
+    SkillBased,
+}
+
+/// Rule for routing new tickets to agents automatically
+///
+/// Rules are tried in `position` order; unset conditions match any ticket.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, SimpleObject)]
+pub struct AssignmentRule {
+    pub id: Uuid,
+    pub product: String,
+    pub name: String,
+    pub position: i32,
+    pub strategy: AssignmentStrategy,
+    pub category: Option<String>,
+    pub priority: Option<TicketPriority>,
+    pub channel: Option<TicketChannel>,
+    /// Only agents on this team are eligible
+    pub team: Option<String>,
+    /// Agents with this many open tickets are skipped
+    pub max_open_tickets: Option<i32>,
+    pub active: bool,
+    /// Round-robin cursor
+    pub last_assigned_to: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Reusable reply template (macro)
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, SimpleObject)]
+#[graphql(complex)]
+pub struct CannedResponse {
+    #[graphql(skip)]
+    pub id: Uuid,
+    pub product: String,
+    pub scope: CannedResponseScope,
+    pub owner_id: Uuid,
+    /// Set for team responses
+    pub team: Option<String>,
+    pub title: String,
+    pub body: String,
+    /// Language the body is written in (BCP 47); `None` means the product's locale
+    pub locale: Option<String>,
+    pub usage_count: i64,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// SMTP headers of an agent reply sent by email
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, SimpleObject)]
+pub struct OutboundEmail {
+    pub message_id: Uuid,
+    pub ticket_id: Uuid,
+    /// Without angle brackets
+    pub smtp_message_id: String,
+    /// References header, oldest first, without angle brackets
+    pub thread_refs: Vec<String>,
+    pub sent_at: DateTime<Utc>,
+}
+
+/// Abusive inbound message recorded by content moderation
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, SimpleObject)]
+pub struct AbuseIncident {
+    pub id: Uuid,
+    pub product: String,
+    pub customer_id: Uuid,
+    pub ticket_id: Uuid,
+    pub message_id: Uuid,
+    pub action: ModerationAction,
+    pub matched_terms: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Customer with repeated abuse incidents, a candidate for blocklisting
+#[derive(Debug, Clone, FromRow, SimpleObject)]
+#[graphql(complex)]
+pub struct AbuseBlocklistCandidate {
+    pub customer_id: Uuid,
+    pub incident_count: i64,
+    pub escalated_count: i64,
+    pub last_incident_at: DateTime<Utc>,
+}
+
+/// Sidebar summary of a customer's relationship with support
+#[derive(Debug, Clone, SimpleObject)]
+#[graphql(complex)]
+pub struct CustomerContext {
+    pub customer_id: Uuid,
+    pub product: String,
+    /// Up to 5 most recent tickets, newest first
+    pub recent_tickets: Vec<CustomerContextTicket>,
+    pub total_tickets: i64,
+    pub open_tickets: i64,
+    pub has_open_ticket: bool,
+    pub avg_csat_score: Option<f64>,
+    /// Unresolved parent incidents the customer's tickets are linked to
+    pub known_issues: Vec<KnownIssueSubscription>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct CustomerContextTicket {
+    pub id: Uuid,
+    pub ticket_ref: String,
+    pub subject: String,
+    pub status: TicketStatus,
+    pub priority: TicketPriority,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    #[graphql(guard = "SensitiveFieldGuard(SensitiveField::CsatScore)")]
+    pub csat_score: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct KnownIssueSubscription {
+    pub parent_ticket_id: Uuid,
+    pub ticket_ref: String,
+    pub subject: String,
+    pub status: TicketStatus,
+    pub created_at: DateTime<Utc>,
+}
+
+/// What happened in a customer journey event
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum JourneyEventKind {
+    TicketOpened,
+    TicketResolved,
+    TicketClosed,
+    CsatSubmitted,
+    /// The ticket was linked to a parent incident (known issue)
+    KnownIssueLinked,
+}
+
+/// One entry of a customer's cross-product timeline
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, SimpleObject)]
+pub struct CustomerJourneyEvent {
+    pub kind: JourneyEventKind,
+    pub occurred_at: DateTime<Utc>,
+    pub product: String,
+    pub ticket_id: Uuid,
+    pub ticket_ref: String,
+    pub subject: String,
+    /// Set for CSAT_SUBMITTED
+    #[graphql(guard = "SensitiveFieldGuard(SensitiveField::CsatScore)")]
+    pub csat_score: Option<i32>,
+    /// Parent incident, set for KNOWN_ISSUE_LINKED
+    pub known_issue_id: Option<Uuid>,
+    pub known_issue_ref: Option<String>,
+    pub known_issue_subject: Option<String>,
+}
+
+/// A customer's support history at a glance, across all products
+#[derive(Debug, Clone, Serialize, SimpleObject)]
+#[graphql(complex)]
+pub struct CustomerSupportSummary {
+    pub customer_id: Uuid,
+    pub total_tickets: i64,
+    pub open_tickets: i64,
+    pub avg_csat_score: Option<f64>,
+    /// When the customer last opened a ticket or wrote a public reply
+    pub last_contact_at: Option<DateTime<Utc>>,
+    /// Up to 5 categories of the customer's tickets, most common first
+    pub common_categories: Vec<CustomerCategoryCount>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct CustomerCategoryCount {
+    pub category: String,
+    pub ticket_count: i64,
+}
+
 // Dashboard metrics structures (prefixed with CrmCore to avoid federation conflicts)
 #[derive(Debug, Clone, SimpleObject)]
 #[graphql(name = "CrmCoreSupportDashboardMetrics")]
@@ -67,13 +728,21 @@ pub struct CrmCoreSupportDashboardMetrics {
     pub response_metrics: CrmCoreResponseMetrics,
     pub top_agents: Vec<CrmCoreAgentPerformance>,
     pub ticket_trends: Vec<CrmCoreTicketTrend>,
+    /// CSAT of the surveys sent in the period, by day and by week
+    pub csat_trends: CrmCoreCsatTrends,
+    pub status_funnel: CrmCoreStatusFunnel,
+    pub channel_metrics: Vec<CrmCoreChannelMetrics>,
+    /// How the product's amounts and days should be formatted
+    pub formatting: ProductLocale,
 }
 
 #[derive(Debug, Clone, FromRow, SimpleObject)]
 #[graphql(name = "CrmCoreSupportOverviewMetrics")]
 pub struct CrmCoreSupportOverviewMetrics {
     pub total_active_tickets: i64,
+    /// Created on the period's last calendar day
     pub new_tickets_today: i64,
+    /// Resolved on the period's last calendar day
     pub resolved_tickets_today: i64,
     pub avg_first_response_time_minutes: Option<f64>,
     pub avg_resolution_time_hours: Option<f64>,
@@ -81,6 +750,8 @@ pub struct CrmCoreSupportOverviewMetrics {
     pub sla_compliance_rate: Option<f64>,
     pub sla_breach_count: i64,
     pub avg_csat_score: Option<f64>,
+    /// Resolved or closed tickets that were reopened, in percent
+    pub reopen_rate: Option<f64>,
 }
 
 #[derive(Debug, Clone, FromRow, SimpleObject)]
@@ -100,6 +771,29 @@ pub struct CrmCoreTicketPriorityCount {
 #[derive(Debug, Clone, FromRow, SimpleObject)]
 #[graphql(name = "CrmCoreSlaMetrics")]
 pub struct CrmCoreSlaMetrics {
+    pub total_tickets: i64,
+    pub tickets_meeting_sla: i64,
+    pub tickets_breaching_sla: i64,
+    pub compliance_rate: f64,
+    pub avg_first_response_minutes: Option<f64>,
+    /// Average time to first response counted within the product's business
+    /// calendar, as the first-response SLA clock runs
+    pub time_to_first_response_business_minutes: Option<f64>,
+    pub avg_resolution_hours: Option<f64>,
+    /// Compliance per priority, URGENT first
+    #[sqlx(skip)]
+    pub by_priority: Vec<CrmCoreSlaBreakdown>,
+    /// Compliance per category, busiest first
+    #[sqlx(skip)]
+    pub by_category: Vec<CrmCoreSlaBreakdown>,
+}
+
+/// SLA compliance of the tickets of one priority or category
+#[derive(Debug, Clone, FromRow, SimpleObject)]
+#[graphql(name = "CrmCoreSlaBreakdown")]
+pub struct CrmCoreSlaBreakdown {
+    /// The priority or category; `None` for tickets without a category
+    pub key: Option<String>,
     pub total_tickets: i64,
     pub tickets_meeting_sla: i64,
     pub tickets_breaching_sla: i64,
@@ -117,6 +811,171 @@ pub struct CrmCoreResponseMetrics {
     pub median_response_minutes: Option<f64>,
     pub avg_resolution_hours: Option<f64>,
     pub median_resolution_hours: Option<f64>,
+    pub avg_customer_wait_hours: Option<f64>,
+    pub median_customer_wait_hours: Option<f64>,
+    /// Time from an agent reply to the customer's next portal view
+    pub avg_reply_to_view_minutes: Option<f64>,
+    pub median_reply_to_view_minutes: Option<f64>,
+}
+
+/// A reporting period, from `start` (inclusive) to `end` (exclusive)
+#[derive(Debug, Clone, Copy, InputObject)]
+#[graphql(name = "CrmCoreMetricsPeriod")]
+pub struct MetricsPeriod {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+/// One metric in two periods, with the change from `period_a` to `period_b`
+#[derive(Debug, Clone, Copy, PartialEq, SimpleObject)]
+#[graphql(name = "CrmCoreMetricDelta")]
+pub struct CrmCoreMetricDelta {
+    pub period_a: Option<f64>,
+    pub period_b: Option<f64>,
+    /// `period_b - period_a`; absent when either period has no value
+    pub absolute_change: Option<f64>,
+    /// Change relative to `period_a`, in percent; absent when `period_a` is zero or has no value
+    pub percent_change: Option<f64>,
+}
+
+impl CrmCoreMetricDelta {
+    /// ```rust
+    /// use pleme_support::CrmCoreMetricDelta;
+    ///
+    /// let delta = CrmCoreMetricDelta::between(Some(40.0), Some(50.0));
+    /// assert_eq!(delta.absolute_change, Some(10.0));
+    /// assert_eq!(delta.percent_change, Some(25.0));
+    ///
+    /// assert_eq!(CrmCoreMetricDelta::between(Some(0.0), Some(3.0)).percent_change, None);
+    /// assert_eq!(CrmCoreMetricDelta::between(None, Some(3.0)).absolute_change, None);
+    /// ```
+    pub fn between(period_a: Option<f64>, period_b: Option<f64>) -> Self {
+        let absolute_change = period_a.zip(period_b).map(|(a, b)| b - a);
+        let percent_change = match (period_a, absolute_change) {
+            (Some(a), Some(change)) if a != 0.0 => Some(change / a.abs() * 100.0),
+            _ => None,
+        };
+        Self { period_a, period_b, absolute_change, percent_change }
+    }
+
+    fn counts(period_a: i64, period_b: i64) -> Self {
+        Self::between(Some(period_a as f64), Some(period_b as f64))
+    }
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+#[graphql(name = "CrmCoreOverviewComparison")]
+pub struct CrmCoreOverviewComparison {
+    pub total_active_tickets: CrmCoreMetricDelta,
+    pub new_tickets_today: CrmCoreMetricDelta,
+    pub resolved_tickets_today: CrmCoreMetricDelta,
+    pub avg_first_response_time_minutes: CrmCoreMetricDelta,
+    pub avg_resolution_time_hours: CrmCoreMetricDelta,
+    pub first_contact_resolution_rate: CrmCoreMetricDelta,
+    pub sla_compliance_rate: CrmCoreMetricDelta,
+    pub sla_breach_count: CrmCoreMetricDelta,
+    pub avg_csat_score: CrmCoreMetricDelta,
+    pub reopen_rate: CrmCoreMetricDelta,
+}
+
+impl CrmCoreOverviewComparison {
+    pub fn new(a: &CrmCoreSupportOverviewMetrics, b: &CrmCoreSupportOverviewMetrics) -> Self {
+        Self {
+            total_active_tickets: CrmCoreMetricDelta::counts(a.total_active_tickets, b.total_active_tickets),
+            new_tickets_today: CrmCoreMetricDelta::counts(a.new_tickets_today, b.new_tickets_today),
+            resolved_tickets_today: CrmCoreMetricDelta::counts(a.resolved_tickets_today, b.resolved_tickets_today),
+            avg_first_response_time_minutes: CrmCoreMetricDelta::between(
+                a.avg_first_response_time_minutes,
+                b.avg_first_response_time_minutes,
+            ),
+            avg_resolution_time_hours: CrmCoreMetricDelta::between(a.avg_resolution_time_hours, b.avg_resolution_time_hours),
+            first_contact_resolution_rate: CrmCoreMetricDelta::between(
+                a.first_contact_resolution_rate,
+                b.first_contact_resolution_rate,
+            ),
+            sla_compliance_rate: CrmCoreMetricDelta::between(a.sla_compliance_rate, b.sla_compliance_rate),
+            sla_breach_count: CrmCoreMetricDelta::counts(a.sla_breach_count, b.sla_breach_count),
+            avg_csat_score: CrmCoreMetricDelta::between(a.avg_csat_score, b.avg_csat_score),
+            reopen_rate: CrmCoreMetricDelta::between(a.reopen_rate, b.reopen_rate),
+        }
+    }
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+#[graphql(name = "CrmCoreSlaComparison")]
+pub struct CrmCoreSlaComparison {
+    pub total_tickets: CrmCoreMetricDelta,
+    pub tickets_meeting_sla: CrmCoreMetricDelta,
+    pub tickets_breaching_sla: CrmCoreMetricDelta,
+    pub compliance_rate: CrmCoreMetricDelta,
+    pub avg_first_response_minutes: CrmCoreMetricDelta,
+    pub avg_resolution_hours: CrmCoreMetricDelta,
+}
+
+impl CrmCoreSlaComparison {
+    pub fn new(a: &CrmCoreSlaMetrics, b: &CrmCoreSlaMetrics) -> Self {
+        Self {
+            total_tickets: CrmCoreMetricDelta::counts(a.total_tickets, b.total_tickets),
+            tickets_meeting_sla: CrmCoreMetricDelta::counts(a.tickets_meeting_sla, b.tickets_meeting_sla),
+            tickets_breaching_sla: CrmCoreMetricDelta::counts(a.tickets_breaching_sla, b.tickets_breaching_sla),
+            compliance_rate: CrmCoreMetricDelta::between(Some(a.compliance_rate), Some(b.compliance_rate)),
+            avg_first_response_minutes: CrmCoreMetricDelta::between(a.avg_first_response_minutes, b.avg_first_response_minutes),
+            avg_resolution_hours: CrmCoreMetricDelta::between(a.avg_resolution_hours, b.avg_resolution_hours),
+        }
+    }
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+#[graphql(name = "CrmCoreResponseComparison")]
+pub struct CrmCoreResponseComparison {
+    pub avg_first_response_minutes: CrmCoreMetricDelta,
+    pub median_first_response_minutes: CrmCoreMetricDelta,
+    pub avg_response_minutes: CrmCoreMetricDelta,
+    pub median_response_minutes: CrmCoreMetricDelta,
+    pub avg_resolution_hours: CrmCoreMetricDelta,
+    pub median_resolution_hours: CrmCoreMetricDelta,
+    pub avg_customer_wait_hours: CrmCoreMetricDelta,
+    pub median_customer_wait_hours: CrmCoreMetricDelta,
+    pub avg_reply_to_view_minutes: CrmCoreMetricDelta,
+    pub median_reply_to_view_minutes: CrmCoreMetricDelta,
+}
+
+impl CrmCoreResponseComparison {
+    pub fn new(a: &CrmCoreResponseMetrics, b: &CrmCoreResponseMetrics) -> Self {
+        Self {
+            avg_first_response_minutes: CrmCoreMetricDelta::between(a.avg_first_response_minutes, b.avg_first_response_minutes),
+            median_first_response_minutes: CrmCoreMetricDelta::between(
+                a.median_first_response_minutes,
+                b.median_first_response_minutes,
+            ),
+            avg_response_minutes: CrmCoreMetricDelta::between(a.avg_response_minutes, b.avg_response_minutes),
+            median_response_minutes: CrmCoreMetricDelta::between(a.median_response_minutes, b.median_response_minutes),
+            avg_resolution_hours: CrmCoreMetricDelta::between(a.avg_resolution_hours, b.avg_resolution_hours),
+            median_resolution_hours: CrmCoreMetricDelta::between(a.median_resolution_hours, b.median_resolution_hours),
+            avg_customer_wait_hours: CrmCoreMetricDelta::between(a.avg_customer_wait_hours, b.avg_customer_wait_hours),
+            median_customer_wait_hours: CrmCoreMetricDelta::between(a.median_customer_wait_hours, b.median_customer_wait_hours),
+            avg_reply_to_view_minutes: CrmCoreMetricDelta::between(a.avg_reply_to_view_minutes, b.avg_reply_to_view_minutes),
+            median_reply_to_view_minutes: CrmCoreMetricDelta::between(
+                a.median_reply_to_view_minutes,
+                b.median_reply_to_view_minutes,
+            ),
+        }
+    }
+}
+
+/// Overview, SLA and response metrics of two periods side by side
+#[derive(Debug, Clone, SimpleObject)]
+#[graphql(name = "CrmCoreMetricsComparison")]
+pub struct CrmCoreMetricsComparison {
+    pub period_a_start: DateTime<Utc>,
+    pub period_a_end: DateTime<Utc>,
+    pub period_b_start: DateTime<Utc>,
+    pub period_b_end: DateTime<Utc>,
+    pub overview: CrmCoreOverviewComparison,
+    pub sla_metrics: CrmCoreSlaComparison,
+    pub response_metrics: CrmCoreResponseComparison,
+    /// How the product's amounts and days should be formatted
+    pub formatting: ProductLocale,
 }
 
 #[derive(Debug, Clone, FromRow, SimpleObject)]
@@ -131,6 +990,89 @@ pub struct CrmCoreAgentPerformance {
     pub csat_score: Option<f64>,
 }
 
+/// Volumes, response times, CSAT and resolution for one intake channel
+#[derive(Debug, Clone, FromRow, SimpleObject)]
+#[graphql(name = "CrmCoreChannelMetrics")]
+pub struct CrmCoreChannelMetrics {
+    pub channel: String,
+    pub total_tickets: i64,
+    pub open_tickets: i64,
+    pub resolved_tickets: i64,
+    /// Share of all tickets in the period
+    pub volume_percentage: f64,
+    pub avg_first_response_minutes: Option<f64>,
+    pub avg_resolution_hours: Option<f64>,
+    pub sla_breach_count: i64,
+    pub avg_csat_score: Option<f64>,
+}
+
+/// Header badge counts, read from maintained counters rather than ticket scans
+#[derive(Debug, Clone, SimpleObject)]
+pub struct QuickStats {
+    pub product: String,
+    pub open_tickets: i64,
+    /// Every priority, most urgent first
+    pub open_by_priority: Vec<PriorityOpenCount>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, SimpleObject)]
+pub struct PriorityOpenCount {
+    pub priority: TicketPriority,
+    pub open_tickets: i64,
+}
+
+/// Customer ranked by ticket volume, for at-risk account reviews
+#[derive(Debug, Clone, FromRow, SimpleObject)]
+#[graphql(complex, name = "CrmCoreTopCustomer")]
+pub struct CrmCoreTopCustomer {
+    pub customer_id: Uuid,
+    pub ticket_count: i64,
+    pub open_tickets: i64,
+    pub avg_csat_score: Option<f64>,
+    pub agent_messages: i64,
+    /// In the product's currency
+    #[sqlx(default)]
+    pub estimated_handling_cost: f64,
+    #[sqlx(default)]
+    pub currency_code: String,
+}
+
+/// Accepted vs rejected customer-facing intake, by telemetry signal
+#[derive(Debug, Clone, SimpleObject)]
+#[graphql(name = "CrmCoreIntakeTelemetryReport")]
+pub struct CrmCoreIntakeTelemetryReport {
+    pub total_submissions: i64,
+    pub accepted: i64,
+    pub rejected: i64,
+    pub rejection_rate: f64,
+    pub median_form_fill_ms_accepted: Option<f64>,
+    pub median_form_fill_ms_rejected: Option<f64>,
+    /// One row per signal, plus NONE for submissions without any signal
+    pub by_signal: Vec<CrmCoreIntakeSignalCount>,
+}
+
+#[derive(Debug, Clone, FromRow, SimpleObject)]
+#[graphql(name = "CrmCoreIntakeSignalCount")]
+pub struct CrmCoreIntakeSignalCount {
+    pub signal: String,
+    pub accepted: i64,
+    pub rejected: i64,
+}
+
+/// Tickets filed from one app release, to spot releases that spiked support volume
+#[derive(Debug, Clone, FromRow, SimpleObject)]
+#[graphql(name = "CrmCoreAppVersionMetrics")]
+pub struct CrmCoreAppVersionMetrics {
+    pub app_version: String,
+    pub platform: Option<ClientPlatform>,
+    pub total_tickets: i64,
+    pub open_tickets: i64,
+    /// Share of the period's tickets that reported an app version
+    pub volume_percentage: f64,
+    pub first_ticket_at: DateTime<Utc>,
+    pub last_ticket_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, FromRow, SimpleObject)]
 #[graphql(name = "CrmCoreTicketTrend")]
 pub struct CrmCoreTicketTrend {
@@ -140,17 +1082,100 @@ pub struct CrmCoreTicketTrend {
     pub active_tickets: i64,
 }
 
+#[derive(Debug, Clone, SimpleObject)]
+#[graphql(name = "CrmCoreCsatTrends")]
+pub struct CrmCoreCsatTrends {
+    /// One point per calendar day of the period, oldest first
+    pub daily: Vec<CrmCoreCsatTrendPoint>,
+    /// One point per week (starting Monday) the period touches, oldest first
+    pub weekly: Vec<CrmCoreCsatTrendPoint>,
+}
+
+#[derive(Debug, Clone, FromRow, SimpleObject)]
+#[graphql(name = "CrmCoreCsatTrendPoint")]
+pub struct CrmCoreCsatTrendPoint {
+    /// First day of the bucket, in the dashboard's timezone
+    pub date: String,
+    /// Surveys sent in the bucket, including direct submissions
+    pub surveys_sent: i64,
+    /// Surveys sent in the bucket that were answered
+    pub responses: i64,
+    /// Percentage of sent surveys that were answered; absent without surveys
+    pub response_rate: Option<f64>,
+    pub avg_score: Option<f64>,
+    /// Responses scoring 1 to 5, in that order
+    pub score_distribution: Vec<i64>,
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+#[graphql(name = "CrmCoreStatusFunnel")]
+pub struct CrmCoreStatusFunnel {
+    pub stages: Vec<CrmCoreStatusFunnelStage>,
+    pub transitions: Vec<CrmCoreStatusTransition>,
+}
+
+#[derive(Debug, Clone, FromRow, SimpleObject)]
+#[graphql(name = "CrmCoreStatusFunnelStage")]
+pub struct CrmCoreStatusFunnelStage {
+    pub status: String,
+    pub tickets_entered: i64,
+    /// Tickets still sitting in this status
+    pub tickets_remaining: i64,
+    /// Percentage of the period's tickets that reached this status
+    pub reached_rate: f64,
+    pub median_minutes_in_status: Option<f64>,
+}
+
+#[derive(Debug, Clone, FromRow, SimpleObject)]
+#[graphql(name = "CrmCoreStatusTransition")]
+pub struct CrmCoreStatusTransition {
+    pub from_status: String,
+    pub to_status: String,
+    pub count: i64,
+    /// Percentage of visits to `from_status` that moved on to `to_status`
+    pub percentage: f64,
+}
+
 // Input types
-#[derive(Debug, Clone, InputObject)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, InputObject)]
 pub struct CreateTicketInput {
     pub customer_id: Uuid,
+    /// B2B account the customer is filing for
+    #[serde(default)]
+    pub organization_id: Option<Uuid>,
     pub subject: String,
     pub description: String,
-    pub priority: TicketPriority,
+    /// Suggested by the repository's `TicketClassifier` when omitted, else MEDIUM
+    #[serde(default)]
+    pub priority: Option<TicketPriority>,
+    /// Suggested by the repository's `TicketClassifier` when omitted
     pub category: Option<String>,
+    #[serde(default)]
+    pub customer_plan: Option<String>,
+    #[serde(default)]
+    #[graphql(default)]
+    pub channel: TicketChannel,
+    /// Customer's language as detected at intake (BCP 47)
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// Sandbox ticket for smoke tests and demos; only for trusted callers
+    #[serde(default)]
+    #[graphql(default)]
+    pub test_mode: bool,
+    /// Error code or crash fingerprint, e.g. from an app's crash form or a
+    /// monitoring alert; repeats within a burst window collapse into one ticket
+    #[serde(default)]
+    pub error_signature: Option<String>,
+    /// App version, platform and device the ticket was filed from
+    #[serde(default)]
+    pub client: Option<ClientInfo>,
+    /// Client-chosen key for retries of this request, unique per product and
+    /// customer; a retry returns the ticket the first attempt created
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
 }
 
-#[derive(Debug, Clone, InputObject)]
+#[derive(Debug, Clone, Default, InputObject)]
 pub struct UpdateTicketInput {
     pub subject: Option<String>,
     pub description: Option<String>,
@@ -158,6 +1183,65 @@ pub struct UpdateTicketInput {
     pub priority: Option<TicketPriority>,
     pub category: Option<String>,
     pub assigned_to: Option<Uuid>,
+    /// Required when resolving a ticket of a product with resolution codes
+    pub resolution_code: Option<String>,
+}
+
+/// Controls which parent ticket changes are copied onto linked child tickets.
+///
+/// Status propagation leaves children that are already CLOSED untouched.
+#[derive(Debug, Clone, Copy, Default, InputObject)]
+pub struct PropagationPolicy {
+    pub priority: bool,
+    pub status: bool,
+}
+
+#[derive(Debug, Clone, InputObject)]
+pub struct UpsertSupportAgentInput {
+    pub user_id: Uuid,
+    pub display_name: String,
+    pub skills: Vec<String>,
+    pub utc_offset_minutes: i32,
+    pub active: bool,
+    pub team: Option<String>,
+    #[graphql(default)]
+    pub role: AgentRole,
+}
+
+#[derive(Debug, Clone, InputObject)]
+pub struct SetSlaTargetInput {
+    pub priority: TicketPriority,
+    pub first_response_minutes: Option<i32>,
+    pub resolution_minutes: Option<i32>,
+}
+
+#[derive(Debug, Clone, InputObject)]
+pub struct CreateAssignmentRuleInput {
+    pub name: String,
+    #[graphql(default)]
+    pub position: i32,
+    pub strategy: AssignmentStrategy,
+    pub category: Option<String>,
+    pub priority: Option<TicketPriority>,
+    pub channel: Option<TicketChannel>,
+    pub team: Option<String>,
+    pub max_open_tickets: Option<i32>,
+}
+
+#[derive(Debug, Clone, InputObject)]
+pub struct CreateCannedResponseInput {
+    pub scope: CannedResponseScope,
+    pub title: String,
+    pub body: String,
+    /// Language of the body; defaults to the product's locale
+    pub locale: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, InputObject)]
+pub struct UpdateCannedResponseInput {
+    pub title: Option<String>,
+    pub body: Option<String>,
+    pub locale: Option<String>,
 }
 
 #[derive(Debug, Clone, InputObject)]
@@ -165,14 +1249,55 @@ pub struct AddTicketMessageInput {
     pub ticket_id: Uuid,
     pub content: String,
     pub is_internal: bool,
+    /// Takes precedence over `is_internal` when set
+    #[graphql(default)]
+    pub visibility: Option<MessageVisibility>,
+    /// `@<agent id>` mentions are recorded in any format
+    #[graphql(default)]
+    pub content_format: MessageContentFormat,
+}
+
+impl AddTicketMessageInput {
+    /// `visibility`, or `INTERNAL`/`PUBLIC` from `is_internal`
+    pub fn visibility(&self) -> MessageVisibility {
+        self.visibility.unwrap_or(if self.is_internal { MessageVisibility::Internal } else { MessageVisibility::Public })
+    }
 }
 
 #[derive(Debug, Clone, InputObject)]
+pub struct SaveViewInput {
+    pub name: String,
+    pub filter: TicketFilter,
+    #[graphql(default)]
+    pub sort_order: TicketSortOrder,
+    /// Notify the owner of tickets that newly match
+    #[graphql(default)]
+    pub notify_on_match: bool,
+}
+
+/// Changes to a saved view; omitted fields keep their value, and a filter
+/// replaces the whole saved filter
+#[derive(Debug, Clone, Default, InputObject)]
+pub struct UpdateSavedViewInput {
+    pub name: Option<String>,
+    pub filter: Option<TicketFilter>,
+    pub sort_order: Option<TicketSortOrder>,
+}
+
+#[derive(Debug, Clone, Default, InputObject)]
 pub struct TicketFilter {
     pub status: Option<TicketStatus>,
     pub priority: Option<TicketPriority>,
     pub assigned_to: Option<Uuid>,
     pub customer_id: Option<Uuid>,
+    /// Only tickets of this B2B account, for tenant-isolated views
+    pub organization_id: Option<Uuid>,
     pub category: Option<String>,
     pub search_query: Option<String>,
+    /// Release reported in the ticket's client info
+    pub app_version: Option<String>,
+    pub platform: Option<ClientPlatform>,
+    /// Also list soft-deleted tickets; for admin views only
+    #[graphql(default)]
+    pub include_deleted: bool,
 }
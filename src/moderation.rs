@@ -0,0 +1,117 @@
+//! Content moderation for inbound customer messages
+//!
+//! Every non-internal message written by the ticket's own customer is passed
+//! to the repository's [`ContentModerator`]. Flagged messages are marked for
+//! agent review; escalated ones also raise the ticket to URGENT, taking on
+//! URGENT's SLA targets and publishing `ticket.updated`. Either way an
+//! abuse incident is recorded against the customer, which feeds the blocklist
+//! workflow (see `SupportRepository::abuse_blocklist_candidates`).
+//!
+//! ```rust
+//! use pleme_support::{ContentModerator, ModerationAction, WordlistModerator};
+//!
+//! let moderator = WordlistModerator::new(["idiot"], ["burn it down"]);
+//! assert_eq!(moderator.moderate("You IDIOT!").action, ModerationAction::Flag);
+//! assert_eq!(moderator.moderate("I will burn it down").action, ModerationAction::Escalate);
+//! assert_eq!(moderator.moderate("Idiomatic question").action, ModerationAction::Allow);
+//! ```
+
+use async_graphql::Enum;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// What to do with an inbound message, in increasing order of severity
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "moderation_action", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ModerationAction {
+    Allow,
+    /// Store the message but mark it for agent review
+    Flag,
+    /// Flag the message and raise the ticket to URGENT
+    Escalate,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ModerationResult {
+    pub action: ModerationAction,
+    /// Terms that triggered the action, for the incident record
+    pub matched_terms: Vec<String>,
+}
+
+impl ModerationResult {
+    pub fn allow() -> Self {
+        Self { action: ModerationAction::Allow, matched_terms: Vec::new() }
+    }
+}
+
+pub trait ContentModerator: Send + Sync {
+    fn moderate(&self, content: &str) -> ModerationResult;
+}
+
+const DEFAULT_FLAG_TERMS: &[&str] = &["asshole", "bastard", "bitch", "cunt", "fuck", "fucking", "idiot", "shit"];
+const DEFAULT_ESCALATE_TERMS: &[&str] = &["kill you", "i know where you live", "you will regret", "bomb"];
+
+/// Whole-word, case-insensitive wordlist matching
+///
+/// Terms may be phrases; they match on word boundaries, so `"ass"` does not
+/// match `"class"`.
+#[derive(Debug, Clone)]
+pub struct WordlistModerator {
+    flag_terms: BTreeSet<String>,
+    escalate_terms: BTreeSet<String>,
+}
+
+impl WordlistModerator {
+    pub fn new<F, E>(flag_terms: F, escalate_terms: E) -> Self
+    where
+        F: IntoIterator,
+        F::Item: AsRef<str>,
+        E: IntoIterator,
+        E::Item: AsRef<str>,
+    {
+        Self {
+            flag_terms: flag_terms.into_iter().map(|t| normalize(t.as_ref())).collect(),
+            escalate_terms: escalate_terms.into_iter().map(|t| normalize(t.as_ref())).collect(),
+        }
+    }
+}
+
+impl Default for WordlistModerator {
+    fn default() -> Self {
+        Self::new(DEFAULT_FLAG_TERMS, DEFAULT_ESCALATE_TERMS)
+    }
+}
+
+impl ContentModerator for WordlistModerator {
+    fn moderate(&self, content: &str) -> ModerationResult {
+        let text = format!(" {} ", normalize(content));
+        let matches = |terms: &BTreeSet<String>| -> Vec<String> {
+            terms
+                .iter()
+                .filter(|term| !term.is_empty() && text.contains(&format!(" {} ", term)))
+                .cloned()
+                .collect()
+        };
+
+        let escalate = matches(&self.escalate_terms);
+        if !escalate.is_empty() {
+            return ModerationResult { action: ModerationAction::Escalate, matched_terms: escalate };
+        }
+
+        let flag = matches(&self.flag_terms);
+        if !flag.is_empty() {
+            return ModerationResult { action: ModerationAction::Flag, matched_terms: flag };
+        }
+
+        ModerationResult::allow()
+    }
+}
+
+/// Lower-case words separated by single spaces, punctuation dropped
+fn normalize(text: &str) -> String {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '\''))
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,90 @@
+//! Relay global object identification
+//!
+//! Support objects implement the Relay `Node` interface: their GraphQL `id`
+//! is an opaque global id, base64 of `TypeName:uuid`, and `node(id)` fetches
+//! any of them back. The raw UUID stays available as `databaseId` for
+//! arguments that take one, e.g. `supportTicket(id: UUID!)`.
+//!
+//! ```rust
+//! use pleme_support::node::{decode_global_id, encode_global_id, NodeType};
+//! use uuid::Uuid;
+//!
+//! let id = Uuid::nil();
+//! let global_id = encode_global_id(NodeType::SupportTicket, id);
+//! assert_eq!(global_id.as_str(), "U3VwcG9ydFRpY2tldDowMDAwMDAwMC0wMDAwLTAwMDAtMDAwMC0wMDAwMDAwMDAwMDA=");
+//! assert_eq!(decode_global_id(&global_id).unwrap(), (NodeType::SupportTicket, id));
+//! assert!(decode_global_id("not-a-global-id").is_err());
+//! ```
+
+use async_graphql::{Interface, ID};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::models::{CannedResponse, SavedView, SupportAgent, SupportTicket, TicketMessage};
+use crate::{Result, SupportError};
+
+/// Object types that can be fetched by global id
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum NodeType {
+    SupportTicket,
+    TicketMessage,
+    SupportAgent,
+    SavedView,
+    CannedResponse,
+}
+
+impl NodeType {
+    /// GraphQL type name, as encoded in global ids
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NodeType::SupportTicket => "SupportTicket",
+            NodeType::TicketMessage => "TicketMessage",
+            NodeType::SupportAgent => "SupportAgent",
+            NodeType::SavedView => "SavedView",
+            NodeType::CannedResponse => "CannedResponse",
+        }
+    }
+}
+
+impl FromStr for NodeType {
+    type Err = SupportError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "SupportTicket" => Ok(NodeType::SupportTicket),
+            "TicketMessage" => Ok(NodeType::TicketMessage),
+            "SupportAgent" => Ok(NodeType::SupportAgent),
+            "SavedView" => Ok(NodeType::SavedView),
+            "CannedResponse" => Ok(NodeType::CannedResponse),
+            other => Err(SupportError::InvalidInput(format!("Unknown node type: {}", other))),
+        }
+    }
+}
+
+pub fn encode_global_id(node_type: NodeType, id: Uuid) -> ID {
+    ID(STANDARD.encode(format!("{}:{}", node_type.as_str(), id)))
+}
+
+pub fn decode_global_id(global_id: &str) -> Result<(NodeType, Uuid)> {
+    let invalid = || SupportError::InvalidInput(format!("Invalid global id: {}", global_id));
+
+    let decoded = STANDARD.decode(global_id).map_err(|_| invalid())?;
+    let decoded = String::from_utf8(decoded).map_err(|_| invalid())?;
+    let (node_type, id) = decoded.split_once(':').ok_or_else(invalid)?;
+
+    Ok((node_type.parse()?, Uuid::parse_str(id).map_err(|_| invalid())?))
+}
+
+/// Relay `Node` interface
+#[allow(clippy::large_enum_variant)]
+#[derive(Interface)]
+#[graphql(field(name = "id", ty = "ID", desc = "Globally unique, opaque id"))]
+pub enum Node {
+    SupportTicket(SupportTicket),
+    TicketMessage(TicketMessage),
+    SupportAgent(SupportAgent),
+    SavedView(SavedView),
+    CannedResponse(CannedResponse),
+}
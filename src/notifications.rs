@@ -0,0 +1,80 @@
+//! Agent notifications from saved views
+//!
+//! A saved view with `notify_on_match` set works as a personal triage rule.
+//! `evaluate_saved_view_notifications`, run periodically (or via
+//! `pleme-support-cli maintenance evaluate-saved-views`), checks tickets
+//! created or updated since the view's last evaluation against its filter and
+//! publishes one [`SavedViewMatched`] event per view with new matches to the
+//! repository's [`NotificationSink`]. A ticket is notified at most once per
+//! view; tickets that already matched when notifications were turned on are
+//! only notified once they change. Changes are held back for
+//! [`SAVED_VIEW_SETTLE_SECONDS`] so a write still committing with an earlier
+//! `updated_at` is not skipped, and a view whose sink call fails is retried
+//! on the next run without holding up the others.
+//!
+//! The sink also receives [`TicketEscalated`] events from `run_escalations`;
+//! see [`escalation`](crate::escalation).
+
+use async_graphql::SimpleObject;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::escalation::TicketEscalated;
+use crate::Result;
+
+/// How long a ticket change must be committed before saved views are matched against it
+pub const SAVED_VIEW_SETTLE_SECONDS: i64 = 10;
+
+/// Tickets that newly match an agent's saved view
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SimpleObject)]
+pub struct SavedViewMatched {
+    pub product: String,
+    pub view_id: Uuid,
+    pub view_name: String,
+    /// The view's owner, who is notified
+    pub agent_id: Uuid,
+    /// Oldest first
+    pub ticket_ids: Vec<Uuid>,
+    pub evaluated_at: DateTime<Utc>,
+}
+
+/// Destination for agent notifications, e.g. a push or in-app inbox service
+#[async_trait]
+pub trait NotificationSink: Send + Sync {
+    async fn notify(&self, event: &SavedViewMatched) -> Result<()>;
+
+    /// Tell a ticket's team leads that it escalated; ignored by default
+    async fn notify_escalation(&self, _event: &TicketEscalated) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Logs notifications at info level
+#[derive(Debug, Clone, Default)]
+pub struct LogNotificationSink;
+
+#[async_trait]
+impl NotificationSink for LogNotificationSink {
+    async fn notify(&self, event: &SavedViewMatched) -> Result<()> {
+        tracing::info!(
+            "{} new tickets match saved view '{}' of agent {}",
+            event.ticket_ids.len(),
+            event.view_name,
+            event.agent_id,
+        );
+        Ok(())
+    }
+
+    async fn notify_escalation(&self, event: &TicketEscalated) -> Result<()> {
+        tracing::info!(
+            "Ticket {} escalated to level {} by rule '{}', notifying {} leads",
+            event.ticket_ref,
+            event.level,
+            event.rule_name,
+            event.lead_ids.len(),
+        );
+        Ok(())
+    }
+}
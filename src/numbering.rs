@@ -0,0 +1,223 @@
+//! Human-readable ticket references
+//!
+//! Each product can configure its reference format: a prefix, zero padding,
+//! and whether numbering restarts every year (`NSK-2024-000042`) or runs
+//! continuously (`NSK-000042`). Numbers come from a per-product database
+//! counter, so concurrent intake never produces duplicates.
+//!
+//! ```rust
+//! use pleme_support::{NumberReset, TicketNumberFormat, TicketNumbering};
+//!
+//! let numbering = TicketNumbering::default().with_format("novaskyn", TicketNumberFormat {
+//!     prefix: "NSK".to_string(),
+//!     padding: 5,
+//!     reset: NumberReset::Yearly,
+//! });
+//!
+//! let (product, number) = numbering.parse_ticket_ref("Re: [NSK-2024-00042] Login issue").unwrap();
+//! assert_eq!(product, "novaskyn");
+//! assert_eq!((number.year, number.sequence), (Some(2024), 42));
+//! ```
+
+use async_graphql::SimpleObject;
+use chrono::{DateTime, Datelike, Utc};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum NumberReset {
+    /// Numbering restarts at 1 every calendar year (UTC); the year is part of the reference
+    Yearly,
+    Continuous,
+}
+
+#[derive(Debug, Clone)]
+pub struct TicketNumberFormat {
+    /// Must not contain `-`, which separates the reference parts
+    pub prefix: String,
+    /// Minimum digits; longer numbers are never truncated
+    pub padding: usize,
+    pub reset: NumberReset,
+}
+
+impl TicketNumberFormat {
+    /// Default format for products without explicit settings: `<PRODUCT>-000042`
+    pub fn for_product(product: &str) -> Self {
+        Self {
+            prefix: product.to_ascii_uppercase(),
+            padding: 6,
+            reset: NumberReset::Continuous,
+        }
+    }
+
+    /// Counter period for a ticket created at `now`: the year, or 0 for continuous numbering
+    pub fn period(&self, now: DateTime<Utc>) -> i32 {
+        match self.reset {
+            NumberReset::Yearly => now.year(),
+            NumberReset::Continuous => 0,
+        }
+    }
+
+    /// Reference text preceding the number, e.g. `NSK-2024-`
+    pub fn ref_prefix(&self, now: DateTime<Utc>) -> String {
+        match self.reset {
+            NumberReset::Yearly => format!("{}-{}-", self.prefix, now.year()),
+            NumberReset::Continuous => format!("{}-", self.prefix),
+        }
+    }
+
+    pub fn format(&self, number: &TicketNumber) -> String {
+        match number.year {
+            Some(year) => format!("{}-{}-{:0width$}", self.prefix, year, number.sequence, width = self.padding),
+            None => format!("{}-{:0width$}", self.prefix, number.sequence, width = self.padding),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, SimpleObject)]
+pub struct TicketNumber {
+    /// Set for yearly-reset formats
+    pub year: Option<i32>,
+    pub sequence: i64,
+}
+
+/// Per-product reference formats
+#[derive(Debug, Clone, Default)]
+pub struct TicketNumbering {
+    formats: HashMap<String, TicketNumberFormat>,
+}
+
+impl TicketNumbering {
+    pub fn with_format(mut self, product: impl Into<String>, format: TicketNumberFormat) -> Self {
+        self.formats.insert(product.into(), format);
+        self
+    }
+
+    pub fn format_for(&self, product: &str) -> TicketNumberFormat {
+        self.formats
+            .get(product)
+            .cloned()
+            .unwrap_or_else(|| TicketNumberFormat::for_product(product))
+    }
+
+    /// Parse a reference such as `NSK-2024-000042` into its product and number
+    ///
+    /// Configured prefixes are matched first; otherwise the prefix is taken to
+    /// be the upper-cased product name (the default format). Matching is
+    /// case-insensitive and the reference may be embedded in other text, such
+    /// as an email subject like `Re: [NSK-000042] Login issue`.
+    pub fn parse_ticket_ref(&self, text: &str) -> Option<(String, TicketNumber)> {
+        text.split(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+            .find_map(|token| self.parse_token(token))
+    }
+
+    fn parse_token(&self, token: &str) -> Option<(String, TicketNumber)> {
+        let (prefix, rest) = token.split_once('-')?;
+        if prefix.is_empty() {
+            return None;
+        }
+
+        let (product, reset) = match self
+            .formats
+            .iter()
+            .find(|(_, f)| f.prefix.eq_ignore_ascii_case(prefix))
+        {
+            Some((product, format)) => (product.clone(), format.reset),
+            None => (prefix.to_ascii_lowercase(), NumberReset::Continuous),
+        };
+
+        let number = match (reset, rest.split_once('-')) {
+            (NumberReset::Yearly, Some((year, sequence))) => TicketNumber {
+                year: Some(parse_digits(year)?.try_into().ok()?),
+                sequence: parse_digits(sequence)?,
+            },
+            (NumberReset::Continuous, None) => TicketNumber {
+                year: None,
+                sequence: parse_digits(rest)?,
+            },
+            _ => return None,
+        };
+
+        Some((product, number))
+    }
+}
+
+fn parse_digits(s: &str) -> Option<i64> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn yearly(prefix: &str, padding: usize) -> TicketNumberFormat {
+        TicketNumberFormat { prefix: prefix.to_string(), padding, reset: NumberReset::Yearly }
+    }
+
+    #[test]
+    fn yearly_numbering_rolls_over_at_utc_new_year() {
+        let format = yearly("NSK", 6);
+        let last_second = Utc.with_ymd_and_hms(2025, 12, 31, 23, 59, 59).unwrap();
+        let new_year = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+
+        assert_eq!(format.period(last_second), 2025);
+        assert_eq!(format.period(new_year), 2026);
+        assert_eq!(format.ref_prefix(last_second), "NSK-2025-");
+        assert_eq!(format.ref_prefix(new_year), "NSK-2026-");
+
+        let continuous = TicketNumberFormat::for_product("novaskyn");
+        assert_eq!(continuous.period(last_second), 0);
+        assert_eq!(continuous.period(new_year), 0);
+        assert_eq!(continuous.ref_prefix(new_year), "NOVASKYN-");
+    }
+
+    #[test]
+    fn pads_without_truncating() {
+        let format = yearly("NSK", 3);
+        assert_eq!(format.format(&TicketNumber { year: Some(2026), sequence: 7 }), "NSK-2026-007");
+        assert_eq!(format.format(&TicketNumber { year: Some(2026), sequence: 12345 }), "NSK-2026-12345");
+
+        let format = TicketNumberFormat::for_product("lilitu");
+        assert_eq!(format.format(&TicketNumber { year: None, sequence: 42 }), "LILITU-000042");
+        assert_eq!(format.format(&TicketNumber { year: None, sequence: 1_234_567 }), "LILITU-1234567");
+    }
+
+    #[test]
+    fn parses_configured_and_default_prefixes_case_insensitively() {
+        let numbering = TicketNumbering::default().with_format("novaskyn", yearly("NSK", 6));
+
+        assert_eq!(
+            numbering.parse_ticket_ref("re: fwd: [nsk-2026-000042] login"),
+            Some(("novaskyn".to_string(), TicketNumber { year: Some(2026), sequence: 42 }))
+        );
+        assert_eq!(
+            numbering.parse_ticket_ref("About Lilitu-000007, again"),
+            Some(("lilitu".to_string(), TicketNumber { year: None, sequence: 7 }))
+        );
+    }
+
+    #[test]
+    fn rejects_references_that_do_not_match_the_format() {
+        let numbering = TicketNumbering::default().with_format("novaskyn", yearly("NSK", 6));
+
+        // Yearly formats need the year, continuous ones must not have one
+        assert_eq!(numbering.parse_ticket_ref("NSK-000042"), None);
+        assert_eq!(numbering.parse_ticket_ref("LILITU-2026-000042"), None);
+
+        for text in ["", "-000042", "NSK-", "NSK-2026-", "NSK-20x6-000042", "LILITU-42a", "LILITU-+42", "LILITU-99999999999999999999"] {
+            assert_eq!(numbering.parse_ticket_ref(text), None, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn takes_the_first_valid_reference_in_the_text() {
+        let numbering = TicketNumbering::default();
+        assert_eq!(
+            numbering.parse_ticket_ref("follow-up to THAI-000003 and THAI-000004"),
+            Some(("thai".to_string(), TicketNumber { year: None, sequence: 3 }))
+        );
+    }
+}
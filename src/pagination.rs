@@ -0,0 +1,86 @@
+//! Relay cursor connections
+//!
+//! `supportTickets` returns a [`TicketConnection`]: one page of edges, each
+//! with an opaque cursor, plus `pageInfo` and the `totalCount` of tickets
+//! matching the filter. Pass a page's `endCursor` as `after` to fetch the
+//! next one. Cursors encode the ticket's `(created_at, id)` sort key, so
+//! pages stay stable while new tickets arrive.
+//!
+//! ```rust
+//! use chrono::{TimeZone, Utc};
+//! use pleme_support::pagination::TicketCursor;
+//! use uuid::Uuid;
+//!
+//! let cursor = TicketCursor { created_at: Utc.timestamp_opt(1_700_000_000, 0).unwrap(), id: Uuid::nil() };
+//! assert_eq!(TicketCursor::decode(&cursor.encode()).unwrap(), cursor);
+//! assert!(TicketCursor::decode("not-a-cursor").is_err());
+//! ```
+
+use async_graphql::SimpleObject;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{DateTime, SecondsFormat, Utc};
+use uuid::Uuid;
+
+use crate::models::SupportTicket;
+use crate::{Result, SupportError};
+
+/// Largest page `supportTickets` returns
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+/// Position of a ticket in the newest-first ticket order
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct TicketCursor {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl TicketCursor {
+    pub fn of(ticket: &SupportTicket) -> Self {
+        Self { created_at: ticket.created_at, id: ticket.id }
+    }
+
+    pub fn encode(&self) -> String {
+        STANDARD.encode(format!(
+            "ticket:{}:{}",
+            self.created_at.to_rfc3339_opts(SecondsFormat::Micros, true),
+            self.id
+        ))
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self> {
+        let invalid = || SupportError::InvalidInput(format!("Invalid cursor: {}", cursor));
+
+        let decoded = STANDARD.decode(cursor).map_err(|_| invalid())?;
+        let decoded = String::from_utf8(decoded).map_err(|_| invalid())?;
+        let rest = decoded.strip_prefix("ticket:").ok_or_else(invalid)?;
+        let (created_at, id) = rest.rsplit_once(':').ok_or_else(invalid)?;
+
+        Ok(Self {
+            created_at: DateTime::parse_from_rfc3339(created_at).map_err(|_| invalid())?.with_timezone(&Utc),
+            id: Uuid::parse_str(id).map_err(|_| invalid())?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct PageInfo {
+    pub has_next_page: bool,
+    pub has_previous_page: bool,
+    pub start_cursor: Option<String>,
+    pub end_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct TicketEdge {
+    pub cursor: String,
+    pub node: SupportTicket,
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+pub struct TicketConnection {
+    pub edges: Vec<TicketEdge>,
+    pub page_info: PageInfo,
+    /// Tickets matching the filter across all pages
+    pub total_count: i64,
+}
@@ -0,0 +1,150 @@
+//! Per-product configuration registry
+//!
+//! Every product tickets are filed under is registered as a [`ProductConfig`]
+//! in `product_configs`: its default priority, the SLA policy it follows, its
+//! business hours and holidays (see [`business_hours`](crate::business_hours))
+//! the channels it accepts tickets on and the categories it allows (see
+//! [`validation`](crate::validation)). Creating a ticket
+//! for an unregistered product fails with [`SupportError::UnknownProduct`],
+//! so a typo in a caller's product string is caught instead of silently
+//! opening a new product.
+//!
+//! Migration 049 registers every product that already has tickets. Register
+//! new ones with `upsert_product_config`, the `upsertProductConfig` mutation
+//! or `pleme-support-cli products register`.
+//!
+//! ```rust
+//! use pleme_support::{ProductConfigInput, TicketChannel};
+//!
+//! let input = ProductConfigInput {
+//!     business_days: Some(vec![1, 2, 3, 4, 5]),
+//!     business_opens_at_minute: Some(9 * 60),
+//!     business_closes_at_minute: Some(18 * 60),
+//!     enabled_channels: Some(vec![TicketChannel::Web, TicketChannel::Email]),
+//!     ..Default::default()
+//! };
+//! assert!(input.validate().is_ok());
+//!
+//! let closed = ProductConfigInput { business_days: Some(vec![8]), ..Default::default() };
+//! assert!(closed.validate().is_err());
+//! ```
+
+use async_graphql::{InputObject, SimpleObject};
+use chrono::{DateTime, NaiveDate, Utc, Weekday};
+use serde::Serialize;
+use sqlx::FromRow;
+
+use crate::business_hours::{BusinessCalendar, BusinessHours};
+use crate::models::{TicketChannel, TicketPriority};
+use crate::validation::MAX_CATEGORY_LENGTH;
+use crate::{Result, SupportError};
+
+/// Longest accepted product key, as stored in `support_tickets.product`
+pub const MAX_PRODUCT_KEY_LENGTH: usize = 50;
+
+/// A registered product and its ticket defaults
+#[derive(Debug, Clone, Serialize, FromRow, SimpleObject)]
+pub struct ProductConfig {
+    pub product: String,
+    pub display_name: Option<String>,
+    /// Priority of tickets created without one that the classifier leaves unset
+    pub default_priority: TicketPriority,
+    /// Name of the SLA policy the product follows, for services that keep several
+    pub sla_policy: Option<String>,
+    /// IANA timezone of the business hours
+    pub business_timezone: String,
+    /// ISO weekdays the product is staffed, 1 = Monday
+    pub business_days: Vec<i32>,
+    /// Minutes after local midnight the business day starts
+    pub business_opens_at_minute: i32,
+    /// Minutes after local midnight the business day ends, up to 1440
+    pub business_closes_at_minute: i32,
+    /// Local dates the product is closed all day
+    pub business_holidays: Vec<NaiveDate>,
+    /// Channels tickets may be created through
+    pub enabled_channels: Vec<TicketChannel>,
+    /// Categories tickets may be filed under; any when empty
+    pub categories: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ProductConfig {
+    pub fn business_hours(&self) -> BusinessHours {
+        BusinessHours {
+            timezone: self.business_timezone.clone(),
+            days: self
+                .business_days
+                .iter()
+                .filter_map(|day| u8::try_from(day - 1).ok().and_then(|day| Weekday::try_from(day).ok()))
+                .collect(),
+            opens_at_minute: self.business_opens_at_minute as u32,
+            closes_at_minute: self.business_closes_at_minute as u32,
+        }
+    }
+
+    /// Business hours and holidays, which first-response SLA clocks run on
+    pub fn business_calendar(&self) -> BusinessCalendar {
+        BusinessCalendar { hours: self.business_hours(), holidays: self.business_holidays.clone() }
+    }
+
+    pub fn accepts_channel(&self, channel: TicketChannel) -> bool {
+        self.enabled_channels.contains(&channel)
+    }
+
+    pub fn allows_category(&self, category: &str) -> bool {
+        self.categories.is_empty() || self.categories.iter().any(|c| c == category)
+    }
+}
+
+/// Settings for registering or changing a product; omitted fields keep their
+/// value, or take the default (MEDIUM, the product locale's timezone, every
+/// hour of every day, every channel) when the product is new
+#[derive(Debug, Clone, Default, InputObject)]
+pub struct ProductConfigInput {
+    pub display_name: Option<String>,
+    pub default_priority: Option<TicketPriority>,
+    pub sla_policy: Option<String>,
+    pub business_timezone: Option<String>,
+    pub business_days: Option<Vec<i32>>,
+    pub business_opens_at_minute: Option<i32>,
+    pub business_closes_at_minute: Option<i32>,
+    /// Replaces the product's holidays
+    pub business_holidays: Option<Vec<NaiveDate>>,
+    pub enabled_channels: Option<Vec<TicketChannel>>,
+    /// Replaces the categories tickets may be filed under; empty allows any
+    pub categories: Option<Vec<String>>,
+}
+
+impl ProductConfigInput {
+    /// Check the business hours and channels given
+    ///
+    /// The timezone, and that the hours open before they close once merged
+    /// with the stored ones, are checked by the repository.
+    pub fn validate(&self) -> Result<()> {
+        if let Some(days) = &self.business_days {
+            if days.is_empty() || days.iter().any(|day| !(1..=7).contains(day)) {
+                return Err(SupportError::Validation(
+                    "business_days must list ISO weekdays between 1 and 7".to_string(),
+                ));
+            }
+        }
+        let opens = self.business_opens_at_minute.unwrap_or(0);
+        let closes = self.business_closes_at_minute.unwrap_or(24 * 60);
+        if !(0..24 * 60).contains(&opens) || !(1..=24 * 60).contains(&closes) {
+            return Err(SupportError::Validation("Business hours must be minutes between 0 and 1440".to_string()));
+        }
+        if self.enabled_channels.as_ref().is_some_and(Vec::is_empty) {
+            return Err(SupportError::Validation("At least one channel must be enabled".to_string()));
+        }
+        if let Some(categories) = &self.categories {
+            if categories.iter().any(|c| c.trim().is_empty() || c.chars().count() > MAX_CATEGORY_LENGTH) {
+                return Err(SupportError::Validation(format!(
+                    "categories must be between 1 and {} characters",
+                    MAX_CATEGORY_LENGTH
+                )));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+//! Narrow ticket projections for list and detail reads
+//!
+//! `SELECT *` on `support_tickets` also reads each row's free-text
+//! description, its metadata JSON and its full-text search vector, which
+//! dominate row size but which ticket lists rarely show. A [`TicketProjection`]
+//! names the wide columns a read needs; the others come back as placeholders,
+//! an empty `description` and `{}` `metadata`, and the search vector is never
+//! read.
+//!
+//! The GraphQL ticket list and detail resolvers build their projection from
+//! the query's selection with `ctx.look_ahead()`, so a list view selecting
+//! `subject`, `status` and `priority` only reads narrow columns, and
+//! `supportTickets` skips its `COUNT(*)` unless `totalCount` is selected.
+//! Nested data (messages, customer info, last-seen) is already loaded only by
+//! the resolvers of selected fields.
+//!
+//! ```rust
+//! use pleme_support::TicketProjection;
+//!
+//! assert!(TicketProjection::FULL.columns().contains("metadata"));
+//! assert!(TicketProjection::SUMMARY.columns().contains("''::TEXT AS description"));
+//! assert!(!TicketProjection::FULL.columns().contains("search_vector"));
+//! ```
+
+/// Columns of a [`SupportTicket`](crate::SupportTicket) other than the wide
+/// ones; keep in sync with the struct
+const TICKET_NARROW_COLUMNS: &str = "id, product, customer_id, organization_id, subject, status, priority, category, \
+    assigned_to, first_response_at, resolved_at, closed_at, sla_breach, sla_breached_at, csat_score, \
+    created_at, updated_at, deleted_at, parent_ticket_id, waiting_since, customer_wait_seconds, customer_plan, \
+    ticket_number, ticket_ref, channel, resolving_agent_id, resolving_message_id, follow_up_of, \
+    csat_submitted_at, linked_at, locale, first_response_due_at, sla_due_at, escalation_level, escalated_at, \
+    tags, test_mode, error_signature, occurrence_count, last_occurred_at, merged_into, resolution_code, \
+    reopen_count";
+
+/// Wide ticket columns a read loads
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct TicketProjection {
+    /// Load `description`; otherwise it reads as empty
+    pub description: bool,
+    /// Load `metadata`, which `submitter` and `client` are read from; otherwise it reads as `{}`
+    pub metadata: bool,
+}
+
+impl TicketProjection {
+    /// Every column a ticket has
+    pub const FULL: Self = Self { description: true, metadata: true };
+
+    /// Only the narrow columns, for list views
+    pub const SUMMARY: Self = Self { description: false, metadata: false };
+
+    /// Select list of the projection over `support_tickets`
+    pub fn columns(&self) -> String {
+        let description = if self.description { "description" } else { "''::TEXT AS description" };
+        let metadata = if self.metadata { "metadata" } else { "'{}'::JSONB AS metadata" };
+        format!("{}, {}, {}", TICKET_NARROW_COLUMNS, description, metadata)
+    }
+}
+
+impl Default for TicketProjection {
+    fn default() -> Self {
+        Self::FULL
+    }
+}
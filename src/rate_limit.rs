@@ -0,0 +1,124 @@
+//! Ticket creation rate limits at intake
+//!
+//! Spam floods on the public submit endpoint often come from one customer
+//! account opening ticket after ticket. With a [`RateLimit`] for the
+//! product, a customer-facing submission (one with submitter info) from a
+//! customer who already opened `max_tickets` tickets in the product within
+//! the rolling `window` fails with `RateLimited`, whose `retry_after` says
+//! when the oldest of them leaves the window.
+//!
+//! Every ticket the customer opened counts, whatever the channel and even if
+//! it was deleted since; test tickets don't. Retries returning an earlier
+//! request's ticket by idempotency key are never limited.
+//!
+//! ```rust
+//! use chrono::Duration;
+//! use pleme_support::{RateLimit, RateLimits};
+//!
+//! let limits = RateLimits::default().with_limit("novaskyn", RateLimit::new(5, Duration::hours(1)));
+//!
+//! assert_eq!(limits.limit_for("novaskyn").map(|l| l.max_tickets), Some(5));
+//! assert!(limits.limit_for("lilitu").is_none());
+//! ```
+
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+
+/// How many tickets a customer may open in a product per rolling window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub max_tickets: u32,
+    pub window: Duration,
+}
+
+impl RateLimit {
+    pub fn new(max_tickets: u32, window: Duration) -> Self {
+        Self { max_tickets, window }
+    }
+
+    /// How long until another ticket is allowed, given the creation times of
+    /// the customer's tickets in the window, newest first; `None` when one is
+    /// allowed now
+    pub(crate) fn retry_after(&self, recent: &[DateTime<Utc>], now: DateTime<Utc>) -> Option<Duration> {
+        let limit = self.max_tickets as usize;
+        if recent.len() < limit {
+            return None;
+        }
+        let frees_up = match limit.checked_sub(1) {
+            Some(index) => recent[index] + self.window,
+            None => now + self.window,
+        };
+        // Whole seconds, rounded up, so a client waiting that long is let through
+        let wait = frees_up - now;
+        let seconds = wait.num_seconds() + i64::from(wait.subsec_nanos() > 0);
+        Some(Duration::seconds(seconds.max(1)))
+    }
+}
+
+/// Per-product ticket rate limits; products without one are not limited
+#[derive(Debug, Clone, Default)]
+pub struct RateLimits {
+    limits: HashMap<String, RateLimit>,
+}
+
+impl RateLimits {
+    pub fn with_limit(mut self, product: impl Into<String>, limit: RateLimit) -> Self {
+        self.limits.insert(product.into(), limit);
+        self
+    }
+
+    pub fn limit_for(&self, product: &str) -> Option<&RateLimit> {
+        self.limits.get(product)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 9, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn allows_tickets_below_the_limit() {
+        let limit = RateLimit::new(3, Duration::hours(1));
+        assert_eq!(limit.retry_after(&[], now()), None);
+        assert_eq!(limit.retry_after(&[now() - Duration::minutes(1), now() - Duration::minutes(2)], now()), None);
+    }
+
+    #[test]
+    fn waits_for_the_oldest_counted_ticket_to_leave_the_window() {
+        let limit = RateLimit::new(2, Duration::hours(1));
+        let recent = [now() - Duration::minutes(10), now() - Duration::minutes(20)];
+        assert_eq!(limit.retry_after(&recent, now()), Some(Duration::minutes(40)));
+
+        // Only the newest `max_tickets` count when more are passed
+        let recent = [now() - Duration::minutes(10), now() - Duration::minutes(20), now() - Duration::minutes(50)];
+        assert_eq!(limit.retry_after(&recent, now()), Some(Duration::minutes(40)));
+    }
+
+    #[test]
+    fn rounds_the_wait_up_to_whole_seconds() {
+        let limit = RateLimit::new(1, Duration::seconds(60));
+        let recent = [now() - Duration::milliseconds(58_800)];
+        assert_eq!(limit.retry_after(&recent, now()), Some(Duration::seconds(2)));
+
+        let recent = [now() - Duration::seconds(58)];
+        assert_eq!(limit.retry_after(&recent, now()), Some(Duration::seconds(2)));
+    }
+
+    #[test]
+    fn waits_at_least_a_second_at_the_window_edge() {
+        let limit = RateLimit::new(1, Duration::seconds(60));
+        assert_eq!(limit.retry_after(&[now() - Duration::seconds(60)], now()), Some(Duration::seconds(1)));
+        assert_eq!(limit.retry_after(&[now() - Duration::seconds(90)], now()), Some(Duration::seconds(1)));
+    }
+
+    #[test]
+    fn a_zero_limit_blocks_for_a_whole_window() {
+        let limit = RateLimit::new(0, Duration::minutes(5));
+        assert_eq!(limit.retry_after(&[], now()), Some(Duration::minutes(5)));
+    }
+}
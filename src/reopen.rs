@@ -0,0 +1,64 @@
+//! Customer replies to closed tickets
+//!
+//! What happens when a customer writes on a CLOSED ticket is a per-product
+//! choice: append to the closed ticket, reopen it, open a follow-up ticket
+//! linked to it, or reject the reply with guidance. Whichever applies is
+//! recorded in the ticket's audit trail.
+//!
+//! ```rust
+//! use pleme_support::{ClosedTicketReplies, ClosedTicketReply};
+//!
+//! let replies = ClosedTicketReplies::default()
+//!     .with_policy("novaskyn", ClosedTicketReply::FollowUp)
+//!     .with_policy("thai", ClosedTicketReply::Reject {
+//!         guidance: "This ticket is closed. Please open a new one from the help center.".to_string(),
+//!     });
+//!
+//! assert_eq!(replies.policy_for("novaskyn"), &ClosedTicketReply::FollowUp);
+//! assert_eq!(replies.policy_for("lilitu"), &ClosedTicketReply::Append);
+//! ```
+
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub enum ClosedTicketReply {
+    /// Add the reply to the closed ticket as is
+    #[default]
+    Append,
+    /// Reopen the ticket: IN_PROGRESS if assigned, otherwise NEW
+    Reopen,
+    /// Open a new ticket, linked to the closed one, holding the reply
+    FollowUp,
+    /// Refuse the reply; `guidance` is returned to the customer
+    Reject { guidance: String },
+}
+
+impl ClosedTicketReply {
+    /// Representation recorded in the audit trail
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClosedTicketReply::Append => "APPENDED",
+            ClosedTicketReply::Reopen => "REOPENED",
+            ClosedTicketReply::FollowUp => "FOLLOW_UP",
+            ClosedTicketReply::Reject { .. } => "REJECTED",
+        }
+    }
+}
+
+/// Per-product handling of customer replies to closed tickets
+#[derive(Debug, Clone, Default)]
+pub struct ClosedTicketReplies {
+    policies: HashMap<String, ClosedTicketReply>,
+}
+
+impl ClosedTicketReplies {
+    pub fn with_policy(mut self, product: impl Into<String>, policy: ClosedTicketReply) -> Self {
+        self.policies.insert(product.into(), policy);
+        self
+    }
+
+    pub fn policy_for(&self, product: &str) -> &ClosedTicketReply {
+        static APPEND: ClosedTicketReply = ClosedTicketReply::Append;
+        self.policies.get(product).unwrap_or(&APPEND)
+    }
+}
@@ -0,0 +1,370 @@
+//! Scheduled summary reports
+//!
+//! A [`ReportSchedule`] asks for a daily or weekly [`SupportReport`] of a
+//! product: the open backlog, tickets opened and resolved, SLA breaches and
+//! CSAT over the last full day or week. Host services call
+//! `run_due_reports`, from a cron job or `pleme-support-cli maintenance
+//! run-reports`, every few minutes; it generates the report of every schedule
+//! that is due, keeps it as a [`ReportRun`], and returns the runs for the host
+//! to deliver to the schedule's `recipients`.
+//!
+//! Schedules run at `hour` o'clock in their timezone (weekly ones on
+//! `day_of_week`, 1 = Monday), and report the period that ended at the last
+//! local midnight. A schedule that missed runs, e.g. while the cron job was
+//! down, runs once and moves on to its next slot.
+//!
+//! ```rust
+//! use pleme_support::ReportFrequency;
+//!
+//! assert_eq!(ReportFrequency::Daily.period_days(), 1);
+//! assert_eq!(ReportFrequency::Weekly.period_days(), 7);
+//! ```
+//!
+//! ## SLA breach digests
+//!
+//! An [`SlaBreachDigest`] lists every deadline a product's tickets missed in
+//! a week, one CSV row per missed deadline (ticket ref, customer, priority,
+//! breach type, minutes over, assigned agent), followed by summary rows per
+//! priority, breach type and agent. `sla_breach_digest` builds one for the
+//! ISO week containing a date; schedules with `include_breach_digest` attach
+//! one for their period to every run.
+//!
+//! ```rust
+//! use chrono::{NaiveDate, TimeZone, Utc};
+//! use pleme_support::reports::{iso_week_start, SlaBreach, SlaBreachDigest, SlaBreachType};
+//! use pleme_support::{ExportFormat, TicketPriority};
+//! use uuid::Uuid;
+//!
+//! let thursday = NaiveDate::from_ymd_opt(2026, 10, 15).unwrap();
+//! assert_eq!(iso_week_start(thursday), NaiveDate::from_ymd_opt(2026, 10, 12).unwrap());
+//!
+//! let breach = SlaBreach {
+//!     ticket_id: Uuid::new_v4(),
+//!     ticket_ref: "NOVASKYN-000042".to_string(),
+//!     customer_id: Uuid::new_v4(),
+//!     priority: TicketPriority::High,
+//!     breach_type: SlaBreachType::FirstResponse,
+//!     minutes_over: 95,
+//!     assigned_to: None,
+//!     assigned_agent: None,
+//! };
+//! let digest = SlaBreachDigest::new(
+//!     "novaskyn".to_string(),
+//!     "UTC".to_string(),
+//!     Utc.with_ymd_and_hms(2026, 10, 12, 0, 0, 0).unwrap(),
+//!     Utc.with_ymd_and_hms(2026, 10, 19, 0, 0, 0).unwrap(),
+//!     vec![breach],
+//! );
+//! assert_eq!(digest.summary[0].breaches, 1);
+//!
+//! let mut csv = Vec::new();
+//! assert_eq!(digest.write_csv(&mut csv, ExportFormat::Csv).unwrap(), 1);
+//! let csv = String::from_utf8(csv).unwrap();
+//! assert!(csv.lines().nth(1).unwrap().starts_with("NOVASKYN-000042,"));
+//! ```
+
+use async_graphql::{Enum, InputObject, SimpleObject};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::collections::BTreeMap;
+use std::io::Write;
+use uuid::Uuid;
+
+use crate::export::{csv_error, csv_writer, text_cell, ExportFormat};
+use crate::models::{PriorityOpenCount, TicketPriority};
+use crate::Result;
+
+/// Most runs returned by one `report_runs` call
+pub const MAX_REPORT_RUNS: i64 = 100;
+
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReportFrequency {
+    Daily,
+    Weekly,
+}
+
+impl ReportFrequency {
+    /// Length of the reported period
+    pub fn period_days(&self) -> i32 {
+        match self {
+            ReportFrequency::Daily => 1,
+            ReportFrequency::Weekly => 7,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportFrequency::Daily => "DAILY",
+            ReportFrequency::Weekly => "WEEKLY",
+        }
+    }
+}
+
+#[derive(Debug, Clone, FromRow, SimpleObject)]
+pub struct ReportSchedule {
+    pub id: Uuid,
+    pub product: String,
+    pub name: String,
+    pub frequency: ReportFrequency,
+    /// ISO weekday weekly reports run on, 1 = Monday; ignored for daily reports
+    pub day_of_week: i32,
+    /// Local hour the report runs at, 0-23
+    pub hour: i32,
+    /// IANA timezone of `hour` and of the reported days
+    pub timezone: String,
+    /// Where the host service delivers the report, e.g. email addresses
+    pub recipients: Vec<String>,
+    /// Attach an SLA breach digest CSV of the period to every run
+    pub include_breach_digest: bool,
+    pub active: bool,
+    pub next_run_at: DateTime<Utc>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, InputObject)]
+pub struct CreateReportScheduleInput {
+    pub name: String,
+    pub frequency: ReportFrequency,
+    /// Defaults to Monday
+    pub day_of_week: Option<i32>,
+    pub hour: i32,
+    /// Defaults to the product's timezone
+    pub timezone: Option<String>,
+    #[graphql(default)]
+    pub recipients: Vec<String>,
+    /// Attach an SLA breach digest CSV of the period to every run
+    #[graphql(default)]
+    pub include_breach_digest: bool,
+}
+
+/// Changes to a schedule; omitted fields keep their value
+#[derive(Debug, Clone, Default, InputObject)]
+pub struct UpdateReportScheduleInput {
+    pub name: Option<String>,
+    pub frequency: Option<ReportFrequency>,
+    pub day_of_week: Option<i32>,
+    pub hour: Option<i32>,
+    pub timezone: Option<String>,
+    pub recipients: Option<Vec<String>>,
+    pub include_breach_digest: Option<bool>,
+    pub active: Option<bool>,
+}
+
+/// Summary of a product's support over a period; test tickets are left out
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct SupportReport {
+    pub product: String,
+    pub frequency: ReportFrequency,
+    pub timezone: String,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    /// Open tickets when the report was generated
+    pub open_backlog: i64,
+    /// Every priority, most urgent first
+    pub open_by_priority: Vec<PriorityOpenCount>,
+    /// Open tickets without an assignee when the report was generated
+    pub unassigned_backlog: i64,
+    pub new_tickets: i64,
+    pub resolved_tickets: i64,
+    /// Tickets whose SLA was breached during the period
+    pub sla_breaches: i64,
+    /// CSAT scores submitted during the period
+    pub csat_responses: i64,
+    pub avg_csat_score: Option<f64>,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// A generated report, kept for its schedule
+#[derive(Debug, Clone, Serialize, SimpleObject)]
+pub struct ReportRun {
+    pub id: Uuid,
+    pub schedule_id: Uuid,
+    pub schedule_name: String,
+    /// Copied from the schedule when the report ran
+    pub recipients: Vec<String>,
+    pub report: SupportReport,
+    /// SLA breach digest of the period as CSV, when the schedule asks for one
+    pub breach_digest_csv: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Header of SLA breach digest rows
+pub const SLA_BREACH_DIGEST_COLUMNS: &[&str] =
+    &["ticket_ref", "customer_id", "priority", "breach_type", "minutes_over", "assigned_agent"];
+
+/// Header of the summary rows that follow the breaches in a digest CSV
+pub const SLA_BREACH_SUMMARY_COLUMNS: &[&str] =
+    &["summary", "value", "breaches", "tickets", "total_minutes_over", "avg_minutes_over"];
+
+/// Monday of the ISO week containing `date`
+pub fn iso_week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SlaBreachType {
+    FirstResponse,
+    Resolution,
+}
+
+impl SlaBreachType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SlaBreachType::FirstResponse => "FIRST_RESPONSE",
+            SlaBreachType::Resolution => "RESOLUTION",
+        }
+    }
+}
+
+/// One deadline a ticket missed
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, SimpleObject)]
+pub struct SlaBreach {
+    pub ticket_id: Uuid,
+    pub ticket_ref: String,
+    pub customer_id: Uuid,
+    pub priority: TicketPriority,
+    pub breach_type: SlaBreachType,
+    /// Minutes past the deadline the ticket was answered or resolved, or
+    /// still open when the digest was generated
+    pub minutes_over: i64,
+    pub assigned_to: Option<Uuid>,
+    /// Display name of the assignee, when they are a registered agent
+    pub assigned_agent: Option<String>,
+}
+
+impl SlaBreach {
+    /// The assignee's display name, else their id
+    fn agent_label(&self) -> String {
+        self.assigned_agent
+            .clone()
+            .or_else(|| self.assigned_to.map(|id| id.to_string()))
+            .unwrap_or_default()
+    }
+}
+
+/// Breach totals for one value of a digest dimension
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct SlaBreachSummary {
+    /// `total`, `priority`, `breach_type` or `agent`
+    pub dimension: String,
+    /// The priority, breach type or agent; `ALL` for the total and
+    /// `UNASSIGNED` for breaches without an assignee
+    pub value: String,
+    pub breaches: i64,
+    /// Distinct tickets, which may breach both deadlines
+    pub tickets: i64,
+    pub total_minutes_over: i64,
+    pub avg_minutes_over: f64,
+}
+
+/// Missed SLA deadlines of a product's tickets breached during a period
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct SlaBreachDigest {
+    pub product: String,
+    pub timezone: String,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    /// Longest overrun first
+    pub breaches: Vec<SlaBreach>,
+    /// The total, then per priority, breach type and agent
+    pub summary: Vec<SlaBreachSummary>,
+}
+
+impl SlaBreachDigest {
+    pub fn new(
+        product: String,
+        timezone: String,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+        breaches: Vec<SlaBreach>,
+    ) -> Self {
+        let mut summary = vec![summarize("total", "ALL".to_string(), breaches.iter())];
+        for priority in [TicketPriority::Urgent, TicketPriority::High, TicketPriority::Medium, TicketPriority::Low] {
+            if breaches.iter().any(|b| b.priority == priority) {
+                let matching = breaches.iter().filter(|b| b.priority == priority);
+                summary.push(summarize("priority", priority.as_str().to_string(), matching));
+            }
+        }
+        let mut types: Vec<SlaBreachType> = breaches.iter().map(|b| b.breach_type).collect();
+        types.sort();
+        types.dedup();
+        for breach_type in types {
+            let matching = breaches.iter().filter(|b| b.breach_type == breach_type);
+            summary.push(summarize("breach_type", breach_type.as_str().to_string(), matching));
+        }
+        let mut agents: BTreeMap<String, Vec<&SlaBreach>> = BTreeMap::new();
+        for breach in &breaches {
+            let agent = Some(breach.agent_label()).filter(|a| !a.is_empty()).unwrap_or_else(|| "UNASSIGNED".to_string());
+            agents.entry(agent).or_default().push(breach);
+        }
+        for (agent, matching) in agents {
+            summary.push(summarize("agent", agent, matching.into_iter()));
+        }
+
+        Self { product, timezone, period_start, period_end, breaches, summary }
+    }
+
+    /// Write the breaches, then the summary under its own header, as CSV
+    ///
+    /// Returns the number of breach rows written.
+    pub fn write_csv<W: Write>(&self, writer: W, format: ExportFormat) -> Result<i64> {
+        let mut csv = csv_writer(writer, format)?;
+        csv.write_record(SLA_BREACH_DIGEST_COLUMNS).map_err(csv_error)?;
+        for breach in &self.breaches {
+            csv.write_record([
+                breach.ticket_ref.clone(),
+                breach.customer_id.to_string(),
+                breach.priority.as_str().to_string(),
+                breach.breach_type.as_str().to_string(),
+                breach.minutes_over.to_string(),
+                text_cell(&breach.agent_label()),
+            ])
+            .map_err(csv_error)?;
+        }
+        csv.write_record(SLA_BREACH_SUMMARY_COLUMNS).map_err(csv_error)?;
+        for row in &self.summary {
+            csv.write_record([
+                row.dimension.clone(),
+                text_cell(&row.value),
+                row.breaches.to_string(),
+                row.tickets.to_string(),
+                row.total_minutes_over.to_string(),
+                format!("{:.1}", row.avg_minutes_over),
+            ])
+            .map_err(csv_error)?;
+        }
+        csv.flush().map_err(|e| csv_error(e.into()))?;
+
+        Ok(self.breaches.len() as i64)
+    }
+}
+
+fn summarize<'a>(dimension: &str, value: String, breaches: impl Iterator<Item = &'a SlaBreach>) -> SlaBreachSummary {
+    let mut count = 0;
+    let mut total_minutes_over = 0;
+    let mut tickets = Vec::new();
+    for breach in breaches {
+        count += 1;
+        total_minutes_over += breach.minutes_over;
+        tickets.push(breach.ticket_id);
+    }
+    tickets.sort();
+    tickets.dedup();
+
+    SlaBreachSummary {
+        dimension: dimension.to_string(),
+        value,
+        breaches: count,
+        tickets: tickets.len() as i64,
+        total_minutes_over,
+        avg_minutes_over: if count > 0 { total_minutes_over as f64 / count as f64 } else { 0.0 },
+    }
+}
@@ -1,463 +1,10995 @@
-use chrono::{DateTime, Utc, Duration};
-use sqlx::PgPool;
+use chrono::{DateTime, Datelike, Months, NaiveDate, NaiveTime, Utc, Duration};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use sqlx::postgres::PgArguments;
+use sqlx::types::Json;
+use futures_util::stream::{self, Stream, TryStreamExt};
+use sqlx::{Acquire, FromRow, PgConnection, PgExecutor, PgPool, Postgres, Transaction};
 use uuid::Uuid;
 
 use crate::{SupportError, Result};
+use crate::agent_activity::{ActivityAnonymization, AgentActivityExport, AgentActivitySummary, AuthoredMessage, HandledTicket, ReceivedCsat};
+use crate::attachments::{
+    normalize_content_type, searchable_text, AttachmentStatus, AttachmentText, IndexAttachmentInput, ScanVerdict, ScannerHook, TextExtractors,
+    TicketAttachment,
+};
+use crate::assignment::{rank_candidates, AgentCandidate, AssigneeSuggestion};
+use crate::burst::{BurstCollapsing, CollapseReason, CollapsedSubmissionCounts};
+use crate::bulk::{BulkTicketResult, BulkUpdateReport, MAX_BULK_TICKETS};
+use crate::backfill::{BackfillProgress, BACKFILL_BATCH_SIZE};
+use crate::api_keys::{api_key_prefix, hash_api_key, new_api_key, ApiKeyScope, ApiKeyUsage, CreateApiKeyInput, CreatedApiKey, IntakeApiKey};
+use crate::automations::{
+    ActionKind, AutomationAction, AutomationContext, AutomationExecution, AutomationFiring, AutomationOutcome, AutomationRule,
+    AutomationTrigger, CreateAutomationRuleInput, MessageAuthor, MAX_AUTOMATION_DEPTH, TIMER_BATCH_SIZE,
+};
+use crate::export::{
+    csv_error, csv_writer, dashboard_records, export_filename, ticket_record, ExportFile, ExportFormat, ExportKind, SupportExport,
+    DASHBOARD_EXPORT_COLUMNS, EXPORT_CONTENT_TYPE, EXPORT_PAGE_SIZE, EXPORT_TTL_HOURS, TICKET_EXPORT_COLUMNS,
+};
+use crate::escalation::{CreateEscalationRuleInput, EscalationRule, TicketEscalated};
+use crate::metrics_history::{MetricsHistory, MetricsHistoryPoint, MetricsInterval, MAX_METRICS_HISTORY_POINTS};
+use crate::events::{AutomationFired, SupportEvent, SupportEventPublisher, SupportEventType};
+use crate::notifications::{LogNotificationSink, NotificationSink, SavedViewMatched, SAVED_VIEW_SETTLE_SECONDS};
+use crate::alerts::{AlertMetric, AlertSink, AlertThresholds, LogAlertSink, MetricThresholdCrossed};
+use crate::channels::email::{EmailIngestion, InboundEmail};
+use crate::classification::{ClassificationRecord, TicketClassifier, CLASSIFICATION_METADATA_KEY};
+use crate::clock::{Clock, SystemClock};
+use crate::contract::{ContractSeverityCompliance, ContractSlaBreach, ContractualSlaReport, SlaContracts};
+use crate::cost::HandlingCosts;
+use crate::csat::{hash_survey_token, new_survey_token, CsatBreakdown, CsatChannel, CsatDimension, CsatFeedbackInput, CsatSurvey, CsatSurveyLink, CsatSurveySettings};
+use crate::directory::{CustomerDirectory, CustomerInfo};
+use crate::integrations::dead_letter::{DeadLetter, DeadLetterHandlers, DeadLetterStatus, NewDeadLetter};
+use crate::integrations::issue_tracker::{EngineeringIssue, ExternalIssue, IssueState, IssueTracker, IssueTrackers, NewIssue};
+use crate::moderation::{ContentModerator, ModerationAction, ModerationResult, WordlistModerator};
+use crate::sentiment::{completes_run, LexiconSentimentAnalyzer, SentimentAnalyzer, SentimentRules};
+use crate::intake::{
+    ClientInfo, ClientPlatform, IntakePrivacy, IntakeTelemetry, SubmitterInfo, CLIENT_METADATA_KEY,
+    INTAKE_TELEMETRY_METADATA_KEY, SUBMITTER_METADATA_KEY,
+};
+use crate::sync::{
+    updated_fields, without_fields, PushedChange, SyncChangeInput, SyncChangeResult, SyncChanges, SyncCursor, SyncOutcome, SyncScope,
+    SyncTombstone, SyncedMessage, TombstoneReason, DEFAULT_SYNC_LIMIT, MAX_PUSH_CHANGES, MAX_SYNC_LIMIT, SYNC_SETTLE_SECONDS,
+};
+use crate::spam::{IntakeSignals, SpamClassifier, SpamSignal, SpamVerdict, TelemetrySpamClassifier};
+use crate::wallboard::{QueueSnapshot, AGENT_ONLINE_WINDOW_MINUTES};
+use crate::viewers::{TicketViewer, TYPING_TIMEOUT_SECONDS, VIEWER_TIMEOUT_SECONDS};
+use crate::kb::{suggestion_limit, ArticleSuggestion, CreateKnowledgeArticleInput, KnowledgeArticle, UpdateKnowledgeArticleInput};
+use crate::locale::ProductLocales;
+use crate::merge::{MergePreview, MergeSettings, MergedDuplicate, TicketMerge, MAX_MERGE_DUPLICATES};
+use crate::maintenance::{CreateMaintenanceWindowInput, MaintenanceWindow};
+use crate::node::{Node, NodeType};
+use crate::reopen::{ClosedTicketReplies, ClosedTicketReply};
+use crate::products::{ProductConfig, ProductConfigInput, MAX_PRODUCT_KEY_LENGTH};
+use crate::rate_limit::RateLimits;
+use crate::projection::TicketProjection;
+use crate::validation;
+use crate::staffing::{
+    agent_utilization, simulate_pickups, summarize, ShiftPattern, ShiftSpan, SimulatedArrival, StaffingSimulation,
+    MAX_STAFFING_SIMULATION_DAYS, STAFFING_DRAIN_DAYS,
+};
+use crate::workload::{AgentWorkload, WorkloadWeights};
+use crate::mentions::{extract_mentions, MessageMention, MAX_AGENT_MENTIONS};
+use crate::resolution::{
+    normalize_resolution_code, CrmCoreResolutionCodeMetrics, KbArticleCandidate, ResolutionCode, ResolutionCodeInput,
+    DEFAULT_KB_CANDIDATE_MIN_TICKETS, KB_CANDIDATE_SAMPLE_TICKETS,
+};
+use crate::reports::{
+    iso_week_start, CreateReportScheduleInput, ReportFrequency, ReportRun, ReportSchedule, SlaBreach, SlaBreachDigest, SupportReport,
+    UpdateReportScheduleInput, MAX_REPORT_RUNS,
+};
+use crate::routing::{RoutingCandidate, RoutingDecision};
+use crate::numbering::{TicketNumberFormat, TicketNumbering};
+use crate::pagination::{PageInfo, TicketConnection, TicketCursor, TicketEdge};
+use crate::sla::{SlaClock, SlaClockTarget, SlaPause, SlaPauseReason, SlaPolicy, SlaTargetSource};
+use crate::templates::{render_template, template_placeholders, TemplateVariable};
+use crate::timeout::{OperationClass, QueryTimeouts};
+use crate::translation::{same_language, TranslationProvider, TranslationStatus};
+use crate::warehouse::{WarehouseBatch, WarehouseExtract, Watermark, WAREHOUSE_SETTLE_SECONDS};
+use crate::idempotency::{normalize_idempotency_key, request_fingerprint};
+use crate::import::{CsatImport, CsatImportReport, CsvImport, ExternalCsatResponse, ImportMode, ImportReport, ImportRowError, UnmatchedCsatRecord, CSAT_MATCH_WINDOW_DAYS};
 use crate::models::{
-    SupportTicket, TicketMessage, CreateTicketInput, UpdateTicketInput, AddTicketMessageInput,
-    TicketFilter, CrmCoreSupportDashboardMetrics, CrmCoreSupportOverviewMetrics, CrmCoreTicketStatusCount,
-    CrmCoreTicketPriorityCount, CrmCoreSlaMetrics, CrmCoreResponseMetrics, CrmCoreAgentPerformance, CrmCoreTicketTrend,
+    SupportTicket, TicketMessage, MessageContentFormat, MessageVisibility, TicketStatus, TicketPriority, TicketChannel, CreateTicketInput, UpdateTicketInput, AddTicketMessageInput,
+    TicketFilter, TicketSearchResult, PropagationPolicy, TicketAuditEntry, SupportAgent, UpsertSupportAgentInput,
+    SlaTarget, SetSlaTargetInput, CannedResponse, CannedResponseScope, CreateCannedResponseInput, UpdateCannedResponseInput,
+    MessageReaction, TicketMessageReaction, TicketView, AttachmentAccess, TicketLastSeen, OutboundEmail, QuickStats, PriorityOpenCount,
+    AbuseIncident, AbuseBlocklistCandidate, SavedView, SavedViewCount, SaveViewInput, TicketSortOrder, UpdateSavedViewInput, TicketRead, CustomerCategoryCount, CustomerContext, CustomerContextTicket, CustomerJourneyEvent, CustomerSupportSummary, KnownIssueSubscription, CrmCoreSupportDashboardMetrics, CrmCoreSupportOverviewMetrics, CrmCoreMetricsComparison, CrmCoreOverviewComparison, CrmCoreSlaComparison, CrmCoreResponseComparison, MetricsPeriod, CrmCoreTicketStatusCount,
+    CrmCoreTicketPriorityCount, CrmCoreSlaBreakdown, CrmCoreSlaMetrics, CrmCoreResponseMetrics, CrmCoreAgentPerformance, CrmCoreTicketTrend, CrmCoreCsatTrends, CrmCoreCsatTrendPoint,
+    CrmCoreChannelMetrics, CrmCoreTopCustomer, CrmCoreIntakeTelemetryReport, CrmCoreAppVersionMetrics, CrmCoreIntakeSignalCount, CrmCoreStatusFunnel, CrmCoreStatusFunnelStage, CrmCoreStatusTransition, SYSTEM_AUTHOR_ID,
+    AssignmentRule, CreateAssignmentRuleInput,
 };
 
-pub struct SupportRepository {
-    pool: PgPool,
-}
+const UPDATE_TICKET_SQL: &str = r#"
+    UPDATE support_tickets SET
+        subject = COALESCE($2, subject),
+        description = COALESCE($3, description),
+        status = COALESCE($4, status),
+        priority = COALESCE($5, priority),
+        category = COALESCE($6, category),
+        assigned_to = COALESCE($7, assigned_to),
+        resolution_code = COALESCE($10, resolution_code),
+        resolved_at = CASE
+            WHEN $4 = 'RESOLVED' AND status <> 'RESOLVED' AND resolved_at IS NULL THEN $8
+            ELSE resolved_at
+        END,
+        closed_at = CASE
+            WHEN $4 = 'CLOSED' AND status <> 'CLOSED' AND closed_at IS NULL THEN $8
+            ELSE closed_at
+        END,
+        -- Resolution (entering RESOLVED, or CLOSED without being resolved) credits the
+        -- actor, else the author of the final public agent reply, else the assignee
+        resolving_message_id = CASE
+            WHEN ($4 = 'RESOLVED' AND status <> 'RESOLVED') OR ($4 = 'CLOSED' AND status NOT IN ('RESOLVED', 'CLOSED'))
+                THEN (
+                    SELECT m.id FROM ticket_messages m
+                    WHERE m.ticket_id = $1 AND m.is_internal = FALSE AND m.author_id <> support_tickets.customer_id
+                      AND m.author_id <> '00000000-0000-0000-0000-000000000000'
+                    ORDER BY m.created_at DESC LIMIT 1
+                )
+            ELSE resolving_message_id
+        END,
+        resolving_agent_id = CASE
+            WHEN ($4 = 'RESOLVED' AND status <> 'RESOLVED') OR ($4 = 'CLOSED' AND status NOT IN ('RESOLVED', 'CLOSED'))
+                THEN COALESCE($9, (
+                    SELECT m.author_id FROM ticket_messages m
+                    WHERE m.ticket_id = $1 AND m.is_internal = FALSE AND m.author_id <> support_tickets.customer_id
+                      AND m.author_id <> '00000000-0000-0000-0000-000000000000'
+                    ORDER BY m.created_at DESC LIMIT 1
+                ), $7, assigned_to)
+            ELSE resolving_agent_id
+        END,
+        waiting_since = CASE
+            WHEN $4 = 'WAITING_ON_CUSTOMER' AND status <> 'WAITING_ON_CUSTOMER' THEN $8
+            WHEN $4 <> 'WAITING_ON_CUSTOMER' THEN NULL
+            ELSE waiting_since
+        END,
+        customer_wait_seconds = CASE
+            WHEN $4 <> 'WAITING_ON_CUSTOMER' AND waiting_since IS NOT NULL
+                THEN customer_wait_seconds + EXTRACT(EPOCH FROM ($8 - waiting_since))::BIGINT
+            ELSE customer_wait_seconds
+        END,
+        updated_at = $8
+    WHERE id = $1 AND deleted_at IS NULL
+    RETURNING *
+"#;
+
+/// Ticket merges with their duplicates; callers append the `WHERE` clause
+const TICKET_MERGE_SELECT: &str = r#"
+    SELECT m.id, m.product, m.primary_ticket_id,
+           ARRAY(SELECT d.ticket_id FROM ticket_merge_duplicates d WHERE d.merge_id = m.id ORDER BY d.ticket_id) AS duplicate_ticket_ids,
+           m.merged_by, m.merged_at, m.undo_until, m.undone_by, m.undone_at
+    FROM ticket_merges m
+"#;
+
+/// Per-ticket grace window for the SLA policy: $3 plans, $4 grace seconds, $5 default grace
+const SLA_GRACE_JOIN: &str = r#"
+    LEFT JOIN UNNEST($3::TEXT[], $4::BIGINT[]) AS g(plan, grace_seconds)
+        ON g.plan = st.customer_plan
+"#;
+
+/// Per-ticket SLA targets in seconds: the product/priority row in `sla_targets`,
+/// else the policy's $1 first response and $2 resolution targets
+const SLA_TARGET_JOIN: &str = r#"
+    LEFT JOIN sla_targets tgt ON tgt.product = st.product AND tgt.priority = st.priority
+"#;
+
+const SLA_TARGET_COLUMNS: &str = r#"
+    COALESCE(tgt.first_response_minutes::BIGINT * 60, $1::BIGINT) as first_response_seconds,
+    COALESCE(tgt.resolution_minutes::BIGINT * 60, $2::BIGINT) as resolution_seconds
+"#;
+
+/// Whether ticket `st` breaches its `graced` targets plus `graced.grace_seconds`:
+/// $6 now, $7 pause while waiting. Maintenance windows never count, and the
+/// first-response clock only runs within the product's business calendar, so
+/// it is credited only the maintenance that falls inside business hours.
+const SLA_BREACHED_EXPR: &str = r#"
+    (
+        (graced.first_response_seconds IS NOT NULL
+            AND support_product_business_seconds(st.product, st.created_at, COALESCE(st.first_response_at, $6))
+                - support_product_business_maintenance_seconds(st.product, st.created_at, COALESCE(st.first_response_at, $6))
+                > graced.first_response_seconds + graced.grace_seconds)
+        OR (graced.resolution_seconds IS NOT NULL
+            AND EXTRACT(EPOCH FROM (COALESCE(st.resolved_at, $6) - st.created_at))
+                - support_maintenance_seconds(st.product, st.created_at, COALESCE(st.resolved_at, $6))
+                - CASE WHEN $7 THEN st.customer_wait_seconds + COALESCE(EXTRACT(EPOCH FROM ($6 - st.waiting_since)), 0) ELSE 0 END
+                > graced.resolution_seconds + graced.grace_seconds)
+    )
+"#;
+
+/// One row per (ticket, status occupied) for tickets created in the period.
+///
+/// Binds: $1 product, $2 period start, $3 period end, $5 organization filter.
+/// Callers bind `$4` as "now".
+const STATUS_SPANS_CTE: &str = r#"
+    WITH tickets AS (
+        SELECT id, created_at
+        FROM support_tickets
+        WHERE product = $1
+          AND deleted_at IS NULL
+          AND NOT test_mode
+          AND ($5::UUID IS NULL OR organization_id = $5)
+          AND created_at BETWEEN $2 AND $3
+    ),
+    events AS (
+        SELECT t.id as ticket_id, 'NEW'::TEXT as status, t.created_at as entered_at
+        FROM tickets t
+        UNION ALL
+        SELECT a.ticket_id, a.new_value as status, a.created_at as entered_at
+        FROM ticket_audit_entries a
+        JOIN tickets t ON t.id = a.ticket_id
+        WHERE a.field = 'status'
+    ),
+    spans AS (
+        SELECT
+            ticket_id,
+            status,
+            entered_at,
+            LEAD(status) OVER w as next_status,
+            LEAD(entered_at) OVER w as left_at
+        FROM events
+        WINDOW w AS (PARTITION BY ticket_id ORDER BY entered_at)
+    )
+"#;
+
+/// What is logged about a screened intake submission
+struct IntakeSubmission {
+    telemetry: Option<IntakeTelemetry>,
+    ip_hash: Option<String>,
+}
+
+/// SMTP id as stored: trimmed, without the surrounding angle brackets
+fn normalize_smtp_id(id: &str) -> String {
+    id.trim().trim_start_matches('<').trim_end_matches('>').trim().to_string()
+}
+
+#[derive(FromRow)]
+struct CustomerContextRow {
+    total_tickets: i64,
+    open_tickets: i64,
+    avg_csat_score: Option<f64>,
+    recent_tickets: Json<Vec<CustomerContextTicket>>,
+    known_issues: Json<Vec<KnownIssueSubscription>>,
+}
+
+#[derive(FromRow)]
+struct CustomerSupportSummaryRow {
+    total_tickets: i64,
+    open_tickets: i64,
+    avg_csat_score: Option<f64>,
+    last_contact_at: Option<DateTime<Utc>>,
+    common_categories: Json<Vec<CustomerCategoryCount>>,
+}
+
+#[derive(FromRow)]
+struct SlaBreakdownRow {
+    by_priority: bool,
+    #[sqlx(flatten)]
+    breakdown: CrmCoreSlaBreakdown,
+}
+
+#[derive(FromRow)]
+struct ContractTicketRow {
+    id: Uuid,
+    ticket_ref: String,
+    priority: TicketPriority,
+    created_at: DateTime<Utc>,
+    first_response_at: Option<DateTime<Utc>>,
+    resolved_at: Option<DateTime<Utc>>,
+    first_response_business_seconds: i64,
+    resolution_business_seconds: i64,
+}
+
+/// What to do once a transaction commits: events to publish and automations to run
+#[derive(Default)]
+struct AfterCommit {
+    events: Vec<SupportEvent>,
+    automations: Vec<AutomationFiring>,
+}
+
+impl AfterCommit {
+    fn append(&mut self, other: &mut AfterCommit) {
+        self.events.append(&mut other.events);
+        self.automations.append(&mut other.automations);
+    }
+}
+
+/// Support writes inside one Postgres transaction, composable with the host
+/// service's own writes
+///
+/// Obtained from [`SupportRepository::begin`]. Ticket creation, updates
+/// (including status changes), reopening, messages and CSAT ratings behave as
+/// their repository counterparts, except that lifecycle events and
+/// automations wait for [`commit`](Self::commit): nothing is published for a
+/// transaction that rolls back. Run domain writes on
+/// [`connection`](Self::connection):
+///
+/// ```rust,no_run
+/// use pleme_support::{CreateTicketInput, SupportRepository};
+/// use uuid::Uuid;
+///
+/// # async fn example(repo: SupportRepository, order_id: Uuid, input: CreateTicketInput) -> pleme_support::Result<()> {
+/// let mut tx = repo.begin().await?;
+/// let ticket = tx.create_ticket("novaskyn", &input).await?;
+/// sqlx::query("INSERT INTO refunds (order_id, ticket_id) VALUES ($1, $2)")
+///     .bind(order_id)
+///     .bind(ticket.id)
+///     .execute(tx.connection())
+///     .await?;
+/// tx.commit().await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Dropping the handle without committing rolls everything back. Intake
+/// screening does not apply: tickets are created as by
+/// [`SupportRepository::create_ticket`]. Only the methods on this handle take
+/// part; every other repository operation, called on the repository, runs in
+/// its own transaction.
+pub struct SupportTransaction<'r> {
+    repo: &'r SupportRepository,
+    tx: Transaction<'static, Postgres>,
+    after: AfterCommit,
+}
+
+impl SupportTransaction<'_> {
+    /// The underlying connection, for the host service's own queries
+    pub fn connection(&mut self) -> &mut PgConnection {
+        &mut self.tx
+    }
+
+    /// Create a ticket; see [`SupportRepository::create_ticket`]
+    ///
+    /// Burst collapsing and classification are skipped; omitted priorities
+    /// take the product's default. A concurrent request with the same
+    /// idempotency key fails with `DuplicateRequest`; roll back then.
+    pub async fn create_ticket(&mut self, product: &str, input: &CreateTicketInput) -> Result<SupportTicket> {
+        let now = self.repo.now();
+        let config = SupportRepository::intake_product_config(&mut *self.tx, product, input).await?;
+        let idempotency = match &input.idempotency_key {
+            Some(key) => {
+                let key = normalize_idempotency_key(key)?;
+                let fingerprint = request_fingerprint(input);
+                if let Some(ticket) =
+                    SupportRepository::idempotent_ticket(&mut self.tx, product, input.customer_id, &key, &fingerprint).await?
+                {
+                    return Ok(ticket);
+                }
+                Some((key, fingerprint))
+            }
+            None => None,
+        };
+        let metadata = SupportRepository::client_metadata(input)?;
+        let input = CreateTicketInput { priority: input.priority.or(Some(config.default_priority)), ..input.clone() };
+        let ticket = self
+            .repo
+            .create_ticket_in_tx(&mut self.tx, product, &input, serde_json::Value::Object(metadata), None, now, &mut self.after)
+            .await?;
+        if let Some((key, fingerprint)) = &idempotency {
+            if !SupportRepository::claim_idempotency_key(&mut self.tx, product, input.customer_id, ticket.id, key, fingerprint, now).await? {
+                return Err(SupportError::DuplicateRequest(format!(
+                    "Idempotency key {} was used by a concurrent request",
+                    key
+                )));
+            }
+        }
+        Ok(ticket)
+    }
+
+    /// Update a ticket; see [`SupportRepository::update_ticket_with_propagation`]
+    pub async fn update_ticket(
+        &mut self,
+        ticket_id: Uuid,
+        actor_id: Option<Uuid>,
+        input: &UpdateTicketInput,
+        policy: PropagationPolicy,
+    ) -> Result<SupportTicket> {
+        let now = self.repo.now();
+        self.repo
+            .update_ticket_in_tx(&mut self.tx, ticket_id, actor_id, input, policy, now, &mut self.after)
+            .await
+    }
+
+    /// Add a message; see [`SupportRepository::add_message`]
+    ///
+    /// A rejected closed-ticket reply leaves its audit entry in the
+    /// transaction; commit to keep it.
+    pub async fn add_message(&mut self, author_id: Uuid, input: &AddTicketMessageInput) -> Result<TicketMessage> {
+        let now = self.repo.now();
+        self.repo
+            .add_message_in_tx(&mut self.tx, author_id, input, now, &mut self.after)
+            .await
+    }
+
+    /// Reopen a resolved or closed ticket; see [`SupportRepository::reopen_ticket`]
+    pub async fn reopen_ticket(&mut self, ticket_id: Uuid, actor_id: Option<Uuid>) -> Result<SupportTicket> {
+        let now = self.repo.now();
+        self.repo
+            .reopen_ticket_in_tx(&mut self.tx, ticket_id, actor_id, now, &mut self.after)
+            .await
+    }
+
+    /// Record a customer's rating; see [`SupportRepository::submit_csat_feedback`]
+    pub async fn submit_csat_feedback(
+        &mut self,
+        ticket_id: Uuid,
+        customer_id: Uuid,
+        input: &CsatFeedbackInput,
+    ) -> Result<CsatSurvey> {
+        let now = self.repo.now();
+        SupportRepository::submit_csat_feedback_in_tx(&mut self.tx, ticket_id, customer_id, input, now).await
+    }
+
+    /// Get a ticket as this transaction sees it
+    pub async fn find_by_id(&mut self, ticket_id: Uuid) -> Result<SupportTicket> {
+        sqlx::query_as::<_, SupportTicket>("SELECT * FROM support_tickets WHERE id = $1 AND deleted_at IS NULL")
+            .bind(ticket_id)
+            .fetch_optional(&mut *self.tx)
+            .await?
+            .ok_or(SupportError::TicketNotFound(ticket_id))
+    }
+
+    /// Commit, then publish the transaction's events and run its automations
+    ///
+    /// Tickets returned before the commit don't reflect changes automations
+    /// make afterwards; reload them if that matters.
+    pub async fn commit(self) -> Result<()> {
+        self.tx.commit().await?;
+        self.repo.after_commit(self.after).await;
+        Ok(())
+    }
+
+    /// Roll back every write made through the handle
+    pub async fn rollback(self) -> Result<()> {
+        self.tx.rollback().await?;
+        Ok(())
+    }
+}
+
+pub struct SupportRepository {
+    pool: PgPool,
+    clock: Arc<dyn Clock>,
+    sla_policy: SlaPolicy,
+    sla_contracts: SlaContracts,
+    csat_surveys: CsatSurveySettings,
+    issue_trackers: IssueTrackers,
+    dead_letter_handlers: DeadLetterHandlers,
+    intake_privacy: IntakePrivacy,
+    numbering: TicketNumbering,
+    locales: ProductLocales,
+    moderator: Arc<dyn ContentModerator>,
+    text_extractors: TextExtractors,
+    scanner_hook: Option<Arc<dyn ScannerHook>>,
+    sentiment_analyzer: Arc<dyn SentimentAnalyzer>,
+    sentiment_rules: SentimentRules,
+    spam_classifier: Arc<dyn SpamClassifier>,
+    rate_limits: RateLimits,
+    workload_weights: WorkloadWeights,
+    ticket_classifier: Option<Arc<dyn TicketClassifier>>,
+    customer_directory: Option<Arc<dyn CustomerDirectory>>,
+    handling_costs: HandlingCosts,
+    alert_thresholds: AlertThresholds,
+    alert_sink: Arc<dyn AlertSink>,
+    notification_sink: Arc<dyn NotificationSink>,
+    closed_ticket_replies: ClosedTicketReplies,
+    burst_collapsing: BurstCollapsing,
+    merge_settings: MergeSettings,
+    query_timeouts: QueryTimeouts,
+    translation_provider: Option<Arc<dyn TranslationProvider>>,
+    event_publisher: Option<Arc<dyn SupportEventPublisher>>,
+    read_only: AtomicBool,
+}
+
+impl SupportRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self::with_clock(pool, Arc::new(SystemClock))
+    }
+
+    /// Create a repository whose business timestamps come from `clock`
+    pub fn with_clock(pool: PgPool, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            pool,
+            clock,
+            sla_policy: SlaPolicy::default(),
+            sla_contracts: SlaContracts::default(),
+            csat_surveys: CsatSurveySettings::default(),
+            issue_trackers: IssueTrackers::default(),
+            dead_letter_handlers: DeadLetterHandlers::default(),
+            intake_privacy: IntakePrivacy::default(),
+            numbering: TicketNumbering::default(),
+            locales: ProductLocales::default(),
+            moderator: Arc::new(WordlistModerator::default()),
+            text_extractors: TextExtractors::default(),
+            scanner_hook: None,
+            sentiment_analyzer: Arc::new(LexiconSentimentAnalyzer::default()),
+            sentiment_rules: SentimentRules::default(),
+            spam_classifier: Arc::new(TelemetrySpamClassifier::default()),
+            rate_limits: RateLimits::default(),
+            workload_weights: WorkloadWeights::default(),
+            ticket_classifier: None,
+            customer_directory: None,
+            handling_costs: HandlingCosts::default(),
+            alert_thresholds: AlertThresholds::default(),
+            alert_sink: Arc::new(LogAlertSink),
+            notification_sink: Arc::new(LogNotificationSink),
+            closed_ticket_replies: ClosedTicketReplies::default(),
+            burst_collapsing: BurstCollapsing::default(),
+            merge_settings: MergeSettings::default(),
+            query_timeouts: QueryTimeouts::default(),
+            translation_provider: None,
+            event_publisher: None,
+            read_only: AtomicBool::new(false),
+        }
+    }
+
+    /// Use `policy` for SLA clock calculations in analytics
+    pub fn with_sla_policy(mut self, policy: SlaPolicy) -> Self {
+        self.sla_policy = policy;
+        self
+    }
+
+    pub fn sla_policy(&self) -> &SlaPolicy {
+        &self.sla_policy
+    }
+
+    /// Use `contracts` for per-account contractual SLA reports
+    pub fn with_sla_contracts(mut self, contracts: SlaContracts) -> Self {
+        self.sla_contracts = contracts;
+        self
+    }
+
+    pub fn sla_contracts(&self) -> &SlaContracts {
+        &self.sla_contracts
+    }
+
+    /// Use `settings` for survey link URLs and expiry
+    pub fn with_csat_survey_settings(mut self, settings: CsatSurveySettings) -> Self {
+        self.csat_surveys = settings;
+        self
+    }
+
+    pub fn csat_survey_settings(&self) -> &CsatSurveySettings {
+        &self.csat_surveys
+    }
+
+    /// Use `trackers` to escalate tickets to engineering
+    pub fn with_issue_trackers(mut self, trackers: IssueTrackers) -> Self {
+        self.issue_trackers = trackers;
+        self
+    }
+
+    /// Publish ticket lifecycle events to `publisher`; see [`crate::events`]
+    pub fn with_event_publisher(mut self, publisher: Arc<dyn SupportEventPublisher>) -> Self {
+        self.event_publisher = Some(publisher);
+        self
+    }
+
+    /// Use `handlers` to redeliver retried dead letters
+    pub fn with_dead_letter_handlers(mut self, handlers: DeadLetterHandlers) -> Self {
+        self.dead_letter_handlers = handlers;
+        self
+    }
+
+    /// Use `numbering` for per-product ticket reference formats
+    pub fn with_ticket_numbering(mut self, numbering: TicketNumbering) -> Self {
+        self.numbering = numbering;
+        self
+    }
+
+    pub fn ticket_numbering(&self) -> &TicketNumbering {
+        &self.numbering
+    }
+
+    /// Use `costs` to estimate handling cost in customer reports
+    pub fn with_handling_costs(mut self, costs: HandlingCosts) -> Self {
+        self.handling_costs = costs;
+        self
+    }
+
+    /// Use `thresholds` when evaluating metric alerts
+    pub fn with_alert_thresholds(mut self, thresholds: AlertThresholds) -> Self {
+        self.alert_thresholds = thresholds;
+        self
+    }
+
+    pub fn alert_thresholds(&self) -> &AlertThresholds {
+        &self.alert_thresholds
+    }
+
+    /// Publish metric alerts to `sink` instead of the log
+    pub fn with_alert_sink(mut self, sink: Arc<dyn AlertSink>) -> Self {
+        self.alert_sink = sink;
+        self
+    }
+
+    /// Send saved view notifications to `sink` instead of the log
+    pub fn with_notification_sink(mut self, sink: Arc<dyn NotificationSink>) -> Self {
+        self.notification_sink = sink;
+        self
+    }
+
+    /// Use `replies` to decide what customer replies to closed tickets do
+    pub fn with_closed_ticket_replies(mut self, replies: ClosedTicketReplies) -> Self {
+        self.closed_ticket_replies = replies;
+        self
+    }
+
+    pub fn closed_ticket_replies(&self) -> &ClosedTicketReplies {
+        &self.closed_ticket_replies
+    }
+
+    /// Collapse duplicate submission bursts at intake per `collapsing`
+    pub fn with_burst_collapsing(mut self, collapsing: BurstCollapsing) -> Self {
+        self.burst_collapsing = collapsing;
+        self
+    }
+
+    pub fn burst_collapsing(&self) -> &BurstCollapsing {
+        &self.burst_collapsing
+    }
+
+    /// Use `settings` for ticket merges, e.g. how long they can be undone
+    pub fn with_merge_settings(mut self, settings: MergeSettings) -> Self {
+        self.merge_settings = settings;
+        self
+    }
+
+    pub fn merge_settings(&self) -> &MergeSettings {
+        &self.merge_settings
+    }
+
+    /// Cancel statements that run longer than `timeouts` allows for their class
+    pub fn with_query_timeouts(mut self, timeouts: QueryTimeouts) -> Self {
+        self.query_timeouts = timeouts;
+        self
+    }
+
+    pub fn query_timeouts(&self) -> &QueryTimeouts {
+        &self.query_timeouts
+    }
+
+    /// Transaction whose statements are cancelled after the class's timeout
+    ///
+    /// The timeout is set with `SET LOCAL` semantics, so it ends with the
+    /// transaction. Dropping the transaction mid-query (a cancelled request)
+    /// rolls it back before the connection is reused.
+    async fn begin_timed(&self, class: OperationClass) -> Result<Transaction<'static, Postgres>> {
+        let mut tx = self.pool.begin().await?;
+        if let Some(timeout) = self.query_timeouts.timeout_for(class) {
+            sqlx::query("SELECT set_config('statement_timeout', $1, TRUE)")
+                .bind(format!("{}ms", timeout.as_millis().max(1)))
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        Ok(tx)
+    }
+
+    /// Use `locales` for per-product analytics formatting
+    pub fn with_product_locales(mut self, locales: ProductLocales) -> Self {
+        self.locales = locales;
+        self
+    }
+
+    pub fn product_locales(&self) -> &ProductLocales {
+        &self.locales
+    }
+
+    /// Use `moderator` to screen inbound customer messages
+    pub fn with_content_moderator(mut self, moderator: Arc<dyn ContentModerator>) -> Self {
+        self.moderator = moderator;
+        self
+    }
+
+    /// Use `extractors` to read attachments for search
+    pub fn with_text_extractors(mut self, extractors: TextExtractors) -> Self {
+        self.text_extractors = extractors;
+        self
+    }
+
+    /// Scan registered attachments with `scanner` before they become available
+    pub fn with_scanner_hook(mut self, scanner: Arc<dyn ScannerHook>) -> Self {
+        self.scanner_hook = Some(scanner);
+        self
+    }
+
+    /// Use `analyzer` to score the sentiment of inbound customer messages
+    pub fn with_sentiment_analyzer(mut self, analyzer: Arc<dyn SentimentAnalyzer>) -> Self {
+        self.sentiment_analyzer = analyzer;
+        self
+    }
+
+    /// Fire `SENTIMENT_DROPPED` automations on runs of negative customer messages
+    pub fn with_sentiment_rules(mut self, rules: SentimentRules) -> Self {
+        self.sentiment_rules = rules;
+        self
+    }
+
+    pub fn sentiment_rules(&self) -> &SentimentRules {
+        &self.sentiment_rules
+    }
+
+    /// Use `classifier` to screen customer-facing intake
+    pub fn with_spam_classifier(mut self, classifier: Arc<dyn SpamClassifier>) -> Self {
+        self.spam_classifier = classifier;
+        self
+    }
+
+    /// Cap how many tickets a customer may open per product through customer-facing intake
+    pub fn with_rate_limits(mut self, limits: RateLimits) -> Self {
+        self.rate_limits = limits;
+        self
+    }
+
+    pub fn rate_limits(&self) -> &RateLimits {
+        &self.rate_limits
+    }
+
+    /// Weigh open tickets by priority with `weights` when balancing and reporting agent workload
+    pub fn with_workload_weights(mut self, weights: WorkloadWeights) -> Self {
+        self.workload_weights = weights;
+        self
+    }
+
+    pub fn workload_weights(&self) -> &WorkloadWeights {
+        &self.workload_weights
+    }
+
+    /// Use `directory` to attach customer names, emails and plans to analytics
+    pub fn with_customer_directory(mut self, directory: Arc<dyn CustomerDirectory>) -> Self {
+        self.customer_directory = Some(directory);
+        self
+    }
+
+    pub fn customer_directory(&self) -> Option<&Arc<dyn CustomerDirectory>> {
+        self.customer_directory.as_ref()
+    }
+
+    /// Use `provider` to translate canned responses into the ticket's language
+    pub fn with_translation_provider(mut self, provider: Arc<dyn TranslationProvider>) -> Self {
+        self.translation_provider = Some(provider);
+        self
+    }
+
+    pub fn translation_provider(&self) -> Option<&Arc<dyn TranslationProvider>> {
+        self.translation_provider.as_ref()
+    }
+
+    /// Use `classifier` to suggest priority and category for tickets created without them
+    pub fn with_ticket_classifier(mut self, classifier: Arc<dyn TicketClassifier>) -> Self {
+        self.ticket_classifier = Some(classifier);
+        self
+    }
+
+    pub fn ticket_classifier(&self) -> Option<&Arc<dyn TicketClassifier>> {
+        self.ticket_classifier.as_ref()
+    }
+
+    /// Customer info for the given ids, empty without a customer directory
+    pub async fn lookup_customers(&self, customer_ids: &[Uuid]) -> Result<HashMap<Uuid, CustomerInfo>> {
+        match &self.customer_directory {
+            Some(directory) if !customer_ids.is_empty() => directory.lookup(customer_ids).await,
+            _ => Ok(HashMap::new()),
+        }
+    }
+
+    /// Use `privacy` to decide what submitter info is stored at intake
+    pub fn with_intake_privacy(mut self, privacy: IntakePrivacy) -> Self {
+        self.intake_privacy = privacy;
+        self
+    }
+
+    /// Start in read-only mode, e.g. while the primary database fails over
+    pub fn with_read_only(self, read_only: bool) -> Self {
+        self.read_only.store(read_only, Ordering::Relaxed);
+        self
+    }
+
+    /// Switch read-only mode at runtime
+    ///
+    /// While read-only, every operation that writes returns
+    /// [`SupportError::ReadOnly`] before touching the database; lookups,
+    /// lists, search and analytics keep working.
+    pub fn set_read_only(&self, read_only: bool) {
+        self.read_only.store(read_only, Ordering::Relaxed);
+        tracing::warn!("Support repository is {}", if read_only { "read-only" } else { "writable again" });
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.load(Ordering::Relaxed)
+    }
+
+    fn ensure_writable(&self) -> Result<()> {
+        if self.is_read_only() {
+            return Err(SupportError::ReadOnly);
+        }
+        Ok(())
+    }
+
+    /// Current time according to the repository's clock
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    /// Start a transaction that support writes and the caller's own writes share
+    pub async fn begin(&self) -> Result<SupportTransaction<'_>> {
+        self.ensure_writable()?;
+        let tx = self.pool.begin().await?;
+        Ok(SupportTransaction { repo: self, tx, after: AfterCommit::default() })
+    }
+
+    /// Create a new support ticket
+    pub async fn create_ticket(&self, product: &str, input: &CreateTicketInput) -> Result<SupportTicket> {
+        self.create_ticket_with_submitter(product, input, None).await
+    }
+
+    /// Create a ticket from a customer-facing intake path, capturing submitter info
+    ///
+    /// What is stored (raw or hashed IP, user agent, geo) follows the
+    /// repository's `IntakePrivacy` settings. A submission repeating an open
+    /// ticket under the product's [`BurstRule`](crate::BurstRule) is collapsed
+    /// into it, and that ticket is returned. An omitted priority or category
+    /// is suggested by the registered [`TicketClassifier`]; a priority it
+    /// leaves unset takes the product's default.
+    ///
+    /// A retry carrying the `idempotency_key` of an earlier request returns
+    /// that request's ticket without being screened again (see
+    /// [`idempotency`](crate::idempotency)).
+    ///
+    /// Fails with `UnknownProduct` unless the product is registered (see
+    /// [`products`](crate::products)), with `Validation` when the product
+    /// does not accept tickets on the input's channel, and with
+    /// `DuplicateRequest` when the idempotency key was first used for a
+    /// different request. Customer-facing submissions fail with `RateLimited`
+    /// once the customer reaches the product's [`RateLimit`](crate::RateLimit).
+    pub async fn create_ticket_with_submitter(
+        &self,
+        product: &str,
+        input: &CreateTicketInput,
+        submitter: Option<&SubmitterInfo>,
+    ) -> Result<SupportTicket> {
+        self.create_ticket_claiming(product, input, submitter, None)
+            .await?
+            .ok_or_else(|| SupportError::Internal("Ticket creation claimed no email".to_string()))
+    }
+
+    /// [`Self::create_ticket_with_submitter`], first claiming `email`'s
+    /// Message-ID in the creating transaction
+    ///
+    /// Returns `None`, creating nothing, when the Message-ID was already claimed.
+    async fn create_ticket_claiming(
+        &self,
+        product: &str,
+        input: &CreateTicketInput,
+        submitter: Option<&SubmitterInfo>,
+        email: Option<&InboundEmail>,
+    ) -> Result<Option<SupportTicket>> {
+        self.ensure_writable()?;
+        let config = Self::intake_product_config(&self.pool, product, input).await?;
+        let idempotency = match &input.idempotency_key {
+            Some(key) => {
+                let key = normalize_idempotency_key(key)?;
+                let fingerprint = request_fingerprint(input);
+                let mut conn = self.pool.acquire().await?;
+                if let Some(ticket) = Self::idempotent_ticket(&mut conn, product, input.customer_id, &key, &fingerprint).await? {
+                    return Ok(Some(ticket));
+                }
+                Some((key, fingerprint))
+            }
+            None => None,
+        };
+        let now = self.now();
+        let mut metadata = Self::client_metadata(input)?;
+        if let Some(captured) = submitter.and_then(|info| self.intake_privacy.apply(info, now)) {
+            let value = serde_json::to_value(captured)
+                .map_err(|e| SupportError::Internal(format!("Failed to encode submitter info: {}", e)))?;
+            metadata.insert(SUBMITTER_METADATA_KEY.to_string(), value);
+        }
+        if let Some(telemetry) = submitter.and_then(|info| info.telemetry.as_ref()) {
+            let value = serde_json::to_value(telemetry)
+                .map_err(|e| SupportError::Internal(format!("Failed to encode intake telemetry: {}", e)))?;
+            metadata.insert(INTAKE_TELEMETRY_METADATA_KEY.to_string(), value);
+        }
+
+        // Only customer-facing intake (with submitter info) is screened for spam;
+        // test tickets stay out of the intake report
+        let screening = match submitter {
+            Some(info) if !input.test_mode => Some(self.screen_submission(product, input, info, now).await?),
+            _ => None,
+        };
+
+        if let Some((submission, verdict)) = &screening {
+            if !verdict.accepted {
+                self.log_intake_submission(product, None, submission, verdict, now).await?;
+                tracing::info!("Rejected {} intake submission: {:?}", product, verdict.signals);
+                return Err(SupportError::SubmissionRejected(
+                    verdict.signals.iter().map(SpamSignal::as_str).collect::<Vec<_>>().join(", "),
+                ));
+            }
+        }
+
+        // Classified before the transaction, so a slow classifier holds no locks
+        let classified = match self.classify_ticket(&config, input, now).await {
+            Some((classified, record)) => {
+                let value = serde_json::to_value(record)
+                    .map_err(|e| SupportError::Internal(format!("Failed to encode classification: {}", e)))?;
+                metadata.insert(CLASSIFICATION_METADATA_KEY.to_string(), value);
+                Some(classified)
+            }
+            None => None,
+        };
+        let input = classified.as_ref().unwrap_or(input);
+        let defaulted;
+        let input = match input.priority {
+            Some(_) => input,
+            None => {
+                defaulted = CreateTicketInput { priority: Some(config.default_priority), ..input.clone() };
+                &defaulted
+            }
+        };
+
+        let mut tx = self.pool.begin().await?;
+        if let Some(email) = email {
+            if !Self::claim_inbound_email(&mut tx, email, now).await? {
+                tx.rollback().await?;
+                return Ok(None);
+            }
+        }
+        if submitter.is_some() && !input.test_mode {
+            self.check_rate_limit(&mut tx, product, input.customer_id, now).await?;
+        }
+        let mut after = AfterCommit::default();
+        let ticket = match self.collapse_into_burst(&mut tx, product, input, now, &mut after).await? {
+            Some(ticket) => ticket,
+            None => {
+                self.create_ticket_in_tx(&mut tx, product, input, serde_json::Value::Object(metadata), None, now, &mut after)
+                    .await?
+            }
+        };
+        if let Some((key, fingerprint)) = &idempotency {
+            if !Self::claim_idempotency_key(&mut tx, product, input.customer_id, ticket.id, key, fingerprint, now).await? {
+                // A concurrent attempt with the same key committed first; its ticket wins
+                tx.rollback().await?;
+                let mut conn = self.pool.acquire().await?;
+                return Self::idempotent_ticket(&mut conn, product, input.customer_id, key, fingerprint)
+                    .await?
+                    .map(Some)
+                    .ok_or_else(|| SupportError::DuplicateRequest(format!("Idempotency key {} is in use", key)));
+            }
+        }
+        if let Some(email) = email {
+            Self::record_inbound_email(&mut tx, email, ticket.id, None).await?;
+        }
+        tx.commit().await?;
+        let automated = self.after_commit(after).await;
+
+        if let Some((submission, verdict)) = &screening {
+            self.log_intake_submission(product, Some(ticket.id), submission, verdict, now).await?;
+        }
+
+        if automated {
+            return self.find_by_id(ticket.id).await.map(Some);
+        }
+        Ok(Some(ticket))
+    }
+
+    /// Ticket metadata holding the input's normalized client info, if any
+    fn client_metadata(input: &CreateTicketInput) -> Result<serde_json::Map<String, serde_json::Value>> {
+        let mut metadata = serde_json::Map::new();
+        if let Some(client) = input.client.as_ref().map(ClientInfo::normalized).transpose()?.flatten() {
+            let value = serde_json::to_value(client)
+                .map_err(|e| SupportError::Internal(format!("Failed to encode client info: {}", e)))?;
+            metadata.insert(CLIENT_METADATA_KEY.to_string(), value);
+        }
+        Ok(metadata)
+    }
+
+    /// The ticket an earlier request with idempotency key `key` created
+    ///
+    /// Fails with `DuplicateRequest` when that request's fingerprint differs
+    /// from `fingerprint`.
+    async fn idempotent_ticket(
+        conn: &mut PgConnection,
+        product: &str,
+        customer_id: Uuid,
+        key: &str,
+        fingerprint: &str,
+    ) -> Result<Option<SupportTicket>> {
+        let existing: Option<(Uuid, String)> = sqlx::query_as(
+            r#"
+            SELECT ticket_id, request_fingerprint
+            FROM ticket_idempotency_keys
+            WHERE product = $1 AND customer_id = $2 AND idempotency_key = $3
+            "#,
+        )
+        .bind(product)
+        .bind(customer_id)
+        .bind(key)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        let Some((ticket_id, request_fingerprint)) = existing else {
+            return Ok(None);
+        };
+        if request_fingerprint != fingerprint {
+            return Err(SupportError::DuplicateRequest(format!(
+                "Idempotency key {} was already used for a different request (ticket {})",
+                key, ticket_id
+            )));
+        }
+
+        let ticket = sqlx::query_as::<_, SupportTicket>("SELECT * FROM support_tickets WHERE id = $1 AND deleted_at IS NULL")
+            .bind(ticket_id)
+            .fetch_optional(&mut *conn)
+            .await?
+            .ok_or(SupportError::TicketNotFound(ticket_id))?;
+        tracing::info!("Returned {} for retried {} request {}", ticket.ticket_ref, product, key);
+        Ok(Some(ticket))
+    }
+
+    /// Remember that `customer_id`'s idempotency key `key` led to `ticket_id`
+    ///
+    /// Returns false when a concurrent request claimed the key first.
+    async fn claim_idempotency_key(
+        conn: &mut PgConnection,
+        product: &str,
+        customer_id: Uuid,
+        ticket_id: Uuid,
+        key: &str,
+        fingerprint: &str,
+        now: DateTime<Utc>,
+    ) -> Result<bool> {
+        let claimed = sqlx::query(
+            r#"
+            INSERT INTO ticket_idempotency_keys (product, customer_id, idempotency_key, ticket_id, request_fingerprint, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (product, customer_id, idempotency_key) DO NOTHING
+            "#,
+        )
+        .bind(product)
+        .bind(customer_id)
+        .bind(key)
+        .bind(ticket_id)
+        .bind(fingerprint)
+        .bind(now)
+        .execute(conn)
+        .await?;
+
+        Ok(claimed.rows_affected() == 1)
+    }
+
+    /// Fill the priority or category `input` omits from the ticket classifier
+    ///
+    /// Returns the completed input and what was applied, or `None` when there
+    /// is no classifier, nothing was omitted, or classification failed.
+    /// Suggested categories the product does not allow are dropped.
+    async fn classify_ticket(
+        &self,
+        config: &ProductConfig,
+        input: &CreateTicketInput,
+        now: DateTime<Utc>,
+    ) -> Option<(CreateTicketInput, ClassificationRecord)> {
+        let classifier = self.ticket_classifier.as_ref()?;
+        if input.priority.is_some() && input.category.is_some() {
+            return None;
+        }
+        let product = config.product.as_str();
+
+        let suggestion = match classifier.classify(product, input).await {
+            Ok(suggestion) => suggestion,
+            Err(e) => {
+                tracing::warn!("Ticket classifier '{}' failed for {}: {}", classifier.name(), product, e);
+                return None;
+            }
+        };
+        let priority = suggestion.priority.filter(|_| input.priority.is_none());
+        let category = suggestion.category.filter(|c| input.category.is_none() && config.allows_category(c));
+
+        let classified = CreateTicketInput {
+            priority: input.priority.or(priority),
+            category: input.category.clone().or_else(|| category.clone()),
+            ..input.clone()
+        };
+        let record = ClassificationRecord {
+            classifier: classifier.name().to_string(),
+            priority,
+            category,
+            confidence: suggestion.confidence,
+            classified_at: now,
+        };
+        Some((classified, record))
+    }
+
+    /// Count `input` as another occurrence of an open ticket it repeats under the
+    /// product's burst rule, returning that ticket
+    ///
+    /// Holds locks on the customer's and the error signature's bursts until
+    /// `tx` commits, so concurrent repeats collapse into the first ticket
+    /// instead of each opening one. The collapsed ticket's `TicketUpdated`
+    /// event is added to `after`.
+    async fn collapse_into_burst(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        product: &str,
+        input: &CreateTicketInput,
+        now: DateTime<Utc>,
+        after: &mut AfterCommit,
+    ) -> Result<Option<SupportTicket>> {
+        let Some(rule) = self.burst_collapsing.rule_for(product) else {
+            return Ok(None);
+        };
+
+        // Always customer before signature, so two submissions never deadlock
+        let signature = input.error_signature.as_deref().map(str::trim).filter(|s| !s.is_empty());
+        let mut burst_keys = Vec::new();
+        if rule.same_customer {
+            burst_keys.push(format!("ticket_burst:{}:customer:{}", product, input.customer_id));
+        }
+        if let Some(signature) = signature.filter(|_| rule.same_error_signature) {
+            burst_keys.push(format!("ticket_burst:{}:signature:{}", product, signature));
+        }
+        for key in &burst_keys {
+            sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended($1, 0))")
+                .bind(key)
+                .execute(&mut **tx)
+                .await?;
+        }
+
+        let repeated: Option<(Uuid, bool)> = sqlx::query_as(
+            r#"
+            SELECT id, same_customer
+            FROM (
+                SELECT
+                    id,
+                    COALESCE(last_occurred_at, created_at) as last_occurrence,
+                    $9 AND customer_id = $2
+                        AND LOWER(REGEXP_REPLACE(BTRIM(subject), '\s+', ' ', 'g')) = LOWER(REGEXP_REPLACE(BTRIM($3), '\s+', ' ', 'g'))
+                        AND LOWER(REGEXP_REPLACE(BTRIM(description), '\s+', ' ', 'g')) = LOWER(REGEXP_REPLACE(BTRIM($4), '\s+', ' ', 'g'))
+                        as same_customer,
+                    $8 AND error_signature = NULLIF(BTRIM($5), '') as same_signature
+                FROM support_tickets
+                WHERE product = $1
+                  AND deleted_at IS NULL
+                  AND test_mode = $6
+                  AND status NOT IN ('RESOLVED', 'CLOSED')
+                  AND COALESCE(last_occurred_at, created_at) > $7
+            ) candidates
+            WHERE same_customer OR same_signature
+            ORDER BY last_occurrence DESC
+            LIMIT 1
+            FOR UPDATE
+            "#,
+        )
+        .bind(product)
+        .bind(input.customer_id)
+        .bind(&input.subject)
+        .bind(&input.description)
+        .bind(&input.error_signature)
+        .bind(input.test_mode)
+        .bind(now - rule.window)
+        .bind(rule.same_error_signature)
+        .bind(rule.same_customer)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        let Some((ticket_id, same_customer)) = repeated else {
+            return Ok(None);
+        };
+        let reason = if same_customer { CollapseReason::SameCustomer } else { CollapseReason::ErrorSignature };
+
+        let ticket = sqlx::query_as::<_, SupportTicket>(
+            r#"
+            UPDATE support_tickets
+            SET occurrence_count = occurrence_count + 1, last_occurred_at = $2, updated_at = $2
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(ticket_id)
+        .bind(now)
+        .fetch_one(&mut **tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO collapsed_submissions (product, ticket_id, customer_id, reason, created_at)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(product)
+        .bind(ticket_id)
+        .bind(input.customer_id)
+        .bind(reason.as_str())
+        .bind(now)
+        .execute(&mut **tx)
+        .await?;
+
+        tracing::info!(
+            "Collapsed {} submission into {} ({}, {} occurrences)",
+            product, ticket.ticket_ref, reason.as_str(), ticket.occurrence_count
+        );
+        after.events.push(SupportEvent::ticket(SupportEventType::TicketUpdated, &ticket, now));
+        Ok(Some(ticket))
+    }
+
+    /// Insert a ticket with its SLA deadlines, routing and maintenance notes inside `tx`,
+    /// adding the event and automations to run once it commits to `after`
+    ///
+    /// `follow_up_of` links the ticket to the closed ticket it follows up on.
+    #[allow(clippy::too_many_arguments)]
+    async fn create_ticket_in_tx(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        product: &str,
+        input: &CreateTicketInput,
+        metadata: serde_json::Value,
+        follow_up_of: Option<Uuid>,
+        now: DateTime<Utc>,
+        after: &mut AfterCommit,
+    ) -> Result<SupportTicket> {
+        let ticket = Self::insert_ticket(
+            &mut **tx,
+            &self.numbering.format_for(product),
+            product,
+            &self.locales.locale_for(product).locale,
+            input,
+            metadata,
+            now,
+        )
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to create support ticket: {}", e);
+                SupportError::from(e)
+            })?;
+        let ticket = match follow_up_of {
+            Some(original_id) => {
+                sqlx::query_as::<_, SupportTicket>("UPDATE support_tickets SET follow_up_of = $2 WHERE id = $1 RETURNING *")
+                    .bind(ticket.id)
+                    .bind(original_id)
+                    .fetch_one(&mut **tx)
+                    .await?
+            }
+            None => ticket,
+        };
+        let ticket = self.apply_sla(tx, &[ticket.id], now).await?
+            .pop()
+            .unwrap_or(ticket);
+        let ticket = match self.route_ticket(tx, &ticket, now).await? {
+            Some(decision) => decision.ticket,
+            None => ticket,
+        };
+        for window in Self::maintenance_windows_at(&mut **tx, product, now).await? {
+            Self::insert_internal_note(tx, ticket.id, SYSTEM_AUTHOR_ID, &window.ticket_note(), now).await?;
+        }
+
+        after.events.push(SupportEvent::ticket(SupportEventType::TicketCreated, &ticket, now));
+        after.automations.push(AutomationFiring::new(AutomationTrigger::TicketCreated, ticket.id));
+        Ok(ticket)
+    }
+
+    /// The configuration of a product tickets are being created for
+    ///
+    /// Fails with `InvalidFields` when the input does not pass
+    /// [`validation`](crate::validation) or has a category the product does
+    /// not allow, with `UnknownProduct` when the product is not registered,
+    /// and with `Validation` when it does not accept tickets on the input's
+    /// channel.
+    async fn intake_product_config<'e, E: PgExecutor<'e>>(
+        executor: E,
+        product: &str,
+        input: &CreateTicketInput,
+    ) -> Result<ProductConfig> {
+        validation::validate_new_ticket(input)?;
+        let channel = input.channel;
+        let config = sqlx::query_as::<_, ProductConfig>("SELECT * FROM product_configs WHERE product = $1")
+            .bind(product)
+            .fetch_optional(executor)
+            .await?
+            .ok_or_else(|| SupportError::UnknownProduct(product.to_string()))?;
+
+        if !config.accepts_channel(channel) {
+            return Err(SupportError::Validation(format!(
+                "Product {} does not accept tickets through {}",
+                product,
+                channel.as_str()
+            )));
+        }
+        validation::check_category(&config, input.category.as_deref())?;
+
+        Ok(config)
+    }
+
+    /// Fail with `RateLimited` when the customer has used up the product's rate limit
+    ///
+    /// Runs in the creating transaction and holds a lock on the product and
+    /// customer until it commits, so concurrent submissions are counted one
+    /// after the other instead of all passing on the same count.
+    async fn check_rate_limit(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        product: &str,
+        customer_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        let Some(limit) = self.rate_limits.limit_for(product) else {
+            return Ok(());
+        };
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended('ticket_rate_limit:' || $1 || ':' || $2::TEXT, 0))")
+            .bind(product)
+            .bind(customer_id)
+            .execute(&mut **tx)
+            .await?;
+        let recent: Vec<DateTime<Utc>> = sqlx::query_scalar(
+            r#"
+            SELECT created_at FROM support_tickets
+            WHERE product = $1 AND customer_id = $2 AND created_at > $3 AND NOT test_mode
+            ORDER BY created_at DESC
+            LIMIT $4
+            "#,
+        )
+        .bind(product)
+        .bind(customer_id)
+        .bind(now - limit.window)
+        .bind(i64::from(limit.max_tickets))
+        .fetch_all(&mut **tx)
+        .await?;
+
+        match limit.retry_after(&recent, now) {
+            Some(retry_after) => {
+                tracing::info!("Rate limited {} intake for customer {}", product, customer_id);
+                Err(SupportError::RateLimited { retry_after })
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Gather intake signals for a submission and classify it
+    async fn screen_submission(
+        &self,
+        product: &str,
+        input: &CreateTicketInput,
+        info: &SubmitterInfo,
+        now: DateTime<Utc>,
+    ) -> Result<(IntakeSubmission, SpamVerdict)> {
+        let submission = IntakeSubmission {
+            telemetry: info.telemetry.clone(),
+            ip_hash: info.ip.map(|ip| self.intake_privacy.hash_ip_address(ip)),
+        };
+        let fingerprint = submission.telemetry.as_ref().and_then(|t| t.client_fingerprint_hash.as_deref());
+
+        let (recent_fingerprint_submissions, recent_ip_submissions): (i64, i64) = sqlx::query_as(
+            r#"
+            SELECT
+                COUNT(*) FILTER (WHERE client_fingerprint_hash = $2)::BIGINT,
+                COUNT(*) FILTER (WHERE ip_hash = $3)::BIGINT
+            FROM intake_submissions
+            WHERE product = $1
+              AND created_at > $4 - INTERVAL '1 hour'
+              AND (client_fingerprint_hash = $2 OR ip_hash = $3)
+            "#,
+        )
+        .bind(product)
+        .bind(fingerprint)
+        .bind(&submission.ip_hash)
+        .bind(now)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        let signals = IntakeSignals {
+            telemetry: submission.telemetry.clone(),
+            recent_fingerprint_submissions,
+            recent_ip_submissions,
+        };
+        let verdict = self.spam_classifier.classify(input, &signals);
+
+        Ok((submission, verdict))
+    }
+
+    async fn log_intake_submission(
+        &self,
+        product: &str,
+        ticket_id: Option<Uuid>,
+        submission: &IntakeSubmission,
+        verdict: &SpamVerdict,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        let telemetry = submission.telemetry.clone().unwrap_or_default();
+        let signals: Vec<&str> = verdict.signals.iter().map(SpamSignal::as_str).collect();
+
+        sqlx::query(
+            r#"
+            INSERT INTO intake_submissions (
+                product, ticket_id, accepted, signals, form_fill_ms, honeypot_triggered,
+                client_fingerprint_hash, ip_hash, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+        )
+        .bind(product)
+        .bind(ticket_id)
+        .bind(verdict.accepted)
+        .bind(signals)
+        .bind(telemetry.form_fill_ms)
+        .bind(telemetry.honeypot_triggered)
+        .bind(telemetry.client_fingerprint_hash)
+        .bind(&submission.ip_hash)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(())
+    }
+
+    async fn insert_ticket<'e, E: PgExecutor<'e>>(
+        executor: E,
+        format: &TicketNumberFormat,
+        product: &str,
+        default_locale: &str,
+        input: &CreateTicketInput,
+        metadata: serde_json::Value,
+        now: DateTime<Utc>,
+    ) -> std::result::Result<SupportTicket, sqlx::Error> {
+        // Number allocation and insert share one statement, so a failed insert
+        // at most leaves a gap in the sequence
+        sqlx::query_as::<_, SupportTicket>(
+            r#"
+            WITH seq AS (
+                INSERT INTO ticket_number_sequences (product, period, last_number)
+                VALUES ($1, $10, 1)
+                ON CONFLICT (product, period) DO UPDATE
+                    SET last_number = ticket_number_sequences.last_number + 1
+                RETURNING last_number
+            )
+            INSERT INTO support_tickets (
+                product, customer_id, subject, description, priority, category, customer_plan, metadata,
+                created_at, updated_at, ticket_number, ticket_ref, channel, locale, search_config, test_mode,
+                error_signature, organization_id
+            )
+            SELECT
+                $1, $2, $3, $4, $5, $6, $7, $8, $9, $9,
+                seq.last_number,
+                $11 || CASE
+                    WHEN LENGTH(seq.last_number::TEXT) >= $12 THEN seq.last_number::TEXT
+                    ELSE LPAD(seq.last_number::TEXT, $12, '0')
+                END,
+                $13, $14, support_search_config(COALESCE($14, $15)), $16,
+                NULLIF(BTRIM($17), ''), $18
+            FROM seq
+            RETURNING *
+            "#,
+        )
+        .bind(product)
+        .bind(input.customer_id)
+        .bind(&input.subject)
+        .bind(&input.description)
+        .bind(input.priority.unwrap_or_default())
+        .bind(&input.category)
+        .bind(&input.customer_plan)
+        .bind(metadata)
+        .bind(now)
+        .bind(format.period(now))
+        .bind(format.ref_prefix(now))
+        .bind(format.padding as i32)
+        .bind(input.channel)
+        .bind(&input.locale)
+        .bind(default_locale)
+        .bind(input.test_mode)
+        .bind(&input.error_signature)
+        .bind(input.organization_id)
+        .fetch_one(executor)
+        .await
+    }
+
+    /// Insert tickets parsed by [`crate::import::csv_tickets`]
+    ///
+    /// Rows go through the same intake checks as tickets created one at a
+    /// time: [`validation`], the product's accepted channels and its category
+    /// list. Failures are reported per field; an unregistered product fails
+    /// the whole import with `UnknownProduct`. In `AllOrNothing` mode nothing is
+    /// inserted if any row failed validation or insertion; in
+    /// `PartialSuccess` mode every insertable row is kept.
+    pub async fn import_tickets(&self, product: &str, import: CsvImport, mode: ImportMode) -> Result<ImportReport> {
+        self.ensure_writable()?;
+        let mut errors = import.errors;
+        let now = self.now();
+        let format = self.numbering.format_for(product);
+        let default_locale = self.locales.locale_for(product).locale;
+        let mut ticket_ids = Vec::with_capacity(import.tickets.len());
+
+        let mut tickets = Vec::with_capacity(import.tickets.len());
+        for (row, input) in &import.tickets {
+            match Self::intake_product_config(&self.pool, product, input).await {
+                Ok(_) => tickets.push((*row, input)),
+                Err(SupportError::InvalidFields(invalid)) => errors.extend(invalid.errors().iter().map(|e| ImportRowError {
+                    row: *row,
+                    column: Some(e.field.clone()),
+                    message: e.message.clone(),
+                })),
+                // The product doesn't take tickets through the row's channel
+                Err(SupportError::Validation(message)) => {
+                    errors.push(ImportRowError { row: *row, column: Some("channel".to_string()), message })
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        match mode {
+            ImportMode::AllOrNothing => {
+                if errors.is_empty() {
+                    let mut tx = self.pool.begin().await?;
+                    for (row, input) in &tickets {
+                        match Self::insert_ticket(&mut *tx, &format, product, &default_locale, input, serde_json::json!({}), now).await {
+                            Ok(ticket) => ticket_ids.push(ticket.id),
+                            Err(e) => {
+                                errors.push(ImportRowError { row: *row, column: None, message: e.to_string() });
+                                break;
+                            }
+                        }
+                    }
+                    if errors.is_empty() {
+                        tx.commit().await?;
+                    } else {
+                        tx.rollback().await?;
+                        ticket_ids.clear();
+                    }
+                }
+            }
+            ImportMode::PartialSuccess => {
+                for (row, input) in &tickets {
+                    match Self::insert_ticket(&self.pool, &format, product, &default_locale, input, serde_json::json!({}), now).await {
+                        Ok(ticket) => ticket_ids.push(ticket.id),
+                        Err(e) => errors.push(ImportRowError { row: *row, column: None, message: e.to_string() }),
+                    }
+                }
+            }
+        }
+
+        if !ticket_ids.is_empty() {
+            let mut conn = self.pool.acquire().await?;
+            self.apply_sla(&mut conn, &ticket_ids, now).await?;
+        }
+
+        errors.sort_by_key(|e| e.row);
+        let mut failed_rows: Vec<i32> = errors.iter().map(|e| e.row).collect();
+        failed_rows.dedup();
+
+        tracing::info!(
+            "Imported {} of {} tickets for product {}", ticket_ids.len(), import.total_rows, product
+        );
+
+        Ok(ImportReport {
+            total_rows: import.total_rows,
+            imported: ticket_ids.len() as i32,
+            failed: failed_rows.len() as i32,
+            ticket_ids,
+            errors,
+        })
+    }
+
+    /// Import survey responses parsed by [`crate::import::csat`] as CSAT surveys
+    ///
+    /// Each response is matched to one of the product's tickets by its
+    /// reference (ticket ref or id), else by email: the respondent's customer
+    /// (from the customer directory, or inbound emails they sent) and the
+    /// ticket of theirs resolved or closed most recently in the
+    /// [`CSAT_MATCH_WINDOW_DAYS`] before the response. Matched responses are
+    /// credited to the ticket's resolving agent and become the ticket's
+    /// `csat_score` unless it has a newer rating. Responses already imported
+    /// from the same source are counted as duplicates; the rest are reported
+    /// as unmatched with the reason.
+    pub async fn import_csat(&self, product: &str, import: CsatImport) -> Result<CsatImportReport> {
+        self.ensure_writable()?;
+        let mut report = CsatImportReport {
+            total_rows: import.total_rows,
+            errors: import.errors,
+            ..Default::default()
+        };
+
+        let mut emails: Vec<String> = import.responses.iter().filter_map(|(_, r)| r.email.clone()).collect();
+        emails.sort();
+        emails.dedup();
+        let customers_by_email = self.customers_by_email(product, &emails).await?;
+        let now = self.now();
+
+        for (row, response) in &import.responses {
+            let ticket = match self.match_csat_ticket(product, response, &customers_by_email).await? {
+                Ok(ticket) => ticket,
+                Err(reason) => {
+                    report.unmatched.push(UnmatchedCsatRecord {
+                        row: *row,
+                        source: response.source.clone(),
+                        response_id: response.response_id.clone(),
+                        reason,
+                    });
+                    continue;
+                }
+            };
+
+            let mut tx = self.pool.begin().await?;
+            let survey = sqlx::query_as::<_, CsatSurvey>(
+                r#"
+                INSERT INTO csat_surveys (
+                    ticket_id, product, customer_id, agent_id, channel, sent_at, score, comment, responded_at,
+                    source, external_id, nps_score
+                )
+                VALUES ($1, $2, $3, $4, 'EMAIL', $5, $6, $7, $5, $8, $9, $10)
+                ON CONFLICT (source, external_id) WHERE external_id IS NOT NULL DO NOTHING
+                RETURNING *
+                "#,
+            )
+            .bind(ticket.id)
+            .bind(&ticket.product)
+            .bind(ticket.customer_id)
+            .bind(ticket.resolving_agent_id)
+            .bind(response.responded_at)
+            .bind(response.score)
+            .bind(&response.comment)
+            .bind(&response.source)
+            .bind(&response.response_id)
+            .bind(response.nps_score)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            let Some(survey) = survey else {
+                report.duplicates += 1;
+                continue;
+            };
+
+            sqlx::query(
+                r#"
+                UPDATE support_tickets SET csat_score = $2, csat_submitted_at = $3, updated_at = $4
+                WHERE id = $1 AND (csat_submitted_at IS NULL OR csat_submitted_at < $3)
+                "#,
+            )
+            .bind(ticket.id)
+            .bind(response.score)
+            .bind(response.responded_at)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+
+            report.imported += 1;
+            report.survey_ids.push(survey.id);
+        }
+
+        tracing::info!(
+            "Imported {} of {} CSAT responses for product {} ({} duplicates, {} unmatched)",
+            report.imported, report.total_rows, product, report.duplicates, report.unmatched.len()
+        );
+        Ok(report)
+    }
+
+    /// Customer ids for lowercased `emails`: from the directory, and from
+    /// the senders of the product's inbound emails
+    async fn customers_by_email(&self, product: &str, emails: &[String]) -> Result<HashMap<String, Vec<Uuid>>> {
+        let mut customers: HashMap<String, Vec<Uuid>> = HashMap::new();
+        if emails.is_empty() {
+            return Ok(customers);
+        }
+
+        if let Some(directory) = &self.customer_directory {
+            for (email, customer_id) in directory.find_by_email(emails).await? {
+                customers.entry(email.to_lowercase()).or_default().push(customer_id);
+            }
+        }
+
+        let senders: Vec<(String, Uuid)> = sqlx::query_as(
+            r#"
+            SELECT DISTINCT LOWER(ie.from_address), t.customer_id
+            FROM inbound_emails ie
+            JOIN support_tickets t ON t.id = ie.ticket_id
+            WHERE t.product = $1 AND LOWER(ie.from_address) = ANY($2)
+            "#,
+        )
+        .bind(product)
+        .bind(emails)
+        .fetch_all(&self.pool)
+        .await?;
+        for (email, customer_id) in senders {
+            let ids = customers.entry(email).or_default();
+            if !ids.contains(&customer_id) {
+                ids.push(customer_id);
+            }
+        }
+
+        Ok(customers)
+    }
+
+    /// The ticket an external response rates, or why there is none
+    async fn match_csat_ticket(
+        &self,
+        product: &str,
+        response: &ExternalCsatResponse,
+        customers_by_email: &HashMap<String, Vec<Uuid>>,
+    ) -> Result<std::result::Result<SupportTicket, String>> {
+        if let Some(reference) = &response.external_ref {
+            let ticket = sqlx::query_as::<_, SupportTicket>(
+                "SELECT * FROM support_tickets WHERE product = $1 AND deleted_at IS NULL AND (ticket_ref = $2 OR id::TEXT = LOWER($2))"
+            )
+            .bind(product)
+            .bind(reference)
+            .fetch_optional(&self.pool)
+            .await?;
+            if let Some(ticket) = ticket {
+                return Ok(Ok(ticket));
+            }
+            if response.email.is_none() {
+                return Ok(Err(format!("No ticket with reference {}", reference)));
+            }
+        }
+
+        let Some(customer_ids) = response.email.as_ref().and_then(|email| customers_by_email.get(email)) else {
+            return Ok(Err("No customer with this email".to_string()));
+        };
+        let ticket = sqlx::query_as::<_, SupportTicket>(
+            r#"
+            SELECT * FROM support_tickets
+            WHERE product = $1 AND deleted_at IS NULL AND customer_id = ANY($2)
+              AND COALESCE(resolved_at, closed_at) BETWEEN $3 AND $4
+            ORDER BY COALESCE(resolved_at, closed_at) DESC, id DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(product)
+        .bind(customer_ids)
+        .bind(response.responded_at - Duration::days(CSAT_MATCH_WINDOW_DAYS))
+        .bind(response.responded_at)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(ticket.ok_or_else(|| {
+            format!("No ticket of this customer resolved in the {} days before the response", CSAT_MATCH_WINDOW_DAYS)
+        }))
+    }
+
+    /// Get many tickets by ID in one query; missing and deleted tickets are left out
+    pub async fn find_by_ids(&self, ticket_ids: &[Uuid]) -> Result<Vec<SupportTicket>> {
+        let tickets = sqlx::query_as::<_, SupportTicket>(
+            "SELECT * FROM support_tickets WHERE id = ANY($1) AND deleted_at IS NULL"
+        )
+        .bind(ticket_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(tickets)
+    }
+
+    /// Tickets of many customers in one query, newest first
+    pub async fn find_by_customers(&self, product: &str, customer_ids: &[Uuid]) -> Result<Vec<SupportTicket>> {
+        let tickets = sqlx::query_as::<_, SupportTicket>(
+            r#"
+            SELECT * FROM support_tickets
+            WHERE product = $1 AND customer_id = ANY($2) AND deleted_at IS NULL
+            ORDER BY created_at DESC, id DESC
+            "#,
+        )
+        .bind(product)
+        .bind(customer_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(tickets)
+    }
+
+    /// Get ticket by ID
+    pub async fn find_by_id(&self, ticket_id: Uuid) -> Result<SupportTicket> {
+        self.find_by_id_projected(ticket_id, TicketProjection::FULL).await
+    }
+
+    /// Get ticket by ID, loading only the wide columns of `projection`
+    pub async fn find_by_id_projected(&self, ticket_id: Uuid, projection: TicketProjection) -> Result<SupportTicket> {
+        let ticket = sqlx::query_as::<_, SupportTicket>(&format!(
+            "SELECT {} FROM support_tickets WHERE id = $1 AND deleted_at IS NULL",
+            projection.columns()
+        ))
+        .bind(ticket_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => SupportError::TicketNotFound(ticket_id),
+            _ => {
+                tracing::error!("Failed to fetch support ticket: {}", e);
+                SupportError::from(e)
+            }
+        })?;
+
+        Ok(ticket)
+    }
+
+    /// Get a ticket of one B2B account by ID
+    ///
+    /// Tickets of other organizations fail with `TicketNotFound`, as if they
+    /// did not exist, so tenant-scoped callers can't probe for them.
+    pub async fn find_in_organization(&self, ticket_id: Uuid, organization_id: Uuid) -> Result<SupportTicket> {
+        sqlx::query_as::<_, SupportTicket>(
+            "SELECT * FROM support_tickets WHERE id = $1 AND organization_id = $2 AND deleted_at IS NULL"
+        )
+        .bind(ticket_id)
+        .bind(organization_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(SupportError::from)?
+        .ok_or(SupportError::TicketNotFound(ticket_id))
+    }
+
+    /// The B2B account a ticket belongs to, deleted or not
+    pub async fn ticket_organization(&self, ticket_id: Uuid) -> Result<Option<Uuid>> {
+        let organization_id: Option<Option<Uuid>> =
+            sqlx::query_scalar("SELECT organization_id FROM support_tickets WHERE id = $1")
+                .bind(ticket_id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        organization_id.ok_or(SupportError::TicketNotFound(ticket_id))
+    }
+
+    /// The ticket a message is on
+    pub async fn message_ticket_id(&self, message_id: Uuid) -> Result<Uuid> {
+        let ticket_id: Option<Uuid> = sqlx::query_scalar("SELECT ticket_id FROM ticket_messages WHERE id = $1")
+            .bind(message_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        ticket_id.ok_or(SupportError::MessageNotFound(message_id))
+    }
+
+    /// Get ticket by its human-readable reference (e.g. `NOVASKYN-000042`)
+    pub async fn find_by_ref(&self, reference: &str) -> Result<SupportTicket> {
+        self.find_by_ref_projected(reference, TicketProjection::FULL).await
+    }
+
+    /// Get ticket by its reference, loading only the wide columns of `projection`
+    pub async fn find_by_ref_projected(&self, reference: &str, projection: TicketProjection) -> Result<SupportTicket> {
+        let (product, number) = self.numbering.parse_ticket_ref(reference).ok_or_else(|| {
+            SupportError::InvalidInput(format!("Unrecognized ticket reference: {}", reference))
+        })?;
+        let canonical = self.numbering.format_for(&product).format(&number);
+
+        let ticket = sqlx::query_as::<_, SupportTicket>(&format!(
+            "SELECT {} FROM support_tickets WHERE product = $1 AND ticket_ref = $2 AND deleted_at IS NULL",
+            projection.columns()
+        ))
+        .bind(&product)
+        .bind(&canonical)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        ticket.ok_or(SupportError::TicketRefNotFound(canonical))
+    }
+
+    /// Fetch an object for the GraphQL `node` resolver; `None` when it does
+    /// not exist or `agent_id` may not see it
+    ///
+    /// Messages above `visibility`, other agents' saved views and canned
+    /// responses outside the agent's scope read as missing, as in
+    /// [`Self::get_messages`], [`Self::list_saved_views`] and
+    /// [`Self::list_canned_responses`].
+    pub async fn find_node(
+        &self,
+        node_type: NodeType,
+        id: Uuid,
+        agent_id: Uuid,
+        visibility: MessageVisibility,
+    ) -> Result<Option<Node>> {
+        let node = match node_type {
+            NodeType::SupportTicket => sqlx::query_as::<_, SupportTicket>(
+                "SELECT * FROM support_tickets WHERE id = $1 AND deleted_at IS NULL"
+            )
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .map(Node::SupportTicket),
+            NodeType::TicketMessage => sqlx::query_as::<_, TicketMessage>("SELECT * FROM ticket_messages WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?
+                .filter(|message| message.visibility.visible_to(visibility))
+                .map(Node::TicketMessage),
+            NodeType::SupportAgent => sqlx::query_as::<_, SupportAgent>("SELECT * FROM support_agents WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?
+                .map(Node::SupportAgent),
+            NodeType::SavedView => sqlx::query_as::<_, SavedView>("SELECT * FROM saved_views WHERE id = $1 AND owner_id = $2")
+                .bind(id)
+                .bind(agent_id)
+                .fetch_optional(&self.pool)
+                .await?
+                .map(Node::SavedView),
+            NodeType::CannedResponse => sqlx::query_as::<_, CannedResponse>(
+                r#"
+                SELECT cr.*
+                FROM canned_responses cr
+                LEFT JOIN support_agents sa ON sa.product = cr.product AND sa.user_id = $2
+                WHERE cr.id = $1
+                  AND (
+                      cr.scope = 'PRODUCT'
+                      OR (cr.scope = 'PERSONAL' AND cr.owner_id = $2)
+                      OR (cr.scope = 'TEAM' AND cr.team = sa.team)
+                  )
+                "#,
+            )
+            .bind(id)
+            .bind(agent_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .map(Node::CannedResponse),
+        };
+
+        Ok(node)
+    }
+
+    /// Update ticket
+    ///
+    /// Status, priority, assignment, subject, description and category changes
+    /// are recorded as audit entries.
+    pub async fn update_ticket(&self, ticket_id: Uuid, input: &UpdateTicketInput) -> Result<SupportTicket> {
+        self.update_ticket_with_propagation(ticket_id, None, input, PropagationPolicy::default()).await
+    }
+
+    /// Record the customer's 1-5 satisfaction rating for a resolved or closed ticket
+    ///
+    /// Submitting again replaces the rating. Analytics credit it to the
+    /// ticket's resolving agent rather than its current assignee.
+    pub async fn submit_csat(&self, ticket_id: Uuid, customer_id: Uuid, score: i32) -> Result<SupportTicket> {
+        let input = CsatFeedbackInput { score, comment: None, channel: CsatChannel::Web };
+        self.submit_csat_feedback(ticket_id, customer_id, &input).await?;
+
+        self.find_by_id(ticket_id).await
+    }
+
+    /// Record a signed-in customer's rating and comment for their resolved or closed ticket
+    ///
+    /// Stored as a responded survey without a link; the score also becomes
+    /// the ticket's `csat_score`, replacing any earlier rating.
+    pub async fn submit_csat_feedback(
+        &self,
+        ticket_id: Uuid,
+        customer_id: Uuid,
+        input: &CsatFeedbackInput,
+    ) -> Result<CsatSurvey> {
+        self.ensure_writable()?;
+        let now = self.now();
+        let mut tx = self.pool.begin().await?;
+        let survey = Self::submit_csat_feedback_in_tx(&mut tx, ticket_id, customer_id, input, now).await?;
+        tx.commit().await?;
+
+        Ok(survey)
+    }
+
+    /// Record a customer's rating and comment inside `conn`
+    async fn submit_csat_feedback_in_tx(
+        conn: &mut PgConnection,
+        ticket_id: Uuid,
+        customer_id: Uuid,
+        input: &CsatFeedbackInput,
+        now: DateTime<Utc>,
+    ) -> Result<CsatSurvey> {
+        validation::validate_csat_score(input.score)?;
+
+        let ticket = sqlx::query_as::<_, SupportTicket>("SELECT * FROM support_tickets WHERE id = $1 AND deleted_at IS NULL")
+            .bind(ticket_id)
+            .fetch_optional(&mut *conn)
+            .await?
+            .ok_or(SupportError::TicketNotFound(ticket_id))?;
+        if ticket.customer_id != customer_id {
+            return Err(SupportError::Unauthorized);
+        }
+        Self::require_ratable(&ticket)?;
+
+        let survey = sqlx::query_as::<_, CsatSurvey>(
+            r#"
+            INSERT INTO csat_surveys (ticket_id, product, customer_id, agent_id, channel, sent_at, score, comment, responded_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $6)
+            RETURNING *
+            "#,
+        )
+        .bind(ticket.id)
+        .bind(&ticket.product)
+        .bind(ticket.customer_id)
+        .bind(ticket.resolving_agent_id)
+        .bind(input.channel)
+        .bind(now)
+        .bind(input.score)
+        .bind(Self::csat_comment(input))
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Self::mirror_csat_score(conn, ticket.id, input.score, now).await?;
+
+        Ok(survey)
+    }
+
+    /// Create a survey for a resolved or closed ticket and return its link token
+    ///
+    /// The rating is credited to the ticket's resolving agent. Sending again
+    /// creates another survey; each link stays valid for the configured period.
+    pub async fn send_csat_survey(&self, ticket_id: Uuid, channel: CsatChannel) -> Result<CsatSurveyLink> {
+        self.ensure_writable()?;
+        let ticket = self.find_by_id(ticket_id).await?;
+        Self::require_ratable(&ticket)?;
+
+        let token = new_survey_token();
+        let now = self.now();
+        let survey = sqlx::query_as::<_, CsatSurvey>(
+            r#"
+            INSERT INTO csat_surveys (ticket_id, product, customer_id, agent_id, channel, token_hash, sent_at, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING *
+            "#,
+        )
+        .bind(ticket.id)
+        .bind(&ticket.product)
+        .bind(ticket.customer_id)
+        .bind(ticket.resolving_agent_id)
+        .bind(channel)
+        .bind(hash_survey_token(&token))
+        .bind(now)
+        .bind(now + self.csat_surveys.validity)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(CsatSurveyLink { url: self.csat_surveys.survey_link(&token), survey, token })
+    }
+
+    /// The survey a link token belongs to, for rendering the survey page
+    pub async fn find_csat_survey_by_token(&self, token: &str) -> Result<CsatSurvey> {
+        sqlx::query_as::<_, CsatSurvey>("SELECT * FROM csat_surveys WHERE token_hash = $1")
+            .bind(hash_survey_token(token))
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| SupportError::Validation("Unknown survey link".to_string()))
+    }
+
+    /// Answer a survey through its link token
+    ///
+    /// Answering again before the link expires replaces the earlier answer.
+    pub async fn respond_to_csat_survey(&self, token: &str, input: &CsatFeedbackInput) -> Result<CsatSurvey> {
+        self.ensure_writable()?;
+        validation::validate_csat_score(input.score)?;
+
+        let survey = self.find_csat_survey_by_token(token).await?;
+        let now = self.now();
+        if survey.expires_at.is_some_and(|expires_at| expires_at <= now) {
+            return Err(SupportError::Validation("Survey link has expired".to_string()));
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let survey = sqlx::query_as::<_, CsatSurvey>(
+            r#"
+            UPDATE csat_surveys
+            SET score = $2, comment = $3, responded_at = $4
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(survey.id)
+        .bind(input.score)
+        .bind(Self::csat_comment(input))
+        .bind(now)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        Self::mirror_csat_score(&mut tx, survey.ticket_id, input.score, now).await?;
+        tx.commit().await?;
+
+        Ok(survey)
+    }
+
+    /// Surveys sent for a ticket, newest first
+    pub async fn list_csat_surveys(&self, ticket_id: Uuid) -> Result<Vec<CsatSurvey>> {
+        let surveys = sqlx::query_as::<_, CsatSurvey>(
+            "SELECT * FROM csat_surveys WHERE ticket_id = $1 ORDER BY sent_at DESC, id DESC"
+        )
+        .bind(ticket_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(surveys)
+    }
+
+    /// CSAT for surveys sent in the period, grouped by agent, category or period
+    ///
+    /// Periods are taken in the product's locale timezone. Each group reports
+    /// response rate, average score and the share of 4-5 ratings.
+    pub async fn get_csat_breakdown(
+        &self,
+        product: &str,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+        by: CsatDimension,
+    ) -> Result<Vec<CsatBreakdown>> {
+        let key = match (by, by.period_unit()) {
+            (_, Some(unit)) => format!("DATE_TRUNC('{}', cs.sent_at AT TIME ZONE $4)::DATE::TEXT", unit),
+            (CsatDimension::Agent, None) => "cs.agent_id::TEXT".to_string(),
+            (_, None) => "t.category::TEXT".to_string(),
+        };
+        let timezone = self.locales.locale_for(product).timezone;
+
+        let mut tx = self.begin_timed(OperationClass::Analytics).await?;
+        let breakdown = sqlx::query_as::<_, CsatBreakdown>(&format!(
+            r#"
+            SELECT
+                {key} as key,
+                COUNT(*)::BIGINT as surveys_sent,
+                COUNT(cs.responded_at)::BIGINT as responses,
+                COUNT(cs.responded_at)::FLOAT / COUNT(*) * 100 as response_rate,
+                AVG(cs.score::FLOAT) as avg_score,
+                CASE WHEN COUNT(cs.responded_at) > 0
+                    THEN COUNT(*) FILTER (WHERE cs.score >= 4)::FLOAT / COUNT(cs.responded_at) * 100
+                END as satisfied_rate,
+                COUNT(cs.comment)::BIGINT as comments
+            FROM csat_surveys cs
+            JOIN support_tickets t ON t.id = cs.ticket_id
+            WHERE cs.product = $1
+              AND cs.sent_at >= $2 AND cs.sent_at < $3
+              AND t.deleted_at IS NULL
+              AND NOT t.test_mode
+            GROUP BY 1
+            ORDER BY 1 NULLS LAST
+            "#,
+        ))
+        .bind(product)
+        .bind(period_start)
+        .bind(period_end)
+        .bind(timezone)
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(breakdown)
+    }
+
+    fn require_ratable(ticket: &SupportTicket) -> Result<()> {
+        if !matches!(ticket.status, TicketStatus::Resolved | TicketStatus::Closed) {
+            return Err(SupportError::Validation("Only resolved or closed tickets can be rated".to_string()));
+        }
+
+        Ok(())
+    }
+
+    fn csat_comment(input: &CsatFeedbackInput) -> Option<&str> {
+        input.comment.as_deref().map(str::trim).filter(|comment| !comment.is_empty())
+    }
+
+    /// Make `score` the ticket's current rating, as read by dashboards
+    async fn mirror_csat_score(conn: &mut PgConnection, ticket_id: Uuid, score: i32, now: DateTime<Utc>) -> Result<()> {
+        sqlx::query(
+            "UPDATE support_tickets SET csat_score = $2, csat_submitted_at = $3, updated_at = $3 WHERE id = $1"
+        )
+        .bind(ticket_id)
+        .bind(score)
+        .bind(now)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// File an engineering issue for a ticket and link it
+    ///
+    /// The issue carries the ticket reference, subject and description. An
+    /// internal note with the issue link is added to the ticket.
+    pub async fn escalate_to_engineering(
+        &self,
+        ticket_id: Uuid,
+        actor_id: Uuid,
+        tracker: IssueTracker,
+        project: &str,
+    ) -> Result<EngineeringIssue> {
+        self.ensure_writable()?;
+        let client = self.issue_trackers.client(tracker)?;
+        let ticket = self.find_by_id(ticket_id).await?;
+        if project.trim().is_empty() {
+            return Err(SupportError::Validation("Engineering project is required".to_string()));
+        }
+
+        let issue = client
+            .create_issue(&NewIssue {
+                project: project.trim().to_string(),
+                title: format!("[{}] {}", ticket.ticket_ref, ticket.subject),
+                body: format!("{}\n\nEscalated from support ticket {}.", ticket.description, ticket.ticket_ref),
+            })
+            .await?;
+
+        self.insert_engineering_issue(&ticket, actor_id, tracker, &issue, "Escalated to engineering").await
+    }
+
+    /// Link an existing engineering issue to a ticket
+    ///
+    /// Linking an issue the ticket already has refreshes its title and state
+    /// without another note.
+    pub async fn link_engineering_issue(
+        &self,
+        ticket_id: Uuid,
+        actor_id: Uuid,
+        tracker: IssueTracker,
+        external_key: &str,
+    ) -> Result<EngineeringIssue> {
+        self.ensure_writable()?;
+        let client = self.issue_trackers.client(tracker)?;
+        let ticket = self.find_by_id(ticket_id).await?;
+        let issue = client.get_issue(external_key.trim()).await?;
+
+        self.insert_engineering_issue(&ticket, actor_id, tracker, &issue, "Linked engineering issue").await
+    }
+
+    /// Engineering issues linked to a ticket, oldest first
+    pub async fn list_engineering_issues(&self, ticket_id: Uuid) -> Result<Vec<EngineeringIssue>> {
+        let issues = sqlx::query_as::<_, EngineeringIssue>(
+            "SELECT * FROM engineering_issues WHERE ticket_id = $1 ORDER BY created_at ASC, id ASC"
+        )
+        .bind(ticket_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(issues)
+    }
+
+    /// An engineering issue link by ID
+    pub async fn get_engineering_issue(&self, issue_id: Uuid) -> Result<EngineeringIssue> {
+        sqlx::query_as::<_, EngineeringIssue>("SELECT * FROM engineering_issues WHERE id = $1")
+            .bind(issue_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| SupportError::Validation(format!("Engineering issue not found: {}", issue_id)))
+    }
+
+    /// Close an engineering issue in its tracker and sync every linked ticket
+    pub async fn close_engineering_issue(&self, issue_id: Uuid) -> Result<Vec<EngineeringIssue>> {
+        self.ensure_writable()?;
+        let issue = self.get_engineering_issue(issue_id).await?;
+
+        self.issue_trackers.client(issue.tracker)?.close_issue(&issue.external_key).await?;
+
+        self.sync_engineering_issue_state(issue.tracker, &issue.external_key, IssueState::Closed).await
+    }
+
+    /// Record an issue's new state from the tracker (webhook or poll)
+    ///
+    /// Every link to the issue is updated. Tickets whose link changed state
+    /// get an internal note, so agents know the fix shipped (or the issue was
+    /// reopened) and can follow up with the customer. Returns the changed links.
+    pub async fn sync_engineering_issue_state(
+        &self,
+        tracker: IssueTracker,
+        external_key: &str,
+        state: IssueState,
+    ) -> Result<Vec<EngineeringIssue>> {
+        self.ensure_writable()?;
+        let now = self.now();
+        let mut tx = self.pool.begin().await?;
+
+        let changed = sqlx::query_as::<_, EngineeringIssue>(
+            r#"
+            UPDATE engineering_issues
+            SET state = $3,
+                closed_at = CASE WHEN $3 = 'CLOSED'::engineering_issue_state THEN $4 END
+            WHERE tracker = $1 AND external_key = $2 AND state <> $3
+            RETURNING *
+            "#,
+        )
+        .bind(tracker)
+        .bind(external_key)
+        .bind(state)
+        .bind(now)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        for issue in &changed {
+            let verb = match state {
+                IssueState::Closed => "was closed",
+                IssueState::Open => "was reopened",
+            };
+            let note = format!("Engineering issue {} {}: {}", issue.external_key, verb, issue.url);
+            Self::insert_internal_note(&mut tx, issue.ticket_id, SYSTEM_AUTHOR_ID, &note, now).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(changed)
+    }
+
+    async fn insert_engineering_issue(
+        &self,
+        ticket: &SupportTicket,
+        actor_id: Uuid,
+        tracker: IssueTracker,
+        issue: &ExternalIssue,
+        action: &str,
+    ) -> Result<EngineeringIssue> {
+        let now = self.now();
+        let mut tx = self.pool.begin().await?;
+
+        let already_linked: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM engineering_issues WHERE ticket_id = $1 AND tracker = $2 AND external_key = $3)"
+        )
+        .bind(ticket.id)
+        .bind(tracker)
+        .bind(&issue.key)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let linked = sqlx::query_as::<_, EngineeringIssue>(
+            r#"
+            INSERT INTO engineering_issues (
+                ticket_id, product, tracker, project, external_key, url, title, state, linked_by, created_at, closed_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, CASE WHEN $8 = 'CLOSED'::engineering_issue_state THEN $10 END)
+            ON CONFLICT (ticket_id, tracker, external_key) DO UPDATE
+            SET url = EXCLUDED.url, title = EXCLUDED.title, state = EXCLUDED.state,
+                closed_at = CASE WHEN EXCLUDED.state = 'CLOSED' THEN COALESCE(engineering_issues.closed_at, EXCLUDED.closed_at) END
+            RETURNING *
+            "#,
+        )
+        .bind(ticket.id)
+        .bind(&ticket.product)
+        .bind(tracker)
+        .bind(&issue.project)
+        .bind(&issue.key)
+        .bind(&issue.url)
+        .bind(&issue.title)
+        .bind(issue.state)
+        .bind(actor_id)
+        .bind(now)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if !already_linked {
+            let note = format!("{}: {} {}", action, issue.key, issue.url);
+            Self::insert_internal_note(&mut tx, ticket.id, actor_id, &note, now).await?;
+        }
+        tx.commit().await?;
+
+        Ok(linked)
+    }
+
+    /// Internal note that skips moderation and first-response tracking
+    async fn insert_internal_note(
+        conn: &mut PgConnection,
+        ticket_id: Uuid,
+        author_id: Uuid,
+        content: &str,
+        now: DateTime<Utc>,
+    ) -> Result<TicketMessage> {
+        let message = sqlx::query_as::<_, TicketMessage>(
+            r#"
+            INSERT INTO ticket_messages (ticket_id, author_id, is_internal, visibility, content, created_at)
+            VALUES ($1, $2, TRUE, 'INTERNAL', $3, $4)
+            RETURNING *
+            "#,
+        )
+        .bind(ticket_id)
+        .bind(author_id)
+        .bind(content)
+        .bind(now)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(message)
+    }
+
+    /// Update ticket and propagate priority/status changes to linked child tickets
+    ///
+    /// The parent update, every child update and their audit entries are
+    /// applied in a single transaction. On resolution the ticket records its
+    /// final public agent reply and credits `actor_id`, else that reply's
+    /// author, else the assignee, as the resolving agent; children resolved
+    /// along with it credit the same agent.
+    pub async fn update_ticket_with_propagation(
+        &self,
+        ticket_id: Uuid,
+        actor_id: Option<Uuid>,
+        input: &UpdateTicketInput,
+        policy: PropagationPolicy,
+    ) -> Result<SupportTicket> {
+        self.ensure_writable()?;
+        let now = self.now();
+        let mut after = AfterCommit::default();
+        let mut tx = self.pool.begin().await?;
+        let ticket = self.update_ticket_in_tx(&mut tx, ticket_id, actor_id, input, policy, now, &mut after).await?;
+        tx.commit().await?;
+        if self.after_commit(after).await {
+            return self.find_by_id(ticket_id).await;
+        }
+
+        Ok(ticket)
+    }
+
+    /// Reopen a RESOLVED or CLOSED ticket, moving it back to IN_PROGRESS, or
+    /// NEW if it is unassigned
+    ///
+    /// Clears `resolved_at` and `closed_at` and counts the reopen in
+    /// `reopen_count`; resolving again stamps a new `resolved_at`. Fails with
+    /// `Validation` for tickets that are still open.
+    pub async fn reopen_ticket(&self, ticket_id: Uuid, actor_id: Option<Uuid>) -> Result<SupportTicket> {
+        self.ensure_writable()?;
+        let now = self.now();
+        let mut after = AfterCommit::default();
+        let mut tx = self.pool.begin().await?;
+        let ticket = self.reopen_ticket_in_tx(&mut tx, ticket_id, actor_id, now, &mut after).await?;
+        tx.commit().await?;
+        tracing::info!("Reopened ticket {} ({} reopens)", ticket.ticket_ref, ticket.reopen_count);
+        if self.after_commit(after).await {
+            return self.find_by_id(ticket_id).await;
+        }
+
+        Ok(ticket)
+    }
+
+    /// Reopen a ticket inside `tx`, adding the events and automations to run
+    /// once it commits to `after`
+    async fn reopen_ticket_in_tx(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        ticket_id: Uuid,
+        actor_id: Option<Uuid>,
+        now: DateTime<Utc>,
+        after: &mut AfterCommit,
+    ) -> Result<SupportTicket> {
+        let before = sqlx::query_as::<_, SupportTicket>(
+            "SELECT * FROM support_tickets WHERE id = $1 AND deleted_at IS NULL FOR UPDATE"
+        )
+        .bind(ticket_id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or(SupportError::TicketNotFound(ticket_id))?;
+        if !matches!(before.status, TicketStatus::Resolved | TicketStatus::Closed) {
+            return Err(SupportError::Validation(format!(
+                "Only resolved or closed tickets can be reopened; ticket {} is {}",
+                before.ticket_ref,
+                before.status.as_str()
+            )));
+        }
+
+        let status = if before.assigned_to.is_some() { TicketStatus::InProgress } else { TicketStatus::New };
+        let ticket = sqlx::query_as::<_, SupportTicket>(
+            r#"
+            UPDATE support_tickets
+            SET status = $2, resolved_at = NULL, closed_at = NULL, reopen_count = reopen_count + 1, updated_at = $3
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(ticket_id)
+        .bind(status)
+        .bind(now)
+        .fetch_one(&mut **tx)
+        .await?;
+        Self::insert_audit_entry(
+            tx, ticket_id, actor_id, "status",
+            Some(before.status.as_str()), Some(status.as_str()), None, now,
+        ).await?;
+
+        let ticket = self.apply_sla(tx, &[ticket_id], now).await?
+            .into_iter()
+            .find(|t| t.id == ticket_id)
+            .unwrap_or(ticket);
+        after.events.push(SupportEvent::ticket(SupportEventType::TicketUpdated, &ticket, now));
+        after.automations.push(AutomationFiring::status_changed(ticket_id, before.status));
+
+        Ok(ticket)
+    }
+
+    /// Apply one update to many tickets in a single transaction
+    ///
+    /// Each ticket is updated as by `update_ticket_with_propagation` (without
+    /// propagation) under its own savepoint: failures are reported per ticket
+    /// and do not affect the others. Duplicate ids are updated once.
+    pub async fn bulk_update_tickets(
+        &self,
+        ticket_ids: &[Uuid],
+        actor_id: Option<Uuid>,
+        input: &UpdateTicketInput,
+    ) -> Result<BulkUpdateReport> {
+        self.ensure_writable()?;
+        let mut unique_ids: Vec<Uuid> = Vec::with_capacity(ticket_ids.len());
+        for id in ticket_ids {
+            if !unique_ids.contains(id) {
+                unique_ids.push(*id);
+            }
+        }
+        if unique_ids.len() > MAX_BULK_TICKETS {
+            return Err(SupportError::Validation(format!(
+                "Bulk operations are limited to {} tickets", MAX_BULK_TICKETS
+            )));
+        }
+
+        let now = self.now();
+        let mut after = AfterCommit::default();
+        let mut tx = self.pool.begin().await?;
+        let mut results = Vec::with_capacity(unique_ids.len());
+        for ticket_id in unique_ids {
+            let mut savepoint = (&mut tx).begin().await?;
+            let mut ticket_after = AfterCommit::default();
+            let outcome = self
+                .update_ticket_in_tx(&mut savepoint, ticket_id, actor_id, input, PropagationPolicy::default(), now, &mut ticket_after)
+                .await;
+            let result = match outcome {
+                Ok(ticket) => {
+                    savepoint.commit().await?;
+                    after.append(&mut ticket_after);
+                    BulkTicketResult { ticket_id, success: true, ticket: Some(ticket), error: None }
+                }
+                Err(e) => {
+                    savepoint.rollback().await?;
+                    BulkTicketResult { ticket_id, success: false, ticket: None, error: Some(e.to_string()) }
+                }
+            };
+            results.push(result);
+        }
+        tx.commit().await?;
+        self.after_commit(after).await;
+
+        let report = BulkUpdateReport::new(results);
+        tracing::info!("Bulk updated {} tickets ({} failed)", report.succeeded, report.failed);
+        Ok(report)
+    }
+
+    /// Assign many tickets to one agent; see `bulk_update_tickets`
+    pub async fn bulk_assign(&self, ticket_ids: &[Uuid], assignee_id: Uuid, actor_id: Option<Uuid>) -> Result<BulkUpdateReport> {
+        let input = UpdateTicketInput {
+            subject: None,
+            description: None,
+            status: None,
+            priority: None,
+            category: None,
+            assigned_to: Some(assignee_id),
+            resolution_code: None,
+        };
+        self.bulk_update_tickets(ticket_ids, actor_id, &input).await
+    }
+
+    /// Close many tickets; see `bulk_update_tickets`
+    pub async fn bulk_close(&self, ticket_ids: &[Uuid], actor_id: Option<Uuid>) -> Result<BulkUpdateReport> {
+        let input = UpdateTicketInput {
+            subject: None,
+            description: None,
+            status: Some(TicketStatus::Closed),
+            priority: None,
+            category: None,
+            assigned_to: None,
+            resolution_code: None,
+        };
+        self.bulk_update_tickets(ticket_ids, actor_id, &input).await
+    }
+
+    /// What changed in `scope` after `cursor` (from the start without one),
+    /// for offline devices; see [`sync`](crate::sync)
+    ///
+    /// Each of the ticket, message and tombstone streams returns up to
+    /// `limit` rows (default 200, at most 1000).
+    pub async fn changes_since(&self, scope: &SyncScope, cursor: Option<&str>, limit: Option<i64>) -> Result<SyncChanges> {
+        let mut cursor = cursor.map(SyncCursor::decode).transpose()?.unwrap_or_default();
+        let limit = limit.unwrap_or(DEFAULT_SYNC_LIMIT).clamp(1, MAX_SYNC_LIMIT);
+        let settled = self.now() - Duration::seconds(SYNC_SETTLE_SECONDS);
+
+        // Tickets assigned away from the agent since the cursor come back as tombstones
+        let changed = sqlx::query_as::<_, SupportTicket>(
+            r#"
+            SELECT * FROM support_tickets st
+            WHERE product = $1
+              AND (updated_at, id) > ($2, $3)
+              AND updated_at <= $4
+              AND ($5::UUID IS NULL OR assigned_to = $5 OR EXISTS (
+                  SELECT 1 FROM ticket_audit_entries a
+                  WHERE a.ticket_id = st.id AND a.field = 'assigned_to'
+                    AND a.old_value = $5::TEXT AND a.created_at > $2
+              ))
+              AND ($7::UUID IS NULL OR organization_id = $7)
+            ORDER BY updated_at, id
+            LIMIT $6
+            "#,
+        )
+        .bind(&scope.product)
+        .bind(cursor.tickets.0)
+        .bind(cursor.tickets.1)
+        .bind(settled)
+        .bind(scope.assigned_to)
+        .bind(limit)
+        .bind(scope.organization_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let messages = sqlx::query_as::<_, SyncedMessage>(
+            r#"
+            SELECT m.*, COALESCE(m.updated_at, m.created_at) as changed_at
+            FROM ticket_messages m
+            JOIN support_tickets st ON st.id = m.ticket_id
+            WHERE st.product = $1
+              AND st.deleted_at IS NULL
+              AND ($5::UUID IS NULL OR st.assigned_to = $5)
+              AND ($7::UUID IS NULL OR st.organization_id = $7)
+              AND (COALESCE(m.updated_at, m.created_at), m.id) > ($2, $3)
+              AND COALESCE(m.updated_at, m.created_at) <= $4
+            ORDER BY COALESCE(m.updated_at, m.created_at), m.id
+            LIMIT $6
+            "#,
+        )
+        .bind(&scope.product)
+        .bind(cursor.messages.0)
+        .bind(cursor.messages.1)
+        .bind(settled)
+        .bind(scope.assigned_to)
+        .bind(limit)
+        .bind(scope.organization_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let purged: Vec<(Uuid, DateTime<Utc>)> = sqlx::query_as(
+            r#"
+            SELECT ticket_id, removed_at FROM sync_tombstones
+            WHERE product = $1
+              AND (removed_at, ticket_id) > ($2, $3)
+              AND removed_at <= $4
+              AND ($5::UUID IS NULL OR assigned_to = $5)
+              AND ($7::UUID IS NULL OR organization_id = $7)
+            ORDER BY removed_at, ticket_id
+            LIMIT $6
+            "#,
+        )
+        .bind(&scope.product)
+        .bind(cursor.tombstones.0)
+        .bind(cursor.tombstones.1)
+        .bind(settled)
+        .bind(scope.assigned_to)
+        .bind(limit)
+        .bind(scope.organization_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let has_more = [changed.len(), messages.len(), purged.len()].iter().any(|&n| n as i64 == limit);
+        if let Some(last) = changed.last() {
+            cursor.tickets = (last.updated_at, last.id);
+        }
+        if let Some(last) = messages.last() {
+            cursor.messages = (last.changed_at, last.message.id);
+        }
+        if let Some(&(ticket_id, removed_at)) = purged.last() {
+            cursor.tombstones = (removed_at, ticket_id);
+        }
+
+        let mut tickets = Vec::with_capacity(changed.len());
+        let mut tombstones = Vec::new();
+        for ticket in changed {
+            let reason = if ticket.deleted_at.is_some() {
+                TombstoneReason::Deleted
+            } else if scope.assigned_to.is_some_and(|agent| ticket.assigned_to != Some(agent)) {
+                TombstoneReason::Unassigned
+            } else {
+                tickets.push(ticket);
+                continue;
+            };
+            tombstones.push(SyncTombstone { ticket_id: ticket.id, reason, removed_at: ticket.updated_at });
+        }
+        tombstones.extend(purged.into_iter().map(|(ticket_id, removed_at)| SyncTombstone {
+            ticket_id,
+            reason: TombstoneReason::Purged,
+            removed_at,
+        }));
+
+        Ok(SyncChanges {
+            tickets,
+            messages: messages.into_iter().map(|m| m.message).collect(),
+            tombstones,
+            cursor: cursor.encode(),
+            has_more,
+        })
+    }
+
+    /// Apply changes an agent made offline to tickets of `product`, in order,
+    /// in a single transaction
+    ///
+    /// Each change is applied under its own savepoint and recorded under its
+    /// `change_id`; pushing a recorded change again returns its first result
+    /// without applying it twice. Update fields changed on the server after
+    /// the change's `base_updated_at` by anyone but `agent_id` keep the
+    /// server's value (a `CONFLICT`). Failed changes are `REJECTED`.
+    pub async fn push_local_changes(
+        &self,
+        agent_id: Uuid,
+        product: &str,
+        changes: &[SyncChangeInput],
+    ) -> Result<Vec<SyncChangeResult>> {
+        self.ensure_writable()?;
+        if changes.len() > MAX_PUSH_CHANGES {
+            return Err(SupportError::Validation(format!(
+                "A sync push is limited to {} changes", MAX_PUSH_CHANGES
+            )));
+        }
+
+        let now = self.now();
+        let mut after = AfterCommit::default();
+        let mut tx = self.pool.begin().await?;
+        let mut pushed = Vec::with_capacity(changes.len());
+        for change in changes {
+            let recorded = sqlx::query_as::<_, PushedChange>(
+                r#"
+                SELECT change_id, ticket_id, outcome, conflicting_fields, error, message_id
+                FROM sync_pushed_changes
+                WHERE change_id = $1
+                "#,
+            )
+            .bind(change.change_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+            if let Some(recorded) = recorded {
+                pushed.push(recorded);
+                continue;
+            }
+
+            let mut savepoint = (&mut tx).begin().await?;
+            let mut change_after = AfterCommit::default();
+            let applied = self
+                .apply_local_change(&mut savepoint, agent_id, product, change, now, &mut change_after)
+                .await;
+            let (outcome, conflicting_fields, error, message_id) = match applied {
+                Ok((conflicting_fields, message_id)) => {
+                    savepoint.commit().await?;
+                    after.append(&mut change_after);
+                    let outcome = if conflicting_fields.is_empty() { SyncOutcome::Applied } else { SyncOutcome::Conflict };
+                    (outcome, conflicting_fields, None, message_id)
+                }
+                Err(e) => {
+                    savepoint.rollback().await?;
+                    (SyncOutcome::Rejected, Vec::new(), Some(e.to_string()), None)
+                }
+            };
+
+            let recorded = sqlx::query_as::<_, PushedChange>(
+                r#"
+                INSERT INTO sync_pushed_changes
+                    (change_id, agent_id, ticket_id, outcome, conflicting_fields, error, message_id, pushed_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                RETURNING change_id, ticket_id, outcome, conflicting_fields, error, message_id
+                "#,
+            )
+            .bind(change.change_id)
+            .bind(agent_id)
+            .bind(change.ticket_id)
+            .bind(outcome.as_str())
+            .bind(&conflicting_fields)
+            .bind(&error)
+            .bind(message_id)
+            .bind(now)
+            .fetch_one(&mut *tx)
+            .await?;
+            pushed.push(recorded);
+        }
+        tx.commit().await?;
+        self.after_commit(after).await;
+
+        // Read back after automations, so devices get the tickets as they ended up
+        let ticket_ids: Vec<Uuid> = pushed.iter().map(|p| p.ticket_id).collect();
+        let tickets = self.find_by_ids(&ticket_ids).await?;
+        let results: Vec<SyncChangeResult> = pushed
+            .into_iter()
+            .map(|p| SyncChangeResult {
+                change_id: p.change_id,
+                outcome: p.outcome,
+                conflicting_fields: p.conflicting_fields,
+                error: p.error,
+                ticket: tickets.iter().find(|t| t.id == p.ticket_id).cloned(),
+                message_id: p.message_id,
+            })
+            .collect();
+        tracing::info!(
+            "Applied {} offline changes from agent {} ({} conflicts, {} rejected)",
+            results.len(),
+            agent_id,
+            results.iter().filter(|r| r.outcome == SyncOutcome::Conflict).count(),
+            results.iter().filter(|r| r.outcome == SyncOutcome::Rejected).count()
+        );
+        Ok(results)
+    }
+
+    /// Apply one offline change inside `tx`, returning the update fields
+    /// dropped as conflicts and the message it created
+    async fn apply_local_change(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        agent_id: Uuid,
+        product: &str,
+        change: &SyncChangeInput,
+        now: DateTime<Utc>,
+        after: &mut AfterCommit,
+    ) -> Result<(Vec<String>, Option<Uuid>)> {
+        // Locked so nobody changes the ticket between the conflict check and the update
+        let ticket_product: Option<String> = sqlx::query_scalar(
+            "SELECT product FROM support_tickets WHERE id = $1 AND deleted_at IS NULL FOR UPDATE"
+        )
+        .bind(change.ticket_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+        if ticket_product.as_deref() != Some(product) {
+            return Err(SupportError::TicketNotFound(change.ticket_id));
+        }
+
+        match (&change.update, &change.message) {
+            (Some(update), None) => {
+                let conflicting_fields: Vec<String> = sqlx::query_scalar(
+                    r#"
+                    SELECT DISTINCT field FROM ticket_audit_entries
+                    WHERE ticket_id = $1 AND field = ANY($2) AND created_at > $3
+                      AND actor_id IS DISTINCT FROM $4
+                    ORDER BY field
+                    "#,
+                )
+                .bind(change.ticket_id)
+                .bind(updated_fields(update))
+                .bind(change.base_updated_at)
+                .bind(agent_id)
+                .fetch_all(&mut **tx)
+                .await?;
+
+                let update = without_fields(update, &conflicting_fields);
+                if !updated_fields(&update).is_empty() {
+                    self.update_ticket_in_tx(tx, change.ticket_id, Some(agent_id), &update, PropagationPolicy::default(), now, after)
+                        .await?;
+                }
+                Ok((conflicting_fields, None))
+            }
+            (None, Some(message)) => {
+                let input = AddTicketMessageInput {
+                    ticket_id: change.ticket_id,
+                    content: message.content.clone(),
+                    is_internal: message.is_internal,
+                    visibility: None,
+                    content_format: message.content_format,
+                };
+                let message = self.add_message_in_tx(tx, agent_id, &input, now, after).await?;
+                Ok((Vec::new(), Some(message.id)))
+            }
+            _ => Err(SupportError::Validation("A sync change needs exactly one of update and message".to_string())),
+        }
+    }
+
+    /// Apply a ticket update with its audit entries, propagation and SLA inside `tx`,
+    /// adding the events and automations to run once it commits to `after`
+    #[allow(clippy::too_many_arguments)]
+    async fn update_ticket_in_tx(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        ticket_id: Uuid,
+        actor_id: Option<Uuid>,
+        input: &UpdateTicketInput,
+        policy: PropagationPolicy,
+        now: DateTime<Utc>,
+        after: &mut AfterCommit,
+    ) -> Result<SupportTicket> {
+        validation::validate_ticket_update(input)?;
+        let before = sqlx::query_as::<_, SupportTicket>(
+            "SELECT * FROM support_tickets WHERE id = $1 AND deleted_at IS NULL FOR UPDATE"
+        )
+        .bind(ticket_id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or(SupportError::TicketNotFound(ticket_id))?;
+        if let Some(category) = input.category.as_deref().filter(|c| before.category.as_deref() != Some(*c)) {
+            let config = sqlx::query_as::<_, ProductConfig>("SELECT * FROM product_configs WHERE product = $1")
+                .bind(&before.product)
+                .fetch_optional(&mut **tx)
+                .await?;
+            if let Some(config) = config {
+                validation::check_category(&config, Some(category))?;
+            }
+        }
+        let resolution_code = Self::check_resolution_code(tx, &before, input).await?;
+
+        let ticket = Self::bind_update(sqlx::query_as::<_, SupportTicket>(UPDATE_TICKET_SQL), ticket_id, actor_id, input, now)
+            .bind(resolution_code)
+            .fetch_one(&mut **tx)
+            .await?;
+
+        if before.status != ticket.status {
+            Self::insert_audit_entry(
+                tx, ticket_id, actor_id, "status",
+                Some(before.status.as_str()), Some(ticket.status.as_str()), None, now,
+            ).await?;
+        }
+        if before.priority != ticket.priority {
+            Self::insert_audit_entry(
+                tx, ticket_id, actor_id, "priority",
+                Some(before.priority.as_str()), Some(ticket.priority.as_str()), None, now,
+            ).await?;
+        }
+        if before.assigned_to != ticket.assigned_to {
+            Self::insert_audit_entry(
+                tx, ticket_id, actor_id, "assigned_to",
+                before.assigned_to.map(|id| id.to_string()).as_deref(),
+                ticket.assigned_to.map(|id| id.to_string()).as_deref(),
+                None, now,
+            ).await?;
+        }
+        if before.subject != ticket.subject {
+            Self::insert_audit_entry(
+                tx, ticket_id, actor_id, "subject",
+                Some(&before.subject), Some(&ticket.subject), None, now,
+            ).await?;
+        }
+        // Descriptions can be long; the entry records who changed it and when
+        if before.description != ticket.description {
+            Self::insert_audit_entry(tx, ticket_id, actor_id, "description", None, None, None, now).await?;
+        }
+        if before.category != ticket.category {
+            Self::insert_audit_entry(
+                tx, ticket_id, actor_id, "category",
+                before.category.as_deref(), ticket.category.as_deref(), None, now,
+            ).await?;
+        }
+        if before.resolution_code != ticket.resolution_code {
+            Self::insert_audit_entry(
+                tx, ticket_id, actor_id, "resolution_code",
+                before.resolution_code.as_deref(), ticket.resolution_code.as_deref(), None, now,
+            ).await?;
+        }
+
+        if policy.priority && input.priority.is_some() {
+            let count = Self::propagate_to_children(tx, ticket_id, actor_id, "priority", now).await?;
+            tracing::info!("Propagated priority of ticket {} to {} child tickets", ticket_id, count);
+        }
+        if policy.status && input.status.is_some() {
+            let count = Self::propagate_to_children(tx, ticket_id, actor_id, "status", now).await?;
+            tracing::info!("Propagated status of ticket {} to {} child tickets", ticket_id, count);
+        }
+
+        let ticket = self.apply_sla(tx, &[ticket_id], now).await?
+            .into_iter()
+            .find(|t| t.id == ticket_id)
+            .unwrap_or(ticket);
+
+        after.events.push(SupportEvent::ticket(SupportEventType::TicketUpdated, &ticket, now));
+        if before.status != ticket.status {
+            after.automations.push(AutomationFiring::status_changed(ticket_id, before.status));
+        }
+        // Same transitions that credit a resolving agent
+        let resolved = match ticket.status {
+            TicketStatus::Resolved => before.status != TicketStatus::Resolved,
+            TicketStatus::Closed => !matches!(before.status, TicketStatus::Resolved | TicketStatus::Closed),
+            _ => false,
+        };
+        if resolved {
+            after.events.push(SupportEvent::ticket(SupportEventType::TicketResolved, &ticket, now));
+        }
+
+        Ok(ticket)
+    }
+
+    /// Normalize an update's resolution code and check it against the product's taxonomy
+    ///
+    /// Resolving a ticket of a product with active codes needs one, given on
+    /// the update or kept from an earlier resolution.
+    async fn check_resolution_code(
+        tx: &mut Transaction<'_, Postgres>,
+        before: &SupportTicket,
+        input: &UpdateTicketInput,
+    ) -> Result<Option<String>> {
+        let code = input.resolution_code.as_deref().map(normalize_resolution_code).transpose()?;
+        let status = input.status.unwrap_or(before.status);
+        if code.is_some() && !matches!(status, TicketStatus::Resolved | TicketStatus::Closed) {
+            return Err(SupportError::Validation(
+                "Resolution codes can only be set on resolved or closed tickets".to_string(),
+            ));
+        }
+        let resolving = status == TicketStatus::Resolved && before.status != TicketStatus::Resolved;
+        if code.is_none() && !resolving {
+            return Ok(None);
+        }
+
+        let active: Vec<String> = sqlx::query_scalar(
+            "SELECT code FROM resolution_codes WHERE product = $1 AND active"
+        )
+        .bind(&before.product)
+        .fetch_all(&mut **tx)
+        .await?;
+        match &code {
+            Some(code) if !active.contains(code) => Err(SupportError::Validation(format!(
+                "Unknown resolution code {} for product {}", code, before.product
+            ))),
+            None if !active.is_empty() && !before.resolution_code.as_ref().is_some_and(|c| active.contains(c)) => {
+                Err(SupportError::Validation(format!(
+                    "Resolving a {} ticket requires a resolution code", before.product
+                )))
+            }
+            _ => Ok(code),
+        }
+    }
+
+    fn bind_update<'q>(
+        query: sqlx::query::QueryAs<'q, Postgres, SupportTicket, PgArguments>,
+        ticket_id: Uuid,
+        actor_id: Option<Uuid>,
+        input: &'q UpdateTicketInput,
+        now: DateTime<Utc>,
+    ) -> sqlx::query::QueryAs<'q, Postgres, SupportTicket, PgArguments> {
+        query
+            .bind(ticket_id)
+            .bind(&input.subject)
+            .bind(&input.description)
+            .bind(input.status)
+            .bind(input.priority)
+            .bind(&input.category)
+            .bind(input.assigned_to)
+            .bind(now)
+            .bind(actor_id)
+    }
+
+    /// Copy a parent column onto its children, writing one audit entry per changed child.
+    ///
+    /// `column` must be one of the trusted column names used by this module.
+    async fn propagate_to_children(
+        tx: &mut Transaction<'_, Postgres>,
+        parent_id: Uuid,
+        actor_id: Option<Uuid>,
+        column: &'static str,
+        now: DateTime<Utc>,
+    ) -> Result<u64> {
+        let (skip_closed, status_timestamps) = if column == "status" {
+            (
+                "AND status <> 'CLOSED'",
+                r#",
+                    resolved_at = CASE WHEN parent.status = 'RESOLVED' AND st.resolved_at IS NULL THEN $3 ELSE st.resolved_at END,
+                    closed_at = CASE WHEN parent.status = 'CLOSED' AND st.closed_at IS NULL THEN $3 ELSE st.closed_at END,
+                    resolving_agent_id = CASE
+                        WHEN parent.status IN ('RESOLVED', 'CLOSED') AND st.status <> 'RESOLVED' THEN parent.resolving_agent_id
+                        ELSE st.resolving_agent_id
+                    END,
+                    resolution_code = CASE
+                        WHEN parent.status IN ('RESOLVED', 'CLOSED') THEN COALESCE(parent.resolution_code, st.resolution_code)
+                        ELSE st.resolution_code
+                    END,
+                    waiting_since = CASE
+                        WHEN parent.status = 'WAITING_ON_CUSTOMER' THEN COALESCE(st.waiting_since, $3)
+                        ELSE NULL
+                    END,
+                    customer_wait_seconds = CASE
+                        WHEN parent.status <> 'WAITING_ON_CUSTOMER' AND st.waiting_since IS NOT NULL
+                            THEN st.customer_wait_seconds + EXTRACT(EPOCH FROM ($3 - st.waiting_since))::BIGINT
+                        ELSE st.customer_wait_seconds
+                    END"#,
+            )
+        } else {
+            ("", "")
+        };
+        let query = format!(
+            r#"
+            WITH children AS (
+                SELECT id, {column} AS old_value
+                FROM support_tickets
+                WHERE parent_ticket_id = $1 AND deleted_at IS NULL {skip_closed}
+                FOR UPDATE
+            ),
+            updated AS (
+                UPDATE support_tickets st
+                SET {column} = parent.{column},
+                    updated_at = $3{status_timestamps}
+                FROM children, support_tickets parent
+                WHERE st.id = children.id
+                  AND parent.id = $1
+                  AND st.{column} IS DISTINCT FROM parent.{column}
+                RETURNING st.id, children.old_value::TEXT AS old_value, st.{column}::TEXT AS new_value
+            )
+            INSERT INTO ticket_audit_entries (ticket_id, actor_id, field, old_value, new_value, source_ticket_id, created_at)
+            SELECT id, $2, '{column}', old_value, new_value, $1, $3 FROM updated
+            "#,
+        );
+
+        let result = sqlx::query(&query)
+            .bind(parent_id)
+            .bind(actor_id)
+            .bind(now)
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to propagate {} to child tickets: {}", column, e);
+                SupportError::from(e)
+            })?;
+
+        Ok(result.rows_affected())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn insert_audit_entry(
+        tx: &mut Transaction<'_, Postgres>,
+        ticket_id: Uuid,
+        actor_id: Option<Uuid>,
+        field: &str,
+        old_value: Option<&str>,
+        new_value: Option<&str>,
+        source_ticket_id: Option<Uuid>,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO ticket_audit_entries (ticket_id, actor_id, field, old_value, new_value, source_ticket_id, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(ticket_id)
+        .bind(actor_id)
+        .bind(field)
+        .bind(old_value)
+        .bind(new_value)
+        .bind(source_ticket_id)
+        .bind(now)
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Link a child ticket to a parent (incident) ticket
+    ///
+    /// Both must belong to the same product and organization, since parent
+    /// changes propagate to children; links across them fail with `Validation`.
+    pub async fn link_child_ticket(&self, parent_id: Uuid, child_id: Uuid) -> Result<SupportTicket> {
+        self.ensure_writable()?;
+        if parent_id == child_id {
+            return Err(SupportError::InvalidInput("A ticket cannot be its own parent".to_string()));
+        }
+
+        let parent = self.find_by_id(parent_id).await?;
+        if parent.parent_ticket_id.is_some() {
+            return Err(SupportError::Validation(format!(
+                "Ticket {} is itself a child ticket and cannot have children", parent_id
+            )));
+        }
+
+        let ticket = sqlx::query_as::<_, SupportTicket>(
+            r#"
+            UPDATE support_tickets SET parent_ticket_id = $2, linked_at = $3
+            WHERE id = $1 AND deleted_at IS NULL
+              AND product = $4 AND organization_id IS NOT DISTINCT FROM $5
+              AND NOT EXISTS (SELECT 1 FROM support_tickets WHERE parent_ticket_id = $1 AND deleted_at IS NULL)
+            RETURNING *
+            "#,
+        )
+        .bind(child_id)
+        .bind(parent_id)
+        .bind(self.now())
+        .bind(&parent.product)
+        .bind(parent.organization_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match ticket {
+            Some(ticket) => Ok(ticket),
+            None => {
+                // Distinguish a missing child, one of another product or
+                // organization, and one that has children of its own
+                let child = self.find_by_id(child_id).await?;
+                if child.product != parent.product || child.organization_id != parent.organization_id {
+                    return Err(SupportError::Validation(format!(
+                        "Ticket {} belongs to another product or organization than ticket {}", child_id, parent_id
+                    )));
+                }
+                Err(SupportError::Validation(format!(
+                    "Ticket {} has child tickets and cannot be linked to a parent", child_id
+                )))
+            }
+        }
+    }
+
+    /// Remove a child ticket's link to its parent
+    pub async fn unlink_child_ticket(&self, child_id: Uuid) -> Result<SupportTicket> {
+        self.ensure_writable()?;
+        let ticket = sqlx::query_as::<_, SupportTicket>(
+            r#"
+            UPDATE support_tickets SET parent_ticket_id = NULL, linked_at = NULL
+            WHERE id = $1 AND deleted_at IS NULL
+            RETURNING *
+            "#,
+        )
+        .bind(child_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => SupportError::TicketNotFound(child_id),
+            _ => SupportError::from(e)
+        })?;
+
+        Ok(ticket)
+    }
+
+    /// Get child tickets linked to a parent ticket
+    pub async fn get_child_tickets(&self, parent_id: Uuid) -> Result<Vec<SupportTicket>> {
+        let tickets = sqlx::query_as::<_, SupportTicket>(
+            "SELECT * FROM support_tickets WHERE parent_ticket_id = $1 AND deleted_at IS NULL ORDER BY created_at ASC"
+        )
+        .bind(parent_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(tickets)
+    }
+
+    /// Lock and check the tickets of a merge: all live, in one product, none merged already
+    ///
+    /// Returns the primary ticket and the distinct duplicates, oldest first.
+    async fn merge_candidates(
+        conn: &mut PgConnection,
+        primary_id: Uuid,
+        duplicate_ids: &[Uuid],
+        lock: bool,
+    ) -> Result<(SupportTicket, Vec<SupportTicket>)> {
+        let mut duplicate_ids = duplicate_ids.to_vec();
+        duplicate_ids.sort();
+        duplicate_ids.dedup();
+        if duplicate_ids.is_empty() {
+            return Err(SupportError::InvalidInput("At least one duplicate ticket is required".to_string()));
+        }
+        if duplicate_ids.len() > MAX_MERGE_DUPLICATES {
+            return Err(SupportError::InvalidInput(format!(
+                "At most {} tickets can be merged at once", MAX_MERGE_DUPLICATES
+            )));
+        }
+        if duplicate_ids.contains(&primary_id) {
+            return Err(SupportError::InvalidInput("A ticket cannot be merged into itself".to_string()));
+        }
+
+        let ticket_ids: Vec<Uuid> = std::iter::once(primary_id).chain(duplicate_ids.iter().copied()).collect();
+        let query = format!(
+            "SELECT * FROM support_tickets WHERE id = ANY($1) AND deleted_at IS NULL ORDER BY id {}",
+            if lock { "FOR UPDATE" } else { "" },
+        );
+        let mut tickets = sqlx::query_as::<_, SupportTicket>(&query)
+            .bind(&ticket_ids)
+            .fetch_all(&mut *conn)
+            .await?;
+        if let Some(missing) = ticket_ids.iter().find(|id| !tickets.iter().any(|t| t.id == **id)) {
+            return Err(SupportError::TicketNotFound(*missing));
+        }
+
+        let primary_index = tickets.iter().position(|t| t.id == primary_id).unwrap_or_default();
+        let primary = tickets.swap_remove(primary_index);
+        if let Some(merged_into) = primary.merged_into {
+            return Err(SupportError::Validation(format!(
+                "Ticket {} is merged into {} and cannot take duplicates", primary.ticket_ref, merged_into
+            )));
+        }
+        for duplicate in &tickets {
+            if duplicate.product != primary.product {
+                return Err(SupportError::Validation(format!(
+                    "Ticket {} belongs to another product than {}", duplicate.ticket_ref, primary.ticket_ref
+                )));
+            }
+            if let Some(merged_into) = duplicate.merged_into {
+                return Err(SupportError::Validation(format!(
+                    "Ticket {} is already merged into {}", duplicate.ticket_ref, merged_into
+                )));
+            }
+        }
+        tickets.sort_by_key(|t| (t.created_at, t.id));
+
+        Ok((primary, tickets))
+    }
+
+    /// The result of merging `duplicate_ids` into `primary_id`, without merging
+    ///
+    /// Runs the same checks as [`merge_tickets`](Self::merge_tickets), so a
+    /// preview that succeeds describes a merge that would succeed now.
+    pub async fn preview_merge(&self, primary_id: Uuid, duplicate_ids: &[Uuid]) -> Result<MergePreview> {
+        let mut tx = self.begin_timed(OperationClass::Read).await?;
+        let (primary, duplicates) = Self::merge_candidates(&mut tx, primary_id, duplicate_ids, false).await?;
+
+        let ticket_ids: Vec<Uuid> = std::iter::once(primary.id).chain(duplicates.iter().map(|t| t.id)).collect();
+        let thread = sqlx::query_as::<_, TicketMessage>(
+            "SELECT * FROM ticket_messages WHERE ticket_id = ANY($1) ORDER BY created_at ASC, id ASC"
+        )
+        .bind(&ticket_ids)
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        let moved_message_count = thread.iter().filter(|m| m.ticket_id != primary.id).count() as i64;
+        Ok(MergePreview { primary, duplicates, thread, moved_message_count })
+    }
+
+    /// Merge duplicates into a primary ticket
+    ///
+    /// The duplicates' messages move to the primary, and each duplicate is
+    /// closed with `merged_into` set. What changed is recorded so that
+    /// [`undo_merge`](Self::undo_merge) can restore the duplicates within the
+    /// configured undo window.
+    pub async fn merge_tickets(
+        &self,
+        primary_id: Uuid,
+        duplicate_ids: &[Uuid],
+        merged_by: Option<Uuid>,
+    ) -> Result<TicketMerge> {
+        self.ensure_writable()?;
+        let now = self.now();
+        let mut tx = self.pool.begin().await?;
+        let (primary, duplicates) = Self::merge_candidates(&mut tx, primary_id, duplicate_ids, true).await?;
+
+        let merge_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO ticket_merges (product, primary_ticket_id, merged_by, merged_at, undo_until)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id
+            "#,
+        )
+        .bind(&primary.product)
+        .bind(primary.id)
+        .bind(merged_by)
+        .bind(now)
+        .bind(now + self.merge_settings.undo_window)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let mut events = Vec::with_capacity(duplicates.len());
+        for duplicate in &duplicates {
+            let message_ids: Vec<Uuid> = sqlx::query_scalar(
+                "UPDATE ticket_messages SET ticket_id = $2, updated_at = $3 WHERE ticket_id = $1 RETURNING id"
+            )
+            .bind(duplicate.id)
+            .bind(primary.id)
+            .bind(now)
+            .fetch_all(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO ticket_merge_duplicates (merge_id, ticket_id, previous_status, previous_closed_at, message_ids)
+                VALUES ($1, $2, $3, $4, $5)
+                "#,
+            )
+            .bind(merge_id)
+            .bind(duplicate.id)
+            .bind(duplicate.status)
+            .bind(duplicate.closed_at)
+            .bind(&message_ids)
+            .execute(&mut *tx)
+            .await?;
+
+            let closed = sqlx::query_as::<_, SupportTicket>(
+                r#"
+                UPDATE support_tickets
+                SET status = 'CLOSED',
+                    closed_at = CASE WHEN status = 'CLOSED' THEN closed_at ELSE $3 END,
+                    merged_into = $2,
+                    waiting_since = NULL,
+                    customer_wait_seconds = CASE
+                        WHEN waiting_since IS NOT NULL
+                            THEN customer_wait_seconds + EXTRACT(EPOCH FROM ($3 - waiting_since))::BIGINT
+                        ELSE customer_wait_seconds
+                    END,
+                    updated_at = $3
+                WHERE id = $1
+                RETURNING *
+                "#,
+            )
+            .bind(duplicate.id)
+            .bind(primary.id)
+            .bind(now)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            if duplicate.status != TicketStatus::Closed {
+                Self::insert_audit_entry(
+                    &mut tx, duplicate.id, merged_by, "status",
+                    Some(duplicate.status.as_str()), Some(closed.status.as_str()), Some(primary.id), now,
+                ).await?;
+            }
+            Self::insert_audit_entry(
+                &mut tx, duplicate.id, merged_by, "merged_into", None, Some(&primary.ticket_ref), Some(primary.id), now,
+            ).await?;
+            Self::insert_audit_entry(
+                &mut tx, primary.id, merged_by, "merged_ticket", None, Some(&duplicate.ticket_ref), Some(duplicate.id), now,
+            ).await?;
+            events.push(SupportEvent::ticket(SupportEventType::TicketUpdated, &closed, now));
+        }
+
+        Self::refresh_search_vectors(&mut tx, primary.id, &duplicates).await?;
+        let merge = Self::fetch_ticket_merge(&mut tx, merge_id, false).await?;
+        tx.commit().await?;
+        tracing::info!("Merged {} tickets into {}", duplicates.len(), primary.ticket_ref);
+        self.publish_events(events);
+
+        Ok(merge)
+    }
+
+    /// Undo a merge within its window, restoring the duplicates
+    ///
+    /// Each duplicate gets back its status, close time and the messages moved
+    /// from it; replies added to the primary since the merge stay there.
+    pub async fn undo_merge(&self, merge_id: Uuid, undone_by: Option<Uuid>) -> Result<TicketMerge> {
+        self.ensure_writable()?;
+        let now = self.now();
+        let mut tx = self.pool.begin().await?;
+
+        let merge = Self::fetch_ticket_merge(&mut tx, merge_id, true).await?;
+        if merge.undone_at.is_some() {
+            return Err(SupportError::Validation(format!("Ticket merge {} was already undone", merge_id)));
+        }
+        if now > merge.undo_until {
+            return Err(SupportError::Validation(format!(
+                "Ticket merge {} can no longer be undone; its window closed at {}", merge_id, merge.undo_until
+            )));
+        }
+
+        let primary = sqlx::query_as::<_, SupportTicket>("SELECT * FROM support_tickets WHERE id = $1 FOR UPDATE")
+            .bind(merge.primary_ticket_id)
+            .fetch_one(&mut *tx)
+            .await?;
+        let merged = sqlx::query_as::<_, MergedDuplicate>(
+            r#"
+            SELECT ticket_id, previous_status, previous_closed_at, message_ids
+            FROM ticket_merge_duplicates
+            WHERE merge_id = $1
+            ORDER BY ticket_id
+            "#,
+        )
+        .bind(merge_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut restored = Vec::with_capacity(merged.len());
+        let mut events = Vec::with_capacity(merged.len());
+        for duplicate in merged {
+            sqlx::query("UPDATE ticket_messages SET ticket_id = $2, updated_at = $4 WHERE id = ANY($1) AND ticket_id = $3")
+                .bind(&duplicate.message_ids)
+                .bind(duplicate.ticket_id)
+                .bind(primary.id)
+                .bind(now)
+                .execute(&mut *tx)
+                .await?;
+
+            let ticket = sqlx::query_as::<_, SupportTicket>(
+                r#"
+                UPDATE support_tickets
+                SET status = $2,
+                    closed_at = $3,
+                    merged_into = NULL,
+                    waiting_since = CASE WHEN $2::ticket_status = 'WAITING_ON_CUSTOMER' THEN $4 END,
+                    updated_at = $4
+                WHERE id = $1 AND merged_into = $5
+                RETURNING *
+                "#,
+            )
+            .bind(duplicate.ticket_id)
+            .bind(duplicate.previous_status)
+            .bind(duplicate.previous_closed_at)
+            .bind(now)
+            .bind(primary.id)
+            .fetch_optional(&mut *tx)
+            .await?;
+            let Some(ticket) = ticket else {
+                continue;
+            };
+
+            if duplicate.previous_status != TicketStatus::Closed {
+                Self::insert_audit_entry(
+                    &mut tx, ticket.id, undone_by, "status",
+                    Some(TicketStatus::Closed.as_str()), Some(ticket.status.as_str()), Some(primary.id), now,
+                ).await?;
+            }
+            Self::insert_audit_entry(
+                &mut tx, ticket.id, undone_by, "merged_into", Some(&primary.ticket_ref), None, Some(primary.id), now,
+            ).await?;
+            Self::insert_audit_entry(
+                &mut tx, primary.id, undone_by, "merged_ticket", Some(&ticket.ticket_ref), None, Some(ticket.id), now,
+            ).await?;
+            events.push(SupportEvent::ticket(SupportEventType::TicketUpdated, &ticket, now));
+            restored.push(ticket);
+        }
+
+        sqlx::query("UPDATE ticket_merges SET undone_by = $2, undone_at = $3 WHERE id = $1")
+            .bind(merge_id)
+            .bind(undone_by)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        Self::refresh_search_vectors(&mut tx, primary.id, &restored).await?;
+        let merge = Self::fetch_ticket_merge(&mut tx, merge_id, false).await?;
+        tx.commit().await?;
+        tracing::info!("Undid merge of {} tickets into {}", restored.len(), primary.ticket_ref);
+        self.publish_events(events);
+
+        Ok(merge)
+    }
+
+    /// Merges into a primary ticket, newest first
+    pub async fn get_ticket_merges(&self, primary_id: Uuid) -> Result<Vec<TicketMerge>> {
+        let merges = sqlx::query_as::<_, TicketMerge>(&format!(
+            "{} WHERE m.primary_ticket_id = $1 ORDER BY m.merged_at DESC, m.id DESC",
+            TICKET_MERGE_SELECT,
+        ))
+        .bind(primary_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(merges)
+    }
+
+    /// A merge by ID, as returned by `merge_tickets`
+    pub async fn get_ticket_merge(&self, merge_id: Uuid) -> Result<TicketMerge> {
+        let mut conn = self.pool.acquire().await?;
+        Self::fetch_ticket_merge(&mut conn, merge_id, false).await
+    }
+
+    async fn fetch_ticket_merge(conn: &mut PgConnection, merge_id: Uuid, lock: bool) -> Result<TicketMerge> {
+        let query = format!(
+            "{} WHERE m.id = $1 {}",
+            TICKET_MERGE_SELECT,
+            if lock { "FOR UPDATE OF m" } else { "" },
+        );
+        sqlx::query_as::<_, TicketMerge>(&query)
+            .bind(merge_id)
+            .fetch_optional(&mut *conn)
+            .await?
+            .ok_or_else(|| SupportError::Validation(format!("Ticket merge not found: {}", merge_id)))
+    }
+
+    /// Re-index tickets whose messages moved; message triggers only see content changes
+    async fn refresh_search_vectors(conn: &mut PgConnection, primary_id: Uuid, tickets: &[SupportTicket]) -> Result<()> {
+        let ticket_ids: Vec<Uuid> = std::iter::once(primary_id).chain(tickets.iter().map(|t| t.id)).collect();
+        sqlx::query(
+            r#"
+            UPDATE support_tickets
+            SET search_vector = support_ticket_search_vector(id, subject, description, search_config)
+            WHERE id = ANY($1)
+            "#,
+        )
+        .bind(&ticket_ids)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Get audit entries for a ticket, oldest first
+    pub async fn get_audit_entries(&self, ticket_id: Uuid) -> Result<Vec<TicketAuditEntry>> {
+        let entries = sqlx::query_as::<_, TicketAuditEntry>(
+            "SELECT * FROM ticket_audit_entries WHERE ticket_id = $1 ORDER BY created_at ASC"
+        )
+        .bind(ticket_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(entries)
+    }
+
+    /// List tickets with filters
+    pub async fn list(&self, product: &str, filter: &TicketFilter, limit: i64, offset: i64) -> Result<Vec<SupportTicket>> {
+        let mut tx = self.begin_timed(OperationClass::Read).await?;
+        let tickets = Self::fetch_ticket_page(&mut tx, product, filter, TicketProjection::FULL, None, limit, offset).await?;
+        tx.commit().await?;
+
+        Ok(tickets)
+    }
+
+    /// One page of tickets, newest first, as a Relay connection
+    ///
+    /// Returns up to `first` tickets after `after` along with the total number
+    /// matching `filter`; both are read in the same transaction.
+    pub async fn list_page(
+        &self,
+        product: &str,
+        filter: &TicketFilter,
+        first: i64,
+        after: Option<TicketCursor>,
+    ) -> Result<TicketConnection> {
+        self.list_page_projected(product, filter, first, after, TicketProjection::FULL, true).await
+    }
+
+    /// One page of tickets as `list_page` returns it, loading only the wide
+    /// columns of `projection`
+    ///
+    /// Without `count_total` the `COUNT(*)` over the filter is skipped and
+    /// `total_count` is 0.
+    pub async fn list_page_projected(
+        &self,
+        product: &str,
+        filter: &TicketFilter,
+        first: i64,
+        after: Option<TicketCursor>,
+        projection: TicketProjection,
+        count_total: bool,
+    ) -> Result<TicketConnection> {
+        let first = first.max(0);
+        let mut tx = self.begin_timed(OperationClass::Read).await?;
+
+        let after_key = after.map(|cursor| (cursor.created_at, cursor.id));
+        let mut tickets = Self::fetch_ticket_page(&mut tx, product, filter, projection, after_key, first + 1, 0).await?;
+
+        let total_count = if count_total {
+            let mut count_query = String::from(
+                "SELECT COUNT(*)::BIGINT FROM support_tickets WHERE product = $1"
+            );
+            Self::push_filter_conditions(&mut count_query, filter, 1);
+            let (total_count,) = Self::bind_filter(sqlx::query_as::<_, (i64,)>(&count_query).bind(product), filter)
+                .fetch_one(&mut *tx)
+                .await?;
+            total_count
+        } else {
+            0
+        };
+
+        tx.commit().await?;
+
+        let has_next_page = tickets.len() as i64 > first;
+        tickets.truncate(first as usize);
+
+        let edges: Vec<TicketEdge> = tickets
+            .into_iter()
+            .map(|ticket| TicketEdge { cursor: TicketCursor::of(&ticket).encode(), node: ticket })
+            .collect();
+
+        Ok(TicketConnection {
+            page_info: PageInfo {
+                has_next_page,
+                has_previous_page: after.is_some(),
+                start_cursor: edges.first().map(|edge| edge.cursor.clone()),
+                end_cursor: edges.last().map(|edge| edge.cursor.clone()),
+            },
+            edges,
+            total_count,
+        })
+    }
+
+    /// Stream every ticket matching `filter`, newest first
+    ///
+    /// Tickets are fetched in keyset-paged batches of `page_size`, each in its
+    /// own read-timed transaction, so no connection is held between batches
+    /// and dropping the stream abandons the export cleanly.
+    pub fn stream_tickets<'a>(
+        &'a self,
+        product: &'a str,
+        filter: &'a TicketFilter,
+        page_size: i64,
+    ) -> impl Stream<Item = Result<SupportTicket>> + 'a {
+        let page_size = page_size.max(1);
+        stream::try_unfold(Some(None), move |cursor: Option<Option<(DateTime<Utc>, Uuid)>>| async move {
+            let Some(after) = cursor else {
+                return Ok::<_, SupportError>(None);
+            };
+            let mut tx = self.begin_timed(OperationClass::Read).await?;
+            let tickets = Self::fetch_ticket_page(&mut tx, product, filter, TicketProjection::FULL, after, page_size, 0).await?;
+            tx.commit().await?;
+
+            let next = match tickets.last() {
+                Some(last) if tickets.len() as i64 == page_size => Some(Some((last.created_at, last.id))),
+                _ => None,
+            };
+            Ok(Some((stream::iter(tickets.into_iter().map(Ok)), next)))
+        })
+        .try_flatten()
+    }
+
+    /// One page of tickets, newest first, optionally after a `(created_at, id)` key
+    async fn fetch_ticket_page(
+        conn: &mut PgConnection,
+        product: &str,
+        filter: &TicketFilter,
+        projection: TicketProjection,
+        after: Option<(DateTime<Utc>, Uuid)>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<SupportTicket>> {
+        let mut query = format!(
+            "SELECT {} FROM support_tickets WHERE product = $1",
+            projection.columns()
+        );
+        let mut params_count = Self::push_filter_conditions(&mut query, filter, 1);
+
+        if after.is_some() {
+            query.push_str(&format!(" AND (created_at, id) < (${}, ${})", params_count + 1, params_count + 2));
+            params_count += 2;
+        }
+
+        query.push_str(" ORDER BY created_at DESC, id DESC");
+        query.push_str(&format!(" LIMIT ${} OFFSET ${}", params_count + 1, params_count + 2));
+
+        let mut q = Self::bind_filter(sqlx::query_as::<_, SupportTicket>(&query).bind(product), filter);
+
+        if let Some((created_at, id)) = after {
+            q = q.bind(created_at).bind(id);
+        }
+
+        q = q.bind(limit).bind(offset);
+
+        let tickets = q.fetch_all(&mut *conn).await?;
+
+        Ok(tickets)
+    }
+
+    /// Full-text search over ticket subjects, descriptions, public messages
+    /// and their attachments' text
+    ///
+    /// `query` uses web-search syntax (`"exact phrase"`, `-excluded`, `or`).
+    /// Results are ordered by relevance, with subject matches weighted above
+    /// description matches, those above message matches and those above
+    /// attachment matches; `filter` narrows them further.
+    pub async fn search_tickets(
+        &self,
+        product: &str,
+        query: &str,
+        filter: &TicketFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<TicketSearchResult>> {
+        self.search_tickets_projected(product, query, filter, limit, offset, TicketProjection::FULL).await
+    }
+
+    /// Search tickets as `search_tickets` does, loading only the wide columns of `projection`
+    pub async fn search_tickets_projected(
+        &self,
+        product: &str,
+        query: &str,
+        filter: &TicketFilter,
+        limit: i64,
+        offset: i64,
+        projection: TicketProjection,
+    ) -> Result<Vec<TicketSearchResult>> {
+        if query.trim().is_empty() {
+            return Err(SupportError::Validation("Search query is required".to_string()));
+        }
+
+        let mut sql = format!(
+            r#"
+            SELECT {}, ts_rank_cd(search_vector, q.query)::FLOAT8 as rank
+            FROM support_tickets, support_search_query($2) AS q(query)
+            WHERE product = $1 AND search_vector @@ q.query
+            "#,
+            projection.columns()
+        );
+        let params_count = Self::push_filter_conditions(&mut sql, filter, 2);
+        sql.push_str(" ORDER BY rank DESC, created_at DESC, id DESC");
+        sql.push_str(&format!(" LIMIT ${} OFFSET ${}", params_count + 1, params_count + 2));
+
+        let q = sqlx::query_as::<_, TicketSearchResult>(&sql)
+            .bind(product)
+            .bind(query);
+        let q = Self::bind_filter(q, filter).bind(limit).bind(offset);
+
+        let mut tx = self.begin_timed(OperationClass::Read).await?;
+        let results = q.fetch_all(&mut *tx).await?;
+        tx.commit().await?;
+
+        Ok(results)
+    }
+
+    /// Append `filter`'s conditions to `query`, whose last placeholder so far
+    /// is `$params_count`; returns the new last placeholder
+    fn push_filter_conditions(query: &mut String, filter: &TicketFilter, mut params_count: usize) -> usize {
+        if !filter.include_deleted {
+            query.push_str(" AND deleted_at IS NULL");
+        }
+
+        if filter.status.is_some() {
+            params_count += 1;
+            query.push_str(&format!(" AND status = ${}", params_count));
+        }
+
+        if filter.priority.is_some() {
+            params_count += 1;
+            query.push_str(&format!(" AND priority = ${}", params_count));
+        }
+
+        if filter.assigned_to.is_some() {
+            params_count += 1;
+            query.push_str(&format!(" AND assigned_to = ${}", params_count));
+        }
+
+        if filter.customer_id.is_some() {
+            params_count += 1;
+            query.push_str(&format!(" AND customer_id = ${}", params_count));
+        }
+
+        if filter.organization_id.is_some() {
+            params_count += 1;
+            query.push_str(&format!(" AND organization_id = ${}", params_count));
+        }
+
+        if filter.category.is_some() {
+            params_count += 1;
+            query.push_str(&format!(" AND category = ${}", params_count));
+        }
+
+        if filter.search_query.is_some() {
+            params_count += 1;
+            query.push_str(&format!(" AND search_vector @@ support_search_query(${})", params_count));
+        }
+
+        if filter.app_version.is_some() {
+            params_count += 1;
+            query.push_str(&format!(" AND metadata->'client'->>'app_version' = ${}", params_count));
+        }
+
+        if filter.platform.is_some() {
+            params_count += 1;
+            query.push_str(&format!(" AND metadata->'client'->>'platform' = ${}", params_count));
+        }
+
+        params_count
+    }
+
+    /// Bind the parameters added by [`Self::push_filter_conditions`], in order
+    fn bind_filter<'q, O>(
+        mut q: sqlx::query::QueryAs<'q, Postgres, O, PgArguments>,
+        filter: &'q TicketFilter,
+    ) -> sqlx::query::QueryAs<'q, Postgres, O, PgArguments> {
+        if let Some(status) = &filter.status {
+            q = q.bind(status);
+        }
+        if let Some(priority) = &filter.priority {
+            q = q.bind(priority);
+        }
+        if let Some(assigned_to) = filter.assigned_to {
+            q = q.bind(assigned_to);
+        }
+        if let Some(customer_id) = filter.customer_id {
+            q = q.bind(customer_id);
+        }
+        if let Some(organization_id) = filter.organization_id {
+            q = q.bind(organization_id);
+        }
+        if let Some(category) = &filter.category {
+            q = q.bind(category);
+        }
+        if let Some(search_query) = &filter.search_query {
+            q = q.bind(search_query);
+        }
+        if let Some(app_version) = &filter.app_version {
+            q = q.bind(app_version);
+        }
+        if let Some(platform) = filter.platform {
+            q = q.bind(platform);
+        }
+
+        q
+    }
+
+    /// Save a named filter for an agent's sidebar
+    pub async fn create_saved_view(&self, product: &str, owner_id: Uuid, input: &SaveViewInput) -> Result<SavedView> {
+        self.ensure_writable()?;
+        if input.name.trim().is_empty() {
+            return Err(SupportError::Validation("View name is required".to_string()));
+        }
+
+        let filter = &input.filter;
+        let view = sqlx::query_as::<_, SavedView>(
+            r#"
+            INSERT INTO saved_views (
+                product, owner_id, name, status, priority, assigned_to, customer_id, category, search_query,
+                notify_on_match, last_evaluated_at, created_at, updated_at, app_version, platform, sort_order,
+                organization_id
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $11, $11, $12, $13, $14, $15)
+            RETURNING *
+            "#,
+        )
+        .bind(product)
+        .bind(owner_id)
+        .bind(input.name.trim())
+        .bind(filter.status)
+        .bind(filter.priority)
+        .bind(filter.assigned_to)
+        .bind(filter.customer_id)
+        .bind(&filter.category)
+        .bind(&filter.search_query)
+        .bind(input.notify_on_match)
+        .bind(self.now())
+        .bind(&filter.app_version)
+        .bind(filter.platform)
+        .bind(input.sort_order)
+        .bind(filter.organization_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(view)
+    }
+
+    /// Rename a saved view or change its filter or sort order
+    ///
+    /// Fails with `Validation` when the owner has no such view.
+    pub async fn update_saved_view(&self, view_id: Uuid, owner_id: Uuid, input: &UpdateSavedViewInput) -> Result<SavedView> {
+        self.ensure_writable()?;
+        let name = input.name.as_deref().map(str::trim);
+        if name.is_some_and(str::is_empty) {
+            return Err(SupportError::Validation("View name is required".to_string()));
+        }
+
+        let filter = input.filter.as_ref();
+        let view = sqlx::query_as::<_, SavedView>(
+            r#"
+            UPDATE saved_views
+            SET name = COALESCE($3, name),
+                status = CASE WHEN $4 THEN $5 ELSE status END,
+                priority = CASE WHEN $4 THEN $6 ELSE priority END,
+                assigned_to = CASE WHEN $4 THEN $7 ELSE assigned_to END,
+                customer_id = CASE WHEN $4 THEN $8 ELSE customer_id END,
+                category = CASE WHEN $4 THEN $9 ELSE category END,
+                search_query = CASE WHEN $4 THEN $10 ELSE search_query END,
+                app_version = CASE WHEN $4 THEN $11 ELSE app_version END,
+                platform = CASE WHEN $4 THEN $12 ELSE platform END,
+                organization_id = CASE WHEN $4 THEN $15 ELSE organization_id END,
+                sort_order = COALESCE($13, sort_order),
+                updated_at = $14
+            WHERE id = $1 AND owner_id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(view_id)
+        .bind(owner_id)
+        .bind(name)
+        .bind(filter.is_some())
+        .bind(filter.and_then(|f| f.status))
+        .bind(filter.and_then(|f| f.priority))
+        .bind(filter.and_then(|f| f.assigned_to))
+        .bind(filter.and_then(|f| f.customer_id))
+        .bind(filter.and_then(|f| f.category.as_deref()))
+        .bind(filter.and_then(|f| f.search_query.as_deref()))
+        .bind(filter.and_then(|f| f.app_version.as_deref()))
+        .bind(filter.and_then(|f| f.platform))
+        .bind(input.sort_order)
+        .bind(self.now())
+        .bind(filter.and_then(|f| f.organization_id))
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| SupportError::Validation(format!("Saved view not found: {}", view_id)))?;
+
+        Ok(view)
+    }
+
+    /// Tickets in one of the owner's saved views, in the view's sort order
+    ///
+    /// With `organization_id` only that B2B account's tickets are listed,
+    /// whatever the view's own filter. Fails with `Validation` when the owner
+    /// has no such view.
+    pub async fn list_saved_view_tickets(
+        &self,
+        view_id: Uuid,
+        owner_id: Uuid,
+        organization_id: Option<Uuid>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<SupportTicket>> {
+        let view = sqlx::query_as::<_, SavedView>("SELECT * FROM saved_views WHERE id = $1 AND owner_id = $2")
+            .bind(view_id)
+            .bind(owner_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| SupportError::Validation(format!("Saved view not found: {}", view_id)))?;
+
+        let mut filter = view.filter();
+        if let Some(organization_id) = organization_id {
+            if filter.organization_id.is_some_and(|o| o != organization_id) {
+                return Ok(Vec::new());
+            }
+            filter.organization_id = Some(organization_id);
+        }
+        self.list_sorted(&view.product, &filter, view.sort_order, limit, offset).await
+    }
+
+    /// List tickets with filters in `sort_order`
+    pub async fn list_sorted(
+        &self,
+        product: &str,
+        filter: &TicketFilter,
+        sort_order: TicketSortOrder,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<SupportTicket>> {
+        let mut query = String::from("SELECT * FROM support_tickets WHERE product = $1");
+        let params_count = Self::push_filter_conditions(&mut query, filter, 1);
+        query.push_str(&format!(" ORDER BY {}", sort_order.order_by()));
+        query.push_str(&format!(" LIMIT ${} OFFSET ${}", params_count + 1, params_count + 2));
+
+        let q = Self::bind_filter(sqlx::query_as::<_, SupportTicket>(&query).bind(product), filter)
+            .bind(limit)
+            .bind(offset);
+
+        let mut tx = self.begin_timed(OperationClass::Read).await?;
+        let tickets = q.fetch_all(&mut *tx).await?;
+        tx.commit().await?;
+
+        Ok(tickets)
+    }
+
+    /// An agent's saved views, by name
+    pub async fn list_saved_views(&self, product: &str, owner_id: Uuid) -> Result<Vec<SavedView>> {
+        let views = sqlx::query_as::<_, SavedView>(
+            "SELECT * FROM saved_views WHERE product = $1 AND owner_id = $2 ORDER BY name ASC"
+        )
+        .bind(product)
+        .bind(owner_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(views)
+    }
+
+    /// Delete a saved view; returns whether the owner had such a view
+    pub async fn delete_saved_view(&self, view_id: Uuid, owner_id: Uuid) -> Result<bool> {
+        self.ensure_writable()?;
+        let result = sqlx::query("DELETE FROM saved_views WHERE id = $1 AND owner_id = $2")
+            .bind(view_id)
+            .bind(owner_id)
+            .execute(&self.pool)
+            .await
+            .map_err(SupportError::from)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Turn a saved view's new-match notifications on or off
+    ///
+    /// Turning them on starts matching from now, so tickets already in the
+    /// view aren't announced until they next change. Fails with `Validation` when the owner has no
+    /// such view.
+    pub async fn set_saved_view_notifications(&self, view_id: Uuid, owner_id: Uuid, enabled: bool) -> Result<SavedView> {
+        self.ensure_writable()?;
+        let now = self.now();
+        let view = sqlx::query_as::<_, SavedView>(
+            r#"
+            UPDATE saved_views
+            SET notify_on_match = $3,
+                last_evaluated_at = CASE WHEN $3 AND NOT notify_on_match THEN $4 ELSE last_evaluated_at END,
+                updated_at = $4
+            WHERE id = $1 AND owner_id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(view_id)
+        .bind(owner_id)
+        .bind(enabled)
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| SupportError::Validation(format!("Saved view not found: {}", view_id)))?;
+
+        Ok(view)
+    }
+
+    /// Notify agents of tickets that newly match their saved views
+    ///
+    /// For each of the product's views with `notify_on_match`, tickets created
+    /// or updated since the view was last evaluated are matched against its
+    /// filter. Tickets not yet notified for the view are recorded and
+    /// published as one [`SavedViewMatched`] event to the notification sink,
+    /// and the view's watermark advances to [`SAVED_VIEW_SETTLE_SECONDS`]
+    /// before now. A sink failure is logged and rolls the view back so its
+    /// matches are retried on the next run; the other views still run.
+    /// Returns the published events.
+    pub async fn evaluate_saved_view_notifications(&self, product: &str) -> Result<Vec<SavedViewMatched>> {
+        self.ensure_writable()?;
+        let now = self.now();
+        let watermark = now - Duration::seconds(SAVED_VIEW_SETTLE_SECONDS);
+        let views = sqlx::query_as::<_, SavedView>(
+            "SELECT * FROM saved_views WHERE product = $1 AND notify_on_match = TRUE ORDER BY created_at ASC, id ASC"
+        )
+        .bind(product)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut events = Vec::new();
+        for view in views {
+            let filter = view.filter();
+            let mut sql = String::from(
+                r#"
+                SELECT id FROM support_tickets
+                WHERE product = $1 AND NOT test_mode
+                  AND updated_at > COALESCE($2, '-infinity') AND updated_at <= $3
+                  AND NOT EXISTS (
+                      SELECT 1 FROM saved_view_notifications n
+                      WHERE n.view_id = $4 AND n.ticket_id = support_tickets.id
+                  )
+                "#,
+            );
+            Self::push_filter_conditions(&mut sql, &filter, 4);
+            sql.push_str(" ORDER BY created_at ASC, id ASC");
+
+            let q = sqlx::query_as::<_, (Uuid,)>(&sql)
+                .bind(product)
+                .bind(view.last_evaluated_at)
+                .bind(watermark)
+                .bind(view.id);
+            let mut tx = self.pool.begin().await?;
+            let ticket_ids: Vec<Uuid> = Self::bind_filter(q, &filter)
+                .fetch_all(&mut *tx)
+                .await?
+                .into_iter()
+                .map(|(id,)| id)
+                .collect();
+
+            sqlx::query("UPDATE saved_views SET last_evaluated_at = GREATEST(last_evaluated_at, $2) WHERE id = $1")
+                .bind(view.id)
+                .bind(watermark)
+                .execute(&mut *tx)
+                .await?;
+
+            if !ticket_ids.is_empty() {
+                sqlx::query(
+                    r#"
+                    INSERT INTO saved_view_notifications (view_id, ticket_id, notified_at)
+                    SELECT $1, ticket_id, $3 FROM UNNEST($2::UUID[]) AS ticket_id
+                    ON CONFLICT DO NOTHING
+                    "#,
+                )
+                .bind(view.id)
+                .bind(&ticket_ids)
+                .bind(now)
+                .execute(&mut *tx)
+                .await?;
+
+                let event = SavedViewMatched {
+                    product: product.to_string(),
+                    view_id: view.id,
+                    view_name: view.name.clone(),
+                    agent_id: view.owner_id,
+                    ticket_ids,
+                    evaluated_at: now,
+                };
+                if let Err(e) = self.notification_sink.notify(&event).await {
+                    tracing::warn!("Notifying saved view {} failed: {}", view.id, e);
+                    tx.rollback().await?;
+                    continue;
+                }
+                events.push(event);
+            }
+
+            tx.commit().await?;
+        }
+
+        Ok(events)
+    }
+
+    /// Ticket and unread counts for a set of saved views, in one query
+    ///
+    /// A ticket is unread for `agent_id` when it was created, or the customer
+    /// last wrote on it, after the agent last read it. Counts are returned in
+    /// the order of `view_ids`; unknown ids are skipped.
+    pub async fn view_counts(&self, view_ids: &[Uuid], agent_id: Uuid) -> Result<Vec<SavedViewCount>> {
+        let counts = sqlx::query_as::<_, SavedViewCount>(
+            r#"
+            SELECT
+                v.id as view_id,
+                COUNT(t.id)::BIGINT as ticket_count,
+                COUNT(t.id) FILTER (
+                    WHERE GREATEST(t.created_at, activity.last_customer_message_at) > COALESCE(r.last_read_at, '-infinity')
+                )::BIGINT as unread_count
+            FROM saved_views v
+            LEFT JOIN support_tickets t
+                ON t.product = v.product
+               AND t.deleted_at IS NULL
+               AND (v.status IS NULL OR t.status = v.status)
+               AND (v.priority IS NULL OR t.priority = v.priority)
+               AND (v.assigned_to IS NULL OR t.assigned_to = v.assigned_to)
+               AND (v.customer_id IS NULL OR t.customer_id = v.customer_id)
+               AND (v.organization_id IS NULL OR t.organization_id = v.organization_id)
+               AND (v.category IS NULL OR t.category = v.category)
+               AND (v.search_query IS NULL OR t.search_vector @@ support_search_query(v.search_query))
+               AND (v.app_version IS NULL OR t.metadata->'client'->>'app_version' = v.app_version)
+               AND (v.platform IS NULL OR t.metadata->'client'->>'platform' = v.platform)
+            LEFT JOIN LATERAL (
+                SELECT MAX(m.created_at) as last_customer_message_at
+                FROM ticket_messages m
+                WHERE m.ticket_id = t.id
+                  AND m.author_id = t.customer_id
+                  AND m.is_internal = FALSE
+            ) activity ON TRUE
+            LEFT JOIN ticket_reads r ON r.ticket_id = t.id AND r.agent_id = $2
+            WHERE v.id = ANY($1)
+            GROUP BY v.id
+            ORDER BY array_position($1, v.id)
+            "#,
+        )
+        .bind(view_ids)
+        .bind(agent_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(counts)
+    }
+
+    /// Record that an agent has read a ticket up to now
+    pub async fn mark_ticket_read(&self, ticket_id: Uuid, agent_id: Uuid) -> Result<TicketRead> {
+        self.ensure_writable()?;
+        let read = sqlx::query_as::<_, TicketRead>(
+            r#"
+            INSERT INTO ticket_reads (ticket_id, agent_id, last_read_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (ticket_id, agent_id) DO UPDATE
+                SET last_read_at = GREATEST(ticket_reads.last_read_at, EXCLUDED.last_read_at)
+            RETURNING *
+            "#,
+        )
+        .bind(ticket_id)
+        .bind(agent_id)
+        .bind(self.now())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(read)
+    }
+
+    /// Add message to ticket
+    ///
+    /// A customer reply to a CLOSED ticket is handled by the product's
+    /// [`ClosedTicketReply`] policy: it may reopen the ticket, land in a new
+    /// follow-up ticket (the returned message's `ticket_id` tells which), or be
+    /// rejected with `ReplyRejected`. The outcome is recorded in the audit trail.
+    ///
+    /// The first public reply not written by the customer or the system
+    /// stamps the ticket's `first_response_at` in the same transaction; if it
+    /// came after the first response target, the ticket is flagged as
+    /// breached straight away.
+    pub async fn add_message(&self, author_id: Uuid, input: &AddTicketMessageInput) -> Result<TicketMessage> {
+        self.ensure_writable()?;
+        let now = self.now();
+        let mut after = AfterCommit::default();
+        let mut tx = self.pool.begin().await.map_err(SupportError::from)?;
+
+        match self.add_message_in_tx(&mut tx, author_id, input, now, &mut after).await {
+            Ok(message) => {
+                tx.commit().await.map_err(SupportError::from)?;
+                self.after_commit(after).await;
+                Ok(message)
+            }
+            Err(e @ SupportError::ReplyRejected(_)) => {
+                // Keep the audit entry, refuse the message
+                tx.commit().await.map_err(SupportError::from)?;
+                Err(e)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Add a message inside `tx`, adding the events and automations to run
+    /// once it commits to `after`
+    ///
+    /// A rejected closed-ticket reply returns `ReplyRejected` with its audit
+    /// entry already written to `tx`.
+    async fn add_message_in_tx(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        author_id: Uuid,
+        input: &AddTicketMessageInput,
+        now: DateTime<Utc>,
+        after: &mut AfterCommit,
+    ) -> Result<TicketMessage> {
+        validation::validate_message(input)?;
+        let ticket = sqlx::query_as::<_, SupportTicket>("SELECT * FROM support_tickets WHERE id = $1 FOR UPDATE")
+            .bind(input.ticket_id)
+            .fetch_optional(&mut **tx)
+            .await
+            .map_err(SupportError::from)?
+            .ok_or(SupportError::TicketNotFound(input.ticket_id))?;
+
+        let visibility = input.visibility();
+        let from_customer = !visibility.is_internal() && author_id == ticket.customer_id;
+        let mut ticket_id = ticket.id;
+        let mut priority = ticket.priority;
+        if from_customer && ticket.status == TicketStatus::Closed {
+            let policy = self.closed_ticket_replies.policy_for(&ticket.product);
+            Self::insert_audit_entry(
+                tx, ticket.id, Some(author_id), "closed_ticket_reply",
+                None, Some(policy.as_str()), None, now,
+            ).await?;
+
+            match policy {
+                ClosedTicketReply::Append => {}
+                ClosedTicketReply::Reopen => {
+                    self.reopen_ticket_in_tx(tx, ticket.id, Some(author_id), now, after).await?;
+                }
+                ClosedTicketReply::FollowUp => {
+                    let follow_up = self.create_follow_up(tx, &ticket, now, after).await?;
+                    Self::insert_audit_entry(
+                        tx, ticket.id, Some(author_id), "follow_up_ticket",
+                        None, Some(&follow_up.ticket_ref), None, now,
+                    ).await?;
+                    Self::insert_audit_entry(
+                        tx, follow_up.id, Some(author_id), "follow_up_of",
+                        None, Some(&ticket.ticket_ref), Some(ticket.id), now,
+                    ).await?;
+                    ticket_id = follow_up.id;
+                    priority = follow_up.priority;
+                }
+                ClosedTicketReply::Reject { guidance } => {
+                    return Err(SupportError::ReplyRejected(guidance.clone()));
+                }
+            }
+        }
+        let (product, customer_id) = (ticket.product, ticket.customer_id);
+
+        // Only inbound customer messages are moderated and scored
+        let (moderation, sentiment_score) = if from_customer {
+            let score = self.sentiment_analyzer.score(&input.content).clamp(-1.0, 1.0);
+            (self.moderator.moderate(&input.content), Some(score))
+        } else {
+            (ModerationResult::allow(), None)
+        };
+
+        let message = sqlx::query_as::<_, TicketMessage>(
+            r#"
+            INSERT INTO ticket_messages (ticket_id, author_id, is_internal, visibility, content, flagged, created_at, content_format, sentiment_score)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING *
+            "#,
+        )
+        .bind(ticket_id)
+        .bind(author_id)
+        .bind(visibility.is_internal())
+        .bind(visibility)
+        .bind(&input.content)
+        .bind(moderation.action != ModerationAction::Allow)
+        .bind(now)
+        .bind(input.content_format)
+        .bind(sentiment_score)
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(SupportError::from)?;
+
+        let mentions = if from_customer {
+            Vec::new()
+        } else {
+            Self::record_mentions(tx, &product, &message).await?
+        };
+
+        if !from_customer {
+            sqlx::query("UPDATE ticket_viewers SET typing_at = NULL WHERE ticket_id = $1 AND user_id = $2")
+                .bind(input.ticket_id)
+                .bind(author_id)
+                .execute(&mut **tx)
+                .await?;
+        }
+
+        if moderation.action != ModerationAction::Allow {
+            sqlx::query(
+                r#"
+                INSERT INTO abuse_incidents (product, customer_id, ticket_id, message_id, action, matched_terms, created_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                "#,
+            )
+            .bind(&product)
+            .bind(customer_id)
+            .bind(ticket_id)
+            .bind(message.id)
+            .bind(moderation.action)
+            .bind(&moderation.matched_terms)
+            .bind(now)
+            .execute(&mut **tx)
+            .await
+            .map_err(SupportError::from)?;
+        }
+
+        if moderation.action == ModerationAction::Escalate && priority != TicketPriority::Urgent {
+            let escalated = sqlx::query_as::<_, SupportTicket>(
+                "UPDATE support_tickets SET priority = 'URGENT', updated_at = $2 WHERE id = $1 RETURNING *"
+            )
+                .bind(ticket_id)
+                .bind(now)
+                .fetch_one(&mut **tx)
+                .await
+                .map_err(SupportError::from)?;
+
+            Self::insert_audit_entry(
+                tx,
+                ticket_id,
+                None,
+                "priority",
+                Some(priority.as_str()),
+                Some(TicketPriority::Urgent.as_str()),
+                None,
+                now,
+            )
+            .await?;
+
+            // Targets are per priority, so the deadlines follow the escalation
+            let escalated = self.apply_sla(tx, &[ticket_id], now).await?
+                .into_iter()
+                .find(|t| t.id == ticket_id)
+                .unwrap_or(escalated);
+            after.events.push(SupportEvent::ticket(SupportEventType::TicketUpdated, &escalated, now));
+        }
+
+        // The message trigger stamps `first_response_at` on the first public
+        // agent reply; a late one breaches now rather than at the next evaluation
+        let first_response = ticket.first_response_at.is_none()
+            && !visibility.is_internal()
+            && author_id != customer_id
+            && author_id != SYSTEM_AUTHOR_ID;
+        let breached = if first_response && !ticket.test_mode {
+            self.flag_sla_breaches(tx, Some(ticket_id), now).await?
+        } else {
+            Vec::new()
+        };
+
+        after.events.push(SupportEvent::message_added(&product, &message).with_mentions(mentions));
+        for ticket in &breached {
+            after.events.push(SupportEvent::ticket(SupportEventType::SlaBreached, ticket, now));
+        }
+        if !visibility.is_internal() {
+            let message_author = if from_customer {
+                MessageAuthor::Customer
+            } else if author_id == SYSTEM_AUTHOR_ID {
+                MessageAuthor::System
+            } else {
+                MessageAuthor::Agent
+            };
+            after.automations.push(AutomationFiring::message_added(ticket_id, message_author));
+        }
+        if from_customer && self.sentiment_dropped(tx, &product, ticket_id, now).await? {
+            after.automations.push(AutomationFiring::new(AutomationTrigger::SentimentDropped, ticket_id));
+        }
+
+        Ok(message)
+    }
+
+    /// Whether the customer message just added completes a run of negative
+    /// ones under the product's sentiment rule, auditing the trigger if so
+    async fn sentiment_dropped(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        product: &str,
+        ticket_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<bool> {
+        let Some(rule) = self.sentiment_rules.rule_for(product) else {
+            return Ok(false);
+        };
+
+        let scores: Vec<Option<f64>> = sqlx::query_scalar(
+            r#"
+            SELECT m.sentiment_score
+            FROM ticket_messages m
+            JOIN support_tickets st ON st.id = m.ticket_id
+            WHERE m.ticket_id = $1 AND m.author_id = st.customer_id AND m.is_internal = FALSE
+            ORDER BY m.created_at DESC, m.id DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(ticket_id)
+        .bind(i64::from(rule.consecutive_messages) + 1)
+        .fetch_all(&mut **tx)
+        .await?;
+        if !completes_run(rule, &scores) {
+            return Ok(false);
+        }
+
+        let run = &scores[..rule.consecutive_messages as usize];
+        let average = run.iter().flatten().sum::<f64>() / run.len() as f64;
+        let note = format!("{:.2} over {} customer messages", average, run.len());
+        Self::insert_audit_entry(tx, ticket_id, None, "sentiment_dropped", None, Some(&note), None, now).await?;
+        Ok(true)
+    }
+
+    /// Keep the agents of `product` that `message` @mentions, returning their ids in mention order
+    async fn record_mentions(
+        tx: &mut Transaction<'_, Postgres>,
+        product: &str,
+        message: &TicketMessage,
+    ) -> Result<Vec<Uuid>> {
+        let mentioned = extract_mentions(&message.content);
+        if mentioned.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let recorded: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            INSERT INTO message_mentions (message_id, agent_id, author_id, created_at)
+            SELECT $1, a.user_id, $2, $3
+            FROM support_agents a
+            WHERE a.product = $4 AND a.user_id = ANY($5)
+            ON CONFLICT (message_id, agent_id) DO NOTHING
+            RETURNING agent_id
+            "#,
+        )
+        .bind(message.id)
+        .bind(message.author_id)
+        .bind(message.created_at)
+        .bind(product)
+        .bind(&mentioned)
+        .fetch_all(&mut **tx)
+        .await?;
+
+        Ok(mentioned.into_iter().filter(|id| recorded.contains(id)).collect())
+    }
+
+    /// Open a follow-up to a closed ticket for a customer reply, created like
+    /// any other ticket; the reply itself becomes its first message
+    async fn create_follow_up(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        original: &SupportTicket,
+        now: DateTime<Utc>,
+        after: &mut AfterCommit,
+    ) -> Result<SupportTicket> {
+        let input = CreateTicketInput {
+            customer_id: original.customer_id,
+            organization_id: original.organization_id,
+            subject: format!("Follow-up: {}", original.subject),
+            description: format!("The customer replied to closed ticket {}.", original.ticket_ref),
+            priority: Some(original.priority),
+            category: original.category.clone(),
+            customer_plan: original.customer_plan.clone(),
+            channel: original.channel,
+            locale: original.locale.clone(),
+            test_mode: original.test_mode,
+            error_signature: None,
+            client: None,
+            idempotency_key: None,
+        };
+        self.create_ticket_in_tx(tx, &original.product, &input, serde_json::json!({}), Some(original.id), now, after)
+            .await
+    }
+
+    /// Get a ticket's messages that a viewer at `viewer` may read, oldest first
+    ///
+    /// Pass `PUBLIC` for customers, `INTERNAL` for agents and `RESTRICTED`
+    /// for agents cleared for restricted notes.
+    pub async fn get_messages(&self, ticket_id: Uuid, viewer: MessageVisibility) -> Result<Vec<TicketMessage>> {
+        let messages = sqlx::query_as::<_, TicketMessage>(
+            "SELECT * FROM ticket_messages WHERE ticket_id = $1 AND visibility = ANY($2) ORDER BY created_at ASC"
+        )
+        .bind(ticket_id)
+        .bind(MessageVisibility::up_to(viewer))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(messages)
+    }
+
+    /// Get messages for many tickets in one query, oldest first
+    pub async fn get_messages_for_tickets(&self, ticket_ids: &[Uuid]) -> Result<Vec<TicketMessage>> {
+        let messages = sqlx::query_as::<_, TicketMessage>(
+            "SELECT * FROM ticket_messages WHERE ticket_id = ANY($1) ORDER BY created_at ASC, id ASC"
+        )
+        .bind(ticket_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(messages)
+    }
+
+    /// Record the SMTP Message-ID and References of a reply sent by email
+    ///
+    /// Inbound ingestion threads customer replies back to the ticket with
+    /// `find_ticket_by_email_refs`. Ids are stored without angle brackets;
+    /// recording the same message again replaces its headers.
+    pub async fn record_outbound_email(
+        &self,
+        message_id: Uuid,
+        smtp_message_id: &str,
+        thread_refs: &[String],
+    ) -> Result<OutboundEmail> {
+        self.ensure_writable()?;
+        let smtp_message_id = normalize_smtp_id(smtp_message_id);
+        if smtp_message_id.is_empty() {
+            return Err(SupportError::Validation("SMTP Message-ID cannot be empty".to_string()));
+        }
+        let thread_refs: Vec<String> = thread_refs
+            .iter()
+            .map(|r| normalize_smtp_id(r))
+            .filter(|r| !r.is_empty())
+            .collect();
+
+        let message: Option<(Uuid, bool)> = sqlx::query_as(
+            "SELECT ticket_id, is_internal FROM ticket_messages WHERE id = $1"
+        )
+        .bind(message_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        let ticket_id = match message {
+            None => return Err(SupportError::MessageNotFound(message_id)),
+            Some((_, true)) => {
+                return Err(SupportError::Validation(
+                    "Internal messages are never sent by email".to_string(),
+                ))
+            }
+            Some((ticket_id, false)) => ticket_id,
+        };
+
+        let email = sqlx::query_as::<_, OutboundEmail>(
+            r#"
+            INSERT INTO outbound_emails (message_id, ticket_id, smtp_message_id, thread_refs, sent_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (message_id) DO UPDATE SET
+                smtp_message_id = EXCLUDED.smtp_message_id,
+                thread_refs = EXCLUDED.thread_refs,
+                sent_at = EXCLUDED.sent_at
+            RETURNING *
+            "#,
+        )
+        .bind(message_id)
+        .bind(ticket_id)
+        .bind(&smtp_message_id)
+        .bind(&thread_refs)
+        .bind(self.now())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(email)
+    }
+
+    /// Ticket an inbound email replies to, from its In-Reply-To and References ids
+    ///
+    /// Matches against Message-IDs recorded by `record_outbound_email` and
+    /// those of ingested customer emails; when several match, the most recent
+    /// email wins.
+    pub async fn find_ticket_by_email_refs(&self, refs: &[String]) -> Result<Option<Uuid>> {
+        let refs: Vec<String> = refs.iter().map(|r| normalize_smtp_id(r)).filter(|r| !r.is_empty()).collect();
+        if refs.is_empty() {
+            return Ok(None);
+        }
+
+        let ticket_id = sqlx::query_scalar(
+            r#"
+            SELECT ticket_id FROM (
+                SELECT ticket_id, sent_at AS at FROM outbound_emails WHERE smtp_message_id = ANY($1)
+                UNION ALL
+                SELECT ticket_id, received_at AS at FROM inbound_emails WHERE smtp_message_id = ANY($1)
+            ) emails
+            ORDER BY at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(&refs)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(ticket_id)
+    }
+
+    /// Turn a customer's inbound email into a ticket or a reply on one
+    ///
+    /// Services resolve the sender to `customer_id`. A reply threaded (by
+    /// `find_ticket_by_email_refs`) to one of that customer's tickets is added
+    /// as a message, without its quoted history, following the usual rules
+    /// for customer replies (moderation, closed-ticket policy). Anything else,
+    /// including replies on another customer's thread, opens an `EMAIL`
+    /// ticket. An email whose Message-ID was already ingested is reported as
+    /// a duplicate and changes nothing; the Message-ID is claimed in the
+    /// transaction that writes the ticket or reply, so concurrent deliveries
+    /// of one email write it once.
+    pub async fn ingest_email(&self, product: &str, customer_id: Uuid, email: &InboundEmail) -> Result<EmailIngestion> {
+        self.ensure_writable()?;
+        let thread = match self.find_ticket_by_email_refs(&email.thread_refs()).await? {
+            Some(ticket_id) => self.find_by_id(ticket_id).await.ok(),
+            None => None,
+        };
+        let thread = thread.filter(|t| t.product == product && t.customer_id == customer_id);
+
+        let Some(ticket) = thread else {
+            let input = CreateTicketInput {
+                customer_id,
+                subject: email.ticket_subject(),
+                description: email.ticket_description(),
+                channel: TicketChannel::Email,
+                ..Default::default()
+            };
+            return match self.create_ticket_claiming(product, &input, None, Some(email)).await? {
+                Some(ticket) => Ok(EmailIngestion { ticket, message: None, created_ticket: true, duplicate: false }),
+                None => self.duplicate_email_ingestion(email).await,
+            };
+        };
+
+        let now = self.now();
+        let mut after = AfterCommit::default();
+        let mut tx = self.pool.begin().await?;
+        if !Self::claim_inbound_email(&mut tx, email, now).await? {
+            tx.rollback().await?;
+            return self.duplicate_email_ingestion(email).await;
+        }
+        let input = AddTicketMessageInput {
+            ticket_id: ticket.id,
+            content: email.reply_content(),
+            is_internal: false,
+            visibility: None,
+            content_format: MessageContentFormat::Plaintext,
+        };
+        let message = match self.add_message_in_tx(&mut tx, customer_id, &input, now, &mut after).await {
+            Ok(message) => message,
+            Err(e @ SupportError::ReplyRejected(_)) => {
+                // Keep the audit entry, refuse the message; a redelivery is checked again
+                Self::release_inbound_email(&mut tx, email).await?;
+                tx.commit().await?;
+                return Err(e);
+            }
+            Err(e) => return Err(e),
+        };
+        Self::record_inbound_email(&mut tx, email, message.ticket_id, Some(message.id)).await?;
+        tx.commit().await?;
+        self.after_commit(after).await;
+
+        // A reply to a closed ticket may have opened a follow-up
+        let ticket = if message.ticket_id == ticket.id { ticket } else { self.find_by_id(message.ticket_id).await? };
+        Ok(EmailIngestion { ticket, message: Some(message), created_ticket: false, duplicate: false })
+    }
+
+    /// Claim `email`'s Message-ID for ingestion in `tx`
+    ///
+    /// Returns false when it was already ingested, or claimed by a
+    /// transaction that has since committed. Emails without a Message-ID are
+    /// never deduplicated.
+    async fn claim_inbound_email(
+        tx: &mut Transaction<'_, Postgres>,
+        email: &InboundEmail,
+        now: DateTime<Utc>,
+    ) -> Result<bool> {
+        let Some(smtp_message_id) = &email.message_id else {
+            return Ok(true);
+        };
+        let claimed: Option<String> = sqlx::query_scalar(
+            r#"
+            INSERT INTO inbound_emails (smtp_message_id, from_address, received_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (smtp_message_id) DO NOTHING
+            RETURNING smtp_message_id
+            "#,
+        )
+        .bind(normalize_smtp_id(smtp_message_id))
+        .bind(&email.from_address)
+        .bind(now)
+        .fetch_optional(&mut **tx)
+        .await?;
+        Ok(claimed.is_some())
+    }
+
+    /// Record the ticket and reply a claimed email was written to
+    async fn record_inbound_email(
+        tx: &mut Transaction<'_, Postgres>,
+        email: &InboundEmail,
+        ticket_id: Uuid,
+        message_id: Option<Uuid>,
+    ) -> Result<()> {
+        if let Some(smtp_message_id) = &email.message_id {
+            sqlx::query("UPDATE inbound_emails SET ticket_id = $2, message_id = $3 WHERE smtp_message_id = $1")
+                .bind(normalize_smtp_id(smtp_message_id))
+                .bind(ticket_id)
+                .bind(message_id)
+                .execute(&mut **tx)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Drop the claim on an email that wrote nothing
+    async fn release_inbound_email(tx: &mut Transaction<'_, Postgres>, email: &InboundEmail) -> Result<()> {
+        if let Some(smtp_message_id) = &email.message_id {
+            sqlx::query("DELETE FROM inbound_emails WHERE smtp_message_id = $1")
+                .bind(normalize_smtp_id(smtp_message_id))
+                .execute(&mut **tx)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// What an email whose Message-ID was already ingested was written to
+    async fn duplicate_email_ingestion(&self, email: &InboundEmail) -> Result<EmailIngestion> {
+        let smtp_message_id = email.message_id.as_deref().map(normalize_smtp_id).unwrap_or_default();
+        let (ticket_id, message_id): (Uuid, Option<Uuid>) = sqlx::query_as(
+            "SELECT ticket_id, message_id FROM inbound_emails WHERE smtp_message_id = $1 AND ticket_id IS NOT NULL"
+        )
+        .bind(&smtp_message_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| SupportError::Internal(format!("Email {} was claimed without a ticket", smtp_message_id)))?;
+        let message = match message_id {
+            Some(id) => sqlx::query_as::<_, TicketMessage>("SELECT * FROM ticket_messages WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?,
+            None => None,
+        };
+        Ok(EmailIngestion { ticket: self.find_by_id(ticket_id).await?, message, created_ticket: false, duplicate: true })
+    }
+
+    /// Add an agent's reaction to an internal message
+    ///
+    /// Reacting twice with the same reaction is a no-op.
+    pub async fn add_reaction(
+        &self,
+        message_id: Uuid,
+        agent_id: Uuid,
+        reaction: MessageReaction,
+    ) -> Result<TicketMessageReaction> {
+        self.ensure_writable()?;
+        let is_internal: Option<bool> = sqlx::query_scalar(
+            "SELECT is_internal FROM ticket_messages WHERE id = $1"
+        )
+        .bind(message_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        match is_internal {
+            None => return Err(SupportError::MessageNotFound(message_id)),
+            Some(false) => {
+                return Err(SupportError::Validation(
+                    "Reactions are only supported on internal messages".to_string(),
+                ))
+            }
+            Some(true) => {}
+        }
+
+        let reaction = sqlx::query_as::<_, TicketMessageReaction>(
+            r#"
+            INSERT INTO ticket_message_reactions (message_id, agent_id, reaction, created_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (message_id, agent_id, reaction) DO UPDATE SET reaction = EXCLUDED.reaction
+            RETURNING *
+            "#,
+        )
+        .bind(message_id)
+        .bind(agent_id)
+        .bind(reaction)
+        .bind(self.now())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(reaction)
+    }
+
+    /// Remove an agent's reaction from a message; returns whether one was removed
+    pub async fn remove_reaction(&self, message_id: Uuid, agent_id: Uuid, reaction: MessageReaction) -> Result<bool> {
+        self.ensure_writable()?;
+        let result = sqlx::query(
+            "DELETE FROM ticket_message_reactions WHERE message_id = $1 AND agent_id = $2 AND reaction = $3"
+        )
+        .bind(message_id)
+        .bind(agent_id)
+        .bind(reaction)
+        .execute(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Get reactions on a message, oldest first
+    pub async fn get_reactions(&self, message_id: Uuid) -> Result<Vec<TicketMessageReaction>> {
+        let reactions = sqlx::query_as::<_, TicketMessageReaction>(
+            "SELECT * FROM ticket_message_reactions WHERE message_id = $1 ORDER BY created_at ASC"
+        )
+        .bind(message_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(reactions)
+    }
+
+    /// Agents a message @mentions
+    pub async fn get_message_mentions(&self, message_id: Uuid) -> Result<Vec<MessageMention>> {
+        let mentions = sqlx::query_as::<_, MessageMention>(
+            r#"
+            SELECT mm.message_id, m.ticket_id, mm.agent_id, mm.author_id, mm.created_at
+            FROM message_mentions mm
+            JOIN ticket_messages m ON m.id = mm.message_id
+            WHERE mm.message_id = $1
+            ORDER BY mm.agent_id ASC
+            "#,
+        )
+        .bind(message_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(mentions)
+    }
+
+    /// Messages that @mention the agent, newest first
+    ///
+    /// Mentions on deleted tickets are left out. `limit` is capped at
+    /// [`MAX_AGENT_MENTIONS`].
+    pub async fn agent_mentions(&self, agent_id: Uuid, since: Option<DateTime<Utc>>, limit: i64) -> Result<Vec<MessageMention>> {
+        let mentions = sqlx::query_as::<_, MessageMention>(
+            r#"
+            SELECT mm.message_id, m.ticket_id, mm.agent_id, mm.author_id, mm.created_at
+            FROM message_mentions mm
+            JOIN ticket_messages m ON m.id = mm.message_id
+            JOIN support_tickets st ON st.id = m.ticket_id
+            WHERE mm.agent_id = $1
+              AND ($2::TIMESTAMPTZ IS NULL OR mm.created_at > $2)
+              AND st.deleted_at IS NULL
+            ORDER BY mm.created_at DESC, mm.message_id ASC
+            LIMIT $3
+            "#,
+        )
+        .bind(agent_id)
+        .bind(since)
+        .bind(limit.clamp(1, MAX_AGENT_MENTIONS))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(mentions)
+    }
+
+    /// Internal notes on tickets assigned to the agent that they have not acknowledged
+    ///
+    /// Notes written by the agent themselves are excluded.
+    pub async fn unacknowledged_internal_notes(&self, agent_id: Uuid) -> Result<Vec<TicketMessage>> {
+        let messages = sqlx::query_as::<_, TicketMessage>(
+            r#"
+            SELECT m.*
+            FROM ticket_messages m
+            JOIN support_tickets st ON st.id = m.ticket_id
+            WHERE st.assigned_to = $1
+              AND st.deleted_at IS NULL
+              AND m.is_internal = TRUE
+              AND m.author_id <> $1
+              AND NOT EXISTS (
+                  SELECT 1 FROM ticket_message_reactions r
+                  WHERE r.message_id = m.id AND r.agent_id = $1 AND r.reaction = 'ACK'
+              )
+            ORDER BY m.created_at ASC
+            "#,
+        )
+        .bind(agent_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(messages)
+    }
+
+    /// Abuse incidents recorded against a customer, newest first
+    pub async fn get_abuse_incidents(&self, customer_id: Uuid, product: &str) -> Result<Vec<AbuseIncident>> {
+        let incidents = sqlx::query_as::<_, AbuseIncident>(
+            r#"
+            SELECT * FROM abuse_incidents
+            WHERE customer_id = $1 AND product = $2
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(customer_id)
+        .bind(product)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(incidents)
+    }
+
+    /// Customers with at least `min_incidents` abuse incidents since `since`,
+    /// most incidents first, for blocklist review
+    pub async fn abuse_blocklist_candidates(
+        &self,
+        product: &str,
+        min_incidents: i64,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<AbuseBlocklistCandidate>> {
+        let candidates = sqlx::query_as::<_, AbuseBlocklistCandidate>(
+            r#"
+            SELECT
+                customer_id,
+                COUNT(*)::BIGINT as incident_count,
+                COUNT(*) FILTER (WHERE action = 'ESCALATE')::BIGINT as escalated_count,
+                MAX(created_at) as last_incident_at
+            FROM abuse_incidents
+            WHERE product = $1 AND created_at >= $2
+            GROUP BY customer_id
+            HAVING COUNT(*) >= $3
+            ORDER BY incident_count DESC, last_incident_at DESC
+            "#,
+        )
+        .bind(product)
+        .bind(since)
+        .bind(min_incidents)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(candidates)
+    }
+
+    /// Recent-context panel for a customer: last 5 tickets, CSAT, open tickets
+    /// and known-issue subscriptions, fetched in a single query
+    ///
+    /// With `organization_id` only that B2B account's tickets are included.
+    pub async fn customer_context(
+        &self,
+        customer_id: Uuid,
+        product: &str,
+        organization_id: Option<Uuid>,
+    ) -> Result<CustomerContext> {
+        let row = sqlx::query_as::<_, CustomerContextRow>(
+            r#"
+            WITH ct AS (
+                SELECT * FROM support_tickets
+                WHERE customer_id = $1 AND product = $2 AND deleted_at IS NULL
+                  AND ($3::UUID IS NULL OR organization_id = $3)
+            )
+            SELECT
+                (SELECT COUNT(*) FROM ct)::BIGINT as total_tickets,
+                (SELECT COUNT(*) FROM ct WHERE status NOT IN ('RESOLVED', 'CLOSED'))::BIGINT as open_tickets,
+                (SELECT AVG(csat_score::FLOAT) FROM ct WHERE csat_score IS NOT NULL) as avg_csat_score,
+                COALESCE((
+                    SELECT json_agg(r ORDER BY r.created_at DESC)
+                    FROM (
+                        SELECT id, ticket_ref, subject, status, priority, created_at, resolved_at, csat_score
+                        FROM ct
+                        ORDER BY created_at DESC
+                        LIMIT 5
+                    ) r
+                ), '[]'::json) as recent_tickets,
+                COALESCE((
+                    SELECT json_agg(k ORDER BY k.created_at DESC)
+                    FROM (
+                        SELECT DISTINCT p.id as parent_ticket_id, p.ticket_ref, p.subject, p.status, p.created_at
+                        FROM ct
+                        JOIN support_tickets p ON p.id = ct.parent_ticket_id
+                        WHERE p.deleted_at IS NULL
+                          AND p.status NOT IN ('RESOLVED', 'CLOSED')
+                    ) k
+                ), '[]'::json) as known_issues
+            "#,
+        )
+        .bind(customer_id)
+        .bind(product)
+        .bind(organization_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(CustomerContext {
+            customer_id,
+            product: product.to_string(),
+            recent_tickets: row.recent_tickets.0,
+            total_tickets: row.total_tickets,
+            open_tickets: row.open_tickets,
+            has_open_ticket: row.open_tickets > 0,
+            avg_csat_score: row.avg_csat_score,
+            known_issues: row.known_issues.0,
+        })
+    }
+
+    /// Totals, CSAT, last contact and most common categories of a customer's
+    /// tickets across all products, for agents picking up their new ticket
+    ///
+    /// With `organization_id` only that B2B account's tickets are counted.
+    /// Test-mode tickets are left out.
+    pub async fn customer_support_summary(
+        &self,
+        customer_id: Uuid,
+        organization_id: Option<Uuid>,
+    ) -> Result<CustomerSupportSummary> {
+        let mut tx = self.begin_timed(OperationClass::Read).await?;
+        let row = sqlx::query_as::<_, CustomerSupportSummaryRow>(
+            r#"
+            WITH ct AS (
+                SELECT * FROM support_tickets
+                WHERE customer_id = $1 AND deleted_at IS NULL
+                  AND NOT test_mode
+                  AND ($2::UUID IS NULL OR organization_id = $2)
+            )
+            SELECT
+                (SELECT COUNT(*) FROM ct)::BIGINT as total_tickets,
+                (SELECT COUNT(*) FROM ct WHERE status NOT IN ('RESOLVED', 'CLOSED'))::BIGINT as open_tickets,
+                (SELECT AVG(csat_score::FLOAT) FROM ct WHERE csat_score IS NOT NULL) as avg_csat_score,
+                GREATEST(
+                    (SELECT MAX(created_at) FROM ct),
+                    (SELECT MAX(m.created_at) FROM ticket_messages m
+                     JOIN ct ON ct.id = m.ticket_id
+                     WHERE m.author_id = $1 AND m.is_internal = FALSE)
+                ) as last_contact_at,
+                COALESCE((
+                    SELECT json_agg(c ORDER BY c.ticket_count DESC, c.category)
+                    FROM (
+                        SELECT category, COUNT(*)::BIGINT as ticket_count
+                        FROM ct
+                        WHERE category IS NOT NULL
+                        GROUP BY category
+                        ORDER BY ticket_count DESC, category
+                        LIMIT 5
+                    ) c
+                ), '[]'::json) as common_categories
+            "#,
+        )
+        .bind(customer_id)
+        .bind(organization_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(CustomerSupportSummary {
+            customer_id,
+            total_tickets: row.total_tickets,
+            open_tickets: row.open_tickets,
+            avg_csat_score: row.avg_csat_score,
+            last_contact_at: row.last_contact_at,
+            common_categories: row.common_categories.0,
+        })
+    }
+
+    /// The customer's tickets, CSAT ratings and known-issue links across all products, oldest first
+    ///
+    /// Covers events that occurred in the period, e.g. for a quarterly
+    /// business review. A ticket opened before the period still contributes
+    /// its later resolution, rating or link. With `organization_id` only that
+    /// B2B account's tickets are included.
+    pub async fn customer_journey(
+        &self,
+        customer_id: Uuid,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+        organization_id: Option<Uuid>,
+    ) -> Result<Vec<CustomerJourneyEvent>> {
+        let events = sqlx::query_as::<_, CustomerJourneyEvent>(
+            r#"
+            WITH ct AS (
+                SELECT * FROM support_tickets
+                WHERE customer_id = $1 AND deleted_at IS NULL
+                  AND ($4::UUID IS NULL OR organization_id = $4)
+            ),
+            events AS (
+                SELECT 'TICKET_OPENED' as kind, created_at as occurred_at, id, NULL::INTEGER as csat_score, NULL::UUID as known_issue_id
+                FROM ct
+                UNION ALL
+                SELECT 'TICKET_RESOLVED', resolved_at, id, NULL, NULL FROM ct WHERE resolved_at IS NOT NULL
+                UNION ALL
+                SELECT 'TICKET_CLOSED', closed_at, id, NULL, NULL FROM ct WHERE closed_at IS NOT NULL
+                UNION ALL
+                SELECT 'CSAT_SUBMITTED', csat_submitted_at, id, csat_score, NULL FROM ct WHERE csat_submitted_at IS NOT NULL
+                UNION ALL
+                SELECT 'KNOWN_ISSUE_LINKED', linked_at, id, NULL, parent_ticket_id FROM ct WHERE linked_at IS NOT NULL
+            )
+            SELECT
+                e.kind,
+                e.occurred_at,
+                ct.product,
+                ct.id as ticket_id,
+                ct.ticket_ref,
+                ct.subject,
+                e.csat_score,
+                e.known_issue_id,
+                p.ticket_ref as known_issue_ref,
+                p.subject as known_issue_subject
+            FROM events e
+            JOIN ct ON ct.id = e.id
+            LEFT JOIN support_tickets p ON p.id = e.known_issue_id
+            WHERE e.occurred_at BETWEEN $2 AND $3
+            ORDER BY e.occurred_at,
+                CASE e.kind
+                    WHEN 'TICKET_OPENED' THEN 1
+                    WHEN 'KNOWN_ISSUE_LINKED' THEN 2
+                    WHEN 'TICKET_RESOLVED' THEN 3
+                    WHEN 'TICKET_CLOSED' THEN 4
+                    WHEN 'CSAT_SUBMITTED' THEN 5
+                END,
+                ct.ticket_ref
+            "#,
+        )
+        .bind(customer_id)
+        .bind(period_start)
+        .bind(period_end)
+        .bind(organization_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(events)
+    }
+
+    /// Record that the ticket's customer viewed it (or a specific reply) in the portal
+    ///
+    /// A `message_id` not on the ticket fails with `MessageNotFound`.
+    pub async fn record_ticket_view(
+        &self,
+        ticket_id: Uuid,
+        customer_id: Uuid,
+        message_id: Option<Uuid>,
+    ) -> Result<TicketView> {
+        self.ensure_writable()?;
+        let ticket = self.find_by_id(ticket_id).await?;
+        if ticket.customer_id != customer_id {
+            return Err(SupportError::Unauthorized);
+        }
+        if let Some(message_id) = message_id {
+            let on_ticket: bool = sqlx::query_scalar(
+                "SELECT EXISTS (SELECT 1 FROM ticket_messages WHERE id = $1 AND ticket_id = $2)"
+            )
+            .bind(message_id)
+            .bind(ticket_id)
+            .fetch_one(&self.pool)
+            .await?;
+            if !on_ticket {
+                return Err(SupportError::MessageNotFound(message_id));
+            }
+        }
+
+        let view = sqlx::query_as::<_, TicketView>(
+            r#"
+            INSERT INTO ticket_views (ticket_id, customer_id, message_id, viewed_at)
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
+            "#,
+        )
+        .bind(ticket_id)
+        .bind(customer_id)
+        .bind(message_id)
+        .bind(self.now())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(view)
+    }
+
+    /// Record a download of one of the ticket's attachments
+    ///
+    /// `origin` carries where the download came from (IP, user agent, coarse
+    /// geo). The IP is stored as given, whatever the intake privacy settings,
+    /// since the log exists for security investigations.
+    pub async fn record_attachment_access(
+        &self,
+        ticket_id: Uuid,
+        attachment_id: Uuid,
+        file_name: Option<&str>,
+        accessed_by: Uuid,
+        origin: Option<&SubmitterInfo>,
+    ) -> Result<AttachmentAccess> {
+        self.ensure_writable()?;
+        self.find_by_id(ticket_id).await?;
+        if let Some(attachment) = self.get_attachment(attachment_id).await? {
+            if attachment.status != AttachmentStatus::Available {
+                return Err(SupportError::Validation(format!(
+                    "Attachment {} is {} and cannot be downloaded",
+                    attachment_id,
+                    attachment.status.as_str()
+                )));
+            }
+        }
+        let geo = origin.and_then(|o| o.geo.as_ref());
+
+        let access = sqlx::query_as::<_, AttachmentAccess>(
+            r#"
+            INSERT INTO attachment_access_log (
+                ticket_id, attachment_id, file_name, accessed_by, accessed_at, ip_address, user_agent, country, region
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING *
+            "#,
+        )
+        .bind(ticket_id)
+        .bind(attachment_id)
+        .bind(file_name)
+        .bind(accessed_by)
+        .bind(self.now())
+        .bind(origin.and_then(|o| o.ip).map(|ip| ip.to_string()))
+        .bind(origin.and_then(|o| o.user_agent.as_deref()))
+        .bind(geo.and_then(|g| g.country.as_deref()))
+        .bind(geo.and_then(|g| g.region.as_deref()))
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(access)
+    }
+
+    /// Downloads of one attachment, newest first
+    pub async fn list_attachment_accesses(&self, attachment_id: Uuid) -> Result<Vec<AttachmentAccess>> {
+        let accesses = sqlx::query_as::<_, AttachmentAccess>(
+            "SELECT * FROM attachment_access_log WHERE attachment_id = $1 ORDER BY accessed_at DESC, id ASC"
+        )
+        .bind(attachment_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(accesses)
+    }
+
+    /// Downloads of any of the ticket's attachments, newest first
+    pub async fn list_ticket_attachment_accesses(&self, ticket_id: Uuid) -> Result<Vec<AttachmentAccess>> {
+        let accesses = sqlx::query_as::<_, AttachmentAccess>(
+            "SELECT * FROM attachment_access_log WHERE ticket_id = $1 ORDER BY accessed_at DESC, id ASC"
+        )
+        .bind(ticket_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(accesses)
+    }
+
+    /// Extract an attachment's text for ticket search
+    ///
+    /// Re-indexing an attachment replaces its text. Returns `None`, storing
+    /// nothing, when no extractor reads the content type or the attachment
+    /// has no text.
+    pub async fn index_attachment_text(&self, input: &IndexAttachmentInput, content: &[u8]) -> Result<Option<AttachmentText>> {
+        self.ensure_writable()?;
+        let content_type = normalize_content_type(&input.content_type);
+        let Some(extractor) = self.text_extractors.for_content_type(&content_type) else {
+            return Ok(None);
+        };
+        self.check_attachment_owner(input).await?;
+        if self.get_attachment(input.attachment_id).await?.is_some_and(|a| a.status != AttachmentStatus::Available) {
+            return Ok(None);
+        }
+
+        let text = searchable_text(&extractor.extract(&content_type, content).await?);
+        if text.is_empty() {
+            self.remove_attachment_text(input.attachment_id).await?;
+            return Ok(None);
+        }
+
+        let indexed = sqlx::query_as::<_, AttachmentText>(
+            r#"
+            INSERT INTO attachment_texts (attachment_id, ticket_id, message_id, file_name, content_type, extractor, content, extracted_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (attachment_id) DO UPDATE SET
+                ticket_id = EXCLUDED.ticket_id,
+                message_id = EXCLUDED.message_id,
+                file_name = EXCLUDED.file_name,
+                content_type = EXCLUDED.content_type,
+                extractor = EXCLUDED.extractor,
+                content = EXCLUDED.content,
+                extracted_at = EXCLUDED.extracted_at
+            RETURNING *
+            "#,
+        )
+        .bind(input.attachment_id)
+        .bind(input.ticket_id)
+        .bind(input.message_id)
+        .bind(&input.file_name)
+        .bind(&content_type)
+        .bind(extractor.name())
+        .bind(&text)
+        .bind(self.now())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(Some(indexed))
+    }
+
+    /// Fail unless the attachment's ticket exists and its message, if any, is on it
+    async fn check_attachment_owner(&self, input: &IndexAttachmentInput) -> Result<()> {
+        self.find_by_id(input.ticket_id).await?;
+        if let Some(message_id) = input.message_id {
+            let on_ticket: bool = sqlx::query_scalar(
+                "SELECT EXISTS (SELECT 1 FROM ticket_messages WHERE id = $1 AND ticket_id = $2)"
+            )
+            .bind(message_id)
+            .bind(input.ticket_id)
+            .fetch_one(&self.pool)
+            .await?;
+            if !on_ticket {
+                return Err(SupportError::MessageNotFound(message_id));
+            }
+        }
+        Ok(())
+    }
+
+    /// Register a stored attachment, scan it, and index it once it is clean
+    ///
+    /// The attachment is recorded as `PENDING`, passed to the repository's
+    /// [`ScannerHook`], then marked `AVAILABLE` and indexed for search, or
+    /// `QUARANTINED` with the threat found and kept out of search. Without a
+    /// scanner it becomes available straight away. When the scanner fails the
+    /// attachment stays pending and the error is returned; registering it
+    /// again rescans it.
+    pub async fn register_attachment(&self, input: &IndexAttachmentInput, content: &[u8]) -> Result<TicketAttachment> {
+        self.ensure_writable()?;
+        self.check_attachment_owner(input).await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO ticket_attachments (attachment_id, ticket_id, message_id, file_name, content_type, size_bytes, status, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, 'PENDING', $7)
+            ON CONFLICT (attachment_id) DO UPDATE SET
+                ticket_id = EXCLUDED.ticket_id,
+                message_id = EXCLUDED.message_id,
+                file_name = EXCLUDED.file_name,
+                content_type = EXCLUDED.content_type,
+                size_bytes = EXCLUDED.size_bytes,
+                status = 'PENDING',
+                scanner = NULL,
+                threat = NULL,
+                scanned_at = NULL
+            "#,
+        )
+        .bind(input.attachment_id)
+        .bind(input.ticket_id)
+        .bind(input.message_id)
+        .bind(&input.file_name)
+        .bind(normalize_content_type(&input.content_type))
+        .bind(content.len() as i64)
+        .bind(self.now())
+        .execute(&self.pool)
+        .await?;
+
+        let (scanner, verdict) = match &self.scanner_hook {
+            Some(scanner) => match scanner.scan(input, content).await {
+                Ok(verdict) => (Some(scanner.name().to_string()), verdict),
+                Err(e) => {
+                    tracing::warn!("Scanner '{}' failed for attachment {}: {}", scanner.name(), input.attachment_id, e);
+                    return Err(e);
+                }
+            },
+            None => (None, ScanVerdict::Clean),
+        };
+        let (status, threat) = match verdict {
+            ScanVerdict::Clean => (AttachmentStatus::Available, None),
+            ScanVerdict::Infected { threat } => (AttachmentStatus::Quarantined, Some(threat)),
+        };
+
+        let attachment = sqlx::query_as::<_, TicketAttachment>(
+            r#"
+            UPDATE ticket_attachments
+            SET status = $2, scanner = $3, threat = $4, scanned_at = $5
+            WHERE attachment_id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(input.attachment_id)
+        .bind(status)
+        .bind(&scanner)
+        .bind(&threat)
+        .bind(self.now())
+        .fetch_one(&self.pool)
+        .await?;
+
+        match attachment.status {
+            AttachmentStatus::Available => {
+                self.index_attachment_text(input, content).await?;
+            }
+            _ => {
+                tracing::warn!(
+                    "Quarantined attachment {} on ticket {}: {}",
+                    attachment.attachment_id,
+                    attachment.ticket_id,
+                    attachment.threat.as_deref().unwrap_or_default()
+                );
+                self.remove_attachment_text(input.attachment_id).await?;
+            }
+        }
+
+        Ok(attachment)
+    }
+
+    /// A registered attachment, whatever its status
+    pub async fn get_attachment(&self, attachment_id: Uuid) -> Result<Option<TicketAttachment>> {
+        let attachment = sqlx::query_as::<_, TicketAttachment>("SELECT * FROM ticket_attachments WHERE attachment_id = $1")
+            .bind(attachment_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(attachment)
+    }
+
+    /// Registered attachments of the ticket, including those of messages
+    /// merged into it, oldest first
+    pub async fn list_ticket_attachments(&self, ticket_id: Uuid) -> Result<Vec<TicketAttachment>> {
+        let attachments = sqlx::query_as::<_, TicketAttachment>(
+            r#"
+            SELECT a.*
+            FROM ticket_attachments a
+            LEFT JOIN ticket_messages m ON m.id = a.message_id
+            WHERE COALESCE(m.ticket_id, a.ticket_id) = $1
+            ORDER BY a.created_at ASC, a.attachment_id ASC
+            "#,
+        )
+        .bind(ticket_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(attachments)
+    }
+
+    /// Drop a deleted attachment's text from search; returns whether it had any
+    pub async fn remove_attachment_text(&self, attachment_id: Uuid) -> Result<bool> {
+        self.ensure_writable()?;
+        let result = sqlx::query("DELETE FROM attachment_texts WHERE attachment_id = $1")
+            .bind(attachment_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Indexed text of the ticket's attachments, including those of messages
+    /// merged into it, oldest first
+    pub async fn get_attachment_texts(&self, ticket_id: Uuid) -> Result<Vec<AttachmentText>> {
+        let texts = sqlx::query_as::<_, AttachmentText>(
+            r#"
+            SELECT a.*
+            FROM attachment_texts a
+            LEFT JOIN ticket_messages m ON m.id = a.message_id
+            WHERE COALESCE(m.ticket_id, a.ticket_id) = $1
+            ORDER BY a.extracted_at ASC, a.attachment_id ASC
+            "#,
+        )
+        .bind(ticket_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(texts)
+    }
+
+    /// Last-seen information for a ticket's customer
+    pub async fn get_last_seen(&self, ticket_id: Uuid) -> Result<TicketLastSeen> {
+        let last_seen = sqlx::query_as::<_, TicketLastSeen>(
+            r#"
+            WITH last_reply AS (
+                SELECT MAX(m.created_at) as created_at
+                FROM ticket_messages m
+                JOIN support_tickets t ON t.id = m.ticket_id
+                WHERE m.ticket_id = $1
+                  AND m.is_internal = FALSE
+                  AND m.author_id <> t.customer_id
+            )
+            SELECT
+                (SELECT MAX(viewed_at) FROM ticket_views WHERE ticket_id = $1) as last_viewed_at,
+                last_reply.created_at as last_agent_reply_at,
+                (SELECT MIN(viewed_at) FROM ticket_views
+                    WHERE ticket_id = $1 AND viewed_at >= last_reply.created_at) as last_agent_reply_viewed_at
+            FROM last_reply
+            "#,
+        )
+        .bind(ticket_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(last_seen)
+    }
+
+    /// Create or update an agent profile for a product
+    pub async fn upsert_agent(&self, product: &str, input: &UpsertSupportAgentInput) -> Result<SupportAgent> {
+        self.ensure_writable()?;
+        let agent = sqlx::query_as::<_, SupportAgent>(
+            r#"
+            INSERT INTO support_agents (
+                product, user_id, display_name, skills, utc_offset_minutes, active, team, role, created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9)
+            ON CONFLICT (product, user_id) DO UPDATE SET
+                display_name = EXCLUDED.display_name,
+                skills = EXCLUDED.skills,
+                utc_offset_minutes = EXCLUDED.utc_offset_minutes,
+                active = EXCLUDED.active,
+                team = EXCLUDED.team,
+                role = EXCLUDED.role,
+                updated_at = EXCLUDED.updated_at
+            RETURNING *
+            "#,
+        )
+        .bind(product)
+        .bind(input.user_id)
+        .bind(&input.display_name)
+        .bind(&input.skills)
+        .bind(input.utc_offset_minutes)
+        .bind(input.active)
+        .bind(&input.team)
+        .bind(input.role)
+        .bind(self.now())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(agent)
+    }
+
+    /// Record a presence heartbeat for an agent; `Unauthorized` for non-agents
+    ///
+    /// Agent consoles call this every minute or so while open; see
+    /// [`wallboard`](crate::wallboard).
+    pub async fn record_agent_presence(&self, product: &str, user_id: Uuid) -> Result<SupportAgent> {
+        self.ensure_writable()?;
+        sqlx::query_as::<_, SupportAgent>(
+            "UPDATE support_agents SET last_seen_at = $3 WHERE product = $1 AND user_id = $2 RETURNING *"
+        )
+        .bind(product)
+        .bind(user_id)
+        .bind(self.now())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(SupportError::Unauthorized)
+    }
+
+    /// Record that an agent has a ticket open, returning everyone on it
+    ///
+    /// Consoles call this every few seconds while the ticket is open; see
+    /// [`viewers`](crate::viewers). `Unauthorized` unless the user has an
+    /// active agent profile in the ticket's product.
+    pub async fn record_ticket_viewing(&self, ticket_id: Uuid, user_id: Uuid) -> Result<Vec<TicketViewer>> {
+        self.record_ticket_viewer(ticket_id, user_id, None).await
+    }
+
+    /// Record that an agent started or stopped typing a reply, returning everyone on the ticket
+    ///
+    /// Typing also counts as viewing. The indicator lapses on its own after
+    /// [`TYPING_TIMEOUT_SECONDS`] without another call.
+    pub async fn record_ticket_typing(&self, ticket_id: Uuid, user_id: Uuid, typing: bool) -> Result<Vec<TicketViewer>> {
+        self.record_ticket_viewer(ticket_id, user_id, Some(typing)).await
+    }
+
+    async fn record_ticket_viewer(&self, ticket_id: Uuid, user_id: Uuid, typing: Option<bool>) -> Result<Vec<TicketViewer>> {
+        self.ensure_writable()?;
+        let now = self.now();
+        let ticket = self.find_by_id(ticket_id).await?;
+
+        let recorded = sqlx::query(
+            r#"
+            INSERT INTO ticket_viewers (ticket_id, user_id, viewing_since, last_seen_at, typing_at)
+            SELECT $1, $2, $3, $3, CASE WHEN $4 THEN $3 END
+            FROM support_agents
+            WHERE product = $5 AND user_id = $2 AND active = TRUE
+            ON CONFLICT (ticket_id, user_id) DO UPDATE SET
+                viewing_since = CASE
+                    WHEN ticket_viewers.last_seen_at < $3 - make_interval(secs => $6) THEN $3
+                    ELSE ticket_viewers.viewing_since
+                END,
+                last_seen_at = $3,
+                typing_at = CASE
+                    WHEN $4 IS NULL THEN ticket_viewers.typing_at
+                    WHEN $4 THEN $3
+                END
+            "#,
+        )
+        .bind(ticket_id)
+        .bind(user_id)
+        .bind(now)
+        .bind(typing)
+        .bind(&ticket.product)
+        .bind(VIEWER_TIMEOUT_SECONDS as f64)
+        .execute(&self.pool)
+        .await?;
+        if recorded.rows_affected() == 0 {
+            return Err(SupportError::Unauthorized);
+        }
+
+        self.get_ticket_viewers(ticket_id).await
+    }
+
+    /// Record that an agent closed a ticket, so others stop seeing them on it
+    pub async fn leave_ticket(&self, ticket_id: Uuid, user_id: Uuid) -> Result<bool> {
+        self.ensure_writable()?;
+        let result = sqlx::query("DELETE FROM ticket_viewers WHERE ticket_id = $1 AND user_id = $2")
+            .bind(ticket_id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Agents currently on a ticket, longest-present first
+    pub async fn get_ticket_viewers(&self, ticket_id: Uuid) -> Result<Vec<TicketViewer>> {
+        let viewers = sqlx::query_as::<_, TicketViewer>(
+            r#"
+            SELECT
+                v.user_id,
+                sa.display_name,
+                v.viewing_since,
+                v.last_seen_at,
+                COALESCE(v.typing_at > $2 - make_interval(secs => $4), FALSE) as typing
+            FROM ticket_viewers v
+            JOIN support_tickets st ON st.id = v.ticket_id
+            LEFT JOIN support_agents sa ON sa.product = st.product AND sa.user_id = v.user_id
+            WHERE v.ticket_id = $1
+              AND v.last_seen_at > $2 - make_interval(secs => $3)
+            ORDER BY v.viewing_since ASC, v.user_id ASC
+            "#,
+        )
+        .bind(ticket_id)
+        .bind(self.now())
+        .bind(VIEWER_TIMEOUT_SECONDS as f64)
+        .bind(TYPING_TIMEOUT_SECONDS as f64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(viewers)
+    }
+
+    /// List agent profiles for a product
+    pub async fn list_agents(&self, product: &str) -> Result<Vec<SupportAgent>> {
+        let agents = sqlx::query_as::<_, SupportAgent>(
+            "SELECT * FROM support_agents WHERE product = $1 ORDER BY display_name ASC"
+        )
+        .bind(product)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(agents)
+    }
+
+    /// Create a canned response; team responses belong to the author's team
+    ///
+    /// Returns `Unauthorized` unless the author's agent profile may manage
+    /// responses in the requested scope.
+    pub async fn create_canned_response(
+        &self,
+        product: &str,
+        author_id: Uuid,
+        input: &CreateCannedResponseInput,
+    ) -> Result<CannedResponse> {
+        self.ensure_writable()?;
+        if input.title.trim().is_empty() || input.body.trim().is_empty() {
+            return Err(SupportError::Validation("Canned response title and body are required".to_string()));
+        }
+        template_placeholders(&input.body)?;
+
+        let author = self.agent_profile(product, author_id).await?;
+        let team = match input.scope {
+            CannedResponseScope::Team => Some(author.team.clone().ok_or_else(|| {
+                SupportError::Validation("Team responses require the author to be on a team".to_string())
+            })?),
+            _ => None,
+        };
+        if !author.can_manage_canned_response(input.scope, author_id, team.as_deref()) {
+            return Err(SupportError::Unauthorized);
+        }
+
+        let response = sqlx::query_as::<_, CannedResponse>(
+            r#"
+            INSERT INTO canned_responses (product, scope, owner_id, team, title, body, locale, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $8)
+            RETURNING *
+            "#,
+        )
+        .bind(product)
+        .bind(input.scope)
+        .bind(author_id)
+        .bind(&team)
+        .bind(input.title.trim())
+        .bind(&input.body)
+        .bind(&input.locale)
+        .bind(self.now())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(response)
+    }
+
+    /// Edit a canned response's title or body
+    ///
+    /// Returns `Unauthorized` unless the editor may manage the response's scope.
+    pub async fn update_canned_response(
+        &self,
+        response_id: Uuid,
+        editor_id: Uuid,
+        input: &UpdateCannedResponseInput,
+    ) -> Result<CannedResponse> {
+        self.ensure_writable()?;
+        let response = self.find_canned_response(response_id).await?;
+        self.authorize_canned_response_edit(&response, editor_id).await?;
+
+        let title = input.title.as_deref().map(str::trim);
+        if title.is_some_and(str::is_empty) || input.body.as_deref().is_some_and(|b| b.trim().is_empty()) {
+            return Err(SupportError::Validation("Canned response title and body are required".to_string()));
+        }
+        if let Some(body) = &input.body {
+            template_placeholders(body)?;
+        }
+
+        let response = sqlx::query_as::<_, CannedResponse>(
+            r#"
+            UPDATE canned_responses
+            SET title = COALESCE($2, title),
+                body = COALESCE($3, body),
+                locale = COALESCE($4, locale),
+                updated_at = $5
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(response_id)
+        .bind(title)
+        .bind(&input.body)
+        .bind(&input.locale)
+        .bind(self.now())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(response)
+    }
+
+    /// Delete a canned response
+    ///
+    /// Returns `Unauthorized` unless the editor may manage the response's scope.
+    pub async fn delete_canned_response(&self, response_id: Uuid, editor_id: Uuid) -> Result<()> {
+        self.ensure_writable()?;
+        let response = self.find_canned_response(response_id).await?;
+        self.authorize_canned_response_edit(&response, editor_id).await?;
+
+        sqlx::query("DELETE FROM canned_responses WHERE id = $1")
+            .bind(response_id)
+            .execute(&self.pool)
+            .await
+            .map_err(SupportError::from)?;
+
+        Ok(())
+    }
+
+    /// Canned responses an agent can use: their own, their team's and the product's
+    pub async fn list_canned_responses(&self, product: &str, agent_id: Uuid) -> Result<Vec<CannedResponse>> {
+        let responses = sqlx::query_as::<_, CannedResponse>(
+            r#"
+            SELECT cr.*
+            FROM canned_responses cr
+            LEFT JOIN support_agents sa ON sa.product = cr.product AND sa.user_id = $2
+            WHERE cr.product = $1
+              AND (
+                  cr.scope = 'PRODUCT'
+                  OR (cr.scope = 'PERSONAL' AND cr.owner_id = $2)
+                  OR (cr.scope = 'TEAM' AND cr.team = sa.team)
+              )
+            ORDER BY cr.scope DESC, cr.title ASC
+            "#,
+        )
+        .bind(product)
+        .bind(agent_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(responses)
+    }
+
+    /// Count a use of a canned response in a reply
+    pub async fn record_canned_response_use(&self, response_id: Uuid) -> Result<CannedResponse> {
+        self.ensure_writable()?;
+        let response = sqlx::query_as::<_, CannedResponse>(
+            r#"
+            UPDATE canned_responses
+            SET usage_count = usage_count + 1, last_used_at = $2
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(response_id)
+        .bind(self.now())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        response.ok_or_else(|| SupportError::Validation(format!("Canned response not found: {}", response_id)))
+    }
+
+    /// Send a canned response as a public reply on a ticket
+    ///
+    /// Placeholders in the body are filled in first, from the ticket,
+    /// customer and agent and from `variables` (see [`crate::templates`]).
+    /// When the response's locale is in a different language from the
+    /// ticket's (either defaulting to the product's locale) and `translate`
+    /// is set, the body is translated with the registered provider first.
+    /// Translation never blocks the reply: the original body is sent when it
+    /// is disabled, unavailable or fails, and the message records why.
+    pub async fn send_canned_response(
+        &self,
+        author_id: Uuid,
+        ticket_id: Uuid,
+        response_id: Uuid,
+        variables: &[TemplateVariable],
+        translate: bool,
+    ) -> Result<TicketMessage> {
+        self.ensure_writable()?;
+        let ticket = self.find_by_id(ticket_id).await?;
+        let (response, body) = self.render_usable_canned_response(author_id, &ticket, response_id, variables).await?;
+
+        let product_locale = self.locales.locale_for(&ticket.product).locale;
+        let source_locale = response.locale.clone().unwrap_or_else(|| product_locale.clone());
+        let target_locale = ticket.locale.clone().unwrap_or(product_locale);
+
+        let (content, status) = if same_language(&source_locale, &target_locale) {
+            (body, TranslationStatus::NotNeeded)
+        } else if !translate {
+            (body, TranslationStatus::Disabled)
+        } else if let Some(provider) = &self.translation_provider {
+            match provider.translate(&body, &source_locale, &target_locale).await {
+                Ok(translated) => (translated, TranslationStatus::Translated),
+                Err(e) => {
+                    tracing::warn!(
+                        canned_response_id = %response_id,
+                        source_locale = %source_locale,
+                        target_locale = %target_locale,
+                        error = %e,
+                        "Canned response translation failed; sending original text"
+                    );
+                    (body, TranslationStatus::Failed)
+                }
+            }
+        } else {
+            (body, TranslationStatus::Unavailable)
+        };
+
+        let message = self
+            .add_message(author_id, &AddTicketMessageInput {
+                ticket_id,
+                content,
+                is_internal: false,
+                visibility: None,
+                content_format: MessageContentFormat::Plaintext,
+            })
+            .await?;
+
+        let translated_from = (status == TranslationStatus::Translated).then_some(source_locale);
+        let message = sqlx::query_as::<_, TicketMessage>(
+            r#"
+            UPDATE ticket_messages
+            SET canned_response_id = $2, translation_status = $3, translated_from = $4
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(message.id)
+        .bind(response_id)
+        .bind(status)
+        .bind(translated_from)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        self.record_canned_response_use(response_id).await?;
+
+        Ok(message)
+    }
+
+    /// A canned response's body with its placeholders filled in for a ticket, untranslated
+    ///
+    /// Lets agents preview what `send_canned_response` would send.
+    pub async fn render_canned_response(
+        &self,
+        author_id: Uuid,
+        ticket_id: Uuid,
+        response_id: Uuid,
+        variables: &[TemplateVariable],
+    ) -> Result<String> {
+        let ticket = self.find_by_id(ticket_id).await?;
+        let (_, body) = self.render_usable_canned_response(author_id, &ticket, response_id, variables).await?;
+
+        Ok(body)
+    }
+
+    /// Check the agent may use the response on the ticket and render its body
+    async fn render_usable_canned_response(
+        &self,
+        author_id: Uuid,
+        ticket: &SupportTicket,
+        response_id: Uuid,
+        variables: &[TemplateVariable],
+    ) -> Result<(CannedResponse, String)> {
+        let response = self.find_canned_response(response_id).await?;
+        if response.product != ticket.product {
+            return Err(SupportError::Validation(format!("Canned response not found: {}", response_id)));
+        }
+        let agent = self.agent_profile(&ticket.product, author_id).await?;
+        if !self.list_canned_responses(&ticket.product, author_id).await?.iter().any(|r| r.id == response_id) {
+            return Err(SupportError::Unauthorized);
+        }
+
+        let mut values = HashMap::from([
+            ("agent_name".to_string(), agent.display_name),
+            ("ticket_ref".to_string(), ticket.ticket_ref.clone()),
+            ("ticket_subject".to_string(), ticket.subject.clone()),
+            ("product".to_string(), ticket.product.clone()),
+        ]);
+        if let Some(customer) = self.lookup_customers(&[ticket.customer_id]).await?.remove(&ticket.customer_id) {
+            values.extend(customer.name.map(|name| ("customer_name".to_string(), name)));
+            values.extend(customer.email.map(|email| ("customer_email".to_string(), email)));
+        }
+        values.extend(variables.iter().map(|v| (v.name.trim().to_string(), v.value.clone())));
+
+        let body = render_template(&response.body, &values)?;
+        Ok((response, body))
+    }
+
+    /// Canned responses across all scopes, most used first, for pruning stale templates
+    ///
+    /// Never-used responses come last, oldest first. `limit` of `None` returns all.
+    pub async fn most_used_canned_responses(&self, product: &str, limit: Option<i64>) -> Result<Vec<CannedResponse>> {
+        let responses = sqlx::query_as::<_, CannedResponse>(
+            r#"
+            SELECT * FROM canned_responses
+            WHERE product = $1
+            ORDER BY usage_count DESC, last_used_at DESC NULLS LAST, created_at ASC
+            LIMIT $2
+            "#,
+        )
+        .bind(product)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(responses)
+    }
+
+    async fn find_canned_response(&self, response_id: Uuid) -> Result<CannedResponse> {
+        sqlx::query_as::<_, CannedResponse>("SELECT * FROM canned_responses WHERE id = $1")
+            .bind(response_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(SupportError::from)?
+            .ok_or_else(|| SupportError::Validation(format!("Canned response not found: {}", response_id)))
+    }
+
+    async fn authorize_canned_response_edit(&self, response: &CannedResponse, editor_id: Uuid) -> Result<()> {
+        let editor = self.agent_profile(&response.product, editor_id).await?;
+        if editor.can_manage_canned_response(response.scope, response.owner_id, response.team.as_deref()) {
+            Ok(())
+        } else {
+            Err(SupportError::Unauthorized)
+        }
+    }
+
+    /// The agent profile of `user_id`; `Unauthorized` for non-agents
+    async fn agent_profile(&self, product: &str, user_id: Uuid) -> Result<SupportAgent> {
+        sqlx::query_as::<_, SupportAgent>("SELECT * FROM support_agents WHERE product = $1 AND user_id = $2")
+            .bind(product)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(SupportError::from)?
+            .ok_or(SupportError::Unauthorized)
+    }
+
+    /// A product's knowledge base articles, most recently updated first
+    pub async fn list_knowledge_articles(&self, product: &str) -> Result<Vec<KnowledgeArticle>> {
+        let articles = sqlx::query_as::<_, KnowledgeArticle>(
+            "SELECT * FROM knowledge_articles WHERE product = $1 ORDER BY updated_at DESC, id ASC"
+        )
+        .bind(product)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(articles)
+    }
+
+    /// Add a knowledge base article, indexed in its locale's language
+    pub async fn create_knowledge_article(
+        &self,
+        product: &str,
+        created_by: Option<Uuid>,
+        input: &CreateKnowledgeArticleInput,
+    ) -> Result<KnowledgeArticle> {
+        self.ensure_writable()?;
+        if input.title.trim().is_empty() || input.body.trim().is_empty() {
+            return Err(SupportError::Validation("Article title and body are required".to_string()));
+        }
+
+        let article = sqlx::query_as::<_, KnowledgeArticle>(
+            r#"
+            INSERT INTO knowledge_articles (
+                product, title, body, locale, search_config, published, created_by, created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, support_search_config(COALESCE($4, $5)), $6, $7, $8, $8)
+            RETURNING *
+            "#,
+        )
+        .bind(product)
+        .bind(input.title.trim())
+        .bind(&input.body)
+        .bind(&input.locale)
+        .bind(self.locales.locale_for(product).locale)
+        .bind(input.published)
+        .bind(created_by)
+        .bind(self.now())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(article)
+    }
+
+    /// Edit or (un)publish a knowledge base article
+    pub async fn update_knowledge_article(&self, article_id: Uuid, input: &UpdateKnowledgeArticleInput) -> Result<KnowledgeArticle> {
+        self.ensure_writable()?;
+        let title = input.title.as_deref().map(str::trim);
+        if title.is_some_and(str::is_empty) || input.body.as_deref().is_some_and(|b| b.trim().is_empty()) {
+            return Err(SupportError::Validation("Article title and body are required".to_string()));
+        }
+
+        let product: String = sqlx::query_scalar("SELECT product FROM knowledge_articles WHERE id = $1")
+            .bind(article_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| SupportError::Validation(format!("Knowledge article not found: {}", article_id)))?;
+
+        let article = sqlx::query_as::<_, KnowledgeArticle>(
+            r#"
+            UPDATE knowledge_articles
+            SET title = COALESCE($2, title),
+                body = COALESCE($3, body),
+                locale = COALESCE($4, locale),
+                search_config = support_search_config(COALESCE($4, locale, $5)),
+                published = COALESCE($6, published),
+                updated_at = $7
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(article_id)
+        .bind(title)
+        .bind(&input.body)
+        .bind(&input.locale)
+        .bind(self.locales.locale_for(&product).locale)
+        .bind(input.published)
+        .bind(self.now())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(article)
+    }
+
+    pub async fn delete_knowledge_article(&self, article_id: Uuid) -> Result<bool> {
+        self.ensure_writable()?;
+        let result = sqlx::query("DELETE FROM knowledge_articles WHERE id = $1")
+            .bind(article_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Published articles most similar to a ticket's subject and description
+    ///
+    /// Words are stemmed in the ticket's language. Best first, at most
+    /// `limit` (see [`suggestion_limit`]); empty when no article shares a
+    /// word with the ticket.
+    pub async fn suggest_articles(&self, ticket_id: Uuid, limit: Option<i64>) -> Result<Vec<ArticleSuggestion>> {
+        let ticket = self.find_by_id(ticket_id).await?;
+        let locale = ticket.locale.unwrap_or_else(|| self.locales.locale_for(&ticket.product).locale);
+
+        self.rank_articles(&ticket.product, &locale, &ticket.subject, &ticket.description, limit).await
+    }
+
+    /// Published articles most similar to a ticket that is not created yet
+    ///
+    /// For deflecting tickets while the customer writes them; `locale`
+    /// defaults to the product's. See [`suggest_articles`](Self::suggest_articles).
+    pub async fn suggest_articles_for_text(
+        &self,
+        product: &str,
+        subject: &str,
+        description: &str,
+        locale: Option<&str>,
+        limit: Option<i64>,
+    ) -> Result<Vec<ArticleSuggestion>> {
+        let locale = match locale {
+            Some(locale) => locale.to_string(),
+            None => self.locales.locale_for(product).locale,
+        };
+
+        self.rank_articles(product, &locale, subject, description, limit).await
+    }
+
+    async fn rank_articles(
+        &self,
+        product: &str,
+        locale: &str,
+        subject: &str,
+        description: &str,
+        limit: Option<i64>,
+    ) -> Result<Vec<ArticleSuggestion>> {
+        let suggestions = sqlx::query_as::<_, ArticleSuggestion>(
+            r#"
+            WITH q AS (
+                SELECT support_similarity_query(support_search_config($2), $3 || ' ' || $4) AS query
+            )
+            SELECT ka.*, ts_rank(ka.search_vector, q.query, 1)::FLOAT8 AS score
+            FROM knowledge_articles ka, q
+            WHERE ka.product = $1
+              AND ka.published = TRUE
+              AND ka.search_vector @@ q.query
+            ORDER BY score DESC, ka.updated_at DESC, ka.id ASC
+            LIMIT $5
+            "#,
+        )
+        .bind(product)
+        .bind(locale)
+        .bind(subject)
+        .bind(description)
+        .bind(suggestion_limit(limit))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(suggestions)
+    }
+
+    /// Rank the product's active agents as assignees for a ticket, with reasons
+    ///
+    /// Works independently of any automatic assignment.
+    pub async fn suggest_assignee(&self, ticket_id: Uuid, limit: usize) -> Result<Vec<AssigneeSuggestion>> {
+        let ticket = self.find_by_id(ticket_id).await?;
+
+        let candidates = sqlx::query_as::<_, AgentCandidate>(&format!(
+            r#"
+            SELECT
+                a.*,
+                COALESCE(w.open_tickets, 0)::BIGINT as open_tickets,
+                COALESCE(w.weighted_workload, 0)::BIGINT as weighted_workload,
+                (SELECT COUNT(*) FROM support_tickets st
+                    WHERE st.assigned_to = a.user_id
+                      AND st.customer_id = $2
+                      AND st.id <> $3
+                      AND st.deleted_at IS NULL)::BIGINT as customer_tickets
+            FROM support_agents a
+            {OPEN_WORKLOAD_JOIN}
+            WHERE a.product = $1 AND a.active = TRUE
+            "#,
+            OPEN_WORKLOAD_JOIN = self.open_workload_join(),
+        ))
+        .bind(&ticket.product)
+        .bind(ticket.customer_id)
+        .bind(ticket.id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        let mut suggestions = rank_candidates(&ticket, candidates, self.now());
+        suggestions.truncate(limit);
+        Ok(suggestions)
+    }
+
+    /// Open tickets and weighted workload of each of the product's active
+    /// agents, heaviest workload first
+    pub async fn agent_workloads(&self, product: &str) -> Result<Vec<AgentWorkload>> {
+        let mut tx = self.begin_timed(OperationClass::Read).await?;
+        let workloads = sqlx::query_as::<_, AgentWorkload>(&format!(
+            r#"
+            SELECT
+                a.user_id as agent_id,
+                a.display_name as agent_name,
+                a.team,
+                COALESCE(w.open_tickets, 0)::BIGINT as open_tickets,
+                COALESCE(w.urgent_tickets, 0)::BIGINT as urgent_tickets,
+                COALESCE(w.high_tickets, 0)::BIGINT as high_tickets,
+                COALESCE(w.medium_tickets, 0)::BIGINT as medium_tickets,
+                COALESCE(w.low_tickets, 0)::BIGINT as low_tickets,
+                COALESCE(w.weighted_workload, 0)::BIGINT as weighted_workload
+            FROM support_agents a
+            {OPEN_WORKLOAD_JOIN}
+            WHERE a.product = $1 AND a.active = TRUE
+            ORDER BY weighted_workload DESC, open_tickets DESC, a.display_name, a.user_id
+            "#,
+            OPEN_WORKLOAD_JOIN = self.open_workload_join(),
+        ))
+        .bind(product)
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(workloads)
+    }
+
+    /// `LEFT JOIN LATERAL` of the open tickets assigned to agent `a` in its
+    /// product, as `w` with per-priority counts and the weighted workload
+    fn open_workload_join(&self) -> String {
+        format!(
+            r#"
+            LEFT JOIN LATERAL (
+                SELECT
+                    COUNT(*) as open_tickets,
+                    COUNT(*) FILTER (WHERE st.priority = 'URGENT') as urgent_tickets,
+                    COUNT(*) FILTER (WHERE st.priority = 'HIGH') as high_tickets,
+                    COUNT(*) FILTER (WHERE st.priority = 'MEDIUM') as medium_tickets,
+                    COUNT(*) FILTER (WHERE st.priority = 'LOW') as low_tickets,
+                    SUM({}) as weighted_workload
+                FROM support_tickets st
+                WHERE st.assigned_to = a.user_id
+                  AND st.product = a.product
+                  AND st.deleted_at IS NULL
+                  AND st.status NOT IN ('RESOLVED', 'CLOSED')
+            ) w ON TRUE
+            "#,
+            self.workload_weights.sql_case("st.priority"),
+        )
+    }
+
+    /// Assign an unassigned ticket with the product's assignment rules
+    ///
+    /// Returns `None` when no active rule matches the ticket or has an
+    /// eligible agent; the ticket then stays unassigned.
+    pub async fn auto_assign_ticket(&self, ticket_id: Uuid) -> Result<Option<RoutingDecision>> {
+        self.ensure_writable()?;
+        let now = self.now();
+        let mut tx = self.pool.begin().await?;
+
+        let ticket = sqlx::query_as::<_, SupportTicket>(
+            "SELECT * FROM support_tickets WHERE id = $1 AND deleted_at IS NULL FOR UPDATE"
+        )
+        .bind(ticket_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(SupportError::TicketNotFound(ticket_id))?;
+
+        if ticket.assigned_to.is_some() {
+            return Err(SupportError::Validation("Ticket is already assigned".to_string()));
+        }
+        if matches!(ticket.status, TicketStatus::Resolved | TicketStatus::Closed) {
+            return Err(SupportError::Validation("Resolved or closed tickets are not routed".to_string()));
+        }
+
+        let decision = self.route_ticket(&mut tx, &ticket, now).await?;
+        tx.commit().await?;
+
+        Ok(decision)
+    }
+
+    /// Apply the first matching assignment rule that has an eligible agent
+    ///
+    /// The product's rules are locked for the transaction so concurrent
+    /// routing advances round-robin cursors one ticket at a time.
+    async fn route_ticket(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        ticket: &SupportTicket,
+        now: DateTime<Utc>,
+    ) -> Result<Option<RoutingDecision>> {
+        let rules = sqlx::query_as::<_, AssignmentRule>(
+            r#"
+            SELECT * FROM assignment_rules
+            WHERE product = $1 AND active = TRUE
+            ORDER BY position ASC, created_at ASC, id ASC
+            FOR UPDATE
+            "#,
+        )
+        .bind(&ticket.product)
+        .fetch_all(&mut **tx)
+        .await?;
+
+        if !rules.iter().any(|rule| rule.matches(ticket)) {
+            return Ok(None);
+        }
+
+        let candidates = sqlx::query_as::<_, RoutingCandidate>(&format!(
+            r#"
+            SELECT
+                a.user_id,
+                a.team,
+                a.skills,
+                COALESCE(w.open_tickets, 0)::BIGINT as open_tickets,
+                COALESCE(w.weighted_workload, 0)::BIGINT as weighted_workload
+            FROM support_agents a
+            {OPEN_WORKLOAD_JOIN}
+            WHERE a.product = $1 AND a.active = TRUE
+            ORDER BY a.user_id ASC
+            "#,
+            OPEN_WORKLOAD_JOIN = self.open_workload_join(),
+        ))
+        .bind(&ticket.product)
+        .fetch_all(&mut **tx)
+        .await?;
+
+        for rule in rules.iter().filter(|rule| rule.matches(ticket)) {
+            let Some(agent_id) = rule.pick_agent(ticket, &candidates) else {
+                continue;
+            };
+
+            sqlx::query("UPDATE assignment_rules SET last_assigned_to = $2 WHERE id = $1")
+                .bind(rule.id)
+                .bind(agent_id)
+                .execute(&mut **tx)
+                .await?;
+
+            let assigned = sqlx::query_as::<_, SupportTicket>(
+                "UPDATE support_tickets SET assigned_to = $2, updated_at = $3 WHERE id = $1 RETURNING *"
+            )
+            .bind(ticket.id)
+            .bind(agent_id)
+            .bind(now)
+            .fetch_one(&mut **tx)
+            .await?;
+
+            let agent = agent_id.to_string();
+            Self::insert_audit_entry(tx, ticket.id, None, "assigned_to", None, Some(&agent), None, now).await?;
+
+            return Ok(Some(RoutingDecision {
+                ticket: assigned,
+                rule_id: rule.id,
+                rule_name: rule.name.clone(),
+                strategy: rule.strategy,
+                agent_id,
+            }));
+        }
+
+        Ok(None)
+    }
+
+    /// A product's assignment rules in routing order
+    pub async fn list_assignment_rules(&self, product: &str) -> Result<Vec<AssignmentRule>> {
+        let rules = sqlx::query_as::<_, AssignmentRule>(
+            "SELECT * FROM assignment_rules WHERE product = $1 ORDER BY position ASC, created_at ASC, id ASC"
+        )
+        .bind(product)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rules)
+    }
+
+    /// Add an assignment rule; new tickets are routed by it immediately
+    pub async fn create_assignment_rule(&self, product: &str, input: &CreateAssignmentRuleInput) -> Result<AssignmentRule> {
+        self.ensure_writable()?;
+        if input.name.trim().is_empty() {
+            return Err(SupportError::Validation("Assignment rule name is required".to_string()));
+        }
+        if input.max_open_tickets.is_some_and(|max| max <= 0) {
+            return Err(SupportError::Validation("max_open_tickets must be positive".to_string()));
+        }
+
+        let rule = sqlx::query_as::<_, AssignmentRule>(
+            r#"
+            INSERT INTO assignment_rules (
+                product, name, position, strategy, category, priority, channel, team, max_open_tickets, created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $10)
+            RETURNING *
+            "#,
+        )
+        .bind(product)
+        .bind(input.name.trim())
+        .bind(input.position)
+        .bind(input.strategy)
+        .bind(&input.category)
+        .bind(input.priority)
+        .bind(input.channel)
+        .bind(&input.team)
+        .bind(input.max_open_tickets)
+        .bind(self.now())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(rule)
+    }
+
+    /// Enable or disable an assignment rule without losing its round-robin position
+    pub async fn set_assignment_rule_active(&self, rule_id: Uuid, active: bool) -> Result<AssignmentRule> {
+        self.ensure_writable()?;
+        sqlx::query_as::<_, AssignmentRule>(
+            "UPDATE assignment_rules SET active = $2, updated_at = $3 WHERE id = $1 RETURNING *"
+        )
+        .bind(rule_id)
+        .bind(active)
+        .bind(self.now())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| SupportError::Validation(format!("Assignment rule not found: {}", rule_id)))
+    }
+
+    pub async fn delete_assignment_rule(&self, rule_id: Uuid) -> Result<bool> {
+        self.ensure_writable()?;
+        let result = sqlx::query("DELETE FROM assignment_rules WHERE id = $1")
+            .bind(rule_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// A product's escalation rules, lowest level first
+    pub async fn list_escalation_rules(&self, product: &str) -> Result<Vec<EscalationRule>> {
+        let rules = sqlx::query_as::<_, EscalationRule>(
+            "SELECT * FROM escalation_rules WHERE product = $1 ORDER BY level ASC, created_at ASC, id ASC"
+        )
+        .bind(product)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rules)
+    }
+
+    /// Add an escalation rule; it applies from the next `run_escalations`
+    pub async fn create_escalation_rule(&self, product: &str, input: &CreateEscalationRuleInput) -> Result<EscalationRule> {
+        self.ensure_writable()?;
+        if input.name.trim().is_empty() {
+            return Err(SupportError::Validation("Escalation rule name is required".to_string()));
+        }
+        if input.level <= 0 {
+            return Err(SupportError::Validation("Escalation level must be positive".to_string()));
+        }
+        if input.after_minutes <= 0 {
+            return Err(SupportError::Validation("after_minutes must be positive".to_string()));
+        }
+
+        let rule = sqlx::query_as::<_, EscalationRule>(
+            r#"
+            INSERT INTO escalation_rules (
+                product, name, level, condition, priority, after_minutes, notify_team, bump_priority, created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9)
+            RETURNING *
+            "#,
+        )
+        .bind(product)
+        .bind(input.name.trim())
+        .bind(input.level)
+        .bind(input.condition)
+        .bind(input.priority)
+        .bind(input.after_minutes)
+        .bind(&input.notify_team)
+        .bind(input.bump_priority)
+        .bind(self.now())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(rule)
+    }
+
+    pub async fn set_escalation_rule_active(&self, rule_id: Uuid, active: bool) -> Result<EscalationRule> {
+        self.ensure_writable()?;
+        sqlx::query_as::<_, EscalationRule>(
+            "UPDATE escalation_rules SET active = $2, updated_at = $3 WHERE id = $1 RETURNING *"
+        )
+        .bind(rule_id)
+        .bind(active)
+        .bind(self.now())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| SupportError::Validation(format!("Escalation rule not found: {}", rule_id)))
+    }
+
+    pub async fn delete_escalation_rule(&self, rule_id: Uuid) -> Result<bool> {
+        self.ensure_writable()?;
+        let result = sqlx::query("DELETE FROM escalation_rules WHERE id = $1")
+            .bind(rule_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Escalate a product's neglected tickets by its active escalation rules
+    ///
+    /// Meant to run every few minutes from a scheduler. Rules are applied
+    /// highest level first; each qualifying ticket is raised to the rule's
+    /// level, its priority bumped if the rule says so (recomputing its SLA
+    /// deadlines), and the change audited. The escalations are sent to the
+    /// notification sink and published as `ticket.updated` events. A sink
+    /// failure rolls back that rule's escalations so they are retried on the
+    /// next run. Does nothing while a maintenance window is active. Returns
+    /// the escalations made.
+    pub async fn run_escalations(&self, product: &str) -> Result<Vec<TicketEscalated>> {
+        self.ensure_writable()?;
+        let now = self.now();
+        if !Self::maintenance_windows_at(&self.pool, product, now).await?.is_empty() {
+            tracing::info!("Skipping escalations for {} during maintenance", product);
+            return Ok(Vec::new());
+        }
+
+        let rules = sqlx::query_as::<_, EscalationRule>(
+            r#"
+            SELECT * FROM escalation_rules
+            WHERE product = $1 AND active = TRUE
+            ORDER BY level DESC, created_at ASC, id ASC
+            "#,
+        )
+        .bind(product)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut escalations = Vec::new();
+        for rule in rules {
+            let mut tx = self.pool.begin().await?;
+            let tickets = sqlx::query_as::<_, SupportTicket>(
+                r#"
+                SELECT * FROM support_tickets
+                WHERE product = $1 AND deleted_at IS NULL AND NOT test_mode
+                  AND status IN ('NEW', 'IN_PROGRESS')
+                  AND escalation_level < $2
+                  AND ($3::ticket_priority IS NULL OR priority = $3)
+                  AND created_at <= $4 - make_interval(mins => $5)
+                  AND ($6 <> 'UNANSWERED' OR first_response_at IS NULL)
+                ORDER BY created_at ASC, id ASC
+                FOR UPDATE SKIP LOCKED
+                "#,
+            )
+            .bind(product)
+            .bind(rule.level)
+            .bind(rule.priority)
+            .bind(now)
+            .bind(rule.after_minutes)
+            .bind(rule.condition)
+            .fetch_all(&mut *tx)
+            .await?;
+            if tickets.is_empty() {
+                continue;
+            }
+
+            let mut rule_escalations = Vec::with_capacity(tickets.len());
+            let mut events = Vec::with_capacity(tickets.len());
+            for before in tickets {
+                let priority = if rule.bump_priority { before.priority.raised() } else { before.priority };
+                let ticket = sqlx::query_as::<_, SupportTicket>(
+                    r#"
+                    UPDATE support_tickets
+                    SET escalation_level = $2, escalated_at = $3, priority = $4, updated_at = $3
+                    WHERE id = $1
+                    RETURNING *
+                    "#,
+                )
+                .bind(before.id)
+                .bind(rule.level)
+                .bind(now)
+                .bind(priority)
+                .fetch_one(&mut *tx)
+                .await?;
+
+                Self::insert_audit_entry(
+                    &mut tx, ticket.id, None, "escalation_level",
+                    Some(&before.escalation_level.to_string()), Some(&ticket.escalation_level.to_string()), None, now,
+                ).await?;
+                let ticket = if before.priority != ticket.priority {
+                    Self::insert_audit_entry(
+                        &mut tx, ticket.id, None, "priority",
+                        Some(before.priority.as_str()), Some(ticket.priority.as_str()), None, now,
+                    ).await?;
+                    self.apply_sla(&mut tx, &[ticket.id], now).await?
+                        .into_iter()
+                        .find(|t| t.id == ticket.id)
+                        .unwrap_or(ticket)
+                } else {
+                    ticket
+                };
+
+                let lead_ids: Vec<Uuid> = sqlx::query_scalar(
+                    r#"
+                    SELECT user_id FROM support_agents
+                    WHERE product = $1 AND active = TRUE AND role = 'LEAD'
+                      AND team = COALESCE($2, (SELECT team FROM support_agents WHERE product = $1 AND user_id = $3))
+                    ORDER BY user_id ASC
+                    "#,
+                )
+                .bind(product)
+                .bind(&rule.notify_team)
+                .bind(ticket.assigned_to)
+                .fetch_all(&mut *tx)
+                .await?;
+
+                let escalation = TicketEscalated {
+                    product: product.to_string(),
+                    ticket_id: ticket.id,
+                    ticket_ref: ticket.ticket_ref.clone(),
+                    rule_id: rule.id,
+                    rule_name: rule.name.clone(),
+                    previous_level: before.escalation_level,
+                    level: ticket.escalation_level,
+                    previous_priority: before.priority,
+                    priority: ticket.priority,
+                    lead_ids,
+                    escalated_at: now,
+                };
+                self.notification_sink.notify_escalation(&escalation).await?;
+                events.push(SupportEvent::ticket(SupportEventType::TicketUpdated, &ticket, now));
+                rule_escalations.push(escalation);
+            }
+
+            tx.commit().await?;
+            tracing::info!("Escalation rule '{}' escalated {} tickets", rule.name, rule_escalations.len());
+            self.publish_events(events);
+            escalations.extend(rule_escalations);
+        }
+
+        Ok(escalations)
+    }
+
+    /// A product's automation rules, by trigger and position
+    pub async fn list_automation_rules(&self, product: &str) -> Result<Vec<AutomationRule>> {
+        let rules = sqlx::query_as::<_, AutomationRule>(
+            "SELECT * FROM automation_rules WHERE product = $1 ORDER BY trigger ASC, position ASC, created_at ASC, id ASC"
+        )
+        .bind(product)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rules)
+    }
+
+    /// Add an automation rule; it runs from the next trigger
+    pub async fn create_automation_rule(&self, product: &str, input: &CreateAutomationRuleInput) -> Result<AutomationRule> {
+        self.ensure_writable()?;
+        input.validate()?;
+        for action in input.actions.iter().filter(|a| a.kind == ActionKind::SendCannedReply) {
+            let exists: bool = sqlx::query_scalar(
+                "SELECT EXISTS (SELECT 1 FROM canned_responses WHERE id = $1 AND product = $2)"
+            )
+            .bind(action.value.trim().parse::<Uuid>().ok())
+            .bind(product)
+            .fetch_one(&self.pool)
+            .await?;
+            if !exists {
+                return Err(SupportError::Validation(format!("Canned response not found: {}", action.value.trim())));
+            }
+        }
+
+        let rule = sqlx::query_as::<_, AutomationRule>(
+            r#"
+            INSERT INTO automation_rules (
+                product, name, position, trigger, timer_minutes, conditions, actions, created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $8)
+            RETURNING *
+            "#,
+        )
+        .bind(product)
+        .bind(input.name.trim())
+        .bind(input.position)
+        .bind(input.trigger)
+        .bind(input.timer_minutes)
+        .bind(Json(&input.conditions))
+        .bind(Json(&input.actions))
+        .bind(self.now())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(rule)
+    }
+
+    pub async fn set_automation_rule_active(&self, rule_id: Uuid, active: bool) -> Result<AutomationRule> {
+        self.ensure_writable()?;
+        sqlx::query_as::<_, AutomationRule>(
+            "UPDATE automation_rules SET active = $2, updated_at = $3 WHERE id = $1 RETURNING *"
+        )
+        .bind(rule_id)
+        .bind(active)
+        .bind(self.now())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| SupportError::Validation(format!("Automation rule not found: {}", rule_id)))
+    }
+
+    /// Delete an automation rule and its execution log
+    pub async fn delete_automation_rule(&self, rule_id: Uuid) -> Result<bool> {
+        self.ensure_writable()?;
+        let result = sqlx::query("DELETE FROM automation_rules WHERE id = $1")
+            .bind(rule_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// A rule's most recent executions, newest first
+    pub async fn list_automation_executions(&self, rule_id: Uuid, limit: i64) -> Result<Vec<AutomationExecution>> {
+        let executions = sqlx::query_as::<_, AutomationExecution>(
+            r#"
+            SELECT * FROM automation_executions
+            WHERE rule_id = $1
+            ORDER BY executed_at DESC, id DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(rule_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(executions)
+    }
+
+    /// Fire a product's `TIMER` automations on tickets idle for long enough
+    ///
+    /// Meant to run every few minutes from a scheduler. Each rule fires on
+    /// tickets matching its conditions that have not been updated for its
+    /// `timer_minutes`, at most once per idle period (per ticket update) and
+    /// for up to [`TIMER_BATCH_SIZE`] tickets per run, oldest first. Returns
+    /// the executions, including those of automations they triggered.
+    pub async fn run_timer_automations(&self, product: &str) -> Result<Vec<AutomationExecution>> {
+        self.ensure_writable()?;
+        let now = self.now();
+        let rules = sqlx::query_as::<_, AutomationRule>(
+            r#"
+            SELECT * FROM automation_rules
+            WHERE product = $1 AND trigger = 'TIMER' AND active = TRUE
+            ORDER BY position ASC, created_at ASC, id ASC
+            "#,
+        )
+        .bind(product)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut executions = Vec::new();
+        for rule in rules {
+            let mut sql = String::from(
+                r#"
+                SELECT id FROM support_tickets st
+                WHERE product = $1 AND deleted_at IS NULL
+                  AND updated_at <= $2 - make_interval(mins => $3)
+                  AND NOT EXISTS (
+                      SELECT 1 FROM automation_executions e
+                      WHERE e.rule_id = $4 AND e.ticket_id = st.id AND e.executed_at >= st.updated_at
+                  )
+                "#,
+            );
+            let mut values = Vec::new();
+            for condition in &rule.conditions {
+                if let Some((condition_sql, value)) = condition.to_sql(6 + values.len()) {
+                    sql.push_str(" AND ");
+                    sql.push_str(&condition_sql);
+                    values.extend(value);
+                }
+            }
+            sql.push_str(" ORDER BY updated_at ASC, id ASC LIMIT $5");
+
+            let mut query = sqlx::query_scalar::<_, Uuid>(&sql)
+                .bind(product)
+                .bind(now)
+                .bind(rule.timer_minutes)
+                .bind(rule.id)
+                .bind(TIMER_BATCH_SIZE);
+            for value in &values {
+                query = query.bind(value);
+            }
+            let firings = query
+                .fetch_all(&self.pool)
+                .await?
+                .into_iter()
+                .map(|ticket_id| AutomationFiring {
+                    rule_id: Some(rule.id),
+                    ..AutomationFiring::new(AutomationTrigger::Timer, ticket_id)
+                })
+                .collect();
+            executions.extend(self.run_automations(firings).await?);
+        }
+
+        Ok(executions)
+    }
+
+    /// Publish a committed change's events, then run the automations it triggered
+    ///
+    /// Automation failures are logged, never returned. Returns whether any
+    /// automation applied, so callers can reload what they return.
+    async fn after_commit(&self, after: AfterCommit) -> bool {
+        self.publish_events(after.events);
+        if after.automations.is_empty() {
+            return false;
+        }
+
+        match self.run_automations(after.automations).await {
+            Ok(executions) => executions.iter().any(|e| e.outcome == AutomationOutcome::Applied),
+            Err(e) => {
+                tracing::error!("Failed to run automations: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Run the rules for each firing, then for the firings their actions cause
+    async fn run_automations(&self, firings: Vec<AutomationFiring>) -> Result<Vec<AutomationExecution>> {
+        let mut queue = VecDeque::from(firings);
+        let mut executions = Vec::new();
+        while let Some(firing) = queue.pop_front() {
+            let rules = sqlx::query_as::<_, AutomationRule>(
+                r#"
+                SELECT r.* FROM automation_rules r
+                JOIN support_tickets st ON st.product = r.product
+                WHERE st.id = $1 AND st.deleted_at IS NULL
+                  AND r.trigger = $2 AND r.active = TRUE
+                  AND ($3::UUID IS NULL OR r.id = $3)
+                ORDER BY r.position ASC, r.created_at ASC, r.id ASC
+                "#,
+            )
+            .bind(firing.ticket_id)
+            .bind(firing.trigger)
+            .bind(firing.rule_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+            for rule in rules {
+                let (execution, follow_ups) = self.run_automation_rule(&rule, &firing).await?;
+                executions.extend(execution);
+                queue.extend(follow_ups);
+            }
+        }
+
+        Ok(executions)
+    }
+
+    /// Apply one rule to the firing's ticket if its conditions hold,
+    /// returning the execution and the firings its actions cause
+    async fn run_automation_rule(
+        &self,
+        rule: &AutomationRule,
+        firing: &AutomationFiring,
+    ) -> Result<(Option<AutomationExecution>, Vec<AutomationFiring>)> {
+        let now = self.now();
+        let mut tx = self.pool.begin().await?;
+        let Some(mut ticket) = sqlx::query_as::<_, SupportTicket>(
+            "SELECT * FROM support_tickets WHERE id = $1 AND deleted_at IS NULL FOR UPDATE"
+        )
+        .bind(firing.ticket_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok((None, Vec::new()));
+        };
+
+        let context = AutomationContext {
+            ticket: &ticket,
+            previous_status: firing.previous_status,
+            message_author: firing.message_author,
+        };
+        if !rule.matches(&context) {
+            return Ok((None, Vec::new()));
+        }
+
+        let depth = i32::try_from(firing.chain.len()).unwrap_or(i32::MAX);
+        if firing.chain.contains(&rule.id) || firing.chain.len() >= MAX_AUTOMATION_DEPTH {
+            tracing::warn!("Stopped automation loop at rule '{}' on ticket {}", rule.name, ticket.ticket_ref);
+            let execution = Self::insert_automation_execution(
+                &mut *tx, rule, firing, depth, AutomationOutcome::LoopPrevented, 0, Some("Automation loop"), now,
+            ).await?;
+            tx.commit().await?;
+            return Ok((Some(execution), Vec::new()));
+        }
+
+        let mut after = AfterCommit::default();
+        for (index, action) in rule.actions.iter().enumerate() {
+            match self.apply_automation_action(&mut tx, rule, &ticket, action, now, &mut after).await {
+                Ok(updated) => ticket = updated,
+                Err(e) => {
+                    tx.rollback().await?;
+                    let error = format!("Action {} ({:?}) failed: {}", index + 1, action.kind, e);
+                    tracing::warn!("Automation rule '{}' on ticket {}: {}", rule.name, ticket.ticket_ref, error);
+                    let execution = Self::insert_automation_execution(
+                        &self.pool, rule, firing, depth, AutomationOutcome::Failed, 0, Some(&error), now,
+                    ).await?;
+                    return Ok((Some(execution), Vec::new()));
+                }
+            }
+        }
+
+        let applied = i32::try_from(rule.actions.len()).unwrap_or(i32::MAX);
+        let execution = Self::insert_automation_execution(
+            &mut *tx, rule, firing, depth, AutomationOutcome::Applied, applied, None, now,
+        ).await?;
+        tx.commit().await?;
+        self.publish_events(after.events);
+
+        let follow_ups = after
+            .automations
+            .into_iter()
+            .map(|mut follow_up| {
+                follow_up.chain = firing.chain.iter().copied().chain([rule.id]).collect();
+                follow_up
+            })
+            .collect();
+        Ok((Some(execution), follow_ups))
+    }
+
+    /// Apply one automation action inside `tx`, returning the ticket after it
+    async fn apply_automation_action(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        rule: &AutomationRule,
+        ticket: &SupportTicket,
+        action: &AutomationAction,
+        now: DateTime<Utc>,
+        after: &mut AfterCommit,
+    ) -> Result<SupportTicket> {
+        let value = action.value.trim();
+        let invalid = |e: String| SupportError::Validation(format!("Invalid {:?} action: {}", action.kind, e));
+        let update = match action.kind {
+            ActionKind::SetStatus => {
+                let (status, resolution_code) = AutomationAction::status_value(value).map_err(invalid)?;
+                (status != ticket.status).then(|| UpdateTicketInput {
+                    status: Some(status),
+                    resolution_code: resolution_code.map(str::to_string),
+                    ..Default::default()
+                })
+            }
+            ActionKind::SetPriority => {
+                let priority = value.parse::<TicketPriority>().map_err(invalid)?;
+                (priority != ticket.priority).then(|| UpdateTicketInput { priority: Some(priority), ..Default::default() })
+            }
+            ActionKind::BoostPriority => {
+                let cap = value.parse::<TicketPriority>().map_err(invalid)?;
+                let at_cap = std::iter::successors(Some(cap), |p| (*p != TicketPriority::Urgent).then(|| p.raised()))
+                    .any(|p| p == ticket.priority);
+                (!at_cap).then(|| UpdateTicketInput { priority: Some(ticket.priority.raised()), ..Default::default() })
+            }
+            ActionKind::SetCategory => (ticket.category.as_deref() != Some(value))
+                .then(|| UpdateTicketInput { category: Some(value.to_string()), ..Default::default() }),
+            ActionKind::Assign => {
+                let agent_id = value.parse::<Uuid>().map_err(|e| invalid(e.to_string()))?;
+                (ticket.assigned_to != Some(agent_id)).then(|| UpdateTicketInput { assigned_to: Some(agent_id), ..Default::default() })
+            }
+            ActionKind::AddTag | ActionKind::RemoveTag => {
+                return self.apply_tag_action(tx, ticket, action.kind, &value.to_lowercase(), now, after).await;
+            }
+            ActionKind::SendCannedReply => {
+                let response_id = value.parse::<Uuid>().map_err(|e| invalid(e.to_string()))?;
+                self.send_automated_reply(tx, ticket, response_id, now, after).await?;
+                return Ok(ticket.clone());
+            }
+            ActionKind::EmitWebhook => {
+                let fired = AutomationFired { rule_id: rule.id, rule_name: rule.name.clone(), label: value.to_string() };
+                after.events.push(SupportEvent::automation_triggered(ticket, fired, now));
+                return Ok(ticket.clone());
+            }
+        };
+
+        match update {
+            Some(input) => self.update_ticket_in_tx(tx, ticket.id, None, &input, PropagationPolicy::default(), now, after).await,
+            None => Ok(ticket.clone()),
+        }
+    }
+
+    async fn apply_tag_action(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        ticket: &SupportTicket,
+        kind: ActionKind,
+        tag: &str,
+        now: DateTime<Utc>,
+        after: &mut AfterCommit,
+    ) -> Result<SupportTicket> {
+        let (sql, old_value, new_value) = if kind == ActionKind::AddTag {
+            (
+                "UPDATE support_tickets SET tags = array_append(tags, $2), updated_at = $3 WHERE id = $1 AND NOT ($2 = ANY(tags)) RETURNING *",
+                None,
+                Some(tag),
+            )
+        } else {
+            (
+                "UPDATE support_tickets SET tags = array_remove(tags, $2), updated_at = $3 WHERE id = $1 AND $2 = ANY(tags) RETURNING *",
+                Some(tag),
+                None,
+            )
+        };
+        let Some(updated) = sqlx::query_as::<_, SupportTicket>(sql)
+            .bind(ticket.id)
+            .bind(tag)
+            .bind(now)
+            .fetch_optional(&mut **tx)
+            .await?
+        else {
+            return Ok(ticket.clone());
+        };
+
+        Self::insert_audit_entry(tx, ticket.id, None, "tags", old_value, new_value, None, now).await?;
+        after.events.push(SupportEvent::ticket(SupportEventType::TicketUpdated, &updated, now));
+        Ok(updated)
+    }
+
+    /// Post a canned response as a public system reply, untranslated
+    ///
+    /// `{{agent_name}}` is the assignee's name; without an assignee, responses
+    /// using it fail to render.
+    async fn send_automated_reply(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        ticket: &SupportTicket,
+        response_id: Uuid,
+        now: DateTime<Utc>,
+        after: &mut AfterCommit,
+    ) -> Result<TicketMessage> {
+        let response = sqlx::query_as::<_, CannedResponse>(
+            "SELECT * FROM canned_responses WHERE id = $1 AND product = $2"
+        )
+        .bind(response_id)
+        .bind(&ticket.product)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or_else(|| SupportError::Validation(format!("Canned response not found: {}", response_id)))?;
+
+        let mut values = HashMap::from([
+            ("ticket_ref".to_string(), ticket.ticket_ref.clone()),
+            ("ticket_subject".to_string(), ticket.subject.clone()),
+            ("product".to_string(), ticket.product.clone()),
+        ]);
+        if let Some(agent_id) = ticket.assigned_to {
+            let name: Option<String> = sqlx::query_scalar(
+                "SELECT display_name FROM support_agents WHERE product = $1 AND user_id = $2"
+            )
+            .bind(&ticket.product)
+            .bind(agent_id)
+            .fetch_optional(&mut **tx)
+            .await?;
+            values.extend(name.map(|name| ("agent_name".to_string(), name)));
+        }
+        if let Some(customer) = self.lookup_customers(&[ticket.customer_id]).await?.remove(&ticket.customer_id) {
+            values.extend(customer.name.map(|name| ("customer_name".to_string(), name)));
+            values.extend(customer.email.map(|email| ("customer_email".to_string(), email)));
+        }
+        let content = render_template(&response.body, &values)?;
+
+        let product_locale = self.locales.locale_for(&ticket.product).locale;
+        let source_locale = response.locale.clone().unwrap_or_else(|| product_locale.clone());
+        let target_locale = ticket.locale.clone().unwrap_or(product_locale);
+        let status = if same_language(&source_locale, &target_locale) {
+            TranslationStatus::NotNeeded
+        } else {
+            TranslationStatus::Disabled
+        };
+
+        let message = sqlx::query_as::<_, TicketMessage>(
+            r#"
+            INSERT INTO ticket_messages (ticket_id, author_id, is_internal, content, canned_response_id, translation_status, created_at)
+            VALUES ($1, $2, FALSE, $3, $4, $5, $6)
+            RETURNING *
+            "#,
+        )
+        .bind(ticket.id)
+        .bind(SYSTEM_AUTHOR_ID)
+        .bind(&content)
+        .bind(response_id)
+        .bind(status)
+        .bind(now)
+        .fetch_one(&mut **tx)
+        .await?;
+
+        sqlx::query("UPDATE canned_responses SET usage_count = usage_count + 1, last_used_at = $2 WHERE id = $1")
+            .bind(response_id)
+            .bind(now)
+            .execute(&mut **tx)
+            .await?;
+
+        after.events.push(SupportEvent::message_added(&ticket.product, &message));
+        after.automations.push(AutomationFiring::message_added(ticket.id, MessageAuthor::System));
+        Ok(message)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn insert_automation_execution<'e>(
+        executor: impl PgExecutor<'e>,
+        rule: &AutomationRule,
+        firing: &AutomationFiring,
+        depth: i32,
+        outcome: AutomationOutcome,
+        actions_applied: i32,
+        error: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<AutomationExecution> {
+        let execution = sqlx::query_as::<_, AutomationExecution>(
+            r#"
+            INSERT INTO automation_executions (rule_id, ticket_id, trigger, depth, outcome, actions_applied, error, executed_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING *
+            "#,
+        )
+        .bind(rule.id)
+        .bind(firing.ticket_id)
+        .bind(firing.trigger)
+        .bind(depth)
+        .bind(outcome)
+        .bind(actions_applied)
+        .bind(error)
+        .bind(now)
+        .fetch_one(executor)
+        .await?;
+
+        Ok(execution)
+    }
+
+    /// Create a product-scoped intake API key; the secret is returned only here
+    pub async fn create_api_key(&self, product: &str, created_by: Uuid, input: &CreateApiKeyInput) -> Result<CreatedApiKey> {
+        self.ensure_writable()?;
+        if input.name.trim().is_empty() {
+            return Err(SupportError::Validation("API key name is required".to_string()));
+        }
+        if input.scopes.is_empty() {
+            return Err(SupportError::Validation("API key needs at least one scope".to_string()));
+        }
+        let mut scopes: Vec<ApiKeyScope> = Vec::with_capacity(input.scopes.len());
+        for scope in &input.scopes {
+            if !scopes.contains(scope) {
+                scopes.push(*scope);
+            }
+        }
+
+        let secret = new_api_key();
+        let api_key = sqlx::query_as::<_, IntakeApiKey>(
+            r#"
+            INSERT INTO intake_api_keys (product, name, key_prefix, key_hash, scopes, organization_id, created_by, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING *
+            "#,
+        )
+        .bind(product)
+        .bind(input.name.trim())
+        .bind(api_key_prefix(&secret))
+        .bind(hash_api_key(&secret))
+        .bind(&scopes)
+        .bind(input.organization_id)
+        .bind(created_by)
+        .bind(self.now())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(CreatedApiKey { api_key, secret })
+    }
+
+    /// A product's intake API keys, newest first, including revoked ones
+    pub async fn list_api_keys(&self, product: &str) -> Result<Vec<IntakeApiKey>> {
+        let keys = sqlx::query_as::<_, IntakeApiKey>(
+            "SELECT * FROM intake_api_keys WHERE product = $1 ORDER BY created_at DESC, id ASC"
+        )
+        .bind(product)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(keys)
+    }
+
+    /// Revoke an API key; requests with it fail from now on
+    pub async fn revoke_api_key(&self, key_id: Uuid) -> Result<IntakeApiKey> {
+        self.ensure_writable()?;
+        sqlx::query_as::<_, IntakeApiKey>(
+            "UPDATE intake_api_keys SET revoked_at = COALESCE(revoked_at, $2) WHERE id = $1 RETURNING *"
+        )
+        .bind(key_id)
+        .bind(self.now())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| SupportError::Validation(format!("API key not found: {}", key_id)))
+    }
+
+    /// Daily request counts for a key since `since`, oldest first
+    pub async fn api_key_usage(&self, key_id: Uuid, since: NaiveDate) -> Result<Vec<ApiKeyUsage>> {
+        let usage = sqlx::query_as::<_, ApiKeyUsage>(
+            "SELECT * FROM intake_api_key_usage WHERE key_id = $1 AND day >= $2 ORDER BY day ASC, scope ASC"
+        )
+        .bind(key_id)
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(usage)
+    }
+
+    /// Authenticate a request made with an intake API key and meter it
+    ///
+    /// Unknown and revoked keys, and keys without `scope`, are `Unauthorized`.
+    /// Callers act on the returned key's product only.
+    pub async fn authenticate_api_key(&self, key: &str, scope: ApiKeyScope) -> Result<IntakeApiKey> {
+        // Usage can't be counted while read-only; keys still authenticate
+        if self.is_read_only() {
+            return sqlx::query_as::<_, IntakeApiKey>(
+                "SELECT * FROM intake_api_keys WHERE key_hash = $1 AND revoked_at IS NULL AND $2 = ANY(scopes)",
+            )
+            .bind(hash_api_key(key))
+            .bind(scope)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or(SupportError::Unauthorized);
+        }
+
+        let now = self.now();
+        let mut tx = self.pool.begin().await?;
+
+        let api_key = sqlx::query_as::<_, IntakeApiKey>(
+            r#"
+            UPDATE intake_api_keys
+            SET last_used_at = $3, usage_count = usage_count + 1
+            WHERE key_hash = $1 AND revoked_at IS NULL AND $2 = ANY(scopes)
+            RETURNING *
+            "#,
+        )
+        .bind(hash_api_key(key))
+        .bind(scope)
+        .bind(now)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(SupportError::Unauthorized)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO intake_api_key_usage (key_id, day, scope, request_count)
+            VALUES ($1, $2, $3, 1)
+            ON CONFLICT (key_id, day, scope) DO UPDATE SET request_count = intake_api_key_usage.request_count + 1
+            "#,
+        )
+        .bind(api_key.id)
+        .bind(now.date_naive())
+        .bind(scope)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(api_key)
+    }
+
+    /// Create a ticket on behalf of a partner holding a `CREATE_TICKETS` key
+    ///
+    /// The ticket goes to the key's product and organization and through the
+    /// same screening and routing as any other intake. Test tickets and a
+    /// caller-supplied `organization_id` are rejected with `Validation`.
+    pub async fn create_ticket_with_api_key(
+        &self,
+        key: &str,
+        input: &CreateTicketInput,
+        submitter: Option<&SubmitterInfo>,
+    ) -> Result<SupportTicket> {
+        let api_key = self.authenticate_api_key(key, ApiKeyScope::CreateTickets).await?;
+        if input.test_mode {
+            return Err(SupportError::Validation("Test tickets can't be created with an intake API key".to_string()));
+        }
+        if input.organization_id.is_some() {
+            return Err(SupportError::Validation(
+                "The organization of a ticket created with an intake API key comes from the key".to_string(),
+            ));
+        }
+        let input = CreateTicketInput { organization_id: api_key.organization_id, ..input.clone() };
+        self.create_ticket_with_submitter(&api_key.product, &input, submitter).await
+    }
+
+    /// Keep an event that exhausted its retries for later retry or discard
+    pub async fn record_dead_letter(&self, letter: &NewDeadLetter) -> Result<DeadLetter> {
+        self.ensure_writable()?;
+        Self::insert_dead_letter(&self.pool, letter, self.now()).await
+    }
+
+    async fn insert_dead_letter(pool: &PgPool, letter: &NewDeadLetter, now: DateTime<Utc>) -> Result<DeadLetter> {
+        let dead_letter = sqlx::query_as::<_, DeadLetter>(
+            r#"
+            INSERT INTO dead_letters (product, source, event_type, payload, ticket_id, attempts, last_error, created_at, last_failed_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $8)
+            RETURNING *
+            "#,
+        )
+        .bind(&letter.product)
+        .bind(&letter.source)
+        .bind(&letter.event_type)
+        .bind(&letter.payload)
+        .bind(letter.ticket_id)
+        .bind(letter.attempts)
+        .bind(&letter.last_error)
+        .bind(now)
+        .fetch_one(pool)
+        .await?;
+
+        tracing::warn!(
+            "Dead-lettered {} event from {} after {} attempts: {}",
+            letter.event_type, letter.source, letter.attempts, letter.last_error
+        );
+        Ok(dead_letter)
+    }
+
+    /// Publish `events` in order in the background, dead-lettering any the
+    /// publisher gives up on; a no-op without a publisher
+    fn publish_events(&self, events: Vec<SupportEvent>) {
+        let Some(publisher) = self.event_publisher.clone() else {
+            return;
+        };
+        if events.is_empty() {
+            return;
+        }
+
+        let pool = self.pool.clone();
+        let clock = self.clock.clone();
+        tokio::spawn(async move {
+            for event in events {
+                let Err(e) = publisher.publish(&event).await else {
+                    continue;
+                };
+                let attempts = i32::try_from(publisher.max_attempts()).unwrap_or(i32::MAX);
+                let recorded = match event.dead_letter(attempts, &e) {
+                    Ok(letter) => Self::insert_dead_letter(&pool, &letter, clock.now()).await.map(|_| ()),
+                    Err(encode_error) => Err(encode_error),
+                };
+                if let Err(record_error) = recorded {
+                    tracing::error!("Lost {} event {}: {}; {}", event.event_type.as_str(), event.id, e, record_error);
+                }
+            }
+        });
+    }
+
+    /// A product's dead letters, oldest first, optionally only those in `status`
+    pub async fn list_dead_letters(
+        &self,
+        product: &str,
+        status: Option<DeadLetterStatus>,
+        limit: i64,
+    ) -> Result<Vec<DeadLetter>> {
+        let letters = sqlx::query_as::<_, DeadLetter>(
+            r#"
+            SELECT * FROM dead_letters
+            WHERE product = $1 AND ($2::dead_letter_status IS NULL OR status = $2)
+            ORDER BY created_at ASC, id ASC
+            LIMIT $3
+            "#,
+        )
+        .bind(product)
+        .bind(status)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(letters)
+    }
+
+    /// Redeliver a pending dead letter through its source's handler
+    ///
+    /// The letter is claimed as `RETRYING` before redelivery, so a concurrent
+    /// retry fails instead of delivering it twice. Success marks it
+    /// `RETRIED`; a failed redelivery puts it back to `PENDING` with the
+    /// attempt counted and the new error recorded.
+    pub async fn retry_dead_letter(&self, letter_id: Uuid, actor_id: Option<Uuid>) -> Result<DeadLetter> {
+        self.ensure_writable()?;
+        let letter = self.find_pending_dead_letter(letter_id).await?;
+        let handler = self.dead_letter_handlers.handler(&letter.source)?;
+        let letter = sqlx::query_as::<_, DeadLetter>(
+            "UPDATE dead_letters SET status = 'RETRYING' WHERE id = $1 AND status IN ('PENDING') RETURNING *"
+        )
+        .bind(letter_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| SupportError::Validation(format!("No pending dead letter: {}", letter_id)))?;
+
+        let outcome = handler.redeliver(&letter).await;
+        let now = self.now();
+        let letter = match outcome {
+            Ok(()) => {
+                sqlx::query_as::<_, DeadLetter>(
+                    r#"
+                    UPDATE dead_letters
+                    SET status = 'RETRIED', attempts = attempts + 1, resolved_at = $2, resolved_by = $3
+                    WHERE id = $1
+                    RETURNING *
+                    "#,
+                )
+                .bind(letter_id)
+                .bind(now)
+                .bind(actor_id)
+                .fetch_one(&self.pool)
+                .await?
+            }
+            Err(e) => {
+                tracing::warn!("Retry of dead letter {} failed: {}", letter_id, e);
+                sqlx::query_as::<_, DeadLetter>(
+                    r#"
+                    UPDATE dead_letters
+                    SET status = 'PENDING', attempts = attempts + 1, last_error = $2, last_failed_at = $3
+                    WHERE id = $1
+                    RETURNING *
+                    "#,
+                )
+                .bind(letter_id)
+                .bind(e.to_string())
+                .bind(now)
+                .fetch_one(&self.pool)
+                .await?
+            }
+        };
+
+        Ok(letter)
+    }
+
+    /// Give up on a pending dead letter
+    pub async fn discard_dead_letter(&self, letter_id: Uuid, actor_id: Option<Uuid>) -> Result<DeadLetter> {
+        self.ensure_writable()?;
+        sqlx::query_as::<_, DeadLetter>(
+            r#"
+            UPDATE dead_letters SET status = 'DISCARDED', resolved_at = $2, resolved_by = $3
+            WHERE id = $1 AND status = 'PENDING'
+            RETURNING *
+            "#,
+        )
+        .bind(letter_id)
+        .bind(self.now())
+        .bind(actor_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| SupportError::Validation(format!("No pending dead letter: {}", letter_id)))
+    }
+
+    async fn find_pending_dead_letter(&self, letter_id: Uuid) -> Result<DeadLetter> {
+        sqlx::query_as::<_, DeadLetter>("SELECT * FROM dead_letters WHERE id = $1 AND status = 'PENDING'")
+            .bind(letter_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| SupportError::Validation(format!("No pending dead letter: {}", letter_id)))
+    }
+
+    /// Schedule a maintenance window; SLA clocks pause for its duration
+    pub async fn create_maintenance_window(
+        &self,
+        product: &str,
+        created_by: Uuid,
+        input: &CreateMaintenanceWindowInput,
+    ) -> Result<MaintenanceWindow> {
+        self.ensure_writable()?;
+        if input.ends_at <= input.starts_at {
+            return Err(SupportError::Validation("Maintenance window must end after it starts".to_string()));
+        }
+        if input.reason.trim().is_empty() {
+            return Err(SupportError::Validation("Maintenance window reason is required".to_string()));
+        }
+
+        let window = sqlx::query_as::<_, MaintenanceWindow>(
+            r#"
+            INSERT INTO maintenance_windows (product, starts_at, ends_at, reason, created_by, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING *
+            "#,
+        )
+        .bind(product)
+        .bind(input.starts_at)
+        .bind(input.ends_at)
+        .bind(input.reason.trim())
+        .bind(created_by)
+        .bind(self.now())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(window)
+    }
+
+    /// A product's maintenance windows ending after `since`, earliest first
+    pub async fn list_maintenance_windows(&self, product: &str, since: DateTime<Utc>) -> Result<Vec<MaintenanceWindow>> {
+        let windows = sqlx::query_as::<_, MaintenanceWindow>(
+            "SELECT * FROM maintenance_windows WHERE product = $1 AND ends_at > $2 ORDER BY starts_at ASC, id ASC"
+        )
+        .bind(product)
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(windows)
+    }
+
+    /// Maintenance windows in effect for the product right now
+    pub async fn active_maintenance_windows(&self, product: &str) -> Result<Vec<MaintenanceWindow>> {
+        Self::maintenance_windows_at(&self.pool, product, self.now()).await
+    }
+
+    /// Delete a maintenance window; SLA due dates are recomputed on the tickets' next update
+    pub async fn delete_maintenance_window(&self, window_id: Uuid) -> Result<bool> {
+        self.ensure_writable()?;
+        let result = sqlx::query("DELETE FROM maintenance_windows WHERE id = $1")
+            .bind(window_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn maintenance_windows_at<'e>(
+        executor: impl PgExecutor<'e>,
+        product: &str,
+        at: DateTime<Utc>,
+    ) -> Result<Vec<MaintenanceWindow>> {
+        let windows = sqlx::query_as::<_, MaintenanceWindow>(
+            r#"
+            SELECT * FROM maintenance_windows
+            WHERE product = $1 AND starts_at <= $2 AND ends_at > $2
+            ORDER BY starts_at ASC, id ASC
+            "#,
+        )
+        .bind(product)
+        .bind(at)
+        .fetch_all(executor)
+        .await?;
+
+        Ok(windows)
+    }
+
+    /// Close tickets that have been RESOLVED since before the given time
+    ///
+    /// Returns the number of tickets closed.
+    pub async fn close_resolved_tickets(&self, resolved_before: DateTime<Utc>) -> Result<u64> {
+        self.ensure_writable()?;
+        let result = sqlx::query(
+            r#"
+            WITH closed AS (
+                UPDATE support_tickets SET status = 'CLOSED', closed_at = $2, updated_at = $2
+                WHERE status = 'RESOLVED'
+                  AND resolved_at < $1
+                  AND deleted_at IS NULL
+                RETURNING id
+            )
+            INSERT INTO ticket_audit_entries (ticket_id, field, old_value, new_value, created_at)
+            SELECT id, 'status', 'RESOLVED', 'CLOSED', $2 FROM closed
+            "#,
+        )
+        .bind(resolved_before)
+        .bind(self.now())
+        .execute(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Flag tickets that have breached their SLA targets
+    ///
+    /// Each ticket's targets come from its product/priority `sla_targets` row,
+    /// else the repository's SLA policy. Targets are extended by the grace window of each ticket's customer plan.
+    /// Breaches are sticky: flagged tickets are never un-flagged. Newly flagged
+    /// tickets are stamped with `sla_breached_at` for breach-rate alerts and
+    /// published as `ticket.sla_breached` events.
+    /// Returns the number of newly breached tickets.
+    pub async fn evaluate_sla_breaches(&self) -> Result<u64> {
+        Ok(self.detect_sla_breaches().await?.len() as u64)
+    }
+
+    /// Flag breached tickets as `evaluate_sla_breaches` does, returning them
+    ///
+    /// A ticket is flagged, and its event published, by one run only, even
+    /// when several instances run at once.
+    pub async fn detect_sla_breaches(&self) -> Result<Vec<SupportTicket>> {
+        self.ensure_writable()?;
+        let now = self.now();
+        let mut conn = self.pool.acquire().await?;
+        let breached = self.flag_sla_breaches(&mut conn, None, now).await?;
+
+        if !breached.is_empty() {
+            tracing::info!("Flagged {} tickets as SLA breached", breached.len());
+            self.publish_events(
+                breached.iter().map(|ticket| SupportEvent::ticket(SupportEventType::SlaBreached, ticket, now)).collect(),
+            );
+        }
+
+        Ok(breached)
+    }
+
+    /// Flag breached tickets as `evaluate_sla_breaches` does, only `ticket_id` when given
+    ///
+    /// Returns the newly flagged tickets; publishing their events is up to the caller.
+    async fn flag_sla_breaches(
+        &self,
+        conn: &mut PgConnection,
+        ticket_id: Option<Uuid>,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<SupportTicket>> {
+        let policy = &self.sla_policy;
+
+        let (plans, grace_seconds): (Vec<String>, Vec<i64>) = policy
+            .plan_grace_windows
+            .iter()
+            .map(|(plan, grace)| (plan.clone(), grace.num_seconds()))
+            .unzip();
+
+        let query = format!(
+            r#"
+            WITH graced AS (
+                SELECT
+                    st.id,
+                    COALESCE(g.grace_seconds, $5) as grace_seconds,
+                    {SLA_TARGET_COLUMNS}
+                FROM support_tickets st
+                {SLA_GRACE_JOIN}
+                {SLA_TARGET_JOIN}
+                WHERE st.deleted_at IS NULL
+                  AND NOT st.test_mode
+                  AND st.sla_breach = FALSE
+                  AND ($8::UUID IS NULL OR st.id = $8)
+            )
+            UPDATE support_tickets st
+            SET sla_breach = TRUE, sla_breached_at = $6, updated_at = $6
+            FROM graced
+            WHERE st.id = graced.id
+              AND st.sla_breach = FALSE
+              AND {SLA_BREACHED_EXPR}
+            RETURNING st.*
+            "#,
+        );
+
+        let breached = sqlx::query_as::<_, SupportTicket>(&query)
+        .bind(policy.first_response_target.map(|d| d.num_seconds()))
+        .bind(policy.resolution_target.map(|d| d.num_seconds()))
+        .bind(plans)
+        .bind(grace_seconds)
+        .bind(policy.default_grace.num_seconds())
+        .bind(now)
+        .bind(policy.pause_while_waiting_on_customer)
+        .bind(ticket_id)
+        .fetch_all(conn)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(breached)
+    }
+
+    /// Every registered product, by key
+    pub async fn list_product_configs(&self) -> Result<Vec<ProductConfig>> {
+        let configs = sqlx::query_as::<_, ProductConfig>("SELECT * FROM product_configs ORDER BY product ASC")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(configs)
+    }
+
+    /// A registered product's configuration; `UnknownProduct` when it is not registered
+    pub async fn get_product_config(&self, product: &str) -> Result<ProductConfig> {
+        sqlx::query_as::<_, ProductConfig>("SELECT * FROM product_configs WHERE product = $1")
+            .bind(product)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| SupportError::UnknownProduct(product.to_string()))
+    }
+
+    /// Register a product, or change a registered product's configuration
+    ///
+    /// Omitted fields keep their value; a new product takes the defaults
+    /// described on [`ProductConfigInput`], with business hours in the
+    /// product locale's timezone.
+    pub async fn upsert_product_config(&self, product: &str, input: &ProductConfigInput) -> Result<ProductConfig> {
+        self.ensure_writable()?;
+        let product = product.trim();
+        if product.is_empty() || product.len() > MAX_PRODUCT_KEY_LENGTH {
+            return Err(SupportError::Validation(format!(
+                "Product key must be between 1 and {} characters",
+                MAX_PRODUCT_KEY_LENGTH
+            )));
+        }
+        input.validate()?;
+        if let Some(timezone) = &input.business_timezone {
+            self.validate_timezone(timezone).await?;
+        }
+        let now = self.now();
+
+        let mut tx = self.pool.begin().await?;
+        let existing = sqlx::query_as::<_, ProductConfig>("SELECT * FROM product_configs WHERE product = $1 FOR UPDATE")
+            .bind(product)
+            .fetch_optional(&mut *tx)
+            .await?;
+        let opens = input.business_opens_at_minute.or(existing.as_ref().map(|c| c.business_opens_at_minute)).unwrap_or(0);
+        let closes = input
+            .business_closes_at_minute
+            .or(existing.as_ref().map(|c| c.business_closes_at_minute))
+            .unwrap_or(24 * 60);
+        if opens >= closes {
+            return Err(SupportError::Validation("Business hours must open before they close".to_string()));
+        }
+
+        let config = sqlx::query_as::<_, ProductConfig>(
+            r#"
+            INSERT INTO product_configs (
+                product, display_name, default_priority, sla_policy, business_timezone, business_days,
+                business_opens_at_minute, business_closes_at_minute, enabled_channels, created_at, updated_at,
+                business_holidays, categories
+            )
+            VALUES (
+                $1, $2, COALESCE($3, 'MEDIUM'), $4, COALESCE($5, $6), COALESCE($7, '{1,2,3,4,5,6,7}'),
+                $8, $9, COALESCE($10, '{EMAIL,CHAT,WEB,API}'), $11, $11, COALESCE($12, '{}'), COALESCE($13, '{}')
+            )
+            ON CONFLICT (product) DO UPDATE SET
+                display_name = COALESCE($2, product_configs.display_name),
+                default_priority = COALESCE($3, product_configs.default_priority),
+                sla_policy = COALESCE($4, product_configs.sla_policy),
+                business_timezone = COALESCE($5, product_configs.business_timezone),
+                business_days = COALESCE($7, product_configs.business_days),
+                business_opens_at_minute = $8,
+                business_closes_at_minute = $9,
+                enabled_channels = COALESCE($10, product_configs.enabled_channels),
+                business_holidays = COALESCE($12, product_configs.business_holidays),
+                categories = COALESCE($13, product_configs.categories),
+                updated_at = $11
+            RETURNING *
+            "#,
+        )
+        .bind(product)
+        .bind(&input.display_name)
+        .bind(input.default_priority)
+        .bind(&input.sla_policy)
+        .bind(&input.business_timezone)
+        .bind(self.locales.locale_for(product).timezone)
+        .bind(&input.business_days)
+        .bind(opens)
+        .bind(closes)
+        .bind(&input.enabled_channels)
+        .bind(now)
+        .bind(&input.business_holidays)
+        .bind(input.categories.as_ref().map(|categories| {
+            categories.iter().map(|c| c.trim().to_string()).collect::<Vec<_>>()
+        }))
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(config)
+    }
+
+    /// Unregister a product; its tickets stay, but no new ones can be created
+    ///
+    /// Returns false when the product was not registered.
+    pub async fn delete_product_config(&self, product: &str) -> Result<bool> {
+        self.ensure_writable()?;
+        let result = sqlx::query("DELETE FROM product_configs WHERE product = $1")
+            .bind(product)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// A product's resolution codes in code order, optionally with retired ones
+    pub async fn list_resolution_codes(&self, product: &str, include_inactive: bool) -> Result<Vec<ResolutionCode>> {
+        let codes = sqlx::query_as::<_, ResolutionCode>(
+            "SELECT * FROM resolution_codes WHERE product = $1 AND (active OR $2) ORDER BY code ASC"
+        )
+        .bind(product)
+        .bind(include_inactive)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(codes)
+    }
+
+    /// Add a resolution code to a registered product's taxonomy, or change one
+    ///
+    /// Codes are stored uppercase; see [`normalize_resolution_code`]. Codes are
+    /// retired with `active: false` rather than deleted, since resolved
+    /// tickets keep them.
+    pub async fn upsert_resolution_code(
+        &self,
+        product: &str,
+        code: &str,
+        input: &ResolutionCodeInput,
+    ) -> Result<ResolutionCode> {
+        self.ensure_writable()?;
+        let code = normalize_resolution_code(code)?;
+        let label = input.label.as_deref().map(str::trim);
+        if label.is_some_and(str::is_empty) {
+            return Err(SupportError::Validation("Resolution code label cannot be empty".to_string()));
+        }
+        let now = self.now();
+
+        let mut tx = self.pool.begin().await?;
+        let registered: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM product_configs WHERE product = $1)")
+            .bind(product)
+            .fetch_one(&mut *tx)
+            .await?;
+        if !registered {
+            return Err(SupportError::UnknownProduct(product.to_string()));
+        }
+        if let Some(article_id) = input.article_id {
+            let exists: bool = sqlx::query_scalar(
+                "SELECT EXISTS(SELECT 1 FROM knowledge_articles WHERE id = $1 AND product = $2)"
+            )
+            .bind(article_id)
+            .bind(product)
+            .fetch_one(&mut *tx)
+            .await?;
+            if !exists {
+                return Err(SupportError::Validation(format!("Knowledge article not found: {}", article_id)));
+            }
+        }
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM resolution_codes WHERE product = $1 AND code = $2 FOR UPDATE)"
+        )
+        .bind(product)
+        .bind(&code)
+        .fetch_one(&mut *tx)
+        .await?;
+        if !exists && label.is_none() {
+            return Err(SupportError::Validation(format!("Resolution code {} needs a label", code)));
+        }
+
+        let resolution_code = sqlx::query_as::<_, ResolutionCode>(
+            r#"
+            INSERT INTO resolution_codes (product, code, label, description, article_id, active, created_at, updated_at)
+            -- Only existing codes may omit the label, and they keep theirs
+            VALUES ($1, $2, COALESCE($3, ''), $4, $5, COALESCE($6, TRUE), $7, $7)
+            ON CONFLICT (product, code) DO UPDATE SET
+                label = COALESCE($3, resolution_codes.label),
+                description = COALESCE($4, resolution_codes.description),
+                article_id = COALESCE($5, resolution_codes.article_id),
+                active = COALESCE($6, resolution_codes.active),
+                updated_at = $7
+            RETURNING *
+            "#,
+        )
+        .bind(product)
+        .bind(&code)
+        .bind(label)
+        .bind(&input.description)
+        .bind(input.article_id)
+        .bind(input.active)
+        .bind(now)
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(resolution_code)
+    }
+
+    /// Tickets resolved in the period, broken down by resolution code
+    ///
+    /// Tickets resolved without a code are left out; the percentages are of
+    /// the coded resolutions.
+    pub async fn get_resolutions_by_code(
+        &self,
+        product: &str,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> Result<Vec<CrmCoreResolutionCodeMetrics>> {
+        let mut tx = self.begin_timed(OperationClass::Analytics).await?;
+        let metrics = sqlx::query_as::<_, CrmCoreResolutionCodeMetrics>(
+            r#"
+            SELECT
+                t.resolution_code,
+                MAX(rc.label) as label,
+                COUNT(*)::BIGINT as resolved_tickets,
+                (COUNT(*) * 100.0 / SUM(COUNT(*)) OVER ())::FLOAT8 as resolution_percentage,
+                AVG(EXTRACT(EPOCH FROM (t.resolved_at - t.created_at)) / 3600.0)::FLOAT8 as avg_resolution_hours,
+                COUNT(*) FILTER (WHERE t.status NOT IN ('RESOLVED', 'CLOSED'))::BIGINT as reopened_tickets
+            FROM support_tickets t
+            LEFT JOIN resolution_codes rc ON rc.product = t.product AND rc.code = t.resolution_code
+            WHERE t.product = $1
+              AND t.resolution_code IS NOT NULL
+              AND t.resolved_at BETWEEN $2 AND $3
+              AND t.deleted_at IS NULL
+              AND NOT t.test_mode
+            GROUP BY t.resolution_code
+            ORDER BY resolved_tickets DESC, t.resolution_code ASC
+            "#,
+        )
+        .bind(product)
+        .bind(period_start)
+        .bind(period_end)
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(metrics)
+    }
+
+    /// (category, resolution code) pairs resolved at least `min_tickets` times
+    /// in the period whose code has no help article, most frequent first
+    ///
+    /// `min_tickets` defaults to [`DEFAULT_KB_CANDIDATE_MIN_TICKETS`].
+    pub async fn kb_article_candidates(
+        &self,
+        product: &str,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+        min_tickets: Option<i64>,
+    ) -> Result<Vec<KbArticleCandidate>> {
+        let min_tickets = min_tickets.unwrap_or(DEFAULT_KB_CANDIDATE_MIN_TICKETS).max(1);
+        let mut tx = self.begin_timed(OperationClass::Analytics).await?;
+        let candidates = sqlx::query_as::<_, KbArticleCandidate>(
+            r#"
+            WITH resolved AS (
+                SELECT category, resolution_code, ticket_ref, resolved_at
+                FROM support_tickets
+                WHERE product = $1
+                  AND category IS NOT NULL
+                  AND resolution_code IS NOT NULL
+                  AND resolved_at BETWEEN $2 AND $3
+                  AND deleted_at IS NULL
+                  AND NOT test_mode
+            ),
+            pairs AS (
+                SELECT
+                    category,
+                    resolution_code,
+                    COUNT(*)::BIGINT as ticket_count,
+                    (COUNT(*) * 100.0 / SUM(COUNT(*)) OVER (PARTITION BY category))::FLOAT8 as category_share,
+                    (ARRAY_AGG(ticket_ref ORDER BY resolved_at DESC))[1:$5] as sample_ticket_refs,
+                    MAX(resolved_at) as last_resolved_at
+                FROM resolved
+                GROUP BY category, resolution_code
+            )
+            SELECT p.*, rc.label
+            FROM pairs p
+            LEFT JOIN resolution_codes rc ON rc.product = $1 AND rc.code = p.resolution_code
+            WHERE p.ticket_count >= $4
+              AND rc.article_id IS NULL
+            ORDER BY p.ticket_count DESC, p.last_resolved_at DESC
+            "#,
+        )
+        .bind(product)
+        .bind(period_start)
+        .bind(period_end)
+        .bind(min_tickets)
+        .bind(KB_CANDIDATE_SAMPLE_TICKETS)
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(candidates)
+    }
+
+    /// SLA targets configured for the product, most urgent priority first
+    pub async fn list_sla_targets(&self, product: &str) -> Result<Vec<SlaTarget>> {
+        let targets = sqlx::query_as::<_, SlaTarget>(
+            r#"
+            SELECT * FROM sla_targets
+            WHERE product = $1
+            ORDER BY
+                CASE priority::TEXT
+                    WHEN 'URGENT' THEN 1
+                    WHEN 'HIGH' THEN 2
+                    WHEN 'MEDIUM' THEN 3
+                    WHEN 'LOW' THEN 4
+                END
+            "#,
+        )
+        .bind(product)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(targets)
+    }
+
+    /// A ticket's SLA clocks, for rendering an SLA widget
+    ///
+    /// Elapsed times, breaches and due dates are computed as breach
+    /// evaluation computes them, honoring business hours, maintenance
+    /// windows, waiting on the customer and the plan's grace.
+    pub async fn sla_clock(&self, ticket_id: Uuid) -> Result<SlaClock> {
+        let ticket = self.find_by_id(ticket_id).await?;
+        let policy = &self.sla_policy;
+        let now = self.now();
+        let first_response_end = ticket.first_response_at.unwrap_or(now);
+        let resolution_end = ticket.resolved_at.unwrap_or(now);
+
+        let target = sqlx::query_as::<_, SlaTarget>("SELECT * FROM sla_targets WHERE product = $1 AND priority = $2")
+            .bind(&ticket.product)
+            .bind(ticket.priority)
+            .fetch_optional(&self.pool)
+            .await?;
+        let pick = |minutes: Option<i32>, fallback: Option<Duration>| match minutes {
+            Some(minutes) => (SlaTargetSource::ProductTarget, Some(Duration::minutes(i64::from(minutes)))),
+            None => (SlaTargetSource::DefaultPolicy, fallback),
+        };
+        let (first_response_source, first_response_target) =
+            pick(target.as_ref().and_then(|t| t.first_response_minutes), policy.first_response_target);
+        let (resolution_source, resolution_target) =
+            pick(target.as_ref().and_then(|t| t.resolution_minutes), policy.resolution_target);
+
+        let (first_response_business, first_response_maintenance, resolution_maintenance, open_now): (i64, i64, i64, bool) =
+            sqlx::query_as(
+                r#"
+                SELECT
+                    support_product_business_seconds($1, $2, $3),
+                    support_product_business_maintenance_seconds($1, $2, $3),
+                    support_maintenance_seconds($1, $2, $4),
+                    support_product_business_seconds($1, $5, $5 + INTERVAL '1 minute') > 0
+                "#,
+            )
+            .bind(&ticket.product)
+            .bind(ticket.created_at)
+            .bind(first_response_end)
+            .bind(resolution_end)
+            .bind(now)
+            .fetch_one(&self.pool)
+            .await?;
+
+        let pause_waiting = policy.pause_while_waiting_on_customer;
+        let mut paused_intervals = Vec::new();
+        if pause_waiting {
+            let status_changes = sqlx::query_as::<_, TicketAuditEntry>(
+                "SELECT * FROM ticket_audit_entries WHERE ticket_id = $1 AND field = 'status' ORDER BY created_at ASC, id ASC"
+            )
+            .bind(ticket.id)
+            .fetch_all(&self.pool)
+            .await?;
+            let waiting = TicketStatus::WaitingOnCustomer.as_str();
+            for change in status_changes {
+                if change.new_value.as_deref() == Some(waiting) && change.old_value.as_deref() != Some(waiting) {
+                    paused_intervals.push(SlaPause { reason: SlaPauseReason::WaitingOnCustomer, started_at: change.created_at, ended_at: None });
+                } else if change.old_value.as_deref() == Some(waiting) && change.new_value.as_deref() != Some(waiting) {
+                    if let Some(pause) = paused_intervals.last_mut().filter(|p| p.ended_at.is_none()) {
+                        pause.ended_at = Some(change.created_at);
+                    }
+                }
+            }
+        }
+
+        let windows = sqlx::query_as::<_, MaintenanceWindow>(
+            "SELECT * FROM maintenance_windows WHERE product = $1 AND starts_at < $3 AND ends_at > $2 ORDER BY starts_at ASC, id ASC"
+        )
+        .bind(&ticket.product)
+        .bind(ticket.created_at)
+        .bind(resolution_end)
+        .fetch_all(&self.pool)
+        .await?;
+        let in_maintenance = windows.iter().any(|w| w.starts_at <= now && now < w.ends_at);
+        paused_intervals.extend(windows.into_iter().map(|window| SlaPause {
+            reason: SlaPauseReason::Maintenance,
+            started_at: window.starts_at.max(ticket.created_at),
+            ended_at: (window.ends_at <= now).then_some(window.ends_at),
+        }));
+        paused_intervals.sort_by_key(|pause| pause.started_at);
+
+        let grace = policy.grace_for(ticket.customer_plan.as_deref());
+        let first_response_elapsed =
+            Duration::seconds(first_response_business - first_response_maintenance).max(Duration::zero());
+        let waited = if pause_waiting { policy.customer_wait(&ticket, resolution_end) } else { Duration::zero() };
+        let resolution_elapsed =
+            (resolution_end - ticket.created_at - Duration::seconds(resolution_maintenance) - waited).max(Duration::zero());
+        let waiting_now = pause_waiting && ticket.waiting_since.is_some();
+
+        Ok(SlaClock {
+            ticket_id: ticket.id,
+            policy_id: target
+                .filter(|t| t.first_response_minutes.is_some() || t.resolution_minutes.is_some())
+                .map(|t| format!("{}/{}", t.product, t.priority.as_str())),
+            first_response: SlaClockTarget::new(
+                first_response_source, first_response_target, grace, first_response_elapsed,
+                ticket.first_response_due_at, ticket.first_response_at, open_now && !in_maintenance,
+            ),
+            resolution: SlaClockTarget::new(
+                resolution_source, resolution_target, grace, resolution_elapsed,
+                ticket.sla_due_at, ticket.resolved_at, !waiting_now && !in_maintenance,
+            ),
+            paused_intervals,
+            computed_at: now,
+        })
+    }
+
+    /// Set the SLA targets for one product and priority
+    ///
+    /// Deadlines of the open tickets it covers are recomputed right away.
+    /// Unset targets fall back to the repository's SLA policy.
+    pub async fn set_sla_target(&self, product: &str, input: &SetSlaTargetInput) -> Result<SlaTarget> {
+        self.ensure_writable()?;
+        if input.first_response_minutes.is_some_and(|m| m <= 0) || input.resolution_minutes.is_some_and(|m| m <= 0) {
+            return Err(SupportError::Validation("SLA targets must be positive".to_string()));
+        }
+
+        let now = self.now();
+        let mut tx = self.pool.begin().await?;
+        let target = sqlx::query_as::<_, SlaTarget>(
+            r#"
+            INSERT INTO sla_targets (product, priority, first_response_minutes, resolution_minutes, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $5)
+            ON CONFLICT (product, priority) DO UPDATE SET
+                first_response_minutes = EXCLUDED.first_response_minutes,
+                resolution_minutes = EXCLUDED.resolution_minutes,
+                updated_at = EXCLUDED.updated_at
+            RETURNING *
+            "#,
+        )
+        .bind(product)
+        .bind(input.priority)
+        .bind(input.first_response_minutes)
+        .bind(input.resolution_minutes)
+        .bind(now)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        self.apply_sla_to_open_tickets(&mut tx, product, input.priority, now).await?;
+        tx.commit().await?;
+
+        Ok(target)
+    }
+
+    /// Remove a product/priority's SLA targets, reverting it to the repository's SLA policy
+    pub async fn delete_sla_target(&self, product: &str, priority: TicketPriority) -> Result<bool> {
+        self.ensure_writable()?;
+        let now = self.now();
+        let mut tx = self.pool.begin().await?;
+        let result = sqlx::query("DELETE FROM sla_targets WHERE product = $1 AND priority = $2")
+            .bind(product)
+            .bind(priority)
+            .execute(&mut *tx)
+            .await?;
+
+        if result.rows_affected() > 0 {
+            self.apply_sla_to_open_tickets(&mut tx, product, priority, now).await?;
+        }
+        tx.commit().await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Contractual SLA compliance of an account for the calendar month containing `month`
+    ///
+    /// Covers the contract's customers' tickets created in that month in the
+    /// contract's timezone. Each ticket is measured in business hours against
+    /// the targets of the severity its priority maps to, plus the contract's
+    /// grace; the report lists every breaching ticket.
+    pub async fn get_contractual_sla_report(
+        &self,
+        product: &str,
+        account: &str,
+        month: NaiveDate,
+    ) -> Result<ContractualSlaReport> {
+        let contract = self
+            .sla_contracts
+            .contract(account)
+            .ok_or_else(|| SupportError::Validation(format!("No SLA contract for account: {}", account)))?;
+        let hours = &contract.business_hours;
+        if hours.opens_at_minute >= hours.closes_at_minute || hours.closes_at_minute > 24 * 60 {
+            return Err(SupportError::Validation("Business hours must open before they close, within one day".to_string()));
+        }
+        self.validate_timezone(&hours.timezone).await?;
+
+        let month = month.with_day(1).unwrap_or(month);
+        let next_month = month
+            .checked_add_months(Months::new(1))
+            .ok_or_else(|| SupportError::InvalidInput(format!("Month out of range: {}", month)))?;
+        let generated_at = self.now();
+
+        let mut tx = self.begin_timed(OperationClass::Analytics).await?;
+
+        let (period_start, period_end): (DateTime<Utc>, DateTime<Utc>) =
+            sqlx::query_as("SELECT $1::TIMESTAMP AT TIME ZONE $3, $2::TIMESTAMP AT TIME ZONE $3")
+                .bind(month.and_time(NaiveTime::MIN))
+                .bind(next_month.and_time(NaiveTime::MIN))
+                .bind(&hours.timezone)
+                .fetch_one(&mut *tx)
+                .await?;
+
+        let tickets = sqlx::query_as::<_, ContractTicketRow>(
+            r#"
+            SELECT
+                id, ticket_ref, priority, created_at, first_response_at, resolved_at,
+                support_business_seconds(created_at, COALESCE(first_response_at, $5), $6, $7, $8, $9)
+                    AS first_response_business_seconds,
+                support_business_seconds(created_at, COALESCE(resolved_at, $5), $6, $7, $8, $9)
+                    AS resolution_business_seconds
+            FROM support_tickets
+            WHERE product = $1
+              AND customer_id = ANY($2)
+              AND deleted_at IS NULL
+              AND NOT test_mode
+              AND created_at >= $3 AND created_at < $4
+            ORDER BY created_at ASC, id ASC
+            "#,
+        )
+        .bind(product)
+        .bind(&contract.customer_ids)
+        .bind(period_start)
+        .bind(period_end)
+        .bind(generated_at)
+        .bind(&hours.timezone)
+        .bind(hours.iso_days())
+        .bind(hours.opens_at_minute as i32)
+        .bind(hours.closes_at_minute as i32)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        let mut severities: Vec<ContractSeverityCompliance> = contract
+            .severities
+            .iter()
+            .map(|severity| ContractSeverityCompliance {
+                severity: severity.name.clone(),
+                total_tickets: 0,
+                met: 0,
+                breached: 0,
+                pending: 0,
+                compliance_rate: None,
+            })
+            .collect();
+        let mut unmapped_tickets = 0;
+        let mut breaches = Vec::new();
+
+        for ticket in tickets {
+            let Some(index) = contract.severities.iter().position(|s| s.priorities.contains(&ticket.priority)) else {
+                unmapped_tickets += 1;
+                continue;
+            };
+            let severity = &contract.severities[index];
+            let exceeds = |target: Option<Duration>, seconds: i64| {
+                target.is_some_and(|target| seconds > (target + contract.grace).num_seconds())
+            };
+            let first_response_breached = exceeds(severity.first_response, ticket.first_response_business_seconds);
+            let resolution_breached = exceeds(severity.resolution, ticket.resolution_business_seconds);
+            let running = (severity.first_response.is_some() && ticket.first_response_at.is_none())
+                || (severity.resolution.is_some() && ticket.resolved_at.is_none());
+
+            let compliance = &mut severities[index];
+            compliance.total_tickets += 1;
+            if first_response_breached || resolution_breached {
+                compliance.breached += 1;
+                breaches.push(ContractSlaBreach {
+                    ticket_id: ticket.id,
+                    ticket_ref: ticket.ticket_ref,
+                    severity: severity.name.clone(),
+                    priority: ticket.priority,
+                    created_at: ticket.created_at,
+                    first_response_at: ticket.first_response_at,
+                    resolved_at: ticket.resolved_at,
+                    first_response_business_minutes: ticket.first_response_business_seconds / 60,
+                    resolution_business_minutes: ticket.resolution_business_seconds / 60,
+                    first_response_breached,
+                    resolution_breached,
+                });
+            } else if running {
+                compliance.pending += 1;
+            } else {
+                compliance.met += 1;
+            }
+        }
+
+        for compliance in &mut severities {
+            let measured = compliance.met + compliance.breached;
+            if measured > 0 {
+                compliance.compliance_rate = Some(compliance.met as f64 / measured as f64 * 100.0);
+            }
+        }
+
+        Ok(ContractualSlaReport {
+            account: account.to_string(),
+            product: product.to_string(),
+            month,
+            timezone: hours.timezone.clone(),
+            period_start,
+            period_end,
+            generated_at,
+            severities,
+            unmapped_tickets,
+            breaches,
+        })
+    }
+
+    async fn apply_sla_to_open_tickets(
+        &self,
+        conn: &mut PgConnection,
+        product: &str,
+        priority: TicketPriority,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        let ticket_ids: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT id FROM support_tickets
+            WHERE product = $1 AND priority = $2
+              AND deleted_at IS NULL
+              AND NOT test_mode
+              AND status NOT IN ('RESOLVED', 'CLOSED')
+            "#,
+        )
+        .bind(product)
+        .bind(priority)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        if !ticket_ids.is_empty() {
+            let updated = self.apply_sla(conn, &ticket_ids, now).await?;
+            tracing::info!("Recomputed SLA deadlines for {} {} tickets", updated.len(), product);
+        }
+
+        Ok(())
+    }
+
+    /// Refresh SLA deadlines and the sticky breach flag of `ticket_ids` and their children
+    ///
+    /// Run whenever a ticket's targets or clocks may have changed: creation,
+    /// updates and target changes. Breaches found here are stamped with
+    /// `sla_breached_at` like those found by [`Self::evaluate_sla_breaches`].
+    async fn apply_sla(&self, conn: &mut PgConnection, ticket_ids: &[Uuid], now: DateTime<Utc>) -> Result<Vec<SupportTicket>> {
+        let policy = &self.sla_policy;
+        let (plans, grace_seconds): (Vec<String>, Vec<i64>) = policy
+            .plan_grace_windows
+            .iter()
+            .map(|(plan, grace)| (plan.clone(), grace.num_seconds()))
+            .unzip();
+
+        let query = format!(
+            r#"
+            WITH graced AS (
+                SELECT
+                    st.id,
+                    COALESCE(g.grace_seconds, $5) as grace_seconds,
+                    {SLA_TARGET_COLUMNS}
+                FROM support_tickets st
+                {SLA_GRACE_JOIN}
+                {SLA_TARGET_JOIN}
+                WHERE (st.id = ANY($8) OR st.parent_ticket_id = ANY($8))
+                  AND st.deleted_at IS NULL
+                  AND NOT st.test_mode
+            )
+            UPDATE support_tickets st
+            SET first_response_due_at = support_product_business_deadline(
+                    st.product, st.created_at,
+                    graced.first_response_seconds
+                        + support_product_business_maintenance_seconds(st.product, st.created_at, COALESCE(st.first_response_at, $6))
+                ),
+                sla_due_at = st.created_at + (
+                    graced.resolution_seconds
+                    + support_maintenance_seconds(st.product, st.created_at, COALESCE(st.resolved_at, $6))
+                    + CASE WHEN $7 THEN st.customer_wait_seconds + COALESCE(EXTRACT(EPOCH FROM ($6 - st.waiting_since)), 0) ELSE 0 END
+                ) * INTERVAL '1 second',
+                sla_breach = st.sla_breach OR {SLA_BREACHED_EXPR},
+                sla_breached_at = CASE
+                    WHEN NOT st.sla_breach AND {SLA_BREACHED_EXPR} THEN $6
+                    ELSE st.sla_breached_at
+                END
+            FROM graced
+            WHERE st.id = graced.id
+            RETURNING st.*
+            "#,
+        );
+
+        let tickets = sqlx::query_as::<_, SupportTicket>(&query)
+            .bind(policy.first_response_target.map(|d| d.num_seconds()))
+            .bind(policy.resolution_target.map(|d| d.num_seconds()))
+            .bind(plans)
+            .bind(grace_seconds)
+            .bind(policy.default_grace.num_seconds())
+            .bind(now)
+            .bind(policy.pause_while_waiting_on_customer)
+            .bind(ticket_ids)
+            .fetch_all(&mut *conn)
+            .await?;
+
+        Ok(tickets)
+    }
+
+    /// Evaluate the product's alert thresholds and publish an event for each one crossed
+    ///
+    /// Each threshold's metric is computed over its window ending now. Metrics
+    /// without data in the window (e.g. compliance with no new tickets) never
+    /// cross. Nothing is published while a maintenance window is active.
+    /// Returns the published events.
+    pub async fn evaluate_alert_thresholds(&self, product: &str) -> Result<Vec<MetricThresholdCrossed>> {
+        let now = self.now();
+        let mut events = Vec::new();
+
+        let active_windows = Self::maintenance_windows_at(&self.pool, product, now).await?;
+        if !active_windows.is_empty() {
+            tracing::info!("Suppressing {} alerts during maintenance: {}", product, active_windows[0].reason);
+            return Ok(events);
+        }
+
+        for threshold in self.alert_thresholds.thresholds_for(product) {
+            let window_start = now - threshold.window;
+            let Some(current_value) = self.alert_metric(product, threshold.metric, window_start, now).await? else {
+                continue;
+            };
+            if !threshold.is_crossed(current_value) {
+                continue;
+            }
+
+            let event = MetricThresholdCrossed {
+                product: product.to_string(),
+                metric: threshold.metric,
+                direction: threshold.direction,
+                threshold: threshold.value,
+                current_value,
+                window_start,
+                window_end: now,
+            };
+            self.alert_sink.publish(&event).await?;
+            events.push(event);
+        }
+
+        Ok(events)
+    }
+
+    async fn alert_metric(
+        &self,
+        product: &str,
+        metric: AlertMetric,
+        window_start: DateTime<Utc>,
+        window_end: DateTime<Utc>,
+    ) -> Result<Option<f64>> {
+        let sql = match metric {
+            AlertMetric::SlaBreaches => {
+                "SELECT COUNT(*)::FLOAT FROM support_tickets
+                 WHERE product = $1 AND deleted_at IS NULL AND NOT test_mode AND sla_breached_at BETWEEN $2 AND $3"
+            }
+            AlertMetric::SlaComplianceRate => {
+                "SELECT COUNT(*) FILTER (WHERE sla_breach = FALSE)::FLOAT / NULLIF(COUNT(*), 0)::FLOAT * 100
+                 FROM support_tickets
+                 WHERE product = $1 AND deleted_at IS NULL AND NOT test_mode AND created_at BETWEEN $2 AND $3"
+            }
+            AlertMetric::NewTickets => {
+                "SELECT COUNT(*)::FLOAT FROM support_tickets
+                 WHERE product = $1 AND deleted_at IS NULL AND NOT test_mode AND created_at BETWEEN $2 AND $3"
+            }
+            AlertMetric::DeadLetters => {
+                "SELECT COUNT(*)::FLOAT FROM dead_letters
+                 WHERE product = $1 AND status IN ('PENDING', 'RETRYING') AND created_at BETWEEN $2 AND $3"
+            }
+            AlertMetric::OldestDeadLetterMinutes => {
+                "SELECT EXTRACT(EPOCH FROM ($3 - MIN(created_at)))::FLOAT / 60
+                 FROM dead_letters
+                 WHERE product = $1 AND status IN ('PENDING', 'RETRYING') AND created_at <= $3"
+            }
+            AlertMetric::AvgFirstResponseMinutes => {
+                "SELECT AVG(EXTRACT(EPOCH FROM (first_response_at - created_at))::FLOAT / 60)
+                 FROM support_tickets
+                 WHERE product = $1 AND deleted_at IS NULL AND NOT test_mode AND first_response_at BETWEEN $2 AND $3"
+            }
+        };
+
+        let value: Option<f64> = sqlx::query_scalar(sql)
+            .bind(product)
+            .bind(window_start)
+            .bind(window_end)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(SupportError::from)?;
+
+        Ok(value)
+    }
+
+    /// Recompute `sla_breach` for the product's tickets created in the period
+    ///
+    /// Unlike [`Self::evaluate_sla_breaches`], flags are set *and cleared* to
+    /// match the current policy, which is what adopting or changing an SLA
+    /// policy on historical data needs. Retroactive breaches get no
+    /// `sla_breached_at`, so a recompute never trips breach-rate alerts.
+    /// Deadlines (`first_response_due_at`, `sla_due_at`) are left as they are.
+    pub async fn recompute_sla_breaches(
+        &self,
+        product: &str,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+        mut progress: impl FnMut(&BackfillProgress),
+    ) -> Result<BackfillProgress> {
+        self.ensure_writable()?;
+        let policy = &self.sla_policy;
+
+        let (plans, grace_seconds): (Vec<String>, Vec<i64>) = policy
+            .plan_grace_windows
+            .iter()
+            .map(|(plan, grace)| (plan.clone(), grace.num_seconds()))
+            .unzip();
+
+        let query = format!(
+            r#"
+            WITH graced AS (
+                SELECT
+                    st.id,
+                    COALESCE(g.grace_seconds, $5) as grace_seconds,
+                    {SLA_TARGET_COLUMNS}
+                FROM support_tickets st
+                {SLA_GRACE_JOIN}
+                {SLA_TARGET_JOIN}
+                WHERE st.id = ANY($8)
+            )
+            UPDATE support_tickets st
+            SET sla_breach = {SLA_BREACHED_EXPR},
+                sla_breached_at = CASE WHEN {SLA_BREACHED_EXPR} THEN st.sla_breached_at END,
+                updated_at = $6
+            FROM graced
+            WHERE st.id = graced.id
+              AND st.sla_breach IS DISTINCT FROM {SLA_BREACHED_EXPR}
+            "#,
+        );
+
+        let now = self.now();
+        self.backfill_batches(
+            product,
+            "NOT test_mode",
+            Some((period_start, period_end)),
+            |ids| {
+                sqlx::query(&query)
+                    .bind(policy.first_response_target.map(|d| d.num_seconds()))
+                    .bind(policy.resolution_target.map(|d| d.num_seconds()))
+                    .bind(plans.clone())
+                    .bind(grace_seconds.clone())
+                    .bind(policy.default_grace.num_seconds())
+                    .bind(now)
+                    .bind(policy.pause_while_waiting_on_customer)
+                    .bind(ids)
+            },
+            &mut progress,
+        )
+        .await
+    }
+
+    /// Fill `first_response_at` from message history for tickets that lack it
+    ///
+    /// The first response is the earliest public message not written by the
+    /// ticket's customer, as maintained by the message trigger for new tickets.
+    pub async fn backfill_first_response(
+        &self,
+        product: &str,
+        mut progress: impl FnMut(&BackfillProgress),
+    ) -> Result<BackfillProgress> {
+        self.ensure_writable()?;
+        let query = r#"
+            UPDATE support_tickets st
+            SET first_response_at = fr.first_response_at, updated_at = $2
+            FROM (
+                SELECT m.ticket_id, MIN(m.created_at) as first_response_at
+                FROM ticket_messages m
+                JOIN support_tickets t ON t.id = m.ticket_id
+                WHERE m.ticket_id = ANY($1)
+                  AND m.is_internal = FALSE
+                  AND m.author_id <> t.customer_id
+                  AND m.author_id <> '00000000-0000-0000-0000-000000000000'
+                GROUP BY m.ticket_id
+            ) fr
+            WHERE st.id = fr.ticket_id
+        "#;
+
+        let now = self.now();
+        self.backfill_batches(
+            product,
+            "first_response_at IS NULL",
+            None,
+            |ids| sqlx::query(query).bind(ids).bind(now),
+            &mut progress,
+        )
+        .await
+    }
+
+    /// Recompute full-text search vectors for the product's tickets
+    ///
+    /// Each ticket's search configuration is re-resolved from its locale, or
+    /// the product's locale when it has none, so this also re-indexes tickets
+    /// after the product's locale changes. Does not change `updated_at`.
+    pub async fn rebuild_search_vectors(
+        &self,
+        product: &str,
+        mut progress: impl FnMut(&BackfillProgress),
+    ) -> Result<BackfillProgress> {
+        self.ensure_writable()?;
+        let query = r#"
+            UPDATE support_tickets
+            SET search_config = support_search_config(COALESCE(locale, $2)),
+                search_vector = support_ticket_search_vector(
+                    id, subject, description, support_search_config(COALESCE(locale, $2))
+                )
+            WHERE id = ANY($1)
+              AND search_vector IS DISTINCT FROM support_ticket_search_vector(
+                    id, subject, description, support_search_config(COALESCE(locale, $2))
+                  )
+        "#;
+        let default_locale = self.locales.locale_for(product).locale;
+
+        self.backfill_batches(
+            product,
+            "TRUE",
+            None,
+            |ids| sqlx::query(query).bind(ids).bind(default_locale.clone()),
+            &mut progress,
+        )
+        .await
+    }
+
+    /// Run `update` in batches over the ids of the product's tickets matching
+    /// `scope` (a condition on `support_tickets`), optionally limited to
+    /// tickets created in `period`
+    async fn backfill_batches<'q>(
+        &self,
+        product: &str,
+        scope: &str,
+        period: Option<(DateTime<Utc>, DateTime<Utc>)>,
+        update: impl Fn(Vec<Uuid>) -> sqlx::query::Query<'q, Postgres, PgArguments>,
+        progress: &mut impl FnMut(&BackfillProgress),
+    ) -> Result<BackfillProgress> {
+        let condition = format!(
+            r#"
+            product = $1
+              AND deleted_at IS NULL
+              AND ($2::TIMESTAMPTZ IS NULL OR created_at >= $2)
+              AND ($3::TIMESTAMPTZ IS NULL OR created_at <= $3)
+              AND {}
+            "#,
+            scope
+        );
+        let (period_start, period_end) = period.unzip();
+
+        let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM support_tickets WHERE {}", condition))
+        .bind(product)
+        .bind(period_start)
+        .bind(period_end)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        let batch_query = format!(
+            r#"
+            SELECT id FROM support_tickets
+            WHERE {} AND id > $4
+            ORDER BY id
+            LIMIT $5
+            "#,
+            condition
+        );
+
+        let mut state = BackfillProgress { total, ..BackfillProgress::default() };
+        let mut after = Uuid::nil();
+
+        loop {
+            let ids: Vec<Uuid> = sqlx::query_scalar(&batch_query)
+                .bind(product)
+                .bind(period_start)
+                .bind(period_end)
+                .bind(after)
+                .bind(BACKFILL_BATCH_SIZE)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(SupportError::from)?;
+
+            let Some(&last) = ids.last() else {
+                break;
+            };
+            after = last;
+            state.processed += ids.len() as i64;
+
+            let result = update(ids)
+                .execute(&self.pool)
+                .await
+                .map_err(SupportError::from)?;
+            state.updated += result.rows_affected();
+
+            progress(&state);
+        }
+
+        Ok(state)
+    }
+
+    /// Strip submitter info older than the `IntakePrivacy` retention window
+    ///
+    /// Intake log entries past the window are deleted as well. Returns the
+    /// number of tickets updated.
+    pub async fn purge_expired_submitter_info(&self) -> Result<u64> {
+        self.ensure_writable()?;
+        let Some(retention) = self.intake_privacy.retention else {
+            return Ok(0);
+        };
+
+        let result = sqlx::query(
+            r#"
+            UPDATE support_tickets SET metadata = metadata - 'submitter'
+            WHERE metadata ? 'submitter'
+              AND (metadata->'submitter'->>'captured_at')::TIMESTAMPTZ < $1
+            "#,
+        )
+        .bind(self.now() - retention)
+        .execute(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        sqlx::query("DELETE FROM intake_submissions WHERE created_at < $1")
+            .bind(self.now() - retention)
+            .execute(&self.pool)
+            .await
+            .map_err(SupportError::from)?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Soft-delete a ticket, hiding it from lookups and listings
+    ///
+    /// The ticket stays restorable until `purge_deleted_tickets` removes it.
+    pub async fn delete_ticket(&self, ticket_id: Uuid, deleted_by: Uuid) -> Result<SupportTicket> {
+        self.ensure_writable()?;
+        self.set_ticket_deleted(ticket_id, deleted_by, true).await
+    }
+
+    /// Restore a soft-deleted ticket
+    pub async fn restore_ticket(&self, ticket_id: Uuid, restored_by: Uuid) -> Result<SupportTicket> {
+        self.ensure_writable()?;
+        self.set_ticket_deleted(ticket_id, restored_by, false).await
+    }
+
+    async fn set_ticket_deleted(&self, ticket_id: Uuid, actor_id: Uuid, deleted: bool) -> Result<SupportTicket> {
+        let now = self.now();
+        let mut tx = self.pool.begin().await?;
+
+        let ticket = sqlx::query_as::<_, SupportTicket>(
+            r#"
+            UPDATE support_tickets SET deleted_at = CASE WHEN $2 THEN $3 END, updated_at = $3
+            WHERE id = $1 AND (deleted_at IS NULL) = $2
+            RETURNING *
+            "#,
+        )
+        .bind(ticket_id)
+        .bind(deleted)
+        .bind(now)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(SupportError::TicketNotFound(ticket_id))?;
+
+        let (old_value, new_value) = if deleted { ("active", "deleted") } else { ("deleted", "active") };
+        Self::insert_audit_entry(&mut tx, ticket_id, Some(actor_id), "deleted", Some(old_value), Some(new_value), None, now).await?;
+        tx.commit().await?;
+
+        Ok(ticket)
+    }
+
+    /// Permanently remove tickets soft-deleted before the given time, in one
+    /// product or (with `None`) all of them
+    ///
+    /// Messages and audit entries are removed by cascade; live tickets linked
+    /// to a purged one are unlinked. Returns the number of tickets purged.
+    pub async fn purge_deleted_tickets(&self, product: Option<&str>, deleted_before: DateTime<Utc>) -> Result<u64> {
+        self.ensure_writable()?;
+        let mut tx = self.pool.begin().await?;
+        let ticket_ids: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT id FROM support_tickets
+            WHERE deleted_at IS NOT NULL AND deleted_at < $2
+              AND ($1::TEXT IS NULL OR product = $1)
+            FOR UPDATE
+            "#,
+        )
+        .bind(product)
+        .bind(deleted_before)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let purged = Self::purge_tickets(&mut tx, &ticket_ids, self.now()).await?;
+        tx.commit().await?;
+
+        Ok(purged)
+    }
+
+    /// Count soft-deleted tickets that `purge_deleted_tickets` would remove
+    pub async fn count_purgeable_tickets(&self, product: Option<&str>, deleted_before: DateTime<Utc>) -> Result<i64> {
+        let count: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM support_tickets
+            WHERE deleted_at IS NOT NULL AND deleted_at < $2
+              AND ($1::TEXT IS NULL OR product = $1)
+            "#,
+        )
+        .bind(product)
+        .bind(deleted_before)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        Ok(count)
+    }
+
+    /// Permanently remove a product's test-mode tickets created before the given time
+    ///
+    /// Messages, audit entries and other ticket data are removed by cascade;
+    /// live tickets linked to a purged one are unlinked. Returns the number of
+    /// tickets purged.
+    pub async fn purge_test_tickets(&self, product: &str, created_before: DateTime<Utc>) -> Result<u64> {
+        self.ensure_writable()?;
+        let mut tx = self.pool.begin().await?;
+        let ticket_ids: Vec<Uuid> = sqlx::query_scalar(
+            "SELECT id FROM support_tickets WHERE product = $1 AND test_mode AND created_at < $2 FOR UPDATE"
+        )
+        .bind(product)
+        .bind(created_before)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let purged = Self::purge_tickets(&mut tx, &ticket_ids, self.now()).await?;
+        tx.commit().await?;
+
+        Ok(purged)
+    }
+
+    /// Delete tickets outright, first unlinking surviving tickets that point at them,
+    /// and leave sync tombstones for devices that still hold them
+    async fn purge_tickets(tx: &mut Transaction<'_, Postgres>, ticket_ids: &[Uuid], now: DateTime<Utc>) -> Result<u64> {
+        sqlx::query(
+            r#"
+            UPDATE support_tickets
+            SET parent_ticket_id = CASE WHEN parent_ticket_id = ANY($1) THEN NULL ELSE parent_ticket_id END,
+                follow_up_of = CASE WHEN follow_up_of = ANY($1) THEN NULL ELSE follow_up_of END
+            WHERE (parent_ticket_id = ANY($1) OR follow_up_of = ANY($1))
+              AND NOT (id = ANY($1))
+            "#,
+        )
+        .bind(ticket_ids)
+        .execute(&mut **tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO sync_tombstones (ticket_id, product, assigned_to, organization_id, removed_at)
+            SELECT id, product, assigned_to, organization_id, $2 FROM support_tickets WHERE id = ANY($1)
+            ON CONFLICT (ticket_id) DO NOTHING
+            "#,
+        )
+        .bind(ticket_ids)
+        .bind(now)
+        .execute(&mut **tx)
+        .await?;
+
+        let result = sqlx::query("DELETE FROM support_tickets WHERE id = ANY($1)")
+            .bind(ticket_ids)
+            .execute(&mut **tx)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Count the test-mode tickets `purge_test_tickets` would remove
+    pub async fn count_test_tickets(&self, product: &str, created_before: DateTime<Utc>) -> Result<i64> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM support_tickets WHERE product = $1 AND test_mode AND created_at < $2"
+        )
+        .bind(product)
+        .bind(created_before)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Get dashboard metrics for support analytics
+    pub async fn get_dashboard_metrics(
+        &self,
+        product: &str,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> Result<CrmCoreSupportDashboardMetrics> {
+        self.get_dashboard_metrics_in_timezone(product, period_start, period_end, None).await
+    }
+
+    /// Dashboard metrics with trend days and "today" counts taken in `timezone`
+    ///
+    /// `timezone` must be an IANA name such as `America/Sao_Paulo`; when `None`
+    /// the product's configured locale timezone is used.
+    pub async fn get_dashboard_metrics_in_timezone(
+        &self,
+        product: &str,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+        timezone: Option<&str>,
+    ) -> Result<CrmCoreSupportDashboardMetrics> {
+        self.dashboard_metrics(product, None, period_start, period_end, timezone).await
+    }
+
+    /// Dashboard metrics of one B2B account's tickets, for tenant-scoped
+    /// analytics; `timezone` as for [`Self::get_dashboard_metrics_in_timezone`]
+    pub async fn get_organization_dashboard_metrics(
+        &self,
+        product: &str,
+        organization_id: Uuid,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+        timezone: Option<&str>,
+    ) -> Result<CrmCoreSupportDashboardMetrics> {
+        self.dashboard_metrics(product, Some(organization_id), period_start, period_end, timezone).await
+    }
+
+    async fn dashboard_metrics(
+        &self,
+        product: &str,
+        organization_id: Option<Uuid>,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+        timezone: Option<&str>,
+    ) -> Result<CrmCoreSupportDashboardMetrics> {
+        let mut formatting = self.locales.locale_for(product);
+        if let Some(timezone) = timezone {
+            formatting.timezone = timezone.to_string();
+        }
+        self.validate_timezone(&formatting.timezone).await?;
+        let timezone = formatting.timezone.as_str();
+
+        // All sections share one connection and the analytics statement timeout
+        let mut tx = self.begin_timed(OperationClass::Analytics).await?;
+
+        // Overview metrics
+        let overview = self.get_overview_metrics(&mut tx, product, organization_id, period_start, period_end, timezone).await?;
+
+        // Ticket counts by status
+        let ticket_by_status = self.get_status_counts(&mut tx, product, organization_id, period_start, period_end).await?;
+
+        // Ticket counts by priority
+        let ticket_by_priority = self.get_priority_counts(&mut tx, product, organization_id, period_start, period_end).await?;
+
+        // SLA metrics
+        let sla_metrics = self.get_sla_metrics(&mut tx, product, organization_id, period_start, period_end).await?;
+
+        // Response metrics
+        let response_metrics = self.get_response_metrics(&mut tx, product, organization_id, period_start, period_end).await?;
+
+        // Top performing agents
+        let top_agents = self.get_top_agents(&mut tx, product, organization_id, period_start, period_end).await?;
+
+        // Ticket trends (last 7 days)
+        let ticket_trends = self.get_ticket_trends(&mut tx, product, organization_id, period_start, period_end, timezone).await?;
+
+        // CSAT trends over the period
+        let csat_trends = self.get_csat_trends(&mut tx, product, organization_id, period_start, period_end, timezone).await?;
+
+        // Status funnel
+        let status_funnel = self.status_funnel(&mut tx, product, organization_id, period_start, period_end).await?;
+
+        // Per-channel breakdown
+        let channel_metrics = self.get_channel_metrics(&mut tx, product, organization_id, period_start, period_end).await?;
+        tx.commit().await?;
+
+        Ok(CrmCoreSupportDashboardMetrics {
+            overview,
+            ticket_by_status,
+            ticket_by_priority,
+            sla_metrics,
+            response_metrics,
+            top_agents,
+            ticket_trends,
+            csat_trends,
+            status_funnel,
+            channel_metrics,
+            formatting,
+        })
+    }
+
+    /// Overview, SLA and response metrics of two periods with the change
+    /// from `period_a` to `period_b`, e.g. last week then this week
+    ///
+    /// Both periods are computed in one transaction, in the product's timezone.
+    /// With `organization_id` only that B2B account's tickets are counted.
+    pub async fn compare_dashboard_periods(
+        &self,
+        product: &str,
+        organization_id: Option<Uuid>,
+        period_a: MetricsPeriod,
+        period_b: MetricsPeriod,
+    ) -> Result<CrmCoreMetricsComparison> {
+        for period in [&period_a, &period_b] {
+            if period.end <= period.start {
+                return Err(SupportError::Validation("Period end must be after its start".to_string()));
+            }
+        }
+        let formatting = self.locales.locale_for(product);
+        self.validate_timezone(&formatting.timezone).await?;
+        let timezone = formatting.timezone.as_str();
+
+        let mut tx = self.begin_timed(OperationClass::Analytics).await?;
+        let overview_a = self.get_overview_metrics(&mut tx, product, organization_id, period_a.start, period_a.end, timezone).await?;
+        let overview_b = self.get_overview_metrics(&mut tx, product, organization_id, period_b.start, period_b.end, timezone).await?;
+        let sla_a = self.get_sla_metrics(&mut tx, product, organization_id, period_a.start, period_a.end).await?;
+        let sla_b = self.get_sla_metrics(&mut tx, product, organization_id, period_b.start, period_b.end).await?;
+        let response_a = self.get_response_metrics(&mut tx, product, organization_id, period_a.start, period_a.end).await?;
+        let response_b = self.get_response_metrics(&mut tx, product, organization_id, period_b.start, period_b.end).await?;
+        tx.commit().await?;
+
+        Ok(CrmCoreMetricsComparison {
+            period_a_start: period_a.start,
+            period_a_end: period_a.end,
+            period_b_start: period_b.start,
+            period_b_end: period_b.end,
+            overview: CrmCoreOverviewComparison::new(&overview_a, &overview_b),
+            sla_metrics: CrmCoreSlaComparison::new(&sla_a, &sla_b),
+            response_metrics: CrmCoreResponseComparison::new(&response_a, &response_b),
+            formatting,
+        })
+    }
+
+    /// Reject anything the database does not know as an IANA timezone name
+    async fn validate_timezone(&self, timezone: &str) -> Result<()> {
+        let known: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM pg_timezone_names WHERE name = $1)"
+        )
+        .bind(timezone)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(SupportError::from)?;
+
+        if !known {
+            return Err(SupportError::InvalidInput(format!("Unknown timezone '{}'", timezone)));
+        }
+
+        Ok(())
+    }
+
+    async fn get_overview_metrics(
+        &self,
+        conn: &mut PgConnection,
+        product: &str,
+        organization_id: Option<Uuid>,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+        timezone: &str,
+    ) -> Result<CrmCoreSupportOverviewMetrics> {
+        let metrics = sqlx::query_as::<_, CrmCoreSupportOverviewMetrics>(
+            r#"
+            WITH last_day AS (
+                -- Start of the period's last calendar day in `timezone`
+                SELECT DATE_TRUNC('day', ($3 - INTERVAL '1 microsecond') AT TIME ZONE $5) AT TIME ZONE $5 as day_start
+            )
+            SELECT
+                COUNT(*) FILTER (WHERE status NOT IN ('CLOSED', 'RESOLVED')) as total_active_tickets,
+                COUNT(*) FILTER (WHERE created_at >= (SELECT day_start FROM last_day) AND created_at < $3) as new_tickets_today,
+                COUNT(*) FILTER (WHERE resolved_at >= (SELECT day_start FROM last_day) AND resolved_at < $3) as resolved_tickets_today,
+                AVG(EXTRACT(EPOCH FROM (first_response_at - created_at))::FLOAT / 60) FILTER (WHERE first_response_at IS NOT NULL) as avg_first_response_time_minutes,
+                AVG((EXTRACT(EPOCH FROM (resolved_at - created_at))::FLOAT - CASE WHEN $4 THEN customer_wait_seconds ELSE 0 END) / 3600) FILTER (WHERE resolved_at IS NOT NULL) as avg_resolution_time_hours,
+                (COUNT(*) FILTER (WHERE resolved_at IS NOT NULL AND first_response_at IS NOT NULL
+                    AND resolved_at - first_response_at < INTERVAL '1 hour')::FLOAT /
+                NULLIF(COUNT(*) FILTER (WHERE resolved_at IS NOT NULL), 0)::FLOAT * 100) as first_contact_resolution_rate,
+                (COUNT(*) FILTER (WHERE sla_breach = FALSE)::FLOAT /
+                NULLIF(COUNT(*), 0)::FLOAT * 100) as sla_compliance_rate,
+                COUNT(*) FILTER (WHERE sla_breach = TRUE) as sla_breach_count,
+                AVG(csat_score::FLOAT) FILTER (WHERE csat_score IS NOT NULL) as avg_csat_score,
+                (COUNT(*) FILTER (WHERE reopen_count > 0)::FLOAT /
+                NULLIF(COUNT(*) FILTER (WHERE reopen_count > 0 OR resolved_at IS NOT NULL OR closed_at IS NOT NULL), 0)::FLOAT * 100) as reopen_rate
+            FROM support_tickets
+            WHERE product = $1
+              AND deleted_at IS NULL
+              AND NOT test_mode
+              AND ($6::UUID IS NULL OR organization_id = $6)
+              AND created_at >= $2 AND created_at < $3
+            "#,
+        )
+        .bind(product)
+        .bind(period_start)
+        .bind(period_end)
+        .bind(self.sla_policy.pause_while_waiting_on_customer)
+        .bind(timezone)
+        .bind(organization_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(metrics)
+    }
+
+    async fn get_status_counts(
+        &self,
+        conn: &mut PgConnection,
+        product: &str,
+        organization_id: Option<Uuid>,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> Result<Vec<CrmCoreTicketStatusCount>> {
+        let counts = sqlx::query_as::<_, CrmCoreTicketStatusCount>(
+            r#"
+            SELECT
+                status::TEXT as status,
+                COUNT(*)::BIGINT as count
+            FROM support_tickets
+            WHERE product = $1
+              AND deleted_at IS NULL
+              AND NOT test_mode
+              AND ($4::UUID IS NULL OR organization_id = $4)
+              AND created_at >= $2 AND created_at < $3
+            GROUP BY status
+            ORDER BY count DESC
+            "#,
+        )
+        .bind(product)
+        .bind(period_start)
+        .bind(period_end)
+        .bind(organization_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(counts)
+    }
+
+    async fn get_channel_metrics(
+        &self,
+        conn: &mut PgConnection,
+        product: &str,
+        organization_id: Option<Uuid>,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> Result<Vec<CrmCoreChannelMetrics>> {
+        // Every channel is listed, including ones without tickets in the period
+        let metrics = sqlx::query_as::<_, CrmCoreChannelMetrics>(
+            r#"
+            SELECT
+                c.channel::TEXT as channel,
+                COUNT(t.id)::BIGINT as total_tickets,
+                COUNT(t.id) FILTER (WHERE t.status NOT IN ('CLOSED', 'RESOLVED'))::BIGINT as open_tickets,
+                COUNT(t.id) FILTER (WHERE t.resolved_at IS NOT NULL)::BIGINT as resolved_tickets,
+                COALESCE(COUNT(t.id)::FLOAT / NULLIF(SUM(COUNT(t.id)) OVER (), 0)::FLOAT * 100, 0) as volume_percentage,
+                AVG(EXTRACT(EPOCH FROM (t.first_response_at - t.created_at))::FLOAT / 60)
+                    FILTER (WHERE t.first_response_at IS NOT NULL) as avg_first_response_minutes,
+                AVG((EXTRACT(EPOCH FROM (t.resolved_at - t.created_at))::FLOAT - CASE WHEN $4 THEN t.customer_wait_seconds ELSE 0 END) / 3600)
+                    FILTER (WHERE t.resolved_at IS NOT NULL) as avg_resolution_hours,
+                COUNT(t.id) FILTER (WHERE t.sla_breach = TRUE)::BIGINT as sla_breach_count,
+                AVG(t.csat_score::FLOAT) FILTER (WHERE t.csat_score IS NOT NULL) as avg_csat_score
+            FROM UNNEST(enum_range(NULL::ticket_channel)) AS c(channel)
+            LEFT JOIN support_tickets t
+                ON t.channel = c.channel
+               AND t.product = $1
+               AND t.deleted_at IS NULL
+               AND NOT t.test_mode
+               AND ($5::UUID IS NULL OR t.organization_id = $5)
+               AND t.created_at >= $2 AND t.created_at < $3
+            GROUP BY c.channel
+            ORDER BY c.channel
+            "#,
+        )
+        .bind(product)
+        .bind(period_start)
+        .bind(period_end)
+        .bind(self.sla_policy.pause_while_waiting_on_customer)
+        .bind(organization_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(metrics)
+    }
+
+    async fn get_priority_counts(
+        &self,
+        conn: &mut PgConnection,
+        product: &str,
+        organization_id: Option<Uuid>,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> Result<Vec<CrmCoreTicketPriorityCount>> {
+        let counts = sqlx::query_as::<_, CrmCoreTicketPriorityCount>(
+            r#"
+            SELECT
+                priority::TEXT as priority,
+                COUNT(*)::BIGINT as count
+            FROM support_tickets
+            WHERE product = $1
+              AND deleted_at IS NULL
+              AND NOT test_mode
+              AND ($4::UUID IS NULL OR organization_id = $4)
+              AND created_at >= $2 AND created_at < $3
+            GROUP BY priority
+            ORDER BY
+                CASE priority::TEXT
+                    WHEN 'URGENT' THEN 1
+                    WHEN 'HIGH' THEN 2
+                    WHEN 'MEDIUM' THEN 3
+                    WHEN 'LOW' THEN 4
+                END
+            "#,
+        )
+        .bind(product)
+        .bind(period_start)
+        .bind(period_end)
+        .bind(organization_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(counts)
+    }
+
+    async fn get_sla_metrics(
+        &self,
+        conn: &mut PgConnection,
+        product: &str,
+        organization_id: Option<Uuid>,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> Result<CrmCoreSlaMetrics> {
+        let metrics = sqlx::query_as::<_, CrmCoreSlaMetrics>(
+            r#"
+            SELECT
+                COUNT(*)::BIGINT as total_tickets,
+                COUNT(*) FILTER (WHERE sla_breach = FALSE)::BIGINT as tickets_meeting_sla,
+                COUNT(*) FILTER (WHERE sla_breach = TRUE)::BIGINT as tickets_breaching_sla,
+                COALESCE(
+                    COUNT(*) FILTER (WHERE sla_breach = FALSE)::FLOAT /
+                    NULLIF(COUNT(*), 0)::FLOAT * 100,
+                    0.0
+                ) as compliance_rate,
+                AVG(EXTRACT(EPOCH FROM (first_response_at - created_at))::FLOAT / 60) FILTER (WHERE first_response_at IS NOT NULL) as avg_first_response_minutes,
+                AVG(support_product_business_seconds(product, created_at, first_response_at)::FLOAT / 60)
+                    FILTER (WHERE first_response_at IS NOT NULL) as time_to_first_response_business_minutes,
+                AVG((EXTRACT(EPOCH FROM (resolved_at - created_at))::FLOAT - CASE WHEN $4 THEN customer_wait_seconds ELSE 0 END) / 3600) FILTER (WHERE resolved_at IS NOT NULL) as avg_resolution_hours
+            FROM support_tickets
+            WHERE product = $1
+              AND deleted_at IS NULL
+              AND NOT test_mode
+              AND ($5::UUID IS NULL OR organization_id = $5)
+              AND created_at >= $2 AND created_at < $3
+            "#,
+        )
+        .bind(product)
+        .bind(period_start)
+        .bind(period_end)
+        .bind(self.sla_policy.pause_while_waiting_on_customer)
+        .bind(organization_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        let (by_priority, by_category): (Vec<_>, Vec<_>) = self
+            .get_sla_breakdowns(conn, product, organization_id, period_start, period_end)
+            .await?
+            .into_iter()
+            .partition(|row| row.by_priority);
+
+        Ok(CrmCoreSlaMetrics {
+            by_priority: by_priority.into_iter().map(|row| row.breakdown).collect(),
+            by_category: by_category.into_iter().map(|row| row.breakdown).collect(),
+            ..metrics
+        })
+    }
+
+    /// `get_sla_metrics` figures per priority and per category, so an overall
+    /// compliance rate can't hide one group doing badly
+    async fn get_sla_breakdowns(
+        &self,
+        conn: &mut PgConnection,
+        product: &str,
+        organization_id: Option<Uuid>,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> Result<Vec<SlaBreakdownRow>> {
+        let rows = sqlx::query_as::<_, SlaBreakdownRow>(
+            r#"
+            SELECT
+                GROUPING(priority) = 0 as by_priority,
+                CASE WHEN GROUPING(priority) = 0 THEN priority::TEXT ELSE category END as key,
+                COUNT(*)::BIGINT as total_tickets,
+                COUNT(*) FILTER (WHERE sla_breach = FALSE)::BIGINT as tickets_meeting_sla,
+                COUNT(*) FILTER (WHERE sla_breach = TRUE)::BIGINT as tickets_breaching_sla,
+                COALESCE(
+                    COUNT(*) FILTER (WHERE sla_breach = FALSE)::FLOAT /
+                    NULLIF(COUNT(*), 0)::FLOAT * 100,
+                    0.0
+                ) as compliance_rate,
+                AVG(EXTRACT(EPOCH FROM (first_response_at - created_at))::FLOAT / 60) FILTER (WHERE first_response_at IS NOT NULL) as avg_first_response_minutes,
+                AVG((EXTRACT(EPOCH FROM (resolved_at - created_at))::FLOAT - CASE WHEN $4 THEN customer_wait_seconds ELSE 0 END) / 3600) FILTER (WHERE resolved_at IS NOT NULL) as avg_resolution_hours
+            FROM support_tickets
+            WHERE product = $1
+              AND deleted_at IS NULL
+              AND NOT test_mode
+              AND ($5::UUID IS NULL OR organization_id = $5)
+              AND created_at >= $2 AND created_at < $3
+            GROUP BY GROUPING SETS ((priority), (category))
+            ORDER BY
+                by_priority DESC,
+                CASE priority::TEXT
+                    WHEN 'URGENT' THEN 1
+                    WHEN 'HIGH' THEN 2
+                    WHEN 'MEDIUM' THEN 3
+                    WHEN 'LOW' THEN 4
+                END,
+                total_tickets DESC,
+                key
+            "#,
+        )
+        .bind(product)
+        .bind(period_start)
+        .bind(period_end)
+        .bind(self.sla_policy.pause_while_waiting_on_customer)
+        .bind(organization_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn get_response_metrics(
+        &self,
+        conn: &mut PgConnection,
+        product: &str,
+        organization_id: Option<Uuid>,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> Result<CrmCoreResponseMetrics> {
+        let metrics = sqlx::query_as::<_, CrmCoreResponseMetrics>(
+            r#"
+            WITH reply_views AS (
+                SELECT
+                    m.created_at,
+                    (SELECT MIN(v.viewed_at) FROM ticket_views v
+                        WHERE v.ticket_id = m.ticket_id AND v.viewed_at >= m.created_at) as first_view_at
+                FROM ticket_messages m
+                JOIN support_tickets t ON t.id = m.ticket_id
+                WHERE t.product = $1
+                  AND t.deleted_at IS NULL
+                  AND NOT t.test_mode
+                  AND ($6::UUID IS NULL OR t.organization_id = $6)
+                  AND m.is_internal = FALSE
+                  AND m.author_id <> t.customer_id
+                  AND m.created_at >= $2 AND m.created_at < $3
+            )
+            SELECT
+                AVG(EXTRACT(EPOCH FROM (first_response_at - created_at))::FLOAT / 60) as avg_first_response_minutes,
+                PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY EXTRACT(EPOCH FROM (first_response_at - created_at)) / 60) as median_first_response_minutes,
+                AVG(EXTRACT(EPOCH FROM (first_response_at - created_at))::FLOAT / 60) as avg_response_minutes,
+                PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY EXTRACT(EPOCH FROM (first_response_at - created_at)) / 60) as median_response_minutes,
+                AVG((EXTRACT(EPOCH FROM (resolved_at - created_at))::FLOAT - CASE WHEN $4 THEN customer_wait_seconds ELSE 0 END) / 3600) as avg_resolution_hours,
+                PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY (EXTRACT(EPOCH FROM (resolved_at - created_at)) - CASE WHEN $4 THEN customer_wait_seconds ELSE 0 END) / 3600) as median_resolution_hours,
+                AVG((customer_wait_seconds + COALESCE(EXTRACT(EPOCH FROM ($5 - waiting_since))::FLOAT, 0)) / 3600) as avg_customer_wait_hours,
+                PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY (customer_wait_seconds + COALESCE(EXTRACT(EPOCH FROM ($5 - waiting_since)), 0)) / 3600) as median_customer_wait_hours,
+                (SELECT AVG(EXTRACT(EPOCH FROM (first_view_at - created_at))::FLOAT / 60) FROM reply_views) as avg_reply_to_view_minutes,
+                (SELECT PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY EXTRACT(EPOCH FROM (first_view_at - created_at)) / 60)
+                    FROM reply_views WHERE first_view_at IS NOT NULL) as median_reply_to_view_minutes
+            FROM support_tickets
+            WHERE product = $1
+              AND deleted_at IS NULL
+              AND NOT test_mode
+              AND ($6::UUID IS NULL OR organization_id = $6)
+              AND created_at >= $2 AND created_at < $3
+            "#,
+        )
+        .bind(product)
+        .bind(period_start)
+        .bind(period_end)
+        .bind(self.sla_policy.pause_while_waiting_on_customer)
+        .bind(self.now())
+        .bind(organization_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(metrics)
+    }
 
-impl SupportRepository {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    async fn get_top_agents(
+        &self,
+        conn: &mut PgConnection,
+        product: &str,
+        organization_id: Option<Uuid>,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> Result<Vec<CrmCoreAgentPerformance>> {
+        let agents = sqlx::query_as::<_, CrmCoreAgentPerformance>(
+            r#"
+            WITH tickets AS (
+                SELECT * FROM support_tickets
+                WHERE product = $1
+                  AND deleted_at IS NULL
+                  AND NOT test_mode
+                  AND ($5::UUID IS NULL OR organization_id = $5)
+                  AND created_at >= $2 AND created_at < $3
+            ),
+            assigned AS (
+                SELECT
+                    assigned_to as agent,
+                    COUNT(*) as tickets_assigned,
+                    AVG(EXTRACT(EPOCH FROM (first_response_at - created_at))::FLOAT / 60) FILTER (WHERE first_response_at IS NOT NULL) as avg_first_response_minutes
+                FROM tickets
+                WHERE assigned_to IS NOT NULL
+                GROUP BY assigned_to
+            ),
+            -- Resolutions and CSAT belong to whoever resolved the ticket, even after reassignment
+            resolved AS (
+                SELECT
+                    resolving_agent_id as agent,
+                    COUNT(*) as tickets_resolved,
+                    AVG((EXTRACT(EPOCH FROM (resolved_at - created_at))::FLOAT - CASE WHEN $4 THEN customer_wait_seconds ELSE 0 END) / 3600) FILTER (WHERE resolved_at IS NOT NULL) as avg_resolution_hours,
+                    AVG(csat_score::FLOAT) FILTER (WHERE csat_score IS NOT NULL) as csat_score
+                FROM tickets
+                WHERE resolving_agent_id IS NOT NULL
+                  AND status IN ('RESOLVED', 'CLOSED')
+                GROUP BY resolving_agent_id
+            )
+            SELECT
+                COALESCE(a.agent, r.agent)::TEXT as agent_id,
+                COALESCE(a.agent, r.agent)::TEXT as agent_name,
+                COALESCE(a.tickets_assigned, 0)::BIGINT as tickets_assigned,
+                COALESCE(r.tickets_resolved, 0)::BIGINT as tickets_resolved,
+                a.avg_first_response_minutes,
+                r.avg_resolution_hours,
+                r.csat_score
+            FROM assigned a
+            FULL JOIN resolved r ON r.agent = a.agent
+            ORDER BY tickets_resolved DESC
+            LIMIT 10
+            "#,
+        )
+        .bind(product)
+        .bind(period_start)
+        .bind(period_end)
+        .bind(self.sla_policy.pause_while_waiting_on_customer)
+        .bind(organization_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(agents)
     }
 
-    /// Create a new support ticket
-    pub async fn create_ticket(&self, product: &str, input: &CreateTicketInput) -> Result<SupportTicket> {
-        let ticket = sqlx::query_as::<_, SupportTicket>(
+    async fn get_ticket_trends(
+        &self,
+        conn: &mut PgConnection,
+        product: &str,
+        organization_id: Option<Uuid>,
+        _period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+        timezone: &str,
+    ) -> Result<Vec<CrmCoreTicketTrend>> {
+        // Get last 7 days of trends, as calendar days in `timezone`
+        let start = period_end - Duration::days(7);
+
+        let trends = sqlx::query_as::<_, CrmCoreTicketTrend>(
             r#"
-            INSERT INTO support_tickets (
-                product, customer_id, subject, description, priority, category
-            ) VALUES ($1, $2, $3, $4, $5, $6)
-            RETURNING *
+            WITH date_series AS (
+                SELECT generate_series(($2 AT TIME ZONE $4)::DATE, ($3 AT TIME ZONE $4)::DATE, '1 day'::INTERVAL)::DATE as date
+            )
+            SELECT
+                ds.date::TEXT as date,
+                COALESCE(COUNT(*) FILTER (WHERE DATE(created_at AT TIME ZONE $4) = ds.date), 0)::BIGINT as new_tickets,
+                COALESCE(COUNT(*) FILTER (WHERE DATE(resolved_at AT TIME ZONE $4) = ds.date), 0)::BIGINT as resolved_tickets,
+                COALESCE(COUNT(*) FILTER (WHERE status NOT IN ('CLOSED', 'RESOLVED') AND DATE(created_at AT TIME ZONE $4) <= ds.date), 0)::BIGINT as active_tickets
+            FROM date_series ds
+            LEFT JOIN support_tickets st ON st.product = $1 AND st.deleted_at IS NULL AND NOT st.test_mode
+                AND ($5::UUID IS NULL OR st.organization_id = $5)
+            GROUP BY ds.date
+            ORDER BY ds.date DESC
             "#,
         )
         .bind(product)
-        .bind(&input.customer_id)
-        .bind(&input.subject)
-        .bind(&input.description)
-        .bind(&input.priority)
-        .bind(&input.category)
-        .fetch_one(&self.pool)
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to create support ticket: {}", e);
-            SupportError::Database(e)
-        })?;
+        .bind(start)
+        .bind(period_end)
+        .bind(timezone)
+        .bind(organization_id)
+        .fetch_all(&mut *conn)
+        .await?;
 
-        Ok(ticket)
+        Ok(trends)
     }
 
-    /// Get ticket by ID
-    pub async fn find_by_id(&self, ticket_id: Uuid) -> Result<SupportTicket> {
-        let ticket = sqlx::query_as::<_, SupportTicket>(
-            "SELECT * FROM support_tickets WHERE id = $1 AND deleted_at IS NULL"
+    async fn get_csat_trends(
+        &self,
+        conn: &mut PgConnection,
+        product: &str,
+        organization_id: Option<Uuid>,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+        timezone: &str,
+    ) -> Result<CrmCoreCsatTrends> {
+        let daily = Self::csat_trend_points(conn, "day", product, organization_id, period_start, period_end, timezone).await?;
+        let weekly = Self::csat_trend_points(conn, "week", product, organization_id, period_start, period_end, timezone).await?;
+
+        Ok(CrmCoreCsatTrends { daily, weekly })
+    }
+
+    /// CSAT per `unit` (a `DATE_TRUNC` field) in `timezone`, from the bucket
+    /// holding the period start to the one holding its (exclusive) end, empty
+    /// buckets included
+    async fn csat_trend_points(
+        conn: &mut PgConnection,
+        unit: &str,
+        product: &str,
+        organization_id: Option<Uuid>,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+        timezone: &str,
+    ) -> Result<Vec<CrmCoreCsatTrendPoint>> {
+        let points = sqlx::query_as::<_, CrmCoreCsatTrendPoint>(
+            r#"
+            WITH buckets AS (
+                SELECT generate_series(
+                    DATE_TRUNC($5, $2 AT TIME ZONE $4),
+                    DATE_TRUNC($5, ($3 AT TIME ZONE $4) - INTERVAL '1 microsecond'),
+                    ('1 ' || $5)::INTERVAL
+                )::DATE as date
+            ),
+            surveys AS (
+                SELECT DATE_TRUNC($5, cs.sent_at AT TIME ZONE $4)::DATE as date, cs.responded_at, cs.score
+                FROM csat_surveys cs
+                JOIN support_tickets t ON t.id = cs.ticket_id
+                WHERE cs.product = $1
+                  AND cs.sent_at >= $2 AND cs.sent_at < $3
+                  AND t.deleted_at IS NULL
+                  AND NOT t.test_mode
+                  AND ($6::UUID IS NULL OR t.organization_id = $6)
+            )
+            SELECT
+                b.date::TEXT as date,
+                COUNT(s.date)::BIGINT as surveys_sent,
+                COUNT(s.responded_at)::BIGINT as responses,
+                CASE WHEN COUNT(s.date) > 0 THEN COUNT(s.responded_at)::FLOAT / COUNT(s.date) * 100 END as response_rate,
+                AVG(s.score::FLOAT) as avg_score,
+                ARRAY[
+                    COUNT(*) FILTER (WHERE s.score = 1),
+                    COUNT(*) FILTER (WHERE s.score = 2),
+                    COUNT(*) FILTER (WHERE s.score = 3),
+                    COUNT(*) FILTER (WHERE s.score = 4),
+                    COUNT(*) FILTER (WHERE s.score = 5)
+                ]::BIGINT[] as score_distribution
+            FROM buckets b
+            LEFT JOIN surveys s ON s.date = b.date
+            GROUP BY b.date
+            ORDER BY b.date
+            "#,
         )
-        .bind(ticket_id)
-        .fetch_one(&self.pool)
-        .await
-        .map_err(|e| match e {
-            sqlx::Error::RowNotFound => SupportError::TicketNotFound(ticket_id),
-            _ => {
-                tracing::error!("Failed to fetch support ticket: {}", e);
-                SupportError::Database(e)
-            }
-        })?;
+        .bind(product)
+        .bind(period_start)
+        .bind(period_end)
+        .bind(timezone)
+        .bind(unit)
+        .bind(organization_id)
+        .fetch_all(&mut *conn)
+        .await?;
 
-        Ok(ticket)
+        Ok(points)
     }
 
-    /// Update ticket
-    pub async fn update_ticket(&self, ticket_id: Uuid, input: &UpdateTicketInput) -> Result<SupportTicket> {
-        let ticket = sqlx::query_as::<_, SupportTicket>(
+    /// Open ticket counts for header badges
+    ///
+    /// Reads the counters a trigger maintains on every ticket write, so the
+    /// cost does not grow with the number of tickets.
+    pub async fn quick_stats(&self, product: &str) -> Result<QuickStats> {
+        let open_by_priority = sqlx::query_as::<_, PriorityOpenCount>(
             r#"
-            UPDATE support_tickets SET
-                subject = COALESCE($2, subject),
-                description = COALESCE($3, description),
-                status = COALESCE($4, status),
-                priority = COALESCE($5, priority),
-                category = COALESCE($6, category),
-                assigned_to = COALESCE($7, assigned_to),
-                updated_at = NOW()
-            WHERE id = $1 AND deleted_at IS NULL
-            RETURNING *
+            SELECT p.priority, COALESCE(c.open_count, 0)::BIGINT as open_tickets
+            FROM UNNEST(enum_range(NULL::ticket_priority)) AS p(priority)
+            LEFT JOIN ticket_open_counters c ON c.product = $1 AND c.priority = p.priority
+            ORDER BY
+                CASE p.priority::TEXT
+                    WHEN 'URGENT' THEN 1
+                    WHEN 'HIGH' THEN 2
+                    WHEN 'MEDIUM' THEN 3
+                    WHEN 'LOW' THEN 4
+                END
             "#,
         )
-        .bind(ticket_id)
-        .bind(&input.subject)
-        .bind(&input.description)
-        .bind(&input.status)
-        .bind(&input.priority)
-        .bind(&input.category)
-        .bind(&input.assigned_to)
-        .fetch_one(&self.pool)
+        .bind(product)
+        .fetch_all(&self.pool)
         .await
-        .map_err(|e| match e {
-            sqlx::Error::RowNotFound => SupportError::TicketNotFound(ticket_id),
-            _ => SupportError::Database(e)
-        })?;
+        .map_err(SupportError::from)?;
 
-        Ok(ticket)
+        Ok(QuickStats {
+            product: product.to_string(),
+            open_tickets: open_by_priority.iter().map(|c| c.open_tickets).sum(),
+            open_by_priority,
+        })
     }
 
-    /// List tickets with filters
-    pub async fn list(&self, product: &str, filter: &TicketFilter, limit: i64, offset: i64) -> Result<Vec<SupportTicket>> {
-        let mut query = String::from(
-            "SELECT * FROM support_tickets WHERE product = $1 AND deleted_at IS NULL"
-        );
-        let mut params_count = 1;
+    /// Live queue aggregates for wallboards; see [`wallboard`](crate::wallboard)
+    pub async fn queue_snapshot(&self, product: &str) -> Result<QueueSnapshot> {
+        let stats = self.quick_stats(product).await?;
+        let now = self.now();
+        let timezone = self.locales.locale_for(product).timezone;
 
-        if filter.status.is_some() {
-            params_count += 1;
-            query.push_str(&format!(" AND status = ${}", params_count));
-        }
+        let mut tx = self.begin_timed(OperationClass::Read).await?;
+        let oldest_waiting: Option<(Uuid, DateTime<Utc>)> = sqlx::query_as(
+            r#"
+            SELECT id, created_at FROM support_tickets
+            WHERE product = $1 AND deleted_at IS NULL AND NOT test_mode
+              AND status IN ('NEW', 'IN_PROGRESS')
+            ORDER BY created_at ASC, id ASC
+            LIMIT 1
+            "#,
+        )
+        .bind(product)
+        .fetch_optional(&mut *tx)
+        .await?;
 
-        if filter.priority.is_some() {
-            params_count += 1;
-            query.push_str(&format!(" AND priority = ${}", params_count));
-        }
+        let (agents_online, breaches_today): (i64, i64) = sqlx::query_as(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM support_agents
+                 WHERE product = $1 AND active AND last_seen_at >= $2)::BIGINT,
+                (SELECT COUNT(*) FROM support_tickets
+                 WHERE product = $1 AND deleted_at IS NULL AND NOT test_mode
+                   AND sla_breached_at >= DATE_TRUNC('day', $3 AT TIME ZONE $4) AT TIME ZONE $4)::BIGINT
+            "#,
+        )
+        .bind(product)
+        .bind(now - Duration::minutes(AGENT_ONLINE_WINDOW_MINUTES))
+        .bind(now)
+        .bind(&timezone)
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
 
-        if filter.assigned_to.is_some() {
-            params_count += 1;
-            query.push_str(&format!(" AND assigned_to = ${}", params_count));
-        }
+        Ok(QueueSnapshot {
+            product: stats.product,
+            taken_at: now,
+            open_tickets: stats.open_tickets,
+            open_by_priority: stats.open_by_priority,
+            oldest_waiting_ticket_id: oldest_waiting.map(|(id, _)| id),
+            oldest_waiting_since: oldest_waiting.map(|(_, since)| since),
+            oldest_waiting_minutes: oldest_waiting.map(|(_, since)| (now - since).num_minutes()),
+            agents_online,
+            breaches_today,
+        })
+    }
 
-        if filter.customer_id.is_some() {
-            params_count += 1;
-            query.push_str(&format!(" AND customer_id = ${}", params_count));
+    /// A product's report schedules, by name
+    pub async fn list_report_schedules(&self, product: &str) -> Result<Vec<ReportSchedule>> {
+        let schedules = sqlx::query_as::<_, ReportSchedule>(
+            "SELECT * FROM report_schedules WHERE product = $1 ORDER BY name ASC, created_at ASC, id ASC"
+        )
+        .bind(product)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(schedules)
+    }
+
+    pub async fn get_report_schedule(&self, schedule_id: Uuid) -> Result<ReportSchedule> {
+        sqlx::query_as::<_, ReportSchedule>("SELECT * FROM report_schedules WHERE id = $1")
+            .bind(schedule_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| SupportError::Validation(format!("Report schedule not found: {}", schedule_id)))
+    }
+
+    /// Add a report schedule; its first report runs at the next matching local hour
+    pub async fn create_report_schedule(
+        &self,
+        product: &str,
+        input: &CreateReportScheduleInput,
+        created_by: Option<Uuid>,
+    ) -> Result<ReportSchedule> {
+        self.ensure_writable()?;
+        if input.name.trim().is_empty() {
+            return Err(SupportError::Validation("Report schedule name is required".to_string()));
         }
+        let day_of_week = input.day_of_week.unwrap_or(1);
+        Self::validate_report_timing(day_of_week, input.hour)?;
+        let timezone = input.timezone.clone().unwrap_or_else(|| self.locales.locale_for(product).timezone);
+        self.validate_timezone(&timezone).await?;
 
-        query.push_str(" ORDER BY created_at DESC");
-        query.push_str(&format!(" LIMIT ${} OFFSET ${}", params_count + 1, params_count + 2));
+        let schedule = sqlx::query_as::<_, ReportSchedule>(
+            r#"
+            INSERT INTO report_schedules (
+                product, name, frequency, day_of_week, hour, timezone, recipients, next_run_at,
+                created_by, created_at, updated_at, include_breach_digest
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, support_report_next_run($9, $6, $3, $4, $5), $8, $9, $9, $10)
+            RETURNING *
+            "#,
+        )
+        .bind(product)
+        .bind(input.name.trim())
+        .bind(input.frequency)
+        .bind(day_of_week)
+        .bind(input.hour)
+        .bind(&timezone)
+        .bind(&input.recipients)
+        .bind(created_by)
+        .bind(self.now())
+        .bind(input.include_breach_digest)
+        .fetch_one(&self.pool)
+        .await?;
 
-        let mut q = sqlx::query_as::<_, SupportTicket>(&query)
-            .bind(product);
+        Ok(schedule)
+    }
 
-        if let Some(status) = &filter.status {
-            q = q.bind(status);
+    /// Change a report schedule
+    ///
+    /// Changing when it runs, or reactivating it, moves its next run to the
+    /// next matching local hour.
+    pub async fn update_report_schedule(
+        &self,
+        schedule_id: Uuid,
+        input: &UpdateReportScheduleInput,
+    ) -> Result<ReportSchedule> {
+        self.ensure_writable()?;
+        let current = self.get_report_schedule(schedule_id).await?;
+        let name = input.name.as_deref().map(str::trim).unwrap_or(&current.name);
+        if name.is_empty() {
+            return Err(SupportError::Validation("Report schedule name is required".to_string()));
         }
-        if let Some(priority) = &filter.priority {
-            q = q.bind(priority);
+        let day_of_week = input.day_of_week.unwrap_or(current.day_of_week);
+        let hour = input.hour.unwrap_or(current.hour);
+        Self::validate_report_timing(day_of_week, hour)?;
+        if let Some(timezone) = &input.timezone {
+            self.validate_timezone(timezone).await?;
         }
-        if let Some(assigned_to) = filter.assigned_to {
-            q = q.bind(assigned_to);
+        let reschedule = input.frequency.is_some()
+            || input.day_of_week.is_some()
+            || input.hour.is_some()
+            || input.timezone.is_some()
+            || (input.active == Some(true) && !current.active);
+
+        let schedule = sqlx::query_as::<_, ReportSchedule>(
+            r#"
+            UPDATE report_schedules
+            SET name = $2,
+                frequency = $3,
+                day_of_week = $4,
+                hour = $5,
+                timezone = $6,
+                recipients = $7,
+                active = $8,
+                next_run_at = CASE WHEN $9 THEN support_report_next_run($10, $6, $3, $4, $5) ELSE next_run_at END,
+                updated_at = $10,
+                include_breach_digest = $11
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(schedule_id)
+        .bind(name)
+        .bind(input.frequency.unwrap_or(current.frequency))
+        .bind(day_of_week)
+        .bind(hour)
+        .bind(input.timezone.as_ref().unwrap_or(&current.timezone))
+        .bind(input.recipients.as_ref().unwrap_or(&current.recipients))
+        .bind(input.active.unwrap_or(current.active))
+        .bind(reschedule)
+        .bind(self.now())
+        .bind(input.include_breach_digest.unwrap_or(current.include_breach_digest))
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| SupportError::Validation(format!("Report schedule not found: {}", schedule_id)))?;
+
+        Ok(schedule)
+    }
+
+    /// Delete a report schedule with its past runs
+    pub async fn delete_report_schedule(&self, schedule_id: Uuid) -> Result<bool> {
+        self.ensure_writable()?;
+        let result = sqlx::query("DELETE FROM report_schedules WHERE id = $1")
+            .bind(schedule_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    fn validate_report_timing(day_of_week: i32, hour: i32) -> Result<()> {
+        if !(1..=7).contains(&day_of_week) {
+            return Err(SupportError::Validation("day_of_week must be between 1 (Monday) and 7 (Sunday)".to_string()));
         }
-        if let Some(customer_id) = filter.customer_id {
-            q = q.bind(customer_id);
+        if !(0..=23).contains(&hour) {
+            return Err(SupportError::Validation("hour must be between 0 and 23".to_string()));
         }
+        Ok(())
+    }
 
-        q = q.bind(limit).bind(offset);
+    /// A product's summary for the last full day or week in `timezone`
+    ///
+    /// `timezone` defaults to the product's. See [`reports`](crate::reports).
+    pub async fn generate_report(
+        &self,
+        product: &str,
+        frequency: ReportFrequency,
+        timezone: Option<&str>,
+    ) -> Result<SupportReport> {
+        let timezone = timezone.map(str::to_string).unwrap_or_else(|| self.locales.locale_for(product).timezone);
+        self.validate_timezone(&timezone).await?;
+        let now = self.now();
+        let mut tx = self.begin_timed(OperationClass::Analytics).await?;
 
-        let tickets = q.fetch_all(&self.pool)
-            .await
-            .map_err(|e| SupportError::Database(e))?;
+        let open_by_priority = sqlx::query_as::<_, PriorityOpenCount>(
+            r#"
+            SELECT p.priority, COUNT(st.id)::BIGINT as open_tickets
+            FROM UNNEST(enum_range(NULL::ticket_priority)) AS p(priority)
+            LEFT JOIN support_tickets st
+                ON st.product = $1 AND st.priority = p.priority
+               AND st.deleted_at IS NULL AND NOT st.test_mode
+               AND st.status NOT IN ('RESOLVED', 'CLOSED')
+            GROUP BY p.priority
+            ORDER BY
+                CASE p.priority::TEXT
+                    WHEN 'URGENT' THEN 1
+                    WHEN 'HIGH' THEN 2
+                    WHEN 'MEDIUM' THEN 3
+                    WHEN 'LOW' THEN 4
+                END
+            "#,
+        )
+        .bind(product)
+        .fetch_all(&mut *tx)
+        .await?;
 
-        Ok(tickets)
-    }
+        #[derive(FromRow)]
+        struct PeriodCounts {
+            period_start: DateTime<Utc>,
+            period_end: DateTime<Utc>,
+            unassigned_backlog: i64,
+            new_tickets: i64,
+            resolved_tickets: i64,
+            sla_breaches: i64,
+            csat_responses: i64,
+            avg_csat_score: Option<f64>,
+        }
 
-    /// Add message to ticket
-    pub async fn add_message(&self, author_id: Uuid, input: &AddTicketMessageInput) -> Result<TicketMessage> {
-        let message = sqlx::query_as::<_, TicketMessage>(
+        let counts = sqlx::query_as::<_, PeriodCounts>(
             r#"
-            INSERT INTO ticket_messages (ticket_id, author_id, is_internal, content)
-            VALUES ($1, $2, $3, $4)
-            RETURNING *
+            WITH period AS (
+                SELECT
+                    (DATE_TRUNC('day', $2 AT TIME ZONE $3) - make_interval(days => $4)) AT TIME ZONE $3 as period_start,
+                    DATE_TRUNC('day', $2 AT TIME ZONE $3) AT TIME ZONE $3 as period_end
+            )
+            SELECT
+                p.period_start,
+                p.period_end,
+                COUNT(st.id) FILTER (
+                    WHERE st.status NOT IN ('RESOLVED', 'CLOSED') AND st.assigned_to IS NULL
+                )::BIGINT as unassigned_backlog,
+                COUNT(st.id) FILTER (
+                    WHERE st.created_at >= p.period_start AND st.created_at < p.period_end
+                )::BIGINT as new_tickets,
+                COUNT(st.id) FILTER (
+                    WHERE st.resolved_at >= p.period_start AND st.resolved_at < p.period_end
+                )::BIGINT as resolved_tickets,
+                COUNT(st.id) FILTER (
+                    WHERE st.sla_breached_at >= p.period_start AND st.sla_breached_at < p.period_end
+                )::BIGINT as sla_breaches,
+                COUNT(st.id) FILTER (
+                    WHERE st.csat_score IS NOT NULL
+                      AND st.csat_submitted_at >= p.period_start AND st.csat_submitted_at < p.period_end
+                )::BIGINT as csat_responses,
+                AVG(st.csat_score) FILTER (
+                    WHERE st.csat_score IS NOT NULL
+                      AND st.csat_submitted_at >= p.period_start AND st.csat_submitted_at < p.period_end
+                )::FLOAT8 as avg_csat_score
+            FROM period p
+            LEFT JOIN support_tickets st
+                ON st.product = $1 AND st.deleted_at IS NULL AND NOT st.test_mode
+            GROUP BY p.period_start, p.period_end
             "#,
         )
-        .bind(&input.ticket_id)
-        .bind(author_id)
-        .bind(input.is_internal)
-        .bind(&input.content)
+        .bind(product)
+        .bind(now)
+        .bind(&timezone)
+        .bind(frequency.period_days())
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(SupportReport {
+            product: product.to_string(),
+            frequency,
+            timezone,
+            period_start: counts.period_start,
+            period_end: counts.period_end,
+            open_backlog: open_by_priority.iter().map(|c| c.open_tickets).sum(),
+            open_by_priority,
+            unassigned_backlog: counts.unassigned_backlog,
+            new_tickets: counts.new_tickets,
+            resolved_tickets: counts.resolved_tickets,
+            sla_breaches: counts.sla_breaches,
+            csat_responses: counts.csat_responses,
+            avg_csat_score: counts.avg_csat_score,
+            generated_at: now,
+        })
+    }
+
+    /// Generate the report of every active schedule that is due, across products
+    ///
+    /// Meant to run every few minutes from a scheduler. Each schedule is
+    /// claimed, reported, kept as a run and moved to its next slot in its own
+    /// transaction, so concurrent runners never report a schedule twice. A
+    /// schedule whose report fails is logged and moved to its next slot
+    /// without a run, so it doesn't hold up the others.
+    /// Returns the runs for the host to deliver.
+    pub async fn run_due_reports(&self) -> Result<Vec<ReportRun>> {
+        self.ensure_writable()?;
+        let now = self.now();
+        let mut runs = Vec::new();
+
+        loop {
+            let mut tx = self.pool.begin().await?;
+            let schedule = sqlx::query_as::<_, ReportSchedule>(
+                r#"
+                SELECT * FROM report_schedules
+                WHERE active = TRUE AND next_run_at <= $1
+                ORDER BY next_run_at ASC, id ASC
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+                "#,
+            )
+            .bind(now)
+            .fetch_optional(&mut *tx)
+            .await?;
+            let Some(schedule) = schedule else {
+                break;
+            };
+
+            let generated = async {
+                let report = self.generate_report(&schedule.product, schedule.frequency, Some(&schedule.timezone)).await?;
+                let breach_digest_csv = if schedule.include_breach_digest {
+                    let digest = self
+                        .sla_breach_digest_for_period(&schedule.product, &schedule.timezone, report.period_start, report.period_end)
+                        .await?;
+                    let mut content = Vec::new();
+                    digest.write_csv(&mut content, ExportFormat::Csv)?;
+                    Some(String::from_utf8(content).map_err(|e| SupportError::Internal(e.to_string()))?)
+                } else {
+                    None
+                };
+                Ok::<_, SupportError>((report, breach_digest_csv))
+            }
+            .await;
+            let (report, breach_digest_csv) = match generated {
+                Ok(generated) => generated,
+                Err(e) => {
+                    tracing::warn!("Report '{}' for {} failed: {}", schedule.name, schedule.product, e);
+                    sqlx::query(
+                        r#"
+                        UPDATE report_schedules
+                        SET next_run_at = support_report_next_run($2, timezone, frequency, day_of_week, hour)
+                        WHERE id = $1
+                        "#,
+                    )
+                    .bind(schedule.id)
+                    .bind(now)
+                    .execute(&mut *tx)
+                    .await?;
+                    tx.commit().await?;
+                    continue;
+                }
+            };
+            let run_id: Uuid = sqlx::query_scalar(
+                r#"
+                INSERT INTO report_runs (schedule_id, product, recipients, report, created_at, breach_digest_csv)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                RETURNING id
+                "#,
+            )
+            .bind(schedule.id)
+            .bind(&schedule.product)
+            .bind(&schedule.recipients)
+            .bind(Json(&report))
+            .bind(now)
+            .bind(&breach_digest_csv)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                r#"
+                UPDATE report_schedules
+                SET last_run_at = $2,
+                    next_run_at = support_report_next_run($2, timezone, frequency, day_of_week, hour)
+                WHERE id = $1
+                "#,
+            )
+            .bind(schedule.id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+
+            tracing::info!("Generated {} report '{}' for {}", schedule.frequency.as_str(), schedule.name, schedule.product);
+            runs.push(ReportRun {
+                id: run_id,
+                schedule_id: schedule.id,
+                schedule_name: schedule.name,
+                recipients: schedule.recipients,
+                report,
+                breach_digest_csv,
+                created_at: now,
+            });
+        }
+
+        Ok(runs)
+    }
+
+    /// Every SLA deadline the product's tickets missed in the ISO week containing `week`
+    ///
+    /// The week runs Monday to Monday in `timezone`, the product's by default.
+    /// See [`reports`](crate::reports) for the CSV layout.
+    pub async fn sla_breach_digest(
+        &self,
+        product: &str,
+        week: NaiveDate,
+        timezone: Option<&str>,
+    ) -> Result<SlaBreachDigest> {
+        let timezone = timezone.map(str::to_string).unwrap_or_else(|| self.locales.locale_for(product).timezone);
+        self.validate_timezone(&timezone).await?;
+        let (period_start, period_end): (DateTime<Utc>, DateTime<Utc>) = sqlx::query_as(
+            "SELECT $1::DATE::TIMESTAMP AT TIME ZONE $2, ($1::DATE + 7)::TIMESTAMP AT TIME ZONE $2",
+        )
+        .bind(iso_week_start(week))
+        .bind(&timezone)
         .fetch_one(&self.pool)
-        .await
-        .map_err(|e| SupportError::Database(e))?;
+        .await?;
 
-        Ok(message)
+        self.sla_breach_digest_for_period(product, &timezone, period_start, period_end).await
     }
 
-    /// Get messages for a ticket
-    pub async fn get_messages(&self, ticket_id: Uuid) -> Result<Vec<TicketMessage>> {
-        let messages = sqlx::query_as::<_, TicketMessage>(
-            "SELECT * FROM ticket_messages WHERE ticket_id = $1 ORDER BY created_at ASC"
+    /// Missed deadlines of the product's tickets whose SLA breached in the period
+    ///
+    /// A ticket can miss both its first-response and resolution deadlines.
+    /// Overruns of tickets still unanswered or unresolved are counted up to now.
+    async fn sla_breach_digest_for_period(
+        &self,
+        product: &str,
+        timezone: &str,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> Result<SlaBreachDigest> {
+        let mut tx = self.begin_timed(OperationClass::Analytics).await?;
+        let breaches = sqlx::query_as::<_, SlaBreach>(
+            r#"
+            WITH breached AS (
+                SELECT st.*, a.display_name as assigned_agent
+                FROM support_tickets st
+                LEFT JOIN support_agents a ON a.product = st.product AND a.user_id = st.assigned_to
+                WHERE st.product = $1
+                  AND st.sla_breached_at >= $2 AND st.sla_breached_at < $3
+                  AND st.deleted_at IS NULL
+                  AND NOT st.test_mode
+            ),
+            overruns AS (
+                SELECT b.*, 'FIRST_RESPONSE' as breach_type,
+                    EXTRACT(EPOCH FROM (COALESCE(b.first_response_at, $4) - b.first_response_due_at)) as seconds_over
+                FROM breached b
+                WHERE b.first_response_due_at IS NOT NULL
+                UNION ALL
+                SELECT b.*, 'RESOLUTION' as breach_type,
+                    EXTRACT(EPOCH FROM (COALESCE(b.resolved_at, $4) - b.sla_due_at)) as seconds_over
+                FROM breached b
+                WHERE b.sla_due_at IS NOT NULL
+            )
+            SELECT
+                id as ticket_id,
+                ticket_ref,
+                customer_id,
+                priority,
+                breach_type,
+                CEIL(seconds_over / 60)::BIGINT as minutes_over,
+                assigned_to,
+                assigned_agent
+            FROM overruns
+            WHERE seconds_over > 0
+            ORDER BY minutes_over DESC, ticket_ref ASC, breach_type ASC
+            "#,
         )
-        .bind(ticket_id)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|e| SupportError::Database(e))?;
+        .bind(product)
+        .bind(period_start)
+        .bind(period_end)
+        .bind(self.now())
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
 
-        Ok(messages)
+        Ok(SlaBreachDigest::new(product.to_string(), timezone.to_string(), period_start, period_end, breaches))
     }
 
-    /// Get dashboard metrics for support analytics
-    pub async fn get_dashboard_metrics(
+    /// Generate an SLA breach digest CSV for the ISO week containing `week` and keep it for download
+    pub async fn generate_sla_breach_digest_export(
         &self,
         product: &str,
-        period_start: DateTime<Utc>,
-        period_end: DateTime<Utc>,
-    ) -> Result<CrmCoreSupportDashboardMetrics> {
-        // Overview metrics
-        let overview = self.get_overview_metrics(product, period_start, period_end).await?;
-
-        // Ticket counts by status
-        let ticket_by_status = self.get_status_counts(product, period_start, period_end).await?;
+        week: NaiveDate,
+        timezone: Option<&str>,
+        format: ExportFormat,
+        created_by: Option<Uuid>,
+    ) -> Result<SupportExport> {
+        self.ensure_writable()?;
+        let digest = self.sla_breach_digest(product, week, timezone).await?;
+        let mut content = Vec::new();
+        let rows = digest.write_csv(&mut content, format)?;
+        self.store_export(product, ExportKind::SlaBreachDigest, format, content, rows, created_by).await
+    }
 
-        // Ticket counts by priority
-        let ticket_by_priority = self.get_priority_counts(product, period_start, period_end).await?;
+    /// A schedule's latest runs, newest first
+    pub async fn get_report_runs(&self, schedule_id: Uuid, limit: i64) -> Result<Vec<ReportRun>> {
+        #[derive(FromRow)]
+        struct ReportRunRow {
+            id: Uuid,
+            schedule_id: Uuid,
+            schedule_name: String,
+            recipients: Vec<String>,
+            report: Json<SupportReport>,
+            breach_digest_csv: Option<String>,
+            created_at: DateTime<Utc>,
+        }
 
-        // SLA metrics
-        let sla_metrics = self.get_sla_metrics(product, period_start, period_end).await?;
+        let rows = sqlx::query_as::<_, ReportRunRow>(
+            r#"
+            SELECT r.id, r.schedule_id, s.name as schedule_name, r.recipients, r.report, r.breach_digest_csv, r.created_at
+            FROM report_runs r
+            JOIN report_schedules s ON s.id = r.schedule_id
+            WHERE r.schedule_id = $1
+            ORDER BY r.created_at DESC, r.id DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(schedule_id)
+        .bind(limit.clamp(1, MAX_REPORT_RUNS))
+        .fetch_all(&self.pool)
+        .await?;
 
-        // Response metrics
-        let response_metrics = self.get_response_metrics(product, period_start, period_end).await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| ReportRun {
+                id: row.id,
+                schedule_id: row.schedule_id,
+                schedule_name: row.schedule_name,
+                recipients: row.recipients,
+                report: row.report.0,
+                breach_digest_csv: row.breach_digest_csv,
+                created_at: row.created_at,
+            })
+            .collect())
+    }
 
-        // Top performing agents
-        let top_agents = self.get_top_agents(product, period_start, period_end).await?;
+    /// Up to `limit` rows of a warehouse extract changed after `after`, oldest change first
+    ///
+    /// Without a watermark the extract starts from the beginning. Rows
+    /// changed within the settle window are left for a later run.
+    pub async fn extract_warehouse<T: WarehouseExtract>(
+        &self,
+        product: &str,
+        after: Option<&Watermark>,
+        limit: i64,
+    ) -> Result<WarehouseBatch<T>> {
+        let query = format!(
+            r#"
+            SELECT * FROM {view}
+            WHERE product = $1
+              AND changed_at <= $2
+              AND ($3::TIMESTAMPTZ IS NULL OR (changed_at, watermark_key) > ($3, $4))
+            ORDER BY changed_at, watermark_key
+            LIMIT $5
+            "#,
+            view = T::VIEW,
+        );
 
-        // Ticket trends (last 7 days)
-        let ticket_trends = self.get_ticket_trends(product, period_start, period_end).await?;
+        let mut tx = self.begin_timed(OperationClass::Analytics).await?;
+        let rows = sqlx::query_as::<_, T>(&query)
+            .bind(product)
+            .bind(self.now() - Duration::seconds(WAREHOUSE_SETTLE_SECONDS))
+            .bind(after.map(|w| w.changed_at))
+            .bind(after.map(|w| w.key.as_str()))
+            .bind(limit)
+            .fetch_all(&mut *tx)
+            .await?;
+        tx.commit().await?;
 
-        Ok(CrmCoreSupportDashboardMetrics {
-            overview,
-            ticket_by_status,
-            ticket_by_priority,
-            sla_metrics,
-            response_metrics,
-            top_agents,
-            ticket_trends,
-        })
+        let watermark = rows.last().map(T::watermark).or_else(|| after.cloned());
+        Ok(WarehouseBatch { rows, watermark })
     }
 
-    async fn get_overview_metrics(
+    /// Customers with the most tickets created in the period
+    ///
+    /// Ties are broken by open tickets. Handling cost is estimated from the
+    /// repository's `HandlingCosts` and reported in the product's currency.
+    pub async fn get_top_customers(
         &self,
         product: &str,
         period_start: DateTime<Utc>,
         period_end: DateTime<Utc>,
-    ) -> Result<CrmCoreSupportOverviewMetrics> {
-        let metrics = sqlx::query_as::<_, CrmCoreSupportOverviewMetrics>(
+        limit: i64,
+    ) -> Result<Vec<CrmCoreTopCustomer>> {
+        let mut tx = self.begin_timed(OperationClass::Analytics).await?;
+        let mut customers = sqlx::query_as::<_, CrmCoreTopCustomer>(
             r#"
             SELECT
-                COUNT(*) FILTER (WHERE status NOT IN ('CLOSED', 'RESOLVED')) as total_active_tickets,
-                COUNT(*) FILTER (WHERE created_at >= NOW() - INTERVAL '1 day') as new_tickets_today,
-                COUNT(*) FILTER (WHERE resolved_at >= NOW() - INTERVAL '1 day') as resolved_tickets_today,
-                AVG(EXTRACT(EPOCH FROM (first_response_at - created_at)) / 60) FILTER (WHERE first_response_at IS NOT NULL) as avg_first_response_time_minutes,
-                AVG(EXTRACT(EPOCH FROM (resolved_at - created_at)) / 3600) FILTER (WHERE resolved_at IS NOT NULL) as avg_resolution_time_hours,
-                (COUNT(*) FILTER (WHERE resolved_at IS NOT NULL AND first_response_at IS NOT NULL
-                    AND resolved_at - first_response_at < INTERVAL '1 hour')::FLOAT /
-                NULLIF(COUNT(*) FILTER (WHERE resolved_at IS NOT NULL), 0)::FLOAT * 100) as first_contact_resolution_rate,
-                (COUNT(*) FILTER (WHERE sla_breach = FALSE)::FLOAT /
-                NULLIF(COUNT(*), 0)::FLOAT * 100) as sla_compliance_rate,
-                COUNT(*) FILTER (WHERE sla_breach = TRUE) as sla_breach_count,
-                AVG(csat_score::FLOAT) FILTER (WHERE csat_score IS NOT NULL) as avg_csat_score
-            FROM support_tickets
-            WHERE product = $1
-              AND deleted_at IS NULL
-              AND created_at BETWEEN $2 AND $3
+                st.customer_id,
+                COUNT(*)::BIGINT as ticket_count,
+                COUNT(*) FILTER (WHERE st.status NOT IN ('CLOSED', 'RESOLVED'))::BIGINT as open_tickets,
+                AVG(st.csat_score::FLOAT) FILTER (WHERE st.csat_score IS NOT NULL) as avg_csat_score,
+                COALESCE(SUM(m.agent_messages), 0)::BIGINT as agent_messages
+            FROM support_tickets st
+            LEFT JOIN LATERAL (
+                SELECT COUNT(*) as agent_messages
+                FROM ticket_messages
+                WHERE ticket_id = st.id AND author_id <> st.customer_id
+            ) m ON TRUE
+            WHERE st.product = $1
+              AND st.deleted_at IS NULL
+              AND NOT st.test_mode
+              AND st.created_at BETWEEN $2 AND $3
+            GROUP BY st.customer_id
+            ORDER BY ticket_count DESC, open_tickets DESC, st.customer_id
+            LIMIT $4
             "#,
         )
         .bind(product)
         .bind(period_start)
         .bind(period_end)
-        .fetch_one(&self.pool)
-        .await
-        .map_err(|e| SupportError::Database(e))?;
+        .bind(limit)
+        .fetch_all(&mut *tx)
+        .await?;
 
-        Ok(metrics)
+        tx.commit().await?;
+
+        let cost = self.handling_costs.cost_for(product);
+        let currency_code = self.locales.locale_for(product).currency_code;
+        for customer in &mut customers {
+            customer.estimated_handling_cost = cost.estimate(customer.ticket_count, customer.agent_messages);
+            customer.currency_code = currency_code.clone();
+        }
+
+        Ok(customers)
     }
 
-    async fn get_status_counts(
+    /// One agent's tickets, messages and CSAT in `[period_start, period_end)`, for performance reviews
+    ///
+    /// Handled tickets are those the agent wrote on or resolved in the
+    /// period. Test and deleted tickets are left out, and so are messages
+    /// above `visibility`, as for [`Self::get_messages`]. QA reviews and
+    /// time logs are not recorded here and are not included.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn export_agent_activity(
         &self,
         product: &str,
+        agent_id: Uuid,
         period_start: DateTime<Utc>,
         period_end: DateTime<Utc>,
-    ) -> Result<Vec<CrmCoreTicketStatusCount>> {
-        let counts = sqlx::query_as::<_, CrmCoreTicketStatusCount>(
+        visibility: MessageVisibility,
+        anonymization: ActivityAnonymization,
+    ) -> Result<AgentActivityExport> {
+        if period_end <= period_start {
+            return Err(SupportError::Validation("Period end must be after its start".to_string()));
+        }
+
+        let mut tx = self.begin_timed(OperationClass::Analytics).await?;
+        let tickets = sqlx::query_as::<_, HandledTicket>(
             r#"
             SELECT
-                status::TEXT as status,
-                COUNT(*)::BIGINT as count
-            FROM support_tickets
-            WHERE product = $1
-              AND deleted_at IS NULL
-              AND created_at BETWEEN $2 AND $3
-            GROUP BY status
-            ORDER BY count DESC
+                t.id as ticket_id, t.ticket_ref, t.customer_id, t.subject, t.status, t.priority, t.channel,
+                t.created_at, t.first_response_at, t.resolved_at, t.sla_breach,
+                COALESCE(t.resolving_agent_id = $2 AND t.resolved_at >= $3 AND t.resolved_at < $4, FALSE) as resolved_by_agent,
+                COALESCE(m.messages, 0) as messages_authored
+            FROM support_tickets t
+            LEFT JOIN (
+                SELECT ticket_id, COUNT(*)::BIGINT as messages
+                FROM ticket_messages
+                WHERE author_id = $2 AND created_at >= $3 AND created_at < $4 AND visibility = ANY($5)
+                GROUP BY ticket_id
+            ) m ON m.ticket_id = t.id
+            WHERE t.product = $1 AND t.deleted_at IS NULL AND NOT t.test_mode
+              AND (m.ticket_id IS NOT NULL
+                   OR (t.resolving_agent_id = $2 AND t.resolved_at >= $3 AND t.resolved_at < $4))
+            ORDER BY t.created_at ASC, t.id ASC
             "#,
         )
         .bind(product)
+        .bind(agent_id)
         .bind(period_start)
         .bind(period_end)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|e| SupportError::Database(e))?;
+        .bind(MessageVisibility::up_to(visibility))
+        .fetch_all(&mut *tx)
+        .await?;
 
-        Ok(counts)
+        let messages = sqlx::query_as::<_, AuthoredMessage>(
+            r#"
+            SELECT m.id as message_id, m.ticket_id, m.is_internal, m.content, m.created_at
+            FROM ticket_messages m
+            JOIN support_tickets t ON t.id = m.ticket_id
+            WHERE t.product = $1 AND t.deleted_at IS NULL AND NOT t.test_mode
+              AND m.author_id = $2 AND m.created_at >= $3 AND m.created_at < $4
+              AND m.visibility = ANY($5)
+            ORDER BY m.created_at ASC, m.id ASC
+            "#,
+        )
+        .bind(product)
+        .bind(agent_id)
+        .bind(period_start)
+        .bind(period_end)
+        .bind(MessageVisibility::up_to(visibility))
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let csat = sqlx::query_as::<_, ReceivedCsat>(
+            r#"
+            SELECT s.id as survey_id, s.ticket_id, s.customer_id, s.score, s.nps_score, s.comment,
+                   s.channel, s.source, s.responded_at
+            FROM csat_surveys s
+            JOIN support_tickets t ON t.id = s.ticket_id
+            WHERE s.product = $1 AND t.deleted_at IS NULL AND NOT t.test_mode
+              AND s.agent_id = $2 AND s.responded_at >= $3 AND s.responded_at < $4
+            ORDER BY s.responded_at ASC, s.id ASC
+            "#,
+        )
+        .bind(product)
+        .bind(agent_id)
+        .bind(period_start)
+        .bind(period_end)
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        let mut export = AgentActivityExport {
+            product: product.to_string(),
+            agent_id,
+            period_start,
+            period_end,
+            generated_at: self.now(),
+            customers_pseudonymized: false,
+            content_redacted: false,
+            summary: AgentActivitySummary::of(&tickets, &messages, &csat),
+            tickets,
+            messages,
+            csat,
+        };
+        export.anonymize(anonymization);
+
+        Ok(export)
     }
 
-    async fn get_priority_counts(
+    /// Accepted vs rejected customer-facing intake in the period, by telemetry signal
+    pub async fn get_intake_telemetry_report(
         &self,
         product: &str,
         period_start: DateTime<Utc>,
         period_end: DateTime<Utc>,
-    ) -> Result<Vec<CrmCoreTicketPriorityCount>> {
-        let counts = sqlx::query_as::<_, CrmCoreTicketPriorityCount>(
+    ) -> Result<CrmCoreIntakeTelemetryReport> {
+        let mut tx = self.begin_timed(OperationClass::Analytics).await?;
+        let (total_submissions, accepted, median_form_fill_ms_accepted, median_form_fill_ms_rejected): (
+            i64,
+            i64,
+            Option<f64>,
+            Option<f64>,
+        ) = sqlx::query_as(
             r#"
             SELECT
-                priority::TEXT as priority,
-                COUNT(*)::BIGINT as count
-            FROM support_tickets
-            WHERE product = $1
-              AND deleted_at IS NULL
-              AND created_at BETWEEN $2 AND $3
-            GROUP BY priority
-            ORDER BY
-                CASE priority::TEXT
-                    WHEN 'URGENT' THEN 1
-                    WHEN 'HIGH' THEN 2
-                    WHEN 'MEDIUM' THEN 3
-                    WHEN 'LOW' THEN 4
-                END
+                COUNT(*)::BIGINT,
+                COUNT(*) FILTER (WHERE accepted)::BIGINT,
+                PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY form_fill_ms) FILTER (WHERE accepted),
+                PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY form_fill_ms) FILTER (WHERE NOT accepted)
+            FROM intake_submissions
+            WHERE product = $1 AND created_at BETWEEN $2 AND $3
             "#,
         )
         .bind(product)
         .bind(period_start)
         .bind(period_end)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|e| SupportError::Database(e))?;
+        .fetch_one(&mut *tx)
+        .await?;
 
-        Ok(counts)
+        let by_signal = sqlx::query_as::<_, CrmCoreIntakeSignalCount>(
+            r#"
+            SELECT
+                s.signal,
+                COUNT(*) FILTER (WHERE i.accepted)::BIGINT as accepted,
+                COUNT(*) FILTER (WHERE NOT i.accepted)::BIGINT as rejected
+            FROM intake_submissions i
+            CROSS JOIN LATERAL UNNEST(
+                CASE WHEN cardinality(i.signals) = 0 THEN ARRAY['NONE'] ELSE i.signals END
+            ) AS s(signal)
+            WHERE i.product = $1 AND i.created_at BETWEEN $2 AND $3
+            GROUP BY s.signal
+            ORDER BY COUNT(*) DESC, s.signal
+            "#,
+        )
+        .bind(product)
+        .bind(period_start)
+        .bind(period_end)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        let rejected = total_submissions - accepted;
+        let rejection_rate = if total_submissions > 0 {
+            rejected as f64 / total_submissions as f64 * 100.0
+        } else {
+            0.0
+        };
+
+        Ok(CrmCoreIntakeTelemetryReport {
+            total_submissions,
+            accepted,
+            rejected,
+            rejection_rate,
+            median_form_fill_ms_accepted,
+            median_form_fill_ms_rejected,
+            by_signal,
+        })
     }
 
-    async fn get_sla_metrics(
+    /// Tickets filed in the period by app release, busiest first
+    ///
+    /// Only tickets whose client reported an app version are counted; test
+    /// tickets are left out. Pass `platform` to look at one platform's releases.
+    pub async fn get_tickets_by_app_version(
         &self,
         product: &str,
         period_start: DateTime<Utc>,
         period_end: DateTime<Utc>,
-    ) -> Result<CrmCoreSlaMetrics> {
-        let metrics = sqlx::query_as::<_, CrmCoreSlaMetrics>(
+        platform: Option<ClientPlatform>,
+    ) -> Result<Vec<CrmCoreAppVersionMetrics>> {
+        let mut tx = self.begin_timed(OperationClass::Analytics).await?;
+        let metrics = sqlx::query_as::<_, CrmCoreAppVersionMetrics>(
             r#"
+            WITH versioned AS (
+                SELECT
+                    metadata->'client'->>'app_version' as app_version,
+                    metadata->'client'->>'platform' as platform,
+                    status,
+                    created_at
+                FROM support_tickets
+                WHERE product = $1
+                  AND metadata ? 'client'
+                  AND metadata->'client'->>'app_version' IS NOT NULL
+                  AND created_at BETWEEN $2 AND $3
+                  AND deleted_at IS NULL
+                  AND NOT test_mode
+                  AND ($4::TEXT IS NULL OR metadata->'client'->>'platform' = $4)
+            )
             SELECT
+                app_version,
+                platform,
                 COUNT(*)::BIGINT as total_tickets,
-                COUNT(*) FILTER (WHERE sla_breach = FALSE)::BIGINT as tickets_meeting_sla,
-                COUNT(*) FILTER (WHERE sla_breach = TRUE)::BIGINT as tickets_breaching_sla,
-                COALESCE(
-                    COUNT(*) FILTER (WHERE sla_breach = FALSE)::FLOAT /
-                    NULLIF(COUNT(*), 0)::FLOAT * 100,
-                    0.0
-                ) as compliance_rate,
-                AVG(EXTRACT(EPOCH FROM (first_response_at - created_at)) / 60) FILTER (WHERE first_response_at IS NOT NULL) as avg_first_response_minutes,
-                AVG(EXTRACT(EPOCH FROM (resolved_at - created_at)) / 3600) FILTER (WHERE resolved_at IS NOT NULL) as avg_resolution_hours
-            FROM support_tickets
-            WHERE product = $1
-              AND deleted_at IS NULL
-              AND created_at BETWEEN $2 AND $3
+                COUNT(*) FILTER (WHERE status NOT IN ('RESOLVED', 'CLOSED'))::BIGINT as open_tickets,
+                (COUNT(*) * 100.0 / SUM(COUNT(*)) OVER ())::FLOAT8 as volume_percentage,
+                MIN(created_at) as first_ticket_at,
+                MAX(created_at) as last_ticket_at
+            FROM versioned
+            GROUP BY app_version, platform
+            ORDER BY total_tickets DESC, first_ticket_at DESC
             "#,
         )
         .bind(product)
         .bind(period_start)
         .bind(period_end)
-        .fetch_one(&self.pool)
-        .await
-        .map_err(|e| SupportError::Database(e))?;
+        .bind(platform)
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
 
         Ok(metrics)
     }
 
-    async fn get_response_metrics(
+    /// Write the product's tickets matching `filter` as CSV, newest first
+    ///
+    /// Tickets are streamed as by [`Self::stream_tickets`]. Returns the number
+    /// of data rows written.
+    pub async fn export_tickets_csv<W: std::io::Write>(
+        &self,
+        product: &str,
+        filter: &TicketFilter,
+        format: ExportFormat,
+        writer: W,
+    ) -> Result<i64> {
+        let mut csv = csv_writer(writer, format)?;
+        csv.write_record(TICKET_EXPORT_COLUMNS).map_err(csv_error)?;
+
+        let mut rows = 0;
+        let tickets = self.stream_tickets(product, filter, EXPORT_PAGE_SIZE);
+        futures_util::pin_mut!(tickets);
+        while let Some(ticket) = tickets.try_next().await? {
+            csv.write_record(ticket_record(&ticket)).map_err(csv_error)?;
+            rows += 1;
+        }
+        csv.flush().map_err(|e| csv_error(e.into()))?;
+
+        Ok(rows)
+    }
+
+    /// Write the product's dashboard metrics for the period as long-form CSV
+    ///
+    /// See [`crate::export`] for the layout. Returns the number of data rows
+    /// written. With `organization_id` only that B2B account's tickets are counted.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn export_dashboard_metrics_csv<W: std::io::Write>(
         &self,
         product: &str,
+        organization_id: Option<Uuid>,
         period_start: DateTime<Utc>,
         period_end: DateTime<Utc>,
-    ) -> Result<CrmCoreResponseMetrics> {
-        let metrics = sqlx::query_as::<_, CrmCoreResponseMetrics>(
+        timezone: Option<&str>,
+        format: ExportFormat,
+        writer: W,
+    ) -> Result<i64> {
+        let metrics = self.dashboard_metrics(product, organization_id, period_start, period_end, timezone).await?;
+
+        let mut csv = csv_writer(writer, format)?;
+        csv.write_record(DASHBOARD_EXPORT_COLUMNS).map_err(csv_error)?;
+        let records = dashboard_records(&metrics);
+        for record in &records {
+            csv.write_record(record).map_err(csv_error)?;
+        }
+        csv.flush().map_err(|e| csv_error(e.into()))?;
+
+        Ok(records.len() as i64)
+    }
+
+    /// Generate a ticket export and keep it for download
+    ///
+    /// The file is built in memory and stored whole; for very large exports
+    /// write to a file with [`Self::export_tickets_csv`].
+    pub async fn generate_ticket_export(
+        &self,
+        product: &str,
+        filter: &TicketFilter,
+        format: ExportFormat,
+        created_by: Option<Uuid>,
+    ) -> Result<SupportExport> {
+        self.ensure_writable()?;
+        let mut content = Vec::new();
+        let rows = self.export_tickets_csv(product, filter, format, &mut content).await?;
+        self.store_export(product, ExportKind::Tickets, format, content, rows, created_by).await
+    }
+
+    /// Generate a dashboard metrics export and keep it for download
+    #[allow(clippy::too_many_arguments)]
+    pub async fn generate_dashboard_export(
+        &self,
+        product: &str,
+        organization_id: Option<Uuid>,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+        timezone: Option<&str>,
+        format: ExportFormat,
+        created_by: Option<Uuid>,
+    ) -> Result<SupportExport> {
+        self.ensure_writable()?;
+        let mut content = Vec::new();
+        let rows = self
+            .export_dashboard_metrics_csv(product, organization_id, period_start, period_end, timezone, format, &mut content)
+            .await?;
+        self.store_export(product, ExportKind::DashboardMetrics, format, content, rows, created_by).await
+    }
+
+    /// The product's core KPIs per `interval` bucket from `from` to `to`
+    ///
+    /// Buckets start in the product's timezone; see [`crate::metrics_history`]
+    /// for the KPIs. Fails with `Validation` when `from` is not before `to` or
+    /// the range needs more than [`MAX_METRICS_HISTORY_POINTS`] buckets. With
+    /// `organization_id` only that B2B account's tickets are counted.
+    pub async fn export_metrics_history(
+        &self,
+        product: &str,
+        organization_id: Option<Uuid>,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        interval: MetricsInterval,
+    ) -> Result<MetricsHistory> {
+        if from >= to {
+            return Err(SupportError::Validation("Metrics history range must end after it starts".to_string()));
+        }
+        let days = (to - from).num_days();
+        let buckets = match interval {
+            MetricsInterval::Day => days,
+            MetricsInterval::Week => days / 7,
+            MetricsInterval::Month => days / 31,
+        } + 1;
+        if buckets > MAX_METRICS_HISTORY_POINTS {
+            return Err(SupportError::Validation(format!(
+                "Metrics history is limited to {} buckets; use a shorter range or a longer interval",
+                MAX_METRICS_HISTORY_POINTS
+            )));
+        }
+        let timezone = self.locales.locale_for(product).timezone;
+        self.validate_timezone(&timezone).await?;
+
+        let mut tx = self.begin_timed(OperationClass::Analytics).await?;
+        let points = sqlx::query_as::<_, MetricsHistoryPoint>(
             r#"
+            WITH buckets AS (
+                SELECT
+                    GREATEST(local_start AT TIME ZONE $5, $2) as bucket_start,
+                    LEAST((local_start + ('1 ' || $4)::INTERVAL) AT TIME ZONE $5, $3) as bucket_end
+                FROM generate_series(
+                    date_trunc($4, $2 AT TIME ZONE $5),
+                    $3 AT TIME ZONE $5,
+                    ('1 ' || $4)::INTERVAL
+                ) as local_start
+            ),
+            tickets AS (
+                SELECT * FROM support_tickets
+                WHERE product = $1 AND deleted_at IS NULL AND NOT test_mode
+                  AND ($7::UUID IS NULL OR organization_id = $7)
+            )
             SELECT
-                AVG(EXTRACT(EPOCH FROM (first_response_at - created_at)) / 60) as avg_first_response_minutes,
-                PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY EXTRACT(EPOCH FROM (first_response_at - created_at)) / 60) as median_first_response_minutes,
-                AVG(EXTRACT(EPOCH FROM (first_response_at - created_at)) / 60) as avg_response_minutes,
-                PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY EXTRACT(EPOCH FROM (first_response_at - created_at)) / 60) as median_response_minutes,
-                AVG(EXTRACT(EPOCH FROM (resolved_at - created_at)) / 3600) as avg_resolution_hours,
-                PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY EXTRACT(EPOCH FROM (resolved_at - created_at)) / 3600) as median_resolution_hours
-            FROM support_tickets
-            WHERE product = $1
-              AND deleted_at IS NULL
-              AND created_at BETWEEN $2 AND $3
+                b.bucket_start,
+                b.bucket_end,
+                created.new_tickets,
+                resolved.resolved_tickets,
+                created.sla_compliance_rate,
+                created.sla_breach_count,
+                csat.csat_responses,
+                csat.avg_csat_score,
+                created.first_response_p50_minutes,
+                created.first_response_p90_minutes,
+                created.resolution_p50_hours,
+                created.resolution_p90_hours
+            FROM buckets b
+            CROSS JOIN LATERAL (
+                SELECT
+                    COUNT(*)::BIGINT as new_tickets,
+                    COUNT(*) FILTER (WHERE NOT sla_breach)::FLOAT / NULLIF(COUNT(*), 0)::FLOAT * 100 as sla_compliance_rate,
+                    COUNT(*) FILTER (WHERE sla_breach)::BIGINT as sla_breach_count,
+                    PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY EXTRACT(EPOCH FROM (first_response_at - created_at)) / 60) as first_response_p50_minutes,
+                    PERCENTILE_CONT(0.9) WITHIN GROUP (ORDER BY EXTRACT(EPOCH FROM (first_response_at - created_at)) / 60) as first_response_p90_minutes,
+                    PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY (EXTRACT(EPOCH FROM (resolved_at - created_at)) - CASE WHEN $6 THEN customer_wait_seconds ELSE 0 END) / 3600) as resolution_p50_hours,
+                    PERCENTILE_CONT(0.9) WITHIN GROUP (ORDER BY (EXTRACT(EPOCH FROM (resolved_at - created_at)) - CASE WHEN $6 THEN customer_wait_seconds ELSE 0 END) / 3600) as resolution_p90_hours
+                FROM tickets
+                WHERE created_at >= b.bucket_start AND created_at < b.bucket_end
+            ) created
+            CROSS JOIN LATERAL (
+                SELECT COUNT(*)::BIGINT as resolved_tickets
+                FROM tickets
+                WHERE resolved_at >= b.bucket_start AND resolved_at < b.bucket_end
+            ) resolved
+            CROSS JOIN LATERAL (
+                SELECT COUNT(*)::BIGINT as csat_responses, AVG(csat_score)::FLOAT as avg_csat_score
+                FROM tickets
+                WHERE csat_score IS NOT NULL AND csat_submitted_at >= b.bucket_start AND csat_submitted_at < b.bucket_end
+            ) csat
+            WHERE b.bucket_start < b.bucket_end
+            ORDER BY b.bucket_start
             "#,
         )
         .bind(product)
-        .bind(period_start)
-        .bind(period_end)
+        .bind(from)
+        .bind(to)
+        .bind(interval.as_sql())
+        .bind(&timezone)
+        .bind(self.sla_policy.pause_while_waiting_on_customer)
+        .bind(organization_id)
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(MetricsHistory { product: product.to_string(), interval, timezone, from, to, points })
+    }
+
+    /// Replay a period's ticket arrivals against a proposed staffing model
+    ///
+    /// `agents` work each shift of `shift_pattern`, in the product's timezone,
+    /// plus the shift's additional agents. See [`crate::staffing`] for the
+    /// queue model. The period may span at most
+    /// [`MAX_STAFFING_SIMULATION_DAYS`] days.
+    pub async fn simulate_staffing(
+        &self,
+        product: &str,
+        historical_period: MetricsPeriod,
+        agents: u32,
+        shift_pattern: &ShiftPattern,
+    ) -> Result<StaffingSimulation> {
+        let MetricsPeriod { start: from, end: to } = historical_period;
+        if from >= to {
+            return Err(SupportError::Validation("Staffing simulation period must end after it starts".to_string()));
+        }
+        if (to - from).num_days() > MAX_STAFFING_SIMULATION_DAYS {
+            return Err(SupportError::Validation(format!(
+                "Staffing simulation is limited to {} days of history",
+                MAX_STAFFING_SIMULATION_DAYS
+            )));
+        }
+        shift_pattern.validate(agents)?;
+        let timezone = self.locales.locale_for(product).timezone;
+        self.validate_timezone(&timezone).await?;
+
+        let policy = &self.sla_policy;
+        let (plans, grace_seconds): (Vec<String>, Vec<i64>) = policy
+            .plan_grace_windows
+            .iter()
+            .map(|(plan, grace)| (plan.clone(), grace.num_seconds()))
+            .unzip();
+
+        let mut tx = self.begin_timed(OperationClass::Analytics).await?;
+        let arrivals = sqlx::query_as::<_, SimulatedArrival>(&format!(
+            r#"
+            WITH graced AS (
+                SELECT
+                    st.id,
+                    st.product,
+                    st.created_at,
+                    st.priority,
+                    COALESCE(g.grace_seconds, $5) as grace_seconds,
+                    {SLA_TARGET_COLUMNS}
+                FROM support_tickets st
+                {SLA_GRACE_JOIN}
+                {SLA_TARGET_JOIN}
+                WHERE st.product = $6
+                  AND st.created_at >= $7 AND st.created_at < $8
+                  AND st.deleted_at IS NULL
+                  AND NOT st.test_mode
+            )
+            SELECT
+                created_at,
+                priority,
+                EXTRACT(ISODOW FROM created_at AT TIME ZONE $9)::INT as weekday,
+                support_product_business_deadline(product, created_at, first_response_seconds)
+                    + grace_seconds * INTERVAL '1 second' as first_response_due_at
+            FROM graced
+            ORDER BY created_at ASC, id ASC
+            "#,
+        ))
+        .bind(policy.first_response_target.map(|d| d.num_seconds()))
+        .bind(policy.resolution_target.map(|d| d.num_seconds()))
+        .bind(plans)
+        .bind(grace_seconds)
+        .bind(policy.default_grace.num_seconds())
+        .bind(product)
+        .bind(from)
+        .bind(to)
+        .bind(&timezone)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        // Shifts from the local day before the period, for overnight shifts
+        // running into it, until the queue has drained
+        let (days, starts, ends, staffed) = shift_pattern.day_shifts(agents);
+        let spans = sqlx::query_as::<_, ShiftSpan>(
+            r#"
+            SELECT
+                (d + make_interval(mins => s.starts_at_minute)) AT TIME ZONE $1 as starts_at,
+                (d + make_interval(
+                    days => CASE WHEN s.ends_at_minute <= s.starts_at_minute THEN 1 ELSE 0 END,
+                    mins => s.ends_at_minute
+                )) AT TIME ZONE $1 as ends_at,
+                s.agents
+            FROM generate_series(
+                (($2 AT TIME ZONE $1)::DATE - 1)::TIMESTAMP,
+                (($3 AT TIME ZONE $1)::DATE + $4)::TIMESTAMP,
+                INTERVAL '1 day'
+            ) as d
+            JOIN UNNEST($5::INT[], $6::INT[], $7::INT[], $8::BIGINT[]) as s(iso_day, starts_at_minute, ends_at_minute, agents)
+                ON EXTRACT(ISODOW FROM d)::INT = s.iso_day
+            WHERE s.agents > 0
+            ORDER BY starts_at ASC
+            "#,
+        )
+        .bind(&timezone)
+        .bind(from)
+        .bind(to)
+        .bind(STAFFING_DRAIN_DAYS)
+        .bind(days)
+        .bind(starts)
+        .bind(ends)
+        .bind(staffed)
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        let handle = Duration::minutes(i64::from(shift_pattern.handle_minutes));
+        let pickups = simulate_pickups(&arrivals, &spans, handle);
+        let (projection, by_weekday) = summarize(&arrivals, &pickups);
+
+        Ok(StaffingSimulation {
+            product: product.to_string(),
+            from,
+            to,
+            agents,
+            timezone,
+            projection,
+            by_weekday,
+            agent_utilization: agent_utilization(&spans, &pickups, handle, from, to),
+        })
+    }
+
+    /// Generate a metrics history CSV and keep it for download
+    #[allow(clippy::too_many_arguments)]
+    pub async fn generate_metrics_history_export(
+        &self,
+        product: &str,
+        organization_id: Option<Uuid>,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        interval: MetricsInterval,
+        format: ExportFormat,
+        created_by: Option<Uuid>,
+    ) -> Result<SupportExport> {
+        self.ensure_writable()?;
+        let history = self.export_metrics_history(product, organization_id, from, to, interval).await?;
+        let mut content = Vec::new();
+        let rows = history.write_csv(&mut content, format)?;
+        self.store_export(product, ExportKind::MetricsHistory, format, content, rows, created_by).await
+    }
+
+    async fn store_export(
+        &self,
+        product: &str,
+        kind: ExportKind,
+        format: ExportFormat,
+        content: Vec<u8>,
+        row_count: i64,
+        created_by: Option<Uuid>,
+    ) -> Result<SupportExport> {
+        let now = self.now();
+        let export = sqlx::query_as::<_, SupportExport>(
+            r#"
+            INSERT INTO support_exports (product, kind, format, filename, content, row_count, created_by, created_at, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id, product, kind, format, filename, row_count, created_by, created_at, expires_at
+            "#,
+        )
+        .bind(product)
+        .bind(kind)
+        .bind(format)
+        .bind(export_filename(product, kind, now))
+        .bind(content)
+        .bind(row_count)
+        .bind(created_by)
+        .bind(now)
+        .bind(now + Duration::hours(EXPORT_TTL_HOURS))
         .fetch_one(&self.pool)
-        .await
-        .map_err(|e| SupportError::Database(e))?;
+        .await?;
 
-        Ok(metrics)
+        tracing::info!("Generated {} export {} ({} rows)", product, export.filename, row_count);
+        Ok(export)
     }
 
-    async fn get_top_agents(
+    /// Content of an export that has not expired yet
+    ///
+    /// Fails with `ExportNotFound` for unknown and expired exports.
+    pub async fn get_export_file(&self, export_id: Uuid) -> Result<ExportFile> {
+        let (filename, content): (String, Vec<u8>) = sqlx::query_as(
+            "SELECT filename, content FROM support_exports WHERE id = $1 AND expires_at > $2",
+        )
+        .bind(export_id)
+        .bind(self.now())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(SupportError::ExportNotFound(export_id))?;
+
+        Ok(ExportFile { filename, content_type: EXPORT_CONTENT_TYPE, content })
+    }
+
+    /// Delete expired exports, returning how many were removed
+    pub async fn purge_expired_exports(&self) -> Result<u64> {
+        self.ensure_writable()?;
+        let result = sqlx::query("DELETE FROM support_exports WHERE expires_at <= $1")
+            .bind(self.now())
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Intake submissions collapsed into existing tickets in the period
+    pub async fn get_collapsed_submission_counts(
         &self,
         product: &str,
         period_start: DateTime<Utc>,
         period_end: DateTime<Utc>,
-    ) -> Result<Vec<CrmCoreAgentPerformance>> {
-        let agents = sqlx::query_as::<_, CrmCoreAgentPerformance>(
+    ) -> Result<CollapsedSubmissionCounts> {
+        let mut tx = self.begin_timed(OperationClass::Analytics).await?;
+        let (total, same_customer, error_signature, tickets): (i64, i64, i64, i64) = sqlx::query_as(
             r#"
             SELECT
-                assigned_to::TEXT as agent_id,
-                assigned_to::TEXT as agent_name,
-                COUNT(*)::BIGINT as tickets_assigned,
-                COUNT(*) FILTER (WHERE status = 'RESOLVED' OR status = 'CLOSED')::BIGINT as tickets_resolved,
-                AVG(EXTRACT(EPOCH FROM (first_response_at - created_at)) / 60) FILTER (WHERE first_response_at IS NOT NULL) as avg_first_response_minutes,
-                AVG(EXTRACT(EPOCH FROM (resolved_at - created_at)) / 3600) FILTER (WHERE resolved_at IS NOT NULL) as avg_resolution_hours,
-                AVG(csat_score::FLOAT) FILTER (WHERE csat_score IS NOT NULL) as csat_score
-            FROM support_tickets
-            WHERE product = $1
-              AND deleted_at IS NULL
-              AND assigned_to IS NOT NULL
-              AND created_at BETWEEN $2 AND $3
-            GROUP BY assigned_to
-            ORDER BY tickets_resolved DESC
-            LIMIT 10
+                COUNT(*)::BIGINT,
+                COUNT(*) FILTER (WHERE c.reason = $4)::BIGINT,
+                COUNT(*) FILTER (WHERE c.reason = $5)::BIGINT,
+                COUNT(DISTINCT c.ticket_id)::BIGINT
+            FROM collapsed_submissions c
+            JOIN support_tickets t ON t.id = c.ticket_id
+            WHERE c.product = $1
+              AND c.created_at BETWEEN $2 AND $3
+              AND t.deleted_at IS NULL
+              AND NOT t.test_mode
             "#,
         )
         .bind(product)
         .bind(period_start)
         .bind(period_end)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|e| SupportError::Database(e))?;
+        .bind(CollapseReason::SameCustomer.as_str())
+        .bind(CollapseReason::ErrorSignature.as_str())
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
 
-        Ok(agents)
+        Ok(CollapsedSubmissionCounts { total, same_customer, error_signature, tickets })
     }
 
-    async fn get_ticket_trends(
+    /// Status funnel for tickets created in the period
+    ///
+    /// Status history is reconstructed from the creation time (NEW) and the
+    /// status audit entries. Time in a status still occupied is measured up to
+    /// now, except for CLOSED, which has no exit.
+    pub async fn get_status_funnel(
         &self,
         product: &str,
-        _period_start: DateTime<Utc>,
+        period_start: DateTime<Utc>,
         period_end: DateTime<Utc>,
-    ) -> Result<Vec<CrmCoreTicketTrend>> {
-        // Get last 7 days of trends
-        let start = period_end - Duration::days(7);
+    ) -> Result<CrmCoreStatusFunnel> {
+        let mut tx = self.begin_timed(OperationClass::Analytics).await?;
+        let funnel = self.status_funnel(&mut tx, product, None, period_start, period_end).await?;
+        tx.commit().await?;
 
-        let trends = sqlx::query_as::<_, CrmCoreTicketTrend>(
+        Ok(funnel)
+    }
+
+    async fn status_funnel(
+        &self,
+        conn: &mut PgConnection,
+        product: &str,
+        organization_id: Option<Uuid>,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> Result<CrmCoreStatusFunnel> {
+        let stages = sqlx::query_as::<_, CrmCoreStatusFunnelStage>(&format!(
             r#"
-            WITH date_series AS (
-                SELECT generate_series($2::DATE, $3::DATE, '1 day'::INTERVAL)::DATE as date
+            {STATUS_SPANS_CTE}
+            SELECT
+                status,
+                COUNT(DISTINCT ticket_id)::BIGINT as tickets_entered,
+                COUNT(*) FILTER (WHERE next_status IS NULL)::BIGINT as tickets_remaining,
+                COALESCE(COUNT(DISTINCT ticket_id)::FLOAT / NULLIF((SELECT COUNT(*) FROM tickets), 0)::FLOAT * 100, 0.0) as reached_rate,
+                PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY EXTRACT(EPOCH FROM (COALESCE(left_at, $4) - entered_at)) / 60)
+                    FILTER (WHERE left_at IS NOT NULL OR status <> 'CLOSED') as median_minutes_in_status
+            FROM spans
+            GROUP BY status
+            ORDER BY
+                CASE status
+                    WHEN 'NEW' THEN 1
+                    WHEN 'IN_PROGRESS' THEN 2
+                    WHEN 'WAITING_ON_CUSTOMER' THEN 3
+                    WHEN 'RESOLVED' THEN 4
+                    WHEN 'CLOSED' THEN 5
+                END
+            "#
+        ))
+        .bind(product)
+        .bind(period_start)
+        .bind(period_end)
+        .bind(self.now())
+        .bind(organization_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let transitions = sqlx::query_as::<_, CrmCoreStatusTransition>(&format!(
+            r#"
+            {STATUS_SPANS_CTE},
+            grouped AS (
+                SELECT
+                    status as from_status,
+                    next_status as to_status,
+                    COUNT(*) as count,
+                    SUM(COUNT(*)) OVER (PARTITION BY status) as from_total
+                FROM spans
+                GROUP BY status, next_status
             )
             SELECT
-                ds.date::TEXT as date,
-                COALESCE(COUNT(*) FILTER (WHERE DATE(created_at) = ds.date), 0)::BIGINT as new_tickets,
-                COALESCE(COUNT(*) FILTER (WHERE DATE(resolved_at) = ds.date), 0)::BIGINT as resolved_tickets,
-                COALESCE(COUNT(*) FILTER (WHERE status NOT IN ('CLOSED', 'RESOLVED') AND DATE(created_at) <= ds.date), 0)::BIGINT as active_tickets
-            FROM date_series ds
-            LEFT JOIN support_tickets st ON st.product = $1 AND st.deleted_at IS NULL
-            GROUP BY ds.date
-            ORDER BY ds.date DESC
-            "#,
-        )
+                from_status,
+                to_status,
+                count::BIGINT as count,
+                (count::FLOAT / from_total::FLOAT * 100) as percentage
+            FROM grouped
+            WHERE to_status IS NOT NULL
+            ORDER BY from_status, count DESC
+            "#
+        ))
         .bind(product)
-        .bind(start)
+        .bind(period_start)
         .bind(period_end)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|e| SupportError::Database(e))?;
+        .bind(self.now())
+        .bind(organization_id)
+        .fetch_all(&mut *conn)
+        .await?;
 
-        Ok(trends)
+        Ok(CrmCoreStatusFunnel { stages, transitions })
     }
 }
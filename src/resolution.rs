@@ -0,0 +1,111 @@
+//! Resolution codes and knowledge mining
+//!
+//! Each product can keep a taxonomy of [`ResolutionCode`]s describing how its
+//! tickets get fixed (`PASSWORD_RESET`, `REFUND_ISSUED`, ...). Once a product
+//! has an active code, moving one of its tickets to RESOLVED requires a
+//! `resolution_code` from the taxonomy, given on the update or kept from an
+//! earlier resolution. Products without codes resolve as before.
+//!
+//! `get_resolutions_by_code` breaks a period's resolutions down by code, and
+//! `kb_article_candidates` surfaces (category, resolution code) pairs that keep
+//! recurring while no help article documents the code: fixes agents repeat by
+//! hand that the knowledge team could write up instead.
+//!
+//! ```rust
+//! use pleme_support::resolution::normalize_resolution_code;
+//!
+//! assert_eq!(normalize_resolution_code(" password_reset ").unwrap(), "PASSWORD_RESET");
+//! assert!(normalize_resolution_code("").is_err());
+//! assert!(normalize_resolution_code("refund issued").is_err());
+//! ```
+
+use async_graphql::{InputObject, SimpleObject};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::{Result, SupportError};
+
+/// Longest accepted resolution code
+pub const MAX_RESOLUTION_CODE_LENGTH: usize = 50;
+
+/// Resolutions a (category, code) pair needs before it is suggested for an article
+pub const DEFAULT_KB_CANDIDATE_MIN_TICKETS: i64 = 5;
+
+/// Ticket references listed per article candidate
+pub const KB_CANDIDATE_SAMPLE_TICKETS: i64 = 5;
+
+/// One way a product's tickets get resolved
+#[derive(Debug, Clone, Serialize, FromRow, SimpleObject)]
+pub struct ResolutionCode {
+    pub product: String,
+    pub code: String,
+    pub label: String,
+    pub description: Option<String>,
+    /// Help article documenting the fix
+    pub article_id: Option<Uuid>,
+    /// Retired codes stay on past tickets but cannot be chosen
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Settings for adding or changing a resolution code; omitted fields keep
+/// their value, and a new code needs a label
+#[derive(Debug, Clone, Default, InputObject)]
+pub struct ResolutionCodeInput {
+    pub label: Option<String>,
+    pub description: Option<String>,
+    pub article_id: Option<Uuid>,
+    pub active: Option<bool>,
+}
+
+/// Resolutions in a period that used one code
+#[derive(Debug, Clone, FromRow, SimpleObject)]
+#[graphql(name = "CrmCoreResolutionCodeMetrics")]
+pub struct CrmCoreResolutionCodeMetrics {
+    pub resolution_code: String,
+    /// `None` when the code is no longer in the taxonomy
+    pub label: Option<String>,
+    pub resolved_tickets: i64,
+    /// Share of the period's coded resolutions
+    pub resolution_percentage: f64,
+    pub avg_resolution_hours: Option<f64>,
+    /// Tickets with this code that are no longer resolved or closed
+    pub reopened_tickets: i64,
+}
+
+/// A category and resolution code that keep occurring together without a
+/// help article documenting the fix
+#[derive(Debug, Clone, FromRow, SimpleObject)]
+pub struct KbArticleCandidate {
+    pub category: String,
+    pub resolution_code: String,
+    pub label: Option<String>,
+    pub ticket_count: i64,
+    /// Share of the category's coded resolutions that used this code
+    pub category_share: f64,
+    /// Most recently resolved examples, for the article's author
+    pub sample_ticket_refs: Vec<String>,
+    pub last_resolved_at: DateTime<Utc>,
+}
+
+/// Trim and uppercase a resolution code, rejecting anything but letters,
+/// digits and underscores
+pub fn normalize_resolution_code(code: &str) -> Result<String> {
+    let code = code.trim().to_uppercase();
+    if code.is_empty() || code.len() > MAX_RESOLUTION_CODE_LENGTH {
+        return Err(SupportError::Validation(format!(
+            "Resolution code must be between 1 and {} characters",
+            MAX_RESOLUTION_CODE_LENGTH
+        )));
+    }
+    if !code.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(SupportError::Validation(format!(
+            "Resolution code {} may only contain letters, digits and underscores",
+            code
+        )));
+    }
+    Ok(code)
+}
@@ -0,0 +1,92 @@
+//! Automatic ticket routing
+//!
+//! Where [`assignment`](crate::assignment) suggests agents for a person to
+//! choose from, routing assigns new tickets without manual triage. A
+//! product's active [`AssignmentRule`]s are tried in `position` order: the
+//! first whose conditions (category, priority, channel) match the ticket and
+//! that has an eligible agent assigns it. Eligible agents are active, on the
+//! rule's team if it has one, and below its `max_open_tickets`; the rule's
+//! [`AssignmentStrategy`] then picks one of them. Load is compared by
+//! priority-weighted [`workload`](crate::workload), not raw ticket counts.
+//!
+//! New tickets are routed when they are created; `auto_assign_ticket` routes
+//! an existing unassigned ticket. Products without rules are left alone.
+
+use async_graphql::SimpleObject;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::models::{AssignmentRule, AssignmentStrategy, SupportTicket};
+
+/// An active agent and their current workload
+#[derive(Debug, Clone, FromRow)]
+pub struct RoutingCandidate {
+    pub user_id: Uuid,
+    pub team: Option<String>,
+    pub skills: Vec<String>,
+    /// Open tickets currently assigned to the agent
+    pub open_tickets: i64,
+    /// Open tickets weighted by priority
+    pub weighted_workload: i64,
+}
+
+/// The rule and agent a ticket was routed to
+#[derive(Debug, Clone, SimpleObject)]
+pub struct RoutingDecision {
+    pub ticket: SupportTicket,
+    pub rule_id: Uuid,
+    pub rule_name: String,
+    pub strategy: AssignmentStrategy,
+    pub agent_id: Uuid,
+}
+
+impl AssignmentRule {
+    /// Whether the rule's conditions match the ticket
+    pub fn matches(&self, ticket: &SupportTicket) -> bool {
+        let category_matches = match (&self.category, &ticket.category) {
+            (None, _) => true,
+            (Some(rule), Some(ticket)) => rule.eq_ignore_ascii_case(ticket),
+            (Some(_), None) => false,
+        };
+
+        category_matches
+            && self.priority.is_none_or(|priority| priority == ticket.priority)
+            && self.channel.is_none_or(|channel| channel == ticket.channel)
+    }
+
+    /// The agent this rule routes the ticket to, if any is eligible
+    ///
+    /// `candidates` must be ordered by `user_id`, which round-robin follows.
+    pub fn pick_agent(&self, ticket: &SupportTicket, candidates: &[RoutingCandidate]) -> Option<Uuid> {
+        let eligible: Vec<&RoutingCandidate> = candidates
+            .iter()
+            .filter(|c| self.team.is_none() || c.team == self.team)
+            .filter(|c| self.max_open_tickets.is_none_or(|max| c.open_tickets < i64::from(max)))
+            .collect();
+
+        let least_loaded = |agents: Vec<&RoutingCandidate>| {
+            agents
+                .into_iter()
+                .min_by_key(|c| (c.weighted_workload, c.open_tickets, c.user_id))
+                .map(|c| c.user_id)
+        };
+
+        match self.strategy {
+            AssignmentStrategy::RoundRobin => eligible
+                .iter()
+                .find(|c| self.last_assigned_to.is_some_and(|last| c.user_id > last))
+                .or(eligible.first())
+                .map(|c| c.user_id),
+            AssignmentStrategy::LeastLoaded => least_loaded(eligible),
+            AssignmentStrategy::SkillBased => {
+                let category = ticket.category.as_deref()?;
+                least_loaded(
+                    eligible
+                        .into_iter()
+                        .filter(|c| c.skills.iter().any(|s| s.eq_ignore_ascii_case(category)))
+                        .collect(),
+                )
+            }
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! Priority boosts on falling customer sentiment
+//!
+//! Every non-internal message written by the ticket's own customer is scored
+//! by the repository's [`SentimentAnalyzer`], from -1 (angry) to 1 (happy),
+//! and the score is kept on the message, readable by agents only. With a
+//! [`SentimentRule`] for the product, a run of `consecutive_messages`
+//! customer messages in a row all scoring below the rule's `threshold` fires
+//! the `SENTIMENT_DROPPED` automation trigger, so a rule with a
+//! `BOOST_PRIORITY` action can raise the ticket before the customer
+//! complains in public. Agent replies in between
+//! don't break the run; an unscored or happier customer message does.
+//!
+//! The trigger fires once per run, when it reaches the rule's length, and is
+//! recorded in the ticket's audit log as `sentiment_dropped` with the run's
+//! average score, ahead of the changes the automations make.
+//!
+//! ```rust
+//! use pleme_support::{LexiconSentimentAnalyzer, SentimentAnalyzer, SentimentRule, SentimentRules};
+//!
+//! let analyzer = LexiconSentimentAnalyzer::default();
+//! assert!(analyzer.score("Still broken. This is ridiculous and useless") < -0.5);
+//! assert!(analyzer.score("Thanks, that fixed it!") > 0.5);
+//! assert_eq!(analyzer.score("My order number is 1042"), 0.0);
+//!
+//! let rules = SentimentRules::default().with_rule("novaskyn", SentimentRule::new(-0.3, 3));
+//! assert!(rules.rule_for("novaskyn").is_some());
+//! assert!(rules.rule_for("lilitu").is_none());
+//! ```
+
+use std::collections::{BTreeSet, HashMap};
+
+/// Scores inbound customer messages
+pub trait SentimentAnalyzer: Send + Sync {
+    /// From -1 (most negative) to 1 (most positive); 0 is neutral
+    fn score(&self, content: &str) -> f64;
+}
+
+const DEFAULT_POSITIVE_WORDS: &[&str] = &[
+    "appreciate", "awesome", "excellent", "fixed", "glad", "great", "happy", "helpful", "love",
+    "perfect", "resolved", "thank", "thanks", "wonderful", "works",
+];
+const DEFAULT_NEGATIVE_WORDS: &[&str] = &[
+    "angry", "awful", "broken", "cancel", "disappointed", "frustrated", "frustrating", "furious",
+    "horrible", "ignored", "ridiculous", "terrible", "unacceptable", "useless", "waiting", "worst",
+];
+
+/// Counts positive and negative words, case-insensitively
+///
+/// The score is `(positive - negative) / (positive + negative)`, or 0 for a
+/// message with neither.
+#[derive(Debug, Clone)]
+pub struct LexiconSentimentAnalyzer {
+    positive: BTreeSet<String>,
+    negative: BTreeSet<String>,
+}
+
+impl LexiconSentimentAnalyzer {
+    pub fn new<P, N>(positive: P, negative: N) -> Self
+    where
+        P: IntoIterator,
+        P::Item: AsRef<str>,
+        N: IntoIterator,
+        N::Item: AsRef<str>,
+    {
+        Self {
+            positive: positive.into_iter().map(|w| w.as_ref().to_lowercase()).collect(),
+            negative: negative.into_iter().map(|w| w.as_ref().to_lowercase()).collect(),
+        }
+    }
+}
+
+impl Default for LexiconSentimentAnalyzer {
+    fn default() -> Self {
+        Self::new(DEFAULT_POSITIVE_WORDS, DEFAULT_NEGATIVE_WORDS)
+    }
+}
+
+impl SentimentAnalyzer for LexiconSentimentAnalyzer {
+    fn score(&self, content: &str) -> f64 {
+        let (mut positive, mut negative) = (0u32, 0u32);
+        for word in content.split(|c: char| !(c.is_alphanumeric() || c == '\'')) {
+            let word = word.to_lowercase();
+            if self.positive.contains(&word) {
+                positive += 1;
+            } else if self.negative.contains(&word) {
+                negative += 1;
+            }
+        }
+
+        if positive + negative == 0 {
+            return 0.0;
+        }
+        (f64::from(positive) - f64::from(negative)) / f64::from(positive + negative)
+    }
+}
+
+/// When falling sentiment fires `SENTIMENT_DROPPED` for a product
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SentimentRule {
+    /// Messages scoring below this count towards a run
+    pub threshold: f64,
+    /// Customer messages in a row below the threshold that fire the trigger;
+    /// at least 1, and 0 counts as 1
+    pub consecutive_messages: u32,
+}
+
+impl SentimentRule {
+    pub fn new(threshold: f64, consecutive_messages: u32) -> Self {
+        Self { threshold, consecutive_messages: consecutive_messages.max(1) }
+    }
+}
+
+/// Per-product sentiment rules; products without one never fire the trigger
+#[derive(Debug, Clone, Default)]
+pub struct SentimentRules {
+    rules: HashMap<String, SentimentRule>,
+}
+
+impl SentimentRules {
+    pub fn with_rule(mut self, product: impl Into<String>, rule: SentimentRule) -> Self {
+        self.rules.insert(product.into(), rule);
+        self
+    }
+
+    pub fn rule_for(&self, product: &str) -> Option<&SentimentRule> {
+        self.rules.get(product)
+    }
+}
+
+/// Whether the latest customer message scores, newest first, complete a run
+///
+/// True when the newest `consecutive_messages` are all below the threshold
+/// and the one before them, if any, is not; `None` is an unscored message.
+pub(crate) fn completes_run(rule: &SentimentRule, scores: &[Option<f64>]) -> bool {
+    let length = rule.consecutive_messages.max(1) as usize;
+    let below = |score: &Option<f64>| score.is_some_and(|s| s < rule.threshold);
+    scores.len() >= length
+        && scores[..length].iter().all(below)
+        && !scores.get(length).is_some_and(below)
+}
@@ -0,0 +1,187 @@
+//! SLA policy
+//!
+//! Resolution-time clocks pause while a ticket sits in WAITING_ON_CUSTOMER
+//! (unless the policy disables it). Cumulative wait time is tracked on the
+//! ticket in `customer_wait_seconds`, with `waiting_since` marking an
+//! ongoing wait. Both clocks also pause during the product's scheduled
+//! [maintenance windows](crate::maintenance), and the first-response clock
+//! only runs within the product's [business calendar](crate::business_hours).
+//!
+//! A ticket breaches once its first-response or resolution clock exceeds the
+//! target plus the grace window of the customer's plan (e.g. Enterprise
+//! breaches only count after 15 minutes of grace). Compliance analytics read
+//! the resulting `sla_breach` flag, so they apply the same grace.
+//!
+//! The policy's targets are defaults: per product/priority targets stored in
+//! `sla_targets` take precedence. The repository computes each ticket's
+//! `first_response_due_at` and `sla_due_at` from them on create and update.
+//!
+//! `sla_clock` returns a ticket's clocks as the breach checks see them
+//! ([`SlaClock`]): elapsed and remaining time per target, where each target
+//! comes from, and the intervals that paused the clocks, so a UI can draw an
+//! SLA widget without redoing the business-hours math.
+
+use async_graphql::{Enum, SimpleObject};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::models::SupportTicket;
+
+#[derive(Debug, Clone)]
+pub struct SlaPolicy {
+    /// Exclude time spent waiting on the customer from resolution time
+    pub pause_while_waiting_on_customer: bool,
+    /// Time allowed before the first agent response; `None` disables the check
+    pub first_response_target: Option<Duration>,
+    /// Time allowed until resolution; `None` disables the check
+    pub resolution_target: Option<Duration>,
+    /// Grace added to every target before a breach counts, keyed by customer plan
+    pub plan_grace_windows: HashMap<String, Duration>,
+    /// Grace for tickets whose plan has no entry in `plan_grace_windows`
+    pub default_grace: Duration,
+}
+
+impl Default for SlaPolicy {
+    fn default() -> Self {
+        Self {
+            pause_while_waiting_on_customer: true,
+            first_response_target: None,
+            resolution_target: None,
+            plan_grace_windows: HashMap::new(),
+            default_grace: Duration::zero(),
+        }
+    }
+}
+
+impl SlaPolicy {
+    /// Total time the ticket has spent waiting on the customer, including an ongoing wait
+    pub fn customer_wait(&self, ticket: &SupportTicket, now: DateTime<Utc>) -> Duration {
+        let ongoing = ticket.waiting_since.map(|since| now - since).unwrap_or_else(Duration::zero);
+        Duration::seconds(ticket.customer_wait_seconds) + ongoing
+    }
+
+    /// Resolution-clock time elapsed so far (or until resolution), honoring the pause setting
+    pub fn resolution_elapsed(&self, ticket: &SupportTicket, now: DateTime<Utc>) -> Duration {
+        let end = ticket.resolved_at.unwrap_or(now);
+        let elapsed = end - ticket.created_at;
+        if self.pause_while_waiting_on_customer {
+            let wait = self.customer_wait(ticket, end);
+            (elapsed - wait).max(Duration::zero())
+        } else {
+            elapsed
+        }
+    }
+
+    /// Grace window for a customer plan
+    pub fn grace_for(&self, plan: Option<&str>) -> Duration {
+        plan.and_then(|p| self.plan_grace_windows.get(p))
+            .copied()
+            .unwrap_or(self.default_grace)
+    }
+
+    /// Whether the ticket has exceeded a target plus its plan's grace window
+    pub fn is_breached(&self, ticket: &SupportTicket, now: DateTime<Utc>) -> bool {
+        let grace = self.grace_for(ticket.customer_plan.as_deref());
+
+        let first_response_breached = self.first_response_target.is_some_and(|target| {
+            let responded = ticket.first_response_at.unwrap_or(now);
+            responded - ticket.created_at > target + grace
+        });
+        let resolution_breached = self.resolution_target.is_some_and(|target| {
+            self.resolution_elapsed(ticket, now) > target + grace
+        });
+
+        first_response_breached || resolution_breached
+    }
+}
+
+/// Where an SLA target comes from
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SlaTargetSource {
+    /// The product/priority row in `sla_targets`
+    ProductTarget,
+    /// The repository's [`SlaPolicy`]
+    DefaultPolicy,
+}
+
+/// Why an SLA clock stopped counting for a while
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SlaPauseReason {
+    /// Pauses the resolution clock, when the policy pauses while waiting
+    WaitingOnCustomer,
+    /// Pauses both clocks
+    Maintenance,
+}
+
+/// An interval during which a ticket's SLA clock was paused
+#[derive(Debug, Clone, Serialize, SimpleObject)]
+pub struct SlaPause {
+    pub reason: SlaPauseReason,
+    pub started_at: DateTime<Utc>,
+    /// `None` while the pause is ongoing
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+/// One of a ticket's SLA clocks
+#[derive(Debug, Clone, Serialize, SimpleObject)]
+pub struct SlaClockTarget {
+    pub source: SlaTargetSource,
+    /// `None` when no target applies
+    pub target_minutes: Option<i64>,
+    /// Grace of the customer's plan, added before a breach counts
+    pub grace_minutes: i64,
+    /// Clock time used, up to now or until the clock stopped; business
+    /// minutes for the first-response clock
+    pub elapsed_minutes: i64,
+    /// Target minus elapsed time, negative once past the target
+    pub remaining_minutes: Option<i64>,
+    pub due_at: Option<DateTime<Utc>>,
+    /// The first agent response or the resolution
+    pub stopped_at: Option<DateTime<Utc>>,
+    /// Whether the clock is counting right now
+    pub running: bool,
+    /// Elapsed time exceeds the target plus grace
+    pub breached: bool,
+}
+
+/// A ticket's SLA clocks as the breach checks compute them
+#[derive(Debug, Clone, Serialize, SimpleObject)]
+pub struct SlaClock {
+    pub ticket_id: Uuid,
+    /// `product/PRIORITY` of the `sla_targets` row in force; `None` when
+    /// both targets come from the repository's policy
+    pub policy_id: Option<String>,
+    pub first_response: SlaClockTarget,
+    pub resolution: SlaClockTarget,
+    /// Pauses since the ticket was created, earliest first
+    pub paused_intervals: Vec<SlaPause>,
+    pub computed_at: DateTime<Utc>,
+}
+
+impl SlaClockTarget {
+    pub(crate) fn new(
+        source: SlaTargetSource,
+        target: Option<Duration>,
+        grace: Duration,
+        elapsed: Duration,
+        due_at: Option<DateTime<Utc>>,
+        stopped_at: Option<DateTime<Utc>>,
+        running: bool,
+    ) -> Self {
+        Self {
+            source,
+            target_minutes: target.map(|t| t.num_minutes()),
+            grace_minutes: grace.num_minutes(),
+            elapsed_minutes: elapsed.num_minutes(),
+            remaining_minutes: target.map(|t| (t - elapsed).num_minutes()),
+            due_at,
+            stopped_at,
+            running: stopped_at.is_none() && running,
+            breached: target.is_some_and(|t| elapsed > t + grace),
+        }
+    }
+}
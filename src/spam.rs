@@ -0,0 +1,117 @@
+//! Spam screening for customer-facing intake
+//!
+//! When a ticket is created with [`SubmitterInfo`](crate::SubmitterInfo), the
+//! repository gathers [`IntakeSignals`] (the form's
+//! [`IntakeTelemetry`] plus how often the same client fingerprint or IP
+//! submitted recently) and asks its [`SpamClassifier`] whether to accept.
+//! Every screened submission is logged, accepted or not, for the intake
+//! telemetry report.
+//!
+//! ```rust
+//! use pleme_support::{CreateTicketInput, IntakeSignals, IntakeTelemetry, SpamClassifier, SpamSignal, TelemetrySpamClassifier};
+//!
+//! let classifier = TelemetrySpamClassifier::default();
+//! let signals = IntakeSignals {
+//!     telemetry: Some(IntakeTelemetry { form_fill_ms: Some(800), ..Default::default() }),
+//!     ..Default::default()
+//! };
+//! let verdict = classifier.classify(&CreateTicketInput::default(), &signals);
+//! assert!(!verdict.accepted);
+//! assert_eq!(verdict.signals, vec![SpamSignal::FastFill]);
+//! ```
+
+use async_graphql::Enum;
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+use crate::intake::IntakeTelemetry;
+use crate::models::CreateTicketInput;
+
+/// Suspicious trait of a submission
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum SpamSignal {
+    /// The hidden honeypot field was filled in
+    Honeypot,
+    /// The form was submitted faster than a person could fill it
+    FastFill,
+    /// Many recent submissions from the same client fingerprint
+    RepeatFingerprint,
+    /// Many recent submissions from the same IP address
+    RepeatIp,
+}
+
+impl SpamSignal {
+    /// Representation stored in the intake log
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SpamSignal::Honeypot => "HONEYPOT",
+            SpamSignal::FastFill => "FAST_FILL",
+            SpamSignal::RepeatFingerprint => "REPEAT_FINGERPRINT",
+            SpamSignal::RepeatIp => "REPEAT_IP",
+        }
+    }
+}
+
+/// What the classifier knows about a submission
+#[derive(Debug, Clone, Default)]
+pub struct IntakeSignals {
+    pub telemetry: Option<IntakeTelemetry>,
+    /// Submissions with the same client fingerprint in the last hour
+    pub recent_fingerprint_submissions: i64,
+    /// Submissions from the same IP address in the last hour
+    pub recent_ip_submissions: i64,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SpamVerdict {
+    pub accepted: bool,
+    /// Signals observed, whether or not they led to rejection
+    pub signals: Vec<SpamSignal>,
+}
+
+pub trait SpamClassifier: Send + Sync {
+    fn classify(&self, input: &CreateTicketInput, signals: &IntakeSignals) -> SpamVerdict;
+}
+
+/// Rejects any submission showing a telemetry signal
+#[derive(Debug, Clone)]
+pub struct TelemetrySpamClassifier {
+    pub min_fill_time: Duration,
+    /// Submissions per fingerprint or IP per hour before they count as repeats
+    pub max_submissions_per_hour: i64,
+}
+
+impl Default for TelemetrySpamClassifier {
+    fn default() -> Self {
+        Self {
+            min_fill_time: Duration::seconds(3),
+            max_submissions_per_hour: 5,
+        }
+    }
+}
+
+impl SpamClassifier for TelemetrySpamClassifier {
+    fn classify(&self, _input: &CreateTicketInput, signals: &IntakeSignals) -> SpamVerdict {
+        let mut observed = Vec::new();
+
+        if let Some(telemetry) = &signals.telemetry {
+            if telemetry.honeypot_triggered {
+                observed.push(SpamSignal::Honeypot);
+            }
+            if telemetry
+                .form_fill_ms
+                .is_some_and(|ms| ms < self.min_fill_time.num_milliseconds())
+            {
+                observed.push(SpamSignal::FastFill);
+            }
+        }
+        if signals.recent_fingerprint_submissions >= self.max_submissions_per_hour {
+            observed.push(SpamSignal::RepeatFingerprint);
+        }
+        if signals.recent_ip_submissions >= self.max_submissions_per_hour {
+            observed.push(SpamSignal::RepeatIp);
+        }
+
+        SpamVerdict { accepted: observed.is_empty(), signals: observed }
+    }
+}
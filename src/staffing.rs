@@ -0,0 +1,493 @@
+//! Staffing simulation
+//!
+//! "Do we need a sixth agent on Mondays?" is answered by replaying a past
+//! period's ticket arrivals against a proposed staffing model.
+//! `simulate_staffing` takes the headcount on each shift and a weekly
+//! [`ShiftPattern`], in the product's timezone, and runs the queue:
+//!
+//! - On-duty agents pick up waiting tickets most urgent first, then oldest
+//!   first, and each pickup takes the pattern's `handle_minutes`. A ticket
+//!   picked up just before a shift ends is finished on overtime.
+//! - Overlapping shifts add up, so an early and a late shift of five agents
+//!   each staff ten during their overlap.
+//! - A ticket's wait is the time from creation to pickup. It breaches when
+//!   it is picked up after its first-response deadline under the current SLA
+//!   targets, grace and business calendar.
+//! - Tickets still waiting a week after the period are reported unanswered.
+//!
+//! The result, a [`StaffingSimulation`], has wait percentiles, the projected
+//! first-response breach rate and agent utilization overall and per weekday,
+//! so runs with and without the extra agent can be compared. Deleted and
+//! test tickets are left out.
+//!
+//! ```rust
+//! use pleme_support::{ShiftPattern, StaffingShift};
+//!
+//! // Weekdays 09:00-17:00, with one extra agent on Mondays
+//! let pattern = ShiftPattern::new(20)
+//!     .with_shift(StaffingShift::new(vec![2, 3, 4, 5], 9 * 60, 17 * 60))
+//!     .with_shift(StaffingShift::new(vec![1], 9 * 60, 17 * 60).with_additional_agents(1));
+//! assert!(pattern.validate(5).is_ok());
+//!
+//! // Overnight shifts end the next day
+//! let night = ShiftPattern::new(20).with_shift(StaffingShift::new(vec![5], 22 * 60, 6 * 60));
+//! assert!(night.validate(2).is_ok());
+//! assert!(ShiftPattern::new(20).validate(5).is_err());
+//! ```
+
+use async_graphql::{InputObject, SimpleObject};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap};
+
+use crate::models::TicketPriority;
+use crate::{Result, SupportError};
+
+/// Longest historical period one simulation may replay, in days
+pub const MAX_STAFFING_SIMULATION_DAYS: i64 = 92;
+
+/// Days past the period that queued tickets may still be picked up
+pub(crate) const STAFFING_DRAIN_DAYS: i32 = 7;
+
+/// Weekly shifts of a proposed staffing model
+#[derive(Debug, Clone, InputObject)]
+pub struct ShiftPattern {
+    pub shifts: Vec<StaffingShift>,
+    /// Agent minutes each ticket takes before the agent picks up the next
+    pub handle_minutes: i32,
+}
+
+/// A shift worked on some weekdays, in the product's timezone
+#[derive(Debug, Clone, InputObject)]
+pub struct StaffingShift {
+    /// ISO weekdays the shift starts on, 1 = Monday
+    pub days: Vec<i32>,
+    /// Minutes after local midnight the shift starts
+    pub starts_at_minute: i32,
+    /// Minutes after local midnight the shift ends, up to 1440; at or before
+    /// `starts_at_minute` for shifts ending the next day
+    pub ends_at_minute: i32,
+    /// Agents on this shift beyond the simulated headcount
+    #[graphql(default)]
+    pub additional_agents: i32,
+}
+
+impl ShiftPattern {
+    pub fn new(handle_minutes: i32) -> Self {
+        Self { shifts: Vec::new(), handle_minutes }
+    }
+
+    pub fn with_shift(mut self, shift: StaffingShift) -> Self {
+        self.shifts.push(shift);
+        self
+    }
+
+    /// Check the pattern staffs at least one shift with `agents` per shift
+    pub fn validate(&self, agents: u32) -> Result<()> {
+        if self.handle_minutes <= 0 {
+            return Err(SupportError::Validation("handle_minutes must be positive".to_string()));
+        }
+        if self.shifts.is_empty() {
+            return Err(SupportError::Validation("shifts must not be empty".to_string()));
+        }
+        for shift in &self.shifts {
+            if shift.days.is_empty() || shift.days.iter().any(|day| !(1..=7).contains(day)) {
+                return Err(SupportError::Validation("shift days must be ISO weekdays from 1 to 7".to_string()));
+            }
+            if !(0..1440).contains(&shift.starts_at_minute) || !(0..=1440).contains(&shift.ends_at_minute) {
+                return Err(SupportError::Validation(
+                    "shift minutes must be between 0 and 1440 after local midnight".to_string(),
+                ));
+            }
+            if shift.additional_agents < 0 {
+                return Err(SupportError::Validation("additional_agents must not be negative".to_string()));
+            }
+        }
+        if self.shifts.iter().all(|shift| shift_agents(agents, shift) == 0) {
+            return Err(SupportError::Validation("At least one shift must have an agent".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// One (ISO day, start, end, agents) tuple per day each shift is worked
+    pub(crate) fn day_shifts(&self, agents: u32) -> (Vec<i32>, Vec<i32>, Vec<i32>, Vec<i64>) {
+        let mut days = Vec::new();
+        let mut starts = Vec::new();
+        let mut ends = Vec::new();
+        let mut staffed = Vec::new();
+        for shift in &self.shifts {
+            for day in &shift.days {
+                days.push(*day);
+                starts.push(shift.starts_at_minute);
+                ends.push(shift.ends_at_minute);
+                staffed.push(shift_agents(agents, shift));
+            }
+        }
+        (days, starts, ends, staffed)
+    }
+}
+
+impl StaffingShift {
+    pub fn new(days: Vec<i32>, starts_at_minute: i32, ends_at_minute: i32) -> Self {
+        Self { days, starts_at_minute, ends_at_minute, additional_agents: 0 }
+    }
+
+    pub fn with_additional_agents(mut self, additional_agents: i32) -> Self {
+        self.additional_agents = additional_agents;
+        self
+    }
+}
+
+fn shift_agents(agents: u32, shift: &StaffingShift) -> i64 {
+    i64::from(agents) + i64::from(shift.additional_agents.max(0))
+}
+
+/// Projected queue for a product under a staffing model
+#[derive(Debug, Clone, Serialize, SimpleObject)]
+pub struct StaffingSimulation {
+    pub product: String,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    /// Agents per shift, before each shift's additional agents
+    pub agents: u32,
+    /// The timezone shifts are in
+    pub timezone: String,
+    pub projection: StaffingProjection,
+    /// Projection per ISO weekday of ticket creation, Monday first; days without tickets are left out
+    pub by_weekday: Vec<StaffingWeekdayProjection>,
+    /// Share of on-duty agent time spent handling tickets, in percent; above 100 with overtime
+    pub agent_utilization: Option<f64>,
+}
+
+/// Projected waits and first-response breaches of a set of tickets
+#[derive(Debug, Clone, Default, PartialEq, Serialize, SimpleObject)]
+pub struct StaffingProjection {
+    pub tickets: i64,
+    /// Tickets still waiting when the simulation ended
+    pub unanswered_tickets: i64,
+    pub avg_wait_minutes: Option<f64>,
+    pub p50_wait_minutes: Option<f64>,
+    pub p90_wait_minutes: Option<f64>,
+    pub max_wait_minutes: Option<f64>,
+    pub first_response_breaches: i64,
+    /// Breaches among tickets with a first-response target, in percent
+    pub first_response_breach_rate: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, SimpleObject)]
+pub struct StaffingWeekdayProjection {
+    /// ISO weekday, 1 = Monday
+    pub weekday: i32,
+    pub projection: StaffingProjection,
+}
+
+/// A historical ticket as the simulation replays it
+#[derive(Debug, Clone, FromRow)]
+pub(crate) struct SimulatedArrival {
+    pub created_at: DateTime<Utc>,
+    pub priority: TicketPriority,
+    /// ISO weekday of creation in the product's timezone
+    pub weekday: i32,
+    /// First-response deadline including grace; absent without a target
+    pub first_response_due_at: Option<DateTime<Utc>>,
+}
+
+/// One worked shift
+#[derive(Debug, Clone, FromRow)]
+pub(crate) struct ShiftSpan {
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub agents: i64,
+}
+
+/// Replay `arrivals`, oldest first, against `spans`, returning each ticket's pickup time
+pub(crate) fn simulate_pickups(arrivals: &[SimulatedArrival], spans: &[ShiftSpan], handle: Duration) -> Vec<Option<DateTime<Utc>>> {
+    let mut changes: Vec<(DateTime<Utc>, i64)> = spans
+        .iter()
+        .flat_map(|span| [(span.starts_at, span.agents), (span.ends_at, -span.agents)])
+        .collect();
+    changes.sort_by_key(|(at, _)| *at);
+
+    let mut pickups = vec![None; arrivals.len()];
+    let mut waiting = BinaryHeap::new();
+    let mut busy_until = BinaryHeap::new();
+    let (mut next_arrival, mut next_change, mut on_duty) = (0, 0, 0i64);
+    loop {
+        let now = [
+            arrivals.get(next_arrival).map(|a| a.created_at),
+            changes.get(next_change).map(|(at, _)| *at),
+            busy_until.peek().map(|Reverse(at)| *at),
+        ]
+        .into_iter()
+        .flatten()
+        .min();
+        let Some(now) = now else { break };
+
+        while let Some(arrival) = arrivals.get(next_arrival).filter(|a| a.created_at <= now) {
+            waiting.push(Reverse((urgency(arrival.priority), arrival.created_at, next_arrival)));
+            next_arrival += 1;
+        }
+        while let Some((_, delta)) = changes.get(next_change).filter(|(at, _)| *at <= now) {
+            on_duty += delta;
+            next_change += 1;
+        }
+        while busy_until.peek().is_some_and(|Reverse(at)| *at <= now) {
+            busy_until.pop();
+        }
+        while (busy_until.len() as i64) < on_duty {
+            let Some(Reverse((_, _, index))) = waiting.pop() else { break };
+            pickups[index] = Some(now);
+            busy_until.push(Reverse(now + handle));
+        }
+
+        if next_arrival == arrivals.len() && waiting.is_empty() {
+            break;
+        }
+    }
+
+    pickups
+}
+
+/// Queue order of a priority, most urgent lowest
+fn urgency(priority: TicketPriority) -> u8 {
+    match priority {
+        TicketPriority::Urgent => 0,
+        TicketPriority::High => 1,
+        TicketPriority::Medium => 2,
+        TicketPriority::Low => 3,
+    }
+}
+
+/// A replayed ticket and when it was picked up
+type Outcome<'a> = (&'a SimulatedArrival, Option<DateTime<Utc>>);
+
+/// Summarize the simulated pickups overall and per weekday
+pub(crate) fn summarize(
+    arrivals: &[SimulatedArrival],
+    pickups: &[Option<DateTime<Utc>>],
+) -> (StaffingProjection, Vec<StaffingWeekdayProjection>) {
+    let outcomes: Vec<Outcome> = arrivals.iter().zip(pickups.iter().copied()).collect();
+    let mut by_weekday: BTreeMap<i32, Vec<Outcome>> = BTreeMap::new();
+    for outcome in &outcomes {
+        by_weekday.entry(outcome.0.weekday).or_default().push(*outcome);
+    }
+
+    let weekdays = by_weekday
+        .into_iter()
+        .map(|(weekday, outcomes)| StaffingWeekdayProjection { weekday, projection: project(&outcomes) })
+        .collect();
+    (project(&outcomes), weekdays)
+}
+
+fn project(outcomes: &[Outcome]) -> StaffingProjection {
+    let mut waits: Vec<f64> = outcomes
+        .iter()
+        .filter_map(|(arrival, pickup)| pickup.map(|at| (at - arrival.created_at).num_seconds() as f64 / 60.0))
+        .collect();
+    waits.sort_by(f64::total_cmp);
+
+    let targeted = outcomes.iter().filter(|(arrival, _)| arrival.first_response_due_at.is_some()).count() as i64;
+    let breaches = outcomes
+        .iter()
+        .filter(|(arrival, pickup)| match (arrival.first_response_due_at, pickup) {
+            (Some(due_at), Some(at)) => *at > due_at,
+            (Some(_), None) => true,
+            (None, _) => false,
+        })
+        .count() as i64;
+
+    StaffingProjection {
+        tickets: outcomes.len() as i64,
+        unanswered_tickets: (outcomes.len() - waits.len()) as i64,
+        avg_wait_minutes: (!waits.is_empty()).then(|| waits.iter().sum::<f64>() / waits.len() as f64),
+        p50_wait_minutes: percentile(&waits, 0.5),
+        p90_wait_minutes: percentile(&waits, 0.9),
+        max_wait_minutes: waits.last().copied(),
+        first_response_breaches: breaches,
+        first_response_breach_rate: (targeted > 0).then(|| breaches as f64 * 100.0 / targeted as f64),
+    }
+}
+
+/// Interpolated percentile of sorted values, as Postgres `percentile_cont`
+fn percentile(sorted: &[f64], fraction: f64) -> Option<f64> {
+    let last = sorted.len().checked_sub(1)?;
+    let position = fraction * last as f64;
+    let (lower, upper) = (position.floor() as usize, position.ceil() as usize);
+    Some(sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f64))
+}
+
+/// Handled share of on-duty agent time within `from..to`, in percent
+pub(crate) fn agent_utilization(
+    spans: &[ShiftSpan],
+    pickups: &[Option<DateTime<Utc>>],
+    handle: Duration,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Option<f64> {
+    let overlap = |start: DateTime<Utc>, end: DateTime<Utc>| (end.min(to) - start.max(from)).num_seconds().max(0);
+    let on_duty: i64 = spans.iter().map(|span| overlap(span.starts_at, span.ends_at) * span.agents).sum();
+    let handling: i64 = pickups.iter().flatten().map(|at| overlap(*at, *at + handle)).sum();
+
+    (on_duty > 0).then(|| handling as f64 * 100.0 / on_duty as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// Monday 2026-03-09 at `hour:minute` UTC
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 9, hour, minute, 0).unwrap()
+    }
+
+    fn arrival(created_at: DateTime<Utc>, priority: TicketPriority) -> SimulatedArrival {
+        SimulatedArrival { created_at, priority, weekday: 1, first_response_due_at: None }
+    }
+
+    fn span(starts_at: DateTime<Utc>, ends_at: DateTime<Utc>, agents: i64) -> ShiftSpan {
+        ShiftSpan { starts_at, ends_at, agents }
+    }
+
+    #[test]
+    fn rejects_unusable_patterns() {
+        let weekdays = || StaffingShift::new(vec![1, 2, 3, 4, 5], 9 * 60, 17 * 60);
+        let invalid = [
+            (ShiftPattern::new(0).with_shift(weekdays()), 1),
+            (ShiftPattern::new(20), 1),
+            (ShiftPattern::new(20).with_shift(StaffingShift::new(vec![], 0, 60)), 1),
+            (ShiftPattern::new(20).with_shift(StaffingShift::new(vec![0], 0, 60)), 1),
+            (ShiftPattern::new(20).with_shift(StaffingShift::new(vec![8], 0, 60)), 1),
+            (ShiftPattern::new(20).with_shift(StaffingShift::new(vec![1], 1440, 60)), 1),
+            (ShiftPattern::new(20).with_shift(StaffingShift::new(vec![1], -1, 60)), 1),
+            (ShiftPattern::new(20).with_shift(StaffingShift::new(vec![1], 0, 1441)), 1),
+            (ShiftPattern::new(20).with_shift(weekdays().with_additional_agents(-1)), 1),
+            (ShiftPattern::new(20).with_shift(weekdays()), 0),
+        ];
+        for (pattern, agents) in invalid {
+            assert!(matches!(pattern.validate(agents), Err(SupportError::Validation(_))), "{:?}", pattern);
+        }
+
+        assert!(ShiftPattern::new(20).with_shift(StaffingShift::new(vec![7], 0, 1440)).validate(1).is_ok());
+        // An extra agent staffs a shift even with no base headcount
+        assert!(ShiftPattern::new(20).with_shift(weekdays().with_additional_agents(1)).validate(0).is_ok());
+    }
+
+    #[test]
+    fn expands_shifts_per_day_with_their_extra_agents() {
+        let pattern = ShiftPattern::new(20)
+            .with_shift(StaffingShift::new(vec![1, 2], 9 * 60, 17 * 60).with_additional_agents(1))
+            .with_shift(StaffingShift::new(vec![5], 22 * 60, 6 * 60));
+        assert_eq!(
+            pattern.day_shifts(2),
+            (vec![1, 2, 5], vec![540, 540, 1320], vec![1020, 1020, 360], vec![3, 3, 2])
+        );
+    }
+
+    #[test]
+    fn an_agent_takes_one_ticket_per_handle_time() {
+        let arrivals = [
+            arrival(at(9, 0), TicketPriority::Medium),
+            arrival(at(9, 0), TicketPriority::Medium),
+            arrival(at(9, 5), TicketPriority::Medium),
+        ];
+        let pickups = simulate_pickups(&arrivals, &[span(at(9, 0), at(17, 0), 1)], Duration::minutes(20));
+        assert_eq!(pickups, vec![Some(at(9, 0)), Some(at(9, 20)), Some(at(9, 40))]);
+    }
+
+    #[test]
+    fn urgent_tickets_jump_the_queue() {
+        let arrivals = [
+            arrival(at(9, 0), TicketPriority::Low),
+            arrival(at(9, 1), TicketPriority::Low),
+            arrival(at(9, 10), TicketPriority::Urgent),
+        ];
+        let pickups = simulate_pickups(&arrivals, &[span(at(9, 0), at(17, 0), 1)], Duration::minutes(20));
+        assert_eq!(pickups, vec![Some(at(9, 0)), Some(at(9, 40)), Some(at(9, 20))]);
+    }
+
+    #[test]
+    fn tickets_outside_shifts_wait_for_the_next_one() {
+        let arrivals = [
+            arrival(at(8, 0), TicketPriority::Medium),
+            arrival(at(9, 50), TicketPriority::Medium),
+            arrival(at(9, 55), TicketPriority::Medium),
+            // Arrives as the shift ends
+            arrival(at(10, 0), TicketPriority::Medium),
+        ];
+        let spans = [span(at(9, 0), at(10, 0), 1), span(at(13, 0), at(14, 0), 1)];
+        let pickups = simulate_pickups(&arrivals, &spans, Duration::minutes(20));
+        // The 9:50 pickup runs past the shift end on overtime
+        assert_eq!(pickups, vec![Some(at(9, 0)), Some(at(9, 50)), Some(at(13, 0)), Some(at(13, 20))]);
+    }
+
+    #[test]
+    fn overlapping_shifts_add_up() {
+        let arrivals: Vec<_> = (0..3).map(|_| arrival(at(12, 0), TicketPriority::Medium)).collect();
+        let spans = [span(at(9, 0), at(17, 0), 1), span(at(12, 0), at(20, 0), 1)];
+        let pickups = simulate_pickups(&arrivals, &spans, Duration::minutes(20));
+        assert_eq!(pickups, vec![Some(at(12, 0)), Some(at(12, 0)), Some(at(12, 20))]);
+    }
+
+    #[test]
+    fn tickets_are_unanswered_without_shifts() {
+        let arrivals = [arrival(at(9, 0), TicketPriority::Urgent), arrival(at(10, 0), TicketPriority::Low)];
+        assert_eq!(simulate_pickups(&arrivals, &[], Duration::minutes(20)), vec![None, None]);
+        assert!(simulate_pickups(&[], &[span(at(9, 0), at(17, 0), 3)], Duration::minutes(20)).is_empty());
+    }
+
+    #[test]
+    fn interpolates_percentiles_like_percentile_cont() {
+        assert_eq!(percentile(&[], 0.5), None);
+        assert_eq!(percentile(&[5.0], 0.9), Some(5.0));
+        assert_eq!(percentile(&[1.0, 2.0, 3.0, 4.0], 0.5), Some(2.5));
+        assert!((percentile(&[1.0, 2.0, 3.0, 4.0], 0.9).unwrap() - 3.7).abs() < 1e-9);
+        assert_eq!(percentile(&[1.0, 2.0, 3.0, 4.0], 1.0), Some(4.0));
+    }
+
+    #[test]
+    fn counts_late_and_unanswered_targeted_tickets_as_breaches() {
+        let due = |mut arrival: SimulatedArrival, due_at: DateTime<Utc>| {
+            arrival.first_response_due_at = Some(due_at);
+            arrival
+        };
+        let tuesday = |mut arrival: SimulatedArrival| {
+            arrival.weekday = 2;
+            arrival
+        };
+        let arrivals = [
+            due(arrival(at(9, 0), TicketPriority::Medium), at(10, 0)),
+            due(arrival(at(9, 0), TicketPriority::Medium), at(9, 30)),
+            tuesday(due(arrival(at(9, 0), TicketPriority::Medium), at(12, 0))),
+            tuesday(arrival(at(9, 0), TicketPriority::Medium)),
+        ];
+        let pickups = [Some(at(10, 0)), Some(at(9, 40)), None, None];
+
+        let (overall, by_weekday) = summarize(&arrivals, &pickups);
+        assert_eq!(overall.tickets, 4);
+        assert_eq!(overall.unanswered_tickets, 2);
+        assert_eq!(overall.avg_wait_minutes, Some(50.0));
+        assert_eq!(overall.max_wait_minutes, Some(60.0));
+        // Picked up at the deadline is on time; the untargeted ticket never breaches
+        assert_eq!(overall.first_response_breaches, 2);
+        assert!((overall.first_response_breach_rate.unwrap() - 200.0 / 3.0).abs() < 1e-9);
+
+        assert_eq!(by_weekday.iter().map(|w| w.weekday).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(by_weekday[1].projection.avg_wait_minutes, None);
+        assert_eq!(by_weekday[1].projection.first_response_breach_rate, Some(100.0));
+    }
+
+    #[test]
+    fn utilization_only_counts_time_inside_the_period() {
+        let spans = [span(at(8, 0), at(12, 0), 2)];
+        let pickups = [Some(at(8, 30)), Some(at(10, 0)), Some(at(10, 50)), None];
+        let utilization = agent_utilization(&spans, &pickups, Duration::minutes(20), at(9, 0), at(11, 0));
+        // 30 handled minutes over 2 agents for 2 hours
+        assert_eq!(utilization, Some(12.5));
+
+        assert_eq!(agent_utilization(&[], &pickups, Duration::minutes(20), at(9, 0), at(11, 0)), None);
+    }
+}
@@ -0,0 +1,237 @@
+//! Offline sync for the field-agent mobile app
+//!
+//! The app keeps a local copy of the tickets in its [`SyncScope`] and works
+//! on it without a connection. `changes_since` returns what changed on the
+//! server after a cursor: tickets, messages, and [`SyncTombstone`]s for
+//! tickets the app should drop. The app stores the returned cursor and
+//! passes it back on its next pull; `has_more` says to pull again at once.
+//!
+//! ```rust
+//! use chrono::{TimeZone, Utc};
+//! use pleme_support::sync::SyncCursor;
+//! use uuid::Uuid;
+//!
+//! let mut cursor = SyncCursor::default();
+//! cursor.tickets = (Utc.with_ymd_and_hms(2026, 3, 1, 9, 0, 0).unwrap(), Uuid::nil());
+//! assert_eq!(SyncCursor::decode(&cursor.encode()).unwrap(), cursor);
+//! assert!(SyncCursor::decode("not a cursor").is_err());
+//! ```
+//!
+//! Work done offline goes back with `push_local_changes`: ticket updates and
+//! new messages, each under a client-generated `change_id` so a batch
+//! re-sent after a dropped response is not applied twice. Messages never
+//! conflict. A ticket update carries the `updated_at` the app last saw; a
+//! field someone else changed on the server after that is a conflict, and
+//! the server's value wins. The update's other fields are still applied, and
+//! the result lists the dropped fields with the ticket as the server has it.
+//!
+//! Changes are held back for [`SYNC_SETTLE_SECONDS`] so a write still
+//! committing with an earlier timestamp is not skipped. Messages of a ticket
+//! newly assigned into an agent's scope are not replayed; the app loads
+//! them from the ticket's message list.
+
+use async_graphql::{Enum, InputObject, SimpleObject};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::models::{MessageContentFormat, SupportTicket, TicketMessage, UpdateTicketInput};
+use crate::{Result, SupportError};
+
+/// How long a change must be committed before a pull picks it up
+pub const SYNC_SETTLE_SECONDS: i64 = 10;
+
+/// Tickets, messages and tombstones returned per pull when no limit is given
+pub const DEFAULT_SYNC_LIMIT: i64 = 200;
+
+/// Most tickets, messages or tombstones one pull returns
+pub const MAX_SYNC_LIMIT: i64 = 1000;
+
+/// Most changes one push may carry
+pub const MAX_PUSH_CHANGES: usize = 100;
+
+/// Tickets a device keeps in sync
+#[derive(Debug, Clone, InputObject)]
+pub struct SyncScope {
+    pub product: String,
+    /// Only tickets assigned to this agent; tickets assigned away come back
+    /// as tombstones
+    pub assigned_to: Option<Uuid>,
+    /// Only tickets of this B2B account
+    pub organization_id: Option<Uuid>,
+}
+
+/// Position in each change stream a device has pulled up to
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SyncCursor {
+    pub tickets: (DateTime<Utc>, Uuid),
+    pub messages: (DateTime<Utc>, Uuid),
+    pub tombstones: (DateTime<Utc>, Uuid),
+}
+
+impl Default for SyncCursor {
+    /// The start of every stream, for a device's first pull
+    fn default() -> Self {
+        let start = (DateTime::<Utc>::UNIX_EPOCH, Uuid::nil());
+        Self { tickets: start, messages: start, tombstones: start }
+    }
+}
+
+impl SyncCursor {
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_string(self).unwrap_or_default();
+        STANDARD.encode(format!("sync:{}", json))
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self> {
+        let invalid = || SupportError::InvalidInput(format!("Invalid sync cursor: {}", cursor));
+
+        let decoded = STANDARD.decode(cursor).map_err(|_| invalid())?;
+        let decoded = String::from_utf8(decoded).map_err(|_| invalid())?;
+        let json = decoded.strip_prefix("sync:").ok_or_else(invalid)?;
+        serde_json::from_str(json).map_err(|_| invalid())
+    }
+}
+
+/// Why a device should drop a ticket
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TombstoneReason {
+    /// Soft-deleted; it comes back as a ticket if restored
+    Deleted,
+    /// Removed for good by a retention or test-ticket purge
+    Purged,
+    /// Assigned away from the scope's agent
+    Unassigned,
+}
+
+/// A ticket that left a device's scope
+#[derive(Debug, Clone, SimpleObject)]
+pub struct SyncTombstone {
+    pub ticket_id: Uuid,
+    pub reason: TombstoneReason,
+    pub removed_at: DateTime<Utc>,
+}
+
+/// What changed in a scope since a cursor
+#[derive(Debug, Clone, SimpleObject)]
+pub struct SyncChanges {
+    pub tickets: Vec<SupportTicket>,
+    /// New messages, and messages moved to another ticket by a merge
+    pub messages: Vec<TicketMessage>,
+    pub tombstones: Vec<SyncTombstone>,
+    /// Pass to the next pull
+    pub cursor: String,
+    /// A stream hit the limit; pull again with `cursor`
+    pub has_more: bool,
+}
+
+/// A message written offline
+#[derive(Debug, Clone, InputObject)]
+pub struct SyncMessageInput {
+    pub content: String,
+    #[graphql(default)]
+    pub is_internal: bool,
+    #[graphql(default)]
+    pub content_format: MessageContentFormat,
+}
+
+/// One change made offline; set exactly one of `update` and `message`
+#[derive(Debug, Clone, InputObject)]
+pub struct SyncChangeInput {
+    /// Generated on the device; pushing the same id again returns the first result
+    pub change_id: Uuid,
+    pub ticket_id: Uuid,
+    /// The ticket's `updatedAt` when the device last synced it
+    pub base_updated_at: DateTime<Utc>,
+    pub update: Option<UpdateTicketInput>,
+    pub message: Option<SyncMessageInput>,
+}
+
+/// How a pushed change was applied
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SyncOutcome {
+    Applied,
+    /// Applied except for `conflictingFields`, which kept the server's values
+    Conflict,
+    /// Nothing applied; see `error`
+    Rejected,
+}
+
+impl SyncOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SyncOutcome::Applied => "APPLIED",
+            SyncOutcome::Conflict => "CONFLICT",
+            SyncOutcome::Rejected => "REJECTED",
+        }
+    }
+}
+
+/// Result of one pushed change
+#[derive(Debug, Clone, SimpleObject)]
+pub struct SyncChangeResult {
+    pub change_id: Uuid,
+    pub outcome: SyncOutcome,
+    /// Update fields the server changed after `baseUpdatedAt`
+    pub conflicting_fields: Vec<String>,
+    pub error: Option<String>,
+    /// The ticket as the server has it after the change
+    pub ticket: Option<SupportTicket>,
+    /// The message a message change created
+    pub message_id: Option<Uuid>,
+}
+
+/// A pushed change's result as stored for re-pushes
+#[derive(Debug, Clone, FromRow)]
+pub(crate) struct PushedChange {
+    pub change_id: Uuid,
+    pub ticket_id: Uuid,
+    pub outcome: SyncOutcome,
+    pub conflicting_fields: Vec<String>,
+    pub error: Option<String>,
+    pub message_id: Option<Uuid>,
+}
+
+/// A message with the time it last changed, for the message stream's cursor
+#[derive(Debug, Clone, FromRow)]
+pub(crate) struct SyncedMessage {
+    #[sqlx(flatten)]
+    pub message: TicketMessage,
+    pub changed_at: DateTime<Utc>,
+}
+
+/// Drop the fields of `update` named in `fields`
+pub(crate) fn without_fields(update: &UpdateTicketInput, fields: &[String]) -> UpdateTicketInput {
+    let keep = |field: &str| !fields.iter().any(|f| f == field);
+    UpdateTicketInput {
+        subject: update.subject.clone().filter(|_| keep("subject")),
+        description: update.description.clone().filter(|_| keep("description")),
+        status: update.status.filter(|_| keep("status")),
+        priority: update.priority.filter(|_| keep("priority")),
+        category: update.category.clone().filter(|_| keep("category")),
+        assigned_to: update.assigned_to.filter(|_| keep("assigned_to")),
+        resolution_code: update.resolution_code.clone().filter(|_| keep("resolution_code")),
+    }
+}
+
+/// Audit field names of the fields `update` sets
+pub(crate) fn updated_fields(update: &UpdateTicketInput) -> Vec<String> {
+    [
+        ("subject", update.subject.is_some()),
+        ("description", update.description.is_some()),
+        ("status", update.status.is_some()),
+        ("priority", update.priority.is_some()),
+        ("category", update.category.is_some()),
+        ("assigned_to", update.assigned_to.is_some()),
+        ("resolution_code", update.resolution_code.is_some()),
+    ]
+    .into_iter()
+    .filter(|(_, set)| *set)
+    .map(|(field, _)| field.to_string())
+    .collect()
+}
@@ -0,0 +1,236 @@
+//! One handle on the support subsystem
+//!
+//! A host service used to build a [`SupportRepository`] with a chain of
+//! `with_*` calls, wrap it in an `Arc`, and register it and any
+//! [`FieldGuard`] in GraphQL data one by one. [`SupportSystemBuilder`] wires
+//! the pieces in one place instead: the repository's clock, settings, event
+//! publisher and customer directory (cached for a TTL), the field guard, and
+//! the host's feature flags. The resulting [`SupportSystem`] is cheap to clone and is
+//! what the resolvers look up in schema data; a bare `Arc<SupportRepository>`
+//! still works for services that have not moved over.
+//!
+//! ```rust,no_run
+//! use pleme_support::{FieldGuard, SensitiveField, SlaPolicy, SupportSystem};
+//! use std::time::Duration;
+//!
+//! # fn example(pool: sqlx::PgPool, publisher: std::sync::Arc<dyn pleme_support::SupportEventPublisher>) {
+//! let system = SupportSystem::builder(pool)
+//!     .event_publisher(publisher)
+//!     .customer_cache_ttl(Duration::from_secs(300))
+//!     .field_guard(FieldGuard::new(|_, field| field != SensitiveField::RestrictedMessages))
+//!     .feature("acme_refund_shortcuts", true)
+//!     .configure(|repo| repo.with_sla_policy(SlaPolicy::default()))
+//!     .build();
+//!
+//! // Schema::build(QueryRoot, MutationRoot, SubscriptionRoot)
+//! //     .data(system.clone())
+//! //     .finish()
+//!
+//! // Workers share the same repository
+//! let detector = pleme_support::SlaBreachDetector::new(system.repository().clone());
+//! # }
+//! ```
+//!
+//! The crate itself consults no feature flags: they are carried for the host
+//! service's own resolvers and extensions, which read them with
+//! [`SupportSystem::feature_enabled`], so flag names are the host's to define.
+//!
+//! New dependencies belong on the builder, so hosts keep one construction
+//! site and resolvers one lookup.
+
+use async_graphql::{Context, Result as GraphQLResult};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::clock::{Clock, SystemClock};
+use crate::directory::{CachedCustomerDirectory, CustomerDirectory};
+use crate::events::SupportEventPublisher;
+use crate::guard::FieldGuard;
+use crate::repository::SupportRepository;
+
+type Configure = Box<dyn FnOnce(SupportRepository) -> SupportRepository + Send>;
+
+/// Named on/off switches for a host's features rolled out gradually
+///
+/// Flags that were never set read as off. Nothing in this crate checks a
+/// flag; names and meanings are defined by the host service.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeatureFlags {
+    flags: HashMap<String, bool>,
+}
+
+impl FeatureFlags {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, name: impl Into<String>, enabled: bool) -> Self {
+        self.flags.insert(name.into(), enabled);
+        self
+    }
+
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.flags.get(name).copied().unwrap_or(false)
+    }
+}
+
+/// The repository and the services around it, as registered in GraphQL data
+#[derive(Clone)]
+pub struct SupportSystem {
+    repository: Arc<SupportRepository>,
+    clock: Arc<dyn Clock>,
+    customer_directory: Option<Arc<dyn CustomerDirectory>>,
+    field_guard: Option<FieldGuard>,
+    features: FeatureFlags,
+}
+
+impl SupportSystem {
+    pub fn builder(pool: PgPool) -> SupportSystemBuilder {
+        SupportSystemBuilder::new(pool)
+    }
+
+    pub fn repository(&self) -> &Arc<SupportRepository> {
+        &self.repository
+    }
+
+    /// The clock the repository takes business timestamps from
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    /// The (cached) customer directory, e.g. for a per-request `CustomerLoader`
+    pub fn customer_directory(&self) -> Option<&Arc<dyn CustomerDirectory>> {
+        self.customer_directory.as_ref()
+    }
+
+    pub fn field_guard(&self) -> Option<&FieldGuard> {
+        self.field_guard.as_ref()
+    }
+
+    pub fn features(&self) -> &FeatureFlags {
+        &self.features
+    }
+
+    /// Whether `name` is enabled in the request's support system; off when
+    /// none is registered
+    pub fn feature_enabled(ctx: &Context<'_>, name: &str) -> bool {
+        ctx.data_opt::<SupportSystem>().is_some_and(|system| system.features.is_enabled(name))
+    }
+}
+
+/// The request's repository: the registered [`SupportSystem`]'s, else a bare
+/// `Arc<SupportRepository>` in schema or request data
+pub(crate) fn repository<'a>(ctx: &Context<'a>) -> GraphQLResult<&'a Arc<SupportRepository>> {
+    match ctx.data_opt::<SupportSystem>() {
+        Some(system) => Ok(&system.repository),
+        None => ctx.data::<Arc<SupportRepository>>(),
+    }
+}
+
+/// Collects the pieces of a [`SupportSystem`]
+pub struct SupportSystemBuilder {
+    pool: PgPool,
+    clock: Arc<dyn Clock>,
+    event_publisher: Option<Arc<dyn SupportEventPublisher>>,
+    customer_directory: Option<Arc<dyn CustomerDirectory>>,
+    customer_cache_ttl: Option<Duration>,
+    field_guard: Option<FieldGuard>,
+    features: FeatureFlags,
+    configure: Vec<Configure>,
+}
+
+impl SupportSystemBuilder {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            clock: Arc::new(SystemClock),
+            event_publisher: None,
+            customer_directory: None,
+            customer_cache_ttl: None,
+            field_guard: None,
+            features: FeatureFlags::default(),
+            configure: Vec::new(),
+        }
+    }
+
+    /// Take business timestamps from `clock` instead of the wall clock
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn event_publisher(mut self, publisher: Arc<dyn SupportEventPublisher>) -> Self {
+        self.event_publisher = Some(publisher);
+        self
+    }
+
+    pub fn customer_directory(mut self, directory: Arc<dyn CustomerDirectory>) -> Self {
+        self.customer_directory = Some(directory);
+        self
+    }
+
+    /// Cache customer directory lookups for `ttl`; no effect without a directory
+    pub fn customer_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.customer_cache_ttl = Some(ttl);
+        self
+    }
+
+    /// Register `guard` for every request of the schema the system is added to
+    pub fn field_guard(mut self, guard: FieldGuard) -> Self {
+        self.field_guard = Some(guard);
+        self
+    }
+
+    pub fn feature(mut self, name: impl Into<String>, enabled: bool) -> Self {
+        self.features = self.features.with(name, enabled);
+        self
+    }
+
+    /// Replace every feature flag set so far
+    pub fn features(mut self, features: FeatureFlags) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Apply any other repository setting, e.g. `|repo| repo.with_sla_policy(policy)`
+    ///
+    /// Runs after the builder's own pieces are wired, in the order given, so
+    /// it can also override them.
+    pub fn configure<F>(mut self, configure: F) -> Self
+    where
+        F: FnOnce(SupportRepository) -> SupportRepository + Send + 'static,
+    {
+        self.configure.push(Box::new(configure));
+        self
+    }
+
+    pub fn build(self) -> SupportSystem {
+        let customer_directory = match (self.customer_directory, self.customer_cache_ttl) {
+            (Some(directory), Some(ttl)) => {
+                Some(Arc::new(CachedCustomerDirectory::new(directory, ttl)) as Arc<dyn CustomerDirectory>)
+            }
+            (directory, _) => directory,
+        };
+
+        let mut repository = SupportRepository::with_clock(self.pool, self.clock.clone());
+        if let Some(publisher) = self.event_publisher {
+            repository = repository.with_event_publisher(publisher);
+        }
+        if let Some(directory) = &customer_directory {
+            repository = repository.with_customer_directory(directory.clone());
+        }
+        for configure in self.configure {
+            repository = configure(repository);
+        }
+
+        SupportSystem {
+            repository: Arc::new(repository),
+            clock: self.clock,
+            customer_directory,
+            field_guard: self.field_guard,
+            features: self.features,
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! Placeholders in reply templates
+//!
+//! Canned responses are the product's reply templates. Their bodies may
+//! contain `{{name}}` placeholders, filled in when the response is sent:
+//!
+//! | Placeholder | Value |
+//! |-------------|-------|
+//! | `{{customer_name}}`, `{{customer_email}}` | From the customer directory, when configured |
+//! | `{{agent_name}}` | The sending agent's display name |
+//! | `{{ticket_ref}}`, `{{ticket_subject}}` | The ticket's reference and subject |
+//! | `{{product}}` | The ticket's product |
+//!
+//! Agents can pass further [`TemplateVariable`]s, which also override the
+//! built-in values. A placeholder without a value fails the send rather than
+//! reaching the customer as `{{customer_name}}`.
+//!
+//! ```rust
+//! use pleme_support::templates::{render_template, template_placeholders};
+//! use std::collections::HashMap;
+//!
+//! let body = "Hi {{ customer_name }}, ticket {{ticket_ref}} is resolved.";
+//! assert_eq!(template_placeholders(body).unwrap(), vec!["customer_name", "ticket_ref"]);
+//!
+//! let mut vars = HashMap::new();
+//! vars.insert("customer_name".to_string(), "Ana".to_string());
+//! vars.insert("ticket_ref".to_string(), "SUP-42".to_string());
+//! assert_eq!(render_template(body, &vars).unwrap(), "Hi Ana, ticket SUP-42 is resolved.");
+//!
+//! vars.remove("customer_name");
+//! assert!(render_template(body, &vars).is_err());
+//! assert!(template_placeholders("Hi {{customer_name").is_err());
+//! ```
+
+use async_graphql::InputObject;
+use std::collections::HashMap;
+
+use crate::{Result, SupportError};
+
+/// Placeholders filled in from the ticket, customer and agent
+pub const BUILTIN_VARIABLES: &[&str] = &[
+    "customer_name",
+    "customer_email",
+    "agent_name",
+    "ticket_ref",
+    "ticket_subject",
+    "product",
+];
+
+/// Value for a `{{name}}` placeholder
+#[derive(Debug, Clone, InputObject)]
+pub struct TemplateVariable {
+    pub name: String,
+    pub value: String,
+}
+
+/// Placeholder names in `template`, in order of first appearance
+///
+/// Fails with `InvalidInput` on an unclosed `{{` or a name that isn't
+/// lowercase letters, digits and underscores.
+pub fn template_placeholders(template: &str) -> Result<Vec<String>> {
+    let mut names: Vec<String> = Vec::new();
+    for segment in parse(template)? {
+        if let Segment::Placeholder(name) = segment {
+            if !names.iter().any(|n| n == name) {
+                names.push(name.to_string());
+            }
+        }
+    }
+    Ok(names)
+}
+
+/// Replace every placeholder in `template` with its value from `variables`
+///
+/// Fails with `Validation` naming the placeholders that have no value.
+pub fn render_template(template: &str, variables: &HashMap<String, String>) -> Result<String> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut missing: Vec<&str> = Vec::new();
+    for segment in parse(template)? {
+        match segment {
+            Segment::Text(text) => rendered.push_str(text),
+            Segment::Placeholder(name) => match variables.get(name) {
+                Some(value) => rendered.push_str(value),
+                None if !missing.contains(&name) => missing.push(name),
+                None => {}
+            },
+        }
+    }
+
+    if !missing.is_empty() {
+        return Err(SupportError::Validation(format!("No value for template placeholders: {}", missing.join(", "))));
+    }
+    Ok(rendered)
+}
+
+enum Segment<'a> {
+    Text(&'a str),
+    Placeholder(&'a str),
+}
+
+fn parse(template: &str) -> Result<Vec<Segment<'_>>> {
+    let mut segments = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        if start > 0 {
+            segments.push(Segment::Text(&rest[..start]));
+        }
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| SupportError::InvalidInput("Unclosed {{ in template".to_string()))?;
+        let name = after[..end].trim();
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_') {
+            return Err(SupportError::InvalidInput(format!("Invalid template placeholder: {{{{{}}}}}", &after[..end])));
+        }
+        segments.push(Segment::Placeholder(name));
+        rest = &after[end + 2..];
+    }
+    if !rest.is_empty() {
+        segments.push(Segment::Text(rest));
+    }
+    Ok(segments)
+}
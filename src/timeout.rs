@@ -0,0 +1,52 @@
+//! Statement timeouts per operation class
+//!
+//! A runaway dashboard query should fail, not hold a pooled connection
+//! indefinitely. Repository operations run under the timeout of their class:
+//! `Read` for ticket lists, view counts and streamed exports, `Analytics` for
+//! dashboards and reports. The limit is applied with `SET LOCAL
+//! statement_timeout` in the operation's transaction, so it never leaks to
+//! other users of the connection, and an exceeded limit surfaces as
+//! [`SupportError::Timeout`](crate::SupportError::Timeout).
+//!
+//! ```rust
+//! use pleme_support::{OperationClass, QueryTimeouts};
+//! use std::time::Duration;
+//!
+//! let timeouts = QueryTimeouts::default().with_timeout(OperationClass::Analytics, Duration::from_secs(30));
+//! assert_eq!(timeouts.timeout_for(OperationClass::Analytics), Some(Duration::from_secs(30)));
+//! assert_eq!(timeouts.timeout_for(OperationClass::Read), None);
+//! ```
+
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum OperationClass {
+    /// Interactive reads: ticket lists, badge counts, exports
+    Read,
+    /// Dashboards and reports scanning a period of tickets
+    Analytics,
+}
+
+/// Statement timeout per operation class; `None` means no limit
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct QueryTimeouts {
+    pub read: Option<Duration>,
+    pub analytics: Option<Duration>,
+}
+
+impl QueryTimeouts {
+    pub fn with_timeout(mut self, class: OperationClass, timeout: Duration) -> Self {
+        match class {
+            OperationClass::Read => self.read = Some(timeout),
+            OperationClass::Analytics => self.analytics = Some(timeout),
+        }
+        self
+    }
+
+    pub fn timeout_for(&self, class: OperationClass) -> Option<Duration> {
+        match class {
+            OperationClass::Read => self.read,
+            OperationClass::Analytics => self.analytics,
+        }
+    }
+}
@@ -0,0 +1,49 @@
+//! Translation of canned responses at send time
+//!
+//! Canned responses are written in one locale, but tickets arrive in many.
+//! When an agent sends a canned response into a ticket whose locale is in a
+//! different language, the repository can translate it first through the
+//! registered [`TranslationProvider`]. Sending never fails because of
+//! translation: without a provider, or when it errors, the original text is
+//! sent and the message's [`TranslationStatus`] says why.
+//!
+//! ```rust
+//! use pleme_support::translation::same_language;
+//!
+//! assert!(same_language("pt-BR", "pt-PT"));
+//! assert!(same_language("EN", "en-US"));
+//! assert!(!same_language("en-US", "th-TH"));
+//! ```
+
+use async_graphql::Enum;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::Result;
+
+#[async_trait]
+pub trait TranslationProvider: Send + Sync {
+    /// Translate `text` from `source_locale` to `target_locale` (BCP 47 tags)
+    async fn translate(&self, text: &str, source_locale: &str, target_locale: &str) -> Result<String>;
+}
+
+/// What happened to a canned response's text on the way into a message
+#[derive(Debug, Clone, Copy, Enum, Eq, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "message_translation_status", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TranslationStatus {
+    /// The response is already in the ticket's language
+    NotNeeded,
+    Translated,
+    /// The agent chose to send the original text
+    Disabled,
+    /// No translation provider is registered
+    Unavailable,
+    /// The provider returned an error; the original text was sent
+    Failed,
+}
+
+/// Whether two BCP 47 tags share a primary language (`pt-BR` and `pt-PT` do)
+pub fn same_language(a: &str, b: &str) -> bool {
+    let primary = |tag: &str| tag.split(['-', '_']).next().unwrap_or_default().to_ascii_lowercase();
+    primary(a) == primary(b)
+}
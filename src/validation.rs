@@ -0,0 +1,181 @@
+//! Input validation for ticket, message and rating writes
+//!
+//! Ticket creation and updates, messages and CSAT ratings are checked before
+//! anything is written, so oversized or blank text and out-of-range scores
+//! fail with the offending fields named instead of as a database error. The
+//! repository validates every write path (direct calls, transactions, bulk
+//! updates, email intake); the GraphQL mutations also validate up front and
+//! report the failures under the error's `fields` extension:
+//!
+//! ```json
+//! { "message": "Validation error: subject: must not be blank",
+//!   "extensions": { "code": "VALIDATION", "fields": [{ "field": "subject", "message": "must not be blank" }] } }
+//! ```
+//!
+//! Products can also restrict ticket categories with
+//! `ProductConfigInput::categories`; tickets filed or recategorized outside
+//! the list fail on `category`, and classifier suggestions outside it are
+//! dropped.
+//!
+//! ```rust
+//! use pleme_support::validation::{validate_new_ticket, MAX_SUBJECT_LENGTH};
+//! use pleme_support::CreateTicketInput;
+//!
+//! let input = CreateTicketInput {
+//!     subject: "x".repeat(MAX_SUBJECT_LENGTH + 1),
+//!     description: "  ".to_string(),
+//!     ..Default::default()
+//! };
+//! let errors = validate_new_ticket(&input).unwrap_err();
+//! assert_eq!(errors.fields().collect::<Vec<_>>(), ["subject", "description"]);
+//!
+//! let input = CreateTicketInput { subject: "Can't log in".into(), description: "Since 9am".into(), ..Default::default() };
+//! assert!(validate_new_ticket(&input).is_ok());
+//! ```
+
+use async_graphql::ErrorExtensions;
+use serde::Serialize;
+use std::fmt;
+
+use crate::models::{AddTicketMessageInput, CreateTicketInput, UpdateTicketInput};
+use crate::products::ProductConfig;
+
+/// Longest accepted ticket subject, in characters
+pub const MAX_SUBJECT_LENGTH: usize = 500;
+
+/// Longest accepted ticket description, in characters
+pub const MAX_DESCRIPTION_LENGTH: usize = 50_000;
+
+/// Longest accepted ticket category, in characters
+pub const MAX_CATEGORY_LENGTH: usize = 100;
+
+/// Longest accepted message, in characters
+pub const MAX_MESSAGE_LENGTH: usize = 50_000;
+
+/// One invalid input field
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    /// Input field name, e.g. `subject`
+    pub field: String,
+    pub message: String,
+}
+
+/// Every invalid field of an input
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationErrors {
+    errors: Vec<FieldError>,
+}
+
+impl ValidationErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that `field` is invalid
+    pub fn add(&mut self, field: &str, message: impl Into<String>) {
+        self.errors.push(FieldError { field: field.to_string(), message: message.into() });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn errors(&self) -> &[FieldError] {
+        &self.errors
+    }
+
+    /// Names of the invalid fields, in the order they were found
+    pub fn fields(&self) -> impl Iterator<Item = &str> {
+        self.errors.iter().map(|e| e.field.as_str())
+    }
+
+    /// `Ok` when no field is invalid
+    pub fn into_result(self) -> Result<(), ValidationErrors> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let errors: Vec<String> = self.errors.iter().map(|e| format!("{}: {}", e.field, e.message)).collect();
+        f.write_str(&errors.join("; "))
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+impl ErrorExtensions for ValidationErrors {
+    fn extend(&self) -> async_graphql::Error {
+        let fields = async_graphql::to_value(&self.errors).unwrap_or_default();
+        async_graphql::Error::new(format!("Validation error: {}", self)).extend_with(|_, extensions| {
+            extensions.set("code", "VALIDATION");
+            extensions.set("fields", fields.clone());
+        })
+    }
+}
+
+/// Check a new ticket's subject, description and category
+pub fn validate_new_ticket(input: &CreateTicketInput) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::new();
+    check_text(&mut errors, "subject", &input.subject, MAX_SUBJECT_LENGTH);
+    check_text(&mut errors, "description", &input.description, MAX_DESCRIPTION_LENGTH);
+    if let Some(category) = &input.category {
+        check_text(&mut errors, "category", category, MAX_CATEGORY_LENGTH);
+    }
+    errors.into_result()
+}
+
+/// Check the text fields an update sets
+pub fn validate_ticket_update(input: &UpdateTicketInput) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::new();
+    if let Some(subject) = &input.subject {
+        check_text(&mut errors, "subject", subject, MAX_SUBJECT_LENGTH);
+    }
+    if let Some(description) = &input.description {
+        check_text(&mut errors, "description", description, MAX_DESCRIPTION_LENGTH);
+    }
+    if let Some(category) = &input.category {
+        check_text(&mut errors, "category", category, MAX_CATEGORY_LENGTH);
+    }
+    errors.into_result()
+}
+
+/// Check a message's content
+pub fn validate_message(input: &AddTicketMessageInput) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::new();
+    check_text(&mut errors, "content", &input.content, MAX_MESSAGE_LENGTH);
+    errors.into_result()
+}
+
+/// Check a CSAT score is on the 1-5 scale
+pub fn validate_csat_score(score: i32) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::new();
+    if !(1..=5).contains(&score) {
+        errors.add("score", "must be between 1 and 5");
+    }
+    errors.into_result()
+}
+
+/// Check `category` is one the product allows
+pub fn check_category(config: &ProductConfig, category: Option<&str>) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::new();
+    if let Some(category) = category.filter(|c| !config.allows_category(c)) {
+        errors.add(
+            "category",
+            format!("{} is not a {} category; expected one of {}", category, config.product, config.categories.join(", ")),
+        );
+    }
+    errors.into_result()
+}
+
+fn check_text(errors: &mut ValidationErrors, field: &str, value: &str, max_length: usize) {
+    if value.trim().is_empty() {
+        errors.add(field, "must not be blank");
+    } else if value.chars().count() > max_length {
+        errors.add(field, format!("must be at most {} characters", max_length));
+    }
+}
@@ -0,0 +1,43 @@
+//! Who is viewing or typing on a ticket
+//!
+//! Two agents answering the same customer at once send conflicting replies.
+//! Agent consoles call `viewTicket` while a ticket is open and
+//! `setTicketTyping` as the agent types, and subscribe to
+//! `ticketViewers(ticketId)` (see [`SupportSubscriptions`](crate::SupportSubscriptions))
+//! to show everyone else on the ticket. The subscription pushes the viewers on
+//! subscribing and then whenever someone arrives, leaves, starts or stops typing.
+//!
+//! Viewing and typing are heartbeats: an agent whose console stops calling
+//! `viewTicket` drops off after [`VIEWER_TIMEOUT_SECONDS`], and a typing
+//! indicator lapses after [`TYPING_TIMEOUT_SECONDS`] without a keystroke.
+//! Sending a reply clears the author's typing indicator.
+
+use async_graphql::SimpleObject;
+use chrono::{DateTime, Utc};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// How recent a viewing heartbeat must be for the agent to count as on the ticket
+pub const VIEWER_TIMEOUT_SECONDS: i64 = 30;
+
+/// How recent a keystroke must be for the agent to count as typing
+pub const TYPING_TIMEOUT_SECONDS: i64 = 8;
+
+/// How often the subscription checks for changes when the subscriber does not pick
+pub const DEFAULT_VIEWER_POLL_SECONDS: u64 = 2;
+
+/// Poll intervals are clamped to this range
+pub const MIN_VIEWER_POLL_SECONDS: u64 = 1;
+pub const MAX_VIEWER_POLL_SECONDS: u64 = 30;
+
+/// An agent with a ticket open
+#[derive(Debug, Clone, FromRow, SimpleObject)]
+pub struct TicketViewer {
+    pub user_id: Uuid,
+    /// From the agent's profile in the ticket's product
+    pub display_name: Option<String>,
+    pub viewing_since: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    /// Typed in the reply box within the last few seconds
+    pub typing: bool,
+}
@@ -0,0 +1,47 @@
+//! Queue snapshots for office wallboards
+//!
+//! Wallboards show a handful of live aggregates all day. Rather than polling
+//! the dashboard query, they subscribe to `queueSnapshot(product,
+//! intervalSeconds)` (see [`SupportSubscriptions`](crate::SupportSubscriptions)),
+//! which pushes a [`QueueSnapshot`] on subscribing and then every interval.
+//! A snapshot is a few indexed reads: open counts come from the maintained
+//! counters behind `quickStats`.
+//!
+//! Agents count as online while their console keeps calling
+//! `recordAgentPresence`; a heartbeat older than [`AGENT_ONLINE_WINDOW_MINUTES`]
+//! counts as offline.
+
+use async_graphql::SimpleObject;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::models::PriorityOpenCount;
+
+/// How recent an agent's presence heartbeat must be to count as online
+pub const AGENT_ONLINE_WINDOW_MINUTES: i64 = 5;
+
+/// Snapshot interval when the subscriber does not pick one
+pub const DEFAULT_SNAPSHOT_INTERVAL_SECONDS: u64 = 30;
+
+/// Intervals are clamped to this range
+pub const MIN_SNAPSHOT_INTERVAL_SECONDS: u64 = 5;
+pub const MAX_SNAPSHOT_INTERVAL_SECONDS: u64 = 3600;
+
+/// Live queue aggregates for a product
+#[derive(Debug, Clone, SimpleObject)]
+pub struct QueueSnapshot {
+    pub product: String,
+    pub taken_at: DateTime<Utc>,
+    pub open_tickets: i64,
+    /// Every priority, most urgent first
+    pub open_by_priority: Vec<PriorityOpenCount>,
+    /// Oldest ticket awaiting an agent (new or in progress, not waiting on the customer)
+    pub oldest_waiting_ticket_id: Option<Uuid>,
+    pub oldest_waiting_since: Option<DateTime<Utc>>,
+    /// Whole minutes the oldest waiting ticket has waited
+    pub oldest_waiting_minutes: Option<i64>,
+    /// Active agents with a presence heartbeat in the online window
+    pub agents_online: i64,
+    /// SLA breaches since midnight in the product's timezone
+    pub breaches_today: i64,
+}
@@ -0,0 +1,151 @@
+//! Dimensional extracts for the analytics warehouse
+//!
+//! The `warehouse_*` views (migration 023) give the analytics team a stable
+//! star schema instead of the operational tables: [`FactTicket`] and
+//! [`FactMessage`] facts, [`DimAgent`] and [`DimCategory`] dimensions. Each
+//! row carries a [`Watermark`]; a load job stores the last one it saw and
+//! passes it back to fetch only rows changed since:
+//!
+//! ```rust,no_run
+//! use pleme_support::{FactTicket, SupportRepository, Watermark};
+//!
+//! # async fn example(repo: SupportRepository, saved: Option<Watermark>) -> pleme_support::Result<()> {
+//! let batch = repo.extract_warehouse::<FactTicket>("novaskyn", saved.as_ref(), 1000).await?;
+//! // ...load batch.rows, then persist batch.watermark for the next run
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Rows changed within the last [`WAREHOUSE_SETTLE_SECONDS`] are held back
+//! so a write still committing with an earlier timestamp is not skipped.
+//! Hard-deleted tickets (retention purges) disappear without a tombstone;
+//! soft deletes show up as `deleted_at`.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgRow;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// How long a change must be committed before extraction picks it up
+pub const WAREHOUSE_SETTLE_SECONDS: i64 = 300;
+
+/// Position in an extract's `(changed_at, key)` order
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Watermark {
+    pub changed_at: DateTime<Utc>,
+    pub key: String,
+}
+
+/// Row type of one `warehouse_*` view
+pub trait WarehouseExtract: for<'r> FromRow<'r, PgRow> + Serialize + Send + Unpin {
+    /// View the rows are read from
+    const VIEW: &'static str;
+
+    fn watermark(&self) -> Watermark;
+}
+
+#[derive(Debug, Clone)]
+pub struct WarehouseBatch<T> {
+    pub rows: Vec<T>,
+    /// Pass to the next extraction; unchanged from the input when `rows` is empty
+    pub watermark: Option<Watermark>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct FactTicket {
+    pub ticket_id: Uuid,
+    pub product: String,
+    pub ticket_ref: String,
+    pub customer_id: Uuid,
+    pub status: String,
+    pub priority: String,
+    pub channel: String,
+    pub category: Option<String>,
+    pub customer_plan: Option<String>,
+    pub assigned_to: Option<Uuid>,
+    pub resolving_agent_id: Option<Uuid>,
+    pub parent_ticket_id: Option<Uuid>,
+    pub follow_up_of: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub first_response_at: Option<DateTime<Utc>>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub first_response_seconds: Option<i64>,
+    pub resolution_seconds: Option<i64>,
+    pub customer_wait_seconds: i64,
+    pub sla_breach: bool,
+    pub sla_due_at: Option<DateTime<Utc>>,
+    pub csat_score: Option<i32>,
+    pub changed_at: DateTime<Utc>,
+}
+
+impl WarehouseExtract for FactTicket {
+    const VIEW: &'static str = "warehouse_fact_tickets";
+
+    fn watermark(&self) -> Watermark {
+        Watermark { changed_at: self.changed_at, key: self.ticket_id.to_string() }
+    }
+}
+
+/// Message metadata; content stays in the operational store
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct FactMessage {
+    pub message_id: Uuid,
+    pub ticket_id: Uuid,
+    pub product: String,
+    pub author_id: Uuid,
+    pub is_customer: bool,
+    pub is_internal: bool,
+    pub flagged: bool,
+    pub content_length: i32,
+    pub created_at: DateTime<Utc>,
+    pub changed_at: DateTime<Utc>,
+}
+
+impl WarehouseExtract for FactMessage {
+    const VIEW: &'static str = "warehouse_fact_messages";
+
+    fn watermark(&self) -> Watermark {
+        Watermark { changed_at: self.changed_at, key: self.message_id.to_string() }
+    }
+}
+
+/// Agent profile; facts reference agents by `user_id`
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct DimAgent {
+    pub agent_id: Uuid,
+    pub product: String,
+    pub user_id: Uuid,
+    pub display_name: String,
+    pub team: Option<String>,
+    pub role: String,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+    pub changed_at: DateTime<Utc>,
+}
+
+impl WarehouseExtract for DimAgent {
+    const VIEW: &'static str = "warehouse_dim_agents";
+
+    fn watermark(&self) -> Watermark {
+        Watermark { changed_at: self.changed_at, key: self.agent_id.to_string() }
+    }
+}
+
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct DimCategory {
+    pub product: String,
+    pub category: String,
+    pub first_seen_at: DateTime<Utc>,
+    pub changed_at: DateTime<Utc>,
+}
+
+impl WarehouseExtract for DimCategory {
+    const VIEW: &'static str = "warehouse_dim_categories";
+
+    fn watermark(&self) -> Watermark {
+        Watermark { changed_at: self.changed_at, key: self.category.clone() }
+    }
+}
@@ -0,0 +1,73 @@
+//! Priority-weighted agent workload
+//!
+//! Raw open-ticket counts treat five LOW tickets the same as five URGENT
+//! ones. An agent's weighted workload sums a weight per open ticket by
+//! priority instead, URGENT=5, HIGH=3, MEDIUM=2 and LOW=1 unless configured
+//! otherwise with `SupportRepository::with_workload_weights`. Least-loaded and
+//! skill-based routing pick the agent with the lowest weighted workload,
+//! assignment suggestions rank on it, and `agent_workloads` reports it.
+//!
+//! ```rust
+//! use pleme_support::{TicketPriority, WorkloadWeights};
+//!
+//! let weights = WorkloadWeights { urgent: 8, ..Default::default() };
+//!
+//! assert_eq!(weights.weight(TicketPriority::Urgent), 8);
+//! assert_eq!(weights.weight(TicketPriority::Low), 1);
+//! ```
+
+use async_graphql::SimpleObject;
+use serde::Serialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::models::TicketPriority;
+
+/// Workload contributed by one open ticket of each priority
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorkloadWeights {
+    pub low: u32,
+    pub medium: u32,
+    pub high: u32,
+    pub urgent: u32,
+}
+
+impl Default for WorkloadWeights {
+    fn default() -> Self {
+        Self { low: 1, medium: 2, high: 3, urgent: 5 }
+    }
+}
+
+impl WorkloadWeights {
+    pub fn weight(&self, priority: TicketPriority) -> u32 {
+        match priority {
+            TicketPriority::Low => self.low,
+            TicketPriority::Medium => self.medium,
+            TicketPriority::High => self.high,
+            TicketPriority::Urgent => self.urgent,
+        }
+    }
+
+    /// SQL expression for the weight of the ticket whose priority is `column`
+    pub(crate) fn sql_case(&self, column: &str) -> String {
+        format!(
+            "CASE {column} WHEN 'URGENT' THEN {} WHEN 'HIGH' THEN {} WHEN 'MEDIUM' THEN {} ELSE {} END",
+            self.urgent, self.high, self.medium, self.low,
+        )
+    }
+}
+
+/// An active agent's open tickets and weighted workload
+#[derive(Debug, Clone, Serialize, FromRow, SimpleObject)]
+pub struct AgentWorkload {
+    pub agent_id: Uuid,
+    pub agent_name: String,
+    pub team: Option<String>,
+    pub open_tickets: i64,
+    pub urgent_tickets: i64,
+    pub high_tickets: i64,
+    pub medium_tickets: i64,
+    pub low_tickets: i64,
+    /// Open tickets weighted by priority
+    pub weighted_workload: i64,
+}